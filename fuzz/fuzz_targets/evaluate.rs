@@ -0,0 +1,20 @@
+//! Fuzz the full evaluator with the default config. Panics are reported as
+//! crashes; `evaluate_detailed` is used rather than `evaluate_fail_closed`
+//! so they are not caught.
+
+#![no_main]
+
+use std::sync::LazyLock;
+
+use cc_toolgate::config::Config;
+use cc_toolgate::eval::CommandRegistry;
+use libfuzzer_sys::fuzz_target;
+
+static REGISTRY: LazyLock<CommandRegistry> =
+    LazyLock::new(|| CommandRegistry::from_config(&Config::default_config()));
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(command) = std::str::from_utf8(data) {
+        let _ = REGISTRY.evaluate_detailed(command);
+    }
+});
@@ -0,0 +1,11 @@
+//! Fuzz the shell parser: any input must parse or be rejected, never panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(command) = std::str::from_utf8(data) {
+        let _ = cc_toolgate::parse::parse_lossy(command);
+    }
+});
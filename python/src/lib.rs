@@ -0,0 +1,104 @@
+//! Python bindings for the evaluator.
+//!
+//! ```python
+//! import cc_toolgate
+//!
+//! result = cc_toolgate.evaluate("git push --force origin main")
+//! assert result.decision == cc_toolgate.Decision.ASK
+//! print(result.reason)
+//! ```
+
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use std::path::PathBuf;
+use toolgate::config::Config;
+use toolgate::eval::{self, CommandRegistry};
+
+/// The decision for a command, ordered ALLOW < ASK < DENY.
+#[pyclass(eq, eq_int, ord, frozen, hash, module = "cc_toolgate")]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Decision {
+    /// Runs without a prompt.
+    #[pyo3(name = "ALLOW")]
+    Allow,
+    /// Requires confirmation.
+    #[pyo3(name = "ASK")]
+    Ask,
+    /// Blocked.
+    #[pyo3(name = "DENY")]
+    Deny,
+}
+
+impl From<eval::Decision> for Decision {
+    fn from(d: eval::Decision) -> Self {
+        match d {
+            eval::Decision::Allow => Self::Allow,
+            eval::Decision::Ask => Self::Ask,
+            eval::Decision::Deny => Self::Deny,
+        }
+    }
+}
+
+impl From<Decision> for eval::Decision {
+    fn from(d: Decision) -> Self {
+        match d {
+            Decision::Allow => Self::Allow,
+            Decision::Ask => Self::Ask,
+            Decision::Deny => Self::Deny,
+        }
+    }
+}
+
+/// A decision and the reason for it.
+#[pyclass(frozen, get_all, module = "cc_toolgate")]
+struct RuleMatch {
+    decision: Decision,
+    reason: String,
+}
+
+#[pymethods]
+impl RuleMatch {
+    fn __repr__(&self) -> String {
+        let label = eval::Decision::from(self.decision).label();
+        format!("RuleMatch(decision={label}, reason={:?})", self.reason)
+    }
+}
+
+/// Evaluate a Bash command.
+///
+/// With `config_path`, the rules are the embedded defaults merged with that
+/// TOML file, exactly as the user config would be. Without it, the config is
+/// loaded the way the hook loads it (user and project overlays included).
+///
+/// Raises `OSError` if `config_path` can't be read and `ValueError` if it
+/// isn't a valid config.
+#[pyfunction]
+#[pyo3(signature = (command, config_path=None))]
+fn evaluate(py: Python<'_>, command: &str, config_path: Option<PathBuf>) -> PyResult<RuleMatch> {
+    let config = match config_path {
+        Some(path) => {
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| PyOSError::new_err(format!("{}: {e}", path.display())))?;
+            Config::with_overlay(&text)
+                .map_err(|e| PyValueError::new_err(format!("{}: {e}", path.display())))?
+        }
+        None => Config::load(),
+    };
+    let result = py.allow_threads(|| {
+        CommandRegistry::from_config(&config)
+            .evaluate_fail_closed(command)
+            .result
+    });
+    Ok(RuleMatch {
+        decision: result.decision.into(),
+        reason: result.reason,
+    })
+}
+
+#[pymodule]
+fn cc_toolgate(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Decision>()?;
+    m.add_class::<RuleMatch>()?;
+    m.add_function(wrap_pyfunction!(evaluate, m)?)?;
+    Ok(())
+}
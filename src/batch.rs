@@ -0,0 +1,301 @@
+//! Batch evaluation of many commands (`cc-toolgate eval --file`), golden
+//! corpus checks (`cc-toolgate test corpus.toml`), and replays of logged
+//! commands (`cc-toolgate replay`).
+//!
+//! Batch input is one command per line, or one PreToolUse hook input (JSON)
+//! per line as captured from Claude Code. Blank lines and `#` comments are
+//! skipped, as are hook inputs for tools other than Bash.
+//!
+//! A corpus lists commands under their expected decision, mirroring the
+//! `[commands]` config lists:
+//!
+//! ```toml
+//! allow = ["git status", "cargo test"]
+//! ask = ["git push origin main"]
+//! deny = ["shred secrets.txt"]
+//! ```
+
+use crate::eval::{CommandRegistry, Decision};
+use serde::{Deserialize, Serialize};
+
+/// The decision for one command of a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BatchResult {
+    /// 1-based line number in the input.
+    pub line: usize,
+    /// The evaluated command.
+    pub command: String,
+    /// The decision.
+    pub decision: Decision,
+    /// Why the command got its decision.
+    pub reason: String,
+}
+
+/// The command on one input line, if it holds one.
+///
+/// A line that parses as a JSON object with a `tool_name` is a hook input;
+/// anything else, including a brace group (`{ rm -rf /tmp/x; }`), is the
+/// command itself.
+pub fn command_from_line(line: &str) -> Option<String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let input = serde_json::from_str::<serde_json::Value>(line).ok();
+    let Some(tool) = input.as_ref().and_then(|v| v.get("tool_name")) else {
+        return Some(line.to_string());
+    };
+    if tool.as_str() != Some("Bash") {
+        return None;
+    }
+    input?
+        .pointer("/tool_input/command")
+        .and_then(|v| v.as_str())
+        .filter(|c| !c.is_empty())
+        .map(String::from)
+}
+
+/// `reason` on one line, for plain output: its non-empty lines joined with
+/// `; `. A header with nothing under it (`compound command:`) loses its
+/// colon, so no separator dangles.
+pub fn one_line(reason: &str) -> String {
+    let lines: Vec<&str> = reason
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    match lines.as_slice() {
+        [header] => header.trim_end_matches(':').to_string(),
+        _ => lines.join("; "),
+    }
+}
+
+/// Evaluate every command in `text`.
+pub fn evaluate_lines(registry: &CommandRegistry, text: &str) -> Vec<BatchResult> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(i, line)| Some((i, command_from_line(line)?)))
+        .map(|(i, command)| {
+            let result = registry.evaluate(&command);
+            BatchResult {
+                line: i + 1,
+                command,
+                decision: result.decision,
+                reason: result.reason,
+            }
+        })
+        .collect()
+}
+
+/// Commands grouped by expected decision.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Corpus {
+    /// Commands expected to be allowed.
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Commands expected to ask.
+    #[serde(default)]
+    pub ask: Vec<String>,
+    /// Commands expected to be denied.
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// A corpus command whose decision differs from the expected one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The command.
+    pub command: String,
+    /// The decision the corpus expects.
+    pub expected: Decision,
+    /// The decision the registry returned.
+    pub actual: Decision,
+    /// The registry's reason for `actual`.
+    pub reason: String,
+}
+
+impl Corpus {
+    /// Parse a corpus from TOML.
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Number of commands in the corpus.
+    pub fn len(&self) -> usize {
+        self.allow.len() + self.ask.len() + self.deny.len()
+    }
+
+    /// Whether the corpus has no commands.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Evaluate every command and return those whose decision differs from
+    /// the expected one, in corpus order (allow, ask, deny).
+    pub fn check(&self, registry: &CommandRegistry) -> Vec<Mismatch> {
+        [
+            (Decision::Allow, &self.allow),
+            (Decision::Ask, &self.ask),
+            (Decision::Deny, &self.deny),
+        ]
+        .into_iter()
+        .flat_map(|(expected, commands)| commands.iter().map(move |c| (expected, c)))
+        .filter_map(|(expected, command)| {
+            let result = registry.evaluate(command);
+            (result.decision != expected).then(|| Mismatch {
+                command: command.clone(),
+                expected,
+                actual: result.decision,
+                reason: result.reason,
+            })
+        })
+        .collect()
+    }
+}
+
+/// Re-evaluate logged `(command, decision)` pairs and return how many
+/// distinct commands were replayed and those whose decision differs from
+/// the logged one (`expected`), in first-seen order. A command logged more
+/// than once is compared against its latest decision.
+pub fn replay<'a>(
+    registry: &CommandRegistry,
+    logged: impl IntoIterator<Item = (&'a str, Decision)>,
+) -> (usize, Vec<Mismatch>) {
+    let mut latest: Vec<(&str, Decision)> = Vec::new();
+    let mut index = std::collections::HashMap::new();
+    for (command, decision) in logged {
+        match index.get(command) {
+            Some(&i) => latest[i] = (command, decision),
+            None => {
+                index.insert(command, latest.len());
+                latest.push((command, decision));
+            }
+        }
+    }
+    let changes = latest
+        .iter()
+        .filter_map(|&(command, expected)| {
+            let result = registry.evaluate(command);
+            (result.decision != expected).then(|| Mismatch {
+                command: command.to_string(),
+                expected,
+                actual: result.decision,
+                reason: result.reason,
+            })
+        })
+        .collect();
+    (latest.len(), changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn plain_and_hook_lines() {
+        assert_eq!(command_from_line("  ls -la "), Some("ls -la".into()));
+        assert_eq!(command_from_line(""), None);
+        assert_eq!(command_from_line("# comment"), None);
+        assert_eq!(
+            command_from_line(r#"{"tool_name":"Bash","tool_input":{"command":"git status"}}"#),
+            Some("git status".into())
+        );
+        assert_eq!(
+            command_from_line(r#"{"tool_name":"Read","tool_input":{"file_path":"/x"}}"#),
+            None
+        );
+        // Not a hook input: a brace group, or JSON without a tool.
+        assert_eq!(
+            command_from_line("{ rm -rf /tmp/x; }"),
+            Some("{ rm -rf /tmp/x; }".into())
+        );
+        assert_eq!(command_from_line(r#"{"a":1}"#), Some(r#"{"a":1}"#.into()));
+    }
+
+    #[test]
+    fn reasons_on_one_line() {
+        assert_eq!(one_line("allowed: ls"), "allowed: ls");
+        assert_eq!(
+            one_line("compound command (&&):\n  [ls] -> ALLOW: allowed\n  [rm x] -> ASK: rm"),
+            "compound command (&&):; [ls] -> ALLOW: allowed; [rm x] -> ASK: rm"
+        );
+        // No sub-reasons: no dangling separator.
+        assert_eq!(one_line("compound command:\n"), "compound command");
+        assert_eq!(one_line("compound command (;):"), "compound command (;)");
+    }
+
+    #[test]
+    fn evaluates_each_line() {
+        let registry = CommandRegistry::from_config(&Config::default_config());
+        let text = "ls\n\n# skipped\nrm -rf build\n{ shred key; }\nshred secret\n";
+        let results = evaluate_lines(&registry, text);
+        let summary: Vec<(usize, &str, Decision)> = results
+            .iter()
+            .map(|r| (r.line, r.command.as_str(), r.decision))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "ls", Decision::Allow),
+                (4, "rm -rf build", Decision::Ask),
+                (5, "{ shred key; }", Decision::Deny),
+                (6, "shred secret", Decision::Deny),
+            ]
+        );
+    }
+
+    #[test]
+    fn corpus_reports_mismatches() {
+        let registry = CommandRegistry::from_config(&Config::default_config());
+        let corpus = Corpus::from_toml(
+            r#"
+            allow = ["git status", "rm -rf build"]
+            ask = ["git push origin main"]
+            deny = ["shred secret", "ls"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(corpus.len(), 5);
+        let mismatches = corpus.check(&registry);
+        let summary: Vec<(&str, Decision, Decision)> = mismatches
+            .iter()
+            .map(|m| (m.command.as_str(), m.expected, m.actual))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("rm -rf build", Decision::Allow, Decision::Ask),
+                ("ls", Decision::Deny, Decision::Allow),
+            ]
+        );
+    }
+
+    #[test]
+    fn replay_reports_changed_decisions() {
+        let registry = CommandRegistry::from_config(&Config::default_config());
+        let logged = [
+            ("ls", Decision::Allow),
+            ("rm -rf build", Decision::Allow),
+            ("shred secret", Decision::Ask),
+            ("rm -rf build", Decision::Ask),
+        ];
+        let (replayed, changes) = replay(&registry, logged);
+        assert_eq!(replayed, 3);
+        let summary: Vec<(&str, Decision, Decision)> = changes
+            .iter()
+            .map(|m| (m.command.as_str(), m.expected, m.actual))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("shred secret", Decision::Ask, Decision::Deny)]
+        );
+    }
+
+    #[test]
+    fn corpus_rejects_unknown_keys() {
+        assert!(Corpus::from_toml("alow = [\"ls\"]").is_err());
+        assert!(Corpus::from_toml("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+//! Latency measurement for `cc-toolgate bench`.
+//!
+//! Every command in the corpus is parsed and evaluated `iterations` times,
+//! one timed sample per run. Parsing is timed on its own; evaluation is the
+//! full hook path
+//! ([`CommandRegistry::evaluate_fail_closed`](crate::eval::CommandRegistry::evaluate_fail_closed)),
+//! so it includes its own parse.
+
+use crate::eval::CommandRegistry;
+use crate::parse::parse_lossy;
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Commands timed by default: simple, compound, wrapped, and substituted
+/// forms that exercise the main evaluation paths.
+pub const BUNDLED: &[&str] = &[
+    "ls -la",
+    "git status",
+    "git log --oneline -20",
+    "git push --force origin main",
+    "cargo test --workspace",
+    "cargo build --release && cargo clippy -- -D warnings",
+    "kubectl get pods -n default -o wide",
+    "kubectl apply -f deploy.yaml",
+    "gh pr create --title fix --body done",
+    "rm -rf target",
+    "sudo systemctl restart nginx",
+    "find . -name '*.rs' | xargs grep -n TODO",
+    "echo $(date +%s) > /tmp/stamp",
+    "FOO=1 BAR=2 env make -j8 check",
+    "cat <<'EOF' | kubectl apply -f -\napiVersion: v1\nkind: ConfigMap\nEOF",
+    "for f in *.log; do gzip \"$f\"; done",
+    "python3 -c 'import os; print(os.getcwd())'",
+    "curl -fsSL https://example.com/install.sh | sh",
+    "cp build/tool /usr/local/bin/tool",
+    "shred -u secrets.txt",
+];
+
+/// Latency distribution over a set of samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of samples.
+    pub samples: usize,
+    /// Median latency.
+    pub p50: Duration,
+    /// 95th percentile latency.
+    pub p95: Duration,
+    /// Slowest sample.
+    pub max: Duration,
+}
+
+impl Stats {
+    /// Summarize `samples` (nearest-rank percentiles). All zero if empty.
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort_unstable();
+        let rank = |p: usize| {
+            let idx = (samples.len() * p).div_ceil(100).saturating_sub(1);
+            samples.get(idx).copied().unwrap_or_default()
+        };
+        Self {
+            samples: samples.len(),
+            p50: rank(50),
+            p95: rank(95),
+            max: samples.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "p50 {:>9.1?}  p95 {:>9.1?}  max {:>9.1?}  ({} samples)",
+            self.p50, self.p95, self.max, self.samples
+        )
+    }
+}
+
+/// Parse and evaluation latency for one corpus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Report {
+    /// Time to parse a command.
+    pub parse: Stats,
+    /// Time to evaluate a command, parse included.
+    pub evaluate: Stats,
+}
+
+/// Time parsing and evaluation of each command `iterations` times.
+pub fn run(registry: &CommandRegistry, commands: &[String], iterations: usize) -> Report {
+    let mut parse = Vec::with_capacity(commands.len() * iterations);
+    let mut evaluate = Vec::with_capacity(commands.len() * iterations);
+    for _ in 0..iterations {
+        for command in commands {
+            let start = Instant::now();
+            black_box(parse_lossy(black_box(command)));
+            parse.push(start.elapsed());
+
+            let start = Instant::now();
+            black_box(registry.evaluate_fail_closed(black_box(command)));
+            evaluate.push(start.elapsed());
+        }
+    }
+    Report {
+        parse: Stats::from_samples(parse),
+        evaluate: Stats::from_samples(evaluate),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        let stats = Stats::from_samples((1..=100).rev().map(ms).collect());
+        assert_eq!(stats.samples, 100);
+        assert_eq!((stats.p50, stats.p95, stats.max), (ms(50), ms(95), ms(100)));
+
+        let stats = Stats::from_samples(vec![ms(3), ms(1), ms(2)]);
+        assert_eq!((stats.p50, stats.p95, stats.max), (ms(2), ms(3), ms(3)));
+    }
+
+    #[test]
+    fn empty_samples() {
+        let stats = Stats::from_samples(Vec::new());
+        assert_eq!(stats.samples, 0);
+        assert_eq!(stats.max, Duration::ZERO);
+    }
+
+    #[test]
+    fn one_sample_per_command_per_iteration() {
+        let registry = CommandRegistry::from_config(&Config::default_config());
+        let commands: Vec<String> = BUNDLED.iter().map(|c| c.to_string()).collect();
+        let report = run(&registry, &commands, 2);
+        assert_eq!(report.parse.samples, BUNDLED.len() * 2);
+        assert_eq!(report.evaluate.samples, BUNDLED.len() * 2);
+        assert!(report.evaluate.p50 <= report.evaluate.p95);
+    }
+
+    #[test]
+    fn bundled_commands_parse() {
+        for command in BUNDLED {
+            assert!(parse_lossy(command).is_some(), "{command}");
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! Import of Claude Code `permissions` patterns as `[[rules]]`.
+//!
+//! Claude Code's `settings.json` holds Bash permission patterns:
+//!
+//! ```json
+//! {"permissions": {"allow": ["Bash(git diff:*)"], "deny": ["Bash(git push --force:*)"]}}
+//! ```
+//!
+//! `Bash(git diff:*)` (or `Bash(git diff *)`) matches `git diff` followed by
+//! anything; `Bash(npm test)` matches exactly `npm test`. [`import`](crate::claude_settings::import) turns
+//! each pattern into a [`RuleConfig`](crate::config::RuleConfig) that compares whole words, deny first,
+//! then ask, then allow, as Claude Code ranks them. Catch-all patterns
+//! (`Bash`, `Bash(*)`) and wildcards anywhere else are not imported.
+//!
+//! [`export`](crate::claude_settings::export) goes the other way, flattening the effective config into
+//! prefix patterns for use without the hook.
+
+use crate::config::{Config, RuleConfig};
+use crate::eval::Decision;
+use agent_shell_parser::parse;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Default)]
+struct Settings {
+    #[serde(default)]
+    permissions: Permissions,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct Permissions {
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    ask: Vec<String>,
+    #[serde(default)]
+    deny: Vec<String>,
+}
+
+/// The result of importing one settings file.
+#[derive(Debug, Default)]
+pub struct Import {
+    /// Translated rules, deny first, then ask, then allow.
+    pub rules: Vec<RuleConfig>,
+    /// Bash patterns that couldn't be translated, with the reason.
+    pub skipped: Vec<String>,
+}
+
+/// Translate the Bash patterns in a `settings.json` document.
+///
+/// With `deny_only`, allow and ask patterns are ignored: a project's settings
+/// may tighten the rules but not loosen them.
+pub fn import(json: &str, deny_only: bool) -> Result<Import, String> {
+    let settings: Settings = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let perms = settings.permissions;
+    let mut lists = vec![(perms.deny, Decision::Deny)];
+    if !deny_only {
+        lists.push((perms.ask, Decision::Ask));
+        lists.push((perms.allow, Decision::Allow));
+    }
+
+    let mut import = Import::default();
+    for (patterns, decision) in lists {
+        for pattern in patterns {
+            let Some(inner) = bash_argument(&pattern) else {
+                continue; // another tool's pattern
+            };
+            match translate(inner) {
+                Ok((command, condition)) => import.rules.push(RuleConfig {
+                    command,
+                    condition,
+                    decision,
+                    reason: format!("Claude Code settings: {pattern}"),
+                    schedule: String::new(),
+                    require_env: Vec::new(),
+                }),
+                Err(why) => import.skipped.push(format!("{pattern}: {why}")),
+            }
+        }
+    }
+    Ok(import)
+}
+
+/// Render `config`'s allow and deny lists as a `settings.json` document.
+///
+/// Each allowed or denied command, each read-only or safe subcommand of
+/// git, cargo, kubectl, gh, hub, tea and fj (but not `kubectl get` or
+/// `describe` while `sensitive_resources` is set, nor a subcommand with a
+/// mutating action or a git one with ref-deleting flags), and each `[[rules]]` entry for a named
+/// command whose condition is `true`, with no schedule or required
+/// environment, becomes a `Bash(<prefix>:*)` pattern.
+/// Everything else the hook decides from arguments, paths or redirections is
+/// left out, so Claude Code asks for it. There's no `ask` list: asking is
+/// already what Claude Code does for unlisted commands.
+pub fn export(config: &Config) -> String {
+    let mut allow = Vec::new();
+    let mut deny = Vec::new();
+    let add = |list: &mut Vec<String>, prefix: String| {
+        let pattern = format!("Bash({prefix}:*)");
+        if !list.contains(&pattern) {
+            list.push(pattern);
+        }
+    };
+
+    for name in &config.commands.allow {
+        add(&mut allow, name.clone());
+    }
+    let subcommands: [(&str, &[String], &[String]); 7] = [
+        ("git", &config.git.read_only, &config.git.mutating),
+        ("cargo", &config.cargo.safe_subcommands, &[]),
+        ("kubectl", &config.kubectl.read_only, &[]),
+        ("gh", &config.gh.read_only, &config.gh.mutating),
+        ("hub", &config.hub.read_only, &config.hub.mutating),
+        ("tea", &config.tea.read_only, &config.tea.mutating),
+        ("fj", &config.fj.read_only, &config.fj.mutating),
+    ];
+    for (command, list, mutating) in subcommands {
+        for sub in list {
+            // `kubectl get:*` would allow `kubectl get secrets` too.
+            let sensitive = command == "kubectl"
+                && !config.kubectl.sensitive_resources.is_empty()
+                && crate::commands::tools::kubectl::SENSITIVE_READS.contains(&sub.as_str());
+            // `git remote:*` would allow `git remote add` too, and `git
+            // branch:*` `git branch -D`.
+            let has_mutating = mutating.iter().any(|m| {
+                m.strip_prefix(sub.as_str())
+                    .is_some_and(|a| a.starts_with(' '))
+            }) || (command == "git"
+                && crate::commands::tools::git::REF_CHANGES
+                    .iter()
+                    .any(|(s, _, _)| s == sub));
+            if !sensitive && !has_mutating {
+                add(&mut allow, format!("{command} {sub}"));
+            }
+        }
+    }
+    for name in &config.commands.deny {
+        add(&mut deny, name.clone());
+    }
+    let unconditional = config.rules.iter().filter(|rule| {
+        !rule.command.is_empty()
+            && rule.schedule.is_empty()
+            && rule.require_env.is_empty()
+            && rule.condition.trim() == "true"
+    });
+    for rule in unconditional {
+        match rule.decision {
+            Decision::Allow => add(&mut allow, rule.command.clone()),
+            Decision::Deny => add(&mut deny, rule.command.clone()),
+            Decision::Ask => {}
+        }
+    }
+
+    let settings = serde_json::json!({"permissions": {"allow": allow, "deny": deny}});
+    serde_json::to_string_pretty(&settings).expect("JSON values serialize")
+}
+
+/// `Bash(inner)` → `inner`, bare `Bash` → `*`. `None` for other tools.
+fn bash_argument(pattern: &str) -> Option<&str> {
+    let rest = pattern.trim().strip_prefix("Bash")?;
+    if rest.is_empty() {
+        return Some("*");
+    }
+    rest.strip_prefix('(')?.strip_suffix(')').map(str::trim)
+}
+
+/// The rule command and condition for a pattern's inner text.
+fn translate(inner: &str) -> Result<(String, String), &'static str> {
+    let (body, prefix) = match inner
+        .strip_suffix(":*")
+        .or_else(|| inner.strip_suffix(" *"))
+    {
+        Some(body) => (body.trim_end(), true),
+        None => (inner, false),
+    };
+    if body.is_empty() || body == "*" {
+        return Err("matches every command");
+    }
+    if body.contains('*') {
+        return Err("wildcards are only supported at the end");
+    }
+    let words = parse::tokenize(body);
+    let (command, args) = words.split_first().ok_or("empty pattern")?;
+    if command.is_assignment() {
+        return Err("leading assignments are not supported");
+    }
+    let mut clauses = vec![format!(
+        "args.len() {} {}",
+        if prefix { ">=" } else { "==" },
+        args.len()
+    )];
+    clauses.extend(
+        args.iter()
+            .enumerate()
+            .map(|(i, arg)| format!("args[{i}] == {}", quote(arg))),
+    );
+    Ok((command.basename().to_string(), clauses.join(" && ")))
+}
+
+/// A condition string literal.
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::eval::CommandRegistry;
+
+    fn rules(json: &str) -> Vec<(String, String, Decision)> {
+        import(json, false)
+            .unwrap()
+            .rules
+            .into_iter()
+            .map(|r| (r.command, r.condition, r.decision))
+            .collect()
+    }
+
+    #[test]
+    fn translates_prefix_and_exact_patterns() {
+        let json = r#"{"permissions": {
+            "allow": ["Bash(git diff:*)", "Bash(npm run test)", "Read(src/**)"],
+            "ask": ["Bash(docker compose *)"],
+            "deny": ["Bash(git push --force:*)"]
+        }}"#;
+        assert_eq!(
+            rules(json),
+            [
+                (
+                    "git".into(),
+                    r#"args.len() >= 2 && args[0] == "push" && args[1] == "--force""#.into(),
+                    Decision::Deny
+                ),
+                (
+                    "docker".into(),
+                    r#"args.len() >= 1 && args[0] == "compose""#.into(),
+                    Decision::Ask
+                ),
+                (
+                    "git".into(),
+                    r#"args.len() >= 1 && args[0] == "diff""#.into(),
+                    Decision::Allow
+                ),
+                (
+                    "npm".into(),
+                    r#"args.len() == 2 && args[0] == "run" && args[1] == "test""#.into(),
+                    Decision::Allow
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_what_it_cannot_express() {
+        let json = r#"{"permissions": {"allow": [
+            "Bash", "Bash(*)", "Bash(ls:*)", "Bash(git * main)", "Bash(FOO=1 make)"
+        ]}}"#;
+        let import = import(json, false).unwrap();
+        assert_eq!(import.rules.len(), 1);
+        assert_eq!(import.skipped.len(), 4, "{:?}", import.skipped);
+        assert!(import.skipped[0].contains("matches every command"));
+    }
+
+    #[test]
+    fn deny_only_for_untrusted_settings() {
+        let json = r#"{"permissions": {"allow": ["Bash(rm:*)"], "ask": ["Bash(curl:*)"], "deny": ["Bash(make deploy)"]}}"#;
+        let import = import(json, true).unwrap();
+        assert_eq!(import.rules.len(), 1);
+        assert_eq!(import.rules[0].decision, Decision::Deny);
+        assert!(super::import("{}", false).unwrap().rules.is_empty());
+        assert!(super::import("not json", false).is_err());
+    }
+
+    #[test]
+    fn export_lists_static_decisions() {
+        let mut config = Config::default_config();
+        config.rules.push(RuleConfig {
+            command: "deployctl".into(),
+            condition: "true".into(),
+            decision: Decision::Deny,
+            reason: String::new(),
+            schedule: String::new(),
+            require_env: Vec::new(),
+        });
+        let settings: serde_json::Value = serde_json::from_str(&export(&config)).unwrap();
+        let list = |name: &str| -> Vec<String> {
+            serde_json::from_value(settings["permissions"][name].clone()).unwrap()
+        };
+        let (allow, deny) = (list("allow"), list("deny"));
+        for pattern in ["Bash(ls:*)", "Bash(git status:*)", "Bash(gh pr view:*)"] {
+            assert!(allow.contains(&pattern.to_string()), "{pattern}");
+        }
+        for pattern in ["Bash(shred:*)", "Bash(deployctl:*)"] {
+            assert!(deny.contains(&pattern.to_string()), "{pattern}");
+        }
+        assert!(!allow.iter().any(|p| p.starts_with("Bash(rm")));
+        // `hostnamectl set-hostname` asks, so neither tool is a prefix.
+        assert!(!allow.contains(&"Bash(hostnamectl:*)".to_string()));
+        assert!(!allow.contains(&"Bash(timedatectl:*)".to_string()));
+        // `kubectl get secrets` asks, so `kubectl get` can't be a prefix.
+        assert!(allow.contains(&"Bash(kubectl logs:*)".to_string()));
+        assert!(!allow.contains(&"Bash(kubectl get:*)".to_string()));
+        // Nor `git remote`, since `git remote add` asks.
+        assert!(!allow.contains(&"Bash(git remote:*)".to_string()));
+        assert!(!allow.contains(&"Bash(git branch:*)".to_string()));
+        assert!(allow.contains(&"Bash(tea pulls list:*)".to_string()));
+        assert!(!allow.contains(&"Bash(tea pulls:*)".to_string()));
+        assert!(settings["permissions"].get("ask").is_none());
+
+        // What's exported imports back without loss.
+        let import = import(&export(&config), false).unwrap();
+        assert!(import.skipped.is_empty(), "{:?}", import.skipped);
+        assert_eq!(import.rules.len(), allow.len() + deny.len());
+    }
+
+    #[test]
+    fn imported_rules_match_whole_words() {
+        let json = r#"{"permissions": {
+            "allow": ["Bash(git diff:*)", "Bash(echo \"a b\")"],
+            "deny": ["Bash(make deploy:*)"]
+        }}"#;
+        let mut config = Config::default_config();
+        config.rules = import(json, false).unwrap().rules;
+        let reg = CommandRegistry::from_config(&config);
+        for (command, expected) in [
+            ("git diff HEAD~1", Decision::Allow),
+            ("git diff", Decision::Allow),
+            ("git diff-tree HEAD", Decision::Ask), // not `git diff`: the git spec decides
+            ("echo 'a b'", Decision::Allow),
+            ("make deploy prod", Decision::Deny),
+            ("make deployment", Decision::Ask),
+        ] {
+            assert_eq!(reg.evaluate(command).decision, expected, "{command}");
+        }
+        let result = reg.evaluate("make deploy");
+        assert_eq!(result.reason, "Claude Code settings: Bash(make deploy:*)");
+    }
+}
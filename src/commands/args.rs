@@ -0,0 +1,233 @@
+//! Schema-aware argument walking for command specs.
+//!
+//! agent-shell-parser's `ParsedCommand` is schema-free: it cannot know that
+//! `-P 22` consumes `22` as a value. Specs that need to find operands (source
+//! and destination paths, URLs, hosts) describe which flags take values with a
+//! [`FlagSchema`](crate::commands::args::FlagSchema) and walk the arguments
+//! with [`ParsedArgs::parse`](crate::commands::args::ParsedArgs::parse).
+//!
+//! [`remote_host`](crate::commands::args::remote_host) classifies operands
+//! of remote-copy tools (scp, rsync).
+
+use agent_shell_parser::parse::Word;
+use std::borrow::Cow;
+
+/// Describes which flags of a command consume a value.
+pub struct FlagSchema {
+    /// Short flag letters that consume a value, either attached (`-P22`) or
+    /// as the next word (`-P 22`). Clustered flags (`-rP 22`) are expanded.
+    pub short_value: &'static str,
+    /// Long flags (including the leading `--`) that consume a value, either
+    /// as `--flag=value` or as the next word.
+    pub long_value: &'static [&'static str],
+}
+
+/// A single flag occurrence, with its value if the schema says it takes one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flag {
+    /// Flag name including dashes (`-r`, `--delete`).
+    pub name: String,
+    /// The consumed value, if any.
+    pub value: Option<String>,
+}
+
+/// Arguments split into flags and positional operands using a [`FlagSchema`].
+#[derive(Debug, Default)]
+pub struct ParsedArgs {
+    /// Flags in source order. Short clusters are expanded (`-rp` → `-r`, `-p`).
+    pub flags: Vec<Flag>,
+    /// Positional operands in source order (including everything after `--`).
+    pub positionals: Vec<String>,
+}
+
+impl ParsedArgs {
+    /// Walk `args` (the words after the command name) using `schema`.
+    pub fn parse(args: &[Word], schema: &FlagSchema) -> Self {
+        let mut parsed = Self::default();
+        let mut iter = args.iter();
+        while let Some(word) = iter.next() {
+            let w = word.as_str();
+            if w == "--" {
+                parsed
+                    .positionals
+                    .extend(iter.by_ref().map(|w| w.to_string()));
+                break;
+            }
+            if let Some(long) = w.strip_prefix("--") {
+                let (name, inline) = match long.split_once('=') {
+                    Some((n, v)) => (format!("--{n}"), Some(v.to_string())),
+                    None => (w.to_string(), None),
+                };
+                let value = if inline.is_none() && schema.long_value.contains(&name.as_str()) {
+                    iter.next().map(|v| v.to_string())
+                } else {
+                    inline
+                };
+                parsed.flags.push(Flag { name, value });
+                continue;
+            }
+            if w.len() > 1 && w.starts_with('-') {
+                let cluster = &w[1..];
+                for (i, c) in cluster.char_indices() {
+                    let name = format!("-{c}");
+                    if schema.short_value.contains(c) {
+                        let rest = &cluster[i + c.len_utf8()..];
+                        let value = if rest.is_empty() {
+                            iter.next().map(|v| v.to_string())
+                        } else {
+                            Some(rest.to_string())
+                        };
+                        parsed.flags.push(Flag { name, value });
+                        break;
+                    }
+                    parsed.flags.push(Flag { name, value: None });
+                }
+                continue;
+            }
+            parsed.positionals.push(w.to_string());
+        }
+        parsed
+    }
+
+    /// Check whether any of the given flag names is present.
+    pub fn has_any(&self, names: &[&str]) -> bool {
+        self.flags.iter().any(|f| names.contains(&f.name.as_str()))
+    }
+
+    /// Values of every occurrence of any of the given flag names, in source order.
+    pub fn values<'a>(&'a self, names: &'a [&str]) -> impl Iterator<Item = &'a str> + 'a {
+        self.flags
+            .iter()
+            .filter(|f| names.contains(&f.name.as_str()))
+            .filter_map(|f| f.value.as_deref())
+    }
+}
+
+/// A value with its shell quoting removed, as the shell passes it on.
+///
+/// The parser strips the quotes around a whole word, but not inside one,
+/// so a value attached to its option (`--command='rm -rf /'`,
+/// `-c'rm -rf /'`) keeps them. Don't use this on a word the parser already
+/// unquoted: its remaining quotes are the value's own.
+pub fn dequote(value: &str) -> Cow<'_, str> {
+    if !value.contains(['\'', '"', '\\']) {
+        return Cow::Borrowed(value);
+    }
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => out.extend(chars.by_ref().take_while(|&c| c != '\'')),
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        // Inside double quotes a backslash escapes only these.
+                        '\\' => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => out.push(c),
+                            Some(c) => out.extend(['\\', c]),
+                            None => out.push('\\'),
+                        },
+                        c => out.push(c),
+                    }
+                }
+            }
+            '\\' => out.extend(chars.next()),
+            c => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Return the host part if a copy operand names a remote location.
+///
+/// Follows the scp/rsync rule: a URL (`scp://host/...`, `rsync://host/...`),
+/// `[v6addr]:path`, or a colon that appears before any slash (`host:path`,
+/// `user@host:path`, `host::module`).
+pub fn remote_host(operand: &str) -> Option<&str> {
+    for scheme in ["scp://", "rsync://"] {
+        if let Some(rest) = operand.strip_prefix(scheme) {
+            return Some(rest.split('/').next().unwrap_or(rest));
+        }
+    }
+    if operand.starts_with('[') {
+        return operand.find("]:").map(|i| &operand[..=i]);
+    }
+    let colon = operand.find(':')?;
+    let host = &operand[..colon];
+    (!host.is_empty() && !host.contains('/')).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: FlagSchema = FlagSchema {
+        short_value: "Pio",
+        long_value: &["--port"],
+    };
+
+    fn parse(cmd: &str) -> ParsedArgs {
+        let words = agent_shell_parser::parse::tokenize(cmd);
+        ParsedArgs::parse(&words[1..], &SCHEMA)
+    }
+
+    #[test]
+    fn value_flag_consumes_next_word() {
+        let a = parse("scp -P 2222 a host:b");
+        assert_eq!(a.values(&["-P"]).collect::<Vec<_>>(), vec!["2222"]);
+        assert_eq!(a.positionals, vec!["a", "host:b"]);
+    }
+
+    #[test]
+    fn attached_short_value() {
+        let a = parse("scp -P2222 a b");
+        assert_eq!(a.values(&["-P"]).collect::<Vec<_>>(), vec!["2222"]);
+        assert_eq!(a.positionals, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn cluster_expands_and_last_letter_takes_value() {
+        let a = parse("scp -rpP 22 a b");
+        assert!(a.has_any(&["-r"]));
+        assert!(a.has_any(&["-p"]));
+        assert_eq!(a.values(&["-P"]).collect::<Vec<_>>(), vec!["22"]);
+        assert_eq!(a.positionals, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn long_value_forms() {
+        let a = parse("x --port 1 --port=2 --verbose a");
+        assert_eq!(a.values(&["--port"]).collect::<Vec<_>>(), vec!["1", "2"]);
+        assert!(a.has_any(&["--verbose"]));
+        assert_eq!(a.positionals, vec!["a"]);
+    }
+
+    #[test]
+    fn remote_host_detection() {
+        assert_eq!(remote_host("host:file"), Some("host"));
+        assert_eq!(remote_host("user@host:"), Some("user@host"));
+        assert_eq!(remote_host("host::module/x"), Some("host"));
+        assert_eq!(remote_host("scp://host:22/x"), Some("host:22"));
+        assert_eq!(remote_host("rsync://host/mod"), Some("host"));
+        assert_eq!(remote_host("[::1]:x"), Some("[::1]"));
+        assert_eq!(remote_host("./a:b"), None);
+        assert_eq!(remote_host("file"), None);
+        assert_eq!(remote_host(":file"), None);
+    }
+
+    #[test]
+    fn dequote_removes_shell_quoting() {
+        assert_eq!(dequote("ls"), "ls");
+        assert_eq!(dequote("'rm -rf /'"), "rm -rf /");
+        assert_eq!(dequote(r#""rm -rf \"$HOME\"""#), r#"rm -rf "$HOME""#);
+        assert_eq!(dequote(r#"'a'"b"\ c"#), "ab c");
+        assert_eq!(dequote(r#""echo 'hi'""#), "echo 'hi'");
+    }
+
+    #[test]
+    fn double_dash_ends_flags() {
+        let a = parse("x -r -- -notaflag");
+        assert_eq!(a.positionals, vec!["-notaflag"]);
+    }
+}
@@ -6,7 +6,12 @@
 //!   subcommand awareness).
 //! - **`tools`** — Subcommand-aware evaluators for specific CLI tools (git, cargo, kubectl, gh),
 //!   each with config-driven classification, env-gated auto-allow, and redirection escalation.
+//!
+//! **`args`** provides schema-aware flag/operand splitting shared by specs that
+//! need to locate operands (paths, hosts) rather than just subcommands.
 
+/// Schema-aware flag and operand parsing for command arguments.
+pub mod args;
 /// Data-driven spec for flat allow/ask/deny command lists.
 pub mod simple;
 /// Subcommand-aware evaluators for specific CLI tools.
@@ -21,4 +26,11 @@ use crate::eval::{CommandContext, RuleMatch};
 pub trait CommandSpec: Send + Sync {
     /// Evaluate the command in the given context and return a decision.
     fn evaluate(&self, ctx: &CommandContext) -> RuleMatch;
+
+    /// A safer alternative to offer when [`evaluate`](Self::evaluate)
+    /// returns ASK or DENY (e.g. a `--dry-run` preview), phrased for the
+    /// reason string. Specs without a well-understood rewrite return `None`.
+    fn suggest(&self, _ctx: &CommandContext) -> Option<String> {
+        None
+    }
 }
@@ -15,9 +15,22 @@ pub struct SimpleCommandSpec {
 
 impl SimpleCommandSpec {
     /// Create a new spec with the given baseline decision.
-    pub fn new(decision: Decision) -> Self {
+    pub const fn new(decision: Decision) -> Self {
         Self { decision }
     }
+
+    /// The process-wide spec for `decision`, shared by every flat-list
+    /// command with that decision.
+    pub fn shared(decision: Decision) -> &'static Self {
+        static ALLOW: SimpleCommandSpec = SimpleCommandSpec::new(Decision::Allow);
+        static ASK: SimpleCommandSpec = SimpleCommandSpec::new(Decision::Ask);
+        static DENY: SimpleCommandSpec = SimpleCommandSpec::new(Decision::Deny);
+        match decision {
+            Decision::Allow => &ALLOW,
+            Decision::Ask => &ASK,
+            Decision::Deny => &DENY,
+        }
+    }
 }
 
 impl CommandSpec for SimpleCommandSpec {
@@ -0,0 +1,188 @@
+//! User and group account evaluation: useradd, usermod, passwd, groupmod
+//! and their relatives.
+//!
+//! Account changes get `[accounts] change`, deny by default: an agent
+//! creating users, resetting passwords or adding itself to `sudo` is a
+//! high-severity event, not an unrecognized command. Queries (`passwd -S`,
+//! `chage -l`, `useradd -D`) are allowed.
+
+use super::super::CommandSpec;
+use super::packages::{decided, query};
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::AccountsConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// shadow-utils flags that consume a value. `-m` (create the home
+/// directory) doesn't, so `useradd -m -G sudo bob` keeps its groups.
+const ACCOUNT_FLAGS: FlagSchema = FlagSchema {
+    short_value: "cdefgGkKlpsuRP",
+    long_value: &[
+        "--comment",
+        "--home",
+        "--home-dir",
+        "--expiredate",
+        "--inactive",
+        "--gid",
+        "--groups",
+        "--skel",
+        "--key",
+        "--login",
+        "--password",
+        "--shell",
+        "--uid",
+        "--root",
+        "--prefix",
+    ],
+};
+
+/// Flags that only read, by command.
+const QUERIES: &[(&str, &[&str])] = &[
+    ("passwd", &["-S", "--status"]),
+    ("chage", &["-l", "--list"]),
+];
+
+/// Account evaluator for useradd, adduser, userdel, deluser, usermod,
+/// passwd, chpasswd, chage, chsh, chfn, newusers, groupadd, addgroup,
+/// groupdel, delgroup, groupmod, gpasswd, vipw and vigr.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `passwd -S`, `chage -l`, `useradd -D` alone, `--help` → ALLOW
+///    (ASK with redirection)
+/// 3. Everything else → `[accounts] change`, naming the groups for
+///    `usermod -G`/`-aG`
+pub struct AccountsSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Decision for an account change.
+    change: Decision,
+}
+
+impl AccountsSpec {
+    /// Build an accounts spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &AccountsConfig) -> Self {
+        Self {
+            base,
+            change: config.change,
+        }
+    }
+
+    /// The query `ctx` runs, if it only reads.
+    fn query(ctx: &CommandContext, args: &ParsedArgs) -> Option<String> {
+        let cmd = ctx.base_command.as_str();
+        if args.has_any(&["-h", "--help"]) {
+            return Some(format!("{cmd} --help"));
+        }
+        if cmd == "useradd" && args.positionals.is_empty() && args.flags.len() == 1 {
+            return args
+                .has_any(&["-D", "--defaults"])
+                .then(|| "useradd -D".into());
+        }
+        let (_, flags) = QUERIES.iter().find(|(name, _)| *name == cmd)?;
+        let flag = args
+            .flags
+            .iter()
+            .find(|f| flags.contains(&f.name.as_str()))?;
+        Some(format!("{cmd} {}", flag.name))
+    }
+}
+
+impl CommandSpec for AccountsSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        if self.base == Decision::Deny {
+            return SimpleCommandSpec::new(self.base).evaluate(ctx);
+        }
+        let args = ParsedArgs::parse(ctx.args(), &ACCOUNT_FLAGS);
+        if let Some(what) = Self::query(ctx, &args) {
+            return query(ctx, &what);
+        }
+        let cmd = &ctx.base_command;
+        let groups: Vec<&str> = args.values(&["-G", "--groups"]).collect();
+        let what = if groups.is_empty() {
+            cmd.clone()
+        } else if cmd == "usermod" && args.has_any(&["-a", "--append"]) {
+            format!("{cmd} -aG {}", groups.join(","))
+        } else {
+            format!("{cmd} -G {}", groups.join(","))
+        };
+        decided(self.change, &what, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let spec = AccountsSpec::from_config(Decision::Ask, &Config::default_config().accounts);
+        spec.evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn queries_allow() {
+        for cmd in [
+            "passwd -S",
+            "passwd -S alice",
+            "passwd --status alice",
+            "chage -l alice",
+            "useradd -D",
+            "usermod --help",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(eval("chage -l alice").reason, "read-only chage -l");
+    }
+
+    #[test]
+    fn changes_deny_by_default() {
+        for cmd in [
+            "useradd -m bob",
+            "adduser bob",
+            "userdel -r bob",
+            "usermod -L bob",
+            "passwd",
+            "passwd bob",
+            "chpasswd",
+            "chage -E 0 bob",
+            "groupadd devs",
+            "groupmod -n admins devs",
+            "gpasswd -a bob wheel",
+            "useradd -D -s /bin/zsh",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Deny, "{cmd}");
+        }
+        assert_eq!(eval("passwd bob").reason, "blocked command: passwd");
+    }
+
+    #[test]
+    fn group_grants_name_the_groups() {
+        assert_eq!(
+            eval("usermod -aG sudo bob").reason,
+            "blocked command: usermod -aG sudo"
+        );
+        assert_eq!(
+            eval("usermod --append --groups docker,wheel bob").reason,
+            "blocked command: usermod -aG docker,wheel"
+        );
+        assert_eq!(
+            eval("useradd -m -G wheel bob").reason,
+            "blocked command: useradd -G wheel"
+        );
+    }
+
+    #[test]
+    fn change_is_configurable() {
+        let config = AccountsConfig {
+            change: Decision::Ask,
+        };
+        let spec = AccountsSpec::from_config(Decision::Ask, &config);
+        let result = spec.evaluate(&CommandContext::from_command("useradd bob"));
+        assert_eq!(result.decision, Decision::Ask);
+        assert_eq!(result.reason, "useradd requires confirmation");
+        let spec = AccountsSpec::from_config(Decision::Deny, &config);
+        let ctx = CommandContext::from_command("passwd -S");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+}
@@ -0,0 +1,333 @@
+//! Ansible evaluation: ansible, ansible-playbook and ansible-inventory.
+//!
+//! Check mode (`--check`), syntax checks, listings, `ansible-inventory`
+//! and fact-gathering modules (`-m setup`, `-m ping`) only read, and are
+//! allowed unless they `--become`. Real runs get `[ansible] run`, and
+//! `--become` raises them to `privileged` as well. An ad-hoc `-m shell`/`command`/`raw` task's
+//! arguments are a command line; the registry evaluates it like a
+//! top-level command (see
+//! [`payload`](crate::commands::tools::ansible::payload)).
+
+use super::super::CommandSpec;
+use super::packages::{decided, query};
+use crate::commands::args::{FlagSchema, ParsedArgs, dequote};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::AnsibleConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// ansible and ansible-playbook flags that consume a value.
+const ANSIBLE_FLAGS: FlagSchema = FlagSchema {
+    short_value: "iMmaueltfcTBP",
+    long_value: &[
+        "--inventory",
+        "--inventory-file",
+        "--module-path",
+        "--module-name",
+        "--args",
+        "--user",
+        "--extra-vars",
+        "--limit",
+        "--tags",
+        "--skip-tags",
+        "--forks",
+        "--connection",
+        "--timeout",
+        "--background",
+        "--poll",
+        "--private-key",
+        "--key-file",
+        "--become-user",
+        "--become-method",
+        "--vault-id",
+        "--vault-password-file",
+        "--start-at-task",
+        "--ssh-common-args",
+        "--ssh-extra-args",
+        "--scp-extra-args",
+        "--sftp-extra-args",
+        "--playbook-dir",
+        "--output",
+        "--host",
+    ],
+};
+
+/// Modules that only gather facts.
+const FACT_MODULES: &[&str] = &[
+    "setup",
+    "ping",
+    "gather_facts",
+    "package_facts",
+    "service_facts",
+];
+
+/// Modules whose arguments are a command line.
+const COMMAND_MODULES: &[&str] = &["shell", "command", "raw"];
+
+/// ansible-playbook flags that only check or list, never change a host.
+const PLAYBOOK_CHECKS: &[&str] = &[
+    "-C",
+    "--check",
+    "--syntax-check",
+    "--list-hosts",
+    "--list-tasks",
+    "--list-tags",
+];
+
+/// A module name without its `ansible.builtin.`/`ansible.legacy.` prefix.
+fn short_module(module: &str) -> &str {
+    module
+        .strip_prefix("ansible.builtin.")
+        .or_else(|| module.strip_prefix("ansible.legacy."))
+        .unwrap_or(module)
+}
+
+/// An ad-hoc `ansible` task that runs a command line.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Payload {
+    /// The host pattern.
+    pub pattern: String,
+    /// `shell`, `command` or `raw`.
+    pub module: String,
+    /// The command line given with `-a`.
+    pub command: String,
+    /// Whether the task runs with `--become`.
+    pub privileged: bool,
+}
+
+/// The command line an ad-hoc `ansible` task runs, if it runs one:
+/// `-m shell`, `command` (the default module) or `raw` with `-a`. These
+/// modules are skipped in check mode, so `--check` runs nothing, but
+/// with `--become` the command line is evaluated regardless.
+pub fn payload(ctx: &CommandContext) -> Option<Payload> {
+    if ctx.base_command != "ansible" {
+        return None;
+    }
+    let args = ParsedArgs::parse(ctx.args(), &ANSIBLE_FLAGS);
+    let privileged = args.has_any(&["-b", "--become"]);
+    if args.has_any(&["-C", "--check"]) && !privileged {
+        return None;
+    }
+    let module = args
+        .values(&["-m", "--module-name"])
+        .last()
+        .unwrap_or("command");
+    let module = short_module(module);
+    if !COMMAND_MODULES.contains(&module) {
+        return None;
+    }
+    Some(Payload {
+        pattern: args.positionals.first()?.clone(),
+        module: module.to_string(),
+        // `-a'...'` and `--args='...'` keep the quotes inside the word.
+        command: dequote(args.values(&["-a", "--args"]).last()?).into_owned(),
+        privileged,
+    })
+}
+
+/// Ansible evaluator for ansible, ansible-playbook and ansible-inventory.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `--become` → the stricter of `[ansible] run` and `privileged`, even
+///    in check mode
+/// 3. `ansible-playbook --check`/`--syntax-check`/`--list-*`,
+///    `ansible-inventory`, ad-hoc `-m setup`/`ping`, `--check` ad-hoc
+///    tasks → ALLOW (ASK with redirection)
+/// 4. Everything else → `[ansible] run`
+///
+/// Ad-hoc `shell`/`command`/`raw` tasks are evaluated by the registry with
+/// their command line; here they get `run`.
+pub struct AnsibleSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Decision for a run that changes hosts.
+    run: Decision,
+    /// Decision for a run with `--become`.
+    privileged: Decision,
+}
+
+impl AnsibleSpec {
+    /// Build an ansible spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &AnsibleConfig) -> Self {
+        Self {
+            base,
+            run: config.run,
+            privileged: config.privileged,
+        }
+    }
+
+    /// The query `ctx` runs, if it only reads.
+    fn query(ctx: &CommandContext, args: &ParsedArgs) -> Option<String> {
+        let cmd = ctx.base_command.as_str();
+        match cmd {
+            // `--output` writes the inventory to a file.
+            "ansible-inventory" => (!args.has_any(&["--output"])).then(|| cmd.to_string()),
+            "ansible-playbook" => {
+                let check = args
+                    .flags
+                    .iter()
+                    .find(|f| PLAYBOOK_CHECKS.contains(&f.name.as_str()))?;
+                Some(format!("{cmd} {}", check.name))
+            }
+            _ => {
+                if args.has_any(&["-C", "--check"]) {
+                    return Some(format!("{cmd} --check"));
+                }
+                let module = args.values(&["-m", "--module-name"]).last()?;
+                FACT_MODULES
+                    .contains(&short_module(module))
+                    .then(|| format!("{cmd} -m {module}"))
+            }
+        }
+    }
+
+    /// `[ansible] run`, raised to `privileged` with `--become`.
+    fn floor(&self, privileged: bool) -> Decision {
+        if privileged {
+            self.run.max(self.privileged)
+        } else {
+            self.run
+        }
+    }
+}
+
+impl CommandSpec for AnsibleSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        if self.base == Decision::Deny {
+            return SimpleCommandSpec::new(self.base).evaluate(ctx);
+        }
+        let args = ParsedArgs::parse(ctx.args(), &ANSIBLE_FLAGS);
+        if args.has_any(&["-b", "--become"]) {
+            let what = format!("{} --become", ctx.base_command);
+            return decided(self.floor(true), &what, None);
+        }
+        if let Some(what) = Self::query(ctx, &args) {
+            return query(ctx, &what);
+        }
+        decided(self.floor(false), &ctx.base_command, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let spec = AnsibleSpec::from_config(Decision::Ask, &Config::default_config().ansible);
+        spec.evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn checks_and_facts_allow() {
+        for cmd in [
+            "ansible-playbook site.yml --check --diff",
+            "ansible-playbook -i prod site.yml -C",
+            "ansible-playbook site.yml --syntax-check",
+            "ansible-playbook site.yml --list-hosts",
+            "ansible-inventory --list",
+            "ansible-inventory -i prod --graph",
+            "ansible all -m setup",
+            "ansible web -i hosts -m ansible.builtin.ping",
+            "ansible all -m copy -a 'src=a dest=/etc/a' --check",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(
+            eval("ansible-playbook site.yml --check --diff").reason,
+            "read-only ansible-playbook --check"
+        );
+        assert_eq!(
+            eval("ansible all -m setup").reason,
+            "read-only ansible -m setup"
+        );
+    }
+
+    #[test]
+    fn runs_ask() {
+        for cmd in [
+            "ansible-playbook site.yml",
+            "ansible-playbook -i prod site.yml --diff",
+            "ansible all -m copy -a 'src=a dest=/etc/a'",
+            "ansible all -m apt -a name=nginx --become",
+            "ansible-inventory --list --output inventory.json",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("ansible-playbook site.yml -b").reason,
+            "ansible-playbook --become requires confirmation"
+        );
+    }
+
+    #[test]
+    fn become_outranks_check_mode() {
+        for cmd in [
+            "ansible all --check --become -m shell -a 'rm -rf /tmp/x'",
+            "ansible-playbook site.yml -C -b",
+            "ansible all -m setup --become",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("ansible all --check --become -m shell -a id").reason,
+            "ansible --become requires confirmation"
+        );
+    }
+
+    #[test]
+    fn become_is_configurable() {
+        let config = AnsibleConfig {
+            run: Decision::Allow,
+            privileged: Decision::Deny,
+        };
+        let spec = AnsibleSpec::from_config(Decision::Ask, &config);
+        let eval = |cmd| spec.evaluate(&CommandContext::from_command(cmd)).decision;
+        assert_eq!(eval("ansible-playbook site.yml"), Decision::Allow);
+        assert_eq!(eval("ansible-playbook site.yml --become"), Decision::Deny);
+        let spec = AnsibleSpec::from_config(Decision::Deny, &config);
+        let ctx = CommandContext::from_command("ansible-inventory --list");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+
+    #[test]
+    fn command_payloads() {
+        let payload_of = |cmd| payload(&CommandContext::from_command(cmd));
+        assert_eq!(
+            payload_of("ansible web -m shell -a 'rm -rf /tmp/cache' -b"),
+            Some(Payload {
+                pattern: "web".into(),
+                module: "shell".into(),
+                command: "rm -rf /tmp/cache".into(),
+                privileged: true,
+            })
+        );
+        // `command` is the default module.
+        assert_eq!(
+            payload_of("ansible all -i hosts -a uptime").map(|p| p.module),
+            Some("command".into())
+        );
+        assert_eq!(
+            payload_of("ansible all -m ansible.builtin.raw -a id").map(|p| p.command),
+            Some("id".into())
+        );
+        assert_eq!(payload_of("ansible all -m setup"), None);
+        assert_eq!(
+            payload_of("ansible all -m shell -a 'rm -rf /' --check"),
+            None
+        );
+        assert_eq!(
+            payload_of("ansible all -m shell -a 'rm -rf /' --check -b").map(|p| p.command),
+            Some("rm -rf /".into())
+        );
+        assert_eq!(
+            payload_of("ansible all -m shell -a'rm -rf /'").map(|p| p.command),
+            Some("rm -rf /".into())
+        );
+        assert_eq!(
+            payload_of("ansible all -m shell --args=\"rm -rf /\"").map(|p| p.command),
+            Some("rm -rf /".into())
+        );
+        assert_eq!(payload_of("ansible-playbook -a x site.yml"), None);
+    }
+}
@@ -0,0 +1,410 @@
+//! AWS CLI evaluation.
+//!
+//! `aws s3` and `aws s3api` are classified by action: listings are
+//! allowed, and downloads, presigned URLs, uploads and deletions each get
+//! their own `[aws]` decision, so `aws s3 rb --force` doesn't share one with
+//! `aws s3 ls`. A download's local destination is also classified by
+//! `[paths]`.
+//!
+//! IAM changes and STS operations that issue credentials get `[aws]
+//! identity`, deny by default: a new access key or an attached policy
+//! outlasts the session that made it. Other services keep the base
+//! decision.
+
+use super::super::CommandSpec;
+use super::packages::{decided, query};
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::{AwsConfig, PathsConfig};
+use crate::eval::paths::PathPolicy;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// Options that consume a value: the global ones, and those of `s3` and
+/// `s3api` that come before an operand.
+const AWS_FLAGS: FlagSchema = FlagSchema {
+    short_value: "",
+    long_value: &[
+        "--profile",
+        "--region",
+        "--endpoint-url",
+        "--output",
+        "--query",
+        "--color",
+        "--ca-bundle",
+        "--cli-read-timeout",
+        "--cli-connect-timeout",
+        "--cli-binary-format",
+        "--cli-input-json",
+        "--exclude",
+        "--include",
+        "--acl",
+        "--grants",
+        "--storage-class",
+        "--sse",
+        "--sse-c",
+        "--sse-c-key",
+        "--sse-kms-key-id",
+        "--content-type",
+        "--cache-control",
+        "--content-disposition",
+        "--content-encoding",
+        "--content-language",
+        "--expires",
+        "--expires-in",
+        "--metadata",
+        "--metadata-directive",
+        "--source-region",
+        "--page-size",
+        "--expected-size",
+        "--bucket",
+        "--key",
+        "--body",
+        "--version-id",
+        "--range",
+        "--prefix",
+        "--delimiter",
+        "--max-items",
+        "--max-keys",
+        "--starting-token",
+        "--continuation-token",
+        "--upload-id",
+        "--part-number",
+        "--copy-source",
+        "--policy",
+        "--tagging",
+    ],
+};
+
+/// `s3api` operations that write to S3, beyond the `put-` and `create-` ones.
+const S3API_WRITES: &[&str] = &[
+    "copy-object",
+    "upload-part",
+    "upload-part-copy",
+    "complete-multipart-upload",
+    "restore-object",
+];
+
+/// STS operations that only read.
+const STS_QUERIES: &[&str] = &[
+    "get-caller-identity",
+    "get-access-key-info",
+    "decode-authorization-message",
+];
+
+/// AWS CLI evaluator.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `s3 ls`, `--dryrun`, and `s3api` `list-`/`head-`/`get-` operations,
+///    `iam get-`/`list-`, `sts get-caller-identity` → ALLOW (ASK with
+///    redirection)
+/// 3. Other `iam` operations, `sts assume-role` and the other credential
+///    issuers → `[aws] identity`
+/// 4. `s3 rm`, `s3 rb`, `s3 mv` out of S3, `s3 sync --delete`, `s3api
+///    delete-` → `[aws] s3_destructive`
+/// 5. `s3 cp`/`sync`/`mv` to S3, `s3 mb`, `s3api put-`/`create-` →
+///    `[aws] s3_upload`
+/// 6. `s3 presign` → `[aws] s3_presign`
+/// 7. `s3 cp`/`sync` from S3, `s3api get-object` → `[aws] s3_download`,
+///    raised by `[paths]` for the local destination
+/// 8. Everything else → the base decision
+pub struct AwsSpec {
+    /// Baseline disposition (the flat-list entry for aws).
+    base: Decision,
+    /// Decision for copying objects from S3.
+    s3_download: Decision,
+    /// Decision for writing objects or buckets to S3.
+    s3_upload: Decision,
+    /// Decision for presigning an object URL.
+    s3_presign: Decision,
+    /// Decision for deleting objects or buckets.
+    s3_destructive: Decision,
+    /// Decision for IAM changes and STS credentials.
+    identity: Decision,
+    /// Path classification for download destinations.
+    paths: PathPolicy,
+}
+
+impl AwsSpec {
+    /// Build an aws spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &AwsConfig, paths: &PathsConfig) -> Self {
+        Self {
+            base,
+            s3_download: config.s3_download,
+            s3_upload: config.s3_upload,
+            s3_presign: config.s3_presign,
+            s3_destructive: config.s3_destructive,
+            identity: config.identity,
+            paths: PathPolicy::from_config(paths),
+        }
+    }
+
+    /// `aws s3 <action>`.
+    fn s3(&self, ctx: &CommandContext, args: &ParsedArgs, action: &str) -> Option<RuleMatch> {
+        let what = format!("aws s3 {action}");
+        let operands = args.positionals.get(2..).unwrap_or_default();
+        if args.has_any(&["--dryrun"]) {
+            return Some(query(ctx, &format!("{what} --dryrun")));
+        }
+        let result = match action {
+            "ls" => query(ctx, &what),
+            "presign" => decided(self.s3_presign, &what, None),
+            "rm" => decided(self.s3_destructive, &what, None),
+            "rb" if args.has_any(&["--force"]) => {
+                decided(self.s3_destructive, "aws s3 rb --force", None)
+            }
+            "rb" => decided(self.s3_destructive, &what, None),
+            "mb" | "website" => decided(self.s3_upload, &what, None),
+            "cp" | "mv" | "sync" => {
+                let [source, destination, ..] = operands else {
+                    return None;
+                };
+                let from_s3 = source.starts_with("s3://");
+                if action == "sync" && args.has_any(&["--delete"]) {
+                    decided(self.s3_destructive, "aws s3 sync --delete", None)
+                } else if action == "mv" && from_s3 {
+                    decided(self.s3_destructive, &format!("{what} from {source}"), None)
+                } else if destination.starts_with("s3://") {
+                    decided(self.s3_upload, &format!("{what} to {destination}"), None)
+                } else if from_s3 {
+                    self.download(ctx, &format!("{what} from {source}"), destination)
+                } else {
+                    return None;
+                }
+            }
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    /// `aws s3api <operation>`.
+    fn s3api(&self, ctx: &CommandContext, args: &ParsedArgs, operation: &str) -> Option<RuleMatch> {
+        let what = format!("aws s3api {operation}");
+        if operation == "get-object" {
+            let outfile = args.positionals.get(2)?;
+            return Some(self.download(ctx, &what, outfile));
+        }
+        let result = if ["list-", "head-", "get-"]
+            .iter()
+            .any(|p| operation.starts_with(p))
+        {
+            query(ctx, &what)
+        } else if operation.starts_with("delete-") {
+            decided(self.s3_destructive, &what, None)
+        } else if operation.starts_with("put-")
+            || operation.starts_with("create-")
+            || S3API_WRITES.contains(&operation)
+        {
+            decided(self.s3_upload, &what, None)
+        } else {
+            return None;
+        };
+        Some(result)
+    }
+
+    /// `aws iam <operation>` or `aws sts <operation>`.
+    fn identity(&self, ctx: &CommandContext, service: &str, operation: &str) -> RuleMatch {
+        let what = format!("aws {service} {operation}");
+        let reads = match service {
+            "iam" => ["get-", "list-"].iter().any(|p| operation.starts_with(p)),
+            _ => STS_QUERIES.contains(&operation),
+        };
+        if reads {
+            query(ctx, &what)
+        } else {
+            decided(self.identity, &what, None)
+        }
+    }
+
+    /// A download to `destination`: `[aws] s3_download`, or stricter if
+    /// the destination is outside the sandbox.
+    fn download(&self, ctx: &CommandContext, what: &str, destination: &str) -> RuleMatch {
+        if destination != "-" {
+            let target = self.paths.classify_write(destination, &ctx.working_dir());
+            if target.decision() > self.s3_download {
+                return RuleMatch {
+                    decision: target.decision(),
+                    reason: format!("{what} writes {destination} ({target})"),
+                };
+            }
+        }
+        match &ctx.redirection {
+            Some(r) if self.s3_download == Decision::Allow => RuleMatch {
+                decision: Decision::Ask,
+                reason: format!("{what} with {r}"),
+            },
+            _ => decided(self.s3_download, what, None),
+        }
+    }
+}
+
+impl CommandSpec for AwsSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        let args = ParsedArgs::parse(ctx.args(), &AWS_FLAGS);
+        let result = match args.positionals.as_slice() {
+            [service, op, ..] if service == "s3" => self.s3(ctx, &args, op),
+            [service, op, ..] if service == "s3api" => self.s3api(ctx, &args, op),
+            [service, op, ..] if service == "iam" || service == "sts" => {
+                Some(self.identity(ctx, service, op))
+            }
+            _ => None,
+        };
+        result.unwrap_or_else(|| base.evaluate(ctx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let config = Config::default_config();
+        let spec = AwsSpec::from_config(Decision::Ask, &config.aws, &config.paths);
+        spec.evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn listings_and_downloads_allow() {
+        for cmd in [
+            "aws s3 ls",
+            "aws s3 ls s3://bucket/prefix/ --recursive",
+            "aws --profile prod s3 ls s3://bucket",
+            "aws s3 cp s3://bucket/report.csv ./report.csv",
+            "aws s3 sync s3://bucket/site ./site",
+            "aws s3 cp s3://bucket/a.json -",
+            "aws s3 rm s3://bucket/key --dryrun",
+            "aws s3api list-objects-v2 --bucket b --prefix logs/",
+            "aws s3api head-object --bucket b --key k",
+            "aws s3api get-bucket-policy --bucket b",
+            "aws s3api get-object --bucket b --key k out.bin",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(
+            eval("aws s3 cp s3://bucket/report.csv .").reason,
+            "allowed: aws s3 cp from s3://bucket/report.csv"
+        );
+        assert_eq!(eval("aws s3 ls > buckets.txt").decision, Decision::Ask);
+    }
+
+    #[test]
+    fn download_destination_is_classified() {
+        let result = eval("aws s3 cp s3://bucket/notes.txt /opt/notes.txt");
+        assert_eq!(result.decision, Decision::Ask);
+        let result = eval("aws s3 cp s3://bucket/bashrc ~/.bashrc");
+        assert_eq!(result.decision, Decision::Deny);
+        let result = eval("aws s3 cp s3://bucket/keys /etc/ssh/sshd_config");
+        assert_eq!(result.decision, Decision::Deny);
+        assert_eq!(
+            result.reason,
+            "aws s3 cp from s3://bucket/keys writes /etc/ssh/sshd_config (protected path /etc)"
+        );
+    }
+
+    #[test]
+    fn uploads_and_presign_ask() {
+        for cmd in [
+            "aws s3 cp ./dist s3://bucket/site --recursive",
+            "aws s3 sync ./site s3://bucket/site",
+            "aws s3 mv local.txt s3://bucket/local.txt",
+            "aws s3 cp s3://a/x s3://b/x",
+            "aws s3 mb s3://new-bucket",
+            "aws s3 presign s3://bucket/key --expires-in 3600",
+            "aws s3api put-object --bucket b --key k --body f",
+            "aws s3api put-bucket-policy --bucket b --policy file://p.json",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("aws s3 cp f s3://bucket/f").reason,
+            "aws s3 cp to s3://bucket/f requires confirmation"
+        );
+        assert_eq!(
+            eval("aws s3 presign s3://bucket/key").reason,
+            "aws s3 presign requires confirmation"
+        );
+    }
+
+    #[test]
+    fn deletions_deny() {
+        for cmd in [
+            "aws s3 rm s3://bucket/key",
+            "aws s3 rm s3://bucket --recursive",
+            "aws s3 rb s3://bucket --force",
+            "aws s3 rb s3://bucket",
+            "aws s3 sync ./site s3://bucket/site --delete",
+            "aws s3 mv s3://bucket/key ./key",
+            "aws s3api delete-bucket --bucket b",
+            "aws s3api delete-objects --bucket b --delete file://keys.json",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Deny, "{cmd}");
+        }
+        assert_eq!(
+            eval("aws s3 rb s3://bucket --force").reason,
+            "blocked command: aws s3 rb --force"
+        );
+    }
+
+    #[test]
+    fn identity_reads_allow() {
+        for cmd in [
+            "aws sts get-caller-identity",
+            "aws iam list-users",
+            "aws iam get-role --role-name app",
+            "aws --profile prod iam list-attached-role-policies --role-name app",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+    }
+
+    #[test]
+    fn identity_changes_deny() {
+        for cmd in [
+            "aws iam create-access-key --user-name bot",
+            "aws iam attach-role-policy --role-name app --policy-arn arn:aws:iam::aws:policy/AdministratorAccess",
+            "aws iam create-user --user-name backdoor",
+            "aws iam put-user-policy --user-name bot --policy-name p --policy-document file://p.json",
+            "aws iam update-assume-role-policy --role-name app --policy-document file://t.json",
+            "aws sts assume-role --role-arn arn:aws:iam::1:role/admin --role-session-name s",
+            "aws sts get-session-token",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Deny, "{cmd}");
+        }
+        assert_eq!(
+            eval("aws iam create-access-key").reason,
+            "blocked command: aws iam create-access-key"
+        );
+    }
+
+    #[test]
+    fn other_services_keep_the_base() {
+        assert_eq!(eval("aws ec2 describe-instances").decision, Decision::Ask);
+        let config = Config::default_config();
+        let spec = AwsSpec::from_config(Decision::Deny, &config.aws, &config.paths);
+        let ctx = CommandContext::from_command("aws s3 ls");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+
+    #[test]
+    fn decisions_are_configurable() {
+        let config = Config::default_config();
+        let aws = AwsConfig {
+            s3_download: Decision::Ask,
+            s3_presign: Decision::Allow,
+            s3_destructive: Decision::Ask,
+            identity: Decision::Ask,
+            ..config.aws.clone()
+        };
+        let spec = AwsSpec::from_config(Decision::Ask, &aws, &config.paths);
+        let eval = |cmd| spec.evaluate(&CommandContext::from_command(cmd)).decision;
+        assert_eq!(eval("aws s3 cp s3://b/k ."), Decision::Ask);
+        assert_eq!(eval("aws s3 presign s3://b/k"), Decision::Allow);
+        assert_eq!(eval("aws s3 rb s3://b --force"), Decision::Ask);
+        assert_eq!(eval("aws sts assume-role"), Decision::Ask);
+    }
+}
@@ -0,0 +1,205 @@
+//! Bazel evaluation: bazel and bazelisk.
+//!
+//! Queries, `info`, `build` and `test` are allowed (the `[bazel] read_only`
+//! list). `bazel run` executes the binary it builds, with whatever
+//! arguments follow `--`, and gets `[bazel] run`. `clean --expunge` and
+//! `mod tidy` ask, with a reason saying what they rewrite or delete.
+
+use super::super::CommandSpec;
+use super::packages::{change, decided, query};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::BazelConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// Commands that change the workspace or the output base, with the flag
+/// that makes them do it (none: always) and what they do.
+const CHANGES: &[(&str, Option<&str>, &str)] = &[
+    (
+        "clean",
+        Some("--expunge"),
+        "deletes the whole output base, external repositories included",
+    ),
+    (
+        "clean",
+        Some("--expunge_async"),
+        "deletes the whole output base, external repositories included",
+    ),
+    ("mod tidy", None, "rewrites MODULE.bazel"),
+    ("sync", None, "refetches every external repository"),
+];
+
+/// Bazel evaluator for bazel and bazelisk.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `[bazel] read_only` commands (`query`, `info`, `build`, `test`) →
+///    ALLOW (ASK with redirection)
+/// 3. `bazel run` → `[bazel] run`, naming the target and noting arguments
+///    passed to the binary
+/// 4. `clean --expunge`, `mod tidy`, `sync` → ASK
+/// 5. Everything else → the base decision
+pub struct BazelSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Commands that only read or build (`query`, `build`, `mod graph`).
+    read_only: Vec<String>,
+    /// Decision for running a built binary.
+    run: Decision,
+}
+
+impl BazelSpec {
+    /// Build a bazel spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &BazelConfig) -> Self {
+        Self {
+            base,
+            read_only: config.read_only.clone(),
+            run: config.run,
+        }
+    }
+
+    /// `bazel run`: the target, and whether arguments follow `--`.
+    fn run(&self, args: &[&str]) -> RuleMatch {
+        let (args, passed) = match args.iter().position(|a| *a == "--") {
+            Some(i) => (&args[..i], !args[i + 1..].is_empty()),
+            None => (args, false),
+        };
+        // Options may take a separate value (`-c opt`), so prefer a word
+        // that looks like a label.
+        let target = args
+            .iter()
+            .find(|a| a.starts_with("//") || a.starts_with('@') || a.contains(':'))
+            .or_else(|| args.iter().find(|a| !a.starts_with('-')));
+        let what = match target {
+            Some(target) => format!("bazel run {target}"),
+            None => "bazel run".to_string(),
+        };
+        if self.run != Decision::Ask {
+            return decided(self.run, &what, None);
+        }
+        let note = if passed {
+            "executes the built binary with arbitrary arguments"
+        } else {
+            "executes the built binary"
+        };
+        change(&what, None, Some(note))
+    }
+}
+
+impl CommandSpec for BazelSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        let words: Vec<&str> = ctx.args().iter().map(|w| w.as_str()).collect();
+        // Startup options (`--output_base=...`) come before the command.
+        let Some(i) = words.iter().position(|w| !w.starts_with('-')) else {
+            if ctx.has_any_flag(&["--version", "version"]) {
+                return query(ctx, "bazel --version");
+            }
+            return base.evaluate(ctx);
+        };
+        let (command, rest) = (words[i], &words[i + 1..]);
+        let action = rest.iter().find(|w| !w.starts_with('-'));
+        let two = action.map(|action| format!("{command} {action}"));
+        let entry = two.filter(|two| self.read_only.contains(two)).or_else(|| {
+            self.read_only
+                .contains(&command.to_string())
+                .then(|| command.to_string())
+        });
+        if let Some(entry) = entry {
+            return query(ctx, &format!("bazel {entry}"));
+        }
+        if command == "run" {
+            return self.run(rest);
+        }
+        let change_of = CHANGES.iter().find(|(name, flag, _)| {
+            let matches_name = match name.split_once(' ') {
+                Some((first, second)) => first == command && action == Some(&second),
+                None => *name == command,
+            };
+            matches_name && flag.is_none_or(|f| rest.contains(&f))
+        });
+        if let Some((name, flag, note)) = change_of {
+            let what = match flag {
+                Some(flag) => format!("bazel {name} {flag}"),
+                None => format!("bazel {name}"),
+            };
+            return change(&what, None, Some(note));
+        }
+        base.evaluate(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let spec = BazelSpec::from_config(Decision::Ask, &Config::default_config().bazel);
+        spec.evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn queries_and_builds_allow() {
+        for cmd in [
+            "bazel query 'deps(//app:main)'",
+            "bazel cquery //app:main --output=files",
+            "bazel aquery //app:main",
+            "bazel info output_base",
+            "bazel build //...",
+            "bazel --output_base=/tmp/ob test //app:all --test_output=errors",
+            "bazelisk build //app:main",
+            "bazel mod graph",
+            "bazel version",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(eval("bazel build //...").reason, "read-only bazel build");
+        assert_eq!(eval("bazel query //... > out.txt").decision, Decision::Ask);
+    }
+
+    #[test]
+    fn run_and_changes_ask() {
+        assert_eq!(
+            eval("bazel run //tools:deploy -- --env=prod").reason,
+            "bazel run //tools:deploy requires confirmation \
+             (executes the built binary with arbitrary arguments)"
+        );
+        assert_eq!(
+            eval("bazel run -c opt //app:main").reason,
+            "bazel run //app:main requires confirmation (executes the built binary)"
+        );
+        assert_eq!(
+            eval("bazel clean --expunge").reason,
+            "bazel clean --expunge requires confirmation \
+             (deletes the whole output base, external repositories included)"
+        );
+        assert_eq!(
+            eval("bazel mod tidy").reason,
+            "bazel mod tidy requires confirmation (rewrites MODULE.bazel)"
+        );
+        for cmd in ["bazel clean", "bazel shutdown", "bazel coverage //..."] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+    }
+
+    #[test]
+    fn decisions_are_configurable() {
+        let config = BazelConfig {
+            read_only: vec!["query".into()],
+            run: Decision::Deny,
+        };
+        let spec = BazelSpec::from_config(Decision::Ask, &config);
+        let eval = |cmd| spec.evaluate(&CommandContext::from_command(cmd));
+        assert_eq!(eval("bazel build //...").decision, Decision::Ask);
+        assert_eq!(
+            eval("bazel run //app").reason,
+            "blocked command: bazel run //app"
+        );
+        let spec = BazelSpec::from_config(Decision::Deny, &config);
+        let ctx = CommandContext::from_command("bazel query //...");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+}
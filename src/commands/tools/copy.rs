@@ -0,0 +1,184 @@
+//! Destination-aware cp, mv, and install evaluation.
+//!
+//! The base disposition comes from the flat `[commands]` lists (cp and mv
+//! are in `ask`). The destination — the last operand, or `-t DIR` — is
+//! classified against the `[paths]` rules, so copies into protected system
+//! prefixes (`/usr`, `/etc`, `/bin`, `/boot`, ...) or sensitive paths deny.
+//! mv also removes its sources, so they are classified the same way.
+
+use super::super::CommandSpec;
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::PathsConfig;
+use crate::eval::paths::PathPolicy;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// cp / mv / install flags that consume a value (GNU coreutils 9.x).
+const COPY_FLAGS: FlagSchema = FlagSchema {
+    short_value: "tSmog",
+    long_value: &[
+        "--target-directory",
+        "--suffix",
+        "--mode",
+        "--owner",
+        "--group",
+        "--strip-program",
+    ],
+};
+
+/// cp / mv / install evaluator.
+///
+/// Evaluation order:
+/// 1. Base decision from the flat `[commands]` lists
+/// 2. The destination (`-t DIR`, the last operand, or every operand of
+///    `install -d`) and, for mv, each source escalate to their path
+///    classification (protected/sensitive → DENY, outside the sandbox → ASK);
+///    a path built by a command substitution → ASK
+pub struct CopySpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Destination path classification.
+    paths: PathPolicy,
+}
+
+impl CopySpec {
+    /// Build a copy spec from its flat-list decision and path configuration.
+    pub fn from_config(base: Decision, paths: &PathsConfig) -> Self {
+        Self {
+            base,
+            paths: PathPolicy::from_config(paths),
+        }
+    }
+
+    /// Paths this invocation writes to or removes, with the verb for reasons.
+    fn touched_paths<'a>(name: &str, args: &'a ParsedArgs) -> Vec<(&'static str, &'a str)> {
+        let operands: Vec<&str> = args.positionals.iter().map(String::as_str).collect();
+        if name == "install" && args.has_any(&["-d", "--directory"]) {
+            return operands.into_iter().map(|d| ("creates", d)).collect();
+        }
+        let (dest, sources) = match args.values(&["-t", "--target-directory"]).last() {
+            Some(dir) => (Some(dir), operands.as_slice()),
+            None => match operands.split_last() {
+                Some((dest, sources)) if !sources.is_empty() => (Some(*dest), sources),
+                _ => (None, &[][..]),
+            },
+        };
+        let mut touched: Vec<(&str, &str)> = dest.map(|d| ("writes", d)).into_iter().collect();
+        if name == "mv" {
+            touched.extend(sources.iter().map(|s| ("removes", *s)));
+        }
+        touched
+    }
+}
+
+impl CommandSpec for CopySpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let mut result = SimpleCommandSpec::new(self.base).evaluate(ctx);
+        if result.decision == Decision::Deny {
+            return result;
+        }
+
+        let name = ctx.base_command.as_str();
+        let args = ParsedArgs::parse(ctx.args(), &COPY_FLAGS);
+        for (verb, path) in Self::touched_paths(name, &args) {
+            if ctx.is_substituted(path) {
+                if result.decision < Decision::Ask {
+                    result = RuleMatch {
+                        decision: Decision::Ask,
+                        reason: format!("{name} {verb} {path} (path known only at run time)"),
+                    };
+                }
+                continue;
+            }
+            let target = self.paths.classify_write(path, &ctx.working_dir());
+            if target.decision() > result.decision {
+                result = RuleMatch {
+                    decision: target.decision(),
+                    reason: format!("{name} {verb} {path} ({target})"),
+                };
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn spec(base: Decision) -> CopySpec {
+        CopySpec::from_config(base, &Config::default_config().paths)
+    }
+
+    fn eval(cmd: &str) -> Decision {
+        let ctx = CommandContext::from_command(cmd);
+        spec(Decision::Ask).evaluate(&ctx).decision
+    }
+
+    fn reason(cmd: &str) -> String {
+        let ctx = CommandContext::from_command(cmd);
+        spec(Decision::Ask).evaluate(&ctx).reason
+    }
+
+    #[test]
+    fn ask_ordinary_copy() {
+        assert_eq!(eval("cp src.txt dst.txt"), Decision::Ask);
+        assert_eq!(eval("mv old.txt new.txt"), Decision::Ask);
+        assert_eq!(reason("cp a b"), "cp requires confirmation");
+    }
+
+    #[test]
+    fn deny_copy_into_system_dir() {
+        assert_eq!(eval("cp tool /usr/local/bin/"), Decision::Deny);
+        assert_eq!(eval("mv x.conf /etc/nginx/conf.d/"), Decision::Deny);
+        assert_eq!(eval("cp vmlinuz /boot/vmlinuz"), Decision::Deny);
+        assert!(reason("cp tool /usr/local/bin/tool").contains("cp writes /usr/local/bin/tool"));
+        assert!(reason("cp tool /usr/local/bin/tool").contains("protected path /usr"));
+    }
+
+    #[test]
+    fn deny_target_directory_flag() {
+        assert_eq!(eval("cp -t /bin a b"), Decision::Deny);
+        assert_eq!(eval("mv --target-directory=/sbin a"), Decision::Deny);
+    }
+
+    #[test]
+    fn deny_install_into_system_dir() {
+        assert_eq!(
+            eval("install -m 0755 -o root tool /usr/local/bin/tool"),
+            Decision::Deny
+        );
+        assert_eq!(eval("install -d /etc/myapp"), Decision::Deny);
+        assert_eq!(eval("install -m 644 a.txt build/a.txt"), Decision::Ask);
+    }
+
+    #[test]
+    fn value_flags_not_mistaken_for_operands() {
+        // -g consumes "wheel"; "tool" is the source, not the destination.
+        assert_eq!(eval("install -g wheel tool out/tool"), Decision::Ask);
+        assert_eq!(eval("cp -S .bak a /etc/a"), Decision::Deny);
+    }
+
+    #[test]
+    fn deny_mv_out_of_protected_or_sensitive() {
+        assert_eq!(eval("mv /etc/hosts /tmp/hosts"), Decision::Deny);
+        assert!(reason("mv ~/.ssh/id_ed25519 /tmp/k").contains("mv removes"));
+    }
+
+    #[test]
+    fn cp_source_is_only_read() {
+        assert_eq!(eval("cp /etc/hosts /tmp/hosts"), Decision::Ask);
+    }
+
+    #[test]
+    fn allowed_cp_escalates_outside_sandbox() {
+        let s = spec(Decision::Allow);
+        let ctx = CommandContext::from_command("cp a /tmp/b");
+        assert_eq!(s.evaluate(&ctx).decision, Decision::Allow);
+        let ctx = CommandContext::from_command("cp a /srv/b");
+        assert_eq!(s.evaluate(&ctx).decision, Decision::Ask);
+        let ctx = CommandContext::from_command("cp a $(echo /tmp/b)");
+        assert_eq!(s.evaluate(&ctx).decision, Decision::Ask);
+    }
+}
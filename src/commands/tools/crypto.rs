@@ -0,0 +1,369 @@
+//! Key tool evaluation: gpg and openssl.
+//!
+//! Verification and inspection (`gpg --verify`, `gpg --list-keys`, `openssl
+//! x509 -text`, `openssl s_client`) only read, and are allowed. Operations
+//! on key material (generating, importing, deleting or signing keys,
+//! `openssl enc`) get `[crypto] key_material`. Printing a secret key
+//! (`gpg --export-secret-keys`, `openssl pkcs12 -nodes`) gets
+//! `secret_export`.
+
+use super::super::CommandSpec;
+use super::packages::{decided, query};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::CryptoConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// gpg commands that only read.
+const GPG_QUERIES: &[&str] = &[
+    "--verify",
+    "--verify-files",
+    "--list-keys",
+    "-k",
+    "--list-public-keys",
+    "--list-secret-keys",
+    "-K",
+    "--list-signatures",
+    "--list-sigs",
+    "--check-signatures",
+    "--check-sigs",
+    "--fingerprint",
+    "--list-packets",
+    "--show-keys",
+    "--list-config",
+    "--card-status",
+    "--print-md",
+    "--version",
+    "--help",
+];
+
+/// gpg commands that create, change, import or remove keys.
+const GPG_KEY_MATERIAL: &[&str] = &[
+    "--gen-key",
+    "--generate-key",
+    "--full-gen-key",
+    "--full-generate-key",
+    "--quick-gen-key",
+    "--quick-generate-key",
+    "--quick-add-key",
+    "--quick-add-uid",
+    "--quick-revoke-uid",
+    "--quick-set-expire",
+    "--edit-key",
+    "--sign-key",
+    "--lsign-key",
+    "--quick-sign-key",
+    "--quick-lsign-key",
+    "--passwd",
+    "--change-passphrase",
+    "--gen-revoke",
+    "--generate-revocation",
+    "--delete-keys",
+    "--delete-key",
+    "--delete-secret-keys",
+    "--delete-secret-key",
+    "--delete-secret-and-public-keys",
+    "--delete-secret-and-public-key",
+    "--import",
+    "--recv-keys",
+    "--receive-keys",
+    "--refresh-keys",
+    "--fetch-keys",
+    "--import-ownertrust",
+];
+
+/// gpg commands that print secret keys, each with its shortest
+/// abbreviation: gpg takes any unambiguous prefix of a long option.
+const GPG_SECRET_EXPORTS: &[(&str, &str)] = &[
+    ("--export-secret-keys", "--export-secret-k"),
+    ("--export-secret-subkeys", "--export-secret-s"),
+];
+
+/// openssl commands that only read.
+const OPENSSL_QUERIES: &[&str] = &[
+    "version",
+    "help",
+    "list",
+    "ciphers",
+    "errstr",
+    "s_client",
+    "verify",
+    "x509",
+    "req",
+    "crl",
+    "asn1parse",
+    "dgst",
+    "sess_id",
+    "ocsp",
+    "pkcs7",
+];
+
+/// Flags that make an otherwise read-only openssl command create or sign
+/// with a key (`x509 -req`, `req -new`, `dgst -sign`).
+const OPENSSL_SIGNING: &[&str] = &[
+    "-req", "-signkey", "-CA", "-CAkey", "-key", "-new", "-newkey", "-x509", "-sign",
+];
+
+/// openssl commands that generate, convert or use key material.
+const OPENSSL_KEY_MATERIAL: &[&str] = &[
+    "genrsa", "gendsa", "genpkey", "ecparam", "dhparam", "rsa", "dsa", "ec", "pkey", "pkcs8",
+    "pkcs12", "enc", "pkeyutl", "rsautl", "ca", "cms", "smime", "kdf",
+];
+
+/// The first of `commands` among `args`.
+fn find<'a>(commands: &[&'a str], args: &[&str]) -> Option<&'a str> {
+    args.iter()
+        .find_map(|arg| commands.iter().find(|command| *command == arg))
+        .copied()
+}
+
+/// The secret export among `args`, by its full name, abbreviated or not.
+fn find_secret_export(args: &[&str]) -> Option<&'static str> {
+    args.iter().find_map(|arg| {
+        GPG_SECRET_EXPORTS
+            .iter()
+            .find(|(name, shortest)| arg.starts_with(shortest) && name.starts_with(arg))
+            .map(|(name, _)| *name)
+    })
+}
+
+/// gpg evaluator for gpg and gpg2.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `--export-secret-keys`/`--export-secret-subkeys`, or an
+///    abbreviation of either → `[crypto] secret_export`
+/// 3. Key generation, import, deletion, signing, `--edit-key` →
+///    `[crypto] key_material`
+/// 4. `--verify`, `--list-keys`, `--fingerprint` → ALLOW (ASK with
+///    redirection)
+/// 5. Everything else (`--encrypt`, `--decrypt`, `--export`) → the base
+///    decision
+pub struct GpgSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Decision for operations on key material.
+    key_material: Decision,
+    /// Decision for printing a secret key.
+    secret_export: Decision,
+}
+
+impl GpgSpec {
+    /// Build a gpg spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &CryptoConfig) -> Self {
+        Self {
+            base,
+            key_material: config.key_material,
+            secret_export: config.secret_export,
+        }
+    }
+}
+
+impl CommandSpec for GpgSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        let args: Vec<&str> = ctx.args().iter().map(|w| w.as_str()).collect();
+        let tool = &ctx.base_command;
+        if let Some(command) = find_secret_export(&args) {
+            return decided(self.secret_export, &format!("{tool} {command}"), None);
+        }
+        if let Some(command) = find(GPG_KEY_MATERIAL, &args) {
+            return decided(self.key_material, &format!("{tool} {command}"), None);
+        }
+        if let Some(command) = find(GPG_QUERIES, &args) {
+            return query(ctx, &format!("{tool} {command}"));
+        }
+        base.evaluate(ctx)
+    }
+}
+
+/// openssl evaluator.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `pkcs12 -nodes`/`-noenc` (private keys printed unencrypted) →
+///    `[crypto] secret_export`
+/// 3. Key generation and use (`genrsa`, `genpkey`, `enc`, `pkeyutl`),
+///    signing (`x509 -req`, `req -new`, `dgst -sign`) →
+///    `[crypto] key_material`
+/// 4. Inspection (`x509 -text`, `s_client`, `verify`, `dgst`) → ALLOW (ASK
+///    with redirection)
+/// 5. Everything else → the base decision
+pub struct OpensslSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Decision for operations on key material.
+    key_material: Decision,
+    /// Decision for printing a secret key.
+    secret_export: Decision,
+}
+
+impl OpensslSpec {
+    /// Build an openssl spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &CryptoConfig) -> Self {
+        Self {
+            base,
+            key_material: config.key_material,
+            secret_export: config.secret_export,
+        }
+    }
+}
+
+impl CommandSpec for OpensslSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        let args: Vec<&str> = ctx.args().iter().map(|w| w.as_str()).collect();
+        let Some((&command, flags)) = args.split_first() else {
+            return base.evaluate(ctx);
+        };
+        if command == "pkcs12"
+            && let Some(flag) = find(&["-nodes", "-noenc"], flags)
+            && !flags.contains(&"-nokeys")
+        {
+            return decided(self.secret_export, &format!("openssl pkcs12 {flag}"), None);
+        }
+        if OPENSSL_KEY_MATERIAL.contains(&command) {
+            return decided(self.key_material, &format!("openssl {command}"), None);
+        }
+        if OPENSSL_QUERIES.contains(&command) {
+            return match find(OPENSSL_SIGNING, flags) {
+                Some(flag) => decided(
+                    self.key_material,
+                    &format!("openssl {command} {flag}"),
+                    None,
+                ),
+                None => query(ctx, &format!("openssl {command}")),
+            };
+        }
+        base.evaluate(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let config = Config::default_config().crypto;
+        let ctx = CommandContext::from_command(cmd);
+        if ctx.base_command == "openssl" {
+            OpensslSpec::from_config(Decision::Ask, &config).evaluate(&ctx)
+        } else {
+            GpgSpec::from_config(Decision::Ask, &config).evaluate(&ctx)
+        }
+    }
+
+    #[test]
+    fn inspection_allows() {
+        for cmd in [
+            "gpg --verify release.tar.gz.sig release.tar.gz",
+            "gpg --list-keys",
+            "gpg -K",
+            "gpg2 --fingerprint alice@example.com",
+            "gpg --show-keys key.asc",
+            "openssl x509 -in cert.pem -text -noout",
+            "openssl s_client -connect example.com:443 -servername example.com",
+            "openssl verify -CAfile ca.pem cert.pem",
+            "openssl req -in req.csr -noout -text",
+            "openssl dgst -sha256 file.tar",
+            "openssl version",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(eval("gpg --list-keys").reason, "read-only gpg --list-keys");
+        assert_eq!(
+            eval("openssl x509 -in cert.pem -text").reason,
+            "read-only openssl x509"
+        );
+        assert_eq!(eval("gpg --list-keys > keys.txt").decision, Decision::Ask);
+    }
+
+    #[test]
+    fn key_material_asks() {
+        for cmd in [
+            "gpg --gen-key",
+            "gpg --batch --full-generate-key params",
+            "gpg --delete-secret-keys ABCD1234",
+            "gpg --import key.asc",
+            "gpg --recv-keys ABCD1234",
+            "openssl genrsa -out key.pem 4096",
+            "openssl genpkey -algorithm ed25519 -out key.pem",
+            "openssl enc -aes-256-cbc -in a -out a.enc",
+            "openssl req -new -key key.pem -out req.csr",
+            "openssl x509 -req -in req.csr -CA ca.pem -CAkey ca.key",
+            "openssl dgst -sha256 -sign key.pem file",
+            "openssl pkcs12 -export -in cert.pem -inkey key.pem -out bundle.p12",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("gpg --delete-secret-keys ABCD1234").reason,
+            "gpg --delete-secret-keys requires confirmation"
+        );
+        assert_eq!(
+            eval("openssl req -new -key key.pem").reason,
+            "openssl req -new requires confirmation"
+        );
+    }
+
+    #[test]
+    fn secret_export_denies() {
+        for cmd in [
+            "gpg --armor --export-secret-keys alice@example.com",
+            "gpg --export-secret-subkeys ABCD1234",
+            "openssl pkcs12 -in bundle.p12 -nodes",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Deny, "{cmd}");
+        }
+        assert_eq!(
+            eval("gpg -a --export-secret-keys").reason,
+            "blocked command: gpg --export-secret-keys"
+        );
+        // gpg takes unambiguous abbreviations of long options.
+        for cmd in [
+            "gpg --export-secret-k KEY",
+            "gpg --export-secret-key KEY",
+            "gpg --export-secret-s KEY",
+            "gpg --export-secret-sub KEY",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Deny, "{cmd}");
+        }
+        assert_eq!(
+            eval("gpg --export-secret-sub KEY").reason,
+            "blocked command: gpg --export-secret-subkeys"
+        );
+        // Too short to tell the two apart, or not an export at all.
+        assert_eq!(eval("gpg --export-secret KEY").decision, Decision::Ask);
+        assert_eq!(
+            eval("gpg --export-secret-keyring KEY").decision,
+            Decision::Ask
+        );
+        // Only the certificates.
+        assert_eq!(
+            eval("openssl pkcs12 -in bundle.p12 -nodes -nokeys").decision,
+            Decision::Ask
+        );
+    }
+
+    #[test]
+    fn decisions_are_configurable() {
+        let config = CryptoConfig {
+            key_material: Decision::Allow,
+            secret_export: Decision::Ask,
+        };
+        let spec = GpgSpec::from_config(Decision::Ask, &config);
+        let eval = |cmd| spec.evaluate(&CommandContext::from_command(cmd)).decision;
+        assert_eq!(eval("gpg --gen-key"), Decision::Allow);
+        assert_eq!(eval("gpg --export-secret-keys"), Decision::Ask);
+        assert_eq!(eval("gpg --decrypt a.gpg"), Decision::Ask);
+        let spec = OpensslSpec::from_config(Decision::Deny, &config);
+        let ctx = CommandContext::from_command("openssl version");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+}
@@ -0,0 +1,373 @@
+//! Container CLI evaluation: docker, podman and nerdctl subcommands other
+//! than `run`, `create` and `exec`, which are evaluated with the command
+//! they run.
+//!
+//! Queries (`ps`, `images`, `logs`, `inspect`) are allowed. The rest is
+//! split into image operations (`build`, `pull`, `tag`, `push`), container
+//! lifecycle (`start`, `stop`, `rm`, `kill`) and destructive operations
+//! that reach past one container (`system prune`, `volume rm`), each with
+//! its own `[docker]` decision.
+
+use super::super::CommandSpec;
+use super::packages::{decided, query};
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::DockerConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// Flags that consume a value: the global ones, and `compose -f`/`-p`,
+/// which come before the compose subcommand.
+const DOCKER_FLAGS: FlagSchema = FlagSchema {
+    short_value: "Hcflp",
+    long_value: &[
+        "--host",
+        "--context",
+        "--config",
+        "--log-level",
+        "--tlscacert",
+        "--tlscert",
+        "--tlskey",
+        "--root",
+        "--runroot",
+        "--storage-driver",
+        "--url",
+        "--connection",
+        "--namespace",
+        "--file",
+        "--project-name",
+        "--project-directory",
+        "--profile",
+        "--env-file",
+    ],
+};
+
+/// Subcommands that only read.
+const QUERIES: &[&str] = &[
+    "ps",
+    "images",
+    "inspect",
+    "logs",
+    "top",
+    "port",
+    "diff",
+    "history",
+    "stats",
+    "events",
+    "version",
+    "info",
+    "search",
+    "container ls",
+    "container list",
+    "container ps",
+    "container inspect",
+    "container logs",
+    "container top",
+    "container port",
+    "container diff",
+    "container stats",
+    "image ls",
+    "image list",
+    "image inspect",
+    "image history",
+    "volume ls",
+    "volume list",
+    "volume inspect",
+    "network ls",
+    "network list",
+    "network inspect",
+    "system df",
+    "system info",
+    "system events",
+    "context ls",
+    "context list",
+    "context show",
+    "context inspect",
+    "compose ps",
+    "compose ls",
+    "compose logs",
+    "compose config",
+    "compose images",
+    "compose top",
+    "compose port",
+    "compose version",
+    "buildx ls",
+    "buildx inspect",
+    "buildx version",
+    "builder ls",
+    "builder inspect",
+    "manifest inspect",
+    "pod ps",
+    "pod ls",
+    "pod inspect",
+    "pod logs",
+    "pod top",
+];
+
+/// Subcommands that build, fetch, name, publish or remove images.
+const IMAGE_OPERATIONS: &[&str] = &[
+    "build",
+    "pull",
+    "push",
+    "tag",
+    "commit",
+    "load",
+    "import",
+    "save",
+    "rmi",
+    "image build",
+    "image pull",
+    "image push",
+    "image tag",
+    "image load",
+    "image import",
+    "image save",
+    "image rm",
+    "image remove",
+    "container commit",
+    "container export",
+    "buildx build",
+    "buildx bake",
+    "builder build",
+    "compose build",
+    "compose pull",
+    "compose push",
+    "manifest create",
+    "manifest push",
+];
+
+/// Subcommands that start, stop, change or remove containers.
+const LIFECYCLE: &[&str] = &[
+    "start",
+    "stop",
+    "restart",
+    "kill",
+    "rm",
+    "pause",
+    "unpause",
+    "rename",
+    "update",
+    "attach",
+    "cp",
+    "container start",
+    "container stop",
+    "container restart",
+    "container kill",
+    "container rm",
+    "container remove",
+    "container pause",
+    "container unpause",
+    "container rename",
+    "container update",
+    "container attach",
+    "container cp",
+    "compose up",
+    "compose down",
+    "compose start",
+    "compose stop",
+    "compose restart",
+    "compose rm",
+    "compose kill",
+    "compose pause",
+    "compose unpause",
+    "compose create",
+    "pod start",
+    "pod stop",
+    "pod restart",
+    "pod kill",
+    "pod rm",
+];
+
+/// Subcommands that remove data or everything unused, not one container.
+const DESTRUCTIVE: &[&str] = &[
+    "system prune",
+    "system reset",
+    "image prune",
+    "container prune",
+    "volume prune",
+    "volume rm",
+    "volume remove",
+    "network prune",
+    "builder prune",
+    "buildx prune",
+    "pod prune",
+];
+
+/// The entry of `list` the subcommand words match: two words (`image ls`)
+/// before one.
+fn listed<'a>(list: &[&'a str], words: &[String]) -> Option<&'a str> {
+    let one = words.first()?;
+    let two = words.get(1).map(|second| format!("{one} {second}"));
+    list.iter()
+        .find(|entry| two.as_deref() == Some(**entry))
+        .or_else(|| list.iter().find(|entry| *entry == one))
+        .copied()
+}
+
+/// Container CLI evaluator for docker, podman and nerdctl.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. Queries (`ps`, `logs`, `image ls`, `compose ps`) → ALLOW (ASK with
+///    redirection)
+/// 3. Destructive operations (`system prune`, `volume rm`, `compose down
+///    -v`) → `[docker] destructive`
+/// 4. Image operations (`build`, `pull`, `tag`, `push`) → `[docker] image`
+/// 5. Container lifecycle (`start`, `stop`, `rm`, `kill`) →
+///    `[docker] lifecycle`
+/// 6. Everything else → the base decision
+pub struct DockerSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Decision for an image operation.
+    image: Decision,
+    /// Decision for a container lifecycle change.
+    lifecycle: Decision,
+    /// Decision for a destructive operation.
+    destructive: Decision,
+}
+
+impl DockerSpec {
+    /// Build a container CLI spec from its flat-list decision and
+    /// configuration.
+    pub fn from_config(base: Decision, config: &DockerConfig) -> Self {
+        Self {
+            base,
+            image: config.image,
+            lifecycle: config.lifecycle,
+            destructive: config.destructive,
+        }
+    }
+}
+
+impl CommandSpec for DockerSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        let args = ParsedArgs::parse(ctx.args(), &DOCKER_FLAGS);
+        let words = &args.positionals;
+        let tool = &ctx.base_command;
+        if let Some(entry) = listed(QUERIES, words) {
+            return query(ctx, &format!("{tool} {entry}"));
+        }
+        // `compose down -v` removes the project's volumes too.
+        let destructive = listed(DESTRUCTIVE, words).or_else(|| {
+            (listed(LIFECYCLE, words) == Some("compose down") && args.has_any(&["-v", "--volumes"]))
+                .then_some("compose down -v")
+        });
+        if let Some(entry) = destructive {
+            return decided(self.destructive, &format!("{tool} {entry}"), None);
+        }
+        if let Some(entry) = listed(IMAGE_OPERATIONS, words) {
+            return decided(self.image, &format!("{tool} {entry}"), None);
+        }
+        if let Some(entry) = listed(LIFECYCLE, words) {
+            return decided(self.lifecycle, &format!("{tool} {entry}"), None);
+        }
+        base.evaluate(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let spec = DockerSpec::from_config(Decision::Ask, &Config::default_config().docker);
+        spec.evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn queries_allow() {
+        for cmd in [
+            "docker ps -a",
+            "docker images",
+            "docker logs -f --tail 100 web",
+            "docker -H tcp://host:2375 inspect web",
+            "docker container ls",
+            "docker image ls",
+            "docker volume inspect data",
+            "docker compose -f dev.yml ps",
+            "podman pod ps",
+            "nerdctl --namespace k8s.io images",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(eval("docker image ls").reason, "read-only docker image ls");
+        assert_eq!(eval("docker ps > ps.txt").decision, Decision::Ask);
+    }
+
+    #[test]
+    fn image_and_lifecycle_ask() {
+        for cmd in [
+            "docker build -t app .",
+            "docker pull alpine",
+            "docker tag app registry/app:1",
+            "docker push registry/app:1",
+            "docker image rm alpine",
+            "docker buildx build --push .",
+            "docker start web",
+            "docker stop web",
+            "docker rm -f web",
+            "docker kill web",
+            "docker container restart web",
+            "docker compose up -d",
+            "docker compose down",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("docker push registry/app:1").reason,
+            "docker push requires confirmation"
+        );
+        assert_eq!(
+            eval("podman container stop web").reason,
+            "podman container stop requires confirmation"
+        );
+    }
+
+    #[test]
+    fn destructive_denies() {
+        for cmd in [
+            "docker system prune -a",
+            "docker system prune --all --volumes -f",
+            "docker volume rm data",
+            "docker volume prune",
+            "docker image prune -a",
+            "docker compose down -v",
+            "podman system reset",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Deny, "{cmd}");
+        }
+        assert_eq!(
+            eval("docker system prune -a").reason,
+            "blocked command: docker system prune"
+        );
+        assert_eq!(
+            eval("docker compose -p app down --volumes").reason,
+            "blocked command: docker compose down -v"
+        );
+    }
+
+    #[test]
+    fn decisions_are_configurable() {
+        let config = DockerConfig {
+            image: Decision::Allow,
+            lifecycle: Decision::Allow,
+            destructive: Decision::Ask,
+            ..Config::default_config().docker
+        };
+        let spec = DockerSpec::from_config(Decision::Ask, &config);
+        let eval = |cmd| spec.evaluate(&CommandContext::from_command(cmd));
+        assert_eq!(eval("docker pull alpine").decision, Decision::Allow);
+        assert_eq!(eval("docker pull alpine").reason, "allowed: docker pull");
+        assert_eq!(eval("docker stop web").decision, Decision::Allow);
+        assert_eq!(eval("docker system prune -a").decision, Decision::Ask);
+        assert_eq!(eval("docker login").decision, Decision::Ask);
+        let spec = DockerSpec::from_config(Decision::Deny, &config);
+        let ctx = CommandContext::from_command("docker ps");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+}
@@ -3,20 +3,124 @@
 //! gh uses two-word subcommands (`pr list`, `issue create`), so both the
 //! two-word form and one-word fallback are checked against the config lists.
 //! Supports env-gated auto-allow and redirection escalation.
+//!
+//! `gh api` requests are checked against `[network]` domain lists: the target
+//! host (`--hostname`, `GH_HOST`, or a full URL endpoint; default
+//! `github.com`) must be allowed, and denied hosts are blocked.
+//!
+//! A first word that isn't one of gh's own commands runs an extension (or
+//! an alias), a third-party binary: `gh dash` runs `gh-dash`, as does `gh
+//! extension exec dash`. Extensions in `[gh] extensions` are allowed; the
+//! rest get `[gh] extension`.
+//!
+//! `gh codespace ssh -- <command>` runs a command in the codespace; the
+//! registry evaluates it like a top-level command (see
+//! [`ssh_command`](crate::commands::tools::gh::ssh_command)). `cs` is read
+//! as `codespace`.
+//!
+//! `hub`, `tea` (Gitea) and `fj` (Forgejo) are evaluated the same way, each
+//! with its own `[hub]`/`[tea]`/`[fj]` lists; the `gh api` host checks and
+//! extensions are gh's alone.
 
 use super::super::CommandSpec;
-use crate::config::GhConfig;
+use super::packages::{decided, query};
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::config::{GhConfig, NetworkConfig};
+use crate::eval::network::{DomainPolicy, url_host};
 use crate::eval::{CommandContext, Decision, RuleMatch};
 use std::collections::HashMap;
 
+/// `gh api` flags that consume a value.
+const GH_API_FLAGS: FlagSchema = FlagSchema {
+    short_value: "XHfFqtp",
+    long_value: &[
+        "--method",
+        "--header",
+        "--raw-field",
+        "--field",
+        "--hostname",
+        "--jq",
+        "--template",
+        "--input",
+        "--preview",
+        "--cache",
+    ],
+};
+
+/// gh's own top-level commands. Any other first word names an extension
+/// or an alias.
+const GH_COMMANDS: &[&str] = &[
+    "agent-task",
+    "alias",
+    "api",
+    "attestation",
+    "auth",
+    "browse",
+    "cache",
+    "co",
+    "codespace",
+    "completion",
+    "config",
+    "copilot",
+    "cs",
+    "extension",
+    "extensions",
+    "ext",
+    "gist",
+    "gpg-key",
+    "help",
+    "issue",
+    "label",
+    "org",
+    "pr",
+    "preview",
+    "project",
+    "release",
+    "repo",
+    "ruleset",
+    "run",
+    "search",
+    "secret",
+    "ssh-key",
+    "status",
+    "variable",
+    "version",
+    "workflow",
+];
+
+/// The command `gh codespace ssh` runs, if `ctx` gives one: the words after
+/// `--`, past any ssh flags. A single word is a shell command line.
+pub fn ssh_command(ctx: &CommandContext) -> Option<String> {
+    if ctx.base_command != "gh" {
+        return None;
+    }
+    let gh_pos = ctx.words.iter().position(|w| w == "gh")?;
+    let rest = &ctx.words[gh_pos + 1..];
+    if !matches!(rest.first()?.as_str(), "codespace" | "cs") || rest.get(1)? != "ssh" {
+        return None;
+    }
+    let dashes = rest.iter().position(|w| w == "--")?;
+    let after = &rest[dashes + 1..];
+    let start = after.iter().position(|w| !w.as_str().starts_with('-'))?;
+    match &after[start..] {
+        [line] => Some(line.as_str().to_string()),
+        words => Some(CommandContext::join_words(words.iter().map(|w| w.as_str()))),
+    }
+}
+
 /// Subcommand-aware gh CLI evaluator.
 ///
 /// Evaluation order:
+/// 0. `gh api` to a denied host → DENY; to a host not in `allowed_domains` → ASK
+/// 0. Extensions (`gh <ext>`, `gh extension exec <ext>`) → ALLOW if listed
+///    in `extensions` (with redirection escalation), else `extension`
 /// 1. Read-only subcommands → ALLOW (with redirection escalation)
 /// 2. Env-gated subcommands → ALLOW if all `config_env` entries match, else ASK
 /// 3. Known mutating subcommands → ASK
 /// 4. Everything else → ASK
 pub struct GhSpec {
+    /// The CLI this spec evaluates (`gh`, `hub`, `tea` or `fj`).
+    tool: String,
     /// Read-only subcommands (e.g. `pr list`, `pr view`, `status`).
     read_only: Vec<String>,
     /// Known mutating subcommands (e.g. `pr create`, `repo delete`).
@@ -25,24 +129,75 @@ pub struct GhSpec {
     allowed_with_config: Vec<String>,
     /// Required env var name→value pairs that gate `allowed_with_config` subcommands.
     config_env: HashMap<String, String>,
+    /// Destination classification for `gh api`.
+    domains: DomainPolicy,
+    /// Extensions allowed to run (`dash` for `gh dash`).
+    extensions: Vec<String>,
+    /// Decision for running any other extension.
+    extension: Decision,
 }
 
 impl GhSpec {
     /// Build a gh spec from configuration.
-    pub fn from_config(config: &GhConfig) -> Self {
+    pub fn from_config(config: &GhConfig, network: &NetworkConfig) -> Self {
+        Self::for_tool("gh", config, network)
+    }
+
+    /// Build a spec for a gh-style CLI (`hub`, `tea`, `fj`) from its own
+    /// section of the same shape.
+    pub fn for_tool(tool: &str, config: &GhConfig, network: &NetworkConfig) -> Self {
         Self {
+            tool: tool.to_string(),
             read_only: config.read_only.clone(),
             mutating: config.mutating.clone(),
             allowed_with_config: config.allowed_with_config.clone(),
             config_env: config.config_env.clone(),
+            domains: DomainPolicy::from_config(network),
+            extensions: config.extensions.clone(),
+            extension: config.extension,
+        }
+    }
+
+    /// The extension `ctx` runs, if it runs one: `gh extension exec <ext>`,
+    /// or a first word that isn't one of gh's own commands.
+    fn extension_name(ctx: &CommandContext) -> Option<&str> {
+        let gh_pos = ctx.words.iter().position(|w| w == "gh")?;
+        let first = ctx.words.get(gh_pos + 1)?.as_str();
+        if matches!(first, "extension" | "extensions" | "ext") {
+            return match ctx.words.get(gh_pos + 2).map(|w| w.as_str()) {
+                Some("exec") => ctx.words.get(gh_pos + 3).map(|w| w.as_str()),
+                _ => None,
+            };
         }
+        (!first.starts_with('-') && !GH_COMMANDS.contains(&first)).then_some(first)
+    }
+
+    /// The host a `gh api` invocation talks to.
+    ///
+    /// A full-URL endpoint wins, then `--hostname`, then an inline or
+    /// previously exported `GH_HOST`, then the default `github.com`.
+    fn api_host(ctx: &CommandContext) -> String {
+        let args = ParsedArgs::parse(ctx.args(), &GH_API_FLAGS);
+        let endpoint = args.positionals.get(1).map(String::as_str).unwrap_or("");
+        if endpoint.starts_with("https://") || endpoint.starts_with("http://") {
+            return url_host(endpoint).unwrap_or(endpoint).to_string();
+        }
+        if let Some(host) = args.values(&["--hostname"]).last() {
+            return host.to_string();
+        }
+        ctx.env_vars
+            .iter()
+            .find(|(k, _)| k == "GH_HOST")
+            .map(|(_, v)| v.clone())
+            .or_else(|| ctx.accumulated_env.get("GH_HOST").cloned())
+            .unwrap_or_else(|| "github.com".into())
     }
 
     /// Get the two-word subcommand (e.g. "pr list") and one-word fallback.
     /// Handles env var prefixes like `GH_TOKEN=abc gh pr create`.
-    fn subcommands(ctx: &CommandContext) -> (String, &str) {
-        // Find position of "gh" in the word list (may be preceded by env vars)
-        let gh_pos = ctx.words.iter().position(|w| w == "gh");
+    fn subcommands<'a>(&self, ctx: &'a CommandContext) -> (String, &'a str) {
+        // Find position of the tool in the word list (may be preceded by env vars)
+        let gh_pos = ctx.words.iter().position(|w| *w == self.tool);
         let after_gh = gh_pos.map(|p| p + 1).unwrap_or(1);
 
         let sub_two = if ctx.words.len() > after_gh + 1 {
@@ -51,6 +206,9 @@ impl GhSpec {
             String::new()
         };
         let sub_one: &str = ctx.words.get(after_gh).map(|w| w.as_str()).unwrap_or("?");
+        if self.tool == "gh" && sub_one == "cs" {
+            return (sub_two.replacen("cs", "codespace", 1), "codespace");
+        }
         (sub_two, sub_one)
     }
 
@@ -64,20 +222,61 @@ impl GhSpec {
 
 impl CommandSpec for GhSpec {
     fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
-        let (sub_two, sub_one) = Self::subcommands(ctx);
+        let (sub_two, sub_one) = self.subcommands(ctx);
+        let tool = &self.tool;
 
+        if tool == "gh" && sub_one == "api" {
+            let host = Self::api_host(ctx);
+            if self.domains.is_denied(&host) {
+                return RuleMatch {
+                    decision: Decision::Deny,
+                    reason: format!("gh api to denied domain {host}"),
+                };
+            }
+            if !self.domains.is_allowed(&host) {
+                return RuleMatch {
+                    decision: Decision::Ask,
+                    reason: format!("gh api to unlisted host {host} requires confirmation"),
+                };
+            }
+        }
+
+        if let Some(name) = Self::extension_name(ctx).filter(|_| tool == "gh") {
+            let name = name.strip_prefix("gh-").unwrap_or(name);
+            let what = format!("gh extension {name}");
+            if self.extensions.iter().any(|e| e == name) {
+                return query(ctx, &what);
+            }
+            let mut result = decided(self.extension, &what, None);
+            if result.decision == Decision::Ask {
+                result.reason = format!("{what} requires confirmation (runs a third-party binary)");
+            }
+            return result;
+        }
+
+        // A two-word mutating entry wins over its one-word read-only prefix
+        // (`issues create` over `issues`).
+        let listed_two =
+            self.mutating.contains(&sub_two) || self.allowed_with_config.contains(&sub_two);
         let in_read_only = self.read_only.iter().any(|s| s == &sub_two)
-            || self.read_only.iter().any(|s| s == sub_one);
+            || (!listed_two && self.read_only.iter().any(|s| s == sub_one));
         if in_read_only {
             if let Some(ref r) = ctx.redirection {
                 return RuleMatch {
                     decision: Decision::Ask,
-                    reason: format!("gh {sub_one} with {}", r),
+                    reason: format!("{tool} {sub_one} with {}", r),
                 };
             }
             return RuleMatch {
                 decision: Decision::Allow,
-                reason: format!("read-only gh {sub_two}"),
+                reason: format!(
+                    "read-only {tool} {}",
+                    if sub_two.is_empty() {
+                        sub_one
+                    } else {
+                        &sub_two
+                    }
+                ),
             };
         }
 
@@ -89,17 +288,17 @@ impl CommandSpec for GhSpec {
                 if let Some(ref r) = ctx.redirection {
                     return RuleMatch {
                         decision: Decision::Ask,
-                        reason: format!("gh {sub_one} with {}", r),
+                        reason: format!("{tool} {sub_one} with {}", r),
                     };
                 }
                 return RuleMatch {
                     decision: Decision::Allow,
-                    reason: format!("gh {sub_two} with {}", self.env_keys_display()),
+                    reason: format!("{tool} {sub_two} with {}", self.env_keys_display()),
                 };
             }
             return RuleMatch {
                 decision: Decision::Ask,
-                reason: format!("gh {sub_two} requires confirmation"),
+                reason: format!("{tool} {sub_two} requires confirmation"),
             };
         }
 
@@ -108,13 +307,13 @@ impl CommandSpec for GhSpec {
         if in_mutating {
             return RuleMatch {
                 decision: Decision::Ask,
-                reason: format!("gh {sub_two} requires confirmation"),
+                reason: format!("{tool} {sub_two} requires confirmation"),
             };
         }
 
         RuleMatch {
             decision: Decision::Ask,
-            reason: format!("gh {sub_one} requires confirmation"),
+            reason: format!("{tool} {sub_one} requires confirmation"),
         }
     }
 }
@@ -125,7 +324,8 @@ mod tests {
     use crate::config::Config;
 
     fn spec() -> GhSpec {
-        GhSpec::from_config(&Config::default_config().gh)
+        let config = Config::default_config();
+        GhSpec::from_config(&config.gh, &config.network)
     }
 
     fn eval(cmd: &str) -> Decision {
@@ -154,6 +354,28 @@ mod tests {
         assert_eq!(eval("gh api repos/owner/repo/pulls"), Decision::Allow);
     }
 
+    #[test]
+    fn ask_api_unlisted_hostname() {
+        assert_eq!(
+            eval("gh api --hostname ghe.corp.example repos/o/r"),
+            Decision::Ask
+        );
+        assert_eq!(eval("GH_HOST=ghe.corp.example gh api user"), Decision::Ask);
+    }
+
+    #[test]
+    fn deny_api_denied_url_endpoint() {
+        assert_eq!(eval("gh api https://webhook.site/abc"), Decision::Deny);
+    }
+
+    #[test]
+    fn allow_api_github_url_endpoint() {
+        assert_eq!(
+            eval("gh api https://api.github.com/repos/o/r"),
+            Decision::Allow
+        );
+    }
+
     #[test]
     fn ask_pr_create() {
         assert_eq!(eval("gh pr create --title 'Fix'"), Decision::Ask);
@@ -174,15 +396,153 @@ mod tests {
         assert_eq!(eval("gh pr list > /tmp/prs.txt"), Decision::Ask);
     }
 
+    // ── Gists and codespaces ──
+
+    #[test]
+    fn gists_and_codespaces() {
+        for cmd in [
+            "gh gist list",
+            "gh gist view abc123 --raw",
+            "gh codespace list",
+            "gh codespace view -c my-space",
+            "gh cs list",
+        ] {
+            assert_eq!(eval(cmd), Decision::Allow, "{cmd}");
+        }
+        for cmd in [
+            "gh gist create notes.md --public",
+            "gh gist edit abc123",
+            "gh gist delete abc123",
+            "gh codespace create -R o/r",
+            "gh codespace delete -c my-space",
+            "gh codespace ssh -c my-space",
+            "gh cs cp remote:/tmp/a .",
+        ] {
+            assert_eq!(eval(cmd), Decision::Ask, "{cmd}");
+        }
+        let ctx = CommandContext::from_command("gh cs delete --all");
+        assert_eq!(
+            spec().evaluate(&ctx).reason,
+            "gh codespace delete requires confirmation"
+        );
+    }
+
+    #[test]
+    fn codespace_ssh_commands() {
+        let command = |cmd| ssh_command(&CommandContext::from_command(cmd));
+        assert_eq!(
+            command("gh codespace ssh -c my-space -- 'rm -rf ~/work'"),
+            Some("rm -rf ~/work".into())
+        );
+        assert_eq!(command("gh cs ssh -- -t ls -la"), Some("ls -la".into()));
+        assert_eq!(command("gh codespace ssh -c my-space"), None);
+        assert_eq!(command("gh codespace cp -- a b"), None);
+    }
+
+    // ── hub, tea, fj ──
+
+    #[test]
+    fn gh_style_tools() {
+        let config = Config::default_config();
+        let eval = |cmd| {
+            let ctx = CommandContext::from_command(cmd);
+            let (tool, section) = match ctx.base_command.as_str() {
+                "hub" => ("hub", &config.hub),
+                "tea" => ("tea", &config.tea),
+                _ => ("fj", &config.fj),
+            };
+            GhSpec::for_tool(tool, section, &config.network).evaluate(&ctx)
+        };
+        for cmd in [
+            "hub pr list",
+            "hub issue",
+            "hub ci-status",
+            "tea issues",
+            "tea pulls list --state open",
+            "tea whoami",
+            "fj pr view 12",
+            "fj issue search bug",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        for cmd in [
+            "hub pull-request -m Fix",
+            "hub issue create -m Bug",
+            "tea issues create --title Bug",
+            "tea pulls merge 3",
+            "fj pr merge 12",
+            "fj repo delete o/r",
+            "tea frobnicate",
+            "fj dash",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(eval("tea issues").reason, "read-only tea issues");
+        assert_eq!(
+            eval("tea pulls merge 3").reason,
+            "tea pulls merge requires confirmation"
+        );
+        assert_eq!(eval("fj dash").reason, "fj dash requires confirmation");
+    }
+
+    // ── Extensions ──
+
+    #[test]
+    fn extensions_ask() {
+        for cmd in [
+            "gh dash",
+            "gh copilot-cli suggest 'list files'",
+            "gh extension exec dash",
+            "gh ext exec gh-poi",
+            "GH_TOKEN=x gh poi --dry-run",
+        ] {
+            assert_eq!(eval(cmd), Decision::Ask, "{cmd}");
+        }
+        let ctx = CommandContext::from_command("gh extension exec dash");
+        assert_eq!(
+            spec().evaluate(&ctx).reason,
+            "gh extension dash requires confirmation (runs a third-party binary)"
+        );
+        // gh's own commands are not extensions.
+        assert_eq!(eval("gh extension list"), Decision::Allow);
+        assert_eq!(eval("gh --version"), Decision::Ask);
+        assert_eq!(
+            spec()
+                .evaluate(&CommandContext::from_command("gh pr merge 1"))
+                .reason,
+            "gh pr merge requires confirmation"
+        );
+    }
+
+    #[test]
+    fn listed_extensions_allow() {
+        let config = GhConfig {
+            extensions: vec!["dash".into()],
+            extension: Decision::Deny,
+            ..Config::default_config().gh
+        };
+        let spec = GhSpec::from_config(&config, &Config::default_config().network);
+        let eval = |cmd| spec.evaluate(&CommandContext::from_command(cmd));
+        assert_eq!(eval("gh dash").decision, Decision::Allow);
+        assert_eq!(eval("gh dash").reason, "read-only gh extension dash");
+        assert_eq!(eval("gh extension exec gh-dash").decision, Decision::Allow);
+        assert_eq!(eval("gh dash > out.txt").decision, Decision::Ask);
+        assert_eq!(eval("gh poi").reason, "blocked command: gh extension poi");
+    }
+
     // ── Env-gated commands ──
 
     fn spec_with_env_gate() -> GhSpec {
-        GhSpec::from_config(&GhConfig {
-            read_only: vec!["pr list".into(), "pr view".into(), "status".into()],
-            mutating: vec!["repo delete".into()],
-            allowed_with_config: vec!["pr create".into(), "pr merge".into()],
-            config_env: HashMap::from([("GH_CONFIG_DIR".into(), "~/.config/gh-ai".into())]),
-        })
+        GhSpec::from_config(
+            &GhConfig {
+                read_only: vec!["pr list".into(), "pr view".into(), "status".into()],
+                mutating: vec!["repo delete".into()],
+                allowed_with_config: vec!["pr create".into(), "pr merge".into()],
+                config_env: HashMap::from([("GH_CONFIG_DIR".into(), "~/.config/gh-ai".into())]),
+                ..Default::default()
+            },
+            &Config::default_config().network,
+        )
     }
 
     fn eval_with_env_gate(cmd: &str) -> Decision {
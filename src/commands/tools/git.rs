@@ -3,40 +3,295 @@
 //! Handles global flags (`-C`, `--no-pager`, etc.) to correctly extract the
 //! subcommand, distinguishes read-only from mutating operations, supports
 //! env-gated auto-allow for configured subcommands, and detects force-push flags.
+//!
+//! Entries can name a subcommand's action too (`remote add`); the two-word
+//! entry is matched before the one-word one, so `remote` can be read-only
+//! while `remote add` asks.
+//!
+//! `git submodule` is decided by action: `status` reads, while `update`,
+//! `add` and `foreach` fetch or run code from elsewhere and ask. The
+//! command `foreach` runs in each submodule is evaluated by the registry
+//! (see [`foreach_command`](crate::commands::tools::git::foreach_command)).
+//!
+//! `git config` is decided by scope and key: reads are allowed, and so are
+//! writes to the repository's own config, except for keys that run
+//! commands or handle credentials (`core.sshCommand`, `credential.helper`).
 
 use super::super::CommandSpec;
-use crate::config::GitConfig;
+use super::packages::decided;
+use crate::commands::args::{FlagSchema, ParsedArgs, remote_host};
+use crate::config::{GitConfig, NetworkConfig};
+use crate::eval::network::{DomainPolicy, url_host};
 use crate::eval::{CommandContext, Decision, RuleMatch};
 use agent_shell_parser::parse::Word;
 use std::collections::HashMap;
 
+/// `git remote add` and `set-url` flags that consume a value.
+const REMOTE_FLAGS: FlagSchema = FlagSchema {
+    short_value: "tm",
+    long_value: &["--track", "--master"],
+};
+
+/// `git config` flags that consume a value.
+const CONFIG_FLAGS: FlagSchema = FlagSchema {
+    short_value: "f",
+    long_value: &[
+        "--file",
+        "--blob",
+        "--type",
+        "--default",
+        "--comment",
+        "--value",
+        "--url",
+    ],
+};
+
+/// `git config` flags (and `git config <action>` actions) that only read.
+const CONFIG_READS: &[&str] = &[
+    "-l",
+    "--list",
+    "--get",
+    "--get-all",
+    "--get-regexp",
+    "--get-urlmatch",
+    "--get-color",
+    "--get-colorbool",
+    "list",
+    "get",
+];
+
+/// `git config` flags that pick what it does, besides the reads.
+const CONFIG_MODES: &[&str] = &[
+    "--unset",
+    "--unset-all",
+    "--add",
+    "--replace-all",
+    "--rename-section",
+    "--remove-section",
+    "-e",
+    "--edit",
+];
+
+/// `git config` actions of the subcommand syntax (`git config set k v`).
+const CONFIG_ACTIONS: &[&str] = &[
+    "list",
+    "get",
+    "set",
+    "unset",
+    "rename-section",
+    "remove-section",
+    "edit",
+];
+
+/// `git config` scope flags that write outside the repository.
+const CONFIG_WIDE_SCOPES: &[&str] = &["--global", "--system", "-f", "--file"];
+
+/// What a mutating action does, for its reason: by entry prefix, and flag
+/// when the note only applies with one.
+const ACTION_NOTES: &[(&str, Option<&str>, &str)] = &[
+    ("remote ", None, "changes where fetches and pushes go"),
+    ("stash drop", None, "destroys the stashed changes"),
+    ("stash clear", None, "destroys every stash"),
+    ("stash pop", None, "removes the stash once applied"),
+    (
+        "worktree remove",
+        Some("--force"),
+        "discards the worktree's uncommitted changes",
+    ),
+    (
+        "worktree remove",
+        Some("-f"),
+        "discards the worktree's uncommitted changes",
+    ),
+    (
+        "worktree prune",
+        None,
+        "forgets worktrees whose directories are missing",
+    ),
+];
+
+/// `git branch` flags that consume a value.
+const BRANCH_FLAGS: FlagSchema = FlagSchema {
+    short_value: "u",
+    long_value: &[
+        "--set-upstream-to",
+        "--contains",
+        "--no-contains",
+        "--merged",
+        "--no-merged",
+        "--points-at",
+        "--sort",
+        "--format",
+        "--color",
+        "--column",
+    ],
+};
+
+/// `git tag` flags that consume a value.
+const TAG_FLAGS: FlagSchema = FlagSchema {
+    short_value: "mFu",
+    long_value: &[
+        "--message",
+        "--file",
+        "--local-user",
+        "--contains",
+        "--no-contains",
+        "--merged",
+        "--no-merged",
+        "--points-at",
+        "--sort",
+        "--format",
+        "--color",
+        "--column",
+        "--cleanup",
+        "--trailer",
+    ],
+};
+
+/// `git push` flags that consume a value.
+const PUSH_FLAGS: FlagSchema = FlagSchema {
+    short_value: "o",
+    long_value: &["--repo", "--receive-pack", "--exec", "--push-option"],
+};
+
+/// `git branch` and `git tag` flags that delete or overwrite a ref, with
+/// what they do.
+pub const REF_CHANGES: &[(&str, &str, &str)] = &[
+    ("branch", "-d", "deletes the branch"),
+    ("branch", "--delete", "deletes the branch"),
+    ("branch", "-D", "deletes the branch even if unmerged"),
+    ("branch", "-f", "resets an existing branch"),
+    ("branch", "--force", "resets an existing branch"),
+    ("branch", "-M", "overwrites an existing branch"),
+    ("branch", "-C", "overwrites an existing branch"),
+    ("tag", "-d", "deletes the tag"),
+    ("tag", "--delete", "deletes the tag"),
+    ("tag", "-f", "moves an existing tag"),
+    ("tag", "--force", "moves an existing tag"),
+];
+
+/// `git push` flags that delete or overwrite remote refs, with what they do.
+const PUSH_REF_CHANGES: &[(&str, &str)] = &[
+    ("-d", "deletes remote refs"),
+    ("--delete", "deletes remote refs"),
+    (
+        "--mirror",
+        "makes every remote ref match a local one, force-updating or deleting the rest",
+    ),
+    (
+        "--prune",
+        "deletes remote refs that have no local counterpart",
+    ),
+];
+
+/// `git submodule` flags that consume a value.
+const SUBMODULE_FLAGS: FlagSchema = FlagSchema {
+    short_value: "bj",
+    long_value: &[
+        "--branch",
+        "--name",
+        "--reference",
+        "--depth",
+        "--jobs",
+        "--filter",
+    ],
+};
+
+/// `git submodule` actions that only read.
+const SUBMODULE_READS: &[&str] = &["status", "summary"];
+
+/// The command `git submodule foreach` runs in each submodule, if `ctx` is
+/// one. A single word is a shell command line; several are a command and
+/// its arguments.
+pub fn foreach_command(ctx: &CommandContext) -> Option<String> {
+    if ctx.base_command != "git" {
+        return None;
+    }
+    let sub = GitSpec::subcommand_index(ctx)?;
+    if ctx.words[sub] != "submodule" {
+        return None;
+    }
+    let (action, rest) = GitSpec::action(ctx)?;
+    if action != "foreach" {
+        return None;
+    }
+    let start = rest.iter().position(|w| !w.as_str().starts_with('-'))?;
+    match &rest[start..] {
+        [line] => Some(line.as_str().to_string()),
+        words => Some(CommandContext::join_words(words.iter().map(|w| w.as_str()))),
+    }
+}
+
+/// Whether `key` matches a config key pattern: exact, or around one `*`
+/// (`credential.*`, `url.*.insteadof`). Both are compared lowercased.
+fn key_matches(pattern: &str, key: &str) -> bool {
+    let (pattern, key) = (pattern.to_ascii_lowercase(), key.to_ascii_lowercase());
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            key.len() >= prefix.len() + suffix.len()
+                && key.starts_with(prefix)
+                && key.ends_with(suffix)
+        }
+        None => key == pattern,
+    }
+}
+
 /// Subcommand-aware git evaluator.
 ///
 /// Evaluation order:
-/// 1. Force-push flags → always ASK
-/// 2. Read-only subcommands → ALLOW (with redirection escalation)
-/// 3. Env-gated subcommands → ALLOW if all `config_env` entries match, else ASK
-/// 4. `--version` → ALLOW
-/// 5. Everything else → ASK
+/// 1. Force-push flags, `git push --delete`/`--mirror`/`--prune`,
+///    `:ref` deletions and `+ref` forced updates, and `git branch`/`git
+///    tag` deletion and force flags → always ASK
+/// 2. `git config`: reads → ALLOW; writes of `sensitive_config_keys` →
+///    `sensitive_config`; `--global`/`--system`/`--file` writes →
+///    `global_config`; other writes → `local_config`
+/// 3. `git submodule`: `status`/`summary` → ALLOW; `add` from a denied
+///    domain → DENY; `update`, `add`, `foreach` and the rest → ASK
+/// 4. `git remote add`/`set-url` to a denied domain → DENY
+/// 5. Two-word mutating entries (`remote add`) → ASK; two-word read-only
+///    entries → ALLOW
+/// 6. Read-only subcommands → ALLOW (with redirection escalation)
+/// 7. Env-gated subcommands → ALLOW if all `config_env` entries match, else ASK
+/// 8. `--version` → ALLOW
+/// 9. Everything else → ASK
 pub struct GitSpec {
     /// Git subcommands that are always allowed (e.g. `status`, `log`, `diff`).
     read_only: Vec<String>,
+    /// Subcommand actions that always ask, matched before `read_only`
+    /// (e.g. `remote add`, `remote set-url`).
+    mutating: Vec<String>,
     /// Subcommands allowed only when all `config_env` entries match.
     allowed_with_config: Vec<String>,
     /// Required env var name→value pairs that gate `allowed_with_config` subcommands.
     config_env: HashMap<String, String>,
     /// Flags indicating force-push (always ASK regardless of env-gating).
     force_push_flags: Vec<String>,
+    /// Destination classification for new remote URLs.
+    domains: DomainPolicy,
+    /// Config keys whose writes get `sensitive_config`.
+    sensitive_config_keys: Vec<String>,
+    /// Decision for writing a sensitive config key, or `git config --edit`.
+    sensitive_config: Decision,
+    /// Decision for writing global, system or file config.
+    global_config: Decision,
+    /// Decision for writing the repository's config.
+    local_config: Decision,
 }
 
 impl GitSpec {
     /// Build a git spec from configuration.
-    pub fn from_config(config: &GitConfig) -> Self {
+    pub fn from_config(config: &GitConfig, network: &NetworkConfig) -> Self {
         Self {
             read_only: config.read_only.clone(),
+            mutating: config.mutating.clone(),
             allowed_with_config: config.allowed_with_config.clone(),
             config_env: config.config_env.clone(),
             force_push_flags: config.force_push_flags.clone(),
+            domains: DomainPolicy::from_config(network),
+            sensitive_config_keys: config.sensitive_config_keys.clone(),
+            sensitive_config: config.sensitive_config,
+            global_config: config.global_config,
+            local_config: config.local_config,
         }
     }
 
@@ -59,16 +314,21 @@ impl GitSpec {
     /// Extract the git subcommand word (e.g. "push" from "git push origin main").
     /// Skips global flags like `-C <path>` that appear before the subcommand.
     fn subcommand(ctx: &CommandContext) -> Option<&Word> {
-        let mut iter = ctx.words.iter();
+        Self::subcommand_index(ctx).map(|i| &ctx.words[i])
+    }
+
+    /// Position of the subcommand word in `ctx.words`.
+    fn subcommand_index(ctx: &CommandContext) -> Option<usize> {
+        let mut iter = ctx.words.iter().enumerate();
         // Advance past env vars to find "git"
-        for word in iter.by_ref() {
+        for (_, word) in iter.by_ref() {
             if word == "git" {
                 break;
             }
         }
         // Skip global flags to find the subcommand
         loop {
-            let word = iter.next()?;
+            let (i, word) = iter.next()?;
             if Self::GLOBAL_ARG_FLAGS.contains(&word.as_str()) {
                 // Consume the flag's argument
                 iter.next();
@@ -78,7 +338,229 @@ impl GitSpec {
                 continue;
             }
             // Not a global flag — this is the subcommand
-            return Some(word);
+            return Some(i);
+        }
+    }
+
+    /// The subcommand's action and the words after it (`add` and `origin
+    /// URL` from `git remote add origin URL`): the first word after the
+    /// subcommand that isn't a flag.
+    fn action(ctx: &CommandContext) -> Option<(&str, &[Word])> {
+        let sub = Self::subcommand_index(ctx)?;
+        let rest = &ctx.words[sub + 1..];
+        let i = rest.iter().position(|w| !w.as_str().starts_with('-'))?;
+        Some((rest[i].as_str(), &rest[i + 1..]))
+    }
+
+    /// The host of the URL `git remote add` or `set-url` points a remote
+    /// at, if it has one (a local path doesn't).
+    fn remote_url_host(action: &str, args: &[Word]) -> Option<String> {
+        if action != "add" && action != "set-url" {
+            return None;
+        }
+        let args = ParsedArgs::parse(args, &REMOTE_FLAGS);
+        let url = args.positionals.get(1)?;
+        let host = if url.contains("://") {
+            url_host(url)?
+        } else {
+            remote_host(url)?
+        };
+        let host = host.rsplit_once('@').map_or(host, |(_, h)| h);
+        Some(host.to_string())
+    }
+
+    /// Decide `git config` by what it does: read, edit, or write a key in
+    /// some scope.
+    fn config(&self, ctx: &CommandContext) -> RuleMatch {
+        let sub = Self::subcommand_index(ctx).unwrap_or(ctx.words.len());
+        let args = ParsedArgs::parse(&ctx.words[sub + 1..], &CONFIG_FLAGS);
+        let mut positionals = args.positionals.iter().map(String::as_str);
+        let action = match args.positionals.first() {
+            Some(first) if CONFIG_ACTIONS.contains(&first.as_str()) => positionals.next(),
+            _ => args
+                .flags
+                .iter()
+                .map(|f| f.name.as_str())
+                .find(|name| CONFIG_READS.contains(name) || CONFIG_MODES.contains(name)),
+        };
+        let key = positionals.next();
+        let reads = action.is_some_and(|a| CONFIG_READS.contains(&a))
+            || (action.is_none() && args.positionals.len() < 2);
+        if reads {
+            if let Some(ref r) = ctx.redirection {
+                return RuleMatch {
+                    decision: Decision::Ask,
+                    reason: format!("git config with {r}"),
+                };
+            }
+            return RuleMatch {
+                decision: Decision::Allow,
+                reason: "read-only git config".into(),
+            };
+        }
+        if matches!(action, Some("-e" | "--edit" | "edit")) {
+            return decided(self.sensitive_config, "git config --edit", None);
+        }
+        let scope = args
+            .flags
+            .iter()
+            .map(|f| f.name.as_str())
+            .find(|name| CONFIG_WIDE_SCOPES.contains(name));
+        let what = match scope {
+            Some(scope) => format!("git config {scope} {}", key.unwrap_or("")),
+            None => format!("git config {}", key.unwrap_or("")),
+        };
+        let what = what.trim_end();
+        let sensitive = key.is_some_and(|key| {
+            self.sensitive_config_keys
+                .iter()
+                .any(|pattern| key_matches(pattern, key))
+        });
+        if sensitive {
+            decided(self.sensitive_config, what, None)
+        } else if scope.is_some() {
+            decided(self.global_config, what, None)
+        } else {
+            decided(self.local_config, what, None)
+        }
+    }
+
+    /// The ref deletion or overwrite `ctx` makes, if any: `git branch
+    /// -d`/`-D`/`-f`, `git tag -d`/`-f`, `git push --delete`, `--mirror`,
+    /// `--prune`, a `:ref` refspec or a `+ref` forced one. These always ask,
+    /// like a force-push.
+    fn ref_change(sub: &str, ctx: &CommandContext) -> Option<RuleMatch> {
+        let rest = &ctx.words[Self::subcommand_index(ctx)? + 1..];
+        let (what, note) = match sub {
+            "branch" | "tag" => {
+                let schema = if sub == "branch" {
+                    &BRANCH_FLAGS
+                } else {
+                    &TAG_FLAGS
+                };
+                let args = ParsedArgs::parse(rest, schema);
+                let (_, flag, note) = REF_CHANGES
+                    .iter()
+                    .find(|(s, flag, _)| *s == sub && args.has_any(&[*flag]))?;
+                (format!("git {sub} {flag}"), *note)
+            }
+            "push" => {
+                let args = ParsedArgs::parse(rest, &PUSH_FLAGS);
+                let flag = PUSH_REF_CHANGES
+                    .iter()
+                    .find(|(flag, _)| args.has_any(&[*flag]));
+                if let Some((flag, note)) = flag {
+                    (format!("git push {flag}"), *note)
+                } else {
+                    let refspec = args
+                        .positionals
+                        .iter()
+                        .skip(1)
+                        .find(|r| r.starts_with([':', '+']))?;
+                    let note = if refspec.starts_with(':') {
+                        "deletes the remote ref"
+                    } else {
+                        "force-updates the remote ref, which can discard its commits"
+                    };
+                    (format!("git push {refspec}"), note)
+                }
+            }
+            _ => return None,
+        };
+        Some(RuleMatch {
+            decision: Decision::Ask,
+            reason: format!("{what} requires confirmation ({note})"),
+        })
+    }
+
+    /// Decide `git submodule` by action. `update` and `add` check out code
+    /// from another repository; `update --remote` takes the upstream tip
+    /// rather than the commit the superproject records.
+    fn submodule(&self, ctx: &CommandContext) -> RuleMatch {
+        let action = Self::action(ctx);
+        let name = action.map_or("status", |(action, _)| action);
+        if SUBMODULE_READS.contains(&name) {
+            if let Some(ref r) = ctx.redirection {
+                return RuleMatch {
+                    decision: Decision::Ask,
+                    reason: format!("git submodule {name} with {r}"),
+                };
+            }
+            return RuleMatch {
+                decision: Decision::Allow,
+                reason: format!("read-only git submodule {name}"),
+            };
+        }
+        let rest = action.map_or(&[][..], |(_, rest)| rest);
+        let args = ParsedArgs::parse(rest, &SUBMODULE_FLAGS);
+        let (what, note) = match name {
+            "update" if args.has_any(&["--remote"]) => (
+                "git submodule update --remote".to_string(),
+                "checks out the upstream tip, not the recorded commit".to_string(),
+            ),
+            "update" => (
+                "git submodule update".to_string(),
+                "fetches and checks out external code".to_string(),
+            ),
+            "add" => {
+                let url = args.positionals.first().map(|w| w.as_str()).unwrap_or("");
+                let host = if url.contains("://") {
+                    url_host(url)
+                } else {
+                    remote_host(url)
+                }
+                .map(|h| h.rsplit_once('@').map_or(h, |(_, h)| h));
+                if let Some(host) = host.filter(|h| self.domains.is_denied(h)) {
+                    return RuleMatch {
+                        decision: Decision::Deny,
+                        reason: format!("git submodule add from denied domain {host}"),
+                    };
+                }
+                let note = match host.filter(|h| !self.domains.is_allowed(h)) {
+                    Some(host) => format!("adds external code; {host} is not an allowed domain"),
+                    None => "adds external code".to_string(),
+                };
+                ("git submodule add".to_string(), note)
+            }
+            "foreach" => (
+                "git submodule foreach".to_string(),
+                "runs a command in every submodule".to_string(),
+            ),
+            "deinit" => (
+                "git submodule deinit".to_string(),
+                "removes the submodule's working tree".to_string(),
+            ),
+            other => {
+                return RuleMatch {
+                    decision: Decision::Ask,
+                    reason: format!("git submodule {other} requires confirmation"),
+                };
+            }
+        };
+        RuleMatch {
+            decision: Decision::Ask,
+            reason: format!("{what} requires confirmation ({note})"),
+        }
+    }
+
+    /// Reason for an ASK on a two-word mutating entry: what the action does
+    /// (`ACTION_NOTES`), and a new remote URL's host when it isn't an
+    /// allowed domain.
+    fn mutating_reason(&self, sub_two: &str, args: &[Word], host: Option<&str>) -> String {
+        let action = ACTION_NOTES
+            .iter()
+            .find(|(prefix, flag, _)| {
+                sub_two.starts_with(prefix) && flag.is_none_or(|f| args.iter().any(|w| w == f))
+            })
+            .map(|(_, _, note)| note.to_string());
+        let host = host
+            .filter(|h| !self.domains.is_allowed(h))
+            .map(|h| format!("{h} is not an allowed domain"));
+        let notes: Vec<String> = action.into_iter().chain(host).collect();
+        if notes.is_empty() {
+            format!("git {sub_two} requires confirmation")
+        } else {
+            format!("git {sub_two} requires confirmation ({})", notes.join("; "))
         }
     }
 
@@ -106,6 +588,47 @@ impl CommandSpec for GitSpec {
             }
         }
 
+        if let Some(change) = Self::ref_change(sub_str, ctx) {
+            return change;
+        }
+
+        if sub_str == "config" {
+            return self.config(ctx);
+        }
+        if sub_str == "submodule" {
+            return self.submodule(ctx);
+        }
+
+        // Subcommand actions (`remote add`) before the subcommand alone
+        if let Some((action, rest)) = Self::action(ctx) {
+            let sub_two = format!("{sub_str} {action}");
+            if self.mutating.contains(&sub_two) {
+                let host = Self::remote_url_host(action, rest);
+                if let Some(host) = host.as_deref().filter(|h| self.domains.is_denied(h)) {
+                    return RuleMatch {
+                        decision: Decision::Deny,
+                        reason: format!("git {sub_two} to denied domain {host}"),
+                    };
+                }
+                return RuleMatch {
+                    decision: Decision::Ask,
+                    reason: self.mutating_reason(&sub_two, rest, host.as_deref()),
+                };
+            }
+            if self.read_only.contains(&sub_two) {
+                if let Some(ref r) = ctx.redirection {
+                    return RuleMatch {
+                        decision: Decision::Ask,
+                        reason: format!("git {sub_two} with {}", r),
+                    };
+                }
+                return RuleMatch {
+                    decision: Decision::Allow,
+                    reason: format!("read-only git {sub_two}"),
+                };
+            }
+        }
+
         // Read-only git subcommands — always allowed
         if self.read_only.iter().any(|s| s == sub_str) {
             if let Some(ref r) = ctx.redirection {
@@ -153,6 +676,21 @@ impl CommandSpec for GitSpec {
             reason: format!("git {sub_str} requires confirmation"),
         }
     }
+
+    /// Replace a bare force-push with `--force-with-lease`, which refuses to
+    /// overwrite remote commits that have not been fetched.
+    fn suggest(&self, ctx: &CommandContext) -> Option<String> {
+        if Self::subcommand(ctx).map(|w| w.as_str()) != Some("push")
+            || !ctx.has_any_flag(&["--force", "-f"])
+        {
+            return None;
+        }
+        let lease = CommandContext::join_words(ctx.words.iter().map(|w| match w.as_str() {
+            "--force" | "-f" => "--force-with-lease",
+            other => other,
+        }));
+        Some(format!("use `{lease}` instead"))
+    }
 }
 
 #[cfg(test)]
@@ -172,7 +710,8 @@ mod tests {
     }
 
     fn default_spec() -> GitSpec {
-        GitSpec::from_config(&Config::default_config().git)
+        let config = Config::default_config();
+        GitSpec::from_config(&config.git, &config.network)
     }
 
     fn eval(cmd: &str) -> Decision {
@@ -183,17 +722,21 @@ mod tests {
 
     /// Build a spec with env-gated config enabled (like a user's custom config).
     fn spec_with_env_gate() -> GitSpec {
-        GitSpec::from_config(&GitConfig {
-            read_only: vec![
-                "status".into(),
-                "log".into(),
-                "diff".into(),
-                "branch".into(),
-            ],
-            allowed_with_config: vec!["push".into(), "pull".into(), "add".into()],
-            config_env: HashMap::from([("GIT_CONFIG_GLOBAL".into(), "~/.gitconfig.ai".into())]),
-            force_push_flags: vec!["--force".into(), "-f".into(), "--force-with-lease".into()],
-        })
+        GitSpec::from_config(
+            &GitConfig {
+                read_only: vec![
+                    "status".into(),
+                    "log".into(),
+                    "diff".into(),
+                    "branch".into(),
+                ],
+                allowed_with_config: vec!["push".into(), "pull".into(), "add".into()],
+                config_env: HashMap::from([("GIT_CONFIG_GLOBAL".into(), "~/.gitconfig.ai".into())]),
+                force_push_flags: vec!["--force".into(), "-f".into(), "--force-with-lease".into()],
+                ..Default::default()
+            },
+            &Default::default(),
+        )
     }
 
     fn eval_with_env_gate(cmd: &str) -> Decision {
@@ -275,6 +818,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn suggest_force_with_lease() {
+        let ctx = CommandContext::from_command("git push -f origin main");
+        assert_eq!(
+            default_spec().suggest(&ctx).as_deref(),
+            Some("use `git push --force-with-lease origin main` instead")
+        );
+        let ctx = CommandContext::from_command("git push --force-with-lease origin main");
+        assert_eq!(default_spec().suggest(&ctx), None);
+        let ctx = CommandContext::from_command("git push origin main");
+        assert_eq!(default_spec().suggest(&ctx), None);
+    }
+
     #[test]
     fn env_gate_commit_still_asks() {
         // commit is not in allowed_with_config
@@ -311,6 +867,343 @@ mod tests {
         assert_eq!(eval("git --no-pager log"), Decision::Allow);
     }
 
+    // ── Subcommand actions ──
+
+    #[test]
+    fn remote_changes_ask() {
+        assert_eq!(eval("git remote -v"), Decision::Allow);
+        assert_eq!(eval("git remote show origin"), Decision::Allow);
+        for cmd in [
+            "git remote add upstream https://github.com/org/repo.git",
+            "git remote set-url origin git@example.com:org/repo.git",
+            "git remote set-url --push origin ../mirror",
+            "git remote rename origin old",
+            "git remote remove upstream",
+        ] {
+            assert_eq!(eval(cmd), Decision::Ask, "{cmd}");
+        }
+        let reason = |cmd| {
+            default_spec()
+                .evaluate(&CommandContext::from_command(cmd))
+                .reason
+        };
+        assert_eq!(
+            reason("git remote add upstream https://github.com/org/repo.git"),
+            "git remote add requires confirmation (changes where fetches and pushes go)"
+        );
+        assert_eq!(
+            reason("git remote set-url origin git@example.com:org/repo.git"),
+            "git remote set-url requires confirmation (changes where fetches and pushes go; \
+             example.com is not an allowed domain)"
+        );
+    }
+
+    #[test]
+    fn remote_to_denied_domain_denies() {
+        let config = Config::default_config();
+        let network = NetworkConfig {
+            denied_domains: vec!["pastebin.com".into()],
+            ..Default::default()
+        };
+        let spec = GitSpec::from_config(&config.git, &network);
+        let ctx = CommandContext::from_command("git remote add x https://pastebin.com/raw/abc");
+        let result = spec.evaluate(&ctx);
+        assert_eq!(result.decision, Decision::Deny);
+        assert_eq!(
+            result.reason,
+            "git remote add to denied domain pastebin.com"
+        );
+    }
+
+    #[test]
+    fn stash_by_action() {
+        for cmd in ["git stash list", "git stash show -p stash@{1}"] {
+            assert_eq!(eval(cmd), Decision::Allow, "{cmd}");
+        }
+        for cmd in [
+            "git stash",
+            "git stash -u",
+            "git stash push -m wip",
+            "git stash pop",
+            "git stash apply stash@{0}",
+            "git stash drop stash@{1}",
+            "git stash clear",
+        ] {
+            assert_eq!(eval(cmd), Decision::Ask, "{cmd}");
+        }
+        let reason = |cmd| {
+            default_spec()
+                .evaluate(&CommandContext::from_command(cmd))
+                .reason
+        };
+        assert_eq!(
+            reason("git stash drop"),
+            "git stash drop requires confirmation (destroys the stashed changes)"
+        );
+        assert_eq!(
+            reason("git stash clear"),
+            "git stash clear requires confirmation (destroys every stash)"
+        );
+        assert_eq!(reason("git stash list"), "read-only git stash list");
+    }
+
+    #[test]
+    fn ref_deletions_ask() {
+        for cmd in [
+            "git branch",
+            "git branch -a",
+            "git branch -vv --merged main",
+            "git branch --contains abc123",
+            "git tag",
+            "git tag -l 'v1.*'",
+        ] {
+            assert_eq!(eval(cmd), Decision::Allow, "{cmd}");
+        }
+        for cmd in [
+            "git branch -d feature",
+            "git branch -D feature",
+            "git branch -df feature",
+            "git branch --delete --remotes origin/feature",
+            "git branch -f main HEAD~3",
+            "git branch -M main",
+            "git tag -d v1.0",
+            "git tag -f v1.0 HEAD",
+            "git push --delete origin feature",
+            "git push origin -d feature",
+            "git push origin :feature",
+            "git push origin main :old",
+            "GIT_CONFIG_GLOBAL=~/.gitconfig.ai git push origin :feature",
+            "GIT_CONFIG_GLOBAL=~/.gitconfig.ai git push origin +main",
+            "GIT_CONFIG_GLOBAL=~/.gitconfig.ai git push --mirror origin",
+            "GIT_CONFIG_GLOBAL=~/.gitconfig.ai git push --prune origin",
+        ] {
+            assert_eq!(eval_with_env_gate(cmd), Decision::Ask, "{cmd}");
+        }
+        let reason = |cmd| {
+            default_spec()
+                .evaluate(&CommandContext::from_command(cmd))
+                .reason
+        };
+        assert_eq!(
+            reason("git branch -D feature"),
+            "git branch -D requires confirmation (deletes the branch even if unmerged)"
+        );
+        assert_eq!(
+            reason("git tag -f v1.0"),
+            "git tag -f requires confirmation (moves an existing tag)"
+        );
+        assert_eq!(
+            reason("git push origin :feature"),
+            "git push :feature requires confirmation (deletes the remote ref)"
+        );
+        assert_eq!(
+            reason("git push --delete origin feature"),
+            "git push --delete requires confirmation (deletes remote refs)"
+        );
+        assert_eq!(
+            reason("git push origin +HEAD:main"),
+            "git push +HEAD:main requires confirmation \
+             (force-updates the remote ref, which can discard its commits)"
+        );
+        assert_eq!(
+            reason("git push --mirror backup"),
+            "git push --mirror requires confirmation \
+             (makes every remote ref match a local one, force-updating or deleting the rest)"
+        );
+        assert_eq!(
+            reason("git push --prune origin"),
+            "git push --prune requires confirmation \
+             (deletes remote refs that have no local counterpart)"
+        );
+    }
+
+    #[test]
+    fn submodule_by_action() {
+        for cmd in [
+            "git submodule",
+            "git submodule status",
+            "git submodule status --recursive",
+            "git submodule summary",
+        ] {
+            assert_eq!(eval(cmd), Decision::Allow, "{cmd}");
+        }
+        for cmd in [
+            "git submodule status > out.txt",
+            "git submodule init",
+            "git submodule update --init --recursive",
+            "git submodule update --remote",
+            "git submodule add https://github.com/org/lib vendor/lib",
+            "git submodule foreach git pull",
+            "git submodule deinit -f vendor/lib",
+            "git submodule sync",
+        ] {
+            assert_eq!(eval(cmd), Decision::Ask, "{cmd}");
+        }
+        let reason = |cmd| {
+            default_spec()
+                .evaluate(&CommandContext::from_command(cmd))
+                .reason
+        };
+        assert_eq!(
+            reason("git submodule update --init"),
+            "git submodule update requires confirmation (fetches and checks out external code)"
+        );
+        assert_eq!(
+            reason("git submodule update --init --remote"),
+            "git submodule update --remote requires confirmation \
+             (checks out the upstream tip, not the recorded commit)"
+        );
+        assert_eq!(
+            reason("git submodule add -b main git@example.org:org/lib.git lib"),
+            "git submodule add requires confirmation \
+             (adds external code; example.org is not an allowed domain)"
+        );
+    }
+
+    #[test]
+    fn submodule_add_from_denied_domain_denies() {
+        let network = NetworkConfig {
+            denied_domains: vec!["pastebin.com".into()],
+            ..Default::default()
+        };
+        let spec = GitSpec::from_config(&Config::default_config().git, &network);
+        let ctx = CommandContext::from_command("git submodule add https://pastebin.com/x.git x");
+        let result = spec.evaluate(&ctx);
+        assert_eq!(result.decision, Decision::Deny);
+        assert_eq!(
+            result.reason,
+            "git submodule add from denied domain pastebin.com"
+        );
+    }
+
+    #[test]
+    fn foreach_commands() {
+        let command = |cmd| foreach_command(&CommandContext::from_command(cmd));
+        assert_eq!(
+            command("git submodule foreach 'git clean -fdx'"),
+            Some("git clean -fdx".into())
+        );
+        assert_eq!(
+            command("git submodule --quiet foreach --recursive git status"),
+            Some("git status".into())
+        );
+        assert_eq!(
+            command("git -C repo submodule foreach ls"),
+            Some("ls".into())
+        );
+        assert_eq!(command("git submodule foreach"), None);
+        assert_eq!(command("git submodule update"), None);
+        assert_eq!(command("git log foreach"), None);
+    }
+
+    #[test]
+    fn worktree_by_action() {
+        assert_eq!(eval("git worktree list --porcelain"), Decision::Allow);
+        for cmd in [
+            "git worktree",
+            "git worktree add ../feature feature",
+            "git worktree remove ../feature",
+            "git worktree remove --force ../feature",
+            "git worktree prune",
+            "git worktree move ../a ../b",
+        ] {
+            assert_eq!(eval(cmd), Decision::Ask, "{cmd}");
+        }
+        let reason = |cmd| {
+            default_spec()
+                .evaluate(&CommandContext::from_command(cmd))
+                .reason
+        };
+        assert_eq!(
+            reason("git worktree add ../feature"),
+            "git worktree add requires confirmation"
+        );
+        assert_eq!(
+            reason("git worktree remove ../feature"),
+            "git worktree remove requires confirmation"
+        );
+        assert_eq!(
+            reason("git worktree remove -f ../feature"),
+            "git worktree remove requires confirmation (discards the worktree's uncommitted changes)"
+        );
+        assert_eq!(
+            reason("git worktree prune"),
+            "git worktree prune requires confirmation (forgets worktrees whose directories are missing)"
+        );
+    }
+
+    // ── git config ──
+
+    #[test]
+    fn config_reads_allow() {
+        for cmd in [
+            "git config user.name",
+            "git config --get user.email",
+            "git config --global --list",
+            "git config -l --show-origin",
+            "git config --get-regexp '^remote\\.'",
+            "git config get --global core.sshCommand",
+            "git config credential.helper",
+            "git config --show-origin core.sshCommand",
+        ] {
+            assert_eq!(eval(cmd), Decision::Allow, "{cmd}");
+        }
+        assert_eq!(eval("git config --list > config.txt"), Decision::Ask);
+    }
+
+    #[test]
+    fn config_writes_by_scope_and_key() {
+        for (cmd, expected) in [
+            ("git config user.name 'A U Thor'", Decision::Allow),
+            ("git config --local pull.rebase true", Decision::Allow),
+            (
+                "git config set --worktree core.autocrlf input",
+                Decision::Allow,
+            ),
+            ("git config --global user.name Me", Decision::Ask),
+            ("git config --system init.defaultBranch main", Decision::Ask),
+            (
+                "git config --file ~/.gitconfig push.default current",
+                Decision::Ask,
+            ),
+            ("git config --unset --global user.name", Decision::Ask),
+            (
+                "git config --global core.sshCommand 'ssh -i key'",
+                Decision::Deny,
+            ),
+            ("git config core.hooksPath /tmp/hooks", Decision::Deny),
+            ("git config credential.helper store", Decision::Deny),
+            (
+                "git config set credential.https://example.com.username me",
+                Decision::Deny,
+            ),
+            (
+                "git config url.git@evil.com:.insteadOf https://github.com/",
+                Decision::Deny,
+            ),
+            ("git config --edit", Decision::Deny),
+        ] {
+            assert_eq!(eval(cmd), expected, "{cmd}");
+        }
+        let reason = |cmd| {
+            default_spec()
+                .evaluate(&CommandContext::from_command(cmd))
+                .reason
+        };
+        assert_eq!(
+            reason("git config --global user.name Me"),
+            "git config --global user.name requires confirmation"
+        );
+        assert_eq!(
+            reason("git config core.sshCommand 'ssh -i key'"),
+            "blocked command: git config core.sshCommand"
+        );
+        assert_eq!(
+            reason("git config pull.rebase true"),
+            "allowed: git config pull.rebase"
+        );
+    }
+
     #[test]
     fn allow_git_c_config_status() {
         // -c key=value is also a global flag
@@ -0,0 +1,114 @@
+//! Interpreter evaluation with inline code scanning.
+//!
+//! One spec serves every `[inline_code.<language>]` table (python, node,
+//! ruby, perl, awk, ...). The base disposition comes from the flat
+//! `[commands]` lists; code passed on the command line is scanned by the
+//! shared [`InlineCodeScanner`](crate::eval::inline_code::InlineCodeScanner)
+//! and can escalate it.
+
+use super::super::CommandSpec;
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::InlineLanguageConfig;
+use crate::eval::inline_code::{InlineCodeScanner, InlineMatch};
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// Interpreter evaluator.
+///
+/// Evaluation order:
+/// 1. Inline code matches a `deny` pattern → DENY
+/// 2. Base decision from the flat `[commands]` lists
+///    (ALLOW with redirection → ASK)
+/// 3. ALLOW with inline code matching an `ask` pattern → ASK
+pub struct InterpreterSpec {
+    /// Baseline disposition (the flat-list entry for this interpreter).
+    base: Decision,
+    /// Inline code scanner for this interpreter's language.
+    scanner: InlineCodeScanner,
+}
+
+impl InterpreterSpec {
+    /// Build an interpreter spec from its flat-list decision and language rules.
+    pub fn from_config(base: Decision, language: &InlineLanguageConfig) -> Self {
+        Self {
+            base,
+            scanner: InlineCodeScanner::from_config(language),
+        }
+    }
+}
+
+impl CommandSpec for InterpreterSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let result = SimpleCommandSpec::new(self.base).evaluate(ctx);
+        match self.scanner.scan(ctx.args()) {
+            Some(InlineMatch::Deny(pattern)) => RuleMatch {
+                decision: Decision::Deny,
+                reason: format!("{} inline code uses `{pattern}`", ctx.base_command),
+            },
+            Some(InlineMatch::Ask(pattern)) if result.decision == Decision::Allow => RuleMatch {
+                decision: Decision::Ask,
+                reason: format!(
+                    "{} inline code uses `{pattern}` (requires confirmation)",
+                    ctx.base_command
+                ),
+            },
+            _ => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn spec(lang: &str, base: Decision) -> InterpreterSpec {
+        InterpreterSpec::from_config(base, &Config::default_config().inline_code.languages[lang])
+    }
+
+    fn eval(lang: &str, base: Decision, cmd: &str) -> Decision {
+        let ctx = CommandContext::from_command(cmd);
+        spec(lang, base).evaluate(&ctx).decision
+    }
+
+    #[test]
+    fn deny_reverse_shell_one_liner() {
+        let cmd = "python3 -c 'import socket,pty;s=socket.socket();pty.spawn(\"sh\")'";
+        assert_eq!(eval("python", Decision::Ask, cmd), Decision::Deny);
+        let ctx = CommandContext::from_command(cmd);
+        assert!(
+            spec("python", Decision::Ask)
+                .evaluate(&ctx)
+                .reason
+                .contains("python3 inline code uses")
+        );
+    }
+
+    #[test]
+    fn ask_pattern_escalates_allowed_interpreter() {
+        assert_eq!(
+            eval("awk", Decision::Allow, "awk 'BEGIN{system(\"id\")}'"),
+            Decision::Ask
+        );
+        assert_eq!(
+            eval("awk", Decision::Allow, "awk '{print $1}' f"),
+            Decision::Allow
+        );
+    }
+
+    #[test]
+    fn base_decision_otherwise() {
+        assert_eq!(
+            eval("node", Decision::Ask, "node -e 'console.log(1)'"),
+            Decision::Ask
+        );
+        assert_eq!(eval("perl", Decision::Ask, "perl script.pl"), Decision::Ask);
+    }
+
+    #[test]
+    fn allowed_with_redirection_asks() {
+        assert_eq!(
+            eval("awk", Decision::Allow, "awk '{print $1}' f > out"),
+            Decision::Ask
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! JVM build tool evaluation: gradle and mvn, and their `./gradlew` and
+//! `./mvnw` wrapper scripts.
+//!
+//! A command line names several tasks (`gradle clean build`) or goals (`mvn
+//! verify dependency:tree`), and each is classified: the ones in
+//! `read_only` only build, test or report. Publishing (`publish`,
+//! `deploy`, `release`) gets `publish`, and `gradle wrapper
+//! --gradle-version` asks, since it changes the Gradle the wrapper
+//! downloads. The strictest task decides the command.
+
+use super::super::CommandSpec;
+use super::packages::{change, decided, query};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::JvmConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// gradle options that consume the next word.
+const GRADLE_VALUES: &[&str] = &[
+    "-x",
+    "--exclude-task",
+    "-p",
+    "--project-dir",
+    "-b",
+    "--build-file",
+    "-c",
+    "--settings-file",
+    "-g",
+    "--gradle-user-home",
+    "-I",
+    "--init-script",
+    "-D",
+    "--system-prop",
+    "-P",
+    "--project-prop",
+    "--console",
+    "--warning-mode",
+    "--max-workers",
+    "--priority",
+    "--include-build",
+    "--gradle-version",
+    "--distribution-type",
+    "--distribution-url",
+    "--tests",
+];
+
+/// gradle tasks that publish, by name prefix.
+const GRADLE_PUBLISH: &[&str] = &["publish", "release", "deploy", "upload"];
+
+/// mvn options that consume the next word.
+const MAVEN_VALUES: &[&str] = &[
+    "-f",
+    "--file",
+    "-s",
+    "--settings",
+    "-gs",
+    "--global-settings",
+    "-t",
+    "--toolchains",
+    "-pl",
+    "--projects",
+    "-P",
+    "--activate-profiles",
+    "-rf",
+    "--resume-from",
+    "-T",
+    "--threads",
+    "-D",
+    "--define",
+    "-l",
+    "--log-file",
+    "-b",
+    "--builder",
+];
+
+/// mvn goals that publish: the `deploy` phase, and the deploy and release
+/// plugins.
+const MAVEN_PUBLISH: &[&str] = &["deploy", "site-deploy", "deploy:", "release:"];
+
+/// The tasks or goals on a command line, and the options around them
+/// (skipping the values of `values`).
+fn tasks<'a>(ctx: &'a CommandContext, values: &[&str]) -> (Vec<&'a str>, Vec<&'a str>) {
+    let mut tasks = Vec::new();
+    let mut flags = Vec::new();
+    let mut args = ctx.args().iter().map(|w| w.as_str());
+    while let Some(arg) = args.next() {
+        if arg.starts_with('-') && arg.len() > 1 {
+            if values.contains(&arg) {
+                args.next();
+            }
+            flags.push(arg);
+        } else {
+            tasks.push(arg);
+        }
+    }
+    (tasks, flags)
+}
+
+/// A task or goal's name: `:app:publish` runs gradle's `publish` task in
+/// project `app`. mvn goals keep their plugin prefix (`dependency:tree`).
+fn task_name(gradle: bool, task: &str) -> &str {
+    if gradle {
+        task.rsplit(':').next().unwrap_or(task)
+    } else {
+        task
+    }
+}
+
+/// gradle and mvn evaluator, for the tools and their wrapper scripts.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `--version`, `--help`, `gradle --dry-run` → ALLOW (ASK with
+///    redirection)
+/// 3. Each task or goal: `publish`/`deploy`/`release` → `[gradle]` or
+///    `[maven]` `publish`; `gradle wrapper --gradle-version` → ASK; the
+///    `read_only` list → ALLOW; anything else → the base decision
+/// 4. The strictest of those; every task read-only → ALLOW (ASK with
+///    redirection)
+pub struct JvmSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Tasks or goals that only build, test or report.
+    read_only: Vec<String>,
+    /// Decision for publishing artifacts.
+    publish: Decision,
+}
+
+impl JvmSpec {
+    /// Build a gradle or mvn spec from its flat-list decision and
+    /// configuration.
+    pub fn from_config(base: Decision, config: &JvmConfig) -> Self {
+        Self {
+            base,
+            read_only: config.read_only.clone(),
+            publish: config.publish,
+        }
+    }
+
+    /// The rule for one gradle task or mvn goal that is not read-only.
+    fn task(&self, ctx: &CommandContext, task: &str, flags: &[&str]) -> RuleMatch {
+        let tool = ctx.base_command.as_str();
+        let what = format!("{tool} {task}");
+        let gradle = matches!(tool, "gradle" | "gradlew");
+        let name = task_name(gradle, task);
+        let publishes = if gradle {
+            GRADLE_PUBLISH.iter().any(|p| name.starts_with(p))
+        } else {
+            MAVEN_PUBLISH
+                .iter()
+                .any(|p| name == *p || (p.ends_with(':') && name.starts_with(p)))
+        };
+        if publishes {
+            return match self.publish {
+                Decision::Ask => change(&what, None, Some("publishes artifacts")),
+                decision => decided(decision, &what, None),
+            };
+        }
+        if gradle
+            && name == "wrapper"
+            && let Some(flag) = ["--gradle-version", "--distribution-url"]
+                .iter()
+                .find(|f| flags.contains(f))
+        {
+            let note = "changes the Gradle version the wrapper downloads";
+            return change(&format!("{what} {flag}"), None, Some(note));
+        }
+        SimpleCommandSpec::new(self.base).evaluate(ctx)
+    }
+}
+
+impl CommandSpec for JvmSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        let tool = ctx.base_command.as_str();
+        let (gradle, values) = match tool {
+            "gradle" | "gradlew" => (true, GRADLE_VALUES),
+            "mvn" | "mvnw" => (false, MAVEN_VALUES),
+            _ => return base.evaluate(ctx),
+        };
+        let (tasks, flags) = tasks(ctx, values);
+        if let Some(flag) = ["--version", "-v", "--help", "-h", "--dry-run", "-m"]
+            .iter()
+            .find(|f| flags.contains(f))
+        {
+            // `mvn -v` prints the version; `gradle -m` runs nothing.
+            return query(ctx, &format!("{tool} {flag}"));
+        }
+        if tasks.is_empty() {
+            return base.evaluate(ctx);
+        }
+        let strictest = tasks
+            .iter()
+            .filter(|task| !self.read_only.iter().any(|r| r == task_name(gradle, task)))
+            .map(|task| self.task(ctx, task, &flags))
+            .reduce(|a, b| if b.decision > a.decision { b } else { a });
+        match strictest {
+            Some(rule) => rule,
+            None => query(ctx, &format!("{tool} {}", tasks.join(" "))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let config = Config::default_config();
+        let ctx = CommandContext::from_command(cmd);
+        let config = match ctx.base_command.as_str() {
+            "mvn" | "mvnw" => config.maven,
+            _ => config.gradle,
+        };
+        JvmSpec::from_config(Decision::Ask, &config).evaluate(&ctx)
+    }
+
+    #[test]
+    fn builds_and_reports_allow() {
+        for cmd in [
+            "gradle build",
+            "gradle clean test --tests com.example.FooTest",
+            "./gradlew check -x spotlessCheck",
+            "./gradlew :app:build",
+            "gradle tasks --all",
+            "gradle --version",
+            "mvn test",
+            "mvn -B -pl core verify",
+            "./mvnw dependency:tree -Dverbose",
+            "mvn -q clean package -DskipTests",
+            "mvn -v",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(
+            eval("./gradlew clean build").reason,
+            "read-only gradlew clean build"
+        );
+        assert_eq!(eval("mvn verify > build.log").decision, Decision::Ask);
+    }
+
+    #[test]
+    fn publishing_asks() {
+        for cmd in [
+            "gradle publish",
+            "./gradlew build publishToMavenLocal",
+            "./gradlew :lib:publish",
+            "gradle release",
+            "mvn deploy",
+            "./mvnw clean deploy -P release",
+            "mvn release:prepare release:perform",
+            "mvn install",
+            "gradle bootRun",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("./gradlew build publish").reason,
+            "gradlew publish requires confirmation (publishes artifacts)"
+        );
+        assert_eq!(
+            eval("mvn release:perform").reason,
+            "mvn release:perform requires confirmation (publishes artifacts)"
+        );
+        assert_eq!(
+            eval("./gradlew wrapper --gradle-version 8.10").reason,
+            "gradlew wrapper --gradle-version requires confirmation \
+             (changes the Gradle version the wrapper downloads)"
+        );
+    }
+
+    #[test]
+    fn decisions_are_configurable() {
+        let config = JvmConfig {
+            read_only: vec!["test".into()],
+            publish: Decision::Deny,
+        };
+        let spec = JvmSpec::from_config(Decision::Ask, &config);
+        let eval = |cmd| spec.evaluate(&CommandContext::from_command(cmd));
+        assert_eq!(eval("gradle build").decision, Decision::Ask);
+        // The strictest task decides.
+        assert_eq!(
+            eval("gradle test publish build").reason,
+            "blocked command: gradle publish"
+        );
+        let spec = JvmSpec::from_config(Decision::Deny, &config);
+        let ctx = CommandContext::from_command("mvn test");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! Distinguishes read-only subcommands (get, describe, logs) from mutating ones
 //! (apply, delete, scale). Supports env-gated auto-allow for subcommands
-//! like `apply` when specific environment variables match.
+//! like `apply` when specific environment variables match. Reading a
+//! sensitive resource (`kubectl get secret -o yaml`) asks, though `get` is
+//! read-only: the output is the credential.
 
 use super::super::CommandSpec;
 use crate::config::KubectlConfig;
@@ -10,13 +12,22 @@ use crate::eval::{CommandContext, Decision, RuleMatch};
 use agent_shell_parser::parse::Word;
 use std::collections::HashMap;
 
+/// Mutating subcommands that accept `--dry-run=server` for a preview.
+const DRY_RUN_SUBCOMMANDS: &[&str] = &[
+    "apply", "create", "delete", "patch", "replace", "run", "expose", "scale",
+];
+
+/// Read-only subcommands that print a resource's contents.
+pub const SENSITIVE_READS: &[&str] = &["get", "describe"];
+
 /// Subcommand-aware kubectl evaluator.
 ///
 /// Evaluation order:
-/// 1. Read-only subcommands → ALLOW (with redirection escalation)
-/// 2. Env-gated subcommands → ALLOW if all `config_env` entries match, else ASK
-/// 3. Known mutating subcommands → ASK
-/// 4. Everything else → ASK
+/// 1. `get`/`describe` of a sensitive resource (`secrets`) → ASK
+/// 2. Read-only subcommands → ALLOW (with redirection escalation)
+/// 3. Env-gated subcommands → ALLOW if all `config_env` entries match, else ASK
+/// 4. Known mutating subcommands → ASK
+/// 5. Everything else → ASK
 pub struct KubectlSpec {
     /// Subcommands that are always allowed (e.g. `get`, `describe`, `logs`).
     read_only: Vec<String>,
@@ -26,6 +37,8 @@ pub struct KubectlSpec {
     allowed_with_config: Vec<String>,
     /// Required env var name→value pairs that gate `allowed_with_config` subcommands.
     config_env: HashMap<String, String>,
+    /// Resources whose `get`/`describe` asks (e.g. `secrets`).
+    sensitive_resources: Vec<String>,
 }
 
 impl KubectlSpec {
@@ -36,9 +49,31 @@ impl KubectlSpec {
             mutating: config.mutating.clone(),
             allowed_with_config: config.allowed_with_config.clone(),
             config_env: config.config_env.clone(),
+            sensitive_resources: config.sensitive_resources.clone(),
         }
     }
 
+    /// The sensitive resource an operand names, if one does. Operands are
+    /// matched as `secrets`, `secret`, `secret/name`, `secrets.v1` or a
+    /// comma-separated list of those.
+    fn sensitive_resource(&self, ctx: &CommandContext) -> Option<&str> {
+        let singular = |name: &str| name.to_ascii_lowercase().trim_end_matches('s').to_string();
+        let operands = ctx.words.iter().skip_while(|w| *w != "kubectl").skip(1);
+        operands
+            .filter(|w| !w.is_flag())
+            .flat_map(|w| w.as_str().split(','))
+            .map(|resource| {
+                let kind = resource.split('/').next().unwrap_or(resource);
+                singular(kind.split('.').next().unwrap_or(kind))
+            })
+            .find_map(|kind| {
+                self.sensitive_resources
+                    .iter()
+                    .find(|entry| singular(entry) == kind)
+            })
+            .map(String::as_str)
+    }
+
     /// Extract the kubectl subcommand (first non-flag word after "kubectl").
     /// Handles env var prefixes like `KUBECONFIG=~/.kube/staging kubectl apply`.
     fn subcommand(ctx: &CommandContext) -> Option<&Word> {
@@ -63,6 +98,15 @@ impl CommandSpec for KubectlSpec {
     fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
         let sub_str: &str = Self::subcommand(ctx).map(|w| w.as_str()).unwrap_or("?");
 
+        if SENSITIVE_READS.contains(&sub_str)
+            && let Some(resource) = self.sensitive_resource(ctx)
+        {
+            return RuleMatch {
+                decision: Decision::Ask,
+                reason: format!("kubectl {sub_str} {resource} requires confirmation"),
+            };
+        }
+
         if self.read_only.iter().any(|s| s == sub_str) {
             if let Some(ref r) = ctx.redirection {
                 return RuleMatch {
@@ -108,6 +152,27 @@ impl CommandSpec for KubectlSpec {
             reason: format!("kubectl {sub_str} requires confirmation"),
         }
     }
+
+    /// Preview mutating subcommands with `--dry-run=server`. Skipped when a
+    /// dry run is already requested or a `--` separator would swallow the flag.
+    fn suggest(&self, ctx: &CommandContext) -> Option<String> {
+        let sub = Self::subcommand(ctx)?;
+        if !DRY_RUN_SUBCOMMANDS.contains(&sub.as_str())
+            || ctx
+                .words
+                .iter()
+                .any(|w| w == "--" || w.as_str().starts_with("--dry-run"))
+        {
+            return None;
+        }
+        let preview = CommandContext::join_words(
+            ctx.words
+                .iter()
+                .map(Word::as_str)
+                .chain(["--dry-run=server"]),
+        );
+        Some(format!("preview first with `{preview}`"))
+    }
 }
 
 #[cfg(test)]
@@ -135,6 +200,27 @@ mod tests {
         s.evaluate(&ctx).decision
     }
 
+    fn suggest(cmd: &str) -> Option<String> {
+        let ctx = CommandContext::from_command(cmd);
+        spec().suggest(&ctx)
+    }
+
+    #[test]
+    fn suggest_server_dry_run() {
+        assert_eq!(
+            suggest("kubectl apply -f deploy.yaml").as_deref(),
+            Some("preview first with `kubectl apply -f deploy.yaml --dry-run=server`")
+        );
+        assert!(suggest("kubectl delete pod web-0").is_some());
+    }
+
+    #[test]
+    fn no_suggestion_when_already_dry_run_or_read_only() {
+        assert_eq!(suggest("kubectl apply -f x.yaml --dry-run=client"), None);
+        assert_eq!(suggest("kubectl get pods"), None);
+        assert_eq!(suggest("kubectl run tmp --image=busybox -- sh"), None);
+    }
+
     #[test]
     fn allow_get() {
         assert_eq!(eval("kubectl get pods"), Decision::Allow);
@@ -165,6 +251,39 @@ mod tests {
         assert_eq!(eval("kubectl get pods > pods.txt"), Decision::Ask);
     }
 
+    #[test]
+    fn secret_reads_ask() {
+        for cmd in [
+            "kubectl get secret db-creds -o yaml",
+            "kubectl get secrets -A",
+            "kubectl -n prod get secret/db-creds -o jsonpath='{.data.password}'",
+            "kubectl describe secrets",
+            "kubectl get pods,secrets",
+            "kubectl get Secrets.v1",
+        ] {
+            assert_eq!(eval(cmd), Decision::Ask, "{cmd}");
+        }
+        let ctx = CommandContext::from_command("kubectl get secret db-creds -o yaml");
+        assert_eq!(
+            spec().evaluate(&ctx).reason,
+            "kubectl get secrets requires confirmation"
+        );
+        assert_eq!(eval("kubectl get pods -o yaml"), Decision::Allow);
+        assert_eq!(eval("kubectl explain secrets"), Decision::Allow);
+    }
+
+    #[test]
+    fn sensitive_resources_are_configurable() {
+        let config = KubectlConfig {
+            sensitive_resources: vec!["configmaps".into()],
+            ..Config::default_config().kubectl
+        };
+        let spec = KubectlSpec::from_config(&config);
+        let eval = |cmd| spec.evaluate(&CommandContext::from_command(cmd)).decision;
+        assert_eq!(eval("kubectl get configmap app -o yaml"), Decision::Ask);
+        assert_eq!(eval("kubectl get secrets"), Decision::Allow);
+    }
+
     // ── Env-gated commands ──
 
     fn spec_with_env_gate() -> KubectlSpec {
@@ -173,6 +292,7 @@ mod tests {
             mutating: vec!["delete".into()],
             allowed_with_config: vec!["apply".into(), "rollout".into()],
             config_env: HashMap::from([("KUBECONFIG".into(), "~/.kube/config.ai".into())]),
+            sensitive_resources: vec!["secrets".into()],
         })
     }
 
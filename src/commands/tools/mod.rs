@@ -4,11 +4,61 @@
 //! logic: subcommand extraction, read-only vs mutating classification,
 //! env-gated auto-allow, and redirection escalation.
 
+/// User and group account evaluation (passwd -S → allow, useradd and usermod -aG → `[accounts] change`).
+pub mod accounts;
+/// Ansible evaluation (--check and -m setup → allow, playbook runs → `[ansible] run`, --become → `privileged`).
+pub mod ansible;
+/// AWS CLI evaluation (s3 ls → allow, s3 cp by direction, s3 rb → `[aws] s3_destructive`, iam and sts → `[aws] identity`).
+pub mod aws;
+/// Bazel evaluation (query and build → allow, run → `[bazel] run`, clean --expunge and mod tidy → ask).
+pub mod bazel;
 /// Subcommand-aware cargo evaluation (build → allow, install → ask, etc.).
 pub mod cargo;
+/// Destination-aware cp, mv, and install evaluation (system directories → deny).
+pub mod copy;
+/// gpg and openssl evaluation (--verify and x509 -text → allow, key generation → `[crypto] key_material`, secret-key export → `secret_export`).
+pub mod crypto;
+/// curl evaluation with domain, method, and output-file classification.
+pub mod curl;
+/// Container CLI evaluation (ps → allow, pull and stop → ask, system prune → `[docker] destructive`).
+pub mod docker;
 /// Subcommand-aware GitHub CLI evaluation (pr list → allow, pr create → ask, etc.).
 pub mod gh;
 /// Subcommand-aware git evaluation with env-gating and force-push detection.
 pub mod git;
+/// Interpreter evaluation (python, node, ruby, perl, awk) with inline code scanning.
+pub mod interpreter;
+/// gradle and mvn evaluation, wrapper scripts included (build and test → allow, publish and deploy → `publish`).
+pub mod jvm;
 /// Subcommand-aware kubectl evaluation (get → allow, apply → ask, etc.).
 pub mod kubectl;
+/// Network configuration evaluation (ip addr → allow, ip route add → ask, iptables, nft, ufw and firewall-cmd by `[firewall]`).
+pub mod network_config;
+/// Nix CLI evaluation (nix eval → allow, nix build → ask, nix run by flake source).
+pub mod nix;
+/// System package managers (apt list → allow, apt install → ask, etc.).
+pub mod packages;
+/// Target-aware chmod/chown/chgrp evaluation (recursive system-root changes → deny).
+pub mod permissions;
+/// pip evaluation with package-index domain checks.
+pub mod pip;
+/// Raw network tool evaluation (nc, socat, telnet: client vs listener vs exec).
+pub mod raw_network;
+/// Target-aware rm evaluation (root, home, shallow directories → deny).
+pub mod rm;
+/// rsync evaluation with destructive-flag and remote-destination escalation.
+pub mod rsync;
+/// Direction-aware scp evaluation (upload vs download, sensitive paths).
+pub mod scp;
+/// SSH key tool evaluation (ssh-keygen -l and ssh-add -l → allow, key generation and ssh-copy-id → `[ssh_keys] change`).
+pub mod ssh_keys;
+/// systemd tool evaluation (hostnamectl status → allow, set-* → ask, journalctl --vacuum-* → ask).
+pub mod systemd;
+/// Path-aware tee evaluation (sandbox → allow, protected → deny, `sudo tee`).
+pub mod tee;
+/// Terraform and OpenTofu evaluation (plan → allow, apply → ask, state surgery by `[terraform]`).
+pub mod terraform;
+/// svn and hg evaluation (status and log → allow, commit and push → `[vcs] change`, hg strip and svn revert → `destructive`).
+pub mod vcs;
+/// wget evaluation with domain, method, and output-file classification.
+pub mod wget;
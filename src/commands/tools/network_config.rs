@@ -0,0 +1,555 @@
+//! Network configuration evaluation: ip, ss, ifconfig, iptables, nft, and
+//! the ufw and firewall-cmd frontends.
+//!
+//! Showing addresses, routes, links and sockets is allowed. Changing an
+//! interface or route asks. Firewall rule changes and flushes get the
+//! `[firewall]` decisions, so rewriting the firewall is never left to the
+//! unrecognized-command fallback.
+
+use super::super::CommandSpec;
+use super::packages::{change, decided, query};
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::FirewallConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// ip options that consume a value. ip options take one dash, even the
+/// long ones.
+const IP_VALUE_OPTIONS: &[&str] = &[
+    "-n", "-netns", "-f", "-family", "-l", "-loops", "-rc", "-rcvbuf", "-b", "-batch",
+];
+
+/// ip actions that only read. ip accepts any prefix of an action.
+const IP_READS: &[&str] = &["show", "list", "lst", "get", "help", "identify", "pids"];
+
+/// ss flags that consume a value.
+const SS_FLAGS: FlagSchema = FlagSchema {
+    short_value: "fAFN",
+    long_value: &["--family", "--query", "--socket", "--filter", "--net"],
+};
+
+/// iptables and ip6tables flags that consume a value.
+const IPTABLES_FLAGS: FlagSchema = FlagSchema {
+    short_value: "tjsdpiomg",
+    long_value: &[
+        "--table",
+        "--jump",
+        "--goto",
+        "--source",
+        "--destination",
+        "--protocol",
+        "--in-interface",
+        "--out-interface",
+        "--match",
+        "--wait",
+    ],
+};
+
+/// iptables commands that only read the rules.
+const IPTABLES_QUERIES: &[&str] = &["-L", "--list", "-S", "--list-rules", "-C", "--check"];
+
+/// iptables commands that change rules or chains.
+const IPTABLES_CHANGES: &[&str] = &[
+    "-A",
+    "--append",
+    "-D",
+    "--delete",
+    "-I",
+    "--insert",
+    "-R",
+    "--replace",
+    "-N",
+    "--new-chain",
+    "-X",
+    "--delete-chain",
+    "-P",
+    "--policy",
+    "-E",
+    "--rename-chain",
+    "-Z",
+    "--zero",
+];
+
+/// iptables commands that flush rules.
+const IPTABLES_FLUSH: &[&str] = &["-F", "--flush"];
+
+/// nft flags that consume a value.
+const NFT_FLAGS: FlagSchema = FlagSchema {
+    short_value: "fID",
+    long_value: &["--file", "--includepath", "--define"],
+};
+
+/// nft commands that only read.
+const NFT_QUERIES: &[&str] = &["list", "monitor", "describe", "help"];
+
+/// nft commands that change the ruleset.
+const NFT_CHANGES: &[&str] = &[
+    "add", "insert", "create", "replace", "delete", "destroy", "rename", "reset", "import",
+];
+
+/// ufw commands that only read.
+const UFW_QUERIES: &[&str] = &["status", "show", "version", "help", "app list", "app info"];
+
+/// ufw commands that turn the firewall off or drop every rule.
+const UFW_FLUSH: &[&str] = &["disable", "reset"];
+
+/// ufw commands that change rules or settings.
+const UFW_CHANGES: &[&str] = &[
+    "enable",
+    "reload",
+    "default",
+    "logging",
+    "allow",
+    "deny",
+    "reject",
+    "limit",
+    "delete",
+    "insert",
+    "prepend",
+    "route",
+    "app update",
+    "app default",
+];
+
+/// firewall-cmd option prefixes that only read.
+const FIREWALLD_QUERIES: &[&str] = &[
+    "--state",
+    "--get-",
+    "--list-",
+    "--query-",
+    "--info-",
+    "--check-config",
+    "--version",
+    "--help",
+];
+
+/// firewall-cmd option prefixes that change the configuration.
+const FIREWALLD_CHANGES: &[&str] = &[
+    "--add-",
+    "--remove-",
+    "--set-",
+    "--new-",
+    "--delete-",
+    "--change-",
+    "--reload",
+    "--complete-reload",
+    "--runtime-to-permanent",
+    "--reset-to-defaults",
+    "--panic-",
+    "--lockdown-",
+    "--passthrough",
+];
+
+/// Network configuration evaluator: ip, ss, ifconfig, iptables,
+/// ip6tables, nft, ufw and firewall-cmd.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `ip` show/list/get, `ss`, `ifconfig` with at most an interface,
+///    `iptables -L`/`-S`/`-C`, `iptables-save`, `nft list`, `ufw status`,
+///    `firewall-cmd --list-*`/`--get-*` → ALLOW (ASK with redirection)
+/// 3. `iptables -F`, `nft flush`, `ufw disable`/`reset` → `[firewall]
+///    flush`
+/// 4. Other iptables, nft, ufw and firewall-cmd changes,
+///    `iptables-restore`, `nft -f` → `[firewall] rule_change`
+/// 5. `ip` changes, `ip -batch`, `ip netns exec`, `ss -K`, `ifconfig`
+///    with settings → ASK
+/// 6. Everything else → the base decision
+pub struct NetworkConfigSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Decision for adding, deleting or replacing firewall rules.
+    rule_change: Decision,
+    /// Decision for flushing firewall rules.
+    flush: Decision,
+}
+
+impl NetworkConfigSpec {
+    /// Build a spec from the command's flat-list decision and the
+    /// `[firewall]` configuration.
+    pub fn from_config(base: Decision, config: &FirewallConfig) -> Self {
+        Self {
+            base,
+            rule_change: config.rule_change,
+            flush: config.flush,
+        }
+    }
+
+    fn evaluate_ip(&self, ctx: &CommandContext) -> RuleMatch {
+        let mut operands = Vec::new();
+        let mut batch = false;
+        let mut args = ctx.args().iter().map(|w| w.as_str());
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') {
+                operands.push(arg);
+                continue;
+            }
+            batch |= matches!(arg, "-b" | "-batch" | "-force");
+            if IP_VALUE_OPTIONS.contains(&arg) {
+                args.next();
+            }
+        }
+        if batch {
+            return change("ip -batch", None, Some("runs the ip commands in a file"));
+        }
+        let Some((object, rest)) = operands.split_first() else {
+            return query(ctx, "ip");
+        };
+        let Some(action) = rest.first() else {
+            return query(ctx, &format!("ip {object}"));
+        };
+        let what = format!("ip {object} {action}");
+        // `ip link s` is `set`, since link checks for it before `show`.
+        let reads = *object == "monitor"
+            || (IP_READS.iter().any(|read| read.starts_with(action))
+                && !(object.starts_with('l') && "set".starts_with(action)));
+        if reads {
+            return query(ctx, &what);
+        }
+        if *object == "netns" && "exec".starts_with(action) {
+            return change(&what, None, Some("runs a command in the namespace"));
+        }
+        change(&what, None, None)
+    }
+
+    fn evaluate_ss(&self, ctx: &CommandContext) -> RuleMatch {
+        let args = ParsedArgs::parse(ctx.args(), &SS_FLAGS);
+        if args.has_any(&["-K", "--kill"]) {
+            return change("ss --kill", None, Some("closes the matching sockets"));
+        }
+        query(ctx, "ss")
+    }
+
+    fn evaluate_ifconfig(&self, ctx: &CommandContext) -> RuleMatch {
+        let args = ParsedArgs::parse(
+            ctx.args(),
+            &FlagSchema {
+                short_value: "",
+                long_value: &[],
+            },
+        );
+        match args.positionals.as_slice() {
+            [] => query(ctx, "ifconfig"),
+            [interface] => query(ctx, &format!("ifconfig {interface}")),
+            [interface, ..] => change(&format!("ifconfig {interface}"), None, None),
+        }
+    }
+
+    fn evaluate_iptables(&self, ctx: &CommandContext) -> Option<RuleMatch> {
+        let cmd = &ctx.base_command;
+        let args = ParsedArgs::parse(ctx.args(), &IPTABLES_FLAGS);
+        if cmd.ends_with("-save") {
+            return Some(query(ctx, cmd));
+        }
+        if cmd.ends_with("-restore") {
+            if args.has_any(&["-t", "--test"]) {
+                return Some(query(ctx, &format!("{cmd} --test")));
+            }
+            let mut result = decided(self.rule_change, cmd, None);
+            if result.decision == Decision::Ask && !args.has_any(&["-n", "--noflush"]) {
+                result = change(cmd, None, Some("replaces the rules of each table it loads"));
+            }
+            return Some(result);
+        }
+        let operation = args.flags.iter().map(|f| f.name.as_str()).find(|name| {
+            [IPTABLES_QUERIES, IPTABLES_CHANGES, IPTABLES_FLUSH]
+                .iter()
+                .any(|list| list.contains(name))
+        })?;
+        let what = format!("{cmd} {operation}");
+        Some(if IPTABLES_QUERIES.contains(&operation) {
+            query(ctx, &what)
+        } else if IPTABLES_FLUSH.contains(&operation) {
+            decided(self.flush, &what, None)
+        } else {
+            decided(self.rule_change, &what, None)
+        })
+    }
+
+    fn evaluate_nft(&self, ctx: &CommandContext) -> Option<RuleMatch> {
+        let args = ParsedArgs::parse(ctx.args(), &NFT_FLAGS);
+        if args.has_any(&["-f", "--file"]) {
+            if args.has_any(&["-c", "--check"]) {
+                return Some(query(ctx, "nft --check"));
+            }
+            return Some(decided(self.rule_change, "nft -f", None));
+        }
+        if args.has_any(&["-i", "--interactive"]) {
+            return Some(change("nft -i", None, None));
+        }
+        // One argument can hold several commands: `nft 'add table x; flush ruleset'`.
+        let line = args.positionals.join(" ");
+        line.split([';', '\n'])
+            .filter_map(|command| command.split_whitespace().next())
+            .map(|verb| {
+                let what = format!("nft {verb}");
+                if NFT_QUERIES.contains(&verb) {
+                    Some(query(ctx, &what))
+                } else if verb == "flush" {
+                    Some(decided(self.flush, &what, None))
+                } else if NFT_CHANGES.contains(&verb) {
+                    Some(decided(self.rule_change, &what, None))
+                } else {
+                    None
+                }
+            })
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .max_by_key(|result| result.decision)
+    }
+
+    fn evaluate_ufw(&self, ctx: &CommandContext) -> Option<RuleMatch> {
+        let args = ParsedArgs::parse(
+            ctx.args(),
+            &FlagSchema {
+                short_value: "",
+                long_value: &[],
+            },
+        );
+        let words = &args.positionals;
+        let sub = words.first()?;
+        let two = words.get(1).map(|second| format!("{sub} {second}"));
+        let entry = two
+            .as_deref()
+            .filter(|two| two.starts_with("app "))
+            .unwrap_or(sub);
+        let what = format!("ufw {entry}");
+        if UFW_QUERIES.contains(&entry) {
+            return Some(query(ctx, &what));
+        }
+        let decision = if UFW_FLUSH.contains(&entry) {
+            self.flush
+        } else if UFW_CHANGES.contains(&entry) {
+            self.rule_change
+        } else {
+            return None;
+        };
+        if args.has_any(&["--dry-run"]) {
+            return Some(query(ctx, &format!("{what} --dry-run")));
+        }
+        Some(decided(decision, &what, None))
+    }
+
+    fn evaluate_firewall_cmd(&self, ctx: &CommandContext) -> Option<RuleMatch> {
+        let args = ParsedArgs::parse(
+            ctx.args(),
+            &FlagSchema {
+                short_value: "",
+                long_value: &[],
+            },
+        );
+        let matching = |prefixes: &[&str]| {
+            args.flags
+                .iter()
+                .map(|f| f.name.as_str())
+                .find(|name| prefixes.iter().any(|p| name.starts_with(p)))
+        };
+        if let Some(option) = matching(FIREWALLD_CHANGES) {
+            return Some(decided(
+                self.rule_change,
+                &format!("firewall-cmd {option}"),
+                None,
+            ));
+        }
+        matching(FIREWALLD_QUERIES).map(|option| query(ctx, &format!("firewall-cmd {option}")))
+    }
+}
+
+impl CommandSpec for NetworkConfigSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        let result = match ctx.base_command.as_str() {
+            "ip" => Some(self.evaluate_ip(ctx)),
+            "ss" => Some(self.evaluate_ss(ctx)),
+            "ifconfig" => Some(self.evaluate_ifconfig(ctx)),
+            "nft" => self.evaluate_nft(ctx),
+            "ufw" => self.evaluate_ufw(ctx),
+            "firewall-cmd" => self.evaluate_firewall_cmd(ctx),
+            _ => self.evaluate_iptables(ctx),
+        };
+        result.unwrap_or_else(|| base.evaluate(ctx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let spec =
+            NetworkConfigSpec::from_config(Decision::Ask, &Config::default_config().firewall);
+        spec.evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn queries_allow() {
+        for cmd in [
+            "ip addr",
+            "ip a",
+            "ip addr show dev eth0",
+            "ip -br -c a s",
+            "ip -4 route show table main",
+            "ip route get 1.1.1.1",
+            "ip -j link show",
+            "ip -n blue link list",
+            "ip netns",
+            "ip monitor",
+            "ss -tlnp",
+            "ss -f inet state established",
+            "ifconfig",
+            "ifconfig -a",
+            "ifconfig eth0",
+            "iptables -L",
+            "iptables -nvL INPUT",
+            "iptables -t nat -S",
+            "ip6tables --list-rules",
+            "iptables-save",
+            "nft list ruleset",
+            "nft -a list table inet filter",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(eval("ip route show").reason, "read-only ip route show");
+        assert_eq!(eval("iptables-save > rules.v4").decision, Decision::Ask);
+    }
+
+    #[test]
+    fn interface_and_route_changes_ask() {
+        for cmd in [
+            "ip link set eth0 down",
+            "ip link s eth0 up",
+            "ip route add default via 10.0.0.1",
+            "ip route del 10.0.0.0/8",
+            "ip addr flush dev eth0",
+            "ip -batch cmds.txt",
+            "ifconfig eth0 down",
+            "ifconfig eth0 10.0.0.2 netmask 255.0.0.0",
+            "ss -K dst 10.0.0.5",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("ip route add default via 10.0.0.1").reason,
+            "ip route add requires confirmation"
+        );
+        assert_eq!(
+            eval("ip netns exec blue bash").reason,
+            "ip netns exec requires confirmation (runs a command in the namespace)"
+        );
+    }
+
+    #[test]
+    fn firewall_changes_use_rule_change() {
+        for cmd in [
+            "iptables -A INPUT -p tcp --dport 22 -j ACCEPT",
+            "iptables -D INPUT 3",
+            "iptables -t nat -I PREROUTING -j DNAT --to 10.0.0.2",
+            "iptables -P INPUT ACCEPT",
+            "ip6tables -X",
+            "iptables-restore < rules.v4",
+            "nft add rule inet filter input tcp dport 22 accept",
+            "nft delete table inet filter",
+            "nft -f /etc/nftables.conf",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("iptables -A INPUT -j DROP").reason,
+            "iptables -A requires confirmation"
+        );
+        assert_eq!(
+            eval("iptables-restore rules.v4").reason,
+            "iptables-restore requires confirmation \
+             (replaces the rules of each table it loads)"
+        );
+        assert_eq!(
+            eval("iptables-restore --test rules.v4").decision,
+            Decision::Allow
+        );
+        assert_eq!(eval("nft -c -f ruleset.nft").decision, Decision::Allow);
+    }
+
+    #[test]
+    fn flushes_use_flush() {
+        for cmd in [
+            "iptables -F",
+            "iptables -t nat --flush",
+            "nft flush ruleset",
+            "nft 'add table inet x; flush ruleset'",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Deny, "{cmd}");
+        }
+        assert_eq!(eval("iptables -F").reason, "blocked command: iptables -F");
+    }
+
+    #[test]
+    fn firewall_frontends() {
+        for cmd in [
+            "ufw status verbose",
+            "ufw show added",
+            "ufw app list",
+            "ufw --dry-run allow 22/tcp",
+            "firewall-cmd --state",
+            "firewall-cmd --list-all",
+            "firewall-cmd --zone=public --list-ports",
+            "firewall-cmd --get-active-zones",
+            "firewall-cmd --query-port=22/tcp",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        for cmd in [
+            "ufw allow 22/tcp",
+            "ufw deny from 10.0.0.5",
+            "ufw delete 3",
+            "ufw --force enable",
+            "ufw default allow incoming",
+            "firewall-cmd --add-port=8080/tcp",
+            "firewall-cmd --permanent --zone=public --remove-service=ssh",
+            "firewall-cmd --reload",
+            "firewall-cmd --list-all --add-service=http",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("ufw allow 22").reason,
+            "ufw allow requires confirmation"
+        );
+        assert_eq!(
+            eval("firewall-cmd --permanent --add-port=8080/tcp").reason,
+            "firewall-cmd --add-port requires confirmation"
+        );
+        assert_eq!(eval("ufw disable").reason, "blocked command: ufw disable");
+        assert_eq!(eval("ufw --force reset").decision, Decision::Deny);
+        assert_eq!(eval("ufw frobnicate").reason, "ufw requires confirmation");
+    }
+
+    #[test]
+    fn firewall_decisions_are_configurable() {
+        let config = FirewallConfig {
+            rule_change: Decision::Deny,
+            flush: Decision::Ask,
+        };
+        let spec = NetworkConfigSpec::from_config(Decision::Ask, &config);
+        let decide = |cmd| spec.evaluate(&CommandContext::from_command(cmd)).decision;
+        assert_eq!(decide("iptables -A INPUT -j DROP"), Decision::Deny);
+        assert_eq!(decide("nft flush ruleset"), Decision::Ask);
+        assert_eq!(decide("nft list ruleset"), Decision::Allow);
+    }
+
+    #[test]
+    fn unknown_and_denied_use_base() {
+        assert_eq!(
+            eval("iptables --version").reason,
+            "iptables requires confirmation"
+        );
+        assert_eq!(eval("nft frobnicate").decision, Decision::Ask);
+        let spec =
+            NetworkConfigSpec::from_config(Decision::Deny, &Config::default_config().firewall);
+        let ctx = CommandContext::from_command("ip addr");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+}
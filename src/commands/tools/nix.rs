@@ -0,0 +1,485 @@
+//! Nix CLI evaluation: nix, nix-env and nix-collect-garbage.
+//!
+//! Evaluating, searching and inspecting the store are allowed; building,
+//! changing a profile and collecting garbage ask. `nix run` builds and
+//! runs a program from a flake, so its reason names where the flake comes
+//! from: the flake registry (`nixpkgs#hello`), a local path, or a remote
+//! host, which is checked against `[network]`.
+//!
+//! The base disposition comes from the flat `[commands]` lists: a denied
+//! nix stays denied, and an unknown subcommand gets the base decision.
+
+use agent_shell_parser::parse::Word;
+
+use super::super::CommandSpec;
+use super::packages::{change, query};
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::NetworkConfig;
+use crate::eval::network::{DomainPolicy, url_host};
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// nix flags that consume a value.
+const NIX_FLAGS: FlagSchema = FlagSchema {
+    short_value: "Ifjo",
+    long_value: &[
+        "--include",
+        "--file",
+        "--expr",
+        "--max-jobs",
+        "--cores",
+        "--store",
+        "--eval-store",
+        "--profile",
+        "--out-link",
+        "--log-format",
+        "--system",
+        "--builders",
+        "--priority",
+        "--inputs-from",
+        "--reference-lock-file",
+        "--output-lock-file",
+        "--experimental-features",
+        "--extra-experimental-features",
+    ],
+};
+
+/// nix flags that consume two values (`--arg NAME EXPR`).
+const NIX_PAIR_FLAGS: &[&str] = &[
+    "--option",
+    "--arg",
+    "--argstr",
+    "--override-input",
+    "--override-flake",
+];
+
+/// nix subcommands that only evaluate or read the store.
+const NIX_READ_ONLY: &[&str] = &[
+    "search",
+    "eval",
+    "log",
+    "path-info",
+    "why-depends",
+    "show-derivation",
+    "show-config",
+    "doctor",
+    "flake show",
+    "flake metadata",
+    "flake info",
+    "store ls",
+    "store cat",
+    "store path-info",
+    "store diff-closures",
+    "store ping",
+    "store info",
+    "store verify",
+    "nar ls",
+    "nar cat",
+    "derivation show",
+    "config show",
+    "registry list",
+    "profile list",
+    "profile history",
+    "profile diff-closures",
+    "hash file",
+    "hash path",
+    "hash convert",
+];
+
+/// nix subcommands that build, fetch, or change a profile or the store.
+const NIX_MUTATING: &[&str] = &[
+    "build",
+    "develop",
+    "shell",
+    "bundle",
+    "copy",
+    "repl",
+    "upgrade-nix",
+    "flake check",
+    "flake update",
+    "flake lock",
+    "flake init",
+    "flake new",
+    "profile install",
+    "profile add",
+    "profile remove",
+    "profile upgrade",
+    "profile rollback",
+    "profile wipe-history",
+    "store gc",
+    "store delete",
+    "store optimise",
+    "store repair",
+    "store add",
+    "registry add",
+    "registry remove",
+    "registry pin",
+];
+
+/// nix-env flags that consume a value.
+const NIX_ENV_FLAGS: FlagSchema = FlagSchema {
+    short_value: "fpI",
+    long_value: &["--file", "--profile", "--include", "--system-filter"],
+};
+
+/// nix-env operations that only read.
+const NIX_ENV_QUERIES: &[&str] = &["-q", "--query", "--list-generations", "--version"];
+
+/// nix-env operations that change a profile.
+const NIX_ENV_CHANGES: &[&str] = &[
+    "-i",
+    "--install",
+    "-e",
+    "--uninstall",
+    "-u",
+    "--upgrade",
+    "--set",
+    "--set-flag",
+    "--rollback",
+    "-G",
+    "--switch-generation",
+    "-S",
+    "--switch-profile",
+    "--delete-generations",
+];
+
+/// Flags that make nix-collect-garbage remove old profile generations.
+const GC_DELETE_OLD: &[&str] = &["-d", "--delete-old", "--delete-older-than"];
+
+/// Where `nix run` gets the flake it runs a program from.
+#[derive(Debug, PartialEq, Eq)]
+enum Source<'a> {
+    /// A flake registry name (`nixpkgs#hello`, `nixpkgs/nixos-24.05#jq`).
+    Registry(&'a str),
+    /// A local flake or a Nix expression (`.#app`, `--file default.nix`).
+    Local,
+    /// A flake fetched from a host (`github:owner/repo`, `git+https://...`).
+    Remote(&'a str),
+}
+
+/// Classify a flake reference (the part of an installable before `#`).
+fn source(flake: &str) -> Source<'_> {
+    if flake.is_empty()
+        || flake.starts_with(['.', '/', '~'])
+        || ["path:", "file:", "git+file:"]
+            .iter()
+            .any(|p| flake.starts_with(p))
+    {
+        return Source::Local;
+    }
+    let hosted = [
+        ("github:", "github.com"),
+        ("gitlab:", "gitlab.com"),
+        ("sourcehut:", "git.sr.ht"),
+    ];
+    if let Some((_, host)) = hosted.iter().find(|(p, _)| flake.starts_with(p)) {
+        return Source::Remote(host);
+    }
+    if flake.contains("://") {
+        return Source::Remote(url_host(flake).unwrap_or("?"));
+    }
+    let name = flake.strip_prefix("flake:").unwrap_or(flake);
+    Source::Registry(name.split('/').next().unwrap_or(name))
+}
+
+/// The args with two-value flags and their values removed, so neither
+/// value is read as an operand.
+fn without_pairs(args: &[Word]) -> Vec<Word> {
+    let mut kept = Vec::new();
+    let mut iter = args.iter();
+    while let Some(word) = iter.next() {
+        if word.as_str() == "--" {
+            kept.push(word.clone());
+            kept.extend(iter.by_ref().cloned());
+            break;
+        }
+        if NIX_PAIR_FLAGS.contains(&word.as_str()) {
+            iter.next();
+            iter.next();
+            continue;
+        }
+        kept.push(word.clone());
+    }
+    kept
+}
+
+/// The entry of `list` the subcommand words match: two words (`flake
+/// show`) before one.
+fn listed<'a>(list: &[&'a str], words: &[String]) -> Option<&'a str> {
+    let one = words.first()?;
+    let two = words.get(1).map(|second| format!("{one} {second}"));
+    list.iter()
+        .find(|entry| two.as_deref() == Some(**entry))
+        .or_else(|| list.iter().find(|entry| *entry == one))
+        .copied()
+}
+
+/// Nix evaluator: nix, nix-env and nix-collect-garbage.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `nix run` from a denied domain → DENY
+/// 3. `nix run` → ASK, naming the flake's source
+/// 4. Read-only subcommands (`search`, `eval`, `flake show`, `store ls`),
+///    `nix-env -q` → ALLOW
+/// 5. Builds, profile and store changes, `nix-env -i`/`-e`,
+///    `nix-collect-garbage` → ASK
+/// 6. Everything else → the base decision
+pub struct NixSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Flake host classification for `nix run`.
+    domains: DomainPolicy,
+}
+
+impl NixSpec {
+    /// Build a nix spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, network: &NetworkConfig) -> Self {
+        Self {
+            base,
+            domains: DomainPolicy::from_config(network),
+        }
+    }
+
+    fn evaluate_nix(&self, ctx: &CommandContext) -> Option<RuleMatch> {
+        let args = ParsedArgs::parse(&without_pairs(ctx.args()), &NIX_FLAGS);
+        let words = &args.positionals;
+        if words.first().is_some_and(|w| w == "run") {
+            return Some(self.evaluate_run(ctx, &args));
+        }
+        if let Some(entry) = listed(NIX_READ_ONLY, words) {
+            return Some(query(ctx, &format!("nix {entry}")));
+        }
+        let entry = listed(NIX_MUTATING, words)?;
+        if args.has_any(&["--dry-run"]) {
+            return Some(query(ctx, &format!("nix {entry} --dry-run")));
+        }
+        Some(change(&format!("nix {entry}"), None, None))
+    }
+
+    /// `nix run [installable] [-- args]`. The args go to the program; the
+    /// installable defaults to the flake in the current directory.
+    fn evaluate_run(&self, ctx: &CommandContext, args: &ParsedArgs) -> RuleMatch {
+        // Everything after `--` is in the positionals too.
+        let program_args = ctx
+            .args()
+            .iter()
+            .position(|w| w.as_str() == "--")
+            .map_or(0, |at| ctx.args().len() - at - 1);
+        let operands = &args.positionals[..args.positionals.len() - program_args];
+        let installable = operands.get(1).map(String::as_str);
+        let expression = args.has_any(&["-f", "--file", "--expr"]);
+        let target = installable.unwrap_or(".");
+        let what = format!("nix run {target}");
+        let from = if expression {
+            Source::Local
+        } else {
+            source(target.split('#').next().unwrap_or(target))
+        };
+        let note = match from {
+            Source::Registry(name) => format!("runs a package from the {name} registry flake"),
+            Source::Local if expression => "runs a program built from a Nix expression".into(),
+            Source::Local => "runs an app from a local flake".into(),
+            Source::Remote(host) if self.domains.is_denied(host) => {
+                return RuleMatch {
+                    decision: Decision::Deny,
+                    reason: format!("nix run fetches from denied domain {host}"),
+                };
+            }
+            Source::Remote(host) => format!("runs code fetched from {host}"),
+        };
+        change(&what, None, Some(&note))
+    }
+
+    fn evaluate_nix_env(&self, ctx: &CommandContext) -> Option<RuleMatch> {
+        let args = ParsedArgs::parse(ctx.args(), &NIX_ENV_FLAGS);
+        let first = |flags: &[&str]| {
+            args.flags
+                .iter()
+                .map(|f| f.name.clone())
+                .find(|name| flags.contains(&name.as_str()))
+        };
+        if let Some(action) = first(NIX_ENV_CHANGES) {
+            let what = format!("nix-env {action}");
+            if args.has_any(&["--dry-run"]) {
+                return Some(query(ctx, &format!("{what} --dry-run")));
+            }
+            return Some(change(&what, None, None));
+        }
+        first(NIX_ENV_QUERIES).map(|action| query(ctx, &format!("nix-env {action}")))
+    }
+
+    fn evaluate_gc(&self, ctx: &CommandContext) -> RuleMatch {
+        let args = ParsedArgs::parse(
+            ctx.args(),
+            &FlagSchema {
+                short_value: "",
+                long_value: &["--delete-older-than", "--max-freed"],
+            },
+        );
+        if args.has_any(&["--dry-run"]) {
+            return query(ctx, "nix-collect-garbage --dry-run");
+        }
+        let note = args
+            .has_any(GC_DELETE_OLD)
+            .then_some("deletes old profile generations, so they can't be rolled back to");
+        change("nix-collect-garbage", None, note)
+    }
+}
+
+impl CommandSpec for NixSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        let result = match ctx.base_command.as_str() {
+            "nix-env" => self.evaluate_nix_env(ctx),
+            "nix-collect-garbage" => Some(self.evaluate_gc(ctx)),
+            _ => self.evaluate_nix(ctx),
+        };
+        result.unwrap_or_else(|| base.evaluate(ctx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let spec = NixSpec::from_config(Decision::Ask, &Config::default_config().network);
+        spec.evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn queries_allow() {
+        for cmd in [
+            "nix search nixpkgs ripgrep",
+            "nix flake show",
+            "nix flake metadata github:NixOS/nixpkgs",
+            "nix eval .#packages.x86_64-linux.default.version",
+            "nix --extra-experimental-features 'nix-command flakes' eval --raw nixpkgs#hello.name",
+            "nix --option substitute false store ls /nix/store/abc-hello",
+            "nix path-info -rS nixpkgs#hello",
+            "nix-env -q",
+            "nix-env -qaP firefox",
+            "nix-env --list-generations",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(
+            eval("nix eval --json .#x > out.json").decision,
+            Decision::Ask
+        );
+        assert_eq!(eval("nix flake show").reason, "read-only nix flake show");
+    }
+
+    #[test]
+    fn changes_ask() {
+        for cmd in [
+            "nix build .#default",
+            "nix develop",
+            "nix profile install nixpkgs#hello",
+            "nix profile remove 3",
+            "nix flake update",
+            "nix store gc",
+            "nix-env -i hello",
+            "nix-env -iA nixpkgs.hello",
+            "nix-env -e hello",
+            "nix-env --rollback",
+            "nix-collect-garbage",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("nix profile install nixpkgs#hello").reason,
+            "nix profile install requires confirmation"
+        );
+        assert_eq!(
+            eval("nix-env -iA nixpkgs.hello").reason,
+            "nix-env -i requires confirmation"
+        );
+        assert_eq!(
+            eval("nix-collect-garbage -d").reason,
+            "nix-collect-garbage requires confirmation \
+             (deletes old profile generations, so they can't be rolled back to)"
+        );
+        assert_eq!(
+            eval("nix-collect-garbage --dry-run").decision,
+            Decision::Allow
+        );
+        assert_eq!(eval("nix-env -i --dry-run hello").decision, Decision::Allow);
+    }
+
+    #[test]
+    fn run_names_the_flake_source() {
+        assert_eq!(
+            eval("nix run nixpkgs#hello").reason,
+            "nix run nixpkgs#hello requires confirmation \
+             (runs a package from the nixpkgs registry flake)"
+        );
+        assert_eq!(
+            eval("nix run nixpkgs/nixos-24.05#jq -- -r .").reason,
+            "nix run nixpkgs/nixos-24.05#jq requires confirmation \
+             (runs a package from the nixpkgs registry flake)"
+        );
+        assert_eq!(
+            eval("nix run").reason,
+            "nix run . requires confirmation (runs an app from a local flake)"
+        );
+        assert_eq!(
+            eval("nix run .#serve -- --port 8080").reason,
+            "nix run .#serve requires confirmation (runs an app from a local flake)"
+        );
+        assert_eq!(
+            eval("nix run github:owner/repo#tool").reason,
+            "nix run github:owner/repo#tool requires confirmation \
+             (runs code fetched from github.com)"
+        );
+        assert_eq!(
+            eval("nix run -f default.nix hello").reason,
+            "nix run hello requires confirmation \
+             (runs a program built from a Nix expression)"
+        );
+    }
+
+    #[test]
+    fn run_from_denied_domain_denies() {
+        let mut network = Config::default_config().network;
+        network.denied_domains.push("git.example.com".into());
+        let spec = NixSpec::from_config(Decision::Ask, &network);
+        let result = spec.evaluate(&CommandContext::from_command(
+            "nix run git+https://git.example.com/x",
+        ));
+        assert_eq!(result.decision, Decision::Deny);
+        assert_eq!(
+            result.reason,
+            "nix run fetches from denied domain git.example.com"
+        );
+    }
+
+    #[test]
+    fn flake_sources() {
+        assert_eq!(source("nixpkgs"), Source::Registry("nixpkgs"));
+        assert_eq!(
+            source("flake:home-manager"),
+            Source::Registry("home-manager")
+        );
+        assert_eq!(source("./tools"), Source::Local);
+        assert_eq!(source("path:/src/app"), Source::Local);
+        assert_eq!(source("gitlab:group/project"), Source::Remote("gitlab.com"));
+        assert_eq!(
+            source("https://example.org/flake.tar.gz"),
+            Source::Remote("example.org")
+        );
+    }
+
+    #[test]
+    fn unknown_and_denied_use_base() {
+        assert_eq!(eval("nix fmt").reason, "nix requires confirmation");
+        let spec = NixSpec::from_config(Decision::Deny, &Config::default_config().network);
+        let ctx = CommandContext::from_command("nix search nixpkgs hello");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+}
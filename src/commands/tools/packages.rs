@@ -0,0 +1,679 @@
+//! System package manager evaluation.
+//!
+//! Queries of the package database are allowed; anything that changes the
+//! installed packages asks. A flag that answers the package manager's own
+//! confirmation prompt (`apt install -y`) is named in the reason, since
+//! the hook's prompt is then the only one left.
+//!
+//! The base disposition comes from the flat `[commands]` lists (the
+//! package managers are in `ask` on Linux): a denied package manager stays
+//! denied, and an unknown subcommand gets the base decision.
+
+use super::super::CommandSpec;
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::{AptConfig, DnfConfig};
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// apt, apt-get and apt-cache flags that consume a value.
+const APT_FLAGS: FlagSchema = FlagSchema {
+    short_value: "oct",
+    long_value: &[
+        "--option",
+        "--config-file",
+        "--target-release",
+        "--default-release",
+        "--host-architecture",
+        "--build-profiles",
+    ],
+};
+
+/// Flags that answer apt's confirmation prompt.
+const APT_YES: &[&str] = &["-y", "--yes", "--assume-yes", "--force-yes"];
+
+/// Flags that make apt only report what it would do.
+const APT_SIMULATE: &[&str] = &[
+    "-s",
+    "--simulate",
+    "--just-print",
+    "--dry-run",
+    "--recon",
+    "--no-act",
+];
+
+/// dpkg flags that consume a value.
+const DPKG_FLAGS: FlagSchema = FlagSchema {
+    short_value: "",
+    long_value: &["--admindir", "--root", "--instdir", "--log", "--status-fd"],
+};
+
+/// dpkg actions that only read the package database or a `.deb`.
+const DPKG_QUERIES: &[&str] = &[
+    "-l",
+    "--list",
+    "-L",
+    "--listfiles",
+    "-s",
+    "--status",
+    "-S",
+    "--search",
+    "-p",
+    "--print-avail",
+    "-c",
+    "--contents",
+    "-I",
+    "--info",
+    "-C",
+    "--audit",
+    "--get-selections",
+    "--print-architecture",
+    "--print-foreign-architectures",
+    "--compare-versions",
+    "--verify",
+    "-V",
+];
+
+/// dpkg actions that change the installed packages or dpkg's state.
+const DPKG_CHANGES: &[&str] = &[
+    "-i",
+    "--install",
+    "-r",
+    "--remove",
+    "-P",
+    "--purge",
+    "--unpack",
+    "--configure",
+    "--triggers-only",
+    "--set-selections",
+    "--clear-selections",
+    "--clear-avail",
+    "--update-avail",
+    "--merge-avail",
+    "--add-architecture",
+    "--remove-architecture",
+    "-B",
+    "--auto-deconfigure",
+];
+
+/// dnf, yum and microdnf flags that consume a value.
+const DNF_FLAGS: FlagSchema = FlagSchema {
+    short_value: "cdexR",
+    long_value: &[
+        "--config",
+        "--debuglevel",
+        "--errorlevel",
+        "--exclude",
+        "--excludepkgs",
+        "--installroot",
+        "--releasever",
+        "--repo",
+        "--repoid",
+        "--enablerepo",
+        "--disablerepo",
+        "--setopt",
+        "--forcearch",
+        "--downloaddir",
+        "--destdir",
+        "--comment",
+        "--color",
+        "--randomwait",
+    ],
+};
+
+/// Flags that answer dnf's confirmation prompt.
+const DNF_YES: &[&str] = &["-y", "--assumeyes"];
+
+/// `history` actions that replay or reverse earlier transactions.
+const DNF_HISTORY_REPLAY: &[&str] = &["undo", "redo", "rollback"];
+
+/// pacman, yay and paru flags that consume a value.
+const PACMAN_FLAGS: FlagSchema = FlagSchema {
+    short_value: "br",
+    long_value: &[
+        "--dbpath",
+        "--root",
+        "--arch",
+        "--cachedir",
+        "--config",
+        "--gpgdir",
+        "--hookdir",
+        "--logfile",
+        "--sysroot",
+        "--color",
+        "--ignore",
+        "--ignoregroup",
+        "--assume-installed",
+        "--overwrite",
+        "--print-format",
+    ],
+};
+
+/// pacman's long operation names, by their short letter.
+const PACMAN_OPERATIONS: &[(&str, char)] = &[
+    ("--database", 'D'),
+    ("--files", 'F'),
+    ("--query", 'Q'),
+    ("--remove", 'R'),
+    ("--sync", 'S'),
+    ("--deptest", 'T'),
+    ("--upgrade", 'U'),
+    ("--version", 'V'),
+    // yay and paru.
+    ("--getpkgbuild", 'G'),
+    ("--show", 'P'),
+    ("--build", 'B'),
+];
+
+/// Long modifiers, by the short letter they share under `-S`, `-F`, `-D`
+/// and `-R`/`-U` (`--print`).
+const PACMAN_MODIFIERS: &[(&str, char)] = &[
+    ("--search", 's'),
+    ("--info", 'i'),
+    ("--list", 'l'),
+    ("--groups", 'g'),
+    ("--print", 'p'),
+    ("--refresh", 'y'),
+    ("--sysupgrade", 'u'),
+    ("--clean", 'c'),
+    ("--downloadonly", 'w'),
+    ("--check", 'k'),
+];
+
+/// Allow a query, unless its output is redirected.
+pub(super) fn query(ctx: &CommandContext, what: &str) -> RuleMatch {
+    match &ctx.redirection {
+        Some(r) => RuleMatch {
+            decision: Decision::Ask,
+            reason: format!("{what} with {r}"),
+        },
+        None => RuleMatch {
+            decision: Decision::Allow,
+            reason: format!("read-only {what}"),
+        },
+    }
+}
+
+/// Ask for a change, naming the flag that skips the package manager's
+/// own prompt if one is given, and any other `note`.
+pub(super) fn change(what: &str, yes: Option<&str>, note: Option<&str>) -> RuleMatch {
+    let yes = yes.map(|flag| format!("{flag} skips the package manager's own prompt"));
+    let notes: Vec<&str> = yes.as_deref().into_iter().chain(note).collect();
+    let reason = if notes.is_empty() {
+        format!("{what} requires confirmation")
+    } else {
+        format!("{what} requires confirmation ({})", notes.join("; "))
+    };
+    RuleMatch {
+        decision: Decision::Ask,
+        reason,
+    }
+}
+
+/// `decision` for `what`, phrased like a flat-list decision.
+pub(super) fn decided(decision: Decision, what: &str, yes: Option<&str>) -> RuleMatch {
+    match decision {
+        Decision::Allow => RuleMatch {
+            decision,
+            reason: format!("allowed: {what}"),
+        },
+        Decision::Ask => change(what, yes, None),
+        Decision::Deny => RuleMatch {
+            decision,
+            reason: format!("blocked command: {what}"),
+        },
+    }
+}
+
+/// The entry of `list` the subcommand words match: two words (`group
+/// list`) before one.
+fn listed(list: &[String], words: &[String]) -> Option<String> {
+    let one = words.first()?;
+    let two = words.get(1).map(|second| format!("{one} {second}"));
+    two.filter(|two| list.contains(two))
+        .or_else(|| list.contains(one).then(|| one.clone()))
+}
+
+/// The first of `flags` given, as written.
+fn first_of<'a>(args: &'a ParsedArgs, flags: &[&str]) -> Option<&'a str> {
+    args.flags
+        .iter()
+        .map(|f| f.name.as_str())
+        .find(|name| flags.contains(name))
+}
+
+/// Debian package evaluator: apt, apt-get, apt-cache, dpkg and dpkg-query.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `[apt] read_only` subcommands, dpkg queries, dpkg-query → ALLOW
+/// 3. `[apt] mutating` subcommands with `-s`/`--simulate` → ALLOW
+/// 4. `[apt] mutating` subcommands, dpkg changes → ASK, noting `-y`
+/// 5. Everything else → the base decision
+pub struct AptSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// apt subcommands that only read (`list`, `show`, `download`).
+    read_only: Vec<String>,
+    /// apt subcommands that change the installed packages.
+    mutating: Vec<String>,
+}
+
+impl AptSpec {
+    /// Build an apt spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &AptConfig) -> Self {
+        Self {
+            base,
+            read_only: config.read_only.clone(),
+            mutating: config.mutating.clone(),
+        }
+    }
+
+    fn evaluate_apt(&self, ctx: &CommandContext) -> Option<RuleMatch> {
+        let args = ParsedArgs::parse(ctx.args(), &APT_FLAGS);
+        let sub = args.positionals.first()?;
+        let what = format!("{} {sub}", ctx.base_command);
+        if self.read_only.contains(sub) {
+            return Some(query(ctx, &what));
+        }
+        if !self.mutating.contains(sub) {
+            return None;
+        }
+        if sub != "update" && args.has_any(APT_SIMULATE) {
+            return Some(query(ctx, &format!("{what} --simulate")));
+        }
+        Some(change(&what, first_of(&args, APT_YES), None))
+    }
+
+    fn evaluate_dpkg(&self, ctx: &CommandContext) -> Option<RuleMatch> {
+        let args = ParsedArgs::parse(ctx.args(), &DPKG_FLAGS);
+        if let Some(action) = first_of(&args, DPKG_CHANGES) {
+            let what = format!("dpkg {action}");
+            if args.has_any(&["--no-act", "--dry-run", "--simulate"]) {
+                return Some(query(ctx, &format!("{what} --dry-run")));
+            }
+            return Some(change(&what, None, None));
+        }
+        first_of(&args, DPKG_QUERIES).map(|action| query(ctx, &format!("dpkg {action}")))
+    }
+}
+
+impl CommandSpec for AptSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        let result = match ctx.base_command.as_str() {
+            "dpkg" => self.evaluate_dpkg(ctx),
+            "dpkg-query" => Some(query(ctx, "dpkg-query")),
+            _ => self.evaluate_apt(ctx),
+        };
+        result.unwrap_or_else(|| base.evaluate(ctx))
+    }
+}
+
+/// Fedora and RHEL package evaluator: dnf, yum and microdnf.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `history undo`/`redo`/`rollback` → `[dnf] history_undo`
+/// 3. `[dnf] read_only` subcommands → ALLOW
+/// 4. `[dnf] mutating` subcommands with `--assumeno` → ALLOW
+/// 5. `[dnf] mutating` subcommands → ASK, noting `-y`
+/// 6. Everything else → the base decision
+pub struct DnfSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Subcommands that only read (`list`, `info`, `repoquery`).
+    read_only: Vec<String>,
+    /// Subcommands that change the installed packages.
+    mutating: Vec<String>,
+    /// Decision for replaying or reversing a transaction from history.
+    history_undo: Decision,
+}
+
+impl DnfSpec {
+    /// Build a dnf spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &DnfConfig) -> Self {
+        Self {
+            base,
+            read_only: config.read_only.clone(),
+            mutating: config.mutating.clone(),
+            history_undo: config.history_undo,
+        }
+    }
+
+    fn evaluate_subcommand(&self, ctx: &CommandContext) -> Option<RuleMatch> {
+        let args = ParsedArgs::parse(ctx.args(), &DNF_FLAGS);
+        let words = &args.positionals;
+        let yes = first_of(&args, DNF_YES);
+        if let [sub, action, ..] = words.as_slice()
+            && sub == "history"
+            && DNF_HISTORY_REPLAY.contains(&action.as_str())
+        {
+            let what = format!("{} history {action}", ctx.base_command);
+            return Some(decided(self.history_undo, &what, yes));
+        }
+        if let Some(entry) = listed(&self.read_only, words) {
+            return Some(query(ctx, &format!("{} {entry}", ctx.base_command)));
+        }
+        let entry = listed(&self.mutating, words)?;
+        let what = format!("{} {entry}", ctx.base_command);
+        if args.has_any(&["--assumeno"]) {
+            return Some(query(ctx, &format!("{what} --assumeno")));
+        }
+        Some(change(&what, yes, None))
+    }
+}
+
+impl CommandSpec for DnfSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        self.evaluate_subcommand(ctx)
+            .unwrap_or_else(|| base.evaluate(ctx))
+    }
+}
+
+/// Arch package evaluator: pacman, and the AUR helpers yay and paru.
+///
+/// pacman's operation is a flag (`-S`, `-Q`, `-R`), with modifiers in the
+/// same cluster (`-Syu`, `-Ss`). Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `-Q`, `-T`, `-V`, `-S` searches and info (`-Ss`, `-Si`, `-Sl`,
+///    `-Sg`), `-F` without `-y`, `-Dk`, and `--print` → ALLOW
+/// 3. `-S`, `-Sy`, `-Syu`, `-Sc`, `-R`, `-U`, `-D` → ASK, noting
+///    `--noconfirm`, and for an AUR helper that it builds PKGBUILDs
+/// 4. An AUR helper without an operation (`yay`, `yay foo`) → ASK
+/// 5. Everything else → the base decision
+pub struct PacmanSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+}
+
+impl PacmanSpec {
+    /// Build a pacman spec from its flat-list decision.
+    pub fn new(base: Decision) -> Self {
+        Self { base }
+    }
+
+    fn evaluate_operation(&self, ctx: &CommandContext) -> Option<RuleMatch> {
+        let args = ParsedArgs::parse(ctx.args(), &PACMAN_FLAGS);
+        let helper = ctx.base_command != "pacman";
+        let note = helper.then(|| {
+            format!(
+                "{} builds and runs PKGBUILDs from the AUR",
+                ctx.base_command
+            )
+        });
+        let yes = first_of(&args, &["--noconfirm"]);
+        let letters: Vec<char> = args
+            .flags
+            .iter()
+            .filter_map(|f| {
+                let name = f.name.as_str();
+                let mut long = PACMAN_OPERATIONS.iter().chain(PACMAN_MODIFIERS);
+                match long.find(|(l, _)| *l == name) {
+                    Some((_, c)) => Some(*c),
+                    None => name
+                        .strip_prefix('-')?
+                        .chars()
+                        .next()
+                        .filter(|_| name.len() == 2),
+                }
+            })
+            .collect();
+        let Some(op) = letters.iter().copied().find(char::is_ascii_uppercase) else {
+            // `yay` alone upgrades everything; `yay foo` searches and installs.
+            return helper.then(|| change(&ctx.base_command, yes, note.as_deref()));
+        };
+        let has = |c: char| letters.contains(&c);
+        let what = format!("{} -{op}", ctx.base_command);
+        let read_only = match op {
+            'Q' | 'T' | 'V' | 'G' | 'P' => true,
+            'S' => {
+                !has('y')
+                    && !has('u')
+                    && !has('c')
+                    && !has('w')
+                    && (has('p') || "silg".chars().any(has))
+            }
+            'F' => !has('y'),
+            'D' => has('k'),
+            'R' | 'U' => has('p'),
+            'B' => false,
+            _ => return None,
+        };
+        if read_only {
+            return Some(query(ctx, &what));
+        }
+        let modifiers: String = letters.iter().filter(|c| "yuc".contains(**c)).collect();
+        let what = format!("{what}{modifiers}");
+        Some(change(&what, yes, note.as_deref()))
+    }
+}
+
+impl CommandSpec for PacmanSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        self.evaluate_operation(ctx)
+            .unwrap_or_else(|| base.evaluate(ctx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let spec = AptSpec::from_config(Decision::Ask, &Config::default_config().apt);
+        spec.evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn queries_allow() {
+        for cmd in [
+            "apt list --installed",
+            "apt show curl",
+            "apt search ripgrep",
+            "apt-cache policy nginx",
+            "apt -o Debug::NoLocking=1 list",
+            "apt-get download curl",
+            "dpkg -l",
+            "dpkg -L coreutils",
+            "dpkg -s bash",
+            "dpkg-query -W -f '${Version}' bash",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(eval("apt list > pkgs.txt").decision, Decision::Ask);
+    }
+
+    #[test]
+    fn changes_ask() {
+        for cmd in [
+            "apt install curl",
+            "apt-get remove nginx",
+            "apt purge nginx",
+            "apt-get dist-upgrade",
+            "apt update",
+            "dpkg -i pkg.deb",
+            "dpkg --purge nginx",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("apt install curl").reason,
+            "apt install requires confirmation"
+        );
+        assert_eq!(
+            eval("dpkg -i pkg.deb").reason,
+            "dpkg -i requires confirmation"
+        );
+    }
+
+    #[test]
+    fn yes_is_noted() {
+        assert_eq!(
+            eval("apt-get install -y curl").reason,
+            "apt-get install requires confirmation (-y skips the package manager's own prompt)"
+        );
+        assert!(eval("apt-get -qy dist-upgrade").reason.contains("-y skips"));
+        assert!(
+            eval("apt remove --yes nginx")
+                .reason
+                .contains("--yes skips")
+        );
+    }
+
+    #[test]
+    fn simulation_allows() {
+        assert_eq!(eval("apt-get install -s curl").decision, Decision::Allow);
+        assert_eq!(eval("apt remove --dry-run nginx").decision, Decision::Allow);
+        assert_eq!(eval("apt-get -s update").decision, Decision::Ask);
+        assert_eq!(eval("dpkg --dry-run -i x.deb").decision, Decision::Allow);
+    }
+
+    #[test]
+    fn unknown_and_denied_use_base() {
+        assert_eq!(
+            eval("apt-get source curl").reason,
+            "apt-get requires confirmation"
+        );
+        assert_eq!(eval("apt").decision, Decision::Ask);
+        let spec = AptSpec::from_config(Decision::Deny, &Config::default_config().apt);
+        let ctx = CommandContext::from_command("apt list");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+
+    fn dnf(cmd: &str) -> RuleMatch {
+        let spec = DnfSpec::from_config(Decision::Ask, &Config::default_config().dnf);
+        spec.evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn dnf_queries_allow() {
+        for cmd in [
+            "dnf list installed",
+            "dnf info kernel",
+            "yum search ripgrep",
+            "dnf repoquery --requires curl",
+            "dnf --enablerepo updates-testing list",
+            "dnf group list",
+            "dnf history list",
+            "microdnf repolist",
+        ] {
+            assert_eq!(dnf(cmd).decision, Decision::Allow, "{cmd}");
+        }
+    }
+
+    #[test]
+    fn dnf_changes_ask() {
+        for cmd in [
+            "dnf install curl",
+            "yum remove nginx",
+            "dnf update",
+            "dnf swap ffmpeg-free ffmpeg",
+            "dnf group install 'Development Tools'",
+            "microdnf upgrade",
+        ] {
+            assert_eq!(dnf(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            dnf("dnf install -y curl").reason,
+            "dnf install requires confirmation (-y skips the package manager's own prompt)"
+        );
+        assert_eq!(
+            dnf("dnf group install x").reason,
+            "dnf group install requires confirmation"
+        );
+        assert_eq!(dnf("dnf install --assumeno curl").decision, Decision::Allow);
+    }
+
+    #[test]
+    fn dnf_history_undo_is_configurable() {
+        assert_eq!(
+            dnf("dnf history undo 42").reason,
+            "dnf history undo requires confirmation"
+        );
+        let mut config = Config::default_config().dnf;
+        config.history_undo = Decision::Deny;
+        let spec = DnfSpec::from_config(Decision::Ask, &config);
+        let result = spec.evaluate(&CommandContext::from_command("yum history redo last"));
+        assert_eq!(result.decision, Decision::Deny);
+        assert_eq!(result.reason, "blocked command: yum history redo");
+        // Reading history is still a query.
+        let result = spec.evaluate(&CommandContext::from_command("dnf history info 42"));
+        assert_eq!(result.decision, Decision::Allow);
+    }
+
+    fn pacman(cmd: &str) -> RuleMatch {
+        PacmanSpec::new(Decision::Ask).evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn pacman_queries_allow() {
+        for cmd in [
+            "pacman -Q",
+            "pacman -Qdtq",
+            "pacman -Qi linux",
+            "pacman -Ss ripgrep",
+            "pacman -Si firefox",
+            "pacman --sync --search vim",
+            "pacman -Fl coreutils",
+            "pacman -Sp firefox",
+            "pacman -Rp firefox",
+            "pacman -T 'python>=3'",
+            "yay -Ss neovim-git",
+            "paru -G neovim-git",
+        ] {
+            assert_eq!(pacman(cmd).decision, Decision::Allow, "{cmd}");
+        }
+    }
+
+    #[test]
+    fn pacman_changes_ask() {
+        for cmd in [
+            "pacman -S firefox",
+            "pacman -Syu",
+            "pacman -Sy",
+            "pacman -Scc",
+            "pacman -Rns firefox",
+            "pacman -U pkg.tar.zst",
+            "pacman -Fy",
+            "pacman --sync --refresh --sysupgrade",
+            "pacman -D --asdeps foo",
+        ] {
+            assert_eq!(pacman(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            pacman("pacman -Syu").reason,
+            "pacman -Syu requires confirmation"
+        );
+        assert_eq!(
+            pacman("pacman -S --noconfirm firefox").reason,
+            "pacman -S requires confirmation (--noconfirm skips the package manager's own prompt)"
+        );
+    }
+
+    #[test]
+    fn aur_helpers_note_pkgbuilds() {
+        assert_eq!(
+            pacman("yay -S neovim-git").reason,
+            "yay -S requires confirmation (yay builds and runs PKGBUILDs from the AUR)"
+        );
+        assert_eq!(pacman("yay").decision, Decision::Ask);
+        assert!(
+            pacman("paru --noconfirm foo")
+                .reason
+                .contains("--noconfirm skips")
+        );
+        assert_eq!(pacman("paru -B .").decision, Decision::Ask);
+    }
+}
@@ -0,0 +1,180 @@
+//! Target-aware chmod, chown, and chgrp evaluation.
+//!
+//! The base disposition comes from the flat `[commands]` lists (all three
+//! are in `ask`). Each file operand is classified against the `[paths]`
+//! rules, so changing a protected or sensitive path denies. A recursive
+//! change (`-R`, `--recursive`) of a system root gets the
+//! `[permissions] system_root` decision: the filesystem root, the home
+//! directory, a directory fewer than `min_depth` levels below `/` (`/etc`,
+//! `/usr`), or everything in one of those (`chmod -R 777 /*`).
+
+use super::super::CommandSpec;
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::{PathsConfig, PermissionsConfig};
+use crate::eval::paths::PathPolicy;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// chmod / chown / chgrp take no flags with a separate value
+/// (`--reference=RFILE` and `--from=OWNER` are inline).
+const PERMISSION_FLAGS: FlagSchema = FlagSchema {
+    short_value: "",
+    long_value: &[],
+};
+
+/// chmod / chown / chgrp evaluator.
+///
+/// Evaluation order:
+/// 1. Base decision from the flat `[commands]` lists; DENY stops here
+/// 2. Recursive changes of a system root → `system_root`
+/// 3. Other files escalate to their path classification (protected or
+///    sensitive → DENY); a path built by a command substitution → ASK
+pub struct PermissionsSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Decision for recursive changes of a system root.
+    system_root: Decision,
+    /// Directories with fewer components than this are system roots.
+    min_depth: usize,
+    /// File path classification.
+    paths: PathPolicy,
+}
+
+impl PermissionsSpec {
+    /// Build a spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &PermissionsConfig, paths: &PathsConfig) -> Self {
+        Self {
+            base,
+            system_root: config.system_root,
+            min_depth: config.min_depth,
+            paths: PathPolicy::from_config(paths),
+        }
+    }
+}
+
+/// Whether a chmod operand is a mode (`755`, `u+x`, `go-w,a+r`) rather
+/// than a file.
+fn is_mode(operand: &str) -> bool {
+    !operand.is_empty()
+        && operand
+            .chars()
+            .all(|c| "01234567ugoarwxXstST+-=,".contains(c))
+}
+
+impl CommandSpec for PermissionsSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let mut result = SimpleCommandSpec::new(self.base).evaluate(ctx);
+        if result.decision == Decision::Deny {
+            return result;
+        }
+
+        let name = ctx.base_command.as_str();
+        let args = ParsedArgs::parse(ctx.args(), &PERMISSION_FLAGS);
+        let recursive = args.has_any(&["-R", "--recursive"]);
+        // The mode or owner comes first unless `--reference` replaces it. A
+        // chmod mode that starts with `-` (`chmod -w f`) parses as a flag.
+        let skip = match name {
+            _ if args.has_any(&["--reference"]) => 0,
+            "chmod" => args.positionals.first().is_some_and(|m| is_mode(m)) as usize,
+            _ => 1,
+        };
+        let verb = if recursive {
+            format!("{name} -R")
+        } else {
+            name.to_string()
+        };
+        let cwd = ctx.working_dir();
+        for file in args.positionals.iter().skip(skip) {
+            if ctx.is_substituted(file) {
+                if result.decision < Decision::Ask {
+                    result = RuleMatch {
+                        decision: Decision::Ask,
+                        reason: format!("{verb} changes {file} (path known only at run time)"),
+                    };
+                }
+                continue;
+            }
+            if recursive
+                && let Some(what) =
+                    self.paths
+                        .system_root(file, &cwd, self.min_depth, &ctx.accumulated_env)
+            {
+                if self.system_root > result.decision {
+                    result = RuleMatch {
+                        decision: self.system_root,
+                        reason: format!("{verb} {file} changes every file in {what}"),
+                    };
+                }
+                continue;
+            }
+            let class = self.paths.classify_write(file, &cwd);
+            if class.decision() > result.decision {
+                result = RuleMatch {
+                    decision: class.decision(),
+                    reason: format!("{verb} changes {file} ({class})"),
+                };
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let config = Config::default_config();
+        let spec = PermissionsSpec::from_config(Decision::Ask, &config.permissions, &config.paths);
+        let mut ctx = CommandContext::from_command(cmd);
+        ctx.cwd = Some("/work/project".into());
+        spec.evaluate(&ctx)
+    }
+
+    #[test]
+    fn ordinary_changes_ask() {
+        assert_eq!(eval("chmod +x build.sh").decision, Decision::Ask);
+        assert_eq!(eval("chmod -R go-w src").decision, Decision::Ask);
+        assert_eq!(eval("chown -R me:me /tmp/cache").decision, Decision::Ask);
+        assert_eq!(
+            eval("chgrp -R staff /opt/tool/share").decision,
+            Decision::Ask
+        );
+        assert_eq!(eval("chmod -w notes.txt").decision, Decision::Ask);
+    }
+
+    #[test]
+    fn deny_recursive_system_roots() {
+        let result = eval("chmod -R 777 /");
+        assert_eq!(result.decision, Decision::Deny);
+        assert_eq!(
+            result.reason,
+            "chmod -R / changes every file in the filesystem root"
+        );
+        let result = eval("chown -R user /opt");
+        assert_eq!(result.decision, Decision::Deny);
+        assert_eq!(
+            result.reason,
+            "chown -R /opt changes every file in /opt (fewer than 2 levels below /)"
+        );
+        assert_eq!(eval("chmod -R a+rwx ~").decision, Decision::Deny);
+        assert_eq!(eval("chgrp --recursive wheel /*").decision, Decision::Deny);
+        assert_eq!(
+            eval("chown -R --reference=a.txt /srv").decision,
+            Decision::Deny
+        );
+    }
+
+    #[test]
+    fn deny_protected_files() {
+        let result = eval("chown -R user /etc");
+        assert_eq!(result.decision, Decision::Deny);
+        assert_eq!(
+            eval("chmod 666 /etc/shadow").reason,
+            "chmod changes /etc/shadow (protected path /etc)"
+        );
+        // The owner operand isn't a path.
+        assert_eq!(eval("chown /etc/x file").decision, Decision::Ask);
+    }
+}
@@ -0,0 +1,235 @@
+//! pip evaluation with package-index destination checks.
+//!
+//! The base disposition comes from the flat `[commands]` lists (pip is in
+//! `ask` by default). URLs pip fetches from — `--index-url`,
+//! `--extra-index-url`, `--find-links`, and direct URL or VCS requirements
+//! (`git+https://...`, `pkg @ https://...`) — are checked against the
+//! `[network]` domain lists: a denied domain denies, and a host outside
+//! `allowed_domains` is never auto-allowed.
+
+use super::super::CommandSpec;
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::NetworkConfig;
+use crate::eval::network::{DomainMatch, DomainPolicy, url_host};
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// pip flags that consume a value (pip 24.x).
+const PIP_FLAGS: FlagSchema = FlagSchema {
+    short_value: "icfrtedC",
+    long_value: &[
+        "--index-url",
+        "--extra-index-url",
+        "--find-links",
+        "--requirement",
+        "--constraint",
+        "--target",
+        "--editable",
+        "--dest",
+        "--config-settings",
+        "--prefix",
+        "--root",
+        "--src",
+        "--trusted-host",
+        "--proxy",
+        "--cert",
+        "--client-cert",
+        "--cache-dir",
+        "--log",
+        "--python",
+        "--platform",
+        "--python-version",
+        "--implementation",
+        "--abi",
+        "--progress-bar",
+        "--upgrade-strategy",
+        "--no-binary",
+        "--only-binary",
+        "--timeout",
+        "--retries",
+        "--exists-action",
+        "--report",
+        "--global-option",
+    ],
+};
+
+/// pip flags whose value may be a remote location.
+const SOURCE_FLAGS: &[&str] = &[
+    "-i",
+    "--index-url",
+    "--extra-index-url",
+    "-f",
+    "--find-links",
+    "-e",
+    "--editable",
+];
+
+/// pip evaluator.
+///
+/// Evaluation order:
+/// 1. Any index, find-links, or requirement URL on a denied domain → DENY
+/// 2. Base decision from the flat `[commands]` lists
+/// 3. ALLOW with a URL outside `allowed_domains` → ASK
+/// 4. ALLOW with redirection → ASK
+pub struct PipSpec {
+    /// Baseline disposition (the flat-list entry for pip/pip3).
+    base: Decision,
+    /// Package source classification.
+    domains: DomainPolicy,
+}
+
+impl PipSpec {
+    /// Build a pip spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, network: &NetworkConfig) -> Self {
+        Self {
+            base,
+            domains: DomainPolicy::from_config(network),
+        }
+    }
+
+    /// Remote locations this invocation fetches from.
+    fn source_urls(args: &ParsedArgs) -> Vec<&str> {
+        args.values(SOURCE_FLAGS)
+            .chain(args.positionals.iter().map(String::as_str))
+            .filter_map(|v| {
+                let start = v.find("://")?;
+                // `pkg@https://...` or `git+https://...`: trim to the scheme.
+                let scheme_start = v[..start]
+                    .rfind(|c: char| !c.is_ascii_alphanumeric() && c != '+')
+                    .map_or(0, |i| i + 1);
+                Some(&v[scheme_start..])
+            })
+            .collect()
+    }
+}
+
+impl CommandSpec for PipSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let args = ParsedArgs::parse(ctx.args(), &PIP_FLAGS);
+        let urls = Self::source_urls(&args);
+        let domain = self.domains.classify_urls(&urls);
+        if let DomainMatch::Denied(host) = domain {
+            return RuleMatch {
+                decision: Decision::Deny,
+                reason: format!("{} fetches from denied domain {host}", ctx.base_command),
+            };
+        }
+
+        let mut result = SimpleCommandSpec::new(self.base).evaluate(ctx);
+        if result.decision == Decision::Allow && !urls.is_empty() && domain != DomainMatch::Allowed
+        {
+            let host = urls
+                .iter()
+                .filter_map(|u| url_host(u))
+                .find(|h| !self.domains.is_allowed(h))
+                .unwrap_or("?");
+            result = RuleMatch {
+                decision: Decision::Ask,
+                reason: format!(
+                    "{} fetches from unlisted host {host} requires confirmation",
+                    ctx.base_command
+                ),
+            };
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn spec(base: Decision) -> PipSpec {
+        PipSpec::from_config(base, &Config::default_config().network)
+    }
+
+    fn eval(base: Decision, cmd: &str) -> Decision {
+        let ctx = CommandContext::from_command(cmd);
+        spec(base).evaluate(&ctx).decision
+    }
+
+    fn reason(base: Decision, cmd: &str) -> String {
+        let ctx = CommandContext::from_command(cmd);
+        spec(base).evaluate(&ctx).reason
+    }
+
+    #[test]
+    fn plain_install_uses_base() {
+        assert_eq!(eval(Decision::Ask, "pip install requests"), Decision::Ask);
+        assert_eq!(
+            eval(Decision::Allow, "pip install requests"),
+            Decision::Allow
+        );
+    }
+
+    #[test]
+    fn deny_denied_index() {
+        assert_eq!(
+            eval(
+                Decision::Ask,
+                "pip install --index-url https://x.ngrok.io/simple pkg"
+            ),
+            Decision::Deny
+        );
+        assert_eq!(
+            eval(
+                Decision::Allow,
+                "pip3 install -i https://pastebin.com/x pkg"
+            ),
+            Decision::Deny
+        );
+    }
+
+    #[test]
+    fn deny_denied_requirement_url() {
+        assert_eq!(
+            eval(
+                Decision::Ask,
+                "pip install git+https://transfer.sh/x/pkg.git"
+            ),
+            Decision::Deny
+        );
+        assert!(
+            reason(Decision::Ask, "pip install pkg@https://0x0.st/p.whl")
+                .contains("denied domain 0x0.st")
+        );
+    }
+
+    #[test]
+    fn unlisted_index_not_auto_allowed() {
+        assert_eq!(
+            eval(
+                Decision::Allow,
+                "pip install --extra-index-url https://pkgs.example.com/simple pkg"
+            ),
+            Decision::Ask
+        );
+    }
+
+    #[test]
+    fn allowed_index_keeps_base() {
+        assert_eq!(
+            eval(
+                Decision::Allow,
+                "pip install --index-url https://pypi.org/simple pkg"
+            ),
+            Decision::Allow
+        );
+        assert_eq!(
+            eval(
+                Decision::Allow,
+                "pip install git+https://github.com/o/r.git"
+            ),
+            Decision::Allow
+        );
+    }
+
+    #[test]
+    fn local_find_links_is_not_a_url() {
+        assert_eq!(
+            eval(Decision::Allow, "pip install -f ./wheels pkg"),
+            Decision::Allow
+        );
+    }
+}
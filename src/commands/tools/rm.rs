@@ -0,0 +1,185 @@
+//! Target-aware rm evaluation.
+//!
+//! Ordinary deletes keep rm's flat-list decision (ask). Each operand is also
+//! classified against the `[paths]` rules, so deleting a protected or
+//! sensitive path denies. A recursive delete (`-r`, `-R`, `--recursive`) of
+//! a catastrophic target gets the `[rm] catastrophic` decision instead:
+//!
+//! - the filesystem root or the home directory (`rm -rf /`, `rm -rf ~`);
+//! - a directory fewer than `min_depth` components below `/` (`/usr`),
+//!   unless it's a sandbox directory;
+//! - everything in one of those (`/*`, `~/*`, or `*` run in one);
+//! - a path that starts with an unset variable (`"$DIR"/`) and would be one
+//!   of the above if the variable expanded empty.
+
+use super::super::CommandSpec;
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::{PathsConfig, RmConfig};
+use crate::eval::paths::PathPolicy;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// rm takes no flags with a separate value (`--interactive=WHEN` is inline).
+const RM_FLAGS: FlagSchema = FlagSchema {
+    short_value: "",
+    long_value: &[],
+};
+
+/// rm evaluator.
+///
+/// Evaluation order:
+/// 1. Base decision from the flat `[commands]` lists; DENY stops here
+/// 2. Recursive deletes of a catastrophic target → `catastrophic`
+/// 3. Other targets escalate to their path classification (protected or
+///    sensitive → DENY); a path built by a command substitution → ASK
+pub struct RmSpec {
+    /// Baseline disposition (the flat-list entry for rm).
+    base: Decision,
+    /// Decision for recursive deletes of a catastrophic target.
+    catastrophic: Decision,
+    /// Directories with fewer components than this are catastrophic targets.
+    min_depth: usize,
+    /// Target path classification.
+    paths: PathPolicy,
+}
+
+impl RmSpec {
+    /// Build an rm spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &RmConfig, paths: &PathsConfig) -> Self {
+        Self {
+            base,
+            catastrophic: config.catastrophic,
+            min_depth: config.min_depth,
+            paths: PathPolicy::from_config(paths),
+        }
+    }
+}
+
+impl CommandSpec for RmSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let mut result = SimpleCommandSpec::new(self.base).evaluate(ctx);
+        if result.decision == Decision::Deny {
+            return result;
+        }
+
+        let args = ParsedArgs::parse(ctx.args(), &RM_FLAGS);
+        let recursive = args.has_any(&["-r", "-R", "--recursive"]);
+        let cwd = ctx.working_dir();
+        for target in &args.positionals {
+            if ctx.is_substituted(target) {
+                if result.decision < Decision::Ask {
+                    result = RuleMatch {
+                        decision: Decision::Ask,
+                        reason: format!("rm deletes {target} (path known only at run time)"),
+                    };
+                }
+                continue;
+            }
+            if recursive
+                && let Some(what) =
+                    self.paths
+                        .system_root(target, &cwd, self.min_depth, &ctx.accumulated_env)
+            {
+                if self.catastrophic > result.decision {
+                    result = RuleMatch {
+                        decision: self.catastrophic,
+                        reason: format!("rm -r {target} deletes {what}"),
+                    };
+                }
+                continue;
+            }
+            let class = self.paths.classify_write(target, &cwd);
+            if class.decision() > result.decision {
+                result = RuleMatch {
+                    decision: class.decision(),
+                    reason: format!("rm deletes {target} ({class})"),
+                };
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval_in(cwd: &str, cmd: &str) -> RuleMatch {
+        let config = Config::default_config();
+        let spec = RmSpec::from_config(Decision::Ask, &config.rm, &config.paths);
+        let mut ctx = CommandContext::from_command(cmd);
+        ctx.cwd = Some(cwd.into());
+        spec.evaluate(&ctx)
+    }
+
+    fn eval(cmd: &str) -> Decision {
+        eval_in("/work/project", cmd).decision
+    }
+
+    #[test]
+    fn ordinary_deletes_ask() {
+        assert_eq!(eval("rm notes.txt"), Decision::Ask);
+        assert_eq!(eval("rm -rf build target/debug"), Decision::Ask);
+        assert_eq!(eval("rm -rf /tmp/junk /tmp"), Decision::Ask);
+        assert_eq!(eval_in("/work/project", "rm -rf *").decision, Decision::Ask);
+        assert_eq!(eval("rm -rf $(mktemp -d)"), Decision::Ask);
+    }
+
+    #[test]
+    fn deny_root_and_home() {
+        assert_eq!(eval("rm -rf /"), Decision::Deny);
+        assert_eq!(eval("rm -rf --no-preserve-root /"), Decision::Deny);
+        assert_eq!(eval("rm -r /*"), Decision::Deny);
+        assert_eq!(eval("rm -rf ~"), Decision::Deny);
+        assert_eq!(eval("rm -rf ~/"), Decision::Deny);
+        assert_eq!(eval("rm -Rf $HOME/*"), Decision::Deny);
+        assert_eq!(eval("rm -rf ../.."), Decision::Deny);
+        assert_eq!(
+            eval_in("/", "rm -rf *").reason,
+            "rm -r * deletes everything in the filesystem root"
+        );
+        // Not recursive: rm refuses directories, so the flat decision stands.
+        assert_eq!(eval("rm /"), Decision::Ask);
+    }
+
+    #[test]
+    fn deny_shallow_directories() {
+        assert_eq!(
+            eval_in("/", "rm -rf opt").reason,
+            "rm -r opt deletes /opt (fewer than 2 levels below /)"
+        );
+        assert_eq!(eval_in("/srv", "rm -rf *").decision, Decision::Deny);
+        assert_eq!(eval("rm -rf /opt/tool"), Decision::Ask);
+    }
+
+    #[test]
+    fn deny_unset_variable_prefixes() {
+        let result = eval_in("/work", r#"rm -rf "$CC_TOOLGATE_TEST_UNSET"/"#);
+        assert_eq!(result.decision, Decision::Deny);
+        assert_eq!(
+            result.reason,
+            "rm -r \"$CC_TOOLGATE_TEST_UNSET\"/ deletes the filesystem root if \
+             $CC_TOOLGATE_TEST_UNSET is empty or unset"
+        );
+        assert_eq!(eval("rm -rf ${CC_TOOLGATE_TEST_UNSET}/*"), Decision::Deny);
+        assert_eq!(eval("rm -rf ${CC_TOOLGATE_TEST_UNSET:?}/"), Decision::Ask);
+        assert_eq!(
+            eval("rm -rf $CC_TOOLGATE_TEST_UNSET/dist/out"),
+            Decision::Ask
+        );
+
+        let mut ctx = CommandContext::from_command("rm -rf $OUT/");
+        ctx.accumulated_env.insert("OUT".into(), "build".into());
+        ctx.cwd = Some("/work/project".into());
+        let config = Config::default_config();
+        let spec = RmSpec::from_config(Decision::Ask, &config.rm, &config.paths);
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Ask);
+    }
+
+    #[test]
+    fn deny_protected_targets() {
+        assert_eq!(eval("rm /etc/hosts"), Decision::Deny);
+        assert_eq!(eval("rm -rf /usr/lib/python3"), Decision::Deny);
+    }
+}
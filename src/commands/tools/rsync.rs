@@ -0,0 +1,262 @@
+//! rsync evaluation with destructive-flag and remote-destination detection.
+//!
+//! Plain rsync asks. Flags that delete files (`--delete`, `--remove-source-files`)
+//! and copies to a remote host escalate to their configured decisions, each
+//! with its own reason. Dry runs whose operands are all local change nothing
+//! and can be allowed.
+
+use super::super::CommandSpec;
+use crate::commands::args::{FlagSchema, ParsedArgs, remote_host};
+use crate::config::RsyncConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// rsync flags that consume a value (rsync 3.2).
+const RSYNC_FLAGS: FlagSchema = FlagSchema {
+    short_value: "eBfMT",
+    long_value: &[
+        "--rsh",
+        "--rsync-path",
+        "--filter",
+        "--exclude",
+        "--include",
+        "--exclude-from",
+        "--include-from",
+        "--files-from",
+        "--temp-dir",
+        "--compare-dest",
+        "--copy-dest",
+        "--link-dest",
+        "--backup-dir",
+        "--suffix",
+        "--chmod",
+        "--chown",
+        "--usermap",
+        "--groupmap",
+        "--timeout",
+        "--contimeout",
+        "--port",
+        "--password-file",
+        "--log-file",
+        "--log-file-format",
+        "--out-format",
+        "--bwlimit",
+        "--max-size",
+        "--min-size",
+        "--max-delete",
+        "--block-size",
+        "--partial-dir",
+        "--compress-choice",
+        "--compress-level",
+        "--checksum-choice",
+        "--skip-compress",
+        "--sockopts",
+        "--remote-option",
+        "--address",
+        "--info",
+        "--debug",
+        "--iconv",
+        "--modify-window",
+        "--write-batch",
+        "--read-batch",
+        "--only-write-batch",
+        "--copy-as",
+        "--outbuf",
+        "--stop-after",
+        "--stop-at",
+    ],
+};
+
+/// rsync evaluator.
+///
+/// Evaluation order:
+/// 1. `-n` / `--dry-run` with only local operands → ALLOW (if enabled)
+/// 2. Each of these contributes its configured decision and reason;
+///    the strictest wins:
+///    - a destructive flag (`--delete`, `--remove-source-files`, …)
+///    - a remote destination (`host:path`, `host::module`, `rsync://`)
+/// 3. Otherwise → ASK
+/// 4. ALLOW with redirection → ASK
+pub struct RsyncSpec {
+    /// Flags that delete files at the destination or source.
+    destructive_flags: Vec<String>,
+    /// Decision when a destructive flag is present.
+    destructive: Decision,
+    /// Decision when the destination is remote.
+    remote_destination: Decision,
+    /// Whether local-only dry runs are allowed.
+    allow_local_dry_run: bool,
+}
+
+impl RsyncSpec {
+    /// Build an rsync spec from configuration.
+    pub fn from_config(config: &RsyncConfig) -> Self {
+        Self {
+            destructive_flags: config.destructive_flags.clone(),
+            destructive: config.destructive,
+            remote_destination: config.remote_destination,
+            allow_local_dry_run: config.allow_local_dry_run,
+        }
+    }
+}
+
+impl CommandSpec for RsyncSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let args = ParsedArgs::parse(ctx.args(), &RSYNC_FLAGS);
+        let all_local = args.positionals.iter().all(|p| remote_host(p).is_none());
+
+        let result = if self.allow_local_dry_run && all_local && args.has_any(&["-n", "--dry-run"])
+        {
+            RuleMatch {
+                decision: Decision::Allow,
+                reason: "rsync local dry run".into(),
+            }
+        } else {
+            let mut decision = Decision::Ask;
+            let mut reasons = Vec::new();
+
+            let mut seen: Vec<&str> = Vec::new();
+            for flag in &args.flags {
+                let name = flag.name.as_str();
+                if self.destructive_flags.iter().any(|f| f == name) && !seen.contains(&name) {
+                    seen.push(name);
+                }
+            }
+            let (source, dest): (Vec<&str>, Vec<&str>) = seen
+                .into_iter()
+                .partition(|f| *f == "--remove-source-files");
+            if !dest.is_empty() {
+                decision = decision.max(self.destructive);
+                reasons.push(format!(
+                    "rsync {} deletes files at the destination",
+                    dest.join(" ")
+                ));
+            }
+            if !source.is_empty() {
+                decision = decision.max(self.destructive);
+                reasons.push("rsync --remove-source-files deletes source files".into());
+            }
+
+            if let Some(host) = args.positionals.last().and_then(|d| remote_host(d))
+                && args.positionals.len() > 1
+            {
+                decision = decision.max(self.remote_destination);
+                reasons.push(format!("rsync to remote host {host}"));
+            }
+
+            if reasons.is_empty() {
+                RuleMatch {
+                    decision,
+                    reason: "rsync requires confirmation".into(),
+                }
+            } else {
+                RuleMatch {
+                    decision,
+                    reason: reasons.join("; "),
+                }
+            }
+        };
+
+        if result.decision == Decision::Allow
+            && let Some(ref r) = ctx.redirection
+        {
+            return RuleMatch {
+                decision: Decision::Ask,
+                reason: format!("{} with {}", result.reason, r),
+            };
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn spec() -> RsyncSpec {
+        RsyncSpec::from_config(&Config::default_config().rsync)
+    }
+
+    fn eval(cmd: &str) -> Decision {
+        let s = spec();
+        let ctx = CommandContext::from_command(cmd);
+        s.evaluate(&ctx).decision
+    }
+
+    fn reason(cmd: &str) -> String {
+        let s = spec();
+        let ctx = CommandContext::from_command(cmd);
+        s.evaluate(&ctx).reason
+    }
+
+    #[test]
+    fn ask_local_copy() {
+        assert_eq!(eval("rsync -av src/ backup/"), Decision::Ask);
+    }
+
+    #[test]
+    fn ask_download() {
+        assert_eq!(eval("rsync -av host:/srv/data/ ./data/"), Decision::Ask);
+    }
+
+    #[test]
+    fn deny_delete() {
+        assert_eq!(eval("rsync -av --delete src/ backup/"), Decision::Deny);
+        assert!(reason("rsync -av --delete src/ backup/").contains("--delete deletes files"));
+    }
+
+    #[test]
+    fn deny_delete_excluded() {
+        assert_eq!(
+            eval("rsync -a --delete-excluded --exclude '*.o' src/ dst/"),
+            Decision::Deny
+        );
+    }
+
+    #[test]
+    fn deny_remove_source_files() {
+        assert_eq!(eval("rsync --remove-source-files a/ b/"), Decision::Deny);
+        assert!(reason("rsync --remove-source-files a/ b/").contains("deletes source files"));
+    }
+
+    #[test]
+    fn deny_remote_destination() {
+        assert_eq!(eval("rsync -az ./ deploy@host:/srv/app/"), Decision::Deny);
+        assert!(reason("rsync -az ./ deploy@host:/srv/app/").contains("remote host deploy@host"));
+    }
+
+    #[test]
+    fn deny_daemon_destination() {
+        assert_eq!(eval("rsync -a ./ host::module/"), Decision::Deny);
+        assert_eq!(eval("rsync -a ./ rsync://host/module/"), Decision::Deny);
+    }
+
+    #[test]
+    fn both_reasons_reported() {
+        let r = reason("rsync -a --delete ./ host:/srv/");
+        assert!(r.contains("--delete"));
+        assert!(r.contains("remote host"));
+    }
+
+    #[test]
+    fn value_flag_not_mistaken_for_destination() {
+        // `-e ssh` consumes "ssh"; destination is still local.
+        assert_eq!(eval("rsync -e ssh host:a ./b"), Decision::Ask);
+    }
+
+    #[test]
+    fn allow_local_dry_run() {
+        assert_eq!(eval("rsync -avn --delete src/ dst/"), Decision::Allow);
+        assert_eq!(eval("rsync --dry-run -a src/ dst/"), Decision::Allow);
+    }
+
+    #[test]
+    fn remote_dry_run_not_allowed() {
+        assert_eq!(eval("rsync -avn ./ host:/srv/"), Decision::Deny);
+    }
+
+    #[test]
+    fn dry_run_with_redirection_asks() {
+        assert_eq!(eval("rsync -avn src/ dst/ > /tmp/plan"), Decision::Ask);
+    }
+}
@@ -0,0 +1,243 @@
+//! SSH key tool evaluation: ssh-keygen, ssh-add and ssh-copy-id.
+//!
+//! These change who can authenticate as whom: generating a key, loading
+//! one into the agent, or installing one on a remote host. Fingerprinting
+//! and listing (`ssh-keygen -l`, `ssh-add -l`) are allowed; the changes get
+//! `[ssh_keys] change`. Generating a key over an existing file (`ssh-keygen
+//! -f` on a path that exists) replaces the key it held, and gets
+//! `overwrite`.
+
+use super::super::CommandSpec;
+use super::packages::{decided, query};
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::SshKeysConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// ssh-keygen flags that consume a value.
+const KEYGEN_FLAGS: FlagSchema = FlagSchema {
+    short_value: "abCDEfFIJjKmMnNOPrRstwYzZ",
+    long_value: &[],
+};
+
+/// ssh-keygen flags that only print: fingerprints, public keys,
+/// certificates, known_hosts lookups, format conversion to stdout.
+const KEYGEN_QUERIES: &[&str] = &["-l", "-B", "-y", "-L", "-F", "-e", "-i", "-Q"];
+
+/// ssh-keygen flags that change an existing key, known_hosts or a
+/// certificate rather than generating a key.
+const KEYGEN_CHANGES: &[&str] = &["-p", "-c", "-R", "-H", "-s", "-k", "-A"];
+
+/// `ssh-keygen -Y` operations that only check a signature.
+const KEYGEN_VERIFIES: &[&str] = &[
+    "verify",
+    "check-novalidate",
+    "find-principals",
+    "match-principals",
+];
+
+/// ssh-add flags that consume a value.
+const ADD_FLAGS: FlagSchema = FlagSchema {
+    short_value: "EtSHh",
+    long_value: &[],
+};
+
+/// ssh-add flags that only list or test the agent's keys.
+const ADD_QUERIES: &[&str] = &["-l", "-L", "-T"];
+
+/// ssh-copy-id flags that consume a value.
+const COPY_ID_FLAGS: FlagSchema = FlagSchema {
+    short_value: "ipotF",
+    long_value: &[],
+};
+
+/// SSH key evaluator for ssh-keygen, ssh-add and ssh-copy-id.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `ssh-keygen -l`/`-y`/`-F`/`-Y verify`, `ssh-add -l`/`-L`,
+///    `ssh-copy-id -n` → ALLOW (ASK with redirection)
+/// 3. `ssh-keygen -f` on an existing file, generating a key →
+///    `[ssh_keys] overwrite`
+/// 4. Key generation, `ssh-keygen -p`/`-R`, loading or removing agent keys,
+///    `ssh-copy-id` → `[ssh_keys] change`
+pub struct SshKeySpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Decision for a change to keys or authorized keys.
+    change: Decision,
+    /// Decision for generating a key over an existing file.
+    overwrite: Decision,
+}
+
+impl SshKeySpec {
+    /// Build an SSH key spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &SshKeysConfig) -> Self {
+        Self {
+            base,
+            change: config.change,
+            overwrite: config.overwrite,
+        }
+    }
+
+    fn keygen(&self, ctx: &CommandContext) -> RuleMatch {
+        let args = ParsedArgs::parse(ctx.args(), &KEYGEN_FLAGS);
+        if let Some(flag) = KEYGEN_QUERIES.iter().find(|f| args.has_any(&[**f])) {
+            return query(ctx, &format!("ssh-keygen {flag}"));
+        }
+        if let Some(op) = args.values(&["-Y"]).last() {
+            let what = format!("ssh-keygen -Y {op}");
+            if KEYGEN_VERIFIES.contains(&op) {
+                return query(ctx, &what);
+            }
+            return decided(self.change, &what, None);
+        }
+        if let Some(flag) = KEYGEN_CHANGES.iter().find(|f| args.has_any(&[**f])) {
+            return decided(self.change, &format!("ssh-keygen {flag}"), None);
+        }
+        if let Some(file) = args.values(&["-f"]).last()
+            && ctx.resolve_path(file).exists()
+        {
+            let what = format!("ssh-keygen overwriting {file}");
+            return decided(self.overwrite, &what, None);
+        }
+        decided(self.change, "ssh-keygen (key generation)", None)
+    }
+
+    fn add(&self, ctx: &CommandContext) -> RuleMatch {
+        let args = ParsedArgs::parse(ctx.args(), &ADD_FLAGS);
+        if let Some(flag) = ADD_QUERIES.iter().find(|f| args.has_any(&[**f])) {
+            return query(ctx, &format!("ssh-add {flag}"));
+        }
+        let what = match ["-d", "-D", "-x", "-X"]
+            .iter()
+            .find(|f| args.has_any(&[**f]))
+        {
+            Some(flag) => format!("ssh-add {flag}"),
+            None => "ssh-add (loading keys into the agent)".to_string(),
+        };
+        decided(self.change, &what, None)
+    }
+
+    fn copy_id(&self, ctx: &CommandContext) -> RuleMatch {
+        let args = ParsedArgs::parse(ctx.args(), &COPY_ID_FLAGS);
+        if args.has_any(&["-n"]) {
+            return query(ctx, "ssh-copy-id -n");
+        }
+        let what = match args.positionals.last() {
+            Some(host) => format!("ssh-copy-id {host}"),
+            None => "ssh-copy-id".to_string(),
+        };
+        decided(self.change, &what, None)
+    }
+}
+
+impl CommandSpec for SshKeySpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        match ctx.base_command.as_str() {
+            "ssh-keygen" => self.keygen(ctx),
+            "ssh-add" => self.add(ctx),
+            "ssh-copy-id" => self.copy_id(ctx),
+            _ => base.evaluate(ctx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let spec = SshKeySpec::from_config(Decision::Ask, &Config::default_config().ssh_keys);
+        spec.evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn fingerprints_and_listings_allow() {
+        for cmd in [
+            "ssh-keygen -l -f ~/.ssh/id_ed25519.pub",
+            "ssh-keygen -lf key.pub",
+            "ssh-keygen -y -f key",
+            "ssh-keygen -F github.com",
+            "ssh-keygen -Y verify -f allowed_signers -I a@b -n file -s f.sig",
+            "ssh-add -l",
+            "ssh-add -L",
+            "ssh-copy-id -n user@host",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(eval("ssh-add -l").reason, "read-only ssh-add -l");
+    }
+
+    #[test]
+    fn changes_ask() {
+        for cmd in [
+            "ssh-keygen -t ed25519 -C me@example.com",
+            "ssh-keygen -t ed25519 -f /nonexistent/deploy_key -N ''",
+            "ssh-keygen -p -f key",
+            "ssh-keygen -R github.com",
+            "ssh-keygen -Y sign -f key -n file data",
+            "ssh-add ~/.ssh/id_ed25519",
+            "ssh-add",
+            "ssh-add -D",
+            "ssh-copy-id -i key.pub user@host",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("ssh-keygen -t ed25519").reason,
+            "ssh-keygen (key generation) requires confirmation"
+        );
+        assert_eq!(
+            eval("ssh-copy-id -i key.pub user@host").reason,
+            "ssh-copy-id user@host requires confirmation"
+        );
+    }
+
+    #[test]
+    fn overwriting_a_key_denies() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("cc-toolgate-test-ssh-keys-{nanos}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("id_ed25519"), "key").unwrap();
+        let spec = SshKeySpec::from_config(Decision::Ask, &Config::default_config().ssh_keys);
+        let eval = |cmd| {
+            let mut ctx = CommandContext::from_command(cmd);
+            ctx.cwd = Some(dir.clone());
+            spec.evaluate(&ctx)
+        };
+        let result = eval("ssh-keygen -t ed25519 -f id_ed25519 -N ''");
+        assert_eq!(result.decision, Decision::Deny);
+        assert_eq!(
+            result.reason,
+            "blocked command: ssh-keygen overwriting id_ed25519"
+        );
+        assert_eq!(eval("ssh-keygen -t rsa -f id_rsa").decision, Decision::Ask);
+        // Changing the passphrase keeps the key.
+        assert_eq!(eval("ssh-keygen -p -f id_ed25519").decision, Decision::Ask);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn decisions_are_configurable() {
+        let config = SshKeysConfig {
+            change: Decision::Allow,
+            overwrite: Decision::Ask,
+        };
+        let spec = SshKeySpec::from_config(Decision::Ask, &config);
+        let eval = |cmd| spec.evaluate(&CommandContext::from_command(cmd)).decision;
+        assert_eq!(eval("ssh-add key"), Decision::Allow);
+        assert_eq!(eval("ssh-keygen -t ed25519"), Decision::Allow);
+        let spec = SshKeySpec::from_config(Decision::Deny, &config);
+        let ctx = CommandContext::from_command("ssh-add -l");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+}
@@ -0,0 +1,316 @@
+//! systemd tools: hostnamectl, timedatectl, localectl, loginctl and
+//! journalctl.
+//!
+//! The settings tools take a verb. Status and listing verbs (and no verb,
+//! which shows the status) are allowed; `set-*` verbs and anything else
+//! that changes the system or a login session ask. A verb the table
+//! doesn't know asks too, so a newer systemd's setter isn't allowed by
+//! accident.
+//!
+//! journalctl only reads, except for its maintenance flags, which remove
+//! or move journal files.
+
+use super::super::CommandSpec;
+use super::packages::{change, query};
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// Flags these tools share that consume a value.
+const CTL_FLAGS: FlagSchema = FlagSchema {
+    short_value: "HMpPsn",
+    long_value: &[
+        "--host",
+        "--machine",
+        "--property",
+        "--signal",
+        "--kill-whom",
+        "--lines",
+        "--output",
+    ],
+};
+
+/// A tool's read-only verbs, and the verbs that only read when given no
+/// operand (`hostnamectl hostname` shows it, `hostnamectl hostname x` sets it).
+struct Verbs {
+    tool: &'static str,
+    queries: &'static [&'static str],
+    getters: &'static [&'static str],
+}
+
+const VERBS: &[Verbs] = &[
+    Verbs {
+        tool: "hostnamectl",
+        queries: &["status"],
+        getters: &["hostname", "icon-name", "chassis", "deployment", "location"],
+    },
+    Verbs {
+        tool: "timedatectl",
+        queries: &[
+            "status",
+            "show",
+            "list-timezones",
+            "timesync-status",
+            "show-timesync",
+        ],
+        getters: &[],
+    },
+    Verbs {
+        tool: "localectl",
+        queries: &[
+            "status",
+            "list-locales",
+            "list-keymaps",
+            "list-x11-keymap-models",
+            "list-x11-keymap-layouts",
+            "list-x11-keymap-variants",
+            "list-x11-keymap-options",
+        ],
+        getters: &[],
+    },
+    Verbs {
+        tool: "loginctl",
+        queries: &[
+            "list-sessions",
+            "session-status",
+            "show-session",
+            "list-users",
+            "user-status",
+            "show-user",
+            "list-seats",
+            "seat-status",
+            "show-seat",
+        ],
+        getters: &[],
+    },
+];
+
+/// Verb-aware evaluator for hostnamectl, timedatectl, localectl and
+/// loginctl.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. No verb, a status or listing verb, or a getter without an operand
+///    → ALLOW (ASK with redirection)
+/// 3. Everything else (`set-hostname`, `set-time`, `terminate-session`)
+///    → ASK
+pub struct SystemdCtlSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+}
+
+impl SystemdCtlSpec {
+    /// Build a spec from the command's flat-list decision.
+    pub fn new(base: Decision) -> Self {
+        Self { base }
+    }
+}
+
+impl CommandSpec for SystemdCtlSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        let Some(verbs) = VERBS.iter().find(|v| v.tool == ctx.base_command) else {
+            return base.evaluate(ctx);
+        };
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        let args = ParsedArgs::parse(ctx.args(), &CTL_FLAGS);
+        let Some((verb, operands)) = args.positionals.split_first() else {
+            return query(ctx, &ctx.base_command);
+        };
+        let what = format!("{} {verb}", ctx.base_command);
+        let reads = verbs.queries.contains(&verb.as_str())
+            || (verbs.getters.contains(&verb.as_str()) && operands.is_empty());
+        if reads {
+            query(ctx, &what)
+        } else {
+            change(&what, None, None)
+        }
+    }
+}
+
+/// journalctl flags that consume a value.
+const JOURNALCTL_FLAGS: FlagSchema = FlagSchema {
+    short_value: "bDFIMnopSUtu",
+    long_value: &[
+        "--boot",
+        "--directory",
+        "--file",
+        "--root",
+        "--image",
+        "--machine",
+        "--lines",
+        "--output",
+        "--output-fields",
+        "--priority",
+        "--facility",
+        "--since",
+        "--until",
+        "--identifier",
+        "--unit",
+        "--user-unit",
+        "--grep",
+        "--cursor",
+        "--after-cursor",
+        "--cursor-file",
+        "--namespace",
+        "--field",
+    ],
+};
+
+/// journalctl flags that delete, rotate or move journal files.
+const JOURNALCTL_MAINTENANCE: &[&str] = &[
+    "--vacuum-size",
+    "--vacuum-time",
+    "--vacuum-files",
+    "--rotate",
+    "--flush",
+    "--relinquish-var",
+    "--smart-relinquish-var",
+    "--sync",
+    "--setup-keys",
+    "--update-catalog",
+];
+
+/// journalctl evaluator.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. Maintenance flags (`--vacuum-size`, `--vacuum-time`, `--rotate`,
+///    `--flush`) → ASK
+/// 3. Everything else reads the journal, `-f` included → ALLOW (ASK with
+///    redirection)
+pub struct JournalctlSpec {
+    /// Baseline disposition (the flat-list entry for journalctl).
+    base: Decision,
+}
+
+impl JournalctlSpec {
+    /// Build a journalctl spec from its flat-list decision.
+    pub fn new(base: Decision) -> Self {
+        Self { base }
+    }
+}
+
+impl CommandSpec for JournalctlSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        if self.base == Decision::Deny {
+            return SimpleCommandSpec::new(self.base).evaluate(ctx);
+        }
+        let args = ParsedArgs::parse(ctx.args(), &JOURNALCTL_FLAGS);
+        let maintenance = args
+            .flags
+            .iter()
+            .find(|f| JOURNALCTL_MAINTENANCE.contains(&f.name.as_str()));
+        match maintenance {
+            Some(flag) => change(&format!("journalctl {}", flag.name), None, None),
+            None => query(ctx, "journalctl"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        SystemdCtlSpec::new(Decision::Allow).evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn queries_allow() {
+        for cmd in [
+            "hostnamectl",
+            "hostnamectl status",
+            "hostnamectl hostname",
+            "hostnamectl --static hostname",
+            "timedatectl",
+            "timedatectl show -p Timezone --value",
+            "timedatectl list-timezones",
+            "localectl list-locales",
+            "loginctl",
+            "loginctl list-sessions",
+            "loginctl show-session 2 -p State",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(
+            eval("timedatectl status").reason,
+            "read-only timedatectl status"
+        );
+        assert_eq!(eval("hostnamectl > host.txt").decision, Decision::Ask);
+    }
+
+    #[test]
+    fn changes_ask() {
+        for cmd in [
+            "hostnamectl set-hostname web01",
+            "hostnamectl hostname web01",
+            "timedatectl set-time '2024-01-01 00:00'",
+            "timedatectl set-ntp false",
+            "localectl set-keymap us",
+            "loginctl terminate-session 2",
+            "loginctl enable-linger app",
+            "timedatectl frobnicate",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("hostnamectl set-hostname web01").reason,
+            "hostnamectl set-hostname requires confirmation"
+        );
+    }
+
+    #[test]
+    fn denied_stays_denied() {
+        let spec = SystemdCtlSpec::new(Decision::Deny);
+        let ctx = CommandContext::from_command("hostnamectl status");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+
+    fn journalctl(cmd: &str) -> RuleMatch {
+        JournalctlSpec::new(Decision::Ask).evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn journalctl_reads_allow() {
+        for cmd in [
+            "journalctl",
+            "journalctl -f",
+            "journalctl -u nginx -n 100 --no-pager",
+            "journalctl -b -1 -p err",
+            "journalctl --since '1 hour ago' -o json",
+            "journalctl --disk-usage",
+            "journalctl --list-boots",
+            "journalctl --verify",
+        ] {
+            assert_eq!(journalctl(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(journalctl("journalctl -f").reason, "read-only journalctl");
+        assert_eq!(
+            journalctl("journalctl -u nginx > nginx.log").decision,
+            Decision::Ask
+        );
+    }
+
+    #[test]
+    fn journalctl_maintenance_asks() {
+        for cmd in [
+            "journalctl --vacuum-size=500M",
+            "journalctl --vacuum-time 2weeks",
+            "journalctl --rotate",
+            "journalctl --flush",
+            "journalctl --rotate --vacuum-files=5",
+        ] {
+            assert_eq!(journalctl(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            journalctl("journalctl --vacuum-time=2weeks").reason,
+            "journalctl --vacuum-time requires confirmation"
+        );
+        let spec = JournalctlSpec::new(Decision::Deny);
+        let ctx = CommandContext::from_command("journalctl -f");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+}
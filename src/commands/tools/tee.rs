@@ -0,0 +1,214 @@
+//! Path-aware tee evaluation.
+//!
+//! Each file operand is classified against the `[paths]` rules: writes into
+//! the sandbox are allowed, writes (or `-a` appends) to protected or
+//! sensitive paths are denied, and anything else asks. Under a
+//! privilege-escalating wrapper (`echo ... | sudo tee /path`), writes outside
+//! the sandbox get the `[tee] privileged` decision.
+
+use super::super::CommandSpec;
+use crate::commands::args::{FlagSchema, ParsedArgs};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::{PathsConfig, TeeConfig};
+use crate::eval::paths::{PathPolicy, WriteTarget};
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// tee takes no flags with a separate value (`--output-error=MODE` is inline).
+const TEE_FLAGS: FlagSchema = FlagSchema {
+    short_value: "",
+    long_value: &[],
+};
+
+/// Device files that tee can write without touching the filesystem.
+const STREAM_TARGETS: &[&str] = &["/dev/null", "/dev/stdout", "/dev/stderr"];
+
+/// tee evaluator.
+///
+/// Evaluation order:
+/// 1. tee in the `deny` list → DENY
+/// 2. Each target contributes its path classification; the strictest wins:
+///    - sensitive or protected path → DENY
+///    - outside the sandbox → ASK (`privileged` under `sudo` and friends)
+///    - inside the sandbox → ALLOW
+///    - built by a command substitution (`tee $(...)`) → ASK
+/// 3. No file targets → ALLOW
+/// 4. ALLOW with redirection → ASK
+pub struct TeeSpec {
+    /// Baseline disposition (the flat-list entry for tee); only DENY applies.
+    base: Decision,
+    /// Decision for privileged writes outside the sandbox.
+    privileged: Decision,
+    /// Target path classification.
+    paths: PathPolicy,
+}
+
+impl TeeSpec {
+    /// Build a tee spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &TeeConfig, paths: &PathsConfig) -> Self {
+        Self {
+            base,
+            privileged: config.privileged,
+            paths: PathPolicy::from_config(paths),
+        }
+    }
+}
+
+impl CommandSpec for TeeSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        if self.base == Decision::Deny {
+            return SimpleCommandSpec::new(self.base).evaluate(ctx);
+        }
+
+        let args = ParsedArgs::parse(ctx.args(), &TEE_FLAGS);
+        let verb = if args.has_any(&["-a", "--append"]) {
+            "tee -a appends to"
+        } else {
+            "tee writes"
+        };
+        let mut result = RuleMatch {
+            decision: Decision::Allow,
+            reason: "tee to stdout".into(),
+        };
+        let mut first = true;
+        for file in &args.positionals {
+            if STREAM_TARGETS.contains(&file.as_str()) {
+                continue;
+            }
+            if ctx.is_substituted(file) {
+                let candidate = RuleMatch {
+                    decision: Decision::Ask,
+                    reason: format!("{verb} {file} (path known only at run time)"),
+                };
+                if first || candidate.decision > result.decision {
+                    result = candidate;
+                    first = false;
+                }
+                continue;
+            }
+            let target = self.paths.classify_write(file, &ctx.working_dir());
+            let candidate = match (&ctx.privileged_by, target) {
+                (Some(wrapper), WriteTarget::Outside) => RuleMatch {
+                    decision: self.privileged,
+                    reason: format!("{wrapper} {verb} {file} as root ({target})"),
+                },
+                _ => RuleMatch {
+                    decision: target.decision(),
+                    reason: format!("{verb} {file} ({target})"),
+                },
+            };
+            if first || candidate.decision > result.decision {
+                result = candidate;
+                first = false;
+            }
+        }
+
+        if result.decision == Decision::Allow
+            && let Some(ref r) = ctx.redirection
+        {
+            return RuleMatch {
+                decision: Decision::Ask,
+                reason: format!("{} with {}", result.reason, r),
+            };
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn spec() -> TeeSpec {
+        let config = Config::default_config();
+        TeeSpec::from_config(Decision::Ask, &config.tee, &config.paths)
+    }
+
+    fn eval(cmd: &str) -> Decision {
+        let s = spec();
+        let ctx = CommandContext::from_command(cmd);
+        s.evaluate(&ctx).decision
+    }
+
+    fn reason(cmd: &str) -> String {
+        let s = spec();
+        let ctx = CommandContext::from_command(cmd);
+        s.evaluate(&ctx).reason
+    }
+
+    fn eval_under(wrapper: &str, cmd: &str) -> RuleMatch {
+        let mut ctx = CommandContext::from_command(cmd);
+        ctx.privileged_by = Some(wrapper.into());
+        spec().evaluate(&ctx)
+    }
+
+    #[test]
+    fn allow_sandbox_targets() {
+        assert_eq!(eval("tee /tmp/scratch.log"), Decision::Allow);
+        assert_eq!(eval("tee -a build.log"), Decision::Allow);
+        assert_eq!(reason("tee /tmp/a"), "tee writes /tmp/a (sandbox)");
+    }
+
+    #[test]
+    fn allow_stdout_only() {
+        assert_eq!(eval("tee"), Decision::Allow);
+        assert_eq!(eval("tee /dev/null"), Decision::Allow);
+    }
+
+    #[test]
+    fn deny_protected_targets() {
+        assert_eq!(eval("tee /etc/hosts"), Decision::Deny);
+        assert_eq!(eval("tee -a ~/.bashrc"), Decision::Deny);
+        assert!(reason("tee -a /etc/sudoers").contains("tee -a appends to /etc/sudoers"));
+        assert!(reason("tee -a /etc/sudoers").contains("protected path /etc"));
+    }
+
+    #[test]
+    fn deny_sensitive_target() {
+        assert_eq!(eval("tee -a ~/.ssh/authorized_keys"), Decision::Deny);
+    }
+
+    #[test]
+    fn ask_outside_sandbox() {
+        assert_eq!(eval("tee /srv/data.txt"), Decision::Ask);
+    }
+
+    #[test]
+    fn strictest_target_wins() {
+        assert_eq!(eval("tee /tmp/a /etc/b /srv/c"), Decision::Deny);
+        assert_eq!(eval("tee /tmp/a /srv/c"), Decision::Ask);
+    }
+
+    #[test]
+    fn sudo_tee_outside_sandbox_denied() {
+        let m = eval_under("sudo", "tee /opt/app/config");
+        assert_eq!(m.decision, Decision::Deny);
+        assert!(m.reason.contains("sudo tee writes /opt/app/config as root"));
+        assert_eq!(
+            eval_under("sudo", "tee /tmp/x").decision,
+            Decision::Allow,
+            "the wrapper floor, not the spec, makes this ASK"
+        );
+    }
+
+    #[test]
+    fn deny_listed_tee() {
+        let config = Config::default_config();
+        let s = TeeSpec::from_config(Decision::Deny, &config.tee, &config.paths);
+        let ctx = CommandContext::from_command("tee /tmp/x");
+        assert_eq!(s.evaluate(&ctx).decision, Decision::Deny);
+    }
+
+    #[test]
+    fn substituted_target_asks() {
+        assert_eq!(eval("tee $(echo /etc/hosts)"), Decision::Ask);
+        assert_eq!(eval("tee /tmp/$(whoami)"), Decision::Ask);
+        assert!(reason("tee `pwd`/x").contains("path known only at run time"));
+        assert_eq!(eval("tee $(pwd)/a /etc/b"), Decision::Deny);
+    }
+
+    #[test]
+    fn allow_with_redirection_asks() {
+        assert_eq!(eval("tee /tmp/a > /tmp/b"), Decision::Ask);
+    }
+}
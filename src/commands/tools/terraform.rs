@@ -0,0 +1,259 @@
+//! Terraform and OpenTofu evaluation.
+//!
+//! Inspection (`plan`, `show`, `state list`) is allowed. `apply` and
+//! `destroy` get `[terraform] apply`. State surgery gets its own decisions,
+//! since it changes what terraform manages without touching the resources:
+//! `state rm`, `state mv` and `import` get `state_change`, and `state
+//! push`, `force-unlock` and `workspace delete`, which can discard state or
+//! break a lock another run holds, get `state_destructive`.
+
+use super::super::CommandSpec;
+use super::packages::{change, decided, query};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::TerraformConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+use agent_shell_parser::parse::Word;
+
+/// Flags that consume the next word when not given as `-flag=value`.
+/// Terraform's flags take one dash or two.
+const TERRAFORM_VALUES: &[&str] = &[
+    "var",
+    "var-file",
+    "target",
+    "replace",
+    "out",
+    "state",
+    "state-out",
+    "backup",
+    "lock-timeout",
+    "parallelism",
+    "backend-config",
+    "config",
+    "provider",
+];
+
+/// Flag names (without dashes or a value) and operands of `args`.
+fn split(args: &[Word]) -> (Vec<&str>, Vec<&str>) {
+    let mut flags = Vec::new();
+    let mut operands = Vec::new();
+    let mut args = args.iter().map(Word::as_str);
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.strip_prefix('-') else {
+            operands.push(arg);
+            continue;
+        };
+        let flag = flag.strip_prefix('-').unwrap_or(flag);
+        match flag.split_once('=') {
+            Some((name, _)) => flags.push(name),
+            None => {
+                if TERRAFORM_VALUES.contains(&flag) {
+                    args.next();
+                }
+                flags.push(flag);
+            }
+        }
+    }
+    (flags, operands)
+}
+
+/// Subcommands that only read.
+const QUERIES: &[&str] = &[
+    "version",
+    "validate",
+    "plan",
+    "show",
+    "output",
+    "providers",
+    "graph",
+    "console",
+    "state list",
+    "state show",
+    "workspace list",
+    "workspace show",
+];
+
+/// Subcommands that change the resources.
+const APPLIES: &[&str] = &["apply", "destroy"];
+
+/// Subcommands that change what the state tracks.
+const STATE_CHANGES: &[&str] = &[
+    "state rm",
+    "state mv",
+    "state replace-provider",
+    "import",
+    "taint",
+    "untaint",
+    "refresh",
+];
+
+/// Subcommands that overwrite or discard state, or break its lock.
+const STATE_DESTRUCTIVE: &[&str] = &["state push", "force-unlock", "workspace delete"];
+
+/// The entry of `list` the subcommand words match: two words (`state rm`)
+/// before one.
+fn listed<'a>(list: &[&'a str], words: &[&str]) -> Option<&'a str> {
+    let one = words.first()?;
+    let two = words.get(1).map(|second| format!("{one} {second}"));
+    list.iter()
+        .find(|entry| two.as_deref() == Some(**entry))
+        .or_else(|| list.iter().find(|entry| *entry == one))
+        .copied()
+}
+
+/// Terraform evaluator for terraform and tofu.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `plan`, `show`, `output`, `validate`, `state list`/`show` → ALLOW
+///    (ASK with redirection)
+/// 3. `state pull` → ASK (it prints the state, secrets included)
+/// 4. `apply`, `destroy` → `[terraform] apply`, noting `-auto-approve`
+/// 5. `state rm`/`mv`, `import`, `taint` → `[terraform] state_change`
+/// 6. `state push`, `force-unlock`, `workspace delete` →
+///    `[terraform] state_destructive`
+/// 7. Everything else (`init`, `workspace select`) → the base decision
+pub struct TerraformSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Decision for changing the resources.
+    apply: Decision,
+    /// Decision for changing what the state tracks.
+    state_change: Decision,
+    /// Decision for overwriting or discarding state.
+    state_destructive: Decision,
+}
+
+impl TerraformSpec {
+    /// Build a terraform spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &TerraformConfig) -> Self {
+        Self {
+            base,
+            apply: config.apply,
+            state_change: config.state_change,
+            state_destructive: config.state_destructive,
+        }
+    }
+}
+
+impl CommandSpec for TerraformSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        let (flags, words) = split(ctx.args());
+        let words = words.as_slice();
+        let tool = &ctx.base_command;
+        if let Some(entry) = listed(QUERIES, words) {
+            return query(ctx, &format!("{tool} {entry}"));
+        }
+        if words.starts_with(&["state", "pull"]) {
+            let note = "prints the state, secrets included";
+            return change(&format!("{tool} state pull"), None, Some(note));
+        }
+        if let Some(entry) = listed(APPLIES, words) {
+            let what = format!("{tool} {entry}");
+            return match self.apply {
+                Decision::Ask if flags.contains(&"auto-approve") => change(
+                    &what,
+                    None,
+                    Some("-auto-approve skips terraform's own prompt"),
+                ),
+                decision => decided(decision, &what, None),
+            };
+        }
+        if let Some(entry) = listed(STATE_CHANGES, words) {
+            return decided(self.state_change, &format!("{tool} {entry}"), None);
+        }
+        if let Some(entry) = listed(STATE_DESTRUCTIVE, words) {
+            return decided(self.state_destructive, &format!("{tool} {entry}"), None);
+        }
+        base.evaluate(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let spec = TerraformSpec::from_config(Decision::Ask, &Config::default_config().terraform);
+        spec.evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn inspection_allows() {
+        for cmd in [
+            "terraform plan",
+            "terraform -chdir=infra plan -out=tf.plan",
+            "terraform show -json tf.plan",
+            "terraform state list",
+            "terraform state show aws_instance.web",
+            "terraform workspace list",
+            "tofu validate",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(
+            eval("terraform state list").reason,
+            "read-only terraform state list"
+        );
+        assert_eq!(eval("terraform plan > plan.txt").decision, Decision::Ask);
+    }
+
+    #[test]
+    fn apply_and_state_changes_ask() {
+        for cmd in [
+            "terraform apply",
+            "terraform destroy",
+            "terraform state rm aws_instance.web",
+            "terraform state mv aws_instance.a aws_instance.b",
+            "terraform import aws_instance.web i-123",
+            "terraform state pull",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(
+            eval("terraform apply -auto-approve").reason,
+            "terraform apply requires confirmation (-auto-approve skips terraform's own prompt)"
+        );
+        assert_eq!(
+            eval("terraform state rm aws_instance.web").reason,
+            "terraform state rm requires confirmation"
+        );
+    }
+
+    #[test]
+    fn state_destruction_denies() {
+        for cmd in [
+            "terraform force-unlock -force 6638b3f1",
+            "terraform workspace delete staging",
+            "terraform state push -force old.tfstate",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Deny, "{cmd}");
+        }
+        assert_eq!(
+            eval("terraform workspace delete staging").reason,
+            "blocked command: terraform workspace delete"
+        );
+    }
+
+    #[test]
+    fn decisions_are_configurable() {
+        let config = TerraformConfig {
+            apply: Decision::Deny,
+            state_change: Decision::Deny,
+            state_destructive: Decision::Ask,
+        };
+        let spec = TerraformSpec::from_config(Decision::Ask, &config);
+        let eval = |cmd| spec.evaluate(&CommandContext::from_command(cmd)).decision;
+        assert_eq!(eval("terraform apply"), Decision::Deny);
+        assert_eq!(eval("terraform import a.b id"), Decision::Deny);
+        assert_eq!(eval("terraform force-unlock 1"), Decision::Ask);
+        assert_eq!(eval("terraform init"), Decision::Ask);
+        let spec = TerraformSpec::from_config(Decision::Deny, &config);
+        let ctx = CommandContext::from_command("terraform plan");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+}
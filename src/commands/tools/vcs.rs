@@ -0,0 +1,386 @@
+//! Legacy version control evaluation: svn and hg (Mercurial).
+//!
+//! Inspection (`svn status`, `hg log`) is allowed. Commits, pushes,
+//! updates and other changes to the working copy or repository get `[vcs]
+//! change`. Operations that throw away work (`hg strip`, `hg purge`, `svn
+//! delete --force`, `svn revert`) get `destructive`, and the reason says
+//! what is lost.
+
+use super::super::CommandSpec;
+use super::packages::{change, decided, query};
+use crate::commands::simple::SimpleCommandSpec;
+use crate::config::VcsConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+use agent_shell_parser::parse::Word;
+
+/// svn options that consume the next word.
+const SVN_VALUES: &[&str] = &[
+    "-m",
+    "--message",
+    "-F",
+    "--file",
+    "-r",
+    "--revision",
+    "-c",
+    "--change",
+    "-l",
+    "--limit",
+    "-x",
+    "--extensions",
+    "--depth",
+    "--set-depth",
+    "--targets",
+    "--changelist",
+    "--cl",
+    "--username",
+    "--password",
+    "--encoding",
+    "--config-dir",
+    "--config-option",
+    "--with-revprop",
+    "--accept",
+    "--search",
+    "--diff-cmd",
+    "--editor-cmd",
+];
+
+/// svn subcommands (and their aliases) that only read.
+const SVN_QUERIES: &[&str] = &[
+    "status",
+    "st",
+    "stat",
+    "log",
+    "diff",
+    "di",
+    "info",
+    "blame",
+    "praise",
+    "annotate",
+    "ann",
+    "cat",
+    "list",
+    "ls",
+    "proplist",
+    "plist",
+    "pl",
+    "propget",
+    "pget",
+    "pg",
+    "mergeinfo",
+    "help",
+    "h",
+    "?",
+];
+
+/// svn subcommands that throw away work, with what they lose and the flag
+/// that makes them do it (none: always).
+const SVN_DESTRUCTIVE: &[(&str, Option<&str>, &str)] = &[
+    (
+        "delete",
+        Some("--force"),
+        "deletes modified or unversioned files",
+    ),
+    (
+        "del",
+        Some("--force"),
+        "deletes modified or unversioned files",
+    ),
+    (
+        "remove",
+        Some("--force"),
+        "deletes modified or unversioned files",
+    ),
+    (
+        "rm",
+        Some("--force"),
+        "deletes modified or unversioned files",
+    ),
+    ("revert", None, "discards local changes"),
+    (
+        "cleanup",
+        Some("--remove-unversioned"),
+        "deletes unversioned files",
+    ),
+];
+
+/// hg options that consume the next word.
+const HG_VALUES: &[&str] = &[
+    "-R",
+    "--repository",
+    "--cwd",
+    "--config",
+    "--encoding",
+    "-m",
+    "--message",
+    "-l",
+    "--logfile",
+    "-r",
+    "--rev",
+    "-b",
+    "--branch",
+    "-u",
+    "--user",
+    "-d",
+    "--date",
+    "-I",
+    "--include",
+    "-X",
+    "--exclude",
+    "-T",
+    "--template",
+    "-e",
+    "--ssh",
+    "--remotecmd",
+    "-s",
+    "--source",
+    "--dest",
+];
+
+/// hg commands (and their aliases) that only read.
+const HG_QUERIES: &[&str] = &[
+    "status",
+    "st",
+    "log",
+    "history",
+    "diff",
+    "summary",
+    "sum",
+    "heads",
+    "tags",
+    "identify",
+    "id",
+    "annotate",
+    "blame",
+    "cat",
+    "files",
+    "manifest",
+    "paths",
+    "root",
+    "parents",
+    "tip",
+    "grep",
+    "incoming",
+    "in",
+    "outgoing",
+    "out",
+    "config",
+    "showconfig",
+    "help",
+    "version",
+];
+
+/// hg commands that only read when given no names: with one, they create,
+/// rename or move a branch or bookmark.
+const HG_LISTINGS: &[&str] = &["branch", "branches", "bookmarks", "bookmark"];
+
+/// hg commands that throw away work, with what they lose and the flag that
+/// makes them do it (none: always).
+const HG_DESTRUCTIVE: &[(&str, Option<&str>, &str)] = &[
+    ("strip", None, "removes changesets from the repository"),
+    ("prune", None, "removes changesets from the repository"),
+    ("rollback", None, "undoes the last transaction"),
+    ("purge", None, "deletes untracked files"),
+    ("clean", None, "deletes untracked files"),
+    ("update", Some("-C"), "discards local changes"),
+    ("update", Some("--clean"), "discards local changes"),
+    ("up", Some("-C"), "discards local changes"),
+    ("up", Some("--clean"), "discards local changes"),
+    ("checkout", Some("-C"), "discards local changes"),
+    ("checkout", Some("--clean"), "discards local changes"),
+    ("revert", None, "discards local changes"),
+];
+
+/// The subcommand and the flags and operands around it, skipping global
+/// options (and the values of `values`) before it.
+fn split<'a>(args: &'a [Word], values: &[&str]) -> Option<(&'a str, Vec<&'a str>, Vec<&'a str>)> {
+    let mut sub = None;
+    let mut flags = Vec::new();
+    let mut operands = Vec::new();
+    let mut args = args.iter().map(Word::as_str);
+    while let Some(arg) = args.next() {
+        if arg.starts_with('-') && arg.len() > 1 {
+            if values.contains(&arg) {
+                args.next();
+            }
+            flags.push(arg);
+        } else if sub.is_none() {
+            sub = Some(arg);
+        } else {
+            operands.push(arg);
+        }
+    }
+    Some((sub?, flags, operands))
+}
+
+/// svn and hg evaluator.
+///
+/// Evaluation order:
+/// 1. Denied by the flat `[commands]` lists → DENY
+/// 2. `svn status`/`log`/`diff`/`info`, `hg status`/`log`/`diff`, `hg
+///    branch`/`bookmarks` without a name, `--version` → ALLOW (ASK with
+///    redirection)
+/// 3. `hg strip`/`purge`/`rollback`/`revert`/`update --clean`, `svn delete
+///    --force`/`revert` → `[vcs] destructive`
+/// 4. Everything else (`commit`, `push`, `update`, `switch`) → `[vcs]
+///    change`
+pub struct VcsSpec {
+    /// Baseline disposition (the flat-list entry for the command).
+    base: Decision,
+    /// Decision for a change to the working copy or repository.
+    change: Decision,
+    /// Decision for an operation that throws away work.
+    destructive: Decision,
+}
+
+impl VcsSpec {
+    /// Build an svn or hg spec from its flat-list decision and configuration.
+    pub fn from_config(base: Decision, config: &VcsConfig) -> Self {
+        Self {
+            base,
+            change: config.change,
+            destructive: config.destructive,
+        }
+    }
+
+    /// `[vcs] destructive` for `what`, saying what it loses when it asks.
+    fn destructive(&self, what: &str, note: &str) -> RuleMatch {
+        match self.destructive {
+            Decision::Ask => change(what, None, Some(note)),
+            decision => decided(decision, what, None),
+        }
+    }
+}
+
+impl CommandSpec for VcsSpec {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        let base = SimpleCommandSpec::new(self.base);
+        if self.base == Decision::Deny {
+            return base.evaluate(ctx);
+        }
+        let tool = ctx.base_command.as_str();
+        let (values, queries, destructive) = match tool {
+            "svn" => (SVN_VALUES, SVN_QUERIES, SVN_DESTRUCTIVE),
+            "hg" => (HG_VALUES, HG_QUERIES, HG_DESTRUCTIVE),
+            _ => return base.evaluate(ctx),
+        };
+        let Some((sub, flags, operands)) = split(ctx.args(), values) else {
+            if ctx.has_flag("--version") {
+                return query(ctx, &format!("{tool} --version"));
+            }
+            return base.evaluate(ctx);
+        };
+        let what = format!("{tool} {sub}");
+        let listing = tool == "hg"
+            && HG_LISTINGS.contains(&sub)
+            && operands.is_empty()
+            && !flags
+                .iter()
+                .any(|f| matches!(*f, "-d" | "--delete" | "-f" | "--force" | "-C" | "--clean"));
+        if queries.contains(&sub) || listing {
+            return query(ctx, &what);
+        }
+        let lost = destructive
+            .iter()
+            .find(|(name, flag, _)| *name == sub && flag.is_none_or(|f| flags.contains(&f)));
+        if let Some((_, flag, note)) = lost {
+            let what = match flag {
+                Some(flag) => format!("{what} {flag}"),
+                None => what,
+            };
+            return self.destructive(&what, note);
+        }
+        decided(self.change, &what, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn eval(cmd: &str) -> RuleMatch {
+        let spec = VcsSpec::from_config(Decision::Ask, &Config::default_config().vcs);
+        spec.evaluate(&CommandContext::from_command(cmd))
+    }
+
+    #[test]
+    fn inspection_allows() {
+        for cmd in [
+            "svn status",
+            "svn st -u",
+            "svn log -l 10 -v",
+            "svn diff -r 100:HEAD",
+            "svn info https://svn.example.com/repo",
+            "svn --non-interactive status",
+            "svn --version",
+            "hg status",
+            "hg log -r tip -T '{node}'",
+            "hg diff -c tip",
+            "hg -R ../other summary",
+            "hg branch",
+            "hg bookmarks",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Allow, "{cmd}");
+        }
+        assert_eq!(eval("hg log").reason, "read-only hg log");
+        assert_eq!(eval("svn log > log.txt").decision, Decision::Ask);
+    }
+
+    #[test]
+    fn changes_ask() {
+        for cmd in [
+            "svn commit -m 'Fix build'",
+            "svn delete old.c",
+            "svn switch ^/branches/feature",
+            "svn update",
+            "hg commit -m 'Fix build'",
+            "hg push",
+            "hg pull -u",
+            "hg branch feature",
+            "hg bookmark -d old",
+        ] {
+            assert_eq!(eval(cmd).decision, Decision::Ask, "{cmd}");
+        }
+        assert_eq!(eval("hg push").reason, "hg push requires confirmation");
+        assert_eq!(eval("svn ci -m msg").reason, "svn ci requires confirmation");
+    }
+
+    #[test]
+    fn destructive_says_what_is_lost() {
+        assert_eq!(
+            eval("hg strip -r 42").reason,
+            "hg strip requires confirmation (removes changesets from the repository)"
+        );
+        assert_eq!(
+            eval("svn delete --force build").reason,
+            "svn delete --force requires confirmation (deletes modified or unversioned files)"
+        );
+        assert_eq!(
+            eval("hg update -C default").reason,
+            "hg update -C requires confirmation (discards local changes)"
+        );
+        assert_eq!(
+            eval("svn revert -R .").reason,
+            "svn revert requires confirmation (discards local changes)"
+        );
+        assert_eq!(
+            eval("svn delete build").reason,
+            "svn delete requires confirmation"
+        );
+    }
+
+    #[test]
+    fn decisions_are_configurable() {
+        let config = VcsConfig {
+            change: Decision::Allow,
+            destructive: Decision::Deny,
+        };
+        let spec = VcsSpec::from_config(Decision::Ask, &config);
+        let eval = |cmd| spec.evaluate(&CommandContext::from_command(cmd));
+        assert_eq!(eval("hg commit -m x").decision, Decision::Allow);
+        assert_eq!(eval("hg strip tip").reason, "blocked command: hg strip");
+        let spec = VcsSpec::from_config(Decision::Deny, &config);
+        let ctx = CommandContext::from_command("svn status");
+        assert_eq!(spec.evaluate(&ctx).decision, Decision::Deny);
+    }
+}
@@ -3,9 +3,13 @@
 //! cc-toolgate ships with sensible defaults embedded in the binary via
 //! `config.default.toml`. Overlays merge on top in this order (later wins):
 //!
-//! 1. Embedded defaults.
-//! 2. User overlay at `~/.config/cc-toolgate/config.toml`.
-//! 3. Project overlay at `<git-root>/.claude/cc-toolgate.toml` (if CWD is
+//! 1. Embedded defaults, then the platform's (`config.linux.toml`,
+//!    `config.macos.toml`) on top.
+//! 2. User overlay at `~/.config/cc-toolgate/config.toml` (under
+//!    `$XDG_CONFIG_HOME` when that is set), or the file named by
+//!    `--config` / `CC_TOOLGATE_CONFIG`.
+//! 3. A signed team policy from `[remote] config_url` (`remote` feature).
+//! 4. Project overlay at `<git-root>/.claude/cc-toolgate.toml` (if CWD is
 //!    inside a git repo). Lets a project permit extra commands without
 //!    loosening user-global rules.
 //!
@@ -15,17 +19,22 @@
 //! operations only — `replace` and `remove_*` fields are stripped for
 //! security (a repo should not be able to weaken user-global rules).
 
+use crate::eval::Decision;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Embedded default configuration (compiled into the binary from `config.default.toml`).
 const DEFAULT_CONFIG: &str = include_str!("../config.default.toml");
+/// Defaults added on Linux.
+const LINUX_DEFAULTS: &str = include_str!("../config.linux.toml");
+/// Defaults added on macOS.
+const MACOS_DEFAULTS: &str = include_str!("../config.macos.toml");
 
 // ── Final (merged) config types ──
 
 /// Top-level configuration, produced by merging embedded defaults with
 /// an optional user overlay from `~/.config/cc-toolgate/config.toml`.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
     /// Global settings (e.g. escalate_deny).
     #[serde(default)]
@@ -48,6 +57,122 @@ pub struct Config {
     /// GitHub CLI (gh) subcommand-aware evaluation rules.
     #[serde(default)]
     pub gh: GhConfig,
+    /// hub (GitHub) rules, read the same way as `gh`'s.
+    #[serde(default)]
+    pub hub: GhConfig,
+    /// tea (Gitea) rules, read the same way as `gh`'s.
+    #[serde(default)]
+    pub tea: GhConfig,
+    /// fj (Forgejo) rules, read the same way as `gh`'s.
+    #[serde(default)]
+    pub fj: GhConfig,
+    /// Debian package manager (apt, apt-get, dpkg) evaluation rules.
+    #[serde(default)]
+    pub apt: AptConfig,
+    /// Fedora/RHEL package manager (dnf, yum, microdnf) evaluation rules.
+    #[serde(default)]
+    pub dnf: DnfConfig,
+    /// Filesystem path classification lists (e.g. sensitive credential stores).
+    #[serde(default)]
+    pub paths: PathsConfig,
+    /// scp transfer-direction decisions.
+    #[serde(default)]
+    pub scp: ScpConfig,
+    /// rsync destructive-flag and remote-destination rules.
+    #[serde(default)]
+    pub rsync: RsyncConfig,
+    /// Network request rules shared by curl, wget, and other network clients.
+    #[serde(default)]
+    pub network: NetworkConfig,
+    /// Raw network tool (nc, ncat, socat, telnet) decisions.
+    #[serde(default)]
+    pub raw_network: RawNetworkConfig,
+    /// Firewall rule changes (iptables, nft).
+    #[serde(default)]
+    pub firewall: FirewallConfig,
+    /// tee target rules beyond the `[paths]` classification.
+    #[serde(default)]
+    pub tee: TeeConfig,
+    /// rm rules for recursive deletes of catastrophic targets.
+    #[serde(default)]
+    pub rm: RmConfig,
+    /// chmod / chown / chgrp rules for recursive changes of system roots.
+    #[serde(default)]
+    pub permissions: PermissionsConfig,
+    /// User and group account changes (useradd, usermod, passwd).
+    #[serde(default)]
+    pub accounts: AccountsConfig,
+    /// AWS CLI rules (`aws s3` actions).
+    #[serde(default)]
+    pub aws: AwsConfig,
+    /// terraform and tofu apply and state-command rules.
+    #[serde(default)]
+    pub terraform: TerraformConfig,
+    /// `docker run`/`create` rules (also podman and nerdctl).
+    #[serde(default)]
+    pub docker: DockerConfig,
+    /// ansible and ansible-playbook run rules.
+    #[serde(default)]
+    pub ansible: AnsibleConfig,
+    /// gpg and openssl key-material rules.
+    #[serde(default)]
+    pub crypto: CryptoConfig,
+    /// ssh-keygen, ssh-add and ssh-copy-id rules.
+    #[serde(default)]
+    pub ssh_keys: SshKeysConfig,
+    /// svn and hg rules.
+    #[serde(default)]
+    pub vcs: VcsConfig,
+    /// bazel and bazelisk rules.
+    #[serde(default)]
+    pub bazel: BazelConfig,
+    /// gradle and `./gradlew` rules.
+    #[serde(default)]
+    pub gradle: JvmConfig,
+    /// mvn and `./mvnw` rules, read the same way as gradle's.
+    #[serde(default)]
+    pub maven: JvmConfig,
+    /// Floor decisions for subshells and brace groups.
+    #[serde(default)]
+    pub groups: GroupsConfig,
+    /// cmd.exe commands run with `cmd /c`.
+    #[serde(default)]
+    pub cmd_exe: CmdExeConfig,
+    /// Output redirection targets that don't count as writes.
+    #[serde(default)]
+    pub redirection: RedirectionConfig,
+    /// Destructive commands whose unquoted globs escalate.
+    #[serde(default)]
+    pub wildcards: WildcardsConfig,
+    /// Inline code (`python -c`, `node -e`, awk programs) scanning rules,
+    /// keyed by language.
+    #[serde(default)]
+    pub inline_code: InlineCodeConfig,
+    /// Organization-specific guidance attached to ASK/DENY reasons, keyed by
+    /// command pattern.
+    #[serde(default)]
+    pub guidance: GuidanceConfig,
+    /// Signed approval tokens for pre-approved, non-interactive runs.
+    #[serde(default)]
+    pub approval_tokens: ApprovalTokensConfig,
+    /// Out-of-tree command specs loaded from dynamic libraries.
+    #[serde(default)]
+    pub plugins: PluginsConfig,
+    /// External evaluator script for policies the built-in rules can't express.
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    /// Signed team policy fetched over HTTPS.
+    #[serde(default)]
+    pub remote: RemoteConfig,
+    /// Trust tiers keyed by directory glob, for use in rule conditions.
+    #[serde(default)]
+    pub trust: HashMap<String, String>,
+    /// Named time windows that restrict when a rule applies.
+    #[serde(default)]
+    pub schedules: HashMap<String, ScheduleConfig>,
+    /// Conditional rules, checked in order before any other rule.
+    #[serde(default)]
+    pub rules: Vec<RuleConfig>,
     /// Path to the project overlay file, if one was loaded.
     /// Set by [`Config::load()`] when a project-level `.claude/cc-toolgate.toml`
     /// is found. Used to annotate ASK decisions with provenance.
@@ -55,21 +180,109 @@ pub struct Config {
     pub project_overlay_path: Option<std::path::PathBuf>,
 }
 
+/// Whether decisions are enforced or only recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Return the evaluated decision to the hook.
+    #[default]
+    Enforce,
+    /// Always return ALLOW to the hook; log the decision that would have applied.
+    Audit,
+}
+
+/// The shell whose syntax commands are read in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    /// Parse commands as bash.
+    #[default]
+    Bash,
+    /// Rewrite zsh-only syntax (`=(cmd)`, glob qualifiers, expansion flags)
+    /// into its bash equivalent before parsing.
+    Zsh,
+}
+
+/// Whose platform-specific defaults apply on top of `config.default.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    /// The platform cc-toolgate was built for.
+    #[default]
+    Auto,
+    /// `config.linux.toml`.
+    Linux,
+    /// `config.macos.toml`.
+    Macos,
+    /// No platform defaults, only the shared ones.
+    Generic,
+}
+
+impl Platform {
+    /// The platform `self` stands for, with `auto` resolved to the build
+    /// target (`generic` on anything but Linux and macOS).
+    pub fn resolve(self) -> Self {
+        match self {
+            Platform::Auto if cfg!(target_os = "linux") => Platform::Linux,
+            Platform::Auto if cfg!(target_os = "macos") => Platform::Macos,
+            Platform::Auto => Platform::Generic,
+            platform => platform,
+        }
+    }
+
+    /// The embedded overlay of this platform's defaults, if it has one.
+    fn defaults(self) -> Option<&'static str> {
+        match self.resolve() {
+            Platform::Linux => Some(LINUX_DEFAULTS),
+            Platform::Macos => Some(MACOS_DEFAULTS),
+            _ => None,
+        }
+    }
+}
+
 /// Global settings that affect evaluation behavior.
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Settings {
+    /// `enforce` (default) or `audit`, which never blocks but logs what the
+    /// decision would have been.
+    #[serde(default)]
+    pub mode: Mode,
     /// When true, DENY decisions are escalated to ASK (the user is prompted
     /// instead of being blocked). Useful for operators who want visibility
     /// without hard blocks.
     #[serde(default)]
     pub escalate_deny: bool,
+    /// When true, a command the user approves at an ASK prompt is allowed
+    /// for the rest of that Claude Code session (exact command text only).
+    /// Requires the PostToolUse hook.
+    #[serde(default)]
+    pub session_grants: bool,
+    /// When true, Bash patterns in Claude Code's `permissions` are imported
+    /// as [`rules`](Config::rules): all of `~/.claude/settings.json`, and the
+    /// deny patterns of the project's `.claude/settings.json` and
+    /// `.claude/settings.local.json`.
+    #[serde(default)]
+    pub import_claude_permissions: bool,
+    /// Evaluations slower than this many milliseconds print a warning to
+    /// stderr. `0` disables the warning.
+    #[serde(default)]
+    pub slow_warning_ms: u64,
+    /// `bash` (default) or `zsh`, for sessions whose commands use zsh
+    /// syntax.
+    #[serde(default)]
+    pub shell: Shell,
+    /// Which platform's defaults apply: `auto` (default), `linux`, `macos`
+    /// or `generic`. Read from the user config (and profile) before the
+    /// defaults are built, so it can't be changed by later overlays.
+    #[serde(default)]
+    pub platform: Platform,
 }
 
 /// Flat command name → decision mappings for simple commands.
 ///
 /// Commands in `allow` run silently, `ask` prompts the user, `deny` blocks outright.
 /// Unrecognized commands default to ASK.
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Commands {
     /// Commands that run silently (e.g. `ls`, `cat`, `grep`).
     #[serde(default)]
@@ -85,7 +298,7 @@ pub struct Commands {
 /// Commands that execute their arguments as subcommands.
 /// The wrapped command is extracted and evaluated; the final decision
 /// is max(floor, wrapped_command_decision).
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct WrapperConfig {
     /// Wrappers with Allow floor: wrapper is safe, wrapped command determines disposition.
     /// e.g. xargs, parallel, env, nohup, nice, timeout, time, watch
@@ -95,14 +308,26 @@ pub struct WrapperConfig {
     /// e.g. sudo, doas, pkexec
     #[serde(default)]
     pub ask_floor: Vec<String>,
+    /// Decision for an interactive privileged shell (`sudo -i`, `sudo su`,
+    /// `sudo bash`), whose commands are never seen.
+    #[serde(default)]
+    pub interactive_shell: Decision,
+    /// Floor for launching an app or container (`flatpak run`, `snap run`,
+    /// `toolbox run`, `distrobox enter`). A command run inside can escalate.
+    #[serde(default)]
+    pub app_launch: Decision,
 }
 
 /// Git subcommand evaluation rules.
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct GitConfig {
     /// Subcommands that are always allowed (e.g. `status`, `log`, `diff`, `branch`).
     #[serde(default)]
     pub read_only: Vec<String>,
+    /// Subcommand actions that always require confirmation, matched before
+    /// `read_only` (e.g. `remote add`, `remote set-url`).
+    #[serde(default)]
+    pub mutating: Vec<String>,
     /// Subcommands that are allowed only when all `config_env` entries match
     /// (e.g. `push`, `pull` when `GIT_CONFIG_GLOBAL=~/.gitconfig.ai`).
     #[serde(default)]
@@ -117,10 +342,26 @@ pub struct GitConfig {
     /// Force-pushes always require confirmation regardless of env-gating.
     #[serde(default)]
     pub force_push_flags: Vec<String>,
+    /// `git config` keys that run commands or handle credentials
+    /// (`core.sshCommand`, `credential.*`). `*` matches any run of
+    /// characters; matching ignores case.
+    #[serde(default)]
+    pub sensitive_config_keys: Vec<String>,
+    /// Decision for writing a `sensitive_config_keys` key in any scope, or
+    /// `git config --edit`.
+    #[serde(default)]
+    pub sensitive_config: Decision,
+    /// Decision for writing other keys with `--global`, `--system` or
+    /// `--file`.
+    #[serde(default)]
+    pub global_config: Decision,
+    /// Decision for writing other keys in the repository's own config.
+    #[serde(default)]
+    pub local_config: Decision,
 }
 
 /// Cargo subcommand evaluation rules.
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct CargoConfig {
     /// Subcommands that are always allowed (e.g. `build`, `test`, `check`, `clippy`).
     #[serde(default)]
@@ -134,7 +375,7 @@ pub struct CargoConfig {
 }
 
 /// kubectl subcommand evaluation rules.
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct KubectlConfig {
     /// Read-only subcommands that are always allowed (e.g. `get`, `describe`, `logs`).
     #[serde(default)]
@@ -148,13 +389,44 @@ pub struct KubectlConfig {
     /// Environment variable requirements for `allowed_with_config` subcommands.
     #[serde(default)]
     pub config_env: HashMap<String, String>,
+    /// Resources whose `get` or `describe` asks even though the subcommand
+    /// is read-only (e.g. `secrets`): the output is the credential.
+    #[serde(default)]
+    pub sensitive_resources: Vec<String>,
+}
+
+/// apt, apt-get and apt-cache subcommand evaluation rules.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct AptConfig {
+    /// Subcommands that only read (e.g. `list`, `show`, `policy`, `download`).
+    #[serde(default)]
+    pub read_only: Vec<String>,
+    /// Subcommands that change the installed packages (e.g. `install`, `purge`).
+    #[serde(default)]
+    pub mutating: Vec<String>,
+}
+
+/// dnf, yum and microdnf subcommand evaluation rules. Entries may be one
+/// word (`install`) or two (`group install`).
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct DnfConfig {
+    /// Subcommands that only read (e.g. `list`, `info`, `repoquery`).
+    #[serde(default)]
+    pub read_only: Vec<String>,
+    /// Subcommands that change the installed packages (e.g. `install`, `swap`).
+    #[serde(default)]
+    pub mutating: Vec<String>,
+    /// Decision for `history undo`, `redo` and `rollback`, which replay or
+    /// reverse an earlier transaction.
+    #[serde(default)]
+    pub history_undo: Decision,
 }
 
 /// GitHub CLI (gh) subcommand evaluation rules.
 ///
 /// gh uses two-word subcommands (e.g. `pr list`, `issue create`), so
 /// both two-word and one-word matches are checked.
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct GhConfig {
     /// Read-only subcommands (e.g. `pr list`, `pr view`, `status`, `api`).
     #[serde(default)]
@@ -168,229 +440,1436 @@ pub struct GhConfig {
     /// Environment variable requirements for `allowed_with_config` subcommands.
     #[serde(default)]
     pub config_env: HashMap<String, String>,
+    /// Extensions allowed to run, by name without the `gh-` prefix
+    /// (`dash` for `gh dash`).
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Decision for running any other extension.
+    #[serde(default)]
+    pub extension: Decision,
 }
 
-// ── Overlay types (user config that merges with defaults) ──
-//
-// These mirror the public config types but use `Option` for scalars and
-// include `replace` flags and `remove_*` lists for the merge system.
-
-/// User-provided configuration overlay, deserialized from `~/.config/cc-toolgate/config.toml`.
-#[derive(Debug, Deserialize, Default)]
-struct ConfigOverlay {
+/// Filesystem path lists shared by path-aware specs.
+///
+/// Entries support `~` and `$VAR` expansion and `*`, `**`, `?` globs. An
+/// entry covers everything beneath it.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct PathsConfig {
+    /// Credential and secret stores that must not leave the machine
+    /// (e.g. `~/.ssh`, `~/.aws`, `~/.netrc`).
     #[serde(default)]
-    settings: SettingsOverlay,
+    pub sensitive: Vec<String>,
+    /// Locations that must not be written (e.g. `/etc`, `/usr`, shell rc files).
     #[serde(default)]
-    commands: CommandsOverlay,
+    pub protected: Vec<String>,
+    /// Directories where file writes are considered safe (e.g. `/tmp`).
     #[serde(default)]
-    wrappers: WrappersOverlay,
+    pub sandbox: Vec<String>,
+    /// Treat the git repository containing the working directory as part
+    /// of the sandbox.
     #[serde(default)]
-    git: GitOverlay,
+    pub project_sandbox: bool,
+}
+
+/// scp evaluation rules, keyed by transfer direction.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct ScpConfig {
+    /// Local → remote copies (e.g. `scp file host:`).
     #[serde(default)]
-    cargo: CargoOverlay,
+    pub upload: Decision,
+    /// Remote → local copies (e.g. `scp host:file .`).
     #[serde(default)]
-    kubectl: KubectlOverlay,
+    pub download: Decision,
+    /// Uploads of a `[paths] sensitive` location, including recursive
+    /// uploads of a directory that contains one (e.g. `scp -r ~ host:`),
+    /// and downloads that overwrite one.
     #[serde(default)]
-    gh: GhOverlay,
+    pub sensitive: Decision,
 }
 
-#[derive(Debug, Deserialize, Default)]
-struct SettingsOverlay {
-    escalate_deny: Option<bool>,
+/// rsync evaluation rules.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct RsyncConfig {
+    /// Flags that delete files at the destination or the source
+    /// (e.g. `--delete`, `--delete-excluded`, `--remove-source-files`).
+    #[serde(default)]
+    pub destructive_flags: Vec<String>,
+    /// Decision when any `destructive_flags` entry is present.
+    #[serde(default)]
+    pub destructive: Decision,
+    /// Decision when the destination is on a remote host
+    /// (`host:path`, `host::module`, `rsync://host/...`).
+    #[serde(default)]
+    pub remote_destination: Decision,
+    /// Allow `-n` / `--dry-run` invocations whose operands are all local.
+    #[serde(default)]
+    pub allow_local_dry_run: bool,
 }
 
-#[derive(Debug, Deserialize, Default)]
-struct WrappersOverlay {
+/// Network request rules shared by network client specs.
+///
+/// Domain entries match the host and its subdomains; `*.example.com`
+/// matches subdomains only.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct NetworkConfig {
+    /// Domains whose read-only requests can be auto-allowed
+    /// (e.g. `github.com`, `crates.io`).
     #[serde(default)]
-    replace: bool,
+    pub allowed_domains: Vec<String>,
+    /// Domains whose requests are always denied (e.g. paste and webhook
+    /// services commonly used for exfiltration).
     #[serde(default)]
-    allow_floor: Vec<String>,
+    pub denied_domains: Vec<String>,
+    /// Decision when a request sends local file contents to a remote host
+    /// (`curl -T file`, `curl -F f=@file`, `curl --data-binary @file`,
+    /// `wget --post-file file`). Uploads of `[paths] sensitive` files always deny.
     #[serde(default)]
-    ask_floor: Vec<String>,
+    pub file_upload: Decision,
+    /// Cloud metadata hostnames and addresses (e.g. `169.254.169.254`,
+    /// `metadata.google.internal`). Matched exactly; IP entries also match
+    /// alternate spellings of the same address.
     #[serde(default)]
-    remove_allow_floor: Vec<String>,
+    pub internal_hosts: Vec<String>,
+    /// Decision for requests to an `internal_hosts` entry or a private,
+    /// link-local, or unique-local IP address, regardless of the domain lists.
     #[serde(default)]
-    remove_ask_floor: Vec<String>,
+    pub internal_network: Decision,
 }
 
-#[derive(Debug, Deserialize, Default)]
-struct CommandsOverlay {
+/// Raw network tool rules (nc, ncat, netcat, socat, telnet), keyed by
+/// connection mode.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct RawNetworkConfig {
+    /// Outbound client connections (e.g. `nc host 80`, `telnet host`).
     #[serde(default)]
-    replace: bool,
-    #[serde(default)]
-    allow: Vec<String>,
+    pub client: Decision,
+    /// Listening sockets (e.g. `nc -l 4444`, `socat TCP-LISTEN:80 ...`).
     #[serde(default)]
-    ask: Vec<String>,
+    pub listen: Decision,
+    /// Connections wired to a program (e.g. `nc -e /bin/sh`,
+    /// `socat ... EXEC:bash`), the reverse/bind shell pattern.
     #[serde(default)]
-    deny: Vec<String>,
+    pub exec: Decision,
+}
+
+/// Firewall rules for iptables, ip6tables, nft, ufw and firewall-cmd.
+/// Listing the rules is always allowed.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct FirewallConfig {
+    /// Adding, deleting or replacing rules and chains, setting a chain
+    /// policy, or loading a ruleset (`iptables -A`, `nft add rule`,
+    /// `iptables-restore`, `ufw allow`, `firewall-cmd --add-port`).
     #[serde(default)]
-    remove_allow: Vec<String>,
+    pub rule_change: Decision,
+    /// Flushing rules or turning the firewall off (`iptables -F`, `nft
+    /// flush ruleset`, `ufw disable`), which can drop every rule protecting
+    /// the machine at once.
     #[serde(default)]
-    remove_ask: Vec<String>,
+    pub flush: Decision,
+}
+
+/// User and group account rules. Queries (`passwd -S`, `chage -l`) are
+/// always allowed.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct AccountsConfig {
+    /// Creating, deleting or changing a user or group, its password, or
+    /// its group memberships (`useradd`, `usermod -aG sudo`, `passwd`).
     #[serde(default)]
-    remove_deny: Vec<String>,
+    pub change: Decision,
 }
 
-#[derive(Debug, Deserialize, Default)]
-struct GitOverlay {
+/// AWS CLI rules. `aws s3 ls`, the `s3api` `list-`, `head-` and `get-`
+/// operations, `iam get-`/`list-` and `sts get-caller-identity` are always
+/// allowed.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct AwsConfig {
+    /// Copying objects from S3 (`aws s3 cp s3://bucket/key .`). A local
+    /// destination outside the sandbox is also classified by `[paths]`.
     #[serde(default)]
-    replace: bool,
+    pub s3_download: Decision,
+    /// Writing objects or buckets (`aws s3 cp file s3://bucket/`, `aws s3
+    /// mb`, `aws s3api put-object`).
     #[serde(default)]
-    read_only: Vec<String>,
+    pub s3_upload: Decision,
+    /// Presigning a shareable object URL (`aws s3 presign`).
     #[serde(default)]
-    allowed_with_config: Vec<String>,
-    config_env: Option<HashMap<String, String>>,
+    pub s3_presign: Decision,
+    /// Deleting objects or buckets (`aws s3 rm`, `aws s3 rb --force`, `aws
+    /// s3 sync --delete`, `aws s3 mv` out of S3).
     #[serde(default)]
-    force_push_flags: Vec<String>,
+    pub s3_destructive: Decision,
+    /// IAM changes (`aws iam create-access-key`, `attach-role-policy`,
+    /// `create-user`) and STS operations that issue credentials (`aws sts
+    /// assume-role`, `get-session-token`).
     #[serde(default)]
-    remove_read_only: Vec<String>,
+    pub identity: Decision,
+}
+
+/// terraform and tofu rules. `plan`, `show`, `output` and `state list`/`show`
+/// are always allowed.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct TerraformConfig {
+    /// Changing the resources (`terraform apply`, `terraform destroy`).
     #[serde(default)]
-    remove_allowed_with_config: Vec<String>,
+    pub apply: Decision,
+    /// Changing what the state tracks without touching the resources
+    /// (`state rm`, `state mv`, `import`, `taint`).
     #[serde(default)]
-    remove_force_push_flags: Vec<String>,
+    pub state_change: Decision,
+    /// Overwriting or discarding state, or breaking its lock (`state push`,
+    /// `force-unlock`, `workspace delete`).
+    #[serde(default)]
+    pub state_destructive: Decision,
 }
 
-#[derive(Debug, Deserialize, Default)]
-struct CargoOverlay {
+/// docker rules, for podman and nerdctl too. A command given to `docker
+/// run` or `docker exec` is evaluated as well.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct DockerConfig {
+    /// Floor for starting a container (`docker run --rm image cmd`).
     #[serde(default)]
-    replace: bool,
+    pub run: Decision,
+    /// Decision for a container given access to the host: `--privileged`,
+    /// a host namespace (`--pid=host`), a capability like `SYS_ADMIN`,
+    /// `--device`, unconfined `--security-opt`, or a mount of `/`, the
+    /// runtime socket, a sensitive path, or a protected path read-write.
     #[serde(default)]
-    safe_subcommands: Vec<String>,
+    pub escalation: Decision,
+    /// Floor for running a command in an existing container
+    /// (`docker exec web ls`).
     #[serde(default)]
-    allowed_with_config: Vec<String>,
-    config_env: Option<HashMap<String, String>>,
+    pub exec: Decision,
+    /// Decision for an interactive shell in a container
+    /// (`docker exec -it web bash`), whose commands are never seen.
     #[serde(default)]
-    remove_safe_subcommands: Vec<String>,
+    pub interactive_shell: Decision,
+    /// Decision for building, pulling, tagging, pushing or removing an
+    /// image (`docker build`, `docker push`).
     #[serde(default)]
-    remove_allowed_with_config: Vec<String>,
+    pub image: Decision,
+    /// Decision for starting, stopping or removing a container
+    /// (`docker stop web`, `docker compose up`).
+    #[serde(default)]
+    pub lifecycle: Decision,
+    /// Decision for removing data or everything unused (`docker system
+    /// prune -a`, `docker volume rm`, `docker compose down -v`).
+    #[serde(default)]
+    pub destructive: Decision,
 }
 
-#[derive(Debug, Deserialize, Default)]
-struct KubectlOverlay {
+/// ansible rules. Check mode, syntax checks, listings, `ansible-inventory`
+/// and fact-gathering modules are always allowed. The command line of an
+/// ad-hoc `-m shell`/`command`/`raw` task is evaluated as well.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct AnsibleConfig {
+    /// Running a playbook or an ad-hoc task (`ansible-playbook site.yml`,
+    /// `ansible all -m copy`).
     #[serde(default)]
-    replace: bool,
+    pub run: Decision,
+    /// Running one with `--become`; the stricter of this and `run` applies.
     #[serde(default)]
-    read_only: Vec<String>,
+    pub privileged: Decision,
+}
+
+/// gpg and openssl rules. Verification and inspection (`gpg --verify`,
+/// `--list-keys`, `openssl x509 -text`, `s_client`) are always allowed.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct CryptoConfig {
+    /// Generating, importing, deleting or signing keys, and encrypting with
+    /// `openssl enc` (`gpg --gen-key`, `openssl genrsa`, `openssl req -new`).
     #[serde(default)]
-    mutating: Vec<String>,
+    pub key_material: Decision,
+    /// Printing a secret key (`gpg --export-secret-keys`, `openssl pkcs12
+    /// -nodes`).
     #[serde(default)]
-    allowed_with_config: Vec<String>,
-    config_env: Option<HashMap<String, String>>,
+    pub secret_export: Decision,
+}
+
+/// ssh-keygen, ssh-add and ssh-copy-id rules. Fingerprinting and listing
+/// (`ssh-keygen -l`, `ssh-add -l`) are always allowed.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct SshKeysConfig {
+    /// Generating or changing a key, loading keys into the agent, or
+    /// installing one on a host (`ssh-keygen -t ed25519`, `ssh-add`,
+    /// `ssh-copy-id`).
     #[serde(default)]
-    remove_read_only: Vec<String>,
+    pub change: Decision,
+    /// Generating a key with `ssh-keygen -f` over a file that exists.
     #[serde(default)]
-    remove_mutating: Vec<String>,
+    pub overwrite: Decision,
+}
+
+/// svn and hg rules. Inspection (`svn status`, `hg log`) is always
+/// allowed.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct VcsConfig {
+    /// Changing the working copy or repository (`svn commit`, `hg push`,
+    /// `hg update`).
     #[serde(default)]
-    remove_allowed_with_config: Vec<String>,
+    pub change: Decision,
+    /// Throwing away work: `hg strip`, `purge`, `rollback`, `revert`,
+    /// `update --clean`; `svn delete --force`, `revert`.
+    #[serde(default)]
+    pub destructive: Decision,
 }
 
-#[derive(Debug, Deserialize, Default)]
-struct GhOverlay {
+/// bazel and bazelisk rules. Entries may be one word (`build`) or two
+/// (`mod graph`).
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct BazelConfig {
+    /// Commands that only read or build (e.g. `query`, `info`, `build`,
+    /// `test`).
     #[serde(default)]
-    replace: bool,
+    pub read_only: Vec<String>,
+    /// Running a built binary (`bazel run //app -- args`).
     #[serde(default)]
-    read_only: Vec<String>,
+    pub run: Decision,
+}
+
+/// gradle and mvn rules. Entries are gradle task names (`build`, matching
+/// `:app:build` too) or mvn phases and goals (`verify`, `dependency:tree`).
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct JvmConfig {
+    /// Tasks or goals that only build, test or report.
     #[serde(default)]
-    mutating: Vec<String>,
+    pub read_only: Vec<String>,
+    /// Publishing artifacts (`publish`, `deploy`, `release`).
     #[serde(default)]
-    allowed_with_config: Vec<String>,
-    config_env: Option<HashMap<String, String>>,
+    pub publish: Decision,
+}
+
+/// tee evaluation rules. Targets are classified by `[paths]`.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct TeeConfig {
+    /// Decision for tee under a privilege-escalating wrapper (`sudo tee`)
+    /// writing outside the sandbox.
     #[serde(default)]
-    remove_read_only: Vec<String>,
+    pub privileged: Decision,
+}
+
+/// rm evaluation rules. Targets are also classified by `[paths]`.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct RmConfig {
+    /// Decision for a recursive delete of the root, the home directory, a
+    /// shallow directory, everything in one of those, or a path that starts
+    /// with an unset variable and would be one of those.
     #[serde(default)]
-    remove_mutating: Vec<String>,
+    pub catastrophic: Decision,
+    /// Directories fewer than this many components below `/` (`/usr` is
+    /// one) are shallow. Sandbox directories never are.
     #[serde(default)]
-    remove_allowed_with_config: Vec<String>,
+    pub min_depth: usize,
 }
 
-// ── Merge logic ──
+/// chmod / chown / chgrp evaluation rules. Files are also classified by
+/// `[paths]`.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct PermissionsConfig {
+    /// Decision for a recursive change of the root, the home directory, a
+    /// shallow directory, or everything in one of those.
+    #[serde(default)]
+    pub system_root: Decision,
+    /// Directories fewer than this many components below `/` are shallow.
+    /// Sandbox directories never are.
+    #[serde(default)]
+    pub min_depth: usize,
+}
 
-/// Merge a user list into a default list.
-/// In replace mode: user list replaces default entirely.
-/// In merge mode: remove items first, then extend with additions (deduped).
-fn merge_list(base: &mut Vec<String>, add: Vec<String>, remove: &[String], replace: bool) {
-    if replace {
-        *base = add;
-    } else {
-        base.retain(|item| !remove.contains(item));
-        for item in add {
-            if !base.contains(&item) {
-                base.push(item);
-            }
-        }
-    }
+/// Floor decisions for grouped commands. A command containing a group is
+/// decided at least this strictly, whatever its segments decide.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct GroupsConfig {
+    /// Floor for `( ... )` subshells.
+    #[serde(default)]
+    pub subshell: Decision,
+    /// Floor for `{ ...; }` brace groups. Function bodies don't count.
+    #[serde(default)]
+    pub brace_group: Decision,
 }
 
-/// Remove items in `winners` from `losers`.
+/// Decisions for the commands of a `cmd /c "..."` command line, which is
+/// split by cmd.exe's rules rather than parsed as bash.
 ///
-/// Used after merging to enforce cross-list priority: if a command
-/// appears in a higher-priority list (e.g. allow), it must be removed
-/// from lower-priority lists (e.g. ask, deny). This prevents stale
-/// entries from default config from shadowing user overrides when the
-/// registry is built with last-writer-wins insertion order.
-fn dedup_winners_over_losers(winners: &[String], losers: &mut Vec<String>) {
-    losers.retain(|item| !winners.contains(item));
+/// Names are matched case-insensitively, without `.exe`. A command in none
+/// of the lists is evaluated like any other command if it reads the same in
+/// both shells, and asks otherwise.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct CmdExeConfig {
+    /// Commands that run silently (e.g. `dir`, `type`, `where`).
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Commands that require confirmation (e.g. `del`, `rd`, `move`).
+    #[serde(default)]
+    pub ask: Vec<String>,
+    /// Commands that are blocked outright (e.g. `format`, `diskpart`).
+    #[serde(default)]
+    pub deny: Vec<String>,
 }
 
-impl Config {
-    /// Load the default embedded configuration.
-    pub fn default_config() -> Self {
-        toml::from_str(DEFAULT_CONFIG).expect("embedded default config must parse")
-    }
+/// Output redirection rules.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct RedirectionConfig {
+    /// Targets that name a standard stream (`/dev/stdout`, `/dev/fd/2`).
+    /// Redirecting to one doesn't escalate a command, like `/dev/null`.
+    /// Entries are matched as globs against the target as written.
+    #[serde(default)]
+    pub safe_sinks: Vec<String>,
+    /// Scratch locations (`/tmp/**`, `./target/**`, `*.log`). Redirecting
+    /// into one doesn't escalate a command either. Patterns with a `/`
+    /// match the resolved path, relative ones against the working
+    /// directory; others match the file name. Protected and sensitive
+    /// paths never count.
+    #[serde(default)]
+    pub safe_targets: Vec<String>,
+}
 
-    /// Load configuration with resolution order:
-    /// 1. Start with embedded defaults
-    /// 2. Merge user overlay from ~/.config/cc-toolgate/config.toml (if exists)
-    /// 3. Merge project overlay from <git-root>/.claude/cc-toolgate.toml
-    ///    (if CWD is inside a git repo and the file exists)
-    ///
-    /// Each overlay merges with what's below it: lists extend, scalars override.
-    /// Set `replace = true` in any section to replace its defaults entirely.
-    /// Use `remove_<field>` lists to subtract specific items.
-    ///
-    /// When a project overlay is loaded, `project_overlay_path` is set to the
-    /// file that was applied. Callers can inspect this to annotate decisions
-    /// with provenance information.
-    pub fn load() -> Self {
-        let mut config = Self::default_config();
-        if let Some(overlay) = Self::load_overlay() {
-            config.apply_overlay(overlay);
-        }
-        if let Some((overlay, path)) = Self::load_project_overlay() {
-            config.apply_overlay(overlay);
-            config.project_overlay_path = Some(path);
-        }
-        config
-    }
+/// Unquoted globs in destructive commands (`rm -rf */`, `chmod -R 777 *`).
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct WildcardsConfig {
+    /// Commands whose glob arguments are a risk (rm, chmod, mv, ...).
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Decision for one of those with an unquoted glob. A stricter decision
+    /// stands, with the glob's scope added to its reason.
+    #[serde(default)]
+    pub decision: Decision,
+}
 
-    /// Try to load user overlay from ~/.config/cc-toolgate/config.toml.
-    fn load_overlay() -> Option<ConfigOverlay> {
-        let home = std::env::var_os("HOME")?;
-        let path = std::path::Path::new(&home).join(".config/cc-toolgate/config.toml");
-        load_overlay_from_path(&path, "config parse error")
-    }
+/// Inline code scanning rules, keyed by language (`[inline_code.python]`, ...).
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct InlineCodeConfig {
+    /// Per-language rules.
+    #[serde(flatten)]
+    pub languages: HashMap<String, InlineLanguageConfig>,
+}
 
-    /// Try to load project overlay from <git-root>/.claude/cc-toolgate.toml.
-    /// Returns both the parsed overlay and the path it was loaded from.
-    ///
-    /// Project overlays may only ADD to allow/ask/deny lists. Any `replace` flags
-    /// or `remove_*` lists are stripped and a warning is emitted. This prevents a
-    /// malicious project config from removing safety rules set at the user level.
-    fn load_project_overlay() -> Option<(ConfigOverlay, std::path::PathBuf)> {
-        let cwd = std::env::current_dir().ok()?;
-        let git_root = find_git_root(&cwd)?;
-        let path = git_root.join(".claude/cc-toolgate.toml");
-        let mut overlay = load_overlay_from_path(&path, "project config parse error")?;
-        strip_project_overlay_dangerous_fields(&mut overlay, &path);
-        Some((overlay, path))
-    }
+/// Guidance templates keyed by command pattern (`"terraform apply" = "..."`).
+///
+/// A pattern is the command name followed by subcommand words and flags;
+/// see [`GuidancePolicy`](crate::eval::guidance::GuidancePolicy) for matching.
+/// A template may use `{reason}` and `{command}`; without `{reason}` it is
+/// appended to the built-in reason.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct GuidanceConfig {
+    /// Pattern → guidance template.
+    #[serde(flatten)]
+    pub rules: HashMap<String, String>,
+}
+
+/// Signed approval token settings. Tokens are read from the
+/// `CC_TOOLGATE_APPROVAL` env var or the file named by
+/// `CC_TOOLGATE_APPROVAL_FILE`.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct ApprovalTokensConfig {
+    /// File holding the HMAC key that tokens are verified against.
+    /// Empty disables token approvals.
+    #[serde(default)]
+    pub key_file: String,
+}
+
+/// Plugin loading (needs the `plugins` feature).
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct PluginsConfig {
+    /// Directory of plugin libraries (`*.so`, `*.dylib`) loaded at startup.
+    /// Empty disables plugins.
+    #[serde(default)]
+    pub dir: String,
+}
+
+/// An external evaluator: a script that receives a segment's
+/// [`SerializedContext`](crate::eval::context::SerializedContext) as JSON on
+/// stdin and prints a verdict (`{"decision": "allow", "reason": "..."}`).
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct HooksConfig {
+    /// Path to the script. Empty disables it.
+    #[serde(default)]
+    pub external_evaluator: String,
+    /// Commands the script decides, replacing any built-in rule for them.
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Also consult the script for commands no rule recognizes, instead of
+    /// asking.
+    #[serde(default)]
+    pub fallback: bool,
+    /// How long to wait for a verdict before asking, in milliseconds.
+    #[serde(default)]
+    pub timeout_ms: u64,
+}
+
+/// A team policy overlay fetched over HTTPS (needs the `remote` feature).
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct RemoteConfig {
+    /// HTTPS URL of a TOML overlay, with its base64 ed25519 signature at
+    /// `<config_url>.sig`. Empty disables it.
+    #[serde(default)]
+    pub config_url: String,
+    /// Base64 ed25519 public key the policy must be signed with.
+    #[serde(default)]
+    pub public_key: String,
+    /// Minutes between fetches.
+    #[serde(default)]
+    pub refresh_minutes: u64,
+    /// How long a fetch may take before the cached copy is used, in
+    /// milliseconds.
+    #[serde(default)]
+    pub timeout_ms: u64,
+}
+
+/// A `[[rules]]` entry: `decision` for `command` when `condition` holds.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RuleConfig {
+    /// Command the rule applies to. Empty applies it to every command.
+    #[serde(default)]
+    pub command: String,
+    /// A [`Condition`](crate::eval::condition::Condition) expression.
+    pub condition: String,
+    /// The decision when the condition holds.
+    pub decision: Decision,
+    /// Reason reported with the decision. Defaults to the condition.
+    #[serde(default)]
+    pub reason: String,
+    /// A `[schedules]` name: the rule only applies while it's active.
+    /// Empty applies it at all times.
+    #[serde(default)]
+    pub schedule: String,
+    /// Variables the hook process's environment must have for the rule to
+    /// apply: `NAME` (set and non-empty) or `NAME=value`.
+    #[serde(default)]
+    pub require_env: Vec<String>,
+}
+
+/// A `[schedules.<name>]` time window; see
+/// [`Schedule`](crate::eval::schedule::Schedule) for the format of each field.
+/// Empty fields don't constrain the window.
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
+pub struct ScheduleConfig {
+    /// Weekdays (`mon` … `sun`).
+    #[serde(default)]
+    pub days: Vec<String>,
+    /// Time of day, `HH:MM-HH:MM`.
+    #[serde(default)]
+    pub hours: String,
+    /// First date, `YYYY-MM-DD`.
+    #[serde(default)]
+    pub from: String,
+    /// Last date, `YYYY-MM-DD`.
+    #[serde(default)]
+    pub until: String,
+    /// Offset from UTC the window is written in, `+HH:MM` or `-HH:MM`.
+    #[serde(default)]
+    pub utc_offset: String,
+}
+
+/// Inline code scanning rules for one interpreter language.
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct InlineLanguageConfig {
+    /// Commands that run this language (e.g. `python`, `python3`).
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Flags whose value is inline code (e.g. `-c`, `-e`, `--eval`).
+    #[serde(default)]
+    pub code_flags: Vec<String>,
+    /// Other flags that consume a value, so it is not mistaken for code or
+    /// a script path (e.g. awk `-F`, `-v`).
+    #[serde(default)]
+    pub value_flags: Vec<String>,
+    /// Whether the first operand is the program text (awk) rather than a
+    /// script path.
+    #[serde(default)]
+    pub program_positional: bool,
+    /// Substrings that deny the command when found in inline code.
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Substrings that require confirmation when found in inline code,
+    /// even if the command is otherwise allowed.
+    #[serde(default)]
+    pub ask: Vec<String>,
+}
+
+// ── Overlay types (user config that merges with defaults) ──
+//
+// These mirror the public config types but use `Option` for scalars and
+// include `replace` flags and `remove_*` lists for the merge system.
+
+/// User-provided configuration overlay, deserialized from `~/.config/cc-toolgate/config.toml`.
+#[derive(Debug, Deserialize, Default)]
+struct ConfigOverlay {
+    #[serde(default)]
+    settings: SettingsOverlay,
+    #[serde(default)]
+    commands: CommandsOverlay,
+    #[serde(default)]
+    wrappers: WrappersOverlay,
+    #[serde(default)]
+    git: GitOverlay,
+    #[serde(default)]
+    cargo: CargoOverlay,
+    #[serde(default)]
+    kubectl: KubectlOverlay,
+    #[serde(default)]
+    gh: GhOverlay,
+    #[serde(default)]
+    hub: GhOverlay,
+    #[serde(default)]
+    tea: GhOverlay,
+    #[serde(default)]
+    fj: GhOverlay,
+    #[serde(default)]
+    apt: AptOverlay,
+    #[serde(default)]
+    dnf: DnfOverlay,
+    #[serde(default)]
+    paths: PathsOverlay,
+    #[serde(default)]
+    scp: ScpOverlay,
+    #[serde(default)]
+    rsync: RsyncOverlay,
+    #[serde(default)]
+    network: NetworkOverlay,
+    #[serde(default)]
+    raw_network: RawNetworkOverlay,
+    #[serde(default)]
+    firewall: FirewallOverlay,
+    #[serde(default)]
+    tee: TeeOverlay,
+    #[serde(default)]
+    rm: RmOverlay,
+    #[serde(default)]
+    permissions: PermissionsOverlay,
+    #[serde(default)]
+    accounts: AccountsOverlay,
+    #[serde(default)]
+    aws: AwsOverlay,
+    #[serde(default)]
+    terraform: TerraformOverlay,
+    #[serde(default)]
+    docker: DockerOverlay,
+    #[serde(default)]
+    ansible: AnsibleOverlay,
+    #[serde(default)]
+    crypto: CryptoOverlay,
+    #[serde(default)]
+    ssh_keys: SshKeysOverlay,
+    #[serde(default)]
+    vcs: VcsOverlay,
+    #[serde(default)]
+    bazel: BazelOverlay,
+    #[serde(default)]
+    gradle: JvmOverlay,
+    #[serde(default)]
+    maven: JvmOverlay,
+    #[serde(default)]
+    groups: GroupsOverlay,
+    #[serde(default)]
+    cmd_exe: CmdExeOverlay,
+    #[serde(default)]
+    redirection: RedirectionOverlay,
+    #[serde(default)]
+    wildcards: WildcardsOverlay,
+    #[serde(default)]
+    inline_code: InlineCodeOverlay,
+    #[serde(default)]
+    guidance: GuidanceOverlay,
+    #[serde(default)]
+    approval_tokens: ApprovalTokensOverlay,
+    #[serde(default)]
+    plugins: PluginsOverlay,
+    #[serde(default)]
+    hooks: HooksOverlay,
+    #[serde(default)]
+    remote: RemoteOverlay,
+    #[serde(default)]
+    trust: HashMap<String, String>,
+    #[serde(default)]
+    schedules: HashMap<String, ScheduleConfig>,
+    #[serde(default)]
+    rules: Vec<RuleConfig>,
+    /// `[profiles.<name>]`: overlays applied on top of this one when
+    /// selected. Only read from the user overlay, and not nested.
+    #[serde(default)]
+    #[cfg_attr(not(feature = "native"), allow(dead_code))] // selected in `load`
+    profiles: HashMap<String, ConfigOverlay>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct SettingsOverlay {
+    mode: Option<Mode>,
+    escalate_deny: Option<bool>,
+    session_grants: Option<bool>,
+    import_claude_permissions: Option<bool>,
+    slow_warning_ms: Option<u64>,
+    shell: Option<Shell>,
+    platform: Option<Platform>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct WrappersOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    allow_floor: Vec<String>,
+    #[serde(default)]
+    ask_floor: Vec<String>,
+    #[serde(default)]
+    remove_allow_floor: Vec<String>,
+    #[serde(default)]
+    remove_ask_floor: Vec<String>,
+    interactive_shell: Option<Decision>,
+    app_launch: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct CommandsOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    ask: Vec<String>,
+    #[serde(default)]
+    deny: Vec<String>,
+    #[serde(default)]
+    remove_allow: Vec<String>,
+    #[serde(default)]
+    remove_ask: Vec<String>,
+    #[serde(default)]
+    remove_deny: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct GitOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    read_only: Vec<String>,
+    #[serde(default)]
+    mutating: Vec<String>,
+    #[serde(default)]
+    allowed_with_config: Vec<String>,
+    config_env: Option<HashMap<String, String>>,
+    #[serde(default)]
+    force_push_flags: Vec<String>,
+    #[serde(default)]
+    remove_read_only: Vec<String>,
+    #[serde(default)]
+    remove_mutating: Vec<String>,
+    #[serde(default)]
+    remove_allowed_with_config: Vec<String>,
+    #[serde(default)]
+    remove_force_push_flags: Vec<String>,
+    #[serde(default)]
+    sensitive_config_keys: Vec<String>,
+    #[serde(default)]
+    remove_sensitive_config_keys: Vec<String>,
+    sensitive_config: Option<Decision>,
+    global_config: Option<Decision>,
+    local_config: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct CargoOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    safe_subcommands: Vec<String>,
+    #[serde(default)]
+    allowed_with_config: Vec<String>,
+    config_env: Option<HashMap<String, String>>,
+    #[serde(default)]
+    remove_safe_subcommands: Vec<String>,
+    #[serde(default)]
+    remove_allowed_with_config: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct KubectlOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    read_only: Vec<String>,
+    #[serde(default)]
+    mutating: Vec<String>,
+    #[serde(default)]
+    allowed_with_config: Vec<String>,
+    config_env: Option<HashMap<String, String>>,
+    #[serde(default)]
+    sensitive_resources: Vec<String>,
+    #[serde(default)]
+    remove_read_only: Vec<String>,
+    #[serde(default)]
+    remove_mutating: Vec<String>,
+    #[serde(default)]
+    remove_allowed_with_config: Vec<String>,
+    #[serde(default)]
+    remove_sensitive_resources: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct AptOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    read_only: Vec<String>,
+    #[serde(default)]
+    mutating: Vec<String>,
+    #[serde(default)]
+    remove_read_only: Vec<String>,
+    #[serde(default)]
+    remove_mutating: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct DnfOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    read_only: Vec<String>,
+    #[serde(default)]
+    mutating: Vec<String>,
+    #[serde(default)]
+    remove_read_only: Vec<String>,
+    #[serde(default)]
+    remove_mutating: Vec<String>,
+    history_undo: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct GhOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    read_only: Vec<String>,
+    #[serde(default)]
+    mutating: Vec<String>,
+    #[serde(default)]
+    allowed_with_config: Vec<String>,
+    config_env: Option<HashMap<String, String>>,
+    #[serde(default)]
+    remove_read_only: Vec<String>,
+    #[serde(default)]
+    remove_mutating: Vec<String>,
+    #[serde(default)]
+    remove_allowed_with_config: Vec<String>,
+    #[serde(default)]
+    extensions: Vec<String>,
+    #[serde(default)]
+    remove_extensions: Vec<String>,
+    extension: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct PathsOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    sensitive: Vec<String>,
+    #[serde(default)]
+    protected: Vec<String>,
+    #[serde(default)]
+    sandbox: Vec<String>,
+    #[serde(default)]
+    remove_sensitive: Vec<String>,
+    #[serde(default)]
+    remove_protected: Vec<String>,
+    #[serde(default)]
+    remove_sandbox: Vec<String>,
+    project_sandbox: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ScpOverlay {
+    upload: Option<Decision>,
+    download: Option<Decision>,
+    sensitive: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct RsyncOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    destructive_flags: Vec<String>,
+    #[serde(default)]
+    remove_destructive_flags: Vec<String>,
+    destructive: Option<Decision>,
+    remote_destination: Option<Decision>,
+    allow_local_dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct NetworkOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    allowed_domains: Vec<String>,
+    #[serde(default)]
+    denied_domains: Vec<String>,
+    #[serde(default)]
+    remove_allowed_domains: Vec<String>,
+    #[serde(default)]
+    remove_denied_domains: Vec<String>,
+    file_upload: Option<Decision>,
+    #[serde(default)]
+    internal_hosts: Vec<String>,
+    #[serde(default)]
+    remove_internal_hosts: Vec<String>,
+    internal_network: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct RawNetworkOverlay {
+    client: Option<Decision>,
+    listen: Option<Decision>,
+    exec: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct FirewallOverlay {
+    rule_change: Option<Decision>,
+    flush: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TeeOverlay {
+    privileged: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct RmOverlay {
+    catastrophic: Option<Decision>,
+    min_depth: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct PermissionsOverlay {
+    system_root: Option<Decision>,
+    min_depth: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct AccountsOverlay {
+    change: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct AwsOverlay {
+    s3_download: Option<Decision>,
+    s3_upload: Option<Decision>,
+    s3_presign: Option<Decision>,
+    s3_destructive: Option<Decision>,
+    identity: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TerraformOverlay {
+    apply: Option<Decision>,
+    state_change: Option<Decision>,
+    state_destructive: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct DockerOverlay {
+    run: Option<Decision>,
+    escalation: Option<Decision>,
+    exec: Option<Decision>,
+    interactive_shell: Option<Decision>,
+    image: Option<Decision>,
+    lifecycle: Option<Decision>,
+    destructive: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct AnsibleOverlay {
+    run: Option<Decision>,
+    privileged: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct CryptoOverlay {
+    key_material: Option<Decision>,
+    secret_export: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct SshKeysOverlay {
+    change: Option<Decision>,
+    overwrite: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct VcsOverlay {
+    change: Option<Decision>,
+    destructive: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct BazelOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    read_only: Vec<String>,
+    #[serde(default)]
+    remove_read_only: Vec<String>,
+    run: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct JvmOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    read_only: Vec<String>,
+    #[serde(default)]
+    remove_read_only: Vec<String>,
+    publish: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct GroupsOverlay {
+    subshell: Option<Decision>,
+    brace_group: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct CmdExeOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    ask: Vec<String>,
+    #[serde(default)]
+    deny: Vec<String>,
+    #[serde(default)]
+    remove_allow: Vec<String>,
+    #[serde(default)]
+    remove_ask: Vec<String>,
+    #[serde(default)]
+    remove_deny: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct RedirectionOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    safe_sinks: Vec<String>,
+    #[serde(default)]
+    remove_safe_sinks: Vec<String>,
+    #[serde(default)]
+    safe_targets: Vec<String>,
+    #[serde(default)]
+    remove_safe_targets: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct WildcardsOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    commands: Vec<String>,
+    #[serde(default)]
+    remove_commands: Vec<String>,
+    decision: Option<Decision>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ApprovalTokensOverlay {
+    key_file: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct PluginsOverlay {
+    dir: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct HooksOverlay {
+    external_evaluator: Option<String>,
+    #[serde(default)]
+    commands: Vec<String>,
+    #[serde(default)]
+    remove_commands: Vec<String>,
+    fallback: Option<bool>,
+    timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct RemoteOverlay {
+    config_url: Option<String>,
+    public_key: Option<String>,
+    refresh_minutes: Option<u64>,
+    timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct GuidanceOverlay {
+    #[serde(flatten)]
+    rules: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct InlineCodeOverlay {
+    #[serde(flatten)]
+    languages: HashMap<String, InlineLanguageOverlay>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct InlineLanguageOverlay {
+    #[serde(default)]
+    replace: bool,
+    #[serde(default)]
+    commands: Vec<String>,
+    #[serde(default)]
+    code_flags: Vec<String>,
+    #[serde(default)]
+    value_flags: Vec<String>,
+    program_positional: Option<bool>,
+    #[serde(default)]
+    deny: Vec<String>,
+    #[serde(default)]
+    ask: Vec<String>,
+    #[serde(default)]
+    remove_commands: Vec<String>,
+    #[serde(default)]
+    remove_code_flags: Vec<String>,
+    #[serde(default)]
+    remove_value_flags: Vec<String>,
+    #[serde(default)]
+    remove_deny: Vec<String>,
+    #[serde(default)]
+    remove_ask: Vec<String>,
+}
+
+// ── Merge logic ──
+
+/// Merge a user list into a default list.
+/// In replace mode: user list replaces default entirely.
+/// In merge mode: remove items first, then extend with additions (deduped).
+fn merge_list(base: &mut Vec<String>, add: Vec<String>, remove: &[String], replace: bool) {
+    if replace {
+        *base = add;
+    } else {
+        base.retain(|item| !remove.contains(item));
+        for item in add {
+            if !base.contains(&item) {
+                base.push(item);
+            }
+        }
+    }
+}
+
+/// Merge a `[gh]`-shaped overlay section (gh, hub, tea, fj) into `config`.
+fn merge_gh(config: &mut GhConfig, gh: GhOverlay) {
+    merge_list(
+        &mut config.read_only,
+        gh.read_only,
+        &gh.remove_read_only,
+        gh.replace,
+    );
+    merge_list(
+        &mut config.mutating,
+        gh.mutating,
+        &gh.remove_mutating,
+        gh.replace,
+    );
+    merge_list(
+        &mut config.allowed_with_config,
+        gh.allowed_with_config,
+        &gh.remove_allowed_with_config,
+        gh.replace,
+    );
+    if let Some(v) = gh.config_env {
+        config.config_env = v;
+    }
+    merge_list(
+        &mut config.extensions,
+        gh.extensions,
+        &gh.remove_extensions,
+        gh.replace,
+    );
+    if let Some(v) = gh.extension {
+        config.extension = v;
+    }
+}
+
+/// Remove items in `winners` from `losers`.
+///
+/// Used after merging to enforce cross-list priority: if a command
+/// appears in a higher-priority list (e.g. allow), it must be removed
+/// from lower-priority lists (e.g. ask, deny). This prevents stale
+/// entries from default config from shadowing user overrides when the
+/// registry is built with last-writer-wins insertion order.
+fn dedup_winners_over_losers(winners: &[String], losers: &mut Vec<String>) {
+    losers.retain(|item| !winners.contains(item));
+}
+
+impl Config {
+    /// Load the default embedded configuration.
+    pub fn default_config() -> Self {
+        Self::default_for(Platform::Auto)
+    }
+
+    /// The embedded defaults with those of `platform` on top.
+    pub fn default_for(platform: Platform) -> Self {
+        let mut config: Self =
+            toml::from_str(DEFAULT_CONFIG).expect("embedded default config must parse");
+        if let Some(text) = platform.defaults() {
+            let overlay = toml::from_str(text).expect("embedded platform defaults must parse");
+            config.apply_overlay(overlay);
+        }
+        config.settings.platform = platform;
+        config
+    }
+
+    /// Load configuration with resolution order:
+    /// 1. Start with embedded defaults
+    /// 2. Merge user overlay from ~/.config/cc-toolgate/config.toml (if exists),
+    ///    then each `~/.config/cc-toolgate/config.d/*.toml` in lexical order,
+    ///    or just the file named by `CC_TOOLGATE_CONFIG`, and then their
+    ///    `[profiles.<name>]` section, if `CC_TOOLGATE_PROFILE` names one
+    /// 3. Merge the team policy from `[remote] config_url`, if its signature
+    ///    verifies (`remote` feature; see `src/remote.rs`)
+    /// 4. Merge project overlay from `<git-root>/.claude/cc-toolgate.toml`
+    ///    (if CWD is inside a git repo and the file exists)
+    ///
+    /// Each overlay merges with what's below it: lists extend, scalars override.
+    /// Set `replace = true` in any section to replace its defaults entirely.
+    /// Use `remove_<field>` lists to subtract specific items.
+    ///
+    /// When a project overlay is loaded, `project_overlay_path` is set to the
+    /// file that was applied. Callers can inspect this to annotate decisions
+    /// with provenance information.
+    ///
+    /// The merged result is cached in `~/.cache/cc-toolgate/`, keyed by a
+    /// hash of the binary version, the embedded defaults, the profile, and
+    /// both overlay files, so later runs skip TOML parsing until one of them changes.
+    /// Configs whose overlays failed to parse or had fields stripped are not
+    /// cached, so their warnings are repeated on every run. Set
+    /// `CC_TOOLGATE_NO_CACHE` to bypass the cache.
+    ///
+    /// `~/.config` stands for `$XDG_CONFIG_HOME` when that is set.
+    #[cfg(feature = "native")]
+    pub fn load() -> Self {
+        let explicit = std::env::var_os(CONFIG_ENV).filter(|p| !p.is_empty());
+        Self::load_from(explicit.as_deref().map(std::path::Path::new))
+    }
+
+    /// [`load`](Self::load), with `file` (if given) as the only user
+    /// overlay, in place of `config.toml` and `config.d`. A `file` that
+    /// can't be read is reported, and the defaults and project overlay
+    /// still apply.
+    #[cfg(feature = "native")]
+    pub fn load_from(file: Option<&std::path::Path>) -> Self {
+        let sources = ConfigSources::read(file);
+        let cache = std::env::var_os(NO_CACHE_ENV)
+            .is_none()
+            .then(|| sources.cache_path())
+            .flatten();
+        let config = match cache.as_deref().and_then(read_cached) {
+            Some(config) => config,
+            None => {
+                let (config, clean) = Self::from_sources(&sources);
+                if clean && let Some(path) = cache {
+                    write_cached(&path, &config);
+                }
+                config
+            }
+        };
+        // A newly fetched team policy is a new source: load again to apply it.
+        #[cfg(feature = "remote")]
+        if crate::remote::refresh(&config.remote) {
+            return Self::load_from(file);
+        }
+        config
+    }
+
+    /// The embedded defaults merged with one overlay given as TOML text, by
+    /// the same rules as `~/.config/cc-toolgate/config.toml`. Touches no
+    /// files or environment, so it is available without the `native`
+    /// feature (e.g. for a browser playground).
+    pub fn with_overlay(text: &str) -> Result<Self, String> {
+        let overlay: ConfigOverlay = toml::from_str(text).map_err(|e| e.to_string())?;
+        let mut config = Self::default_for(overlay.settings.platform.unwrap_or_default());
+        config.apply_overlay(overlay);
+        Ok(config)
+    }
+
+    /// A hash of everything [`load`](Self::load) reads. It changes when any
+    /// config file, the profile, or the binary does, so long-running callers
+    /// can poll it to know when to load again.
+    #[cfg(feature = "native")]
+    pub fn fingerprint() -> String {
+        let explicit = std::env::var_os(CONFIG_ENV).filter(|p| !p.is_empty());
+        ConfigSources::read(explicit.as_deref().map(std::path::Path::new)).key()
+    }
+
+    /// [`load`](Self::load), uncached, also recording which source each
+    /// value came from.
+    #[cfg(feature = "native")]
+    pub fn load_traced() -> (Self, Provenance) {
+        let explicit = std::env::var_os(CONFIG_ENV).filter(|p| !p.is_empty());
+        let sources = ConfigSources::read(explicit.as_deref().map(std::path::Path::new));
+        let mut provenance = Provenance::default();
+        let (config, _) = Self::from_sources_traced(&sources, &mut |source, config| {
+            provenance.record(source, config)
+        });
+        (config, provenance)
+    }
+
+    /// Merge the overlays in `sources` onto the defaults. The flag is false
+    /// if an overlay failed to parse or had fields stripped.
+    #[cfg(feature = "native")]
+    fn from_sources(sources: &ConfigSources) -> (Self, bool) {
+        Self::from_sources_traced(sources, &mut |_, _| {})
+    }
+
+    /// [`from_sources`](Self::from_sources), calling `trace` with a label
+    /// and the config so far after each source is applied.
+    #[cfg(feature = "native")]
+    fn from_sources_traced(
+        sources: &ConfigSources,
+        trace: &mut dyn FnMut(&str, &Config),
+    ) -> (Self, bool) {
+        let mut clean = true;
+        for error in &sources.unreadable {
+            eprintln!("cc-toolgate: {error}");
+            clean = false;
+        }
+        // The main user overlay, then each drop-in. A profile may be spread
+        // over several of them; its parts apply in the same order.
+        let mut overlays = Vec::new();
+        let mut profiles: HashMap<String, Vec<ConfigOverlay>> = HashMap::new();
+        let main = sources
+            .user
+            .as_ref()
+            .map(|text| (text, "user config".to_string()));
+        let dropins = sources
+            .user_dropins
+            .iter()
+            .map(|(path, text)| (text, path.display().to_string()));
+        for (text, source) in main.into_iter().chain(dropins) {
+            match parse_overlay(text, &format!("config parse error in {source}")) {
+                Some(mut overlay) => {
+                    for (name, profile) in std::mem::take(&mut overlay.profiles) {
+                        profiles.entry(name).or_default().push(profile);
+                    }
+                    overlays.push((source, overlay));
+                }
+                None => clean = false,
+            }
+        }
+        let profile = match &sources.profile {
+            Some(name) => match profiles.remove(name) {
+                Some(parts) => Some((name, parts)),
+                None => {
+                    eprintln!("cc-toolgate: no [profiles.{name}] in the user config");
+                    clean = false;
+                    None
+                }
+            },
+            None => None,
+        };
+        // The platform picks the defaults everything else applies to.
+        let platform = overlays
+            .iter()
+            .map(|(_, overlay)| overlay)
+            .chain(profile.iter().flat_map(|(_, parts)| parts))
+            .filter_map(|overlay| overlay.settings.platform)
+            .next_back()
+            .unwrap_or_default();
+        let mut config = Self::default_for(platform);
+        trace("default", &config);
+        for (source, overlay) in overlays {
+            config.apply_overlay(overlay);
+            trace(&source, &config);
+        }
+        if let Some((name, parts)) = profile {
+            for part in parts {
+                config.apply_overlay(part);
+            }
+            trace(&format!("profile {name}"), &config);
+        }
+        #[cfg(feature = "remote")]
+        if let Some(remote) = &sources.remote {
+            match remote.policy_for(&config.remote) {
+                Ok(Some(text)) => match parse_overlay(text, "remote policy parse error") {
+                    Some(mut overlay) => {
+                        // The policy can't redirect where it comes from.
+                        overlay.remote = RemoteOverlay::default();
+                        config.apply_overlay(overlay);
+                        trace(&format!("remote policy {}", remote.url), &config);
+                    }
+                    None => clean = false,
+                },
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("cc-toolgate: cached remote policy rejected: {e}");
+                    clean = false;
+                }
+            }
+        }
+        // Project overlays may only ADD to allow/ask/deny lists. Any `replace`
+        // flags or `remove_*` lists are stripped and a warning is emitted. This
+        // prevents a malicious project config from removing safety rules set
+        // at the user level.
+        if let Some((path, text)) = &sources.project {
+            match parse_overlay(text, "project config parse error") {
+                Some(mut overlay) => {
+                    clean &= !strip_project_overlay_dangerous_fields(&mut overlay, path);
+                    config.apply_overlay(overlay);
+                    config.project_overlay_path = Some(path.clone());
+                    trace(&path.display().to_string(), &config);
+                }
+                None => clean = false,
+            }
+        }
+        if config.settings.import_claude_permissions {
+            let user = sources.claude_user.iter().map(|s| (s, false));
+            let project = sources.claude_project.iter().map(|s| (s, true));
+            for ((path, text), deny_only) in user.chain(project) {
+                match crate::claude_settings::import(text, deny_only) {
+                    Ok(import) => {
+                        for skipped in &import.skipped {
+                            log::warn!("{}: not imported: {skipped}", path.display());
+                        }
+                        config.rules.extend(import.rules);
+                        trace(&path.display().to_string(), &config);
+                    }
+                    Err(e) => {
+                        log::warn!("{}: {e}", path.display());
+                        clean = false;
+                    }
+                }
+            }
+        }
+        (config, clean)
+    }
 
     /// Apply an overlay on top of this config (merge semantics).
     fn apply_overlay(&mut self, overlay: ConfigOverlay) {
         // Settings: scalar overrides
+        if let Some(v) = overlay.settings.mode {
+            self.settings.mode = v;
+        }
         if let Some(v) = overlay.settings.escalate_deny {
             self.settings.escalate_deny = v;
         }
+        if let Some(v) = overlay.settings.session_grants {
+            self.settings.session_grants = v;
+        }
+        if let Some(v) = overlay.settings.import_claude_permissions {
+            self.settings.import_claude_permissions = v;
+        }
+        if let Some(v) = overlay.settings.slow_warning_ms {
+            self.settings.slow_warning_ms = v;
+        }
+        if let Some(v) = overlay.settings.shell {
+            self.settings.shell = v;
+        }
+        if let Some(v) = overlay.settings.platform {
+            self.settings.platform = v;
+        }
 
         // Commands
         let c = overlay.commands;
@@ -428,6 +1907,12 @@ impl Config {
 
         // Cross-list dedup for wrappers: allow_floor > ask_floor.
         dedup_winners_over_losers(&self.wrappers.allow_floor, &mut self.wrappers.ask_floor);
+        if let Some(v) = w.interactive_shell {
+            self.wrappers.interactive_shell = v;
+        }
+        if let Some(v) = w.app_launch {
+            self.wrappers.app_launch = v;
+        }
 
         // Git
         let g = overlay.git;
@@ -437,6 +1922,12 @@ impl Config {
             &g.remove_read_only,
             g.replace,
         );
+        merge_list(
+            &mut self.git.mutating,
+            g.mutating,
+            &g.remove_mutating,
+            g.replace,
+        );
         merge_list(
             &mut self.git.allowed_with_config,
             g.allowed_with_config,
@@ -449,9 +1940,24 @@ impl Config {
             &g.remove_force_push_flags,
             g.replace,
         );
+        merge_list(
+            &mut self.git.sensitive_config_keys,
+            g.sensitive_config_keys,
+            &g.remove_sensitive_config_keys,
+            g.replace,
+        );
         if let Some(v) = g.config_env {
             self.git.config_env = v;
         }
+        if let Some(v) = g.sensitive_config {
+            self.git.sensitive_config = v;
+        }
+        if let Some(v) = g.global_config {
+            self.git.global_config = v;
+        }
+        if let Some(v) = g.local_config {
+            self.git.local_config = v;
+        }
 
         // Cargo
         let ca = overlay.cargo;
@@ -491,32 +1997,428 @@ impl Config {
             &k.remove_allowed_with_config,
             k.replace,
         );
+        merge_list(
+            &mut self.kubectl.sensitive_resources,
+            k.sensitive_resources,
+            &k.remove_sensitive_resources,
+            k.replace,
+        );
         if let Some(v) = k.config_env {
             self.kubectl.config_env = v;
         }
 
-        // Gh
-        let gh = overlay.gh;
+        // Gh, and the gh-style hub, tea and fj
+        merge_gh(&mut self.gh, overlay.gh);
+        merge_gh(&mut self.hub, overlay.hub);
+        merge_gh(&mut self.tea, overlay.tea);
+        merge_gh(&mut self.fj, overlay.fj);
+
+        // Apt
+        let apt = overlay.apt;
+        merge_list(
+            &mut self.apt.read_only,
+            apt.read_only,
+            &apt.remove_read_only,
+            apt.replace,
+        );
+        merge_list(
+            &mut self.apt.mutating,
+            apt.mutating,
+            &apt.remove_mutating,
+            apt.replace,
+        );
+
+        // Dnf
+        let dnf = overlay.dnf;
+        merge_list(
+            &mut self.dnf.read_only,
+            dnf.read_only,
+            &dnf.remove_read_only,
+            dnf.replace,
+        );
+        merge_list(
+            &mut self.dnf.mutating,
+            dnf.mutating,
+            &dnf.remove_mutating,
+            dnf.replace,
+        );
+        if let Some(v) = dnf.history_undo {
+            self.dnf.history_undo = v;
+        }
+
+        // Paths
+        let p = overlay.paths;
+        merge_list(
+            &mut self.paths.sensitive,
+            p.sensitive,
+            &p.remove_sensitive,
+            p.replace,
+        );
+        merge_list(
+            &mut self.paths.protected,
+            p.protected,
+            &p.remove_protected,
+            p.replace,
+        );
+        merge_list(
+            &mut self.paths.sandbox,
+            p.sandbox,
+            &p.remove_sandbox,
+            p.replace,
+        );
+        if let Some(v) = p.project_sandbox {
+            self.paths.project_sandbox = v;
+        }
+
+        // Scp
+        let scp = overlay.scp;
+        if let Some(v) = scp.upload {
+            self.scp.upload = v;
+        }
+        if let Some(v) = scp.download {
+            self.scp.download = v;
+        }
+        if let Some(v) = scp.sensitive {
+            self.scp.sensitive = v;
+        }
+
+        // Rsync
+        let r = overlay.rsync;
+        merge_list(
+            &mut self.rsync.destructive_flags,
+            r.destructive_flags,
+            &r.remove_destructive_flags,
+            r.replace,
+        );
+        if let Some(v) = r.destructive {
+            self.rsync.destructive = v;
+        }
+        if let Some(v) = r.remote_destination {
+            self.rsync.remote_destination = v;
+        }
+        if let Some(v) = r.allow_local_dry_run {
+            self.rsync.allow_local_dry_run = v;
+        }
+
+        // Network
+        let n = overlay.network;
+        merge_list(
+            &mut self.network.allowed_domains,
+            n.allowed_domains,
+            &n.remove_allowed_domains,
+            n.replace,
+        );
+        merge_list(
+            &mut self.network.denied_domains,
+            n.denied_domains,
+            &n.remove_denied_domains,
+            n.replace,
+        );
+        if let Some(v) = n.file_upload {
+            self.network.file_upload = v;
+        }
+        merge_list(
+            &mut self.network.internal_hosts,
+            n.internal_hosts,
+            &n.remove_internal_hosts,
+            n.replace,
+        );
+        if let Some(v) = n.internal_network {
+            self.network.internal_network = v;
+        }
+
+        // Raw network tools
+        let raw = overlay.raw_network;
+        if let Some(v) = raw.client {
+            self.raw_network.client = v;
+        }
+        if let Some(v) = raw.listen {
+            self.raw_network.listen = v;
+        }
+        if let Some(v) = raw.exec {
+            self.raw_network.exec = v;
+        }
+
+        // Firewall
+        if let Some(v) = overlay.firewall.rule_change {
+            self.firewall.rule_change = v;
+        }
+        if let Some(v) = overlay.firewall.flush {
+            self.firewall.flush = v;
+        }
+
+        // Tee
+        if let Some(v) = overlay.tee.privileged {
+            self.tee.privileged = v;
+        }
+
+        // rm
+        if let Some(v) = overlay.rm.catastrophic {
+            self.rm.catastrophic = v;
+        }
+        if let Some(v) = overlay.rm.min_depth {
+            self.rm.min_depth = v;
+        }
+
+        // chmod / chown / chgrp
+        if let Some(v) = overlay.permissions.system_root {
+            self.permissions.system_root = v;
+        }
+        if let Some(v) = overlay.permissions.min_depth {
+            self.permissions.min_depth = v;
+        }
+
+        // Accounts
+        if let Some(v) = overlay.accounts.change {
+            self.accounts.change = v;
+        }
+
+        // Aws
+        if let Some(v) = overlay.aws.s3_download {
+            self.aws.s3_download = v;
+        }
+        if let Some(v) = overlay.aws.s3_upload {
+            self.aws.s3_upload = v;
+        }
+        if let Some(v) = overlay.aws.s3_presign {
+            self.aws.s3_presign = v;
+        }
+        if let Some(v) = overlay.aws.s3_destructive {
+            self.aws.s3_destructive = v;
+        }
+        if let Some(v) = overlay.aws.identity {
+            self.aws.identity = v;
+        }
+
+        // Terraform
+        if let Some(v) = overlay.terraform.apply {
+            self.terraform.apply = v;
+        }
+        if let Some(v) = overlay.terraform.state_change {
+            self.terraform.state_change = v;
+        }
+        if let Some(v) = overlay.terraform.state_destructive {
+            self.terraform.state_destructive = v;
+        }
+
+        // Docker
+        if let Some(v) = overlay.docker.run {
+            self.docker.run = v;
+        }
+        if let Some(v) = overlay.docker.escalation {
+            self.docker.escalation = v;
+        }
+        if let Some(v) = overlay.docker.exec {
+            self.docker.exec = v;
+        }
+        if let Some(v) = overlay.docker.interactive_shell {
+            self.docker.interactive_shell = v;
+        }
+        if let Some(v) = overlay.docker.image {
+            self.docker.image = v;
+        }
+        if let Some(v) = overlay.docker.lifecycle {
+            self.docker.lifecycle = v;
+        }
+        if let Some(v) = overlay.docker.destructive {
+            self.docker.destructive = v;
+        }
+
+        // Ansible
+        if let Some(v) = overlay.ansible.run {
+            self.ansible.run = v;
+        }
+        if let Some(v) = overlay.ansible.privileged {
+            self.ansible.privileged = v;
+        }
+
+        // Crypto
+        if let Some(v) = overlay.crypto.key_material {
+            self.crypto.key_material = v;
+        }
+        if let Some(v) = overlay.crypto.secret_export {
+            self.crypto.secret_export = v;
+        }
+
+        // SSH keys
+        if let Some(v) = overlay.ssh_keys.change {
+            self.ssh_keys.change = v;
+        }
+        if let Some(v) = overlay.ssh_keys.overwrite {
+            self.ssh_keys.overwrite = v;
+        }
+
+        // svn and hg
+        if let Some(v) = overlay.vcs.change {
+            self.vcs.change = v;
+        }
+        if let Some(v) = overlay.vcs.destructive {
+            self.vcs.destructive = v;
+        }
+
+        // Bazel
+        let bazel = overlay.bazel;
+        merge_list(
+            &mut self.bazel.read_only,
+            bazel.read_only,
+            &bazel.remove_read_only,
+            bazel.replace,
+        );
+        if let Some(v) = bazel.run {
+            self.bazel.run = v;
+        }
+
+        // Gradle, Maven
+        for (config, jvm) in [
+            (&mut self.gradle, overlay.gradle),
+            (&mut self.maven, overlay.maven),
+        ] {
+            merge_list(
+                &mut config.read_only,
+                jvm.read_only,
+                &jvm.remove_read_only,
+                jvm.replace,
+            );
+            if let Some(v) = jvm.publish {
+                config.publish = v;
+            }
+        }
+
+        // Groups
+        if let Some(v) = overlay.groups.subshell {
+            self.groups.subshell = v;
+        }
+        if let Some(v) = overlay.groups.brace_group {
+            self.groups.brace_group = v;
+        }
+
+        // cmd.exe
+        let c = overlay.cmd_exe;
+        merge_list(&mut self.cmd_exe.allow, c.allow, &c.remove_allow, c.replace);
+        merge_list(&mut self.cmd_exe.ask, c.ask, &c.remove_ask, c.replace);
+        merge_list(&mut self.cmd_exe.deny, c.deny, &c.remove_deny, c.replace);
+        dedup_winners_over_losers(&self.cmd_exe.allow, &mut self.cmd_exe.ask);
+        dedup_winners_over_losers(&self.cmd_exe.allow, &mut self.cmd_exe.deny);
+        dedup_winners_over_losers(&self.cmd_exe.ask, &mut self.cmd_exe.deny);
+
+        // Redirection
+        let rd = overlay.redirection;
+        merge_list(
+            &mut self.redirection.safe_sinks,
+            rd.safe_sinks,
+            &rd.remove_safe_sinks,
+            rd.replace,
+        );
         merge_list(
-            &mut self.gh.read_only,
-            gh.read_only,
-            &gh.remove_read_only,
-            gh.replace,
+            &mut self.redirection.safe_targets,
+            rd.safe_targets,
+            &rd.remove_safe_targets,
+            rd.replace,
         );
+
+        // Wildcards
+        let w = overlay.wildcards;
         merge_list(
-            &mut self.gh.mutating,
-            gh.mutating,
-            &gh.remove_mutating,
-            gh.replace,
+            &mut self.wildcards.commands,
+            w.commands,
+            &w.remove_commands,
+            w.replace,
         );
+        if let Some(v) = w.decision {
+            self.wildcards.decision = v;
+        }
+
+        // Inline code
+        for (name, lang) in overlay.inline_code.languages {
+            let base = self.inline_code.languages.entry(name).or_default();
+            merge_list(
+                &mut base.commands,
+                lang.commands,
+                &lang.remove_commands,
+                lang.replace,
+            );
+            merge_list(
+                &mut base.code_flags,
+                lang.code_flags,
+                &lang.remove_code_flags,
+                lang.replace,
+            );
+            merge_list(
+                &mut base.value_flags,
+                lang.value_flags,
+                &lang.remove_value_flags,
+                lang.replace,
+            );
+            merge_list(&mut base.deny, lang.deny, &lang.remove_deny, lang.replace);
+            merge_list(&mut base.ask, lang.ask, &lang.remove_ask, lang.replace);
+            if let Some(v) = lang.program_positional {
+                base.program_positional = v;
+            }
+        }
+        // Approval tokens
+        if let Some(v) = overlay.approval_tokens.key_file {
+            self.approval_tokens.key_file = v;
+        }
+        // Plugins
+        if let Some(v) = overlay.plugins.dir {
+            self.plugins.dir = v;
+        }
+
+        // External evaluator
+        let hooks = overlay.hooks;
+        if let Some(v) = hooks.external_evaluator {
+            self.hooks.external_evaluator = v;
+        }
         merge_list(
-            &mut self.gh.allowed_with_config,
-            gh.allowed_with_config,
-            &gh.remove_allowed_with_config,
-            gh.replace,
+            &mut self.hooks.commands,
+            hooks.commands,
+            &hooks.remove_commands,
+            false,
         );
-        if let Some(v) = gh.config_env {
-            self.gh.config_env = v;
+        if let Some(v) = hooks.fallback {
+            self.hooks.fallback = v;
+        }
+        if let Some(v) = hooks.timeout_ms {
+            self.hooks.timeout_ms = v;
+        }
+
+        // Remote policy
+        let remote = overlay.remote;
+        if let Some(v) = remote.config_url {
+            self.remote.config_url = v;
+        }
+        if let Some(v) = remote.public_key {
+            self.remote.public_key = v;
+        }
+        if let Some(v) = remote.refresh_minutes {
+            self.remote.refresh_minutes = v;
+        }
+        if let Some(v) = remote.timeout_ms {
+            self.remote.timeout_ms = v;
+        }
+
+        // Trust tiers: per-directory override; an empty tier clears it
+        for (pattern, tier) in overlay.trust {
+            if tier.is_empty() {
+                self.trust.remove(&pattern);
+            } else {
+                self.trust.insert(pattern, tier);
+            }
+        }
+
+        // Schedules: each named window is replaced whole
+        self.schedules.extend(overlay.schedules);
+
+        // Conditional rules: appended after the ones already configured
+        self.rules.extend(overlay.rules);
+
+        // Guidance: per-pattern override; an empty template clears it
+        for (pattern, template) in overlay.guidance.rules {
+            if template.is_empty() {
+                self.guidance.rules.remove(&pattern);
+            } else {
+                self.guidance.rules.insert(pattern, template);
+            }
         }
     }
 
@@ -528,6 +2430,26 @@ impl Config {
     }
 }
 
+/// Clear the fields of a `[gh]`-shaped project overlay section that could
+/// loosen user rules, returning whether any were set. Listing an extension
+/// allows running it.
+#[cfg(feature = "native")]
+fn strip_gh(gh: &mut GhOverlay) -> bool {
+    let stripped = gh.replace
+        || !gh.remove_read_only.is_empty()
+        || !gh.remove_mutating.is_empty()
+        || !gh.remove_allowed_with_config.is_empty()
+        || !gh.extensions.is_empty()
+        || gh.extension.is_some();
+    gh.replace = false;
+    gh.remove_read_only.clear();
+    gh.remove_mutating.clear();
+    gh.remove_allowed_with_config.clear();
+    gh.extensions.clear();
+    gh.extension = None;
+    stripped
+}
+
 /// Strip `replace` flags and `remove_*` lists from a project overlay.
 ///
 /// Project overlays are untrusted: they live in the repo and could be crafted
@@ -535,9 +2457,31 @@ impl Config {
 /// function enforces the invariant that project overlays can only ADD entries,
 /// never remove or replace them. If any dangerous field was non-empty/true, a
 /// warning is printed to stderr.
-fn strip_project_overlay_dangerous_fields(overlay: &mut ConfigOverlay, path: &std::path::Path) {
+#[cfg(feature = "native")]
+fn strip_project_overlay_dangerous_fields(
+    overlay: &mut ConfigOverlay,
+    path: &std::path::Path,
+) -> bool {
     let mut stripped = false;
 
+    // settings: audit mode, session grants, and imported allow patterns
+    // turn ASK/DENY into ALLOW
+    if overlay.settings.mode.is_some()
+        || overlay.settings.session_grants.is_some()
+        || overlay.settings.import_claude_permissions.is_some()
+    {
+        stripped = true;
+    }
+    overlay.settings.mode = None;
+    overlay.settings.session_grants = None;
+    overlay.settings.import_claude_permissions = None;
+
+    // settings.platform: chooses the defaults, which a project can't replace
+    if overlay.settings.platform.is_some() {
+        stripped = true;
+    }
+    overlay.settings.platform = None;
+
     // commands
     if overlay.commands.replace
         || !overlay.commands.remove_allow.is_empty()
@@ -555,16 +2499,25 @@ fn strip_project_overlay_dangerous_fields(overlay: &mut ConfigOverlay, path: &st
     if overlay.wrappers.replace
         || !overlay.wrappers.remove_allow_floor.is_empty()
         || !overlay.wrappers.remove_ask_floor.is_empty()
+        || overlay.wrappers.interactive_shell.is_some()
+        || overlay.wrappers.app_launch.is_some()
     {
         stripped = true;
     }
     overlay.wrappers.replace = false;
     overlay.wrappers.remove_allow_floor.clear();
     overlay.wrappers.remove_ask_floor.clear();
+    overlay.wrappers.interactive_shell = None;
+    overlay.wrappers.app_launch = None;
 
     // git
     if overlay.git.replace
         || !overlay.git.remove_read_only.is_empty()
+        || !overlay.git.remove_mutating.is_empty()
+        || !overlay.git.remove_sensitive_config_keys.is_empty()
+        || overlay.git.sensitive_config.is_some()
+        || overlay.git.global_config.is_some()
+        || overlay.git.local_config.is_some()
         || !overlay.git.remove_allowed_with_config.is_empty()
         || !overlay.git.remove_force_push_flags.is_empty()
     {
@@ -572,6 +2525,11 @@ fn strip_project_overlay_dangerous_fields(overlay: &mut ConfigOverlay, path: &st
     }
     overlay.git.replace = false;
     overlay.git.remove_read_only.clear();
+    overlay.git.remove_mutating.clear();
+    overlay.git.remove_sensitive_config_keys.clear();
+    overlay.git.sensitive_config = None;
+    overlay.git.global_config = None;
+    overlay.git.local_config = None;
     overlay.git.remove_allowed_with_config.clear();
     overlay.git.remove_force_push_flags.clear();
 
@@ -591,6 +2549,7 @@ fn strip_project_overlay_dangerous_fields(overlay: &mut ConfigOverlay, path: &st
         || !overlay.kubectl.remove_read_only.is_empty()
         || !overlay.kubectl.remove_mutating.is_empty()
         || !overlay.kubectl.remove_allowed_with_config.is_empty()
+        || !overlay.kubectl.remove_sensitive_resources.is_empty()
     {
         stripped = true;
     }
@@ -598,19 +2557,349 @@ fn strip_project_overlay_dangerous_fields(overlay: &mut ConfigOverlay, path: &st
     overlay.kubectl.remove_read_only.clear();
     overlay.kubectl.remove_mutating.clear();
     overlay.kubectl.remove_allowed_with_config.clear();
+    overlay.kubectl.remove_sensitive_resources.clear();
+
+    // gh, hub, tea, fj
+    for section in [
+        &mut overlay.gh,
+        &mut overlay.hub,
+        &mut overlay.tea,
+        &mut overlay.fj,
+    ] {
+        stripped |= strip_gh(section);
+    }
+
+    // apt: read_only additions or dropping mutating entries loosen user rules
+    if overlay.apt.replace
+        || !overlay.apt.read_only.is_empty()
+        || !overlay.apt.remove_mutating.is_empty()
+    {
+        stripped = true;
+    }
+    overlay.apt.replace = false;
+    overlay.apt.read_only.clear();
+    overlay.apt.remove_mutating.clear();
+
+    // dnf: same as apt, and history_undo could be lowered
+    if overlay.dnf.replace
+        || !overlay.dnf.read_only.is_empty()
+        || !overlay.dnf.remove_mutating.is_empty()
+        || overlay.dnf.history_undo.is_some()
+    {
+        stripped = true;
+    }
+    overlay.dnf.replace = false;
+    overlay.dnf.read_only.clear();
+    overlay.dnf.remove_mutating.clear();
+    overlay.dnf.history_undo = None;
+
+    // paths: sandbox additions would widen where writes are allowed
+    if overlay.paths.replace
+        || !overlay.paths.remove_sensitive.is_empty()
+        || !overlay.paths.remove_protected.is_empty()
+        || !overlay.paths.remove_sandbox.is_empty()
+        || !overlay.paths.sandbox.is_empty()
+        || overlay.paths.project_sandbox.is_some()
+    {
+        stripped = true;
+    }
+    overlay.paths.replace = false;
+    overlay.paths.remove_sensitive.clear();
+    overlay.paths.remove_protected.clear();
+    overlay.paths.remove_sandbox.clear();
+    overlay.paths.sandbox.clear();
+    overlay.paths.project_sandbox = None;
+
+    // scp: decision overrides could loosen user rules
+    if overlay.scp.upload.is_some()
+        || overlay.scp.download.is_some()
+        || overlay.scp.sensitive.is_some()
+    {
+        stripped = true;
+    }
+    overlay.scp = ScpOverlay::default();
+
+    // rsync: only additive destructive_flags survive
+    if overlay.rsync.replace
+        || !overlay.rsync.remove_destructive_flags.is_empty()
+        || overlay.rsync.destructive.is_some()
+        || overlay.rsync.remote_destination.is_some()
+        || overlay.rsync.allow_local_dry_run.is_some()
+    {
+        stripped = true;
+    }
+    overlay.rsync.replace = false;
+    overlay.rsync.remove_destructive_flags.clear();
+    overlay.rsync.destructive = None;
+    overlay.rsync.remote_destination = None;
+    overlay.rsync.allow_local_dry_run = None;
+
+    // network: allowed_domains additions would auto-allow new destinations
+    if overlay.network.replace
+        || !overlay.network.allowed_domains.is_empty()
+        || !overlay.network.remove_allowed_domains.is_empty()
+        || !overlay.network.remove_denied_domains.is_empty()
+        || overlay.network.file_upload.is_some()
+        || !overlay.network.remove_internal_hosts.is_empty()
+        || overlay.network.internal_network.is_some()
+    {
+        stripped = true;
+    }
+    overlay.network.replace = false;
+    overlay.network.allowed_domains.clear();
+    overlay.network.remove_allowed_domains.clear();
+    overlay.network.remove_denied_domains.clear();
+    overlay.network.file_upload = None;
+    overlay.network.remove_internal_hosts.clear();
+    overlay.network.internal_network = None;
+
+    // raw_network: decision overrides could loosen user rules
+    if overlay.raw_network.client.is_some()
+        || overlay.raw_network.listen.is_some()
+        || overlay.raw_network.exec.is_some()
+    {
+        stripped = true;
+    }
+    overlay.raw_network = RawNetworkOverlay::default();
+
+    // firewall: decision overrides could loosen user rules
+    if overlay.firewall.rule_change.is_some() || overlay.firewall.flush.is_some() {
+        stripped = true;
+    }
+    overlay.firewall = FirewallOverlay::default();
+
+    // tee: decision override could loosen user rules
+    if overlay.tee.privileged.is_some() {
+        stripped = true;
+    }
+    overlay.tee = TeeOverlay::default();
+
+    // rm: a laxer decision or depth loosens user rules
+    if overlay.rm.catastrophic.is_some() || overlay.rm.min_depth.is_some() {
+        stripped = true;
+    }
+    overlay.rm = RmOverlay::default();
+
+    // permissions: a laxer decision or depth loosens user rules
+    if overlay.permissions.system_root.is_some() || overlay.permissions.min_depth.is_some() {
+        stripped = true;
+    }
+    overlay.permissions = PermissionsOverlay::default();
+
+    // accounts: decision override could loosen user rules
+    if overlay.accounts.change.is_some() {
+        stripped = true;
+    }
+    overlay.accounts = AccountsOverlay::default();
+
+    // aws: decision overrides could loosen user rules
+    if overlay.aws.s3_download.is_some()
+        || overlay.aws.s3_upload.is_some()
+        || overlay.aws.s3_presign.is_some()
+        || overlay.aws.s3_destructive.is_some()
+        || overlay.aws.identity.is_some()
+    {
+        stripped = true;
+    }
+    overlay.aws = AwsOverlay::default();
+
+    // terraform: decision overrides could loosen user rules
+    if overlay.terraform.apply.is_some()
+        || overlay.terraform.state_change.is_some()
+        || overlay.terraform.state_destructive.is_some()
+    {
+        stripped = true;
+    }
+    overlay.terraform = TerraformOverlay::default();
+
+    // docker: decision overrides could loosen user rules
+    if overlay.docker.run.is_some()
+        || overlay.docker.escalation.is_some()
+        || overlay.docker.exec.is_some()
+        || overlay.docker.interactive_shell.is_some()
+        || overlay.docker.image.is_some()
+        || overlay.docker.lifecycle.is_some()
+        || overlay.docker.destructive.is_some()
+    {
+        stripped = true;
+    }
+    overlay.docker = DockerOverlay::default();
+
+    // ansible: decision overrides could loosen user rules
+    if overlay.ansible.run.is_some() || overlay.ansible.privileged.is_some() {
+        stripped = true;
+    }
+    overlay.ansible = AnsibleOverlay::default();
+
+    // crypto: decision overrides could loosen user rules
+    if overlay.crypto.key_material.is_some() || overlay.crypto.secret_export.is_some() {
+        stripped = true;
+    }
+    overlay.crypto = CryptoOverlay::default();
+
+    // ssh_keys: decision overrides could loosen user rules
+    if overlay.ssh_keys.change.is_some() || overlay.ssh_keys.overwrite.is_some() {
+        stripped = true;
+    }
+    overlay.ssh_keys = SshKeysOverlay::default();
+
+    // vcs: decision overrides could loosen user rules
+    if overlay.vcs.change.is_some() || overlay.vcs.destructive.is_some() {
+        stripped = true;
+    }
+    overlay.vcs = VcsOverlay::default();
+
+    // bazel: read_only additions or a lower run decision loosen user rules
+    if overlay.bazel.replace || !overlay.bazel.read_only.is_empty() || overlay.bazel.run.is_some() {
+        stripped = true;
+    }
+    overlay.bazel.replace = false;
+    overlay.bazel.read_only.clear();
+    overlay.bazel.run = None;
+
+    // gradle, maven: read_only additions or a lower publish decision loosen
+    // user rules
+    for jvm in [&mut overlay.gradle, &mut overlay.maven] {
+        if jvm.replace || !jvm.read_only.is_empty() || jvm.publish.is_some() {
+            stripped = true;
+        }
+        jvm.replace = false;
+        jvm.read_only.clear();
+        jvm.publish = None;
+    }
+
+    // groups: lowering a floor loosens user rules
+    if overlay.groups.subshell.is_some() || overlay.groups.brace_group.is_some() {
+        stripped = true;
+    }
+    overlay.groups = GroupsOverlay::default();
+
+    // cmd_exe
+    if overlay.cmd_exe.replace
+        || !overlay.cmd_exe.remove_allow.is_empty()
+        || !overlay.cmd_exe.remove_ask.is_empty()
+        || !overlay.cmd_exe.remove_deny.is_empty()
+    {
+        stripped = true;
+    }
+    overlay.cmd_exe.replace = false;
+    overlay.cmd_exe.remove_allow.clear();
+    overlay.cmd_exe.remove_ask.clear();
+    overlay.cmd_exe.remove_deny.clear();
+
+    // redirection: a new safe sink or target would hide writes
+    if overlay.redirection.replace
+        || !overlay.redirection.safe_sinks.is_empty()
+        || !overlay.redirection.safe_targets.is_empty()
+    {
+        stripped = true;
+    }
+    overlay.redirection.replace = false;
+    overlay.redirection.safe_sinks.clear();
+    overlay.redirection.safe_targets.clear();
+
+    // wildcards: dropping a command or lowering the decision loosens user rules
+    if overlay.wildcards.replace
+        || !overlay.wildcards.remove_commands.is_empty()
+        || overlay.wildcards.decision.is_some()
+    {
+        stripped = true;
+    }
+    overlay.wildcards.replace = false;
+    overlay.wildcards.remove_commands.clear();
+    overlay.wildcards.decision = None;
+
+    // inline_code: value_flags additions could hide a code flag's payload
+    for lang in overlay.inline_code.languages.values_mut() {
+        if lang.replace
+            || !lang.value_flags.is_empty()
+            || lang.program_positional.is_some()
+            || !lang.remove_commands.is_empty()
+            || !lang.remove_code_flags.is_empty()
+            || !lang.remove_value_flags.is_empty()
+            || !lang.remove_deny.is_empty()
+            || !lang.remove_ask.is_empty()
+        {
+            stripped = true;
+        }
+        lang.replace = false;
+        lang.value_flags.clear();
+        lang.program_positional = None;
+        lang.remove_commands.clear();
+        lang.remove_code_flags.clear();
+        lang.remove_value_flags.clear();
+        lang.remove_deny.clear();
+        lang.remove_ask.clear();
+    }
+
+    // approval_tokens: a project-chosen key would let it sign its own approvals
+    if overlay.approval_tokens.key_file.is_some() {
+        stripped = true;
+    }
+    overlay.approval_tokens = ApprovalTokensOverlay::default();
 
-    // gh
-    if overlay.gh.replace
-        || !overlay.gh.remove_read_only.is_empty()
-        || !overlay.gh.remove_mutating.is_empty()
-        || !overlay.gh.remove_allowed_with_config.is_empty()
+    // plugins: loading a project's library runs its code in the hook
+    if overlay.plugins.dir.is_some() {
+        stripped = true;
+    }
+    overlay.plugins = PluginsOverlay::default();
+
+    // hooks: a project-chosen script would run in the hook and decide commands
+    if overlay.hooks.external_evaluator.is_some()
+        || !overlay.hooks.commands.is_empty()
+        || !overlay.hooks.remove_commands.is_empty()
+        || overlay.hooks.fallback.is_some()
+        || overlay.hooks.timeout_ms.is_some()
+    {
+        stripped = true;
+    }
+    overlay.hooks = HooksOverlay::default();
+
+    // remote: a project-chosen policy URL and key would be trusted like the
+    // user's own config
+    if overlay.remote.config_url.is_some()
+        || overlay.remote.public_key.is_some()
+        || overlay.remote.refresh_minutes.is_some()
+        || overlay.remote.timeout_ms.is_some()
     {
         stripped = true;
     }
-    overlay.gh.replace = false;
-    overlay.gh.remove_read_only.clear();
-    overlay.gh.remove_mutating.clear();
-    overlay.gh.remove_allowed_with_config.clear();
+    overlay.remote = RemoteOverlay::default();
+
+    // profiles: selected by the user, so they belong in the user overlay
+    if !overlay.profiles.is_empty() {
+        stripped = true;
+    }
+    overlay.profiles.clear();
+
+    // trust: a project can't choose how far its own directory is trusted
+    if !overlay.trust.is_empty() {
+        overlay.trust.clear();
+        stripped = true;
+    }
+
+    // schedules: redefining a user's window would move when their rules apply
+    if !overlay.schedules.is_empty() {
+        overlay.schedules.clear();
+        stripped = true;
+    }
+
+    // rules: only DENY rules, which can't loosen anything, are kept
+    let before = overlay.rules.len();
+    overlay.rules.retain(|rule| rule.decision == Decision::Deny);
+    if overlay.rules.len() != before {
+        stripped = true;
+    }
+
+    // guidance: an empty template removes the user's guidance for a pattern
+    let before = overlay.guidance.rules.len();
+    overlay
+        .guidance
+        .rules
+        .retain(|_, template| !template.is_empty());
+    if overlay.guidance.rules.len() != before {
+        stripped = true;
+    }
 
     if stripped {
         eprintln!(
@@ -618,14 +2907,13 @@ fn strip_project_overlay_dangerous_fields(overlay: &mut ConfigOverlay, path: &st
             path.display()
         );
     }
+    stripped
 }
 
-/// Read and parse a ConfigOverlay from `path`. Returns `None` if the file
-/// can't be read (missing, permission denied, etc.); logs to stderr and
-/// returns `None` on parse errors.
-fn load_overlay_from_path(path: &std::path::Path, err_label: &str) -> Option<ConfigOverlay> {
-    let content = std::fs::read_to_string(path).ok()?;
-    match toml::from_str(&content) {
+/// Parse overlay TOML; logs to stderr and returns `None` on parse errors.
+#[cfg(feature = "native")]
+fn parse_overlay(content: &str, err_label: &str) -> Option<ConfigOverlay> {
+    match toml::from_str(content) {
         Ok(overlay) => Some(overlay),
         Err(e) => {
             eprintln!("cc-toolgate: {err_label}: {e}");
@@ -634,10 +2922,428 @@ fn load_overlay_from_path(path: &std::path::Path, err_label: &str) -> Option<Con
     }
 }
 
+// ── Provenance ──
+
+/// Which source last set each value of a config, from
+/// [`Config::load_traced`].
+///
+/// A scalar is attributed to the last source that changed it, and a list
+/// entry to the source that added it.
+#[cfg(feature = "native")]
+#[derive(Debug, Default)]
+pub struct Provenance {
+    /// Leaf id (see [`leaves`]) → (value, source).
+    sources: HashMap<String, (String, String)>,
+}
+
+#[cfg(feature = "native")]
+impl Provenance {
+    /// Attribute whatever changed since the last call to `source`.
+    pub fn record(&mut self, source: &str, config: &Config) {
+        let mut current = HashMap::new();
+        for leaf in leaves(config) {
+            let source = match self.sources.remove(&leaf.id) {
+                Some((value, previous)) if value == leaf.value => previous,
+                _ => source.to_string(),
+            };
+            current.insert(leaf.id, (leaf.value, source));
+        }
+        self.sources = current;
+    }
+
+    /// The source of `value` (as JSON) at `path`: a scalar's path such as
+    /// `settings.mode`, or a list's path with `[]` appended, such as
+    /// `commands.allow[]`.
+    pub fn source(&self, path: &str, value: &str) -> Option<&str> {
+        let id = leaf_id(path, value);
+        match self.sources.get(&id) {
+            Some((current, source)) if current == value => Some(source),
+            _ => None,
+        }
+    }
+
+    /// `config` as one `path = value  # source` line per scalar and
+    /// `path[] = value  # source` line per list entry.
+    pub fn annotate(&self, config: &Config) -> String {
+        let mut out = String::new();
+        for leaf in leaves(config) {
+            let source = self
+                .sources
+                .get(&leaf.id)
+                .map_or("?", |(_, source)| source.as_str());
+            out.push_str(&format!("{} = {}  # {source}\n", leaf.path, leaf.value));
+        }
+        out
+    }
+}
+
+/// One difference between two configs, from [`diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigChange {
+    /// A list entry, or a map key, only in the second config.
+    Added { path: String, value: String },
+    /// A list entry, or a map key, only in the first config.
+    Removed { path: String, value: String },
+    /// A scalar with a different value.
+    Changed {
+        path: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl std::fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added { path, value } => write!(f, "+ {path} = {value}"),
+            Self::Removed { path, value } => write!(f, "- {path} = {value}"),
+            Self::Changed { path, from, to } => write!(f, "~ {path} = {from} -> {to}"),
+        }
+    }
+}
+
+/// What `config` changes relative to `base`, with paths and values as in
+/// [`Provenance::annotate`]: changed scalars and added list entries in
+/// `config`'s order, then removed entries in `base`'s order.
+pub fn diff(base: &Config, config: &Config) -> Vec<ConfigChange> {
+    let before: HashMap<String, Leaf> = leaves(base)
+        .into_iter()
+        .map(|leaf| (leaf.id.clone(), leaf))
+        .collect();
+    let after = leaves(config);
+    let after_ids: std::collections::HashSet<&str> =
+        after.iter().map(|leaf| leaf.id.as_str()).collect();
+
+    let mut changes = Vec::new();
+    for leaf in &after {
+        match before.get(&leaf.id) {
+            Some(old) if old.value == leaf.value => {}
+            Some(old) => changes.push(ConfigChange::Changed {
+                path: leaf.path.clone(),
+                from: old.value.clone(),
+                to: leaf.value.clone(),
+            }),
+            None => changes.push(ConfigChange::Added {
+                path: leaf.path.clone(),
+                value: leaf.value.clone(),
+            }),
+        }
+    }
+    for leaf in leaves(base) {
+        if !after_ids.contains(leaf.id.as_str()) {
+            changes.push(ConfigChange::Removed {
+                path: leaf.path,
+                value: leaf.value,
+            });
+        }
+    }
+    changes
+}
+
+/// One scalar or list entry in a config.
+struct Leaf {
+    /// `path` for scalars, `path[value]` for list entries.
+    id: String,
+    /// Dotted path, with `[]` appended for list entries.
+    path: String,
+    /// The value as JSON.
+    value: String,
+}
+
+/// How a leaf at `path` with `value` is identified: scalars by path, list
+/// entries (`path[]`) by path and value.
+fn leaf_id(path: &str, value: &str) -> String {
+    match path.strip_suffix("[]") {
+        Some(list) => format!("{list}[{value}]"),
+        None => path.to_string(),
+    }
+}
+
+/// The scalars and list entries of `config`, in serialization order.
+fn leaves(config: &Config) -> Vec<Leaf> {
+    fn walk(value: &serde_json::Value, path: &str, out: &mut Vec<Leaf>) {
+        match value {
+            serde_json::Value::Object(fields) => {
+                for (key, value) in fields {
+                    let path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{path}.{key}")
+                    };
+                    walk(value, &path, out);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                let path = format!("{path}[]");
+                for item in items {
+                    let value = item.to_string();
+                    out.push(Leaf {
+                        id: leaf_id(&path, &value),
+                        path: path.clone(),
+                        value,
+                    });
+                }
+            }
+            scalar => out.push(Leaf {
+                id: path.to_string(),
+                path: path.to_string(),
+                value: scalar.to_string(),
+            }),
+        }
+    }
+    let mut out = Vec::new();
+    let value = serde_json::to_value(config).expect("config serializes");
+    walk(&value, "", &mut out);
+    out
+}
+
+// ── Compiled config cache ──
+
+/// Env var naming a user overlay file to use instead of `config.toml` and
+/// `config.d`.
+#[cfg(feature = "native")]
+pub const CONFIG_ENV: &str = "CC_TOOLGATE_CONFIG";
+
+/// Env var naming the `[profiles.<name>]` section to apply.
+#[cfg(feature = "native")]
+pub const PROFILE_ENV: &str = "CC_TOOLGATE_PROFILE";
+
+/// Env var that bypasses the compiled config cache when set.
+#[cfg(feature = "native")]
+pub const NO_CACHE_ENV: &str = "CC_TOOLGATE_NO_CACHE";
+
+/// Cache entries unused for this long are removed when a new one is written.
+#[cfg(feature = "native")]
+const CACHE_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(30 * 86_400);
+
+/// The raw inputs to [`Config::load`].
+#[cfg(feature = "native")]
+#[derive(Default)]
+struct ConfigSources {
+    /// `~/.config/cc-toolgate/config.toml` or the explicit config file, if
+    /// present.
+    user: Option<String>,
+    /// `~/.config/cc-toolgate/config.d/*.toml`, sorted by file name.
+    user_dropins: Vec<(std::path::PathBuf, String)>,
+    /// The selected profile, if any.
+    profile: Option<String>,
+    /// `<git-root>/.claude/cc-toolgate.toml` and its path, if present.
+    project: Option<(std::path::PathBuf, String)>,
+    /// Why an explicitly named config file couldn't be read.
+    unreadable: Vec<String>,
+    /// The last remote policy fetched, if any.
+    #[cfg(feature = "remote")]
+    remote: Option<crate::remote::CachedPolicy>,
+    /// Claude Code's `~/.claude/settings.json`, if present.
+    claude_user: Option<(std::path::PathBuf, String)>,
+    /// The project's `.claude/settings.json` and `.claude/settings.local.json`,
+    /// those present.
+    claude_project: Vec<(std::path::PathBuf, String)>,
+}
+
+#[cfg(feature = "native")]
+impl ConfigSources {
+    /// Read the user and project overlay files, and Claude Code's settings.
+    /// `explicit` replaces the user's `config.toml` and `config.d`.
+    fn read(explicit: Option<&std::path::Path>) -> Self {
+        let read =
+            |path: std::path::PathBuf| Some((path.clone(), std::fs::read_to_string(path).ok()?));
+        let home = std::env::var_os("HOME").map(std::path::PathBuf::from);
+        let mut unreadable = Vec::new();
+        let (user, user_dropins) = match explicit {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(text) => (Some(text), Vec::new()),
+                Err(e) => {
+                    unreadable.push(format!("config file {}: {e}", path.display()));
+                    (None, Vec::new())
+                }
+            },
+            None => match user_config_dir() {
+                Some(dir) => (
+                    read(dir.join("config.toml")).map(|(_, text)| text),
+                    read_dropins(&dir.join("config.d")),
+                ),
+                None => (None, Vec::new()),
+            },
+        };
+        let root = std::env::current_dir()
+            .ok()
+            .and_then(|cwd| find_git_root(&cwd));
+        let project = root
+            .as_ref()
+            .and_then(|root| read(root.join(".claude/cc-toolgate.toml")));
+        let claude_user = home.and_then(|home| read(home.join(".claude/settings.json")));
+        let claude_project = root
+            .map(|root| {
+                ["settings.json", "settings.local.json"]
+                    .into_iter()
+                    .filter_map(|name| read(root.join(".claude").join(name)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let profile = std::env::var(PROFILE_ENV).ok().filter(|p| !p.is_empty());
+        Self {
+            user,
+            user_dropins,
+            unreadable,
+            #[cfg(feature = "remote")]
+            remote: crate::remote::CachedPolicy::default_path()
+                .and_then(|path| crate::remote::CachedPolicy::read(&path)),
+            profile,
+            project,
+            claude_user,
+            claude_project,
+        }
+    }
+
+    /// Cache file for this exact set of inputs, under `~/.cache/cc-toolgate/`.
+    /// `None` when a named config file is missing, so the error is reported.
+    fn cache_path(&self) -> Option<std::path::PathBuf> {
+        if !self.unreadable.is_empty() {
+            return None;
+        }
+        let home = std::env::var_os("HOME")?;
+        Some(
+            std::path::Path::new(&home)
+                .join(".cache/cc-toolgate")
+                .join(format!("config-{}.json", self.key())),
+        )
+    }
+
+    /// A hash of every input, which changes whenever one of them does.
+    fn key(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let mut hash = Sha256::new();
+        // Length-prefix each part so adjacent inputs can't run together.
+        let mut part = |bytes: &[u8]| {
+            hash.update((bytes.len() as u64).to_le_bytes());
+            hash.update(bytes);
+        };
+        part(env!("CARGO_PKG_VERSION").as_bytes());
+        part(DEFAULT_CONFIG.as_bytes());
+        part(LINUX_DEFAULTS.as_bytes());
+        part(MACOS_DEFAULTS.as_bytes());
+        part(self.user.as_deref().unwrap_or_default().as_bytes());
+        part(self.profile.as_deref().unwrap_or_default().as_bytes());
+        #[cfg(feature = "remote")]
+        if let Some(remote) = &self.remote {
+            part(remote.url.as_bytes());
+            part(remote.policy.as_deref().unwrap_or_default().as_bytes());
+            part(remote.signature.as_bytes());
+        }
+        for (path, text) in self
+            .user_dropins
+            .iter()
+            .chain(&self.project)
+            .chain(&self.claude_user)
+            .chain(&self.claude_project)
+        {
+            part(path.as_os_str().as_encoded_bytes());
+            part(text.as_bytes());
+        }
+        hash.finalize()[..16]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+}
+
+/// `$XDG_CONFIG_HOME/cc-toolgate`, or `~/.config/cc-toolgate`. A relative
+/// `XDG_CONFIG_HOME` is ignored, as the XDG spec requires.
+#[cfg(feature = "native")]
+fn user_config_dir() -> Option<std::path::PathBuf> {
+    let xdg = std::env::var_os("XDG_CONFIG_HOME")
+        .map(std::path::PathBuf::from)
+        .filter(|dir| dir.is_absolute());
+    let base = match xdg {
+        Some(dir) => dir,
+        None => std::path::Path::new(&std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("cc-toolgate"))
+}
+
+/// The `*.toml` files in `dir`, sorted by name; empty if there is no `dir`.
+#[cfg(feature = "native")]
+fn read_dropins(dir: &std::path::Path) -> Vec<(std::path::PathBuf, String)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml") && path.is_file())
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| Some((path.clone(), std::fs::read_to_string(path).ok()?)))
+        .collect()
+}
+
+/// A merged config as stored in the cache. `project_overlay_path` is not
+/// serialized with [`Config`], so it is carried alongside.
+#[cfg(feature = "native")]
+#[derive(Serialize, Deserialize)]
+struct CachedConfig {
+    config: Config,
+    project_overlay_path: Option<std::path::PathBuf>,
+}
+
+/// Load a cached config; `None` if missing or unreadable.
+#[cfg(feature = "native")]
+fn read_cached(path: &std::path::Path) -> Option<Config> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let cached: CachedConfig = serde_json::from_str(&text).ok()?;
+    let mut config = cached.config;
+    config.project_overlay_path = cached.project_overlay_path;
+    Some(config)
+}
+
+/// Best-effort write of a cache entry (atomic rename), pruning stale ones.
+#[cfg(feature = "native")]
+fn write_cached(path: &std::path::Path, config: &Config) {
+    let Some(dir) = path.parent() else { return };
+    if std::fs::create_dir_all(dir).is_err() {
+        return;
+    }
+    prune_cache(dir);
+    let cached = CachedConfig {
+        config: config.clone(),
+        project_overlay_path: config.project_overlay_path.clone(),
+    };
+    let Ok(json) = serde_json::to_string(&cached) else {
+        return;
+    };
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    if std::fs::write(&tmp, json).is_ok() && std::fs::rename(&tmp, path).is_err() {
+        std::fs::remove_file(&tmp).ok();
+    }
+}
+
+/// Remove cache entries not modified within [`CACHE_MAX_AGE`].
+#[cfg(feature = "native")]
+fn prune_cache(dir: &std::path::Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .is_some_and(|age| age > CACHE_MAX_AGE);
+        let is_entry = entry.file_name().to_string_lossy().starts_with("config-");
+        if stale && is_entry {
+            std::fs::remove_file(entry.path()).ok();
+        }
+    }
+}
+
 /// Walk up from `start` looking for a `.git` entry (dir for normal repos,
 /// file for worktrees). Returns the containing directory, or `None` if no
 /// ancestor contains `.git`.
-fn find_git_root(start: &std::path::Path) -> Option<std::path::PathBuf> {
+pub(crate) fn find_git_root(start: &std::path::Path) -> Option<std::path::PathBuf> {
     let mut current = Some(start);
     while let Some(dir) = current {
         if dir.join(".git").exists() {
@@ -653,29 +3359,125 @@ mod tests {
     use super::*;
 
     #[test]
-    fn default_config_parses() {
-        let config = Config::default_config();
-        assert!(!config.commands.allow.is_empty());
-        assert!(!config.commands.ask.is_empty());
-        assert!(!config.commands.deny.is_empty());
-        assert!(!config.git.read_only.is_empty());
-        assert!(!config.cargo.safe_subcommands.is_empty());
-        assert!(!config.kubectl.read_only.is_empty());
-        assert!(!config.gh.read_only.is_empty());
+    fn default_config_parses() {
+        let config = Config::default_config();
+        assert!(!config.commands.allow.is_empty());
+        assert!(!config.commands.ask.is_empty());
+        assert!(!config.commands.deny.is_empty());
+        assert!(!config.git.read_only.is_empty());
+        assert!(!config.cargo.safe_subcommands.is_empty());
+        assert!(!config.kubectl.read_only.is_empty());
+        assert!(!config.gh.read_only.is_empty());
+    }
+
+    #[test]
+    fn default_config_has_expected_commands() {
+        let config = Config::default_config();
+        assert!(config.commands.allow.contains(&"ls".to_string()));
+        assert!(config.commands.ask.contains(&"rm".to_string()));
+        assert!(config.commands.deny.contains(&"shred".to_string()));
+    }
+
+    #[test]
+    fn with_overlay_merges_toml_text() {
+        let config = Config::with_overlay(
+            r#"
+            [commands]
+            allow = ["my-tool"]
+            remove_deny = ["eval"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.commands.allow.contains(&"my-tool".to_string()));
+        assert!(config.commands.allow.contains(&"ls".to_string()));
+        assert!(!config.commands.deny.contains(&"eval".to_string()));
+        assert!(config.project_overlay_path.is_none());
+        assert!(Config::with_overlay("[commands\n").is_err());
+    }
+
+    #[test]
+    fn default_escalate_deny_is_false() {
+        let config = Config::default_config();
+        assert!(!config.settings.escalate_deny);
+    }
+
+    #[test]
+    fn default_inline_code_languages() {
+        let config = Config::default_config();
+        for lang in ["python", "node", "ruby", "perl", "awk"] {
+            assert!(
+                config.inline_code.languages.contains_key(lang),
+                "missing {lang}"
+            );
+        }
+        assert!(config.inline_code.languages["awk"].program_positional);
+    }
+
+    #[test]
+    fn overlay_inline_code_extends_and_adds_language() {
+        let mut config = Config::default_config();
+        config.apply_overlay_str(
+            r#"
+            [inline_code.python]
+            deny = ["ctypes"]
+            remove_ask = ["open("]
+
+            [inline_code.lua]
+            commands = ["lua"]
+            code_flags = ["-e"]
+            deny = ["os.execute"]
+            "#,
+        );
+        let python = &config.inline_code.languages["python"];
+        assert!(python.deny.contains(&"ctypes".to_string()));
+        assert!(python.deny.contains(&"pty.spawn".to_string()));
+        assert!(!python.ask.contains(&"open(".to_string()));
+        assert_eq!(config.inline_code.languages["lua"].commands, vec!["lua"]);
+    }
+
+    #[test]
+    fn overlay_guidance_adds_and_clears() {
+        let mut config = Config::default_config();
+        config.apply_overlay_str(
+            r#"
+            [guidance]
+            "terraform apply" = "terraform apply must go through CI"
+            "helm" = "use the deploy pipeline"
+            "#,
+        );
+        assert_eq!(config.guidance.rules.len(), 2);
+        config.apply_overlay_str(
+            r#"
+            [guidance]
+            "helm" = ""
+            "#,
+        );
+        assert_eq!(
+            config.guidance.rules.keys().collect::<Vec<_>>(),
+            vec!["terraform apply"]
+        );
     }
 
     #[test]
-    fn default_config_has_expected_commands() {
+    fn default_scp_decisions() {
         let config = Config::default_config();
-        assert!(config.commands.allow.contains(&"ls".to_string()));
-        assert!(config.commands.ask.contains(&"rm".to_string()));
-        assert!(config.commands.deny.contains(&"shred".to_string()));
+        assert_eq!(config.scp.upload, Decision::Ask);
+        assert_eq!(config.scp.download, Decision::Ask);
+        assert_eq!(config.scp.sensitive, Decision::Deny);
+        assert!(config.paths.sensitive.contains(&"~/.ssh".to_string()));
     }
 
     #[test]
-    fn default_escalate_deny_is_false() {
-        let config = Config::default_config();
-        assert!(!config.settings.escalate_deny);
+    fn overlay_scp_decision_override() {
+        let mut config = Config::default_config();
+        config.apply_overlay_str(
+            r#"
+            [scp]
+            download = "allow"
+        "#,
+        );
+        assert_eq!(config.scp.download, Decision::Allow);
+        assert_eq!(config.scp.upload, Decision::Ask);
     }
 
     #[test]
@@ -759,60 +3561,428 @@ mod tests {
                 .allow_floor
                 .contains(&"my-wrapper".to_string())
         );
-        assert!(config.wrappers.allow_floor.contains(&"xargs".to_string()));
+        assert!(config.wrappers.allow_floor.contains(&"xargs".to_string()));
+    }
+
+    #[test]
+    fn overlay_replace_commands() {
+        let mut config = Config::default_config();
+        config.apply_overlay_str(
+            r#"
+            [commands]
+            replace = true
+            allow = ["ls", "cat"]
+            ask = ["rm"]
+            deny = ["shred"]
+        "#,
+        );
+        assert_eq!(config.commands.allow, vec!["ls", "cat"]);
+        assert_eq!(config.commands.ask, vec!["rm"]);
+        assert_eq!(config.commands.deny, vec!["shred"]);
+    }
+
+    #[test]
+    fn overlay_git_env_gate() {
+        let mut config = Config::default_config();
+        config.apply_overlay_str(
+            r#"
+            [git]
+            allowed_with_config = ["commit", "add", "push"]
+            [git.config_env]
+            GIT_CONFIG_GLOBAL = "~/.gitconfig.ai"
+        "#,
+        );
+        assert_eq!(
+            config.git.config_env.get("GIT_CONFIG_GLOBAL").unwrap(),
+            "~/.gitconfig.ai"
+        );
+        assert_eq!(
+            config.git.allowed_with_config,
+            vec!["commit", "add", "push"]
+        );
+        // Default read_only still present
+        assert!(config.git.read_only.contains(&"status".to_string()));
+        assert!(config.git.read_only.contains(&"log".to_string()));
+    }
+
+    #[test]
+    fn overlay_git_config_rules() {
+        let mut config = Config::default_config();
+        assert_eq!(config.git.sensitive_config, Decision::Deny);
+        assert_eq!(config.git.local_config, Decision::Allow);
+        config.apply_overlay_str(
+            r#"
+            [git]
+            sensitive_config_keys = ["user.signingkey"]
+            remove_sensitive_config_keys = ["alias.*"]
+            global_config = "deny"
+        "#,
+        );
+        let keys = &config.git.sensitive_config_keys;
+        assert!(keys.contains(&"user.signingkey".to_string()));
+        assert!(keys.contains(&"core.sshcommand".to_string()));
+        assert!(!keys.contains(&"alias.*".to_string()));
+        assert_eq!(config.git.global_config, Decision::Deny);
+        assert_eq!(config.git.sensitive_config, Decision::Deny);
+    }
+
+    #[test]
+    fn overlay_gh_extensions() {
+        let mut config = Config::default_config();
+        assert!(config.gh.extensions.is_empty());
+        assert_eq!(config.gh.extension, Decision::Ask);
+        config.apply_overlay_str(
+            r#"
+            [gh]
+            extensions = ["dash", "poi"]
+            extension = "deny"
+        "#,
+        );
+        config.apply_overlay_str(
+            r#"
+            [gh]
+            remove_extensions = ["poi"]
+        "#,
+        );
+        assert_eq!(config.gh.extensions, ["dash"]);
+        assert_eq!(config.gh.extension, Decision::Deny);
+    }
+
+    #[test]
+    fn overlay_gh_style_sections() {
+        let mut config = Config::default_config();
+        assert!(config.tea.mutating.contains(&"pulls merge".to_string()));
+        config.apply_overlay_str(
+            r#"
+            [tea]
+            read_only = ["times"]
+            remove_mutating = ["pulls merge"]
+        "#,
+        );
+        assert!(config.tea.read_only.contains(&"times".to_string()));
+        assert!(!config.tea.mutating.contains(&"pulls merge".to_string()));
+        assert!(config.gh.mutating.contains(&"pr merge".to_string()));
+    }
+
+    #[test]
+    fn overlay_escalate_deny() {
+        let mut config = Config::default_config();
+        config.apply_overlay_str(
+            r#"
+            [settings]
+            escalate_deny = true
+        "#,
+        );
+        assert!(config.settings.escalate_deny);
+    }
+
+    #[test]
+    fn overlay_audit_mode() {
+        let mut config = Config::default_config();
+        assert_eq!(config.settings.mode, Mode::Enforce);
+        config.apply_overlay_str(
+            r#"
+            [settings]
+            mode = "audit"
+        "#,
+        );
+        assert_eq!(config.settings.mode, Mode::Audit);
+    }
+
+    #[test]
+    fn overlay_session_grants() {
+        let mut config = Config::default_config();
+        assert!(!config.settings.session_grants);
+        config.apply_overlay_str(
+            r#"
+            [settings]
+            session_grants = true
+        "#,
+        );
+        assert!(config.settings.session_grants);
+    }
+
+    #[test]
+    fn overlay_slow_warning_threshold() {
+        let mut config = Config::default_config();
+        assert_eq!(config.settings.slow_warning_ms, 50);
+        config.apply_overlay_str(
+            r#"
+            [settings]
+            slow_warning_ms = 0
+        "#,
+        );
+        assert_eq!(config.settings.slow_warning_ms, 0);
+    }
+
+    #[test]
+    fn overlay_redirection_lists() {
+        let mut config = Config::default_config();
+        assert!(
+            config
+                .redirection
+                .safe_sinks
+                .contains(&"/dev/stderr".to_string())
+        );
+        config.apply_overlay_str(
+            r#"
+            [redirection]
+            safe_sinks = ["/dev/tty"]
+            remove_safe_sinks = ["/dev/stdout"]
+            safe_targets = ["/tmp/**", "*.log"]
+        "#,
+        );
+        assert_eq!(config.redirection.safe_targets, ["/tmp/**", "*.log"]);
+        assert!(
+            config
+                .redirection
+                .safe_sinks
+                .contains(&"/dev/tty".to_string())
+        );
+        assert!(
+            !config
+                .redirection
+                .safe_sinks
+                .contains(&"/dev/stdout".to_string())
+        );
+    }
+
+    #[test]
+    fn overlay_apt_lists() {
+        let mut config = Config::default_config();
+        assert!(config.apt.read_only.contains(&"download".to_string()));
+        assert!(config.apt.mutating.contains(&"install".to_string()));
+        config.apply_overlay_str(
+            r#"
+            [apt]
+            read_only = ["changelog"]
+            mutating = ["source"]
+            remove_read_only = ["download"]
+        "#,
+        );
+        assert!(config.apt.read_only.contains(&"changelog".to_string()));
+        assert!(!config.apt.read_only.contains(&"download".to_string()));
+        assert!(config.apt.mutating.contains(&"source".to_string()));
+    }
+
+    #[test]
+    fn overlay_dnf() {
+        let mut config = Config::default_config();
+        assert_eq!(config.dnf.history_undo, Decision::Ask);
+        config.apply_overlay_str(
+            r#"
+            [dnf]
+            mutating = ["makecache"]
+            history_undo = "deny"
+        "#,
+        );
+        assert!(config.dnf.mutating.contains(&"makecache".to_string()));
+        assert_eq!(config.dnf.history_undo, Decision::Deny);
+    }
+
+    #[test]
+    fn overlay_firewall() {
+        let mut config = Config::default_config();
+        assert_eq!(config.firewall.rule_change, Decision::Ask);
+        assert_eq!(config.firewall.flush, Decision::Deny);
+        config.apply_overlay_str(
+            r#"
+            [firewall]
+            rule_change = "deny"
+        "#,
+        );
+        assert_eq!(config.firewall.rule_change, Decision::Deny);
+        assert_eq!(config.firewall.flush, Decision::Deny);
+    }
+
+    #[test]
+    fn overlay_aws() {
+        let mut config = Config::default_config();
+        assert_eq!(config.aws.s3_download, Decision::Allow);
+        assert_eq!(config.aws.s3_destructive, Decision::Deny);
+        assert_eq!(config.aws.identity, Decision::Deny);
+        config.apply_overlay_str(
+            r#"
+            [aws]
+            s3_download = "ask"
+        "#,
+        );
+        assert_eq!(config.aws.s3_download, Decision::Ask);
+        assert_eq!(config.aws.s3_upload, Decision::Ask);
+    }
+
+    #[test]
+    fn overlay_terraform() {
+        let mut config = Config::default_config();
+        assert_eq!(config.terraform.apply, Decision::Ask);
+        assert_eq!(config.terraform.state_destructive, Decision::Deny);
+        config.apply_overlay_str(
+            r#"
+            [terraform]
+            state_change = "deny"
+        "#,
+        );
+        assert_eq!(config.terraform.state_change, Decision::Deny);
+        assert_eq!(config.terraform.apply, Decision::Ask);
+    }
+
+    #[test]
+    fn overlay_docker() {
+        let mut config = Config::default_config();
+        assert_eq!(config.docker.exec, Decision::Ask);
+        assert_eq!(config.docker.interactive_shell, Decision::Deny);
+        config.apply_overlay_str(
+            r#"
+            [docker]
+            interactive_shell = "ask"
+            destructive = "ask"
+        "#,
+        );
+        assert_eq!(config.docker.interactive_shell, Decision::Ask);
+        assert_eq!(config.docker.destructive, Decision::Ask);
+        assert_eq!(config.docker.escalation, Decision::Deny);
+        assert_eq!(config.docker.image, Decision::Ask);
+    }
+
+    #[test]
+    fn overlay_ansible() {
+        let mut config = Config::default_config();
+        assert_eq!(config.ansible.run, Decision::Ask);
+        assert_eq!(config.ansible.privileged, Decision::Ask);
+        config.apply_overlay_str(
+            r#"
+            [ansible]
+            privileged = "deny"
+        "#,
+        );
+        assert_eq!(config.ansible.privileged, Decision::Deny);
+        assert_eq!(config.ansible.run, Decision::Ask);
+    }
+
+    #[test]
+    fn overlay_crypto() {
+        let mut config = Config::default_config();
+        assert_eq!(config.crypto.key_material, Decision::Ask);
+        assert_eq!(config.crypto.secret_export, Decision::Deny);
+        config.apply_overlay_str(
+            r#"
+            [crypto]
+            key_material = "allow"
+        "#,
+        );
+        assert_eq!(config.crypto.key_material, Decision::Allow);
+        assert_eq!(config.crypto.secret_export, Decision::Deny);
+    }
+
+    #[test]
+    fn overlay_ssh_keys() {
+        let mut config = Config::default_config();
+        assert_eq!(config.ssh_keys.change, Decision::Ask);
+        assert_eq!(config.ssh_keys.overwrite, Decision::Deny);
+        config.apply_overlay_str(
+            r#"
+            [ssh_keys]
+            overwrite = "ask"
+        "#,
+        );
+        assert_eq!(config.ssh_keys.overwrite, Decision::Ask);
+        assert_eq!(config.ssh_keys.change, Decision::Ask);
+    }
+
+    #[test]
+    fn overlay_vcs() {
+        let mut config = Config::default_config();
+        assert_eq!(config.vcs.change, Decision::Ask);
+        assert_eq!(config.vcs.destructive, Decision::Ask);
+        config.apply_overlay_str(
+            r#"
+            [vcs]
+            destructive = "deny"
+        "#,
+        );
+        assert_eq!(config.vcs.destructive, Decision::Deny);
+        assert_eq!(config.vcs.change, Decision::Ask);
+    }
+
+    #[test]
+    fn overlay_bazel() {
+        let mut config = Config::default_config();
+        assert!(config.bazel.read_only.contains(&"build".to_string()));
+        assert_eq!(config.bazel.run, Decision::Ask);
+        config.apply_overlay_str(
+            r#"
+            [bazel]
+            remove_read_only = ["test"]
+            run = "deny"
+        "#,
+        );
+        assert!(!config.bazel.read_only.contains(&"test".to_string()));
+        assert_eq!(config.bazel.run, Decision::Deny);
+    }
+
+    #[test]
+    fn overlay_gradle_and_maven() {
+        let mut config = Config::default_config();
+        assert!(config.gradle.read_only.contains(&"build".to_string()));
+        assert!(config.maven.read_only.contains(&"verify".to_string()));
+        config.apply_overlay_str(
+            r#"
+            [gradle]
+            read_only = ["lint"]
+            [maven]
+            publish = "deny"
+        "#,
+        );
+        assert!(config.gradle.read_only.contains(&"lint".to_string()));
+        assert_eq!(config.gradle.publish, Decision::Ask);
+        assert_eq!(config.maven.publish, Decision::Deny);
     }
 
     #[test]
-    fn overlay_replace_commands() {
+    fn overlay_wildcards() {
         let mut config = Config::default_config();
+        assert!(config.wildcards.commands.contains(&"rm".to_string()));
+        assert_eq!(config.wildcards.decision, Decision::Ask);
         config.apply_overlay_str(
             r#"
-            [commands]
-            replace = true
-            allow = ["ls", "cat"]
-            ask = ["rm"]
-            deny = ["shred"]
+            [wildcards]
+            commands = ["cp"]
+            remove_commands = ["mv"]
+            decision = "deny"
         "#,
         );
-        assert_eq!(config.commands.allow, vec!["ls", "cat"]);
-        assert_eq!(config.commands.ask, vec!["rm"]);
-        assert_eq!(config.commands.deny, vec!["shred"]);
+        assert!(config.wildcards.commands.contains(&"cp".to_string()));
+        assert!(!config.wildcards.commands.contains(&"mv".to_string()));
+        assert_eq!(config.wildcards.decision, Decision::Deny);
     }
 
     #[test]
-    fn overlay_git_env_gate() {
+    fn overlay_cmd_exe_lists() {
         let mut config = Config::default_config();
+        assert!(config.cmd_exe.ask.contains(&"del".to_string()));
         config.apply_overlay_str(
             r#"
-            [git]
-            allowed_with_config = ["commit", "add", "push"]
-            [git.config_env]
-            GIT_CONFIG_GLOBAL = "~/.gitconfig.ai"
+            [cmd_exe]
+            allow = ["del"]
+            deny = ["mshta"]
         "#,
         );
-        assert_eq!(
-            config.git.config_env.get("GIT_CONFIG_GLOBAL").unwrap(),
-            "~/.gitconfig.ai"
-        );
-        assert_eq!(
-            config.git.allowed_with_config,
-            vec!["commit", "add", "push"]
-        );
-        // Default read_only still present
-        assert!(config.git.read_only.contains(&"status".to_string()));
-        assert!(config.git.read_only.contains(&"log".to_string()));
+        assert!(config.cmd_exe.allow.contains(&"del".to_string()));
+        assert!(!config.cmd_exe.ask.contains(&"del".to_string()));
+        assert!(config.cmd_exe.deny.contains(&"mshta".to_string()));
     }
 
     #[test]
-    fn overlay_escalate_deny() {
+    fn overlay_shell() {
         let mut config = Config::default_config();
+        assert_eq!(config.settings.shell, Shell::Bash);
         config.apply_overlay_str(
             r#"
             [settings]
-            escalate_deny = true
+            shell = "zsh"
         "#,
         );
-        assert!(config.settings.escalate_deny);
+        assert_eq!(config.settings.shell, Shell::Zsh);
     }
 
     #[test]
@@ -1000,6 +4170,36 @@ mod tests {
         assert_eq!(config.git.read_only.len(), original.git.read_only.len());
     }
 
+    #[test]
+    fn diff_lists_added_removed_and_changed_values() {
+        let base = Config::default_config();
+        let config = Config::with_overlay(
+            r#"
+            [settings]
+            mode = "audit"
+
+            [commands]
+            remove_allow = ["cat"]
+            allow = ["helm"]
+
+            [guidance]
+            "terraform apply" = "use the pipeline"
+            "#,
+        )
+        .unwrap();
+        let changes: Vec<String> = diff(&base, &config).iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            [
+                r#"+ commands.allow[] = "helm""#,
+                r#"+ guidance.terraform apply = "use the pipeline""#,
+                r#"~ settings.mode = "enforce" -> "audit""#,
+                r#"- commands.allow[] = "cat""#,
+            ]
+        );
+        assert!(diff(&base, &Config::default_config()).is_empty());
+    }
+
     // ── Project overlay discovery ──
 
     /// Make a scratch dir under std::env::temp_dir() unique to this test run.
@@ -1026,6 +4226,7 @@ mod tests {
         std::fs::remove_dir_all(&root).ok();
     }
 
+    #[cfg(feature = "native")]
     #[test]
     fn project_overlay_file_parses_and_extends_allow() {
         let root = scratch_dir("project-overlay");
@@ -1041,7 +4242,8 @@ mod tests {
         .unwrap();
 
         let path = root.join(".claude/cc-toolgate.toml");
-        let overlay = load_overlay_from_path(&path, "test").expect("parses");
+        let text = std::fs::read_to_string(&path).unwrap();
+        let overlay = parse_overlay(&text, "test").expect("parses");
 
         let mut config = Config::default_config();
         config.apply_overlay(overlay);
@@ -1060,14 +4262,20 @@ mod tests {
     // These tests change the process CWD and HOME, so they require nextest
     // (which runs each test in its own process) for isolation safety.
 
-    /// Assert we are running under nextest (process-per-test isolation).
+    /// Assert we are running under nextest (process-per-test isolation), and
+    /// clear the env vars that would pick a config other than `$HOME`'s.
+    #[cfg(feature = "native")]
     fn require_nextest() {
         assert!(
             std::env::var("NEXTEST").is_ok(),
             "this test mutates process CWD/HOME and requires nextest (cargo nextest run)"
         );
+        for var in ["XDG_CONFIG_HOME", CONFIG_ENV, PROFILE_ENV] {
+            unsafe { std::env::remove_var(var) };
+        }
     }
 
+    #[cfg(feature = "native")]
     #[test]
     fn config_load_applies_project_overlay() {
         require_nextest();
@@ -1122,6 +4330,355 @@ mod tests {
         std::fs::remove_dir_all(&root).ok();
     }
 
+    #[cfg(feature = "native")]
+    #[test]
+    fn selected_profile_applies_over_user_overlay() {
+        let user = r#"
+            [commands]
+            allow = ["terraform"]
+
+            [profiles.strict.commands]
+            remove_allow = ["terraform"]
+            deny = ["terraform"]
+
+            [profiles.strict.settings]
+            escalate_deny = false
+
+            [profiles.permissive.settings]
+            mode = "audit"
+        "#;
+        let load = |profile: Option<&str>| {
+            Config::from_sources(&ConfigSources {
+                user: Some(user.into()),
+                profile: profile.map(Into::into),
+                ..Default::default()
+            })
+        };
+
+        let (base, clean) = load(None);
+        assert!(clean);
+        assert!(base.commands.allow.contains(&"terraform".to_string()));
+        assert_eq!(base.settings.mode, Mode::Enforce);
+
+        let (strict, _) = load(Some("strict"));
+        assert!(!strict.commands.allow.contains(&"terraform".to_string()));
+        assert!(strict.commands.deny.contains(&"terraform".to_string()));
+
+        let (permissive, _) = load(Some("permissive"));
+        assert_eq!(permissive.settings.mode, Mode::Audit);
+
+        // An unknown profile leaves the user config in place, uncached.
+        let (unknown, clean) = load(Some("nope"));
+        assert!(!clean);
+        assert!(unknown.commands.allow.contains(&"terraform".to_string()));
+    }
+
+    #[test]
+    fn platform_defaults() {
+        let has = |list: &[String], name: &str| list.iter().any(|c| c == name);
+        let linux = Config::default_for(Platform::Linux);
+        assert!(has(&linux.commands.ask, "systemctl"));
+        assert!(!has(&linux.commands.ask, "launchctl"));
+        let macos = Config::default_for(Platform::Macos);
+        assert!(has(&macos.commands.ask, "launchctl"));
+        assert!(has(&macos.commands.deny, "csrutil"));
+        assert!(!has(&macos.commands.ask, "systemctl"));
+        let generic = Config::default_for(Platform::Generic);
+        assert!(!has(&generic.commands.ask, "systemctl"));
+        assert!(!has(&generic.commands.ask, "launchctl"));
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn user_config_picks_the_platform() {
+        let has = |list: &[String], name: &str| list.iter().any(|c| c == name);
+        // Its lists still apply on top of that platform's.
+        let (config, clean) = Config::from_sources(&ConfigSources {
+            user: Some(
+                "[settings]\nplatform = \"macos\"\n\n[commands]\nremove_ask = [\"brew\"]\nallow = [\"brew\"]\n"
+                    .into(),
+            ),
+            ..Default::default()
+        });
+        assert!(clean);
+        assert_eq!(config.settings.platform, Platform::Macos);
+        assert!(has(&config.commands.allow, "brew"));
+        assert!(has(&config.commands.ask, "launchctl"));
+        assert!(!has(&config.commands.ask, "systemctl"));
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn dropins_apply_in_name_order_after_main_overlay() {
+        let dir = scratch_dir("config-d");
+        std::fs::write(
+            dir.join("20-docker.toml"),
+            "[commands]\nremove_allow = [\"docker\"]\ndeny = [\"docker\"]\n\n[profiles.strict.commands]\ndeny = [\"podman\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("10-k8s.toml"),
+            "[commands]\nallow = [\"docker\", \"helm\"]\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "[commands\n").unwrap();
+        std::fs::create_dir(dir.join("old.toml")).unwrap();
+
+        let dropins = read_dropins(&dir);
+        let names: Vec<_> = dropins
+            .iter()
+            .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["10-k8s.toml", "20-docker.toml"]);
+
+        let (config, clean) = Config::from_sources(&ConfigSources {
+            user: Some(
+                "[commands]\nallow = [\"kind\"]\n\n[profiles.strict.commands]\nremove_allow = [\"kind\"]\ndeny = [\"kind\"]\n"
+                    .into(),
+            ),
+            user_dropins: dropins,
+            profile: Some("strict".into()),
+            ..Default::default()
+        });
+        assert!(clean);
+        assert!(config.commands.allow.contains(&"helm".to_string()));
+        assert!(config.commands.deny.contains(&"docker".to_string()));
+        assert!(!config.commands.allow.contains(&"docker".to_string()));
+        // The profile's parts from both files apply.
+        assert!(config.commands.deny.contains(&"kind".to_string()));
+        assert!(config.commands.deny.contains(&"podman".to_string()));
+        assert!(read_dropins(&dir.join("missing")).is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn config_load_honors_xdg_config_home_and_explicit_file() {
+        require_nextest();
+
+        let root = scratch_dir("load-xdg");
+        let xdg = root.join("xdg");
+        std::fs::create_dir_all(xdg.join("cc-toolgate/config.d")).unwrap();
+        std::fs::write(
+            xdg.join("cc-toolgate/config.toml"),
+            "[commands]\nallow = [\"xdg-tool\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            xdg.join("cc-toolgate/config.d/extra.toml"),
+            "[commands]\nallow = [\"dropin-tool\"]\n",
+        )
+        .unwrap();
+        let explicit = root.join("ci.toml");
+        std::fs::write(&explicit, "[commands]\nallow = [\"ci-tool\"]\n").unwrap();
+        unsafe { std::env::set_var("HOME", root.join("fakehome")) };
+        unsafe { std::env::set_var("XDG_CONFIG_HOME", &xdg) };
+        unsafe { std::env::set_var(NO_CACHE_ENV, "1") };
+        let original_dir = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&root).unwrap();
+
+        let from_xdg = Config::load();
+        unsafe { std::env::set_var(CONFIG_ENV, &explicit) };
+        let from_env = Config::load();
+        let missing = Config::load_from(Some(&root.join("missing.toml")));
+
+        std::env::set_current_dir(&original_dir).unwrap();
+
+        let allows =
+            |config: &Config, tool: &str| config.commands.allow.contains(&tool.to_string());
+        assert!(allows(&from_xdg, "xdg-tool") && allows(&from_xdg, "dropin-tool"));
+        assert!(allows(&from_env, "ci-tool"));
+        assert!(!allows(&from_env, "xdg-tool") && !allows(&from_env, "dropin-tool"));
+        assert!(allows(&missing, "ls") && !allows(&missing, "xdg-tool"));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[cfg(feature = "remote")]
+    #[test]
+    fn verified_remote_policy_applies_after_user_overlay() {
+        use base64::Engine;
+        use base64::engine::general_purpose::STANDARD as BASE64;
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let key = SigningKey::from_bytes(&[9; 32]);
+        let policy = "[commands]\nremove_allow = [\"terraform\"]\ndeny = [\"terraform\"]\n\n[remote]\nconfig_url = \"https://elsewhere.example/p.toml\"\n";
+        let url = "https://policy.example/team.toml";
+        let user = format!(
+            "[commands]\nallow = [\"terraform\"]\n\n[remote]\nconfig_url = \"{url}\"\npublic_key = \"{}\"\n",
+            BASE64.encode(key.verifying_key().to_bytes())
+        );
+        let load = |policy_text: &str| {
+            Config::from_sources(&ConfigSources {
+                user: Some(user.clone()),
+                remote: Some(crate::remote::CachedPolicy {
+                    url: url.into(),
+                    policy: Some(policy_text.into()),
+                    signature: BASE64.encode(key.sign(policy.as_bytes()).to_bytes()),
+                    checked_at: 0,
+                }),
+                ..Default::default()
+            })
+        };
+
+        let (config, clean) = load(policy);
+        assert!(clean);
+        assert!(config.commands.deny.contains(&"terraform".to_string()));
+        assert_eq!(config.remote.config_url, url);
+
+        // A cached copy that no longer matches its signature is ignored.
+        let (config, clean) = load("[commands]\nallow = [\"shred\"]\n");
+        assert!(!clean);
+        assert!(config.commands.allow.contains(&"terraform".to_string()));
+        assert!(!config.commands.allow.contains(&"shred".to_string()));
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn provenance_names_the_source_of_each_value() {
+        let sources = ConfigSources {
+            user: Some(
+                "[settings]\nmode = \"audit\"\n\n[commands]\nremove_allow = [\"cat\"]\nallow = [\"helm\"]\n"
+                    .into(),
+            ),
+            user_dropins: vec![(
+                "/u/config.d/k8s.toml".into(),
+                "[commands]\nallow = [\"kubectx\"]\n\n[settings]\nmode = \"enforce\"\n".into(),
+            )],
+            ..Default::default()
+        };
+        let mut provenance = Provenance::default();
+        let (config, _) = Config::from_sources_traced(&sources, &mut |source, config| {
+            provenance.record(source, config)
+        });
+
+        assert_eq!(
+            provenance.source("commands.allow[]", "\"ls\""),
+            Some("default")
+        );
+        assert_eq!(
+            provenance.source("commands.allow[]", "\"helm\""),
+            Some("user config")
+        );
+        assert_eq!(
+            provenance.source("commands.allow[]", "\"kubectx\""),
+            Some("/u/config.d/k8s.toml")
+        );
+        assert_eq!(provenance.source("commands.allow[]", "\"cat\""), None);
+        // Set back to the default value, but by the drop-in.
+        assert_eq!(
+            provenance.source("settings.mode", "\"enforce\""),
+            Some("/u/config.d/k8s.toml")
+        );
+        assert_eq!(
+            provenance.source("settings.escalate_deny", "false"),
+            Some("default")
+        );
+
+        let annotated = provenance.annotate(&config);
+        assert!(annotated.contains("commands.allow[] = \"helm\"  # user config\n"));
+        assert!(annotated.contains("settings.mode = \"enforce\"  # /u/config.d/k8s.toml\n"));
+    }
+
+    #[cfg(feature = "native")]
+    fn cache_entries(home: &std::path::Path) -> Vec<std::path::PathBuf> {
+        std::fs::read_dir(home.join(".cache/cc-toolgate"))
+            .map(|d| d.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default()
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn cached_config_round_trips() {
+        let root = scratch_dir("cache-round-trip");
+        let path = root.join("config-x.json");
+        let mut config = Config::default_config();
+        config.project_overlay_path = Some("/p/.claude/cc-toolgate.toml".into());
+        write_cached(&path, &config);
+        let cached = read_cached(&path).expect("cache entry readable");
+        assert_eq!(
+            serde_json::to_value(&cached).unwrap(),
+            serde_json::to_value(&config).unwrap()
+        );
+        assert_eq!(cached.project_overlay_path, config.project_overlay_path);
+        std::fs::write(&path, "{truncated").unwrap();
+        assert!(read_cached(&path).is_none());
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn config_load_uses_and_rebuilds_cache() {
+        require_nextest();
+
+        let root = scratch_dir("load-cache");
+        std::fs::create_dir(root.join(".git")).unwrap();
+        let fake_home = root.join("fakehome");
+        std::fs::create_dir_all(fake_home.join(".config/cc-toolgate")).unwrap();
+        let user = fake_home.join(".config/cc-toolgate/config.toml");
+        std::fs::write(&user, "[commands]\nallow = [\"first-tool\"]\n").unwrap();
+        unsafe { std::env::set_var("HOME", &fake_home) };
+        unsafe { std::env::remove_var(NO_CACHE_ENV) };
+        let original_dir = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&root).unwrap();
+
+        let first = Config::load();
+        let entries = cache_entries(&fake_home);
+        assert_eq!(entries.len(), 1, "first load writes one cache entry");
+
+        // A second load with unchanged inputs is served from the cache.
+        let mut cached: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&entries[0]).unwrap()).unwrap();
+        cached["config"]["commands"]["allow"] = serde_json::json!(["from-cache"]);
+        std::fs::write(&entries[0], cached.to_string()).unwrap();
+        assert_eq!(Config::load().commands.allow, ["from-cache"]);
+
+        // Editing the overlay changes the key, so the config is rebuilt.
+        std::fs::write(&user, "[commands]\nallow = [\"second-tool\"]\n").unwrap();
+        let rebuilt = Config::load();
+        std::env::set_current_dir(&original_dir).unwrap();
+
+        assert!(first.commands.allow.contains(&"first-tool".to_string()));
+        assert!(rebuilt.commands.allow.contains(&"second-tool".to_string()));
+        assert!(!rebuilt.commands.allow.contains(&"first-tool".to_string()));
+        assert_eq!(cache_entries(&fake_home).len(), 2);
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn config_load_does_not_cache_stripped_or_broken_overlays() {
+        require_nextest();
+
+        let root = scratch_dir("load-no-cache");
+        std::fs::create_dir(root.join(".git")).unwrap();
+        std::fs::create_dir(root.join(".claude")).unwrap();
+        std::fs::write(
+            root.join(".claude/cc-toolgate.toml"),
+            "[commands]\nremove_deny = [\"shred\"]\n",
+        )
+        .unwrap();
+        let fake_home = root.join("fakehome");
+        std::fs::create_dir_all(&fake_home).unwrap();
+        unsafe { std::env::set_var("HOME", &fake_home) };
+        unsafe { std::env::remove_var(NO_CACHE_ENV) };
+        let original_dir = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&root).unwrap();
+
+        let stripped = Config::load();
+        assert!(cache_entries(&fake_home).is_empty());
+        std::fs::write(root.join(".claude/cc-toolgate.toml"), "[commands\n").unwrap();
+        Config::load();
+        assert!(cache_entries(&fake_home).is_empty());
+
+        std::env::set_current_dir(&original_dir).unwrap();
+        assert!(stripped.commands.deny.contains(&"shred".to_string()));
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[cfg(feature = "native")]
     #[test]
     fn config_load_project_overlay_replace_is_stripped() {
         require_nextest();
@@ -1183,6 +4740,7 @@ mod tests {
         std::fs::remove_dir_all(&root).ok();
     }
 
+    #[cfg(feature = "native")]
     #[test]
     fn config_load_project_overlay_remove_deny_is_stripped() {
         require_nextest();
@@ -1220,6 +4778,54 @@ mod tests {
         std::fs::remove_dir_all(&root).ok();
     }
 
+    #[cfg(feature = "native")]
+    #[test]
+    fn config_load_imports_claude_permissions() {
+        require_nextest();
+
+        let root = scratch_dir("load-claude-permissions");
+        std::fs::create_dir(root.join(".git")).unwrap();
+        std::fs::create_dir(root.join(".claude")).unwrap();
+        // Project settings may only add deny patterns.
+        std::fs::write(
+            root.join(".claude/settings.local.json"),
+            r#"{"permissions": {"allow": ["Bash(rm:*)"], "deny": ["Bash(make deploy:*)"]}}"#,
+        )
+        .unwrap();
+        let fake_home = root.join("fakehome");
+        std::fs::create_dir_all(fake_home.join(".claude")).unwrap();
+        std::fs::create_dir_all(fake_home.join(".config/cc-toolgate")).unwrap();
+        std::fs::write(
+            fake_home.join(".claude/settings.json"),
+            r#"{"permissions": {"allow": ["Bash(cargo build:*)"]}}"#,
+        )
+        .unwrap();
+        let user = fake_home.join(".config/cc-toolgate/config.toml");
+        unsafe { std::env::set_var("HOME", &fake_home) };
+        let original_dir = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&root).unwrap();
+
+        let disabled = Config::load();
+        std::fs::write(&user, "[settings]\nimport_claude_permissions = true\n").unwrap();
+        let enabled = Config::load();
+
+        std::env::set_current_dir(&original_dir).unwrap();
+
+        assert!(disabled.rules.is_empty());
+        let imported: Vec<_> = enabled
+            .rules
+            .iter()
+            .map(|r| (r.command.as_str(), r.decision))
+            .collect();
+        assert_eq!(
+            imported,
+            [("cargo", Decision::Allow), ("make", Decision::Deny)]
+        );
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[cfg(feature = "native")]
     #[test]
     fn strip_project_overlay_dangerous_fields_clears_all_sections() {
         let path = std::path::PathBuf::from("/fake/path/.claude/cc-toolgate.toml");
@@ -1236,11 +4842,18 @@ mod tests {
                 replace: true,
                 remove_allow_floor: vec!["xargs".into()],
                 remove_ask_floor: vec!["sudo".into()],
+                interactive_shell: Some(Decision::Allow),
+                app_launch: Some(Decision::Allow),
                 ..Default::default()
             },
             git: GitOverlay {
                 replace: true,
                 remove_read_only: vec!["status".into()],
+                remove_mutating: vec!["remote add".into()],
+                remove_sensitive_config_keys: vec!["credential.*".into()],
+                sensitive_config: Some(Decision::Allow),
+                global_config: Some(Decision::Allow),
+                local_config: Some(Decision::Allow),
                 remove_allowed_with_config: vec!["push".into()],
                 remove_force_push_flags: vec!["--force".into()],
                 read_only: vec!["log".into()],
@@ -1257,6 +4870,7 @@ mod tests {
                 remove_read_only: vec!["get".into()],
                 remove_mutating: vec!["apply".into()],
                 remove_allowed_with_config: vec!["exec".into()],
+                remove_sensitive_resources: vec!["secrets".into()],
                 ..Default::default()
             },
             gh: GhOverlay {
@@ -1264,14 +4878,257 @@ mod tests {
                 remove_read_only: vec!["pr list".into()],
                 remove_mutating: vec!["pr merge".into()],
                 remove_allowed_with_config: vec!["pr create".into()],
+                extensions: vec!["dash".into()],
+                extension: Some(Decision::Allow),
                 ..Default::default()
             },
-            ..Default::default()
+            hub: GhOverlay {
+                remove_read_only: vec!["pr list".into()],
+                ..Default::default()
+            },
+            tea: GhOverlay {
+                replace: true,
+                remove_mutating: vec!["pulls merge".into()],
+                ..Default::default()
+            },
+            fj: GhOverlay {
+                remove_allowed_with_config: vec!["pr create".into()],
+                ..Default::default()
+            },
+            paths: PathsOverlay {
+                replace: true,
+                remove_sensitive: vec!["~/.ssh".into()],
+                remove_protected: vec!["/etc".into()],
+                remove_sandbox: vec!["/tmp".into()],
+                sandbox: vec!["/".into()],
+                project_sandbox: Some(false),
+                sensitive: vec!["~/.vault-token".into()],
+                protected: vec!["/srv".into()],
+            },
+            scp: ScpOverlay {
+                upload: Some(Decision::Allow),
+                download: Some(Decision::Allow),
+                sensitive: Some(Decision::Allow),
+            },
+            rsync: RsyncOverlay {
+                replace: true,
+                remove_destructive_flags: vec!["--delete".into()],
+                destructive: Some(Decision::Allow),
+                remote_destination: Some(Decision::Allow),
+                allow_local_dry_run: Some(true),
+                ..Default::default()
+            },
+            network: NetworkOverlay {
+                replace: true,
+                allowed_domains: vec!["attacker.example".into()],
+                denied_domains: vec!["leak.example".into()],
+                remove_allowed_domains: vec!["github.com".into()],
+                remove_denied_domains: vec!["pastebin.com".into()],
+                file_upload: Some(Decision::Allow),
+                internal_hosts: vec!["metadata.corp.example".into()],
+                remove_internal_hosts: vec!["169.254.169.254".into()],
+                internal_network: Some(Decision::Allow),
+            },
+            raw_network: RawNetworkOverlay {
+                client: Some(Decision::Allow),
+                listen: Some(Decision::Allow),
+                exec: Some(Decision::Allow),
+            },
+            firewall: FirewallOverlay {
+                rule_change: Some(Decision::Allow),
+                flush: Some(Decision::Allow),
+            },
+            tee: TeeOverlay {
+                privileged: Some(Decision::Allow),
+            },
+            rm: RmOverlay {
+                catastrophic: Some(Decision::Ask),
+                min_depth: Some(0),
+            },
+            permissions: PermissionsOverlay {
+                system_root: Some(Decision::Allow),
+                min_depth: Some(0),
+            },
+            accounts: AccountsOverlay {
+                change: Some(Decision::Allow),
+            },
+            aws: AwsOverlay {
+                s3_download: Some(Decision::Allow),
+                s3_upload: Some(Decision::Allow),
+                s3_presign: Some(Decision::Allow),
+                s3_destructive: Some(Decision::Allow),
+                identity: Some(Decision::Allow),
+            },
+            terraform: TerraformOverlay {
+                apply: Some(Decision::Allow),
+                state_change: Some(Decision::Allow),
+                state_destructive: Some(Decision::Allow),
+            },
+            docker: DockerOverlay {
+                run: Some(Decision::Allow),
+                escalation: Some(Decision::Allow),
+                exec: Some(Decision::Allow),
+                interactive_shell: Some(Decision::Allow),
+                image: Some(Decision::Allow),
+                lifecycle: Some(Decision::Allow),
+                destructive: Some(Decision::Allow),
+            },
+            ansible: AnsibleOverlay {
+                run: Some(Decision::Allow),
+                privileged: Some(Decision::Allow),
+            },
+            crypto: CryptoOverlay {
+                key_material: Some(Decision::Allow),
+                secret_export: Some(Decision::Allow),
+            },
+            ssh_keys: SshKeysOverlay {
+                change: Some(Decision::Allow),
+                overwrite: Some(Decision::Allow),
+            },
+            vcs: VcsOverlay {
+                change: Some(Decision::Allow),
+                destructive: Some(Decision::Allow),
+            },
+            bazel: BazelOverlay {
+                replace: true,
+                read_only: vec!["run".into()],
+                run: Some(Decision::Allow),
+                ..Default::default()
+            },
+            gradle: JvmOverlay {
+                replace: true,
+                read_only: vec!["publish".into()],
+                publish: Some(Decision::Allow),
+                ..Default::default()
+            },
+            maven: JvmOverlay {
+                read_only: vec!["deploy".into()],
+                ..Default::default()
+            },
+            groups: GroupsOverlay {
+                subshell: Some(Decision::Allow),
+                brace_group: Some(Decision::Allow),
+            },
+            cmd_exe: CmdExeOverlay {
+                replace: true,
+                remove_ask: vec!["del".into()],
+                remove_deny: vec!["format".into()],
+                ..Default::default()
+            },
+            redirection: RedirectionOverlay {
+                replace: true,
+                safe_sinks: vec!["/etc/**".into()],
+                safe_targets: vec!["**".into()],
+                ..Default::default()
+            },
+            apt: AptOverlay {
+                replace: true,
+                read_only: vec!["install".into()],
+                remove_mutating: vec!["purge".into()],
+                ..Default::default()
+            },
+            dnf: DnfOverlay {
+                replace: true,
+                read_only: vec!["install".into()],
+                remove_mutating: vec!["swap".into()],
+                history_undo: Some(Decision::Allow),
+                ..Default::default()
+            },
+            wildcards: WildcardsOverlay {
+                replace: true,
+                remove_commands: vec!["rm".into()],
+                decision: Some(Decision::Allow),
+                ..Default::default()
+            },
+            inline_code: InlineCodeOverlay {
+                languages: HashMap::from([(
+                    "python".into(),
+                    InlineLanguageOverlay {
+                        replace: true,
+                        value_flags: vec!["-c".into()],
+                        program_positional: Some(false),
+                        remove_commands: vec!["python".into()],
+                        remove_code_flags: vec!["-c".into()],
+                        remove_value_flags: vec!["-W".into()],
+                        remove_deny: vec!["pty.spawn".into()],
+                        remove_ask: vec!["subprocess".into()],
+                        deny: vec!["ctypes".into()],
+                        ..Default::default()
+                    },
+                )]),
+            },
+            guidance: GuidanceOverlay {
+                rules: HashMap::from([
+                    ("terraform apply".into(), String::new()),
+                    ("helm install".into(), "use the deploy pipeline".into()),
+                ]),
+            },
+            settings: SettingsOverlay {
+                mode: Some(Mode::Audit),
+                session_grants: Some(true),
+                import_claude_permissions: Some(true),
+                platform: Some(Platform::Generic),
+                ..Default::default()
+            },
+            approval_tokens: ApprovalTokensOverlay {
+                key_file: Some("/repo/key".into()),
+            },
+            plugins: PluginsOverlay {
+                dir: Some("/repo/plugins".into()),
+            },
+            hooks: HooksOverlay {
+                external_evaluator: Some("/repo/policy.sh".into()),
+                commands: vec!["git".into()],
+                fallback: Some(true),
+                ..Default::default()
+            },
+            remote: RemoteOverlay {
+                config_url: Some("https://attacker.example/policy.toml".into()),
+                public_key: Some("a2V5".into()),
+                ..Default::default()
+            },
+            profiles: HashMap::from([("lax".into(), ConfigOverlay::default())]),
+            trust: HashMap::from([("/repo/**".into(), "high".into())]),
+            schedules: HashMap::from([("working_hours".into(), ScheduleConfig::default())]),
+            rules: vec![
+                RuleConfig {
+                    command: "git".into(),
+                    condition: "true".into(),
+                    decision: Decision::Allow,
+                    reason: String::new(),
+                    schedule: String::new(),
+                    require_env: Vec::new(),
+                },
+                RuleConfig {
+                    command: "make".into(),
+                    condition: r#""deploy" in args"#.into(),
+                    decision: Decision::Deny,
+                    reason: "use the pipeline".into(),
+                    schedule: String::new(),
+                    require_env: Vec::new(),
+                },
+            ],
         };
 
         strip_project_overlay_dangerous_fields(&mut overlay, &path);
 
         // All dangerous fields cleared.
+        assert!(overlay.settings.mode.is_none());
+        assert!(overlay.settings.session_grants.is_none());
+        assert!(overlay.settings.import_claude_permissions.is_none());
+        assert!(overlay.settings.platform.is_none());
+        assert!(overlay.approval_tokens.key_file.is_none());
+        assert!(overlay.plugins.dir.is_none());
+        assert!(overlay.hooks.external_evaluator.is_none());
+        assert!(overlay.hooks.commands.is_empty());
+        assert!(overlay.hooks.fallback.is_none());
+        assert!(overlay.profiles.is_empty());
+        assert!(overlay.remote.config_url.is_none());
+        assert!(overlay.trust.is_empty());
+        assert!(overlay.schedules.is_empty());
+        // Only DENY rules survive.
+        assert_eq!(overlay.rules.len(), 1);
+        assert_eq!(overlay.rules[0].command, "make");
         assert!(!overlay.commands.replace);
         assert!(overlay.commands.remove_allow.is_empty());
         assert!(overlay.commands.remove_ask.is_empty());
@@ -1280,9 +5137,16 @@ mod tests {
         assert!(!overlay.wrappers.replace);
         assert!(overlay.wrappers.remove_allow_floor.is_empty());
         assert!(overlay.wrappers.remove_ask_floor.is_empty());
+        assert!(overlay.wrappers.interactive_shell.is_none());
+        assert!(overlay.wrappers.app_launch.is_none());
 
         assert!(!overlay.git.replace);
         assert!(overlay.git.remove_read_only.is_empty());
+        assert!(overlay.git.remove_mutating.is_empty());
+        assert!(overlay.git.remove_sensitive_config_keys.is_empty());
+        assert!(overlay.git.sensitive_config.is_none());
+        assert!(overlay.git.global_config.is_none());
+        assert!(overlay.git.local_config.is_none());
         assert!(overlay.git.remove_allowed_with_config.is_empty());
         assert!(overlay.git.remove_force_push_flags.is_empty());
 
@@ -1294,17 +5158,135 @@ mod tests {
         assert!(overlay.kubectl.remove_read_only.is_empty());
         assert!(overlay.kubectl.remove_mutating.is_empty());
         assert!(overlay.kubectl.remove_allowed_with_config.is_empty());
+        assert!(overlay.kubectl.remove_sensitive_resources.is_empty());
 
         assert!(!overlay.gh.replace);
         assert!(overlay.gh.remove_read_only.is_empty());
         assert!(overlay.gh.remove_mutating.is_empty());
         assert!(overlay.gh.remove_allowed_with_config.is_empty());
+        assert!(overlay.gh.extensions.is_empty());
+        assert!(overlay.gh.extension.is_none());
+        assert!(overlay.hub.remove_read_only.is_empty());
+        assert!(!overlay.tea.replace);
+        assert!(overlay.tea.remove_mutating.is_empty());
+        assert!(overlay.fj.remove_allowed_with_config.is_empty());
+
+        assert!(!overlay.paths.replace);
+        assert!(overlay.paths.remove_sensitive.is_empty());
+        assert!(overlay.paths.remove_protected.is_empty());
+        assert!(overlay.paths.remove_sandbox.is_empty());
+        assert!(overlay.paths.sandbox.is_empty());
+        assert!(overlay.paths.project_sandbox.is_none());
+
+        assert!(overlay.scp.upload.is_none());
+        assert!(overlay.scp.download.is_none());
+        assert!(overlay.scp.sensitive.is_none());
+
+        assert!(!overlay.rsync.replace);
+        assert!(overlay.rsync.remove_destructive_flags.is_empty());
+        assert!(overlay.rsync.destructive.is_none());
+        assert!(overlay.rsync.remote_destination.is_none());
+        assert!(overlay.rsync.allow_local_dry_run.is_none());
+
+        assert!(!overlay.network.replace);
+        assert!(overlay.network.allowed_domains.is_empty());
+        assert!(overlay.network.remove_allowed_domains.is_empty());
+        assert!(overlay.network.remove_denied_domains.is_empty());
+        assert!(overlay.network.file_upload.is_none());
+        assert!(overlay.network.remove_internal_hosts.is_empty());
+        assert!(overlay.network.internal_network.is_none());
+
+        assert!(overlay.raw_network.client.is_none());
+        assert!(overlay.raw_network.listen.is_none());
+        assert!(overlay.raw_network.exec.is_none());
+        assert!(overlay.firewall.rule_change.is_none());
+        assert!(overlay.firewall.flush.is_none());
+
+        assert!(overlay.tee.privileged.is_none());
+        assert!(overlay.rm.catastrophic.is_none());
+        assert!(overlay.rm.min_depth.is_none());
+        assert!(overlay.permissions.system_root.is_none());
+        assert!(overlay.permissions.min_depth.is_none());
+        assert!(overlay.accounts.change.is_none());
+        assert!(overlay.aws.s3_download.is_none());
+        assert!(overlay.aws.s3_upload.is_none());
+        assert!(overlay.aws.s3_presign.is_none());
+        assert!(overlay.aws.s3_destructive.is_none());
+        assert!(overlay.aws.identity.is_none());
+        assert!(overlay.terraform.apply.is_none());
+        assert!(overlay.terraform.state_change.is_none());
+        assert!(overlay.terraform.state_destructive.is_none());
+        assert!(overlay.docker.run.is_none());
+        assert!(overlay.docker.escalation.is_none());
+        assert!(overlay.docker.exec.is_none());
+        assert!(overlay.docker.interactive_shell.is_none());
+        assert!(overlay.docker.image.is_none());
+        assert!(overlay.docker.lifecycle.is_none());
+        assert!(overlay.docker.destructive.is_none());
+        assert!(overlay.ansible.run.is_none());
+        assert!(overlay.ansible.privileged.is_none());
+        assert!(overlay.crypto.key_material.is_none());
+        assert!(overlay.crypto.secret_export.is_none());
+        assert!(overlay.ssh_keys.change.is_none());
+        assert!(overlay.ssh_keys.overwrite.is_none());
+        assert!(overlay.vcs.change.is_none());
+        assert!(overlay.vcs.destructive.is_none());
+        assert!(!overlay.bazel.replace);
+        assert!(overlay.bazel.read_only.is_empty());
+        assert!(overlay.bazel.run.is_none());
+        assert!(!overlay.gradle.replace);
+        assert!(overlay.gradle.read_only.is_empty());
+        assert!(overlay.gradle.publish.is_none());
+        assert!(overlay.maven.read_only.is_empty());
+        assert!(overlay.groups.subshell.is_none());
+        assert!(overlay.groups.brace_group.is_none());
+        assert!(!overlay.cmd_exe.replace);
+        assert!(overlay.cmd_exe.remove_ask.is_empty());
+        assert!(overlay.cmd_exe.remove_deny.is_empty());
+        assert!(!overlay.redirection.replace);
+        assert!(overlay.redirection.safe_sinks.is_empty());
+        assert!(overlay.redirection.safe_targets.is_empty());
+        assert!(!overlay.apt.replace);
+        assert!(overlay.apt.read_only.is_empty());
+        assert!(overlay.apt.remove_mutating.is_empty());
+        assert!(!overlay.dnf.replace);
+        assert!(overlay.dnf.read_only.is_empty());
+        assert!(overlay.dnf.remove_mutating.is_empty());
+        assert!(overlay.dnf.history_undo.is_none());
+        assert!(!overlay.wildcards.replace);
+        assert!(overlay.wildcards.remove_commands.is_empty());
+        assert!(overlay.wildcards.decision.is_none());
+
+        let python = &overlay.inline_code.languages["python"];
+        assert!(!python.replace);
+        assert!(python.value_flags.is_empty());
+        assert!(python.program_positional.is_none());
+        assert!(python.remove_commands.is_empty());
+        assert!(python.remove_code_flags.is_empty());
+        assert!(python.remove_value_flags.is_empty());
+        assert!(python.remove_deny.is_empty());
+        assert!(python.remove_ask.is_empty());
+        assert_eq!(python.deny, vec!["ctypes"]);
+
+        assert!(!overlay.guidance.rules.contains_key("terraform apply"));
+        assert_eq!(
+            overlay.guidance.rules["helm install"],
+            "use the deploy pipeline"
+        );
 
         // Additive fields are preserved.
         assert_eq!(overlay.commands.allow, vec!["my-tool"]);
         assert_eq!(overlay.git.read_only, vec!["log"]);
+        assert_eq!(overlay.paths.sensitive, vec!["~/.vault-token"]);
+        assert_eq!(overlay.paths.protected, vec!["/srv"]);
+        assert_eq!(overlay.network.denied_domains, vec!["leak.example"]);
+        assert_eq!(
+            overlay.network.internal_hosts,
+            vec!["metadata.corp.example"]
+        );
     }
 
+    #[cfg(feature = "native")]
     #[test]
     fn strip_project_overlay_no_op_when_safe() {
         let path = std::path::PathBuf::from("/fake/path/.claude/cc-toolgate.toml");
@@ -1322,6 +5304,7 @@ mod tests {
         assert!(!overlay.commands.replace);
     }
 
+    #[cfg(feature = "native")]
     #[test]
     fn config_load_no_project_overlay_path_when_absent() {
         require_nextest();
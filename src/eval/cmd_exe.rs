@@ -0,0 +1,209 @@
+//! `cmd /c "..."` command lines, split by cmd.exe's rules.
+//!
+//! The bash grammar reads cmd.exe syntax wrongly: `^` is cmd's escape,
+//! backslashes are path separators, and `&` separates commands rather than
+//! backgrounding one. So the command line passed to `cmd /c` is split here on
+//! `&`, `&&`, `||` and `|` (outside double quotes, not `^`-escaped), and each
+//! command is decided by `[cmd_exe]`.
+
+use std::collections::HashSet;
+
+use crate::config::CmdExeConfig;
+use crate::eval::Decision;
+
+/// The command line `cmd /c` runs, if `base` is cmd.exe and `args` carry
+/// `/c` or `/k` (`//c` from Git Bash, which would rewrite `/c` as a path).
+pub(crate) fn command_line(base: &str, args: &[&str]) -> Option<String> {
+    let name = base.rsplit(['/', '\\']).next().unwrap_or(base);
+    if !name.eq_ignore_ascii_case("cmd") && !name.eq_ignore_ascii_case("cmd.exe") {
+        return None;
+    }
+    // Switches like /d, /q and /v:on come before /c.
+    let start = args.iter().position(|arg| {
+        let switch = arg.strip_prefix('/').unwrap_or(arg);
+        ["/c", "/k"]
+            .iter()
+            .any(|c| switch.eq_ignore_ascii_case(c) || arg.eq_ignore_ascii_case(c))
+    })?;
+    let line = args[start + 1..].join(" ");
+    (!line.trim().is_empty()).then_some(line)
+}
+
+/// One command of a cmd.exe command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Piece {
+    /// The command as written.
+    pub(crate) text: String,
+    /// The command name, lowercase, without `@` or `.exe`.
+    pub(crate) name: String,
+    /// Output redirection to a file (`> out.txt`), if any. `nul` and
+    /// `2>&1` don't count.
+    pub(crate) redirect: Option<String>,
+}
+
+impl Piece {
+    fn new(text: &str) -> Self {
+        let text = text.trim();
+        let first = words(text).next().unwrap_or_default();
+        let name = first.trim_start_matches('@').to_ascii_lowercase();
+        let name = name.strip_suffix(".exe").unwrap_or(&name).to_string();
+        Self {
+            text: text.to_string(),
+            name,
+            redirect: output_redirect(text),
+        }
+    }
+
+    /// Whether the command reads the same as bash: no `%VAR%`, `!VAR!`, `^`
+    /// escapes, backslash paths, or characters bash would expand.
+    pub(crate) fn is_portable(&self) -> bool {
+        !self
+            .text
+            .contains(['%', '!', '^', '\\', '\'', '$', '`', '(', ')', '*', '?'])
+    }
+}
+
+/// Split a cmd.exe command line into its commands.
+pub(crate) fn split(line: &str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '^' if !quoted => {
+                current.push(c);
+                current.extend(chars.next());
+            }
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            // `2>&1` and `<&` duplicate a handle.
+            '&' if !quoted && current.ends_with(['>', '<']) => current.push(c),
+            '&' | '|' if !quoted => {
+                if chars.peek() == Some(&c) {
+                    chars.next();
+                }
+                pieces.push(std::mem::take(&mut current));
+            }
+            _ => current.push(c),
+        }
+    }
+    pieces.push(current);
+    pieces
+        .iter()
+        .filter(|text| !text.trim().is_empty())
+        .map(|text| Piece::new(text))
+        .collect()
+}
+
+/// Whitespace-separated words, keeping double-quoted spaces.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    let mut quoted = false;
+    text.split(move |c: char| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        c.is_whitespace() && !quoted
+    })
+    .filter(|w| !w.is_empty())
+}
+
+/// The first output redirection to a file in `text`, as `> target`.
+fn output_redirect(text: &str) -> Option<String> {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '^' if !quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '>' if !quoted => {
+                let rest = text[i..].trim_start_matches('>');
+                if rest.starts_with('&') {
+                    continue;
+                }
+                let target = words(rest).next().unwrap_or_default();
+                if !target.trim_matches('"').eq_ignore_ascii_case("nul") {
+                    return Some(format!("> {target}"));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The `[cmd_exe]` lists, lowercased for lookup.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CmdExePolicy {
+    allow: HashSet<String>,
+    ask: HashSet<String>,
+    deny: HashSet<String>,
+}
+
+impl CmdExePolicy {
+    pub(crate) fn from_config(config: &CmdExeConfig) -> Self {
+        let set = |names: &[String]| names.iter().map(|n| n.to_ascii_lowercase()).collect();
+        Self {
+            allow: set(&config.allow),
+            ask: set(&config.ask),
+            deny: set(&config.deny),
+        }
+    }
+
+    /// The listed decision for a command name, strictest list first.
+    pub(crate) fn decision(&self, name: &str) -> Option<Decision> {
+        if self.deny.contains(name) {
+            Some(Decision::Deny)
+        } else if self.ask.contains(name) {
+            Some(Decision::Ask)
+        } else if self.allow.contains(name) {
+            Some(Decision::Allow)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_command_line() {
+        let line = |base, args: &[&str]| command_line(base, args);
+        assert_eq!(line("cmd", &["/c", "dir", "/b"]), Some("dir /b".into()));
+        assert_eq!(
+            line("CMD.EXE", &["/d", "/C", "del x"]),
+            Some("del x".into())
+        );
+        assert_eq!(line("cmd", &["//c", "ver"]), Some("ver".into()));
+        assert_eq!(line("cmd", &["/k", "echo hi"]), Some("echo hi".into()));
+        assert_eq!(line("cmd", &["/c"]), None);
+        assert_eq!(line("cmd", &["dir"]), None);
+        assert_eq!(line("bash", &["/c", "dir"]), None);
+    }
+
+    #[test]
+    fn splits_on_cmd_operators() {
+        let names = |line| -> Vec<String> { split(line).into_iter().map(|p| p.name).collect() };
+        assert_eq!(
+            names("del /s /q build & rd /s /q out && @echo done || DIR.EXE | more"),
+            ["del", "rd", "echo", "dir", "more"]
+        );
+        assert_eq!(names(r#"echo "a & b" ^& c"#), ["echo"]);
+        assert_eq!(names("dir 2>&1 | findstr x"), ["dir", "findstr"]);
+    }
+
+    #[test]
+    fn finds_output_redirection() {
+        let redirect = |line| split(line).remove(0).redirect;
+        assert_eq!(redirect("dir > out.txt"), Some("> out.txt".into()));
+        assert_eq!(redirect("dir >>log.txt"), Some("> log.txt".into()));
+        assert_eq!(redirect("dir > nul 2>&1"), None);
+        assert_eq!(redirect(r#"echo "a > b""#), None);
+        assert_eq!(redirect("echo a ^> b"), None);
+    }
+}
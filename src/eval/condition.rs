@@ -0,0 +1,654 @@
+//! Condition expressions for `[[rules]]`.
+//!
+//! A small expression language of its own, evaluated against a
+//! [`CommandContext`]:
+//!
+//! ```text
+//! args.contains("-auto-approve") && env["TF_WORKSPACE"] == "prod"
+//! "--force" in args || redirection.starts_with("/etc/")
+//! args.len() > 2 && !cwd.ends_with("/scratch")
+//! ```
+//!
+//! Variables:
+//!
+//! - `command`: the base command name.
+//! - `args`: the words after the command name.
+//! - `words`: every word, leading assignments included.
+//! - `env`: leading `KEY=value` assignments and variables exported by earlier
+//!   segments of a compound command.
+//! - `process_env`: the hook process's own environment, e.g. whether it runs
+//!   in CI. Unlike `env`, nothing in the command can change it.
+//! - `redirection`: the output redirection target, `""` if none. A file
+//!   target is resolved to an absolute path against `cwd`.
+//! - `privileged`: whether the command runs under `sudo`, `doas`, ...
+//! - `cwd`: the directory the command runs in (the hook input's `cwd`).
+//! - `trust`: the `[trust]` tier of `cwd`, `""` if no entry matches.
+//!
+//! Literals are double-quoted strings (escapes `\"`, `\\`, `\n`, `\t`),
+//! non-negative integers, and `true`/`false`; parentheses group.
+//! Operators, loosest first: `||`, `&&`, comparisons (`==`, `!=`, `<`, `<=`,
+//! `>`, `>=`) and `in`, `!`, then indexing (`args[0]`, `env["HOME"]`) and
+//! method calls. Indexing past the end or a missing key gives `()`.
+//! Methods: `contains`, `starts_with`, `ends_with`, `len`, `is_empty`.
+//! Values of different types are never equal; other type mismatches are
+//! errors.
+
+use crate::eval::CommandContext;
+use std::collections::HashMap;
+use std::fmt;
+
+/// What a condition is evaluated against.
+#[derive(Debug, Clone, Copy)]
+pub struct Scope<'a> {
+    /// The command segment.
+    pub ctx: &'a CommandContext,
+    /// The trust tier of the working directory.
+    pub trust: &'a str,
+}
+
+/// A parsed condition.
+#[derive(Debug, Clone)]
+pub struct Condition {
+    expr: Expr,
+}
+
+impl Condition {
+    /// Parse `source`. Unknown variables and methods are rejected here.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let tokens = lex(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(Self { expr }),
+            Some(token) => Err(format!("unexpected {token}")),
+        }
+    }
+
+    /// Evaluate against `ctx`, outside any trust tier. The result must be a
+    /// boolean.
+    pub fn evaluate(&self, ctx: &CommandContext) -> Result<bool, String> {
+        self.evaluate_in(&Scope { ctx, trust: "" })
+    }
+
+    /// Evaluate against `scope`. The result must be a boolean.
+    pub fn evaluate_in(&self, scope: &Scope<'_>) -> Result<bool, String> {
+        match self.expr.eval(scope)? {
+            Value::Bool(b) => Ok(b),
+            other => Err(format!(
+                "condition is {}, not true or false",
+                other.type_name()
+            )),
+        }
+    }
+}
+
+/// A runtime value.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Unit,
+    Bool(bool),
+    Int(i64),
+    Str(String),
+    Array(Vec<String>),
+    Map(HashMap<String, String>),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Unit => "()",
+            Value::Bool(_) => "a boolean",
+            Value::Int(_) => "an integer",
+            Value::Str(_) => "a string",
+            Value::Array(_) => "an array",
+            Value::Map(_) => "a map",
+        }
+    }
+
+    fn bool(self, op: &str) -> Result<bool, String> {
+        match self {
+            Value::Bool(b) => Ok(b),
+            other => Err(format!("`{op}` needs booleans, got {}", other.type_name())),
+        }
+    }
+
+    fn str(self, what: &str) -> Result<String, String> {
+        match self {
+            Value::Str(s) => Ok(s),
+            other => Err(format!("{what} needs a string, got {}", other.type_name())),
+        }
+    }
+
+    /// `needle in self`.
+    fn contains(&self, needle: Value) -> Result<bool, String> {
+        match self {
+            Value::Array(items) => Ok(match needle {
+                Value::Str(s) => items.contains(&s),
+                _ => false,
+            }),
+            Value::Str(s) => Ok(s.contains(needle.str("`contains` on a string")?.as_str())),
+            Value::Map(map) => Ok(map.contains_key(&needle.str("`contains` on a map")?)),
+            other => Err(format!("{} has no `contains`", other.type_name())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Var {
+    Command,
+    Args,
+    Words,
+    Env,
+    ProcessEnv,
+    Redirection,
+    Privileged,
+    Cwd,
+    Trust,
+}
+
+impl Var {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "command" => Var::Command,
+            "args" => Var::Args,
+            "words" => Var::Words,
+            "env" => Var::Env,
+            "process_env" => Var::ProcessEnv,
+            "redirection" => Var::Redirection,
+            "privileged" => Var::Privileged,
+            "cwd" => Var::Cwd,
+            "trust" => Var::Trust,
+            _ => return None,
+        })
+    }
+
+    fn value(self, scope: &Scope<'_>) -> Value {
+        let ctx = scope.ctx;
+        let strings = |words: &[agent_shell_parser::parse::Word]| {
+            words.iter().map(|w| w.to_string()).collect()
+        };
+        match self {
+            Var::Command => Value::Str(ctx.base_command.clone()),
+            Var::Args => Value::Array(strings(ctx.args())),
+            Var::Words => Value::Array(strings(&ctx.words)),
+            Var::Env => {
+                let mut env = ctx.accumulated_env.clone();
+                env.extend(ctx.env_vars.iter().cloned());
+                Value::Map(env)
+            }
+            Var::ProcessEnv => Value::Map(std::env::vars().collect()),
+            Var::Redirection => Value::Str(match ctx.redirect_path() {
+                Some(path) => path.display().to_string(),
+                None => ctx
+                    .redirection
+                    .as_ref()
+                    .map(|r| r.target.clone())
+                    .unwrap_or_default(),
+            }),
+            Var::Privileged => Value::Bool(ctx.privileged_by.is_some()),
+            Var::Cwd => Value::Str(ctx.working_dir().display().to_string()),
+            Var::Trust => Value::Str(scope.trust.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Method {
+    Contains,
+    StartsWith,
+    EndsWith,
+    Len,
+    IsEmpty,
+}
+
+impl Method {
+    fn from_name(name: &str) -> Option<(Self, usize)> {
+        Some(match name {
+            "contains" => (Method::Contains, 1),
+            "starts_with" => (Method::StartsWith, 1),
+            "ends_with" => (Method::EndsWith, 1),
+            "len" => (Method::Len, 0),
+            "is_empty" => (Method::IsEmpty, 0),
+            _ => return None,
+        })
+    }
+
+    fn call(self, receiver: Value, arg: Option<Value>) -> Result<Value, String> {
+        let len = |v: &Value| match v {
+            Value::Str(s) => Ok(s.chars().count()),
+            Value::Array(a) => Ok(a.len()),
+            Value::Map(m) => Ok(m.len()),
+            other => Err(format!("{} has no length", other.type_name())),
+        };
+        Ok(match (self, arg) {
+            (Method::Contains, Some(arg)) => Value::Bool(receiver.contains(arg)?),
+            (Method::StartsWith, Some(arg)) => {
+                let prefix = arg.str("`starts_with`")?;
+                Value::Bool(receiver.str("`starts_with`")?.starts_with(&prefix))
+            }
+            (Method::EndsWith, Some(arg)) => {
+                let suffix = arg.str("`ends_with`")?;
+                Value::Bool(receiver.str("`ends_with`")?.ends_with(&suffix))
+            }
+            (Method::Len, None) => Value::Int(len(&receiver)? as i64),
+            (Method::IsEmpty, None) => Value::Bool(len(&receiver)? == 0),
+            _ => unreachable!("arity is checked when parsing"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Lit(Value),
+    Var(Var),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Cmp(CmpOp, Box<Expr>, Box<Expr>),
+    In(Box<Expr>, Box<Expr>),
+    Index(Box<Expr>, Box<Expr>),
+    Call(Box<Expr>, Method, Option<Box<Expr>>),
+}
+
+impl Expr {
+    fn eval(&self, scope: &Scope<'_>) -> Result<Value, String> {
+        Ok(match self {
+            Expr::Lit(v) => v.clone(),
+            Expr::Var(var) => var.value(scope),
+            Expr::Not(e) => Value::Bool(!e.eval(scope)?.bool("!")?),
+            Expr::And(a, b) => {
+                Value::Bool(a.eval(scope)?.bool("&&")? && b.eval(scope)?.bool("&&")?)
+            }
+            Expr::Or(a, b) => Value::Bool(a.eval(scope)?.bool("||")? || b.eval(scope)?.bool("||")?),
+            Expr::Cmp(op, a, b) => {
+                let (a, b) = (a.eval(scope)?, b.eval(scope)?);
+                let ordering = match (&a, &b) {
+                    (Value::Int(x), Value::Int(y)) => Some(x.cmp(y)),
+                    (Value::Str(x), Value::Str(y)) => Some(x.cmp(y)),
+                    _ => None,
+                };
+                Value::Bool(match op {
+                    CmpOp::Eq => a == b,
+                    CmpOp::Ne => a != b,
+                    _ => {
+                        let ordering = ordering.ok_or_else(|| {
+                            format!("can't order {} and {}", a.type_name(), b.type_name())
+                        })?;
+                        match op {
+                            CmpOp::Lt => ordering.is_lt(),
+                            CmpOp::Le => ordering.is_le(),
+                            CmpOp::Gt => ordering.is_gt(),
+                            _ => ordering.is_ge(),
+                        }
+                    }
+                })
+            }
+            Expr::In(needle, haystack) => {
+                let needle = needle.eval(scope)?;
+                Value::Bool(haystack.eval(scope)?.contains(needle)?)
+            }
+            Expr::Index(target, index) => match (target.eval(scope)?, index.eval(scope)?) {
+                (Value::Array(items), Value::Int(i)) => usize::try_from(i)
+                    .ok()
+                    .and_then(|i| items.into_iter().nth(i))
+                    .map_or(Value::Unit, Value::Str),
+                (Value::Map(map), Value::Str(key)) => {
+                    map.get(&key).cloned().map_or(Value::Unit, Value::Str)
+                }
+                (target, index) => {
+                    return Err(format!(
+                        "can't index {} with {}",
+                        target.type_name(),
+                        index.type_name()
+                    ));
+                }
+            },
+            Expr::Call(receiver, method, arg) => {
+                let arg = arg.as_ref().map(|a| a.eval(scope)).transpose()?;
+                method.call(receiver.eval(scope)?, arg)?
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Str(String),
+    Int(i64),
+    Ident(String),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Dot,
+    Not,
+    And,
+    Or,
+    Cmp(CmpOp),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Str(s) => write!(f, "{s:?}"),
+            Token::Int(i) => write!(f, "`{i}`"),
+            Token::Ident(s) => write!(f, "`{s}`"),
+            Token::LParen => f.write_str("`(`"),
+            Token::RParen => f.write_str("`)`"),
+            Token::LBracket => f.write_str("`[`"),
+            Token::RBracket => f.write_str("`]`"),
+            Token::Dot => f.write_str("`.`"),
+            Token::Not => f.write_str("`!`"),
+            Token::And => f.write_str("`&&`"),
+            Token::Or => f.write_str("`||`"),
+            Token::Cmp(_) => f.write_str("comparison"),
+        }
+    }
+}
+
+fn lex(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '.' => Token::Dot,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+            '=' if chars.next_if_eq(&'=').is_some() => Token::Cmp(CmpOp::Eq),
+            '!' if chars.next_if_eq(&'=').is_some() => Token::Cmp(CmpOp::Ne),
+            '!' => Token::Not,
+            '<' if chars.next_if_eq(&'=').is_some() => Token::Cmp(CmpOp::Le),
+            '<' => Token::Cmp(CmpOp::Lt),
+            '>' if chars.next_if_eq(&'=').is_some() => Token::Cmp(CmpOp::Ge),
+            '>' => Token::Cmp(CmpOp::Gt),
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some(c @ ('"' | '\\')) => s.push(c),
+                            Some(c) => return Err(format!("unknown escape `\\{c}`")),
+                            None => return Err("unterminated string".into()),
+                        },
+                        Some(c) => s.push(c),
+                        None => return Err("unterminated string".into()),
+                    }
+                }
+                Token::Str(s)
+            }
+            c if c.is_ascii_digit() => {
+                let mut digits = c.to_string();
+                while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                    digits.push(d);
+                }
+                Token::Int(
+                    digits
+                        .parse()
+                        .map_err(|_| format!("number too large: {digits}"))?,
+                )
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut ident = c.to_string();
+                while let Some(d) = chars.next_if(|d| d.is_alphanumeric() || *d == '_') {
+                    ident.push(d);
+                }
+                Token::Ident(ident)
+            }
+            c => return Err(format!("unexpected `{c}`")),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or("unexpected end of condition")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, want: &Token) -> bool {
+        let found = self.peek() == Some(want);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, want: &Token) -> Result<(), String> {
+        match self.next()? {
+            ref t if t == want => Ok(()),
+            t => Err(format!("expected {want}, found {t}")),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.and()?;
+        while self.eat(&Token::Or) {
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.comparison()?;
+        while self.eat(&Token::And) {
+            lhs = Expr::And(Box::new(lhs), Box::new(self.comparison()?));
+        }
+        Ok(lhs)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let lhs = self.unary()?;
+        Ok(match self.peek() {
+            Some(Token::Cmp(op)) => {
+                let op = *op;
+                self.pos += 1;
+                Expr::Cmp(op, Box::new(lhs), Box::new(self.unary()?))
+            }
+            Some(Token::Ident(kw)) if kw == "in" => {
+                self.pos += 1;
+                Expr::In(Box::new(lhs), Box::new(self.unary()?))
+            }
+            _ => lhs,
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat(&Token::LBracket) {
+                let index = self.or()?;
+                self.expect(&Token::RBracket)?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else if self.eat(&Token::Dot) {
+                let name = match self.next()? {
+                    Token::Ident(name) => name,
+                    t => return Err(format!("expected a method name, found {t}")),
+                };
+                let (method, arity) =
+                    Method::from_name(&name).ok_or_else(|| format!("unknown method `{name}`"))?;
+                self.expect(&Token::LParen)?;
+                let arg = if arity == 1 {
+                    Some(Box::new(self.or()?))
+                } else {
+                    None
+                };
+                self.expect(&Token::RParen)
+                    .map_err(|_| format!("`{name}` takes {arity} argument(s)"))?;
+                expr = Expr::Call(Box::new(expr), method, arg);
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        Ok(match self.next()? {
+            Token::Str(s) => Expr::Lit(Value::Str(s)),
+            Token::Int(i) => Expr::Lit(Value::Int(i)),
+            Token::LParen if self.eat(&Token::RParen) => Expr::Lit(Value::Unit),
+            Token::LParen => {
+                let expr = self.or()?;
+                self.expect(&Token::RParen)?;
+                expr
+            }
+            Token::Ident(name) => match name.as_str() {
+                "true" => Expr::Lit(Value::Bool(true)),
+                "false" => Expr::Lit(Value::Bool(false)),
+                _ => Expr::Var(
+                    Var::from_name(&name).ok_or_else(|| format!("unknown variable `{name}`"))?,
+                ),
+            },
+            t => return Err(format!("unexpected {t}")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str, command: &str) -> Result<bool, String> {
+        Condition::parse(source)?.evaluate(&CommandContext::from_command(command))
+    }
+
+    #[test]
+    fn trust_tier_comes_from_the_scope() {
+        let ctx = CommandContext::from_command("make deploy");
+        let condition = Condition::parse(r#"trust == "high""#).unwrap();
+        assert_eq!(condition.evaluate(&ctx), Ok(false));
+        let scope = Scope {
+            ctx: &ctx,
+            trust: "high",
+        };
+        assert_eq!(condition.evaluate_in(&scope), Ok(true));
+    }
+
+    #[test]
+    fn variables_and_methods() {
+        let cmd = "TF_WORKSPACE=prod terraform apply -auto-approve > plan.txt";
+        assert_eq!(eval(r#"command == "terraform""#, cmd), Ok(true));
+        assert_eq!(eval(r#"args.contains("-auto-approve")"#, cmd), Ok(true));
+        assert_eq!(
+            eval(r#""apply" in args && !("plan" in args)"#, cmd),
+            Ok(true)
+        );
+        assert_eq!(eval(r#"env["TF_WORKSPACE"] == "prod""#, cmd), Ok(true));
+        assert_eq!(eval(r#"env.contains("HOME")"#, cmd), Ok(false));
+        assert_eq!(eval(r#"redirection.ends_with(".txt")"#, cmd), Ok(true));
+        assert_eq!(eval("args.len() == 2 && words.len() >= 3", cmd), Ok(true));
+        assert_eq!(
+            eval(r#"args[0] == "apply" && args[9] == ()"#, cmd),
+            Ok(true)
+        );
+        assert_eq!(eval("privileged || redirection.is_empty()", cmd), Ok(false));
+        assert_eq!(eval("cwd.starts_with(\"/\")", cmd), Ok(true));
+    }
+
+    #[test]
+    fn process_env_is_not_the_command_env() {
+        let cmd = "CC_TOOLGATE_TEST_UNSET=1 make";
+        assert_eq!(
+            eval(r#"env.contains("CC_TOOLGATE_TEST_UNSET")"#, cmd),
+            Ok(true)
+        );
+        assert_eq!(
+            eval(r#"process_env.contains("CC_TOOLGATE_TEST_UNSET")"#, cmd),
+            Ok(false)
+        );
+        assert_eq!(eval(r#"process_env["PATH"] != ()"#, cmd), Ok(true));
+    }
+
+    #[test]
+    fn paths_resolve_against_the_command_cwd() {
+        let mut ctx = CommandContext::from_command("echo x > ../notes.txt");
+        ctx.cwd = Some("/srv/app".into());
+        let condition = |source: &str| Condition::parse(source).unwrap().evaluate(&ctx);
+        assert_eq!(condition(r#"cwd == "/srv/app""#), Ok(true));
+        assert_eq!(condition(r#"redirection == "/srv/notes.txt""#), Ok(true));
+        let ctx = CommandContext::from_command("echo x >&2");
+        assert_eq!(ctx.redirect_path(), None);
+    }
+
+    #[test]
+    fn precedence_and_short_circuit() {
+        assert_eq!(eval("true || false && false", "ls"), Ok(true));
+        assert_eq!(eval("(true || false) && false", "ls"), Ok(false));
+        assert_eq!(eval("!false && !!true", "ls"), Ok(true));
+        // The right-hand side would be a type error if evaluated.
+        assert_eq!(eval("false && args", "ls"), Ok(false));
+        assert_eq!(eval(r#""b" < "c" && 10 > 9"#, "ls"), Ok(true));
+        assert_eq!(eval(r#""a\"b" == "a\"b""#, "ls"), Ok(true));
+        assert_eq!(eval(r#"1 == "1""#, "ls"), Ok(false));
+    }
+
+    #[test]
+    fn parse_errors() {
+        for (source, expected) in [
+            ("args.contains(", "unexpected end"),
+            ("argz", "unknown variable `argz`"),
+            ("args.push(\"x\")", "unknown method `push`"),
+            ("args.len(1)", "takes 0 argument"),
+            ("\"open", "unterminated string"),
+            ("true true", "unexpected `true`"),
+            ("a = b", "unexpected `=`"),
+        ] {
+            let err = Condition::parse(source).err().unwrap();
+            assert!(err.contains(expected), "{source}: {err}");
+        }
+    }
+
+    #[test]
+    fn type_errors() {
+        for (source, expected) in [
+            ("args", "condition is an array"),
+            ("args && true", "`&&` needs booleans"),
+            ("args < 3", "can't order"),
+            ("command[0]", "can't index a string"),
+            ("privileged.len()", "a boolean has no length"),
+        ] {
+            let err = eval(source, "ls -la").err().unwrap();
+            assert!(err.contains(expected), "{source}: {err}");
+        }
+    }
+}
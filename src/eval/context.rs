@@ -1,6 +1,10 @@
 //! Per-segment command context: tokenization, env var extraction, and redirection detection.
 
+use crate::parse::RedirectionAnalysis;
 use agent_shell_parser::parse::{Redirection, ShellSegment, Word};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// Context for evaluating a single command segment.
 #[derive(Debug)]
@@ -11,18 +15,56 @@ pub struct CommandContext {
     pub words: Vec<Word>,
     /// Leading KEY=VALUE environment variable assignments.
     pub env_vars: Vec<(String, String)>,
-    /// Detected output redirection, if any.
+    /// Detected output redirection, if any. The registry sets it to the
+    /// first of [`redirections`](Self::redirections) that isn't to a safe
+    /// sink or target.
     pub redirection: Option<Redirection>,
+    /// Every output redirection on the command, in source order.
+    pub redirections: Vec<Redirection>,
     /// Environment variables accumulated from prior segments in a compound command
     /// (e.g. `export FOO=bar ; git push` makes FOO=bar available to the git push segment).
     pub accumulated_env: std::collections::HashMap<String, String>,
+    /// The privilege-escalating wrapper this command runs under, if any
+    /// (e.g. `sudo` for `sudo tee /etc/hosts`). Set by the registry when it
+    /// unwraps an `ask_floor` wrapper.
+    pub privileged_by: Option<String>,
+    /// Source text of each command substitution in the segment
+    /// (`$(...)`, backticks, `<(...)`, `>(...)`), in order. Their contents
+    /// are evaluated as separate segments; a spec only sees the placeholder
+    /// word, and can ask [`is_substituted`](Self::is_substituted) whether an
+    /// argument's value is only known at run time.
+    pub substitutions: Vec<String>,
+    /// The directory the command runs in, from the hook input. `None` means
+    /// the process's current directory. Set by the registry.
+    pub cwd: Option<PathBuf>,
+    /// Words with an unquoted glob (`*`, `?`, `[`) the shell will expand,
+    /// as written (`*/`, `logs/*.gz`). Words don't keep their quotes, so
+    /// these come from the segment's source text.
+    pub globs: Vec<String>,
 }
 
 impl CommandContext {
     /// Build a CommandContext from a raw command string.
     ///
-    /// Used for simple (non-compound) command evaluation and in tests.
+    /// Used for simple (non-compound) command evaluation and in tests. A
+    /// command that parses to a single segment goes through
+    /// [`from_segment`](Self::from_segment), so specs see the same context
+    /// either way; anything else falls back to shlex tokenization.
     pub fn from_command(raw: &str) -> Self {
+        match crate::parse::parse_lossy(raw) {
+            Some(pipeline)
+                if !pipeline.has_parse_errors
+                    && pipeline.segments.len() == 1
+                    && pipeline.segments[0].command == raw.trim() =>
+            {
+                Self::from_segment(&pipeline.segments[0])
+            }
+            _ => Self::from_tokens(raw),
+        }
+    }
+
+    /// Build a CommandContext by shlex-tokenizing `raw`.
+    fn from_tokens(raw: &str) -> Self {
         let base_command = agent_shell_parser::parse::base_command(raw);
         let env_vars = agent_shell_parser::parse::env_vars(raw);
         let words = agent_shell_parser::parse::tokenize(raw);
@@ -33,6 +75,7 @@ impl CommandContext {
                 operator: ">",
                 fd: None,
                 target: "(parse error)".into(),
+                resolved: None,
             },
         ));
 
@@ -40,47 +83,57 @@ impl CommandContext {
             base_command,
             words,
             env_vars,
+            redirections: redirection.iter().cloned().collect(),
             redirection,
             accumulated_env: std::collections::HashMap::new(),
+            privileged_by: None,
+            substitutions: Vec::new(),
+            cwd: None,
+            globs: unquoted_globs(raw),
         }
     }
 
     /// Build a CommandContext from a parsed [`ShellSegment`].
     ///
-    /// Uses the segment's pre-tokenized `words` field directly — tree-sitter
-    /// already handles word boundaries correctly, including preserving
-    /// substitution syntax (`$(...)`, backticks) as single tokens.
-    ///
-    /// Redirection is detected by parsing the segment's command text (inline
-    /// redirections like `cat > file`) or inherited from the segment's
-    /// `redirection` field (wrapping-construct redirections like `for ... done > file`).
+    /// Uses the segment's pre-tokenized `words` and `redirection` directly —
+    /// the initial parse already found word boundaries (preserving
+    /// substitution syntax as single tokens) and both inline redirections
+    /// (`cat > file`) and wrapping-construct ones (`for ... done > file`), so
+    /// nothing is parsed again.
     pub fn from_segment(segment: &ShellSegment) -> Self {
-        let words = segment.words.clone();
+        let mut ctx = Self::from_words(segment.words.clone());
+        ctx.redirection = segment.redirection.clone();
+        ctx.redirections = segment.redirections.clone();
+        ctx.substitutions = segment
+            .substitutions
+            .iter()
+            .filter_map(|s| segment.command.get(s.start..s.end))
+            .map(String::from)
+            .collect();
+        ctx.globs = unquoted_globs(&segment.command);
+        ctx
+    }
+
+    /// Build a CommandContext from already-tokenized words, without parsing.
+    ///
+    /// Used for the inner command of a wrapper (`sudo rm -rf x` → `rm -rf x`),
+    /// whose words come from the wrapper's segment. The words carry no
+    /// redirection of their own: a quoted `'a > b'` argument stays one word.
+    /// Nor do they carry [`globs`](Self::globs); the wrapper's apply.
+    pub fn from_words(words: Vec<Word>) -> Self {
         let base_command = Self::base_command_from_words(&words);
         let env_vars = Self::env_vars_from_words(&words);
-        // Detect inline redirections from the command text, falling back to
-        // the segment's wrapping-construct redirection if present.
-        let redirection = match agent_shell_parser::parse::has_output_redirection(&segment.command)
-        {
-            Ok(r) => r.or_else(|| segment.redirection.clone()),
-            Err(_) => {
-                segment
-                    .redirection
-                    .clone()
-                    .or(Some(agent_shell_parser::parse::Redirection {
-                        operator: ">",
-                        fd: None,
-                        target: "(parse error)".into(),
-                    }))
-            }
-        };
-
         Self {
             base_command,
             words,
             env_vars,
-            redirection,
+            redirection: None,
+            redirections: Vec::new(),
             accumulated_env: std::collections::HashMap::new(),
+            privileged_by: None,
+            substitutions: Vec::new(),
+            cwd: None,
+            globs: Vec::new(),
         }
     }
 
@@ -154,6 +207,37 @@ impl CommandContext {
         }
     }
 
+    /// Whether `word` contains a command substitution, i.e. its value is
+    /// only known when the command runs (`rm $(find . -name '*.tmp')`).
+    pub fn is_substituted(&self, word: &str) -> bool {
+        self.substitutions.iter().any(|s| word.contains(s.as_str()))
+    }
+
+    /// The directory the command runs in: [`cwd`](Self::cwd), else the
+    /// process's current directory, else `/`.
+    pub fn working_dir(&self) -> PathBuf {
+        self.cwd
+            .clone()
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_else(|| PathBuf::from("/"))
+    }
+
+    /// Resolve a path argument against [`working_dir`](Self::working_dir),
+    /// as [`paths::resolve`](crate::eval::paths::resolve) does.
+    pub fn resolve_path(&self, raw: &str) -> PathBuf {
+        crate::eval::paths::resolve_in(raw, &self.working_dir())
+    }
+
+    /// The output redirection target resolved to an absolute path, `None`
+    /// if there's no redirection or it duplicates a file descriptor (`>&2`).
+    pub fn redirect_path(&self) -> Option<PathBuf> {
+        let redirection = self.redirection.as_ref()?;
+        redirection
+            .resolved
+            .clone()
+            .or_else(|| crate::eval::redirect::destination(redirection, &self.working_dir()))
+    }
+
     /// Check if any word matches a flag.
     pub fn has_flag(&self, flag: &str) -> bool {
         self.words.iter().any(|w| w == flag)
@@ -163,6 +247,156 @@ impl CommandContext {
     pub fn has_any_flag(&self, flags: &[&str]) -> bool {
         self.words.iter().any(|w| flags.contains(&w.as_str()))
     }
+
+    /// Render words back into a command line, single-quoting any word that
+    /// contains characters the shell would interpret.
+    pub fn join_words<'a>(words: impl IntoIterator<Item = &'a str>) -> String {
+        words
+            .into_iter()
+            .map(|w| {
+                let plain = !w.is_empty()
+                    && w.chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,~".contains(c));
+                if plain {
+                    w.to_string()
+                } else {
+                    format!("'{}'", w.replace('\'', "'\\''"))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Words of `command` with a glob character outside quotes, escapes,
+/// `${...}` and `$(...)` (whose commands are evaluated on their own).
+fn unquoted_globs(command: &str) -> Vec<String> {
+    let mut globs = Vec::new();
+    let mut word = String::new();
+    let mut globbed = false;
+    let mut quote = None;
+    let mut depth = 0usize;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some('"'), '\\') => {
+                word.push(c);
+                word.extend(chars.next());
+                continue;
+            }
+            (Some(_), _) => {}
+            (None, '\\') => {
+                word.push(c);
+                word.extend(chars.next());
+                continue;
+            }
+            (None, '\'' | '"') if depth == 0 => quote = Some(c),
+            (None, '$') if matches!(chars.peek(), Some('(' | '{')) => {
+                depth += 1;
+                word.push(c);
+                word.extend(chars.next());
+                continue;
+            }
+            (None, '(' | '{') if depth > 0 => depth += 1,
+            (None, ')' | '}') if depth > 0 => depth -= 1,
+            (None, '*' | '?' | '[') if depth == 0 => globbed = true,
+            (None, _) if depth == 0 && (c.is_whitespace() || ";&|<>()".contains(c)) => {
+                if globbed {
+                    globs.push(std::mem::take(&mut word));
+                }
+                word.clear();
+                globbed = false;
+                continue;
+            }
+            _ => {}
+        }
+        word.push(c);
+    }
+    if globbed {
+        globs.push(word);
+    }
+    globs
+}
+
+/// Redirection operators the parser produces. [`Redirection::operator`] is
+/// `&'static str`, so a deserialized operator is mapped back onto this list.
+const REDIRECTION_OPERATORS: &[&str] = &[">", ">>", ">|", "&>", "&>>", "<>", ">&"];
+
+/// A [`CommandContext`] as JSON, for evaluators outside the process
+/// (plugins, external scripts).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedContext {
+    /// The base command name.
+    pub base_command: String,
+    /// All words in the command.
+    pub words: Vec<Word>,
+    /// Leading `KEY=VALUE` assignments.
+    pub env_vars: Vec<(String, String)>,
+    /// Output redirection, if any.
+    pub redirection: Option<RedirectionAnalysis>,
+    /// Variables exported by earlier segments of a compound command.
+    pub accumulated_env: HashMap<String, String>,
+    /// The privilege-escalating wrapper the command runs under, if any.
+    pub privileged_by: Option<String>,
+    /// Source text of each command substitution in the segment.
+    pub substitutions: Vec<String>,
+    /// The directory the command runs in, if known.
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// Words with an unquoted glob, as written.
+    #[serde(default)]
+    pub globs: Vec<String>,
+}
+
+impl From<&CommandContext> for SerializedContext {
+    fn from(ctx: &CommandContext) -> Self {
+        Self {
+            base_command: ctx.base_command.clone(),
+            words: ctx.words.clone(),
+            env_vars: ctx.env_vars.clone(),
+            redirection: ctx.redirection.as_ref().map(|r| RedirectionAnalysis {
+                operator: r.operator.to_string(),
+                fd: r.fd,
+                target: r.target.clone(),
+            }),
+            accumulated_env: ctx.accumulated_env.clone(),
+            privileged_by: ctx.privileged_by.clone(),
+            substitutions: ctx.substitutions.clone(),
+            cwd: ctx.cwd.clone(),
+            globs: ctx.globs.clone(),
+        }
+    }
+}
+
+impl SerializedContext {
+    /// Rebuild the context on the receiving side.
+    pub fn into_context(self) -> CommandContext {
+        // An operator this build doesn't know is still an output
+        // redirection; `>` keeps the spec's redirection checks engaged.
+        let redirection = self.redirection.map(|r| Redirection {
+            operator: REDIRECTION_OPERATORS
+                .iter()
+                .find(|op| **op == r.operator)
+                .copied()
+                .unwrap_or(">"),
+            fd: r.fd,
+            target: r.target,
+            resolved: None,
+        });
+        CommandContext {
+            base_command: self.base_command,
+            words: self.words,
+            env_vars: self.env_vars,
+            redirections: redirection.iter().cloned().collect(),
+            redirection,
+            accumulated_env: self.accumulated_env,
+            privileged_by: self.privileged_by,
+            substitutions: self.substitutions,
+            cwd: self.cwd,
+            globs: self.globs,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -182,6 +416,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn join_words_quotes_shell_syntax() {
+        assert_eq!(
+            CommandContext::join_words(["git", "commit", "-m", "it's done"]),
+            "git commit -m 'it'\\''s done'"
+        );
+        assert_eq!(
+            CommandContext::join_words(["kubectl", "apply", "-f", "a b.yaml"]),
+            "kubectl apply -f 'a b.yaml'"
+        );
+        assert_eq!(CommandContext::join_words(["echo", ""]), "echo ''");
+    }
+
     #[test]
     fn env_satisfies_inline_exact() {
         let ctx = CommandContext::from_command("FOO=bar git push");
@@ -324,4 +571,44 @@ mod tests {
         assert!(ctx.env_satisfies(&req), "expected 'beta', env was tampered");
         unsafe { std::env::remove_var(COLLISION_KEY) };
     }
+
+    #[test]
+    fn serialized_context_round_trips() {
+        let mut ctx = CommandContext::from_command("FOO=1 deployctl push $(cat id) 2>> log");
+        ctx.privileged_by = Some("sudo".into());
+        ctx.accumulated_env.insert("ENV".into(), "prod".into());
+        let json = serde_json::to_string(&SerializedContext::from(&ctx)).unwrap();
+        let back = serde_json::from_str::<SerializedContext>(&json)
+            .unwrap()
+            .into_context();
+        assert_eq!(back.base_command, "deployctl");
+        assert_eq!(back.words, ctx.words);
+        assert_eq!(back.env_vars, ctx.env_vars);
+        assert_eq!(back.redirection, ctx.redirection);
+        assert_eq!(back.accumulated_env, ctx.accumulated_env);
+        assert_eq!(back.privileged_by.as_deref(), Some("sudo"));
+        assert_eq!(back.substitutions, ["$(cat id)"]);
+    }
+
+    #[test]
+    fn unknown_redirection_operator_stays_a_redirection() {
+        let ctx = CommandContext::from_command("deployctl > out");
+        let mut serialized = SerializedContext::from(&ctx);
+        serialized.redirection.as_mut().unwrap().operator = "?>".into();
+        let ctx = serialized.into_context();
+        assert_eq!(ctx.redirection.unwrap().operator, ">");
+    }
+
+    #[test]
+    fn unquoted_globs_skip_quotes_and_expansions() {
+        assert_eq!(unquoted_globs("rm -rf */ logs/*.gz"), ["*/", "logs/*.gz"]);
+        assert_eq!(
+            unquoted_globs("ls file?.txt [ab].c"),
+            ["file?.txt", "[ab].c"]
+        );
+        assert!(unquoted_globs(r#"rm -rf '*' "a*" b\* ${x#*} $(ls *)"#).is_empty());
+        assert_eq!(unquoted_globs(r#"rm "$DIR"/*"#), [r#""$DIR"/*"#]);
+        let ctx = CommandContext::from_command("chmod -R 777 *");
+        assert_eq!(ctx.globs, ["*"]);
+    }
 }
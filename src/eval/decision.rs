@@ -1,15 +1,23 @@
 //! Decision types for command evaluation.
 
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
 /// The gating decision for a command.
 ///
 /// Variants are ordered by severity: `Allow < Ask < Deny`.
 /// When evaluating compound commands, the strictest decision across
 /// all segments wins.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+///
+/// Deserializes from the lowercase names used in config (`"allow"`, `"ask"`,
+/// `"deny"`). Defaults to `Ask` so that an unset decision fails closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Decision {
     /// Command runs silently without user confirmation.
     Allow,
     /// Claude Code prompts the user for confirmation before running.
+    #[default]
     Ask,
     /// Command is blocked outright and cannot be executed.
     Deny,
@@ -36,10 +44,70 @@ impl Decision {
 }
 
 /// The result of evaluating a command: a decision and a human-readable reason.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuleMatch {
     /// The gating decision.
     pub decision: Decision,
     /// Human-readable explanation of why this decision was reached.
     pub reason: String,
 }
+
+impl RuleMatch {
+    /// The audit-mode form of this result: always ALLOW, with the decision
+    /// that would have applied recorded in the reason.
+    pub fn audited(&self) -> RuleMatch {
+        RuleMatch {
+            decision: Decision::Allow,
+            reason: format!(
+                "audit mode, would {}: {}",
+                self.decision.as_str(),
+                self.reason
+            ),
+        }
+    }
+}
+
+/// The decision for one command segment of an evaluated command.
+///
+/// Compound commands produce one entry per segment, including segments
+/// inside command substitutions; a simple command produces one entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SegmentResult {
+    /// The segment's command text.
+    pub command: String,
+    /// The decision for this segment.
+    pub decision: Decision,
+    /// Why this segment got its decision.
+    pub reason: String,
+    /// A safer alternative, for ASK and DENY segments that have one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+/// A full evaluation: the overall decision plus each segment's result.
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    /// The overall decision (strictest across segments) and combined reason.
+    pub result: RuleMatch,
+    /// Per-segment decisions, in evaluation order.
+    pub segments: Vec<SegmentResult>,
+    /// How long parsing and evaluation took.
+    pub timings: Timings,
+}
+
+/// Time spent in each phase of an evaluation. Always zero without the
+/// `native` feature, since wasm32-unknown-unknown has no clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// Parsing the command, substitutions included.
+    pub parse: Duration,
+    /// Evaluating the parsed command.
+    pub eval: Duration,
+}
+
+impl Timings {
+    /// Both phases together.
+    pub fn total(&self) -> Duration {
+        self.parse + self.eval
+    }
+}
@@ -0,0 +1,493 @@
+//! Container runs: `docker run`, `docker create` and `docker exec`, and the
+//! same commands under podman and nerdctl.
+//!
+//! Starting a container gets `[docker] run`. Options that hand the
+//! container the host (`--privileged`, `--pid=host`, `--cap-add
+//! SYS_ADMIN`, `--device`, a mount of `/` or the runtime socket) get
+//! `[docker] escalation`. A command given to run inside is evaluated too,
+//! and the strictest decision wins.
+//!
+//! Running a command in an existing container gets `[docker] exec`, raised
+//! by the command. An interactive shell (`docker exec -it web bash`) gets
+//! `[docker] interactive_shell`: what's typed into it is never seen.
+
+use std::path::Path;
+
+use super::paths::{PathPolicy, WriteTarget};
+
+/// A `docker run` or `docker create` invocation.
+#[derive(Debug)]
+pub(crate) struct Run<'a> {
+    /// The container CLI, e.g. `docker` or `podman`.
+    pub(crate) tool: &'a str,
+    /// `run` or `create`.
+    pub(crate) action: &'a str,
+    /// The image, if one is given.
+    pub(crate) image: Option<&'a str>,
+    /// The command run in the container, if one is given.
+    pub(crate) command: Vec<&'a str>,
+    /// Options before the image, with their values.
+    options: Vec<(String, Option<&'a str>)>,
+}
+
+/// Container CLIs that take docker's arguments.
+const TOOLS: &[&str] = &["docker", "podman", "nerdctl"];
+
+/// Global options (before the subcommand) that take a value.
+const GLOBAL_VALUES: &[&str] = &[
+    "-H",
+    "--host",
+    "-c",
+    "--context",
+    "--config",
+    "-l",
+    "--log-level",
+    "--tlscacert",
+    "--tlscert",
+    "--tlskey",
+    "--root",
+    "--runroot",
+    "--storage-driver",
+    "--url",
+    "--connection",
+    "--namespace",
+    "-n",
+];
+
+/// `run` options that don't take a value. Every other option does.
+const RUN_BOOLEANS: &[&str] = &[
+    "--detach",
+    "--interactive",
+    "--tty",
+    "--rm",
+    "--privileged",
+    "--init",
+    "--publish-all",
+    "--read-only",
+    "--no-healthcheck",
+    "--oom-kill-disable",
+    "--sig-proxy",
+    "--quiet",
+    "--help",
+    "--disable-content-trust",
+    "--use-api-socket",
+    "--replace",
+    "--rmi",
+    "--no-hosts",
+    "--read-only-tmpfs",
+    "--env-host",
+    "--http-proxy",
+];
+
+/// Short `run` options that don't take a value.
+const RUN_SHORT_BOOLEANS: &str = "ditPq";
+
+/// Namespace options that share the host's namespace when set to `host`.
+const HOST_NAMESPACES: &[&str] = &["--pid", "--ipc", "--uts", "--userns", "--cgroupns"];
+
+/// Capabilities that let a container reach the host.
+const HOST_CAPABILITIES: &[&str] = &[
+    "ALL",
+    "SYS_ADMIN",
+    "SYS_PTRACE",
+    "SYS_MODULE",
+    "SYS_RAWIO",
+    "DAC_READ_SEARCH",
+    "BPF",
+];
+
+/// `--security-opt` values that turn off confinement.
+const UNCONFINED: &[&str] = &[
+    "seccomp=unconfined",
+    "seccomp:unconfined",
+    "apparmor=unconfined",
+    "apparmor:unconfined",
+    "label=disable",
+    "label:disable",
+    "systempaths=unconfined",
+];
+
+/// Sockets that control a container runtime.
+const RUNTIME_SOCKETS: &[&str] = &["docker.sock", "podman.sock", "containerd.sock"];
+
+/// The container CLI, its subcommand if it's one of `actions`, and the
+/// words after it. Global options and a `container` prefix are skipped.
+fn subcommand<'a, 'b>(
+    words: &'b [&'a str],
+    actions: &[&str],
+) -> Option<(
+    &'a str,
+    &'a str,
+    std::iter::Copied<std::slice::Iter<'b, &'a str>>,
+)> {
+    let (first, rest) = words.split_first()?;
+    let tool = first.rsplit('/').next()?;
+    if !TOOLS.contains(&tool) {
+        return None;
+    }
+    let mut words = rest.iter().copied();
+    while let Some(word) = words.next() {
+        if word.starts_with('-') {
+            if GLOBAL_VALUES.contains(&word) {
+                words.next();
+            }
+            continue;
+        }
+        if word == "container" {
+            continue;
+        }
+        return actions.contains(&word).then_some((tool, word, words));
+    }
+    None
+}
+
+/// Parse `words` (without leading assignments) as a `run` or `create`.
+pub(crate) fn run<'a>(words: &[&'a str]) -> Option<Run<'a>> {
+    let (tool, action, mut words) = subcommand(words, &["run", "create"])?;
+    let mut options = Vec::new();
+    let mut image = None;
+    while let Some(word) = words.next() {
+        if word == "--" {
+            image = words.next();
+            break;
+        }
+        if let Some(long) = word.strip_prefix("--") {
+            match long.split_once('=') {
+                Some((name, value)) => options.push((format!("--{name}"), Some(value))),
+                None if RUN_BOOLEANS.contains(&word) => options.push((word.to_string(), None)),
+                None => options.push((word.to_string(), words.next())),
+            }
+            continue;
+        }
+        let Some(cluster) = word.strip_prefix('-').filter(|c| !c.is_empty()) else {
+            image = Some(word);
+            break;
+        };
+        for (at, c) in cluster.char_indices() {
+            let name = format!("-{c}");
+            if RUN_SHORT_BOOLEANS.contains(c) {
+                options.push((name, None));
+                continue;
+            }
+            let value = match &cluster[at + c.len_utf8()..] {
+                "" => words.next(),
+                inline => Some(inline.trim_start_matches('=')),
+            };
+            options.push((name, value));
+            break;
+        }
+    }
+    Some(Run {
+        tool,
+        action,
+        image,
+        command: words.collect(),
+        options,
+    })
+}
+
+impl<'a> Run<'a> {
+    /// Values given to any of `names`.
+    fn values<'s>(&'s self, names: &'s [&str]) -> impl Iterator<Item = &'a str> + 's {
+        self.options
+            .iter()
+            .filter(|(name, _)| names.contains(&name.as_str()))
+            .filter_map(|(_, value)| *value)
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.options.iter().any(|(n, _)| *n == name)
+    }
+
+    /// How this container reaches the host, if it does: privileged mode,
+    /// host namespaces, capabilities, devices, and mounts of the host root,
+    /// a runtime socket, or `[paths]` entries. Relative mount sources are
+    /// taken from `cwd`.
+    pub(crate) fn escalations(&self, paths: &PathPolicy, cwd: &Path) -> Vec<String> {
+        let mut found = Vec::new();
+        if self.has("--privileged") {
+            found.push("--privileged".to_string());
+        }
+        for ns in HOST_NAMESPACES {
+            if self.values(&[ns]).any(|v| v == "host") {
+                found.push(format!("{ns}=host"));
+            }
+        }
+        for cap in self.values(&["--cap-add"]).flat_map(|v| v.split(',')) {
+            let upper = cap.to_ascii_uppercase();
+            let name = upper.strip_prefix("CAP_").unwrap_or(&upper);
+            if HOST_CAPABILITIES.contains(&name) {
+                found.push(format!("--cap-add {name}"));
+            }
+        }
+        for opt in self.values(&["--security-opt"]) {
+            if UNCONFINED.contains(&opt) {
+                found.push(format!("--security-opt {opt}"));
+            }
+        }
+        for device in self.values(&["--device"]) {
+            found.push(format!("--device {device}"));
+        }
+        if self.has("--use-api-socket") {
+            found.push("--use-api-socket".to_string());
+        }
+        for (source, read_only) in self.bind_mounts() {
+            if let Some(why) = mount_escalation(source, read_only, paths, cwd) {
+                found.push(why);
+            }
+        }
+        found
+    }
+
+    /// Host paths bind-mounted with `-v`/`--volume` or `--mount`, and
+    /// whether each is read-only. Named volumes aren't host paths.
+    fn bind_mounts(&self) -> Vec<(&'a str, bool)> {
+        let mut mounts = Vec::new();
+        for volume in self.values(&["-v", "--volume"]) {
+            let mut parts = volume.split(':');
+            let source = parts.next().unwrap_or_default();
+            let read_only = parts
+                .nth(1)
+                .is_some_and(|o| o.split(',').any(|o| o == "ro"));
+            if source.starts_with(['/', '~', '.']) {
+                mounts.push((source, read_only));
+            }
+        }
+        for mount in self.values(&["--mount"]) {
+            let fields: Vec<(&str, &str)> = mount
+                .split(',')
+                .map(|f| f.split_once('=').unwrap_or((f, "true")))
+                .collect();
+            let field = |keys: &[&str]| {
+                fields
+                    .iter()
+                    .find(|(k, _)| keys.contains(k))
+                    .map(|(_, v)| *v)
+            };
+            if field(&["type"]) != Some("bind") {
+                continue;
+            }
+            if let Some(source) = field(&["source", "src"]) {
+                let read_only = field(&["readonly", "ro"]).is_some_and(|v| v != "false");
+                mounts.push((source, read_only));
+            }
+        }
+        mounts
+    }
+}
+
+/// A `docker exec` invocation.
+#[derive(Debug)]
+pub(crate) struct Exec<'a> {
+    /// The container CLI, e.g. `docker` or `podman`.
+    pub(crate) tool: &'a str,
+    /// The container, or `None` for podman's `--latest`.
+    pub(crate) container: Option<&'a str>,
+    /// The command run in the container.
+    pub(crate) command: Vec<&'a str>,
+    /// Both `-i` and `-t`: the command gets a terminal to type into.
+    pub(crate) interactive: bool,
+    /// `--privileged`: the command gets every capability.
+    pub(crate) privileged: bool,
+}
+
+/// `exec` options that take a value. Every other option doesn't.
+const EXEC_VALUES: &[&str] = &[
+    "-e",
+    "--env",
+    "--env-file",
+    "-u",
+    "--user",
+    "-w",
+    "--workdir",
+    "--detach-keys",
+    "--preserve-fds",
+];
+
+/// Parse `words` (without leading assignments) as an `exec`.
+pub(crate) fn exec<'a>(words: &[&'a str]) -> Option<Exec<'a>> {
+    let (tool, _, mut words) = subcommand(words, &["exec"])?;
+    let mut flags = Vec::new();
+    let mut operand = None;
+    while let Some(word) = words.next() {
+        if word == "--" {
+            operand = words.next();
+            break;
+        }
+        if let Some(long) = word.strip_prefix("--") {
+            let name = long.split_once('=').map_or(long, |(name, _)| name);
+            if !long.contains('=') && EXEC_VALUES.contains(&word) {
+                words.next();
+            }
+            flags.push(name.to_string());
+            continue;
+        }
+        let Some(cluster) = word.strip_prefix('-').filter(|c| !c.is_empty()) else {
+            operand = Some(word);
+            break;
+        };
+        for (at, c) in cluster.char_indices() {
+            flags.push(c.to_string());
+            if EXEC_VALUES.contains(&format!("-{c}").as_str()) {
+                if at + c.len_utf8() == cluster.len() {
+                    words.next();
+                }
+                break;
+            }
+        }
+    }
+    let has = |short: &str, long: &str| flags.iter().any(|f| f == short || f == long);
+    // With `--latest` there's no container name: the first operand is the
+    // command.
+    let (container, command) = if has("l", "latest") {
+        (None, operand.into_iter().chain(words).collect())
+    } else {
+        (operand, words.collect())
+    };
+    Some(Exec {
+        tool,
+        container,
+        command,
+        interactive: has("i", "interactive") && has("t", "tty"),
+        privileged: has("privileged", "privileged"),
+    })
+}
+
+/// Why mounting `source` gives the container the host, if it does.
+fn mount_escalation(
+    source: &str,
+    read_only: bool,
+    paths: &PathPolicy,
+    cwd: &Path,
+) -> Option<String> {
+    let resolved = super::paths::resolve_in(source, cwd);
+    if resolved == Path::new("/") {
+        return Some("a mount of the host root /".into());
+    }
+    if RUNTIME_SOCKETS.iter().any(|s| resolved.ends_with(s)) {
+        return Some(format!("a mount of the runtime socket {source}"));
+    }
+    if let Some(entry) = paths.sensitive_match(source, cwd) {
+        return Some(format!("a mount of sensitive path {entry}"));
+    }
+    if let Some(entry) = paths.contains_sensitive(source, cwd) {
+        return Some(format!("a mount of {source}, which contains {entry}"));
+    }
+    match paths.classify_write(source, cwd) {
+        WriteTarget::Protected(entry) if !read_only => {
+            Some(format!("a read-write mount of protected path {entry}"))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn parse(command: &str) -> Option<(Option<&str>, String)> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        run(&words).map(|r| (r.image, r.command.join(" ")))
+    }
+
+    fn exec_of(command: &str) -> Option<(Option<&str>, String, bool)> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        exec(&words).map(|e| (e.container, e.command.join(" "), e.interactive))
+    }
+
+    fn escalations(command: &str) -> Vec<String> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        let paths = PathPolicy::from_config(&Config::default_config().paths);
+        run(&words)
+            .unwrap()
+            .escalations(&paths, Path::new("/home/user/project"))
+    }
+
+    #[test]
+    fn image_and_command() {
+        assert_eq!(
+            parse("docker run --rm -it ubuntu bash -c ls"),
+            Some((Some("ubuntu"), "bash -c ls".into()))
+        );
+        assert_eq!(
+            parse("docker run -e FOO=1 -p 8080:80 --name web nginx"),
+            Some((Some("nginx"), String::new()))
+        );
+        assert_eq!(
+            parse("podman container create -v=/data:/data alpine"),
+            Some((Some("alpine"), String::new()))
+        );
+        assert_eq!(
+            parse("docker -H tcp://host:2375 run --network=host alpine id"),
+            Some((Some("alpine"), "id".into()))
+        );
+        assert_eq!(parse("docker ps -a"), None);
+        assert_eq!(parse("docker exec web ls"), None);
+    }
+
+    #[test]
+    fn exec_container_and_command() {
+        assert_eq!(
+            exec_of("docker exec web ls -la"),
+            Some((Some("web"), "ls -la".into(), false))
+        );
+        assert_eq!(
+            exec_of("docker exec -it -u root -w /app web bash"),
+            Some((Some("web"), "bash".into(), true))
+        );
+        assert_eq!(
+            exec_of("docker container exec -e A=1 --env=B=2 -itu0 web sh"),
+            Some((Some("web"), "sh".into(), true))
+        );
+        assert_eq!(
+            exec_of("podman exec -l --tty --interactive cat /etc/os-release"),
+            Some((None, "cat /etc/os-release".into(), true))
+        );
+        assert_eq!(exec_of("docker run web ls"), None);
+    }
+
+    #[test]
+    fn ordinary_runs_dont_escalate() {
+        assert!(escalations("docker run --rm alpine echo hi").is_empty());
+        assert!(escalations("docker run -v mydata:/data alpine").is_empty());
+        assert!(escalations("docker run -v ./src:/src alpine").is_empty());
+        assert!(escalations("docker run -v /etc/hosts:/etc/hosts:ro alpine").is_empty());
+        assert!(escalations("docker run --cap-add NET_BIND_SERVICE alpine").is_empty());
+        assert!(escalations("docker run --pid=container:web alpine").is_empty());
+    }
+
+    #[test]
+    fn host_access_escalates() {
+        assert_eq!(
+            escalations("docker run --privileged alpine"),
+            ["--privileged"]
+        );
+        assert_eq!(escalations("docker run --pid=host alpine"), ["--pid=host"]);
+        assert_eq!(
+            escalations("docker run --cap-add cap_sys_admin alpine"),
+            ["--cap-add SYS_ADMIN"]
+        );
+        assert_eq!(
+            escalations("docker run --device /dev/sda alpine"),
+            ["--device /dev/sda"]
+        );
+        assert_eq!(
+            escalations("docker run --security-opt seccomp=unconfined alpine"),
+            ["--security-opt seccomp=unconfined"]
+        );
+        assert_eq!(
+            escalations("docker run -v /:/host alpine"),
+            ["a mount of the host root /"]
+        );
+        assert_eq!(
+            escalations("docker run -v /var/run/docker.sock:/var/run/docker.sock alpine"),
+            ["a mount of the runtime socket /var/run/docker.sock"]
+        );
+        assert_eq!(
+            escalations("docker run --mount type=bind,source=/etc,target=/x alpine"),
+            ["a read-write mount of protected path /etc"]
+        );
+        assert_eq!(escalations("docker run -itv /:/h alpine").len(), 1);
+    }
+}
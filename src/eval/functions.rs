@@ -0,0 +1,151 @@
+//! Shell functions defined within the command being evaluated.
+//!
+//! The parser flattens a function body into the surrounding segments, so
+//! `f() { rm -rf "$1"; }; f /important` is evaluated as `rm -rf "$1"` and
+//! then `f /important`, an unknown command. [`Functions`] keeps the
+//! definitions the parser records so that a call to `f` is also evaluated
+//! as its body, with the call's arguments put in for `$1`, `$@` and
+//! friends.
+
+use agent_shell_parser::parse::ParsedPipeline;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Calls nested deeper than this (a function calling itself) are not
+/// followed.
+pub(crate) const MAX_DEPTH: usize = 4;
+
+/// Function bodies by name, and how deep in function calls evaluation is.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Functions {
+    bodies: Arc<HashMap<String, String>>,
+    pub(crate) depth: usize,
+}
+
+impl Functions {
+    /// Collect the function definitions in `pipeline` and the pipelines
+    /// nested in it. Later definitions of a name replace earlier ones, as
+    /// in the shell.
+    pub(crate) fn from_pipeline(pipeline: &ParsedPipeline) -> Self {
+        let mut bodies = HashMap::new();
+        collect(pipeline, &mut bodies);
+        if bodies.is_empty() {
+            return Self::default();
+        }
+        Self {
+            bodies: Arc::new(bodies),
+            depth: 0,
+        }
+    }
+
+    /// Whether no functions are defined.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bodies.is_empty()
+    }
+
+    /// The body of `name` with `args` put in for the positional parameters,
+    /// if `name` is a defined function.
+    pub(crate) fn call(&self, name: &str, args: &[&str]) -> Option<String> {
+        self.bodies.get(name).map(|body| substitute(body, args))
+    }
+
+    /// The same definitions, one call deeper.
+    pub(crate) fn nested(&self) -> Self {
+        Self {
+            bodies: Arc::clone(&self.bodies),
+            depth: self.depth + 1,
+        }
+    }
+}
+
+fn collect(pipeline: &ParsedPipeline, bodies: &mut HashMap<String, String>) {
+    for definition in &pipeline.functions {
+        bodies.insert(definition.name.clone(), definition.body.clone());
+    }
+    let nested = pipeline
+        .structural_substitutions
+        .iter()
+        .chain(pipeline.segments.iter().flat_map(|s| &s.substitutions));
+    for sub in nested {
+        collect(&sub.pipeline, bodies);
+    }
+}
+
+/// Put `args` in for `$1`…`$9`, `${1}`…`${9}`, `$@` and `$*`. An argument
+/// the shell would split or expand is left as the parameter, which
+/// evaluates as unknown rather than as something it isn't.
+fn substitute(body: &str, args: &[&str]) -> String {
+    let plain = |arg: &&str| {
+        !arg.is_empty()
+            && arg
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,~".contains(c))
+    };
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        let (param, len) = match after.as_bytes() {
+            [b'{', d @ b'1'..=b'9', b'}', ..] => (Some(*d), 3),
+            [d @ (b'1'..=b'9' | b'@' | b'*'), ..] => (Some(*d), 1),
+            _ => (None, 0),
+        };
+        let value = match param {
+            Some(b'@' | b'*') if args.iter().all(plain) => Some(args.join(" ")),
+            Some(d @ b'1'..=b'9') => {
+                let arg = args.get(usize::from(d - b'1')).copied().unwrap_or_default();
+                (arg.is_empty() || plain(&arg)).then(|| arg.to_string())
+            }
+            _ => None,
+        };
+        match value {
+            Some(value) => {
+                out.push_str(&value);
+                rest = &after[len..];
+            }
+            None => {
+                out.push('$');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_shell_parser::parse::parse_with_substitutions;
+
+    fn defined(command: &str) -> Functions {
+        Functions::from_pipeline(&parse_with_substitutions(command).unwrap())
+    }
+
+    #[test]
+    fn collects_both_definition_forms() {
+        let functions = defined("f() { rm -rf \"$1\"; }; function g { ls; }; h() ( cd x ); f /a");
+        assert_eq!(
+            functions.call("f", &["/a"]).as_deref(),
+            Some("rm -rf \"/a\";")
+        );
+        assert_eq!(functions.call("g", &[]).as_deref(), Some("ls;"));
+        assert_eq!(functions.call("h", &[]).as_deref(), Some("( cd x )"));
+        assert_eq!(functions.call("ls", &[]), None);
+        assert!(defined("ls -la").is_empty());
+        let nested = defined("echo $(f() { ls; }; f)");
+        assert_eq!(nested.call("f", &[]).as_deref(), Some("ls;"));
+    }
+
+    #[test]
+    fn substitutes_positional_parameters() {
+        assert_eq!(
+            substitute("cp $1 ${2} && echo $@", &["a", "b"]),
+            "cp a b && echo a b"
+        );
+        assert_eq!(substitute("rm $3 $HOME $$", &["a"]), "rm  $HOME $$");
+        assert_eq!(substitute("rm \"$1\"", &["$(reboot)"]), "rm \"$1\"");
+        assert_eq!(substitute("echo $*", &["a b"]), "echo $*");
+    }
+}
@@ -0,0 +1,199 @@
+//! Organization-specific guidance for ASK and DENY reasons.
+//!
+//! Each `[guidance]` entry maps a command pattern to a template. A pattern is
+//! the command name, then subcommand words, then flags: `terraform apply`
+//! matches `terraform -chdir=infra apply -auto-approve`, and
+//! `git push --force` matches any force-push. The subcommand words must be
+//! the leading operands in order; flags may appear anywhere. When several
+//! patterns match, the one with the most words wins.
+//!
+//! Templates interpolate `{reason}` (the built-in reason) and `{command}`
+//! (the command line). A template without `{reason}` is appended to it.
+
+use crate::config::GuidanceConfig;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+
+/// One parsed `[guidance]` entry.
+#[derive(Debug, Clone)]
+struct GuidanceRule {
+    /// Command name (matched against the base command).
+    command: String,
+    /// Subcommand words that must lead the operands, in order.
+    operands: Vec<String>,
+    /// Flags that must appear somewhere in the arguments.
+    flags: Vec<String>,
+    /// Template with `{reason}` / `{command}` placeholders.
+    template: String,
+}
+
+impl GuidanceRule {
+    /// Number of pattern words, used to prefer more specific rules.
+    fn specificity(&self) -> usize {
+        1 + self.operands.len() + self.flags.len()
+    }
+
+    fn matches(&self, ctx: &CommandContext) -> bool {
+        if ctx.base_command != self.command {
+            return false;
+        }
+        let args = ctx.args();
+        let mut operands = args.iter().filter(|w| !w.is_flag());
+        self.operands
+            .iter()
+            .all(|want| operands.next().is_some_and(|w| w == want.as_str()))
+            && self
+                .flags
+                .iter()
+                .all(|flag| args.iter().any(|w| w == flag.as_str()))
+    }
+}
+
+/// Matches commands against `[guidance]` patterns and decorates reasons.
+#[derive(Debug, Clone, Default)]
+pub struct GuidancePolicy {
+    /// Rules, most specific first.
+    rules: Vec<GuidanceRule>,
+}
+
+impl GuidancePolicy {
+    /// Build a guidance policy from configuration. Empty patterns and
+    /// templates are ignored.
+    pub fn from_config(config: &GuidanceConfig) -> Self {
+        let mut rules: Vec<GuidanceRule> = config
+            .rules
+            .iter()
+            .filter(|(_, template)| !template.is_empty())
+            .filter_map(|(pattern, template)| {
+                let mut words = pattern.split_whitespace();
+                let command = words.next()?.to_string();
+                let (flags, operands): (Vec<String>, Vec<String>) =
+                    words.map(String::from).partition(|w| w.starts_with('-'));
+                Some(GuidanceRule {
+                    command,
+                    operands,
+                    flags,
+                    template: template.clone(),
+                })
+            })
+            .collect();
+        // Most specific first; ties broken by pattern text for determinism.
+        rules.sort_by(|a, b| {
+            b.specificity()
+                .cmp(&a.specificity())
+                .then_with(|| a.operands.cmp(&b.operands))
+                .then_with(|| a.flags.cmp(&b.flags))
+        });
+        Self { rules }
+    }
+
+    /// Attach the best-matching guidance to an ASK or DENY result.
+    /// ALLOW results and unmatched commands are returned unchanged.
+    pub fn apply(&self, ctx: &CommandContext, mut result: RuleMatch) -> RuleMatch {
+        if result.decision == Decision::Allow {
+            return result;
+        }
+        let Some(rule) = self.rules.iter().find(|r| r.matches(ctx)) else {
+            return result;
+        };
+        let command = CommandContext::join_words(ctx.words.iter().map(|w| w.as_str()));
+        let guidance = rule.template.replace("{command}", &command);
+        result.reason = if guidance.contains("{reason}") {
+            guidance.replace("{reason}", &result.reason)
+        } else {
+            format!("{}: {guidance}", result.reason)
+        };
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn policy(rules: &[(&str, &str)]) -> GuidancePolicy {
+        GuidancePolicy::from_config(&GuidanceConfig {
+            rules: rules
+                .iter()
+                .map(|(p, t)| (p.to_string(), t.to_string()))
+                .collect::<HashMap<_, _>>(),
+        })
+    }
+
+    fn apply(policy: &GuidancePolicy, cmd: &str, decision: Decision) -> String {
+        let ctx = CommandContext::from_command(cmd);
+        policy
+            .apply(
+                &ctx,
+                RuleMatch {
+                    decision,
+                    reason: "built-in reason".into(),
+                },
+            )
+            .reason
+    }
+
+    #[test]
+    fn appends_guidance_without_placeholder() {
+        let p = policy(&[("terraform apply", "must go through CI")]);
+        assert_eq!(
+            apply(&p, "terraform apply -auto-approve", Decision::Ask),
+            "built-in reason: must go through CI"
+        );
+    }
+
+    #[test]
+    fn interpolates_reason_and_command() {
+        let p = policy(&[("kubectl delete", "{command} blocked ({reason})")]);
+        assert_eq!(
+            apply(&p, "kubectl delete ns prod", Decision::Deny),
+            "kubectl delete ns prod blocked (built-in reason)"
+        );
+    }
+
+    #[test]
+    fn operands_must_lead_in_order() {
+        let p = policy(&[("terraform apply", "x")]);
+        assert_ne!(
+            apply(&p, "terraform -chdir=infra apply", Decision::Ask),
+            "built-in reason"
+        );
+        assert_eq!(
+            apply(&p, "terraform plan apply", Decision::Ask),
+            "built-in reason"
+        );
+        assert_eq!(apply(&p, "terraform", Decision::Ask), "built-in reason");
+    }
+
+    #[test]
+    fn flags_may_appear_anywhere() {
+        let p = policy(&[("git push --force", "no force-pushes")]);
+        assert_eq!(
+            apply(&p, "git push origin main --force", Decision::Ask),
+            "built-in reason: no force-pushes"
+        );
+        assert_eq!(
+            apply(&p, "git push origin main", Decision::Ask),
+            "built-in reason"
+        );
+    }
+
+    #[test]
+    fn most_specific_pattern_wins() {
+        let p = policy(&[("git", "general"), ("git push", "push-specific")]);
+        assert_eq!(
+            apply(&p, "git push", Decision::Ask),
+            "built-in reason: push-specific"
+        );
+        assert_eq!(
+            apply(&p, "git commit", Decision::Ask),
+            "built-in reason: general"
+        );
+    }
+
+    #[test]
+    fn allow_is_untouched() {
+        let p = policy(&[("git", "general")]);
+        assert_eq!(apply(&p, "git status", Decision::Allow), "built-in reason");
+    }
+}
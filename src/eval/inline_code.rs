@@ -0,0 +1,227 @@
+//! Inline code extraction and pattern scanning for interpreter specs.
+//!
+//! Interpreters accept program text on the command line: `python -c`,
+//! `node -e`, `perl -e`, `ruby -e`, or an awk program operand. An
+//! [`InlineCodeScanner`](crate::eval::inline_code::InlineCodeScanner) built
+//! from one `[inline_code.<language>]` table pulls those payloads out of the
+//! arguments and searches them for the table's `deny` and `ask` substrings,
+//! so every interpreter shares one engine.
+
+use agent_shell_parser::parse::Word;
+
+use crate::config::InlineLanguageConfig;
+
+/// A pattern found in inline code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlineMatch<'a> {
+    /// Matched a `deny` pattern (carries the pattern).
+    Deny(&'a str),
+    /// Matched an `ask` pattern (carries the pattern).
+    Ask(&'a str),
+}
+
+/// Extracts and scans the inline code of one language's interpreters.
+#[derive(Debug, Clone, Default)]
+pub struct InlineCodeScanner {
+    /// Flags whose value is inline code.
+    code_flags: Vec<String>,
+    /// Other flags that consume a value.
+    value_flags: Vec<String>,
+    /// Whether the first operand is the program text.
+    program_positional: bool,
+    /// Patterns that deny.
+    deny: Vec<String>,
+    /// Patterns that require confirmation.
+    ask: Vec<String>,
+}
+
+impl InlineCodeScanner {
+    /// Build a scanner from one `[inline_code.<language>]` table.
+    pub fn from_config(config: &InlineLanguageConfig) -> Self {
+        Self {
+            code_flags: config.code_flags.clone(),
+            value_flags: config.value_flags.clone(),
+            program_positional: config.program_positional,
+            deny: config.deny.clone(),
+            ask: config.ask.clone(),
+        }
+    }
+
+    /// Inline code payloads in `args` (the words after the command name).
+    ///
+    /// Flags are walked until the first operand, which is either the program
+    /// text (`program_positional`) or a script path; everything after it
+    /// belongs to the program. Short flag clusters are expanded
+    /// (`perl -ne '...'`, `python -Bc '...'`).
+    pub fn payloads<'a>(&self, args: &'a [Word]) -> Vec<&'a str> {
+        let is = |list: &[String], flag: &str| list.iter().any(|f| f == flag);
+        let mut payloads = Vec::new();
+        let mut iter = args.iter().map(Word::as_str);
+        while let Some(w) = iter.next() {
+            if w == "--" {
+                if self.program_positional
+                    && payloads.is_empty()
+                    && let Some(program) = iter.next()
+                {
+                    payloads.push(program);
+                }
+                break;
+            }
+            if w.starts_with("--") {
+                let (name, inline) = match w.split_once('=') {
+                    Some((n, v)) => (n, Some(v)),
+                    None => (w, None),
+                };
+                if is(&self.code_flags, name) {
+                    payloads.extend(inline.or_else(|| iter.next()));
+                } else if is(&self.value_flags, name) && inline.is_none() {
+                    iter.next();
+                }
+                continue;
+            }
+            if w.len() > 1 && w.starts_with('-') {
+                let cluster = &w[1..];
+                for (i, c) in cluster.char_indices() {
+                    let flag = format!("-{c}");
+                    let rest = &cluster[i + c.len_utf8()..];
+                    if is(&self.code_flags, &flag) {
+                        payloads.extend(if rest.is_empty() {
+                            iter.next()
+                        } else {
+                            Some(rest)
+                        });
+                        break;
+                    }
+                    if is(&self.value_flags, &flag) {
+                        if rest.is_empty() {
+                            iter.next();
+                        }
+                        break;
+                    }
+                }
+                continue;
+            }
+            if self.program_positional && payloads.is_empty() {
+                payloads.push(w);
+            }
+            break;
+        }
+        payloads
+    }
+
+    /// Scan the inline code in `args`. A `deny` pattern anywhere wins over
+    /// an `ask` pattern; returns `None` if there is no inline code or no
+    /// pattern matches.
+    pub fn scan(&self, args: &[Word]) -> Option<InlineMatch<'_>> {
+        let payloads = self.payloads(args);
+        find_pattern(&self.deny, &payloads)
+            .map(InlineMatch::Deny)
+            .or_else(|| find_pattern(&self.ask, &payloads).map(InlineMatch::Ask))
+    }
+}
+
+/// The first pattern that occurs in any payload.
+fn find_pattern<'p>(patterns: &'p [String], payloads: &[&str]) -> Option<&'p str> {
+    patterns
+        .iter()
+        .find(|p| payloads.iter().any(|code| code.contains(p.as_str())))
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::eval::CommandContext;
+
+    fn scanner(lang: &str) -> InlineCodeScanner {
+        InlineCodeScanner::from_config(&Config::default_config().inline_code.languages[lang])
+    }
+
+    fn payloads(lang: &str, cmd: &str) -> Vec<String> {
+        let ctx = CommandContext::from_command(cmd);
+        scanner(lang)
+            .payloads(ctx.args())
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    fn scan(lang: &str, cmd: &str) -> Option<String> {
+        let ctx = CommandContext::from_command(cmd);
+        scanner(lang).scan(ctx.args()).map(|m| format!("{m:?}"))
+    }
+
+    #[test]
+    fn python_c_payload() {
+        assert_eq!(
+            payloads("python", "python3 -c 'print(1)'"),
+            vec!["print(1)"]
+        );
+        assert_eq!(payloads("python", "python -Bc 'x=1'"), vec!["x=1"]);
+        assert_eq!(payloads("python", "python -W ignore -c 'x=1'"), vec!["x=1"]);
+    }
+
+    #[test]
+    fn script_arguments_are_not_code() {
+        assert!(payloads("python", "python script.py -c 'import pty'").is_empty());
+        assert!(payloads("node", "node app.js -e 'x'").is_empty());
+    }
+
+    #[test]
+    fn perl_clustered_and_repeated() {
+        assert_eq!(
+            payloads("perl", "perl -ne 'print' -e 'exit' file"),
+            vec!["print", "exit"]
+        );
+        assert_eq!(payloads("perl", "perl -MData::Dumper -e 'x'"), vec!["x"]);
+    }
+
+    #[test]
+    fn node_long_flags() {
+        assert_eq!(payloads("node", "node --eval 'a'"), vec!["a"]);
+        assert_eq!(payloads("node", "node --print=1+1"), vec!["1+1"]);
+        assert_eq!(payloads("node", "node -r dotenv/config -e 'b'"), vec!["b"]);
+    }
+
+    #[test]
+    fn awk_program_operand() {
+        assert_eq!(payloads("awk", "awk '{print $1}' f"), vec!["{print $1}"]);
+        assert_eq!(
+            payloads("awk", "awk -F : '{print $1}' f"),
+            vec!["{print $1}"]
+        );
+        assert_eq!(
+            payloads("awk", "awk -F: -v x=1 '{print x}'"),
+            vec!["{print x}"]
+        );
+        assert_eq!(payloads("awk", "gawk -e 'BEGIN{}'"), vec!["BEGIN{}"]);
+    }
+
+    #[test]
+    fn deny_wins_over_ask() {
+        assert_eq!(
+            scan(
+                "python",
+                "python -c 'import os,pty,socket;s=socket.socket();os.dup2(s.fileno(),0);pty.spawn(\"sh\")'"
+            )
+            .as_deref(),
+            Some("Deny(\"pty.spawn\")")
+        );
+    }
+
+    #[test]
+    fn ask_pattern() {
+        assert_eq!(
+            scan("awk", "awk 'BEGIN{system(\"id\")}'").as_deref(),
+            Some("Ask(\"system(\")")
+        );
+    }
+
+    #[test]
+    fn clean_code_matches_nothing() {
+        assert_eq!(scan("python", "python3 -c 'print(2+2)'"), None);
+        assert_eq!(scan("awk", "awk '{print $2}' file"), None);
+        assert_eq!(scan("python", "python script.py"), None);
+    }
+}
@@ -0,0 +1,309 @@
+//! Interactive privileged shells: `sudo -i`, `sudo -s`, `sudo su`,
+//! `sudo bash`, `doas -s`, a bare `su` or `pkexec`.
+//!
+//! Every command typed into such a shell runs unseen, so these get the
+//! `[wrappers] interactive_shell` decision rather than the wrapper's floor.
+//! A shell given a command (`sudo bash -c ...`, `su -c ...`) or a script
+//! isn't interactive and is left to the usual wrapper handling.
+
+use std::borrow::Cow;
+
+use crate::commands::args::dequote;
+
+/// Shells that read commands interactively when given no command or script.
+const SHELLS: &[&str] = &[
+    "sh", "bash", "zsh", "fish", "dash", "ksh", "mksh", "ash", "csh", "tcsh", "nu", "pwsh",
+    "elvish", "xonsh",
+];
+
+/// Whether `words` (a privilege-escalating wrapper and its arguments)
+/// open an interactive shell.
+pub(crate) fn opens_shell(words: &[&str]) -> bool {
+    let Some((first, rest)) = words.split_first() else {
+        return false;
+    };
+    match basename(first) {
+        "sudo" => sudo(rest),
+        "doas" => doas(rest),
+        "su" => su(rest),
+        "runuser" => runuser(rest),
+        "pkexec" => pkexec(rest),
+        _ => false,
+    }
+}
+
+/// Whether a command run under a wrapper is itself an interactive shell,
+/// directly or through another wrapper (`sudo su`, `doas sudo -i`).
+pub(crate) fn runs_shell(words: &[&str]) -> bool {
+    let words = skip_assignments(words);
+    match words.split_first() {
+        Some((first, rest)) if SHELLS.contains(&basename(first)) => shell(rest),
+        _ => opens_shell(words),
+    }
+}
+
+fn basename(word: &str) -> &str {
+    word.rsplit('/').next().unwrap_or(word)
+}
+
+/// `sudo VAR=value cmd` passes the assignments in the environment.
+fn skip_assignments<'a, 'b>(words: &'a [&'b str]) -> &'a [&'b str] {
+    let start = words
+        .iter()
+        .position(|w| !w.split_once('=').is_some_and(|(k, _)| is_name(k)))
+        .unwrap_or(words.len());
+    &words[start..]
+}
+
+fn is_name(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Options given before the command.
+#[derive(Default)]
+struct Seen<'a> {
+    /// Short option letters.
+    short: String,
+    /// Long option names, without `--` or an inline `=value`.
+    long: Vec<&'a str>,
+}
+
+impl Seen<'_> {
+    fn has(&self, short: char, long: &str) -> bool {
+        self.short.contains(short) || self.long.contains(&long)
+    }
+}
+
+/// Split leading options from the command, given the short options that
+/// take a value and the long ones that do.
+fn options<'a, 'b>(
+    words: &'a [&'b str],
+    short_value: &str,
+    long_value: &[&str],
+) -> (Seen<'b>, &'a [&'b str]) {
+    let mut seen = Seen::default();
+    let mut i = 0;
+    while let Some(word) = words.get(i) {
+        i += 1;
+        if *word == "--" {
+            break;
+        }
+        if let Some(long) = word.strip_prefix("--") {
+            match long.split_once('=') {
+                Some((name, _)) => seen.long.push(name),
+                None => {
+                    if long_value.contains(&long) {
+                        i += 1;
+                    }
+                    seen.long.push(long);
+                }
+            }
+            continue;
+        }
+        let Some(cluster) = word.strip_prefix('-').filter(|c| !c.is_empty()) else {
+            i -= 1;
+            break;
+        };
+        for (at, c) in cluster.char_indices() {
+            seen.short.push(c);
+            if short_value.contains(c) {
+                if at + c.len_utf8() == cluster.len() {
+                    i += 1;
+                }
+                break;
+            }
+        }
+    }
+    (seen, &words[i.min(words.len())..])
+}
+
+fn sudo(args: &[&str]) -> bool {
+    let (seen, command) = options(
+        args,
+        "ugphCDrtUT",
+        &[
+            "user",
+            "group",
+            "host",
+            "prompt",
+            "chdir",
+            "close-from",
+            "role",
+            "type",
+            "other-user",
+            "command-timeout",
+        ],
+    );
+    let command = skip_assignments(command);
+    if command.is_empty() {
+        seen.has('i', "login") || seen.has('s', "shell")
+    } else {
+        runs_shell(command)
+    }
+}
+
+fn doas(args: &[&str]) -> bool {
+    let (seen, command) = options(args, "uC", &[]);
+    if command.is_empty() {
+        seen.short.contains('s')
+    } else {
+        runs_shell(command)
+    }
+}
+
+fn pkexec(args: &[&str]) -> bool {
+    let (_, command) = options(args, "", &["user"]);
+    // With no program, pkexec runs the target user's shell.
+    command.is_empty() || runs_shell(command)
+}
+
+fn su(args: &[&str]) -> bool {
+    // `su [options] [-] [user [args]]`: args go to the user's shell.
+    let mut rest = args;
+    let mut positional = 0;
+    loop {
+        let (seen, after) = options(
+            rest,
+            "csgGw",
+            &[
+                "command",
+                "session-command",
+                "shell",
+                "group",
+                "supp-group",
+                "whitelist-environment",
+            ],
+        );
+        if seen.has('c', "command") || seen.long.contains(&"session-command") {
+            return false;
+        }
+        match after.split_first() {
+            // `-` alone is `--login`.
+            Some((&"-", tail)) => rest = tail,
+            Some((_, tail)) => {
+                positional += 1;
+                rest = tail;
+            }
+            None => return positional <= 1,
+        }
+    }
+}
+
+/// `runuser -u user` needs a command; without `-u` it's su.
+fn runuser(args: &[&str]) -> bool {
+    let (seen, _) = options(args, "csgGuw", &["user"]);
+    !seen.has('u', "user") && su(args)
+}
+
+fn shell(args: &[&str]) -> bool {
+    let (seen, operands) = options(args, "oO", &["rcfile", "init-file"]);
+    !seen.short.contains('c') && operands.is_empty()
+}
+
+/// The command string a shell is given with `-c` (`bash -c '...'`,
+/// `sh -lc '...'`), if `words` are such an invocation. bash and sh reject
+/// a string attached to `-c` (`-c'...'`), but it's unquoted and returned
+/// rather than read as option letters.
+pub(crate) fn shell_command<'a>(words: &[&'a str]) -> Option<Cow<'a, str>> {
+    let (first, args) = words.split_first()?;
+    if !SHELLS.contains(&basename(first)) {
+        return None;
+    }
+    let (seen, operands) = options(args, "oO", &["rcfile", "init-file"]);
+    if !seen.short.contains('c') {
+        return None;
+    }
+    let flags = &args[..args.len() - operands.len()];
+    let attached = flags.iter().find_map(|flag| {
+        let cluster = flag.strip_prefix('-').filter(|c| !c.starts_with('-'))?;
+        let at = cluster.find('c')?;
+        Some(&cluster[at + 1..]).filter(|rest| !rest.is_empty())
+    });
+    match attached {
+        Some(string) => Some(dequote(string)),
+        None => operands.first().map(|a| Cow::Borrowed(*a)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opens(command: &str) -> bool {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        opens_shell(&words)
+    }
+
+    #[test]
+    fn shell_command_strings() {
+        let string = |words: &[&str]| shell_command(words).map(Cow::into_owned);
+        assert_eq!(
+            string(&["bash", "-c", "rm -rf /"]).as_deref(),
+            Some("rm -rf /")
+        );
+        assert_eq!(
+            string(&["/bin/sh", "-lc", "ls", "arg0"]).as_deref(),
+            Some("ls")
+        );
+        assert_eq!(string(&["sh", "-c'rm -rf /'"]).as_deref(), Some("rm -rf /"));
+        assert_eq!(string(&["bash", "-lc\"ls -l\""]).as_deref(), Some("ls -l"));
+        assert_eq!(string(&["bash", "script.sh"]), None);
+        assert_eq!(string(&["python3", "-c", "print(1)"]), None);
+    }
+
+    #[test]
+    fn interactive_shells() {
+        for command in [
+            "sudo -i",
+            "sudo -s",
+            "sudo --login",
+            "sudo -u postgres -i",
+            "sudo -iu postgres",
+            "sudo su",
+            "sudo su -",
+            "sudo su - root",
+            "sudo bash",
+            "sudo -E /bin/bash -l",
+            "sudo HOME=/root zsh",
+            "doas -s",
+            "doas sh",
+            "doas sudo -i",
+            "su",
+            "su -l postgres",
+            "runuser -l postgres",
+            "pkexec",
+            "pkexec --user root bash",
+        ] {
+            assert!(opens(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn commands_and_scripts() {
+        for command in [
+            "sudo",
+            "sudo -l",
+            "sudo --user root",
+            "sudo -i whoami",
+            "sudo -s make install",
+            "sudo bash -c ls",
+            "sudo bash -lc ls",
+            "sudo bash script.sh",
+            "sudo apt update",
+            "sudo su -c whoami",
+            "sudo su root script.sh",
+            "doas",
+            "doas ls",
+            "su -c whoami",
+            "su --command=whoami root",
+            "runuser -u postgres -- psql",
+            "runuser -u postgres",
+            "pkexec apt update",
+            "env bash",
+        ] {
+            assert!(!opens(command), "{command}");
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! App and container launchers: `flatpak run`, `snap run`, `toolbox run`
+//! and `distrobox enter`.
+//!
+//! Launching the app or container gets `[wrappers] app_launch`. A command
+//! given to run inside it is evaluated too, and the stricter decision wins.
+
+/// A launcher invocation.
+#[derive(Debug)]
+pub(crate) struct Launch<'a> {
+    /// The launcher and its subcommand, e.g. `flatpak run`.
+    pub(crate) launcher: &'static str,
+    /// The app or container, when named.
+    pub(crate) app: Option<&'a str>,
+    /// The command run inside, if one is given.
+    pub(crate) command: Vec<&'a str>,
+}
+
+/// `flatpak run` options that take a value. Most are given as
+/// `--name=value`, but GLib accepts `--name value` too.
+const FLATPAK_VALUES: &[&str] = &[
+    "--arch",
+    "--branch",
+    "--command",
+    "--commit",
+    "--cwd",
+    "--runtime",
+    "--runtime-version",
+    "--runtime-commit",
+    "--filesystem",
+    "--nofilesystem",
+    "--env",
+    "--unset-env",
+    "--socket",
+    "--nosocket",
+    "--share",
+    "--unshare",
+    "--device",
+    "--nodevice",
+    "--allow",
+    "--disallow",
+    "--persist",
+    "--talk-name",
+    "--own-name",
+    "--system-talk-name",
+    "--system-own-name",
+    "--instance-id-fd",
+    "--parent-pid",
+];
+
+/// Parse `words` (without leading assignments) as a launcher invocation.
+pub(crate) fn launch<'a>(words: &[&'a str]) -> Option<Launch<'a>> {
+    let (first, rest) = words.split_first()?;
+    match (first.rsplit('/').next()?, rest) {
+        ("flatpak", [sub, args @ ..]) if *sub == "run" => Some(flatpak(args)),
+        ("snap", [sub, args @ ..]) if *sub == "run" => Some(snap(args)),
+        ("toolbox", _) => toolbox(rest),
+        ("distrobox", [sub, args @ ..]) if *sub == "enter" => Some(distrobox(args)),
+        ("distrobox-enter", _) => Some(distrobox(rest)),
+        _ => None,
+    }
+}
+
+/// Split leading options from the operands, given the options that take a
+/// separate value. Returns the options seen, with their values, and the
+/// operands after them.
+fn options<'a, 'b>(
+    words: &'a [&'b str],
+    value_flags: &[&str],
+) -> (Vec<(&'b str, Option<&'b str>)>, &'a [&'b str]) {
+    let mut seen = Vec::new();
+    let mut i = 0;
+    while let Some(word) = words.get(i) {
+        if *word == "--" || !word.starts_with('-') || *word == "-" {
+            break;
+        }
+        i += 1;
+        match word.split_once('=') {
+            Some((name, value)) => seen.push((name, Some(value))),
+            None if value_flags.contains(word) => {
+                seen.push((*word, words.get(i).copied()));
+                i += 1;
+            }
+            None => seen.push((*word, None)),
+        }
+    }
+    (seen, &words[i.min(words.len())..])
+}
+
+/// The words after a `--`, if there is one.
+fn after_terminator<'a, 'b>(words: &'a [&'b str]) -> &'a [&'b str] {
+    match words.iter().position(|w| *w == "--") {
+        Some(at) => &words[at + 1..],
+        None => &[],
+    }
+}
+
+/// `flatpak run [options] APP [args]`. The args go to the app, unless
+/// `--command` names the program to run in its sandbox. Anything after a
+/// `--` is read as a command too.
+fn flatpak<'a>(args: &[&'a str]) -> Launch<'a> {
+    let (seen, operands) = options(args, FLATPAK_VALUES);
+    let program = seen
+        .iter()
+        .find(|(name, _)| *name == "--command")
+        .and_then(|(_, value)| *value);
+    let operands = match operands.first() {
+        Some(&"--") => &operands[1..],
+        _ => operands,
+    };
+    let (app, app_args) = match operands.split_first() {
+        Some((app, rest)) => (Some(*app), rest),
+        None => (None, operands),
+    };
+    let command = match program {
+        Some(program) => std::iter::once(program)
+            .chain(app_args.iter().copied())
+            .collect(),
+        None => after_terminator(app_args).to_vec(),
+    };
+    Launch {
+        launcher: "flatpak run",
+        app,
+        command,
+    }
+}
+
+/// `snap run [options] SNAP[.APP] [args]` runs the snap's own app, so
+/// there's no separate command.
+fn snap<'a>(args: &[&'a str]) -> Launch<'a> {
+    let (_, operands) = options(args, &[]);
+    Launch {
+        launcher: "snap run",
+        app: operands.iter().find(|w| **w != "--").copied(),
+        command: Vec::new(),
+    }
+}
+
+/// `toolbox [global options] run [-c NAME] [-d DISTRO] [-r RELEASE]
+/// COMMAND...` and `toolbox enter [NAME]`.
+fn toolbox<'a>(args: &[&'a str]) -> Option<Launch<'a>> {
+    let (_, rest) = options(args, &["--log-level"]);
+    let (sub, args) = rest.split_first()?;
+    let container_flags = ["-c", "--container", "-d", "--distro", "-r", "--release"];
+    let (seen, operands) = options(args, &container_flags);
+    let container = seen
+        .iter()
+        .find(|(name, _)| matches!(*name, "-c" | "--container"))
+        .and_then(|(_, value)| *value);
+    let operands = match operands.first() {
+        Some(&"--") => &operands[1..],
+        _ => operands,
+    };
+    match *sub {
+        "run" => Some(Launch {
+            launcher: "toolbox run",
+            app: container,
+            command: operands.to_vec(),
+        }),
+        "enter" => Some(Launch {
+            launcher: "toolbox enter",
+            app: container.or(operands.first().copied()),
+            command: Vec::new(),
+        }),
+        _ => None,
+    }
+}
+
+/// `distrobox enter [NAME] [options] [-- COMMAND...]`. The older `-e`
+/// works like `--`.
+fn distrobox<'a>(args: &[&'a str]) -> Launch<'a> {
+    let mut app = None;
+    let mut command = Vec::new();
+    let mut words = args.iter().copied();
+    while let Some(word) = words.next() {
+        match word {
+            "--" | "-e" | "--exec" => {
+                command = words.collect();
+                break;
+            }
+            "-n" | "--name" => app = words.next(),
+            "-a" | "--additional-flags" => {
+                words.next();
+            }
+            _ if word.starts_with('-') => {}
+            _ => app = app.or(Some(word)),
+        }
+    }
+    Launch {
+        launcher: "distrobox enter",
+        app,
+        command,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(command: &str) -> Option<(Option<&str>, String)> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        launch(&words).map(|l| (l.app, l.command.join(" ")))
+    }
+
+    #[test]
+    fn flatpak_run() {
+        assert_eq!(
+            parse("flatpak run org.gimp.GIMP photo.png"),
+            Some((Some("org.gimp.GIMP"), String::new()))
+        );
+        assert_eq!(
+            parse("flatpak run --command=sh org.gimp.GIMP -c ls"),
+            Some((Some("org.gimp.GIMP"), "sh -c ls".into()))
+        );
+        assert_eq!(
+            parse("flatpak run --branch stable --command rm org.app -rf /"),
+            Some((Some("org.app"), "rm -rf /".into()))
+        );
+        assert_eq!(
+            parse("flatpak run org.app -- rm -rf /"),
+            Some((Some("org.app"), "rm -rf /".into()))
+        );
+        assert_eq!(parse("flatpak install org.app"), None);
+    }
+
+    #[test]
+    fn snap_run() {
+        assert_eq!(
+            parse("snap run --shell lxd.lxc list"),
+            Some((Some("lxd.lxc"), String::new()))
+        );
+    }
+
+    #[test]
+    fn toolbox() {
+        assert_eq!(
+            parse("toolbox run -c dev make test"),
+            Some((Some("dev"), "make test".into()))
+        );
+        assert_eq!(
+            parse("toolbox --log-level debug run --distro fedora -- ls -la"),
+            Some((None, "ls -la".into()))
+        );
+        assert_eq!(
+            parse("toolbox enter dev"),
+            Some((Some("dev"), String::new()))
+        );
+        assert_eq!(parse("toolbox list"), None);
+    }
+
+    #[test]
+    fn distrobox_enter() {
+        assert_eq!(
+            parse("distrobox enter dev -- rm -rf /"),
+            Some((Some("dev"), "rm -rf /".into()))
+        );
+        assert_eq!(
+            parse("distrobox enter --name dev --no-workdir"),
+            Some((Some("dev"), String::new()))
+        );
+        assert_eq!(
+            parse("distrobox-enter -n dev -- ls"),
+            Some((Some("dev"), "ls".into()))
+        );
+        assert_eq!(
+            parse("distrobox enter dev -e make"),
+            Some((Some("dev"), "make".into()))
+        );
+        assert_eq!(parse("distrobox create dev"), None);
+    }
+}
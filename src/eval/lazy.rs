@@ -0,0 +1,54 @@
+//! Deferred spec construction for the registry.
+//!
+//! Tool specs (path-aware ones in particular) are comparatively expensive
+//! to build, and a hook invocation typically evaluates one or two commands.
+//! A [`LazySpec`] holds the recipe and builds the spec on first lookup.
+
+use crate::commands::CommandSpec;
+use crate::config::Config;
+use std::sync::{Arc, OnceLock};
+
+/// Builds a spec, typically from the registry's shared config.
+type Build = Box<dyn Fn() -> Box<dyn CommandSpec> + Send + Sync>;
+
+/// A command spec that is built the first time it is needed.
+///
+/// One `LazySpec` may be registered under several names (`nc`, `ncat`,
+/// ...), in which case they share the built spec.
+pub(crate) struct LazySpec {
+    spec: OnceLock<Box<dyn CommandSpec>>,
+    build: Build,
+}
+
+impl LazySpec {
+    /// Defer `build` until the spec is first used.
+    pub(crate) fn new(
+        config: &Arc<Config>,
+        build: impl Fn(&Config) -> Box<dyn CommandSpec> + Send + Sync + 'static,
+    ) -> Arc<Self> {
+        let config = Arc::clone(config);
+        Arc::new(Self {
+            spec: OnceLock::new(),
+            build: Box::new(move || build(&config)),
+        })
+    }
+
+    /// Wrap an already-built spec.
+    pub(crate) fn ready(spec: Box<dyn CommandSpec>) -> Arc<Self> {
+        Arc::new(Self {
+            spec: OnceLock::from(spec),
+            build: Box::new(|| unreachable!("spec is prebuilt")),
+        })
+    }
+
+    /// The spec, building it on first call.
+    pub(crate) fn get(&self) -> &dyn CommandSpec {
+        self.spec.get_or_init(|| (self.build)()).as_ref()
+    }
+
+    /// Whether the spec has been built yet.
+    #[cfg(test)]
+    pub(crate) fn is_built(&self) -> bool {
+        self.spec.get().is_some()
+    }
+}
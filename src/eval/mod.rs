@@ -5,22 +5,71 @@
 //! handles compound command decomposition, substitution evaluation, wrapper
 //! command unwrapping, and decision aggregation.
 
+/// `cmd /c` command lines, split by cmd.exe's rules.
+mod cmd_exe;
+/// Condition expressions for `[[rules]]`.
+pub mod condition;
 /// Per-segment evaluation context (base command, args, env vars, redirections).
 pub mod context;
 /// Decision enum and rule match types.
 pub mod decision;
+/// `docker run`/`create`/`exec`: host escalation and inner commands.
+mod docker;
+/// Shell functions defined and called within one command.
+mod functions;
+/// Organization-specific guidance attached to ASK/DENY reasons.
+pub mod guidance;
+/// Inline code extraction and pattern scanning for interpreter specs.
+pub mod inline_code;
+/// Interactive privileged shells (`sudo -i`, `sudo su`, `sudo bash`).
+mod interactive;
+/// App and container launchers (`flatpak run`, `toolbox run`).
+mod launchers;
+/// Specs built on first use.
+mod lazy;
+/// Network destination classification against configured domain lists.
+pub mod network;
+/// Path classification against configured path lists (sensitive, ...).
+pub mod paths;
+/// `[redirection]` targets that don't count as writes.
+mod redirect;
+/// Conditional `[[rules]]` evaluated before command specs.
+pub mod rules;
+/// Time windows that restrict when `[[rules]]` apply.
+pub mod schedule;
+/// Evaluation trace for `--trace`.
+mod trace;
+/// Per-directory trust tiers referenced by `[[rules]]` conditions.
+pub mod trust;
+/// Option schemas for wrappers, and `su -c` command strings.
+mod wrappers;
+/// Rewrites for zsh syntax, with `settings.shell = "zsh"`.
+mod zsh;
 
 pub use context::CommandContext;
-pub use decision::{Decision, RuleMatch};
+pub use decision::{Decision, Evaluation, RuleMatch, SegmentResult, Timings};
 
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use crate::commands::CommandSpec;
-use crate::config::Config;
+use crate::commands::simple::SimpleCommandSpec;
+use crate::commands::tools::{ansible, gh, git};
+use crate::config::{Config, Shell};
 use agent_shell_parser::parse;
 use agent_shell_parser::parse::{
-    CommandConfig, Operator, ParsedPipeline, ResolvedCommand, ShellSegment, WrapperSpec,
+    CommandConfig, GroupKind, Operator, ParsedPipeline, ResolvedCommand, ShellSegment, Word,
+    WrapperSpec,
 };
+use cmd_exe::CmdExePolicy;
+use functions::Functions;
+use guidance::GuidancePolicy;
+use lazy::LazySpec;
+use paths::PathPolicy;
+use redirect::RedirectPolicy;
+use rules::{RuleOutcome, RulePolicy};
+use trace::Trace;
 
 /// Check whether a command segment is likely to succeed unconditionally.
 ///
@@ -133,14 +182,141 @@ fn extract_unset_vars(words: &[parse::Word]) -> Vec<&str> {
     result
 }
 
+/// Shells that execute a script read from stdin (`curl ... | sh`).
+const STDIN_SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh"];
+
+/// Suggest downloading to a file instead of piping a fetch into a shell.
+///
+/// Returns a suggestion when `shell` is a stdin shell and `fetch` is a curl
+/// or wget segment feeding it: save the script with `-o` / `-O`, inspect it,
+/// then run the file.
+fn download_then_inspect(fetch: &ShellSegment, shell: &CommandContext) -> Option<String> {
+    if !STDIN_SHELLS.contains(&shell.base_command.as_str()) {
+        return None;
+    }
+    let output_flag = match CommandContext::base_command_from_words(&fetch.words).as_str() {
+        "curl" => "-o",
+        "wget" => "-O",
+        _ => return None,
+    };
+    let download = CommandContext::join_words(
+        fetch
+            .words
+            .iter()
+            .map(parse::Word::as_str)
+            .chain([output_flag, "install.sh"]),
+    );
+    Some(format!(
+        "download first with `{download}`, inspect install.sh, then run `{} install.sh`",
+        shell.base_command
+    ))
+}
+
+/// A trace line for a spec's decision.
+fn spec_step(name: &str, result: &RuleMatch) -> String {
+    format!(
+        "spec {name} -> {}: {}",
+        result.decision.label(),
+        result.reason
+    )
+}
+
+/// A segment's text as it will run: trimmed, with backslash-newline line
+/// continuations joined the way the shell joins them. Comments and blank
+/// lines never reach a segment; the parser drops them. Single-quoted text,
+/// where a backslash is literal, and heredoc bodies are left as written.
+fn segment_text(raw: &str) -> String {
+    let raw = raw.trim();
+    let mut out = String::with_capacity(raw.len());
+    let (mut single, mut double) = (false, false);
+    let mut chars = raw.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' if !double => single = !single,
+            '"' if !single => double = !double,
+            '<' if !single && !double && raw[i..].starts_with("<<") => {
+                out.push_str(&raw[i..]);
+                break;
+            }
+            '\\' if !single => {
+                match chars.next() {
+                    Some((_, '\n')) if double => {}
+                    Some((_, '\n')) => {
+                        // Indentation of the continued line only separates
+                        // words; show it as one space.
+                        let mut indented = false;
+                        while chars.next_if(|&(_, c)| c == ' ' || c == '\t').is_some() {
+                            indented = true;
+                        }
+                        if indented && !out.ends_with([' ', '\t']) {
+                            out.push(' ');
+                        }
+                    }
+                    Some((_, escaped)) => {
+                        out.push(c);
+                        out.push(escaped);
+                    }
+                    None => out.push(c),
+                }
+                continue;
+            }
+            _ => {}
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// What a glob covers: `*/` → "every directory in /work", `logs/*.gz` →
+/// "*.gz in /work/logs".
+fn wildcard_scope(glob: &str, cwd: &std::path::Path) -> String {
+    let glob = glob.replace(['"', '\''], "");
+    let (dir, pattern) = match glob
+        .find(['*', '?', '['])
+        .and_then(|i| glob[..i].rfind('/'))
+    {
+        Some(0) => ("/", &glob[1..]),
+        Some(slash) => (&glob[..slash], &glob[slash + 1..]),
+        None => (".", glob.as_str()),
+    };
+    let dir = paths::resolve_in(dir, cwd);
+    let dir = dir.display();
+    match pattern {
+        "*" => format!("everything in {dir}"),
+        "*/" => format!("every directory in {dir}"),
+        _ => format!("{pattern} in {dir}"),
+    }
+}
+
+/// Append a suggestion to a reason string, if there is one.
+fn with_suggestion(reason: &str, suggestion: Option<&str>) -> String {
+    match suggestion {
+        Some(s) => format!("{reason} (suggestion: {s})"),
+        None => reason.to_string(),
+    }
+}
+
 /// Registry of all command specs, keyed by command name.
 ///
 /// Built from [`Config`] via [`from_config`](Self::from_config).
 /// Handles single-command evaluation, compound command decomposition,
 /// wrapper command unwrapping, substitution evaluation, and decision aggregation.
+///
+/// # Thread safety
+///
+/// The registry is `Send + Sync` and evaluation takes `&self`: nothing is
+/// mutated while evaluating except each tool spec's one-time construction,
+/// which goes through a `OnceLock`. A daemon or multi-threaded host can
+/// build one registry, finish configuring it (e.g.
+/// [`set_escalate_deny`](Self::set_escalate_deny)), then hand out clones of
+/// the [`into_shared`](Self::into_shared) handle and evaluate concurrently.
 pub struct CommandRegistry {
-    /// Command name → evaluation spec (git, cargo, kubectl, gh, simple, deny).
-    specs: HashMap<String, Box<dyn CommandSpec>>,
+    /// Flat-list command name → decision. These all share the
+    /// [`SimpleCommandSpec`] for their decision rather than each owning one.
+    simple: HashMap<String, Decision>,
+    /// Command name → tool spec (git, cargo, kubectl, gh, ...), built on
+    /// first use. Takes precedence over `simple`.
+    specs: HashMap<String, Arc<LazySpec>>,
     /// Wrapper commands (e.g. `xargs`, `sudo`, `env`) → floor decision.
     /// These execute their arguments as subcommands and are handled
     /// separately from regular specs.
@@ -149,67 +325,337 @@ pub struct CommandRegistry {
     /// default config extended with any cc-toolgate wrappers that aren't
     /// already known to the parser.
     resolve_config: CommandConfig,
+    /// Config for unwrapping `su` and `runuser` themselves. The merged one
+    /// leaves them out, so the parser stops there and `sudo su -c '...'`
+    /// reaches the command-string handling.
+    su_resolve_config: CommandConfig,
     /// When true, DENY decisions are escalated to ASK.
     escalate_deny: bool,
+    /// `[guidance]` templates appended to ASK/DENY reasons.
+    guidance: GuidancePolicy,
+    /// `[[rules]]` checked before wrappers and specs.
+    rules: RulePolicy,
+    /// `[groups]` floors for subshells and brace groups.
+    groups: crate::config::GroupsConfig,
+    /// `[wrappers] interactive_shell` decision for `sudo -i` and the like.
+    interactive_shell: Decision,
+    /// `[wrappers] app_launch` floor for `flatpak run` and the like.
+    app_launch: Decision,
+    /// `[docker]` decisions for `docker run`, `create` and `exec`. The
+    /// other subcommands are left to `DockerSpec`.
+    docker: crate::config::DockerConfig,
+    /// `[ansible]` decisions for ad-hoc tasks that run a command line. The
+    /// rest is left to `AnsibleSpec`.
+    ansible: crate::config::AnsibleConfig,
+    /// `[wildcards]` commands whose unquoted globs escalate.
+    wildcards: crate::config::WildcardsConfig,
+    /// Whether commands are rewritten from zsh syntax before parsing.
+    shell: Shell,
+    /// `[cmd_exe]` decisions for `cmd /c` command lines.
+    cmd_exe: CmdExePolicy,
+    /// `[paths]` classification for output redirection targets.
+    paths: PathPolicy,
+    /// `[redirection]` targets that don't count as writes.
+    redirects: RedirectPolicy,
     /// Path to the project overlay file that contributed to this config,
     /// if one was loaded. Used to annotate ASK decisions with provenance.
     project_overlay_path: Option<std::path::PathBuf>,
+    /// Consulted for commands no rule recognizes, instead of asking.
+    fallback: Option<Box<dyn CommandSpec>>,
+    /// The directory commands run in, if not the process's own.
+    cwd: Option<std::path::PathBuf>,
 }
 
 impl CommandRegistry {
     /// Build the registry from configuration.
     pub fn from_config(config: &Config) -> Self {
-        use crate::commands::{
-            simple::SimpleCommandSpec,
-            tools::{cargo::CargoSpec, gh::GhSpec, git::GitSpec, kubectl::KubectlSpec},
+        use crate::commands::tools::{
+            accounts::AccountsSpec,
+            ansible::AnsibleSpec,
+            aws::AwsSpec,
+            bazel::BazelSpec,
+            cargo::CargoSpec,
+            copy::CopySpec,
+            crypto::{GpgSpec, OpensslSpec},
+            curl::CurlSpec,
+            docker::DockerSpec,
+            gh::GhSpec,
+            git::GitSpec,
+            interpreter::InterpreterSpec,
+            jvm::JvmSpec,
+            kubectl::KubectlSpec,
+            network_config::NetworkConfigSpec,
+            nix::NixSpec,
+            packages::{AptSpec, DnfSpec, PacmanSpec},
+            permissions::PermissionsSpec,
+            pip::PipSpec,
+            raw_network::RawNetworkSpec,
+            rm::RmSpec,
+            rsync::RsyncSpec,
+            scp::ScpSpec,
+            ssh_keys::SshKeySpec,
+            systemd::{JournalctlSpec, SystemdCtlSpec},
+            tee::TeeSpec,
+            terraform::TerraformSpec,
+            vcs::VcsSpec,
+            wget::WgetSpec,
         };
 
-        let mut specs: HashMap<String, Box<dyn CommandSpec>> = HashMap::new();
-
-        // Deny commands (registered first, complex specs override if needed)
-        for name in &config.commands.deny {
-            specs.insert(
-                name.clone(),
-                Box::new(SimpleCommandSpec::new(Decision::Deny)),
-            );
-        }
-
-        // Allow commands
-        for name in &config.commands.allow {
-            specs.insert(
-                name.clone(),
-                Box::new(SimpleCommandSpec::new(Decision::Allow)),
-            );
-        }
-
-        // Ask commands
-        for name in &config.commands.ask {
-            specs.insert(
-                name.clone(),
-                Box::new(SimpleCommandSpec::new(Decision::Ask)),
-            );
+        // Flat lists, applied deny, allow, ask: a name in several lists takes
+        // the last one.
+        let mut simple = HashMap::new();
+        for (list, decision) in [
+            (&config.commands.deny, Decision::Deny),
+            (&config.commands.allow, Decision::Allow),
+            (&config.commands.ask, Decision::Ask),
+        ] {
+            for name in list {
+                simple.insert(name.clone(), decision);
+            }
         }
 
-        // Complex command specs (override any simple entry for the same name)
-        specs.insert("git".into(), Box::new(GitSpec::from_config(&config.git)));
-        specs.insert(
-            "cargo".into(),
-            Box::new(CargoSpec::from_config(&config.cargo)),
+        // Complex command specs (override any simple entry for the same name).
+        // Each is built lazily from a shared copy of the config.
+        let shared = Arc::new(config.clone());
+        let mut specs: HashMap<String, Arc<LazySpec>> = HashMap::new();
+        let mut lazy = |names: &[&str], build: fn(&Config, &str) -> Box<dyn CommandSpec>| {
+            for &name in names {
+                let owned = name.to_string();
+                specs.insert(
+                    owned.clone(),
+                    LazySpec::new(&shared, move |c| build(c, &owned)),
+                );
+            }
+        };
+        lazy(&["git"], |c, _| {
+            Box::new(GitSpec::from_config(&c.git, &c.network))
+        });
+        lazy(&["cargo"], |c, _| {
+            Box::new(CargoSpec::from_config(&c.cargo))
+        });
+        lazy(&["kubectl"], |c, _| {
+            Box::new(KubectlSpec::from_config(&c.kubectl))
+        });
+        lazy(&["gh"], |c, _| {
+            Box::new(GhSpec::from_config(&c.gh, &c.network))
+        });
+        lazy(&["hub", "tea", "fj"], |c, name| {
+            let config = match name {
+                "hub" => &c.hub,
+                "tea" => &c.tea,
+                _ => &c.fj,
+            };
+            Box::new(GhSpec::for_tool(name, config, &c.network))
+        });
+        lazy(&["scp"], |c, _| {
+            Box::new(ScpSpec::from_config(&c.scp, &c.paths))
+        });
+        lazy(&["rsync"], |c, _| {
+            Box::new(RsyncSpec::from_config(&c.rsync))
+        });
+        // curl/wget keep their flat-list disposition as a base decision;
+        // the specs add output-file path classification on top.
+        lazy(&["curl"], |c, name| {
+            Box::new(CurlSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.network,
+                &c.paths,
+            ))
+        });
+        lazy(&["wget"], |c, name| {
+            Box::new(WgetSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.network,
+                &c.paths,
+            ))
+        });
+        lazy(&["cp", "mv", "install"], |c, name| {
+            Box::new(CopySpec::from_config(
+                Self::flat_decision(c, name),
+                &c.paths,
+            ))
+        });
+        lazy(&["tee"], |c, name| {
+            Box::new(TeeSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.tee,
+                &c.paths,
+            ))
+        });
+        lazy(&["rm"], |c, name| {
+            Box::new(RmSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.rm,
+                &c.paths,
+            ))
+        });
+        lazy(&["chmod", "chown", "chgrp"], |c, name| {
+            Box::new(PermissionsSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.permissions,
+                &c.paths,
+            ))
+        });
+        lazy(
+            &["apt", "apt-get", "apt-cache", "dpkg", "dpkg-query"],
+            |c, name| Box::new(AptSpec::from_config(Self::flat_decision(c, name), &c.apt)),
+        );
+        lazy(&["dnf", "dnf5", "yum", "microdnf"], |c, name| {
+            Box::new(DnfSpec::from_config(Self::flat_decision(c, name), &c.dnf))
+        });
+        lazy(&["pacman", "yay", "paru"], |c, name| {
+            Box::new(PacmanSpec::new(Self::flat_decision(c, name)))
+        });
+        lazy(&["nix", "nix-env", "nix-collect-garbage"], |c, name| {
+            Box::new(NixSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.network,
+            ))
+        });
+        lazy(
+            &["hostnamectl", "timedatectl", "localectl", "loginctl"],
+            |c, name| Box::new(SystemdCtlSpec::new(Self::flat_decision(c, name))),
+        );
+        lazy(&["journalctl"], |c, name| {
+            Box::new(JournalctlSpec::new(Self::flat_decision(c, name)))
+        });
+        lazy(
+            &[
+                "ip",
+                "ss",
+                "ifconfig",
+                "iptables",
+                "ip6tables",
+                "iptables-save",
+                "ip6tables-save",
+                "iptables-restore",
+                "ip6tables-restore",
+                "nft",
+                "ufw",
+                "firewall-cmd",
+            ],
+            |c, name| {
+                Box::new(NetworkConfigSpec::from_config(
+                    Self::flat_decision(c, name),
+                    &c.firewall,
+                ))
+            },
         );
-        specs.insert(
-            "kubectl".into(),
-            Box::new(KubectlSpec::from_config(&config.kubectl)),
+        lazy(
+            &[
+                "useradd", "adduser", "userdel", "deluser", "usermod", "passwd", "chpasswd",
+                "chage", "chsh", "chfn", "newusers", "groupadd", "addgroup", "groupdel",
+                "delgroup", "groupmod", "gpasswd", "vipw", "vigr",
+            ],
+            |c, name| {
+                Box::new(AccountsSpec::from_config(
+                    Self::flat_decision(c, name),
+                    &c.accounts,
+                ))
+            },
         );
-        specs.insert("gh".into(), Box::new(GhSpec::from_config(&config.gh)));
+        lazy(&["docker", "podman", "nerdctl"], |c, name| {
+            Box::new(DockerSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.docker,
+            ))
+        });
+        lazy(&["aws"], |c, name| {
+            Box::new(AwsSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.aws,
+                &c.paths,
+            ))
+        });
+        lazy(&["terraform", "tofu"], |c, name| {
+            Box::new(TerraformSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.terraform,
+            ))
+        });
+        lazy(
+            &["ansible", "ansible-playbook", "ansible-inventory"],
+            |c, name| {
+                Box::new(AnsibleSpec::from_config(
+                    Self::flat_decision(c, name),
+                    &c.ansible,
+                ))
+            },
+        );
+        lazy(&["gpg", "gpg2"], |c, name| {
+            Box::new(GpgSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.crypto,
+            ))
+        });
+        lazy(&["openssl"], |c, name| {
+            Box::new(OpensslSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.crypto,
+            ))
+        });
+        lazy(&["ssh-keygen", "ssh-add", "ssh-copy-id"], |c, name| {
+            Box::new(SshKeySpec::from_config(
+                Self::flat_decision(c, name),
+                &c.ssh_keys,
+            ))
+        });
+        lazy(&["bazel", "bazelisk"], |c, name| {
+            Box::new(BazelSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.bazel,
+            ))
+        });
+        lazy(&["gradle", "gradlew"], |c, name| {
+            Box::new(JvmSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.gradle,
+            ))
+        });
+        lazy(&["mvn", "mvnw"], |c, name| {
+            Box::new(JvmSpec::from_config(Self::flat_decision(c, name), &c.maven))
+        });
+        lazy(&["svn", "hg"], |c, name| {
+            Box::new(VcsSpec::from_config(Self::flat_decision(c, name), &c.vcs))
+        });
+        lazy(&["pip", "pip3"], |c, name| {
+            Box::new(PipSpec::from_config(
+                Self::flat_decision(c, name),
+                &c.network,
+            ))
+        });
+        // The raw network tools don't depend on their name: share one spec.
+        let raw_network = LazySpec::new(&shared, |c| {
+            Box::new(RawNetworkSpec::from_config(&c.raw_network))
+        });
+        for name in ["nc", "ncat", "netcat", "socat", "telnet"] {
+            specs.insert(name.into(), Arc::clone(&raw_network));
+        }
+        for (language, table) in &config.inline_code.languages {
+            for name in &table.commands {
+                let (language, owned) = (language.clone(), name.clone());
+                specs.insert(
+                    name.clone(),
+                    LazySpec::new(&shared, move |c| {
+                        Box::new(InterpreterSpec::from_config(
+                            Self::flat_decision(c, &owned),
+                            &c.inline_code.languages[&language],
+                        ))
+                    }),
+                );
+            }
+        }
 
         // Wrapper commands: these execute their arguments as subcommands.
         // Remove them from the specs map (they're handled separately in evaluate_single).
         let mut wrappers = HashMap::new();
         for name in &config.wrappers.allow_floor {
+            simple.remove(name);
             specs.remove(name);
             wrappers.insert(name.clone(), Decision::Allow);
         }
         for name in &config.wrappers.ask_floor {
+            simple.remove(name);
             specs.remove(name);
             wrappers.insert(name.clone(), Decision::Ask);
         }
@@ -219,13 +665,46 @@ impl CommandRegistry {
         // that aren't already known to the parser. This lets resolve_command_with
         // handle ALL wrappers — no fallback flag-skipping needed.
         let resolve_config = Self::build_resolve_config(&wrappers);
+        let su_resolve_config = CommandConfig {
+            wrappers: wrappers::command_string_specs(),
+            ..resolve_config.clone()
+        };
 
         Self {
+            simple,
             specs,
             wrappers,
             resolve_config,
+            su_resolve_config,
             escalate_deny: config.settings.escalate_deny,
+            guidance: GuidancePolicy::from_config(&config.guidance),
+            rules: RulePolicy::from_config(&config.rules, &config.trust, &config.schedules),
+            groups: config.groups.clone(),
+            wildcards: config.wildcards.clone(),
+            interactive_shell: config.wrappers.interactive_shell,
+            app_launch: config.wrappers.app_launch,
+            docker: config.docker.clone(),
+            ansible: config.ansible.clone(),
+            shell: config.settings.shell,
+            cmd_exe: CmdExePolicy::from_config(&config.cmd_exe),
+            paths: PathPolicy::from_config(&config.paths),
+            redirects: RedirectPolicy::from_config(&config.redirection, &config.paths),
             project_overlay_path: config.project_overlay_path.clone(),
+            fallback: None,
+            cwd: None,
+        }
+    }
+
+    /// The decision a command would get from the flat `[commands]` lists
+    /// (ASK if it is in none of them).
+    fn flat_decision(config: &Config, name: &str) -> Decision {
+        let listed = |list: &[String]| list.iter().any(|n| n == name);
+        if listed(&config.commands.deny) {
+            Decision::Deny
+        } else if listed(&config.commands.allow) {
+            Decision::Allow
+        } else {
+            Decision::Ask
         }
     }
 
@@ -234,23 +713,66 @@ impl CommandRegistry {
         self.escalate_deny = escalate;
     }
 
+    /// Evaluate commands as run from `cwd` (e.g. the hook input's `cwd`)
+    /// rather than the process's current directory: relative paths and
+    /// redirection targets resolve against it.
+    pub fn set_cwd(&mut self, cwd: Option<std::path::PathBuf>) {
+        self.cwd = cwd;
+    }
+
+    /// Register an out-of-tree spec under `names`, replacing any built-in
+    /// rule for them. All names share the one spec.
+    ///
+    /// This is how plugins (the `plugins` feature) and the external evaluator
+    /// are added; library users can call it directly with their own
+    /// [`CommandSpec`] implementations.
+    pub fn register(&mut self, names: &[String], spec: Box<dyn CommandSpec>) {
+        let spec = LazySpec::ready(spec);
+        for name in names {
+            self.specs.insert(name.clone(), Arc::clone(&spec));
+        }
+    }
+
+    /// Consult `spec` for commands no rule recognizes, instead of asking.
+    pub fn set_fallback(&mut self, spec: Box<dyn CommandSpec>) {
+        self.fallback = Some(spec);
+    }
+
+    /// Freeze the registry into a handle that can be cloned across threads.
+    pub fn into_shared(self) -> Arc<Self> {
+        Arc::new(self)
+    }
+
     /// Look up a spec by exact command name.
     fn get(&self, name: &str) -> Option<&dyn CommandSpec> {
-        self.specs.get(name).map(|b| b.as_ref())
+        match self.specs.get(name) {
+            Some(spec) => Some(spec.get()),
+            None => self
+                .simple
+                .get(name)
+                .map(|d| SimpleCommandSpec::shared(*d) as &dyn CommandSpec),
+        }
     }
 
     /// Build a merged [`CommandConfig`] for `resolve_command_with`.
     ///
-    /// Starts from agent-shell-parser's default config and adds a minimal
+    /// Starts from agent-shell-parser's default config and adds a
     /// [`WrapperSpec`] for any cc-toolgate wrapper that isn't already known
-    /// to the parser. This ensures `resolve_command_with` can handle all
-    /// wrappers without a fallback code path.
+    /// to the parser: its schema if [`wrappers`] has one, else a minimal
+    /// one. This ensures `resolve_command_with` can handle all wrappers
+    /// without a fallback code path. `su` and `runuser` are left out; see
+    /// `su_resolve_config`.
     fn build_resolve_config(wrappers: &HashMap<String, Decision>) -> CommandConfig {
         let mut config = parse::default_command_config().clone();
 
         for name in wrappers.keys() {
             let already_known = config.wrappers.iter().any(|w| w.name == *name);
-            if !already_known {
+            if already_known || wrappers::runs_command_string(name) {
+                continue;
+            }
+            if let Some(spec) = wrappers::spec(name) {
+                config.wrappers.push(spec);
+            } else {
                 // Add a minimal spec: skip leading flags, no value-consuming
                 // flags (conservative — may stop early, which is safe since
                 // the inner command gets evaluated anyway).
@@ -277,27 +799,47 @@ impl CommandRegistry {
     ///
     /// Uses `resolve_command_with` with the merged config that includes both
     /// agent-shell-parser's built-in wrappers and any cc-toolgate-only wrappers.
-    fn extract_wrapped_command(&self, ctx: &CommandContext) -> (String, bool) {
-        let resolved = parse::resolve_command_with(&ctx.words, &self.resolve_config);
+    fn extract_wrapped_command(&self, ctx: &CommandContext) -> (Vec<Word>, bool) {
+        let config = if wrappers::runs_command_string(&ctx.base_command) {
+            &self.su_resolve_config
+        } else {
+            &self.resolve_config
+        };
+        let resolved = parse::resolve_command_with(&ctx.words, config);
         match resolved {
             ResolvedCommand::Resolved(ref parsed) if parsed.command != ctx.base_command => {
-                // Successfully stripped the wrapper — return the inner command
-                (parsed.to_words().join(" "), false)
+                // Successfully stripped the wrapper — return the inner command's
+                // words as-is, so they are not re-tokenized or re-parsed.
+                (parsed.to_words(), false)
             }
             ResolvedCommand::Resolved(_) => {
                 // resolve_command returned the same command (e.g. wrapper with
                 // no inner command, or wrapper not recognized despite config).
-                (String::new(), false)
+                (Vec::new(), false)
             }
             ResolvedCommand::Unanalyzable(_) => {
                 // Unanalyzable (eval, source, shell -c) — signal to caller
-                (String::new(), true)
+                (Vec::new(), true)
             }
             // Future variants: treat as unanalyzable (fail-closed)
-            _ => (String::new(), true),
+            _ => (Vec::new(), true),
         }
     }
 
+    /// Ask the spec for `ctx` for a safer alternative.
+    ///
+    /// Uses the same lookup as [`evaluate_ctx`](Self::evaluate_ctx); wrappers
+    /// and unrecognized commands have no suggestion.
+    fn suggest_ctx(&self, ctx: &CommandContext) -> Option<String> {
+        if ctx.base_command.is_empty() || self.wrapper_floor(&ctx.base_command).is_some() {
+            return None;
+        }
+        let prefix = ctx.base_command.split('.').next().unwrap_or_default();
+        self.get(&ctx.base_command)
+            .or_else(|| self.get(prefix))
+            .and_then(|spec| spec.suggest(ctx))
+    }
+
     /// Apply escalate_deny: DENY → ASK with annotation.
     fn maybe_escalate(&self, mut result: RuleMatch) -> RuleMatch {
         if self.escalate_deny && result.decision == Decision::Deny {
@@ -321,10 +863,24 @@ impl CommandRegistry {
         result
     }
 
+    /// Set a context's redirection to the first of its redirections that
+    /// isn't to a `[redirection]` safe sink or target, if any.
+    fn without_safe_redirects(&self, mut ctx: CommandContext) -> CommandContext {
+        if ctx.cwd.is_none() {
+            ctx.cwd = self.cwd.clone();
+        }
+        let cwd = ctx.working_dir();
+        if ctx.redirections.is_empty() {
+            ctx.redirections.extend(ctx.redirection.take());
+        }
+        ctx.redirection = self.redirects.effective(&ctx.redirections, &cwd);
+        ctx
+    }
+
     /// Evaluate a single (non-compound) command against the registry.
     pub fn evaluate_single(&self, command: &str) -> RuleMatch {
-        let ctx = CommandContext::from_command(command);
-        let result = self.evaluate_ctx(ctx);
+        let ctx = self.without_safe_redirects(CommandContext::from_command(command));
+        let result = self.evaluate_ctx(ctx, &mut Trace::off());
         self.maybe_annotate_project_overlay(result)
     }
 
@@ -332,12 +888,78 @@ impl CommandRegistry {
     ///
     /// This is the core evaluation method. All paths — simple commands,
     /// compound segments, and wrapper-extracted inner commands — converge here.
-    fn evaluate_ctx(&self, ctx: CommandContext) -> RuleMatch {
+    /// Whatever the command decides, unquoted globs in a destructive command
+    /// escalate to `[wildcards]`, and redirecting output into a protected or
+    /// sensitive path denies.
+    fn evaluate_ctx(&self, mut ctx: CommandContext, trace: &mut Trace) -> RuleMatch {
+        if ctx.cwd.is_none() {
+            ctx.cwd = self.cwd.clone();
+        }
+        let result = self.evaluate_command(&ctx, trace);
+        let result = self.check_wildcards(&ctx, result, trace);
+        let Some(path) = ctx.redirect_path() else {
+            return result;
+        };
+        let class = self
+            .paths
+            .classify_write(&path.to_string_lossy(), &ctx.working_dir());
+        if class.decision() != Decision::Deny || result.decision == Decision::Deny {
+            return result;
+        }
+        let target = ctx.redirection.as_ref().map_or("", |r| r.target.as_str());
+        let result = RuleMatch {
+            decision: Decision::Deny,
+            reason: format!("output redirection to {target} ({class})"),
+        };
+        let result = self.guidance.apply(&ctx, result);
+        self.finish(result, trace)
+    }
+
+    /// Escalate a destructive command with unquoted globs to the
+    /// `[wildcards]` decision, naming what each glob covers.
+    fn check_wildcards(
+        &self,
+        ctx: &CommandContext,
+        result: RuleMatch,
+        trace: &mut Trace,
+    ) -> RuleMatch {
+        if ctx.globs.is_empty()
+            || result.decision == Decision::Deny
+            || !self.wildcards.commands.contains(&ctx.base_command)
+        {
+            return result;
+        }
+        let cwd = ctx.working_dir();
+        let scope = ctx
+            .globs
+            .iter()
+            .map(|glob| format!("wildcard {glob}: {}", wildcard_scope(glob, &cwd)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        trace.step(|| format!("[wildcards]: {scope}"));
+        if self.wildcards.decision > result.decision {
+            let result = RuleMatch {
+                decision: self.wildcards.decision,
+                reason: format!("{} with unquoted {scope}", ctx.base_command),
+            };
+            let result = self.guidance.apply(ctx, result);
+            self.finish(result, trace)
+        } else {
+            RuleMatch {
+                reason: format!("{} ({scope})", result.reason),
+                ..result
+            }
+        }
+    }
+
+    /// Decide a command context by rules, wrappers, specs, and fallbacks.
+    fn evaluate_command(&self, ctx: &CommandContext, trace: &mut Trace) -> RuleMatch {
         // Bare variable assignments (e.g. "FOO=bar") are always safe.
         // Check before the empty-command guard: a segment like "VAR=$(cmd)"
         // has base_command="" (the token is parsed as an env var with no
         // command), but it's a valid assignment, not an empty command.
         if ctx.words.len() == 1 && ctx.words[0].as_assignment().is_some() {
+            trace.step(|| "variable assignment -> ALLOW".into());
             return RuleMatch {
                 decision: Decision::Allow,
                 reason: format!("variable assignment: {}", ctx.words[0]),
@@ -345,30 +967,160 @@ impl CommandRegistry {
         }
 
         if ctx.base_command.is_empty() {
+            trace.step(|| "empty command -> ALLOW".into());
             return RuleMatch {
                 decision: Decision::Allow,
                 reason: "empty".into(),
             };
         }
 
+        // Conditional rules decide before anything else for their command.
+        // One that fails to evaluate can't loosen it: the command is
+        // decided without rules and asks at least.
+        match self.rules.evaluate(ctx) {
+            Some(RuleOutcome::Matched(result)) => {
+                trace.step(|| {
+                    format!(
+                        "[[rules]] -> {}: {}",
+                        result.decision.label(),
+                        result.reason
+                    )
+                });
+                let result = self.guidance.apply(ctx, result);
+                return self.finish(result, trace);
+            }
+            Some(RuleOutcome::Failed(failure)) => {
+                trace.step(|| format!("[[rules]]: {failure}"));
+                let result = self.evaluate_unruled(ctx, trace);
+                let result = RuleMatch {
+                    decision: result.decision.max(Decision::Ask),
+                    reason: format!("{failure}; {}", result.reason),
+                };
+                trace.step(|| format!("=> {}: {}", result.decision.label(), result.reason));
+                return result;
+            }
+            None if !self.rules.is_empty() => trace.step(|| "[[rules]]: no match".into()),
+            None => {}
+        }
+        self.evaluate_unruled(ctx, trace)
+    }
+
+    /// Decide a command context by wrappers, specs, and fallbacks, as if
+    /// there were no `[[rules]]`.
+    fn evaluate_unruled(&self, ctx: &CommandContext, trace: &mut Trace) -> RuleMatch {
+        // `cmd /c "..."` runs a cmd.exe command line, not bash.
+        let args: Vec<&str> = ctx.args().iter().map(|w| w.as_str()).collect();
+        if let Some(line) = cmd_exe::command_line(&ctx.base_command, &args) {
+            let result = self.evaluate_cmd_exe(&line, trace);
+            let result = self.guidance.apply(ctx, result);
+            return self.finish(result, trace);
+        }
+
+        let words: Vec<&str> = ctx.words[ctx.env_vars.len()..]
+            .iter()
+            .map(|w| w.as_str())
+            .collect();
+        // `docker run`: the container's access to the host, and any command
+        // inside. A denied docker stays denied.
+        if let Some(run) = docker::run(&words)
+            && self.simple.get(&ctx.base_command) != Some(&Decision::Deny)
+        {
+            let result = self.evaluate_container_run(ctx, run, trace);
+            let result = self.guidance.apply(ctx, result);
+            return self.finish(result, trace);
+        }
+        if let Some(exec) = docker::exec(&words)
+            && self.simple.get(&ctx.base_command) != Some(&Decision::Deny)
+        {
+            let result = self.evaluate_container_exec(ctx, exec, trace);
+            let result = self.guidance.apply(ctx, result);
+            return self.finish(result, trace);
+        }
+        // `ansible -m shell -a '...'`: the command line each host runs.
+        if let Some(payload) = ansible::payload(ctx)
+            && self.simple.get(&ctx.base_command) != Some(&Decision::Deny)
+        {
+            let result = self.evaluate_ansible_payload(ctx, payload, trace);
+            let result = self.guidance.apply(ctx, result);
+            return self.finish(result, trace);
+        }
+        // `git submodule foreach '...'`: the command run in each submodule.
+        if let Some(command) = git::foreach_command(ctx)
+            && let Some(spec) = self.get(&ctx.base_command)
+        {
+            let floor = spec.evaluate(ctx);
+            let result = self.evaluate_payload("git submodule foreach", floor, &command, trace);
+            let result = self.guidance.apply(ctx, result);
+            return self.finish(result, trace);
+        }
+        // `gh codespace ssh -- ...`: the command run in the codespace.
+        if let Some(command) = gh::ssh_command(ctx)
+            && let Some(spec) = self.get(&ctx.base_command)
+        {
+            let floor = spec.evaluate(ctx);
+            let result = self.evaluate_payload("gh codespace ssh", floor, &command, trace);
+            let result = self.guidance.apply(ctx, result);
+            return self.finish(result, trace);
+        }
+
+        // `flatpak run`, `toolbox run`, ...: the launch, and any command inside.
+        if let Some(launch) = launchers::launch(&words) {
+            let result = self.evaluate_launch(ctx, launch, trace);
+            let result = self.guidance.apply(ctx, result);
+            return self.finish(result, trace);
+        }
+
         // Wrapper commands: extract inner command, evaluate it, return max(floor, inner).
         if let Some(floor) = self.wrapper_floor(&ctx.base_command) {
-            let (wrapped_cmd, is_unanalyzable) = self.extract_wrapped_command(&ctx);
+            trace.step(|| format!("wrapper {} (floor {})", ctx.base_command, floor.label()));
+            if interactive::opens_shell(&words) {
+                let result = RuleMatch {
+                    decision: self.interactive_shell.max(floor),
+                    reason: format!(
+                        "{} opens an interactive privileged shell",
+                        CommandContext::join_words(words.iter().copied())
+                    ),
+                };
+                let result = self.guidance.apply(ctx, result);
+                return self.finish(result, trace);
+            }
+            // `su -c '...'` runs a command string, not the words after it.
+            let command_string = wrappers::su_command(&words);
+            let (wrapped_words, is_unanalyzable) = match command_string {
+                Some(_) => (Vec::new(), false),
+                None => self.extract_wrapped_command(ctx),
+            };
             let mut strictest = floor;
-            let mut reason = if is_unanalyzable {
+            let mut reason = if let Some(command) = command_string {
+                let inner = trace.nested(|trace| {
+                    trace.step(|| format!("command string: {command}"));
+                    self.evaluate_detailed_with(&command, trace).result
+                });
+                strictest = strictest.max(inner.decision);
+                format!("{} runs: {}", ctx.base_command, inner.reason)
+            } else if is_unanalyzable {
                 // Unanalyzable (eval, source, shell -c) → ASK
                 strictest = Decision::Ask;
                 format!("{} wraps unanalyzable command", ctx.base_command)
-            } else if !wrapped_cmd.is_empty() {
+            } else if !wrapped_words.is_empty() {
                 // env -i / env - clears the environment for the wrapped command.
                 let inner_env = if ctx.base_command == "env" && ctx.has_any_flag(&["-i", "-"]) {
                     HashMap::new()
                 } else {
                     ctx.accumulated_env.clone()
                 };
-                let mut inner_ctx = CommandContext::from_command(&wrapped_cmd);
+                let mut inner_ctx = CommandContext::from_words(wrapped_words);
                 inner_ctx.accumulated_env = inner_env;
-                let inner = self.evaluate_ctx(inner_ctx);
+                inner_ctx.globs = ctx.globs.clone();
+                // ask_floor wrappers are the privilege-escalating ones (sudo, doas, ...).
+                inner_ctx.privileged_by = ctx
+                    .privileged_by
+                    .clone()
+                    .or_else(|| (floor == Decision::Ask).then(|| ctx.base_command.clone()));
+                let inner = trace.nested(|trace| {
+                    trace.step(|| format!("inner: {}", inner_ctx.words.join(" ")));
+                    self.evaluate_ctx(inner_ctx, trace)
+                });
                 if inner.decision > strictest {
                     strictest = inner.decision;
                 }
@@ -381,30 +1133,63 @@ impl CommandRegistry {
                 strictest = Decision::Ask;
                 reason = format!("{} with output redirection", reason);
             }
-            return self.maybe_escalate(RuleMatch {
-                decision: strictest,
-                reason,
-            });
+            return self.finish(
+                RuleMatch {
+                    decision: strictest,
+                    reason,
+                },
+                trace,
+            );
         }
 
         // Look up by exact base command name
         if let Some(spec) = self.get(&ctx.base_command) {
-            return self.maybe_escalate(spec.evaluate(&ctx));
+            let result = spec.evaluate(ctx);
+            trace.step(|| spec_step(&ctx.base_command, &result));
+            let result = self.guidance.apply(ctx, result);
+            return self.finish(result, trace);
         }
+        trace.step(|| format!("spec {}: none", ctx.base_command));
 
         // Dotted command fallback for deny list (e.g. mkfs.ext4 → mkfs)
         if let Some(prefix) = ctx.base_command.split('.').next()
             && prefix != ctx.base_command
             && let Some(spec) = self.get(prefix)
         {
-            return self.maybe_escalate(spec.evaluate(&ctx));
+            let result = spec.evaluate(ctx);
+            trace.step(|| format!("dotted fallback {}", spec_step(prefix, &result)));
+            let result = self.guidance.apply(ctx, result);
+            return self.finish(result, trace);
         }
 
-        // Fallthrough → ask
-        RuleMatch {
-            decision: Decision::Ask,
-            reason: format!("unrecognized command: {}", ctx.base_command),
+        // Fallthrough → the fallback spec, if any, else ask
+        if let Some(spec) = &self.fallback {
+            let result = spec.evaluate(ctx);
+            trace.step(|| {
+                format!(
+                    "fallback spec -> {}: {}",
+                    result.decision.label(),
+                    result.reason
+                )
+            });
+            let result = self.guidance.apply(ctx, result);
+            return self.finish(result, trace);
         }
+        let result = self.guidance.apply(
+            ctx,
+            RuleMatch {
+                decision: Decision::Ask,
+                reason: format!("unrecognized command: {}", ctx.base_command),
+            },
+        );
+        self.finish(result, trace)
+    }
+
+    /// Apply escalate_deny to a segment's decision and trace the outcome.
+    fn finish(&self, result: RuleMatch, trace: &mut Trace) -> RuleMatch {
+        let result = self.maybe_escalate(result);
+        trace.step(|| format!("=> {}: {}", result.decision.label(), result.reason));
+        result
     }
 
     /// Recursively evaluate a pipeline tree, collecting substitution results.
@@ -417,17 +1202,30 @@ impl CommandRegistry {
         pipeline: &ParsedPipeline,
         accumulated_env: &mut HashMap<String, String>,
         reasons: &mut Vec<String>,
+        segments: &mut Vec<SegmentResult>,
+        trace: &mut Trace,
+        functions: &Functions,
     ) -> Decision {
         let mut strictest = Decision::Allow;
 
         // Evaluate structural substitutions first (for-loop values, case subjects)
         for sub in &pipeline.structural_substitutions {
-            let sub_decision = self.evaluate_pipeline(&sub.pipeline, &mut HashMap::new(), reasons);
+            trace.step(|| "structural substitution".into());
+            let sub_decision = trace.nested(|trace| {
+                self.evaluate_pipeline(
+                    &sub.pipeline,
+                    &mut HashMap::new(),
+                    reasons,
+                    segments,
+                    trace,
+                    functions,
+                )
+            });
             let label: String = sub
                 .pipeline
                 .segments
                 .iter()
-                .map(|s| s.command.as_str())
+                .map(|s| segment_text(&s.command))
                 .collect::<Vec<_>>()
                 .join(" && ");
             let label: String = label.trim().chars().take(60).collect();
@@ -471,14 +1269,23 @@ impl CommandRegistry {
             // Evaluate substitutions within this segment (recursive tree walk).
             // Substitutions don't propagate env to parent — use a fresh env.
             for sub in &segment.substitutions {
-                let sub_decision =
-                    self.evaluate_pipeline(&sub.pipeline, &mut HashMap::new(), reasons);
+                trace.step(|| format!("substitution in: {}", segment_text(&segment.command)));
+                let sub_decision = trace.nested(|trace| {
+                    self.evaluate_pipeline(
+                        &sub.pipeline,
+                        &mut HashMap::new(),
+                        reasons,
+                        segments,
+                        trace,
+                        functions,
+                    )
+                });
                 // Build a readable label from the substitution's inner pipeline segments
                 let label: String = sub
                     .pipeline
                     .segments
                     .iter()
-                    .map(|s| s.command.as_str())
+                    .map(|s| segment_text(&s.command))
                     .collect::<Vec<_>>()
                     .join(" && ");
                 let label: String = label.trim().chars().take(60).collect();
@@ -493,10 +1300,40 @@ impl CommandRegistry {
 
             // Build a CommandContext from the structured segment — uses the
             // pre-tokenized words from tree-sitter directly.
-            let mut ctx = CommandContext::from_segment(segment);
+            let mut ctx = self.without_safe_redirects(CommandContext::from_segment(segment));
             ctx.accumulated_env = accumulated_env.clone();
+            let redirection = ctx.redirection.clone();
 
-            let mut result = self.evaluate_ctx(ctx);
+            let piped = i > 0
+                && matches!(
+                    pipeline.operators[i - 1],
+                    Operator::Pipe | Operator::PipeErr
+                );
+            let suggestion = if piped {
+                download_then_inspect(&pipeline.segments[i - 1], &ctx)
+            } else {
+                None
+            }
+            .or_else(|| self.suggest_ctx(&ctx));
+
+            // A call to a function defined earlier in the command runs its
+            // body, not the command of that name.
+            let call = (!functions.is_empty())
+                .then(|| {
+                    let args: Vec<&str> = ctx.args().iter().map(|w| w.as_str()).collect();
+                    functions.call(&ctx.base_command, &args)
+                })
+                .flatten()
+                .map(|body| (ctx.base_command.clone(), body));
+
+            let text = segment_text(&segment.command);
+            trace.step(|| format!("segment: {text}"));
+            let mut result = trace.nested(|trace| match call {
+                Some((name, body)) => {
+                    self.evaluate_function(&name, &body, accumulated_env, functions, trace)
+                }
+                None => self.evaluate_ctx(ctx, trace),
+            });
 
             // Accumulate env vars from this segment if it's known to execute.
             // Use the segment's pre-tokenized words directly (substitutions
@@ -512,17 +1349,24 @@ impl CommandRegistry {
 
             // Propagate redirection from wrapping constructs
             if result.decision == Decision::Allow
-                && let Some(ref r) = segment.redirection
+                && let Some(ref r) = redirection
             {
                 result.decision = Decision::Ask;
                 result.reason = format!("{} (escalated: wrapping {})", result.reason, r);
             }
-            let label: String = segment.command.trim().chars().take(60).collect();
+            let suggestion = suggestion.filter(|_| result.decision != Decision::Allow);
+            let label: String = text.chars().take(60).collect();
             reasons.push(format!(
                 "  [{label}] -> {}: {}",
                 result.decision.label(),
-                result.reason
+                with_suggestion(&result.reason, suggestion.as_deref())
             ));
+            segments.push(SegmentResult {
+                command: text,
+                decision: result.decision,
+                reason: result.reason,
+                suggestion,
+            });
             if result.decision > strictest {
                 strictest = result.decision;
             }
@@ -531,36 +1375,472 @@ impl CommandRegistry {
         strictest
     }
 
+    /// The strictest `[groups]` floor among the subshells and brace groups
+    /// in `pipeline`, with a reason line for each group it applies to.
+    fn group_floor(
+        &self,
+        pipeline: &ParsedPipeline,
+        reasons: &mut Vec<String>,
+        trace: &mut Trace,
+    ) -> Decision {
+        let floors = &self.groups;
+        if floors.subshell == Decision::Allow && floors.brace_group == Decision::Allow {
+            return Decision::Allow;
+        }
+        let mut strictest = Decision::Allow;
+        // Segments in one group share it: take each group once, outermost
+        // first, then the groups inside substitutions.
+        let mut seen = Vec::new();
+        let groups = pipeline
+            .segments
+            .iter()
+            .flat_map(|seg| &seg.groups)
+            .filter(|group| {
+                let first = !seen.contains(&group.start);
+                seen.push(group.start);
+                first
+            });
+        for group in groups {
+            let (floor, name) = match group.kind {
+                GroupKind::Subshell => (floors.subshell, "subshell"),
+                GroupKind::BraceGroup => (floors.brace_group, "brace_group"),
+            };
+            if floor == Decision::Allow {
+                continue;
+            }
+            let label: String = segment_text(&group.text).chars().take(60).collect();
+            trace.step(|| format!("[groups] {name} {label} -> {}", floor.label()));
+            reasons.push(format!(
+                "  [{label}] -> {}: grouped command ([groups] {name} floor)",
+                floor.label()
+            ));
+            strictest = strictest.max(floor);
+        }
+        let nested = pipeline
+            .structural_substitutions
+            .iter()
+            .chain(pipeline.segments.iter().flat_map(|s| &s.substitutions));
+        for sub in nested {
+            strictest = strictest.max(self.group_floor(&sub.pipeline, reasons, trace));
+        }
+        strictest
+    }
+
+    /// Evaluate a call to a function defined in the command: its body, with
+    /// the call's arguments, in place of the call.
+    fn evaluate_function(
+        &self,
+        name: &str,
+        body: &str,
+        env: &HashMap<String, String>,
+        functions: &Functions,
+        trace: &mut Trace,
+    ) -> RuleMatch {
+        if functions.depth >= functions::MAX_DEPTH {
+            trace.step(|| format!("function {name}: nested too deeply -> ASK"));
+            return RuleMatch {
+                decision: Decision::Ask,
+                reason: format!("function {name}: calls nested too deeply (fail-closed)"),
+            };
+        }
+        trace.step(|| format!("function {name}: {}", segment_text(body)));
+        let Ok(pipeline) = parse::parse_with_substitutions(body) else {
+            return RuleMatch {
+                decision: Decision::Ask,
+                reason: format!("function {name}: parse error (fail-closed)"),
+            };
+        };
+        let mut reasons = Vec::new();
+        let mut decision = trace.nested(|trace| {
+            self.evaluate_pipeline(
+                &pipeline,
+                &mut env.clone(),
+                &mut reasons,
+                &mut Vec::new(),
+                trace,
+                &functions.nested(),
+            )
+        });
+        if pipeline.has_parse_errors_recursive() {
+            decision = decision.max(Decision::Ask);
+        }
+        let reasons: Vec<&str> = reasons.iter().map(|r| r.trim()).collect();
+        RuleMatch {
+            decision,
+            reason: format!("function {name} runs {}", reasons.join("; ")),
+        }
+    }
+
+    /// Evaluate an app or container launch: the `app_launch` floor, raised
+    /// by the command run inside if there is one.
+    fn evaluate_launch(
+        &self,
+        ctx: &CommandContext,
+        launch: launchers::Launch,
+        trace: &mut Trace,
+    ) -> RuleMatch {
+        let target = match launch.app {
+            Some(app) => format!("{} {app}", launch.launcher),
+            None => launch.launcher.to_string(),
+        };
+        trace.step(|| format!("launcher {target} (floor {})", self.app_launch.label()));
+        let mut result = if launch.command.is_empty() {
+            RuleMatch {
+                decision: self.app_launch,
+                reason: format!("{target} (no command)"),
+            }
+        } else {
+            let mut inner_ctx =
+                CommandContext::from_words(launch.command.into_iter().map(Word::from).collect());
+            inner_ctx.globs = ctx.globs.clone();
+            let inner = trace.nested(|trace| {
+                trace.step(|| format!("inner: {}", inner_ctx.words.join(" ")));
+                self.evaluate_ctx(inner_ctx, trace)
+            });
+            RuleMatch {
+                decision: self.app_launch.max(inner.decision),
+                reason: format!("{target} runs: {}", inner.reason),
+            }
+        };
+        if result.decision == Decision::Allow && ctx.redirection.is_some() {
+            result.decision = Decision::Ask;
+            result.reason = format!("{} with output redirection", result.reason);
+        }
+        result
+    }
+
+    /// Evaluate a `docker run` or `docker create`: the `[docker] run` floor,
+    /// `escalation` if the container gets the host, raised by the command
+    /// run inside if there is one.
+    fn evaluate_container_run(
+        &self,
+        ctx: &CommandContext,
+        run: docker::Run,
+        trace: &mut Trace,
+    ) -> RuleMatch {
+        let mut target = format!("{} {}", run.tool, run.action);
+        if let Some(image) = run.image {
+            target = format!("{target} {image}");
+        }
+        let escalations = run.escalations(&self.paths, &ctx.working_dir());
+        let floor = if escalations.is_empty() {
+            self.docker.run
+        } else {
+            target = format!("{target} with {}", escalations.join(", "));
+            self.docker.run.max(self.docker.escalation)
+        };
+        trace.step(|| format!("container {target} (floor {})", floor.label()));
+        let mut result = if run.command.is_empty() {
+            RuleMatch {
+                decision: floor,
+                reason: format!("{target} (image's default command)"),
+            }
+        } else {
+            let inner = self.evaluate_in_container(ctx, run.command, trace);
+            RuleMatch {
+                decision: floor.max(inner.decision),
+                reason: format!("{target} runs: {}", inner.reason),
+            }
+        };
+        if result.decision == Decision::Allow && ctx.redirection.is_some() {
+            result.decision = Decision::Ask;
+            result.reason = format!("{} with output redirection", result.reason);
+        }
+        result
+    }
+
+    /// Evaluate a `docker exec`: the `[docker] exec` floor (`escalation`
+    /// with `--privileged`) raised by the command, or `interactive_shell`
+    /// for a shell given a terminal and no command.
+    fn evaluate_container_exec(
+        &self,
+        ctx: &CommandContext,
+        exec: docker::Exec,
+        trace: &mut Trace,
+    ) -> RuleMatch {
+        let mut target = format!("{} exec", exec.tool);
+        if let Some(container) = exec.container {
+            target = format!("{target} {container}");
+        }
+        let floor = if exec.privileged {
+            target = format!("{target} with --privileged");
+            self.docker.exec.max(self.docker.escalation)
+        } else {
+            self.docker.exec
+        };
+        trace.step(|| format!("container {target} (floor {})", floor.label()));
+        if exec.interactive && interactive::runs_shell(&exec.command) {
+            return RuleMatch {
+                decision: self.docker.interactive_shell.max(floor),
+                reason: format!(
+                    "{target} opens an interactive shell: {}",
+                    CommandContext::join_words(exec.command.iter().copied())
+                ),
+            };
+        }
+        let mut result = if exec.command.is_empty() {
+            RuleMatch {
+                decision: floor,
+                reason: format!("{target} (no command)"),
+            }
+        } else {
+            let inner = self.evaluate_in_container(ctx, exec.command, trace);
+            RuleMatch {
+                decision: floor.max(inner.decision),
+                reason: format!("{target} runs: {}", inner.reason),
+            }
+        };
+        if result.decision == Decision::Allow && ctx.redirection.is_some() {
+            result.decision = Decision::Ask;
+            result.reason = format!("{} with output redirection", result.reason);
+        }
+        result
+    }
+
+    /// Evaluate an ad-hoc `ansible` task that runs a command line: the
+    /// `[ansible] run` floor (`privileged` too with `--become`) raised by the
+    /// command line, evaluated like a top-level command.
+    fn evaluate_ansible_payload(
+        &self,
+        ctx: &CommandContext,
+        payload: ansible::Payload,
+        trace: &mut Trace,
+    ) -> RuleMatch {
+        let mut target = format!("ansible {} -m {}", payload.pattern, payload.module);
+        let floor = if payload.privileged {
+            target = format!("{target} with --become");
+            self.ansible.run.max(self.ansible.privileged)
+        } else {
+            self.ansible.run
+        };
+        trace.step(|| format!("ad-hoc {target} (floor {})", floor.label()));
+        let inner = trace.nested(|trace| {
+            trace.step(|| format!("command string: {}", payload.command));
+            self.evaluate_detailed_with(&payload.command, trace).result
+        });
+        let mut result = RuleMatch {
+            decision: floor.max(inner.decision),
+            reason: format!("{target} runs: {}", inner.reason),
+        };
+        if result.decision == Decision::Allow && ctx.redirection.is_some() {
+            result.decision = Decision::Ask;
+            result.reason = format!("{} with output redirection", result.reason);
+        }
+        result
+    }
+
+    /// Evaluate a subcommand that runs a command line (`git submodule
+    /// foreach`, `gh codespace ssh`): the tool's own decision for it raised
+    /// by the command line, evaluated like a top-level command.
+    fn evaluate_payload(
+        &self,
+        what: &str,
+        floor: RuleMatch,
+        command: &str,
+        trace: &mut Trace,
+    ) -> RuleMatch {
+        trace.step(|| format!("{what} (floor {})", floor.decision.label()));
+        if floor.decision == Decision::Deny {
+            return floor;
+        }
+        let inner = trace.nested(|trace| {
+            trace.step(|| format!("command string: {command}"));
+            self.evaluate_detailed_with(command, trace).result
+        });
+        RuleMatch {
+            decision: floor.decision.max(inner.decision),
+            reason: format!("{what} runs: {}", inner.reason),
+        }
+    }
+
+    /// Evaluate a command run in a container. A shell's `-c` string is
+    /// evaluated like a top-level command.
+    fn evaluate_in_container(
+        &self,
+        ctx: &CommandContext,
+        command: Vec<&str>,
+        trace: &mut Trace,
+    ) -> RuleMatch {
+        if let Some(string) = interactive::shell_command(&command) {
+            return trace.nested(|trace| {
+                trace.step(|| format!("command string: {string}"));
+                self.evaluate_detailed_with(&string, trace).result
+            });
+        }
+        let mut inner_ctx =
+            CommandContext::from_words(command.into_iter().map(Word::from).collect());
+        inner_ctx.globs = ctx.globs.clone();
+        trace.nested(|trace| {
+            trace.step(|| format!("inner: {}", inner_ctx.words.join(" ")));
+            self.evaluate_ctx(inner_ctx, trace)
+        })
+    }
+
+    /// Evaluate the command line of `cmd /c`: each of its commands by
+    /// `[cmd_exe]`, or like any other command if it reads the same in bash.
+    fn evaluate_cmd_exe(&self, line: &str, trace: &mut Trace) -> RuleMatch {
+        trace.step(|| format!("cmd.exe: {line}"));
+        let mut decision = Decision::Allow;
+        let mut reasons = Vec::new();
+        for piece in cmd_exe::split(line) {
+            let name = &piece.name;
+            let mut result = match self.cmd_exe.decision(name) {
+                Some(decision) => RuleMatch {
+                    decision,
+                    reason: match decision {
+                        Decision::Allow => format!("allowed: {name}"),
+                        Decision::Ask => format!("{name} requires confirmation"),
+                        Decision::Deny => format!("blocked command: {name}"),
+                    },
+                },
+                None if piece.is_portable() => {
+                    trace.nested(|trace| self.evaluate_detailed_with(&piece.text, trace).result)
+                }
+                None => RuleMatch {
+                    decision: Decision::Ask,
+                    reason: format!("unrecognized cmd.exe command: {name}"),
+                },
+            };
+            if result.decision == Decision::Allow
+                && let Some(redirect) = &piece.redirect
+            {
+                result = RuleMatch {
+                    decision: Decision::Ask,
+                    reason: format!("{name} with output redirection ({redirect})"),
+                };
+            }
+            trace.step(|| format!("[{}] -> {}", piece.text, result.decision.label()));
+            decision = decision.max(result.decision);
+            reasons.push(format!(
+                "[{}] -> {}: {}",
+                piece.text,
+                result.decision.label(),
+                result.reason
+            ));
+        }
+        RuleMatch {
+            decision,
+            reason: format!("cmd.exe runs {}", reasons.join("; ")),
+        }
+    }
+
     /// Evaluate a full command string, handling compound expressions and substitutions.
     pub fn evaluate(&self, command: &str) -> RuleMatch {
-        let pipeline = match parse::parse_with_substitutions(command) {
+        self.evaluate_detailed(command).result
+    }
+
+    /// Like [`evaluate_detailed`](Self::evaluate_detailed), but a panic
+    /// anywhere in parsing or evaluation becomes ASK instead of aborting.
+    ///
+    /// A hook that crashes returns no decision, so the hook binary uses this
+    /// to fail closed.
+    pub fn evaluate_fail_closed(&self, command: &str) -> Evaluation {
+        self.fail_closed(command, &mut Trace::off())
+    }
+
+    fn fail_closed(&self, command: &str, trace: &mut Trace) -> Evaluation {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.evaluate_detailed_with(command, trace)
+        }))
+        .unwrap_or_else(|_| Evaluation {
+            result: RuleMatch {
+                decision: Decision::Ask,
+                reason: "internal error during evaluation (fail-closed)".into(),
+            },
+            segments: Vec::new(),
+            timings: Timings::default(),
+        })
+    }
+
+    /// Evaluate a full command string and keep each segment's result.
+    ///
+    /// Same decision as [`evaluate`](Self::evaluate); additionally reports
+    /// which segment of a compound command produced which decision.
+    pub fn evaluate_detailed(&self, command: &str) -> Evaluation {
+        self.evaluate_detailed_with(command, &mut Trace::off())
+    }
+
+    /// Like [`evaluate_fail_closed`](Self::evaluate_fail_closed), and also
+    /// return a trace of each lookup consulted per segment: rules, wrappers,
+    /// the spec by exact name, the dotted fallback, and the decision reached.
+    pub fn evaluate_traced(&self, command: &str) -> (Evaluation, Vec<String>) {
+        let mut trace = Trace::on();
+        let evaluation = self.fail_closed(command, &mut trace);
+        (evaluation, trace.into_lines())
+    }
+
+    fn evaluate_detailed_with(&self, command: &str, trace: &mut Trace) -> Evaluation {
+        let elapsed = stopwatch();
+        let command = match self.shell {
+            Shell::Bash => Cow::Borrowed(command),
+            Shell::Zsh => zsh::normalize(command),
+        };
+        if let Cow::Owned(rewritten) = &command {
+            trace.step(|| format!("zsh syntax read as: {rewritten}"));
+        }
+        let parsed = parse::parse_with_substitutions(&command);
+        let parse = elapsed();
+        let mut evaluation = self.evaluate_parsed(&command, parsed, trace);
+        evaluation.timings = Timings {
+            parse,
+            eval: elapsed().saturating_sub(parse),
+        };
+        evaluation
+    }
+
+    fn evaluate_parsed(
+        &self,
+        command: &str,
+        parsed: Result<ParsedPipeline, parse::ParseError>,
+        trace: &mut Trace,
+    ) -> Evaluation {
+        let pipeline = match parsed {
             Ok(p) => p,
             Err(_) => {
                 // ParseError → ASK (fail-closed)
-                return RuleMatch {
-                    decision: Decision::Ask,
-                    reason: "parse error (fail-closed)".into(),
+                trace.step(|| "parse error -> ASK".into());
+                return Evaluation {
+                    result: RuleMatch {
+                        decision: Decision::Ask,
+                        reason: "parse error (fail-closed)".into(),
+                    },
+                    segments: Vec::new(),
+                    timings: Timings::default(),
                 };
             }
         };
+        let mut segments = Vec::new();
 
         // Check for parse errors in the pipeline tree → ASK (fail-closed)
         if pipeline.has_parse_errors_recursive() {
             // Still evaluate what we can, but escalate to ASK minimum
             let mut strictest = Decision::Ask;
             let mut reasons = vec!["  parse errors detected (fail-closed)".to_string()];
+            trace.step(|| "parse errors: at least ASK".into());
             let mut accumulated_env: HashMap<String, String> = HashMap::new();
-            let tree_decision =
-                self.evaluate_pipeline(&pipeline, &mut accumulated_env, &mut reasons);
+            let tree_decision = self
+                .evaluate_pipeline(
+                    &pipeline,
+                    &mut accumulated_env,
+                    &mut reasons,
+                    &mut segments,
+                    trace,
+                    &Functions::from_pipeline(&pipeline),
+                )
+                .max(self.group_floor(&pipeline, &mut reasons, trace));
             if tree_decision > strictest {
                 strictest = tree_decision;
             }
-            return RuleMatch {
-                decision: strictest,
-                reason: format!(
-                    "compound command (parse errors, fail-closed):\n{}",
-                    reasons.join("\n")
-                ),
+            return Evaluation {
+                result: RuleMatch {
+                    decision: strictest,
+                    reason: format!(
+                        "compound command (parse errors, fail-closed):\n{}",
+                        reasons.join("\n")
+                    ),
+                },
+                segments,
+                timings: Timings::default(),
             };
         }
 
@@ -583,13 +1863,46 @@ impl CommandRegistry {
                 None => true,
             };
             if is_passthrough {
-                return self.evaluate_single(command);
+                // Reuse the segment from the parse above rather than parsing
+                // the command again.
+                let ctx = match pipeline.segments.first() {
+                    Some(seg) => CommandContext::from_segment(seg),
+                    None => CommandContext::from_command(command),
+                };
+                let ctx = self.without_safe_redirects(ctx);
+                let suggestion = self.suggest_ctx(&ctx);
+                let text = segment_text(command);
+                trace.step(|| format!("segment: {text}"));
+                let result = trace.nested(|trace| self.evaluate_ctx(ctx, trace));
+                let mut result = self.maybe_annotate_project_overlay(result);
+                let suggestion = suggestion.filter(|_| result.decision != Decision::Allow);
+                segments.push(SegmentResult {
+                    command: text,
+                    decision: result.decision,
+                    reason: result.reason.clone(),
+                    suggestion: suggestion.clone(),
+                });
+                result.reason = with_suggestion(&result.reason, suggestion.as_deref());
+                return Evaluation {
+                    result,
+                    segments,
+                    timings: Timings::default(),
+                };
             }
         }
 
         let mut reasons = Vec::new();
         let mut accumulated_env: HashMap<String, String> = HashMap::new();
-        let strictest = self.evaluate_pipeline(&pipeline, &mut accumulated_env, &mut reasons);
+        let strictest = self
+            .evaluate_pipeline(
+                &pipeline,
+                &mut accumulated_env,
+                &mut reasons,
+                &mut segments,
+                trace,
+                &Functions::from_pipeline(&pipeline),
+            )
+            .max(self.group_floor(&pipeline, &mut reasons, trace));
 
         // Build summary header
         let mut desc = Vec::new();
@@ -617,12 +1930,39 @@ impl CommandRegistry {
             format!("compound command ({})", desc.join("; "))
         };
 
-        self.maybe_annotate_project_overlay(RuleMatch {
-            decision: strictest,
-            reason: format!("{}:\n{}", header, reasons.join("\n")),
-        })
+        Evaluation {
+            result: self.maybe_annotate_project_overlay(RuleMatch {
+                decision: strictest,
+                reason: if reasons.is_empty() {
+                    header
+                } else {
+                    format!("{}:\n{}", header, reasons.join("\n"))
+                },
+            }),
+            segments,
+            timings: Timings::default(),
+        }
     }
 }
 
+/// Start timing; the returned closure reads the time elapsed since. Always
+/// zero without the `native` feature, since not every target has a clock.
+#[cfg(feature = "native")]
+fn stopwatch() -> impl Fn() -> std::time::Duration {
+    let start = std::time::Instant::now();
+    move || start.elapsed()
+}
+
+#[cfg(not(feature = "native"))]
+fn stopwatch() -> impl Fn() -> std::time::Duration {
+    || std::time::Duration::ZERO
+}
+
+// The thread-safety contract documented on `CommandRegistry`.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CommandRegistry>();
+};
+
 #[cfg(test)]
 mod tests;
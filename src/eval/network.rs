@@ -0,0 +1,337 @@
+//! Network destination classification against configured domain lists.
+//!
+//! `[network] allowed_domains` and `denied_domains` entries match a host
+//! exactly or any of its subdomains (`github.com` covers `api.github.com`).
+//! A leading `*.` restricts an entry to subdomains only. Denied entries take
+//! precedence over allowed ones.
+//!
+//! Separately, destinations inside the machine's network — cloud metadata
+//! endpoints (`[network] internal_hosts`) and private, link-local, or
+//! unique-local IP addresses — are detected regardless of the domain lists.
+//! IPv4 literals are parsed the way `inet_aton` does, so integer, hex, and
+//! octal spellings (`http://2852039166/`, `0xa9.0xfe.0xa9.0xfe`) are caught.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::config::NetworkConfig;
+
+/// Classification of a network destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DomainMatch<'a> {
+    /// Matches a `denied_domains` entry (carries the host).
+    Denied(&'a str),
+    /// Every destination matches an `allowed_domains` entry.
+    Allowed,
+    /// At least one destination is in neither list (or could not be parsed).
+    Unknown,
+}
+
+/// Classifies request destinations against the `[network]` domain lists.
+#[derive(Debug, Clone, Default)]
+pub struct DomainPolicy {
+    /// Domains whose requests can be auto-allowed.
+    allowed: Vec<String>,
+    /// Domains whose requests are denied.
+    denied: Vec<String>,
+    /// Cloud metadata hostnames and addresses.
+    internal: Vec<String>,
+}
+
+/// Why a destination is considered internal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InternalTarget<'a> {
+    /// Matches an `internal_hosts` entry (carries the host).
+    Metadata(&'a str),
+    /// A private, link-local, or unique-local IP address (carries the host).
+    PrivateAddress(&'a str),
+}
+
+impl std::fmt::Display for InternalTarget<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Metadata(h) => write!(f, "cloud metadata endpoint {h}"),
+            Self::PrivateAddress(h) => write!(f, "private network address {h}"),
+        }
+    }
+}
+
+impl DomainPolicy {
+    /// Build a policy from the `[network]` config section.
+    pub fn from_config(config: &NetworkConfig) -> Self {
+        let normalize = |list: &[String]| list.iter().map(|d| d.to_ascii_lowercase()).collect();
+        Self {
+            allowed: normalize(&config.allowed_domains),
+            denied: normalize(&config.denied_domains),
+            internal: normalize(&config.internal_hosts),
+        }
+    }
+
+    fn listed(list: &[String], host: &str) -> bool {
+        list.iter().any(|entry| match entry.strip_prefix("*.") {
+            Some(parent) => host.ends_with(&format!(".{parent}")),
+            None => host == entry || host.ends_with(&format!(".{entry}")),
+        })
+    }
+
+    /// Whether `host` matches a `denied_domains` entry.
+    pub fn is_denied(&self, host: &str) -> bool {
+        Self::listed(&self.denied, &host.to_ascii_lowercase())
+    }
+
+    /// Whether `host` matches an `allowed_domains` entry.
+    pub fn is_allowed(&self, host: &str) -> bool {
+        Self::listed(&self.allowed, &host.to_ascii_lowercase())
+    }
+
+    /// Classify a set of hosts: any denied host wins, then all-allowed,
+    /// otherwise unknown. An empty set is unknown.
+    pub fn classify<'a>(&self, hosts: &[&'a str]) -> DomainMatch<'a> {
+        if let Some(host) = hosts.iter().find(|h| self.is_denied(h)) {
+            return DomainMatch::Denied(host);
+        }
+        if !hosts.is_empty() && hosts.iter().all(|h| self.is_allowed(h)) {
+            return DomainMatch::Allowed;
+        }
+        DomainMatch::Unknown
+    }
+
+    /// Classify the hosts of a set of URLs. Any URL without a parseable host
+    /// makes the result at most [`DomainMatch::Unknown`].
+    pub fn classify_urls<'a>(&self, urls: &[&'a str]) -> DomainMatch<'a> {
+        let hosts: Vec<&str> = urls.iter().filter_map(|u| url_host(u)).collect();
+        match self.classify(&hosts) {
+            DomainMatch::Allowed if hosts.len() < urls.len() => DomainMatch::Unknown,
+            other => other,
+        }
+    }
+
+    /// Find the first URL whose host is a metadata endpoint or a private
+    /// network address.
+    pub fn internal_target<'a>(&self, urls: &[&'a str]) -> Option<InternalTarget<'a>> {
+        urls.iter().filter_map(|u| url_host(u)).find_map(|host| {
+            let lower = host.to_ascii_lowercase();
+            let ip = parse_ip(&lower);
+            let listed = self
+                .internal
+                .iter()
+                .any(|e| *e == lower || (ip.is_some() && parse_ip(e) == ip));
+            if listed {
+                Some(InternalTarget::Metadata(host))
+            } else if ip.is_some_and(is_private) {
+                Some(InternalTarget::PrivateAddress(host))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// An IP address literal in any of the spellings network clients accept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ip {
+    V4(Ipv4Addr),
+    V6(Ipv6Addr),
+}
+
+/// Parse an IPv6 literal or an `inet_aton`-style IPv4 literal: one to four
+/// dot-separated parts, each decimal, `0x` hex, or leading-zero octal, with
+/// the last part filling the remaining bytes (`10.1` is `10.0.0.1`).
+fn parse_ip(host: &str) -> Option<Ip> {
+    if host.contains(':') {
+        return host.parse().ok().map(Ip::V6);
+    }
+    let parts: Vec<u64> = host
+        .split('.')
+        .map(|p| {
+            if let Some(hex) = p.strip_prefix("0x") {
+                u64::from_str_radix(hex, 16).ok()
+            } else if p.len() > 1 && p.starts_with('0') {
+                u64::from_str_radix(&p[1..], 8).ok()
+            } else if !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()) {
+                p.parse().ok()
+            } else {
+                None
+            }
+        })
+        .collect::<Option<_>>()?;
+    let (last, head) = parts.split_last()?;
+    if head.len() > 3 || head.iter().any(|&p| p > 0xff) {
+        return None;
+    }
+    let rest_bits = 8 * (4 - head.len() as u32);
+    if rest_bits < 64 && *last >= 1 << rest_bits {
+        return None;
+    }
+    let value = head
+        .iter()
+        .enumerate()
+        .fold(*last, |acc, (i, &p)| acc | (p << (24 - 8 * i)));
+    Some(Ip::V4(Ipv4Addr::from(u32::try_from(value).ok()?)))
+}
+
+/// RFC 1918 private, link-local, and IPv6 unique-local/link-local ranges.
+fn is_private(ip: Ip) -> bool {
+    match ip {
+        Ip::V4(v4) => v4.is_private() || v4.is_link_local(),
+        Ip::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_private(Ip::V4(v4)),
+            None => {
+                let first = v6.segments()[0];
+                first & 0xfe00 == 0xfc00 || first & 0xffc0 == 0xfe80
+            }
+        },
+    }
+}
+
+/// Extract the host from a URL (`scheme://user@host:port/path`). A missing
+/// scheme is accepted (`example.com/x`), as curl and wget do.
+pub fn url_host(url: &str) -> Option<&str> {
+    let rest = url.split_once("://").map_or(url, |(_, r)| r);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let host = if let Some(v6) = host_port.strip_prefix('[') {
+        v6.split(']').next().unwrap_or(v6)
+    } else {
+        host_port.split(':').next().unwrap_or(host_port)
+    };
+    let host = host.trim_end_matches('.');
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> DomainPolicy {
+        DomainPolicy::from_config(&NetworkConfig {
+            allowed_domains: vec!["github.com".into(), "*.example.org".into()],
+            denied_domains: vec!["evil.github.com".into(), "pastebin.com".into()],
+            internal_hosts: vec![
+                "169.254.169.254".into(),
+                "metadata.google.internal".into(),
+                "fd00:ec2::254".into(),
+            ],
+            ..Default::default()
+        })
+    }
+
+    fn internal(url: &str) -> Option<String> {
+        policy().internal_target(&[url]).map(|t| t.to_string())
+    }
+
+    #[test]
+    fn host_extraction() {
+        assert_eq!(
+            url_host("https://u:p@API.github.com:443/x?y"),
+            Some("API.github.com")
+        );
+        assert_eq!(url_host("example.com/path"), Some("example.com"));
+        assert_eq!(url_host("http://[::1]:8080/"), Some("::1"));
+        assert_eq!(url_host("https://host./"), Some("host"));
+        assert_eq!(url_host("https:///nohost"), None);
+    }
+
+    #[test]
+    fn subdomains_match() {
+        let p = policy();
+        assert!(p.is_allowed("github.com"));
+        assert!(p.is_allowed("api.GitHub.com"));
+        assert!(!p.is_allowed("notgithub.com"));
+    }
+
+    #[test]
+    fn wildcard_is_subdomain_only() {
+        let p = policy();
+        assert!(p.is_allowed("www.example.org"));
+        assert!(!p.is_allowed("example.org"));
+    }
+
+    #[test]
+    fn denied_wins() {
+        let p = policy();
+        assert_eq!(
+            p.classify(&["evil.github.com"]),
+            DomainMatch::Denied("evil.github.com")
+        );
+        assert_eq!(
+            p.classify(&["github.com", "pastebin.com"]),
+            DomainMatch::Denied("pastebin.com")
+        );
+    }
+
+    #[test]
+    fn all_must_be_allowed() {
+        let p = policy();
+        assert_eq!(p.classify(&["github.com"]), DomainMatch::Allowed);
+        assert_eq!(p.classify(&["github.com", "x.io"]), DomainMatch::Unknown);
+        assert_eq!(p.classify(&[]), DomainMatch::Unknown);
+        assert_eq!(
+            p.classify_urls(&["https://github.com/", "https:///"]),
+            DomainMatch::Unknown
+        );
+    }
+
+    #[test]
+    fn metadata_endpoints() {
+        assert_eq!(
+            internal("http://169.254.169.254/latest/meta-data/").as_deref(),
+            Some("cloud metadata endpoint 169.254.169.254")
+        );
+        assert!(internal("http://Metadata.Google.Internal/computeMetadata/v1/").is_some());
+        assert!(
+            internal("http://[fd00:ec2::254]/")
+                .unwrap()
+                .starts_with("cloud")
+        );
+    }
+
+    #[test]
+    fn obfuscated_metadata_ip() {
+        for url in [
+            "http://2852039166/",
+            "http://0xa9fea9fe/",
+            "http://0251.0376.0251.0376/",
+            "http://169.254.43518/",
+        ] {
+            assert!(
+                internal(url).unwrap().starts_with("cloud metadata"),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn private_addresses() {
+        for url in [
+            "http://10.0.0.5:8080/",
+            "https://172.16.3.4/",
+            "192.168.1.1",
+            "http://169.254.1.1/",
+            "http://[fe80::1]/",
+            "http://[fd12:3456::1]/",
+            "http://[::ffff:10.1.2.3]/",
+        ] {
+            assert!(
+                internal(url)
+                    .unwrap()
+                    .starts_with("private network address"),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn public_and_loopback_not_internal() {
+        for url in [
+            "https://github.com/",
+            "http://8.8.8.8/",
+            "http://172.32.0.1/",
+            "http://127.0.0.1:3000/",
+            "http://localhost/",
+            "http://10.0.0.256/",
+            "https://1.2.3.4.5/",
+        ] {
+            assert_eq!(internal(url), None, "{url}");
+        }
+    }
+}
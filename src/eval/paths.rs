@@ -0,0 +1,454 @@
+//! Filesystem path classification against configured path lists.
+//!
+//! Entries in the `[paths]` config section are shell-style patterns: `~` and
+//! `$VAR` are expanded, `*` matches within one path component, `**` matches
+//! across components, and `?` matches a single character. An entry matches a
+//! path if it matches the path itself or any of its ancestors, so `~/.ssh`
+//! covers `~/.ssh/id_ed25519`.
+//!
+//! Command arguments are resolved the same way before matching: expanded,
+//! made absolute against the directory the command runs in, and lexically
+//! normalized (`.` and `..` removed). No filesystem access is needed.
+//!
+//! Write destinations (download targets, copy destinations, ...) are
+//! classified with [`PathPolicy::classify_write`] into a
+//! [`WriteTarget`](crate::eval::paths::WriteTarget), which carries its own
+//! decision: sensitive and protected locations deny, the sandbox allows,
+//! everything else asks.
+
+use crate::config::PathsConfig;
+use crate::eval::Decision;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// One configured path pattern, kept in both original and expanded form.
+#[derive(Debug, Clone)]
+struct PathEntry {
+    /// The entry as written in config (used in reason strings).
+    original: String,
+    /// The expanded, normalized pattern used for matching.
+    pattern: String,
+}
+
+/// A list of path patterns with match and containment queries.
+#[derive(Debug, Clone, Default)]
+struct PathList {
+    entries: Vec<PathEntry>,
+}
+
+impl PathList {
+    fn new(raw: &[String]) -> Self {
+        let entries = raw
+            .iter()
+            .map(|original| PathEntry {
+                original: original.clone(),
+                pattern: resolve(original).to_string_lossy().into_owned(),
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Return the first entry matching `path` or one of its ancestors.
+    fn matching(&self, path: &Path) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|e| {
+                path.ancestors()
+                    .any(|a| glob_match(&e.pattern, &a.to_string_lossy()))
+            })
+            .map(|e| e.original.as_str())
+    }
+
+    /// Return the first entry located at or beneath the directory `dir`.
+    ///
+    /// Only the literal prefix of each pattern (up to the first glob
+    /// character) is considered, so this is a conservative approximation.
+    fn beneath(&self, dir: &Path) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|e| Path::new(literal_prefix(&e.pattern)).starts_with(dir))
+            .map(|e| e.original.as_str())
+    }
+}
+
+/// Classification of a path that a command writes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteTarget<'a> {
+    /// Inside a `[paths] sensitive` entry (carries the entry).
+    Sensitive(&'a str),
+    /// Inside a `[paths] protected` entry (carries the entry).
+    Protected(&'a str),
+    /// Inside a sandbox directory or the project root.
+    Sandbox,
+    /// Anywhere else.
+    Outside,
+}
+
+impl WriteTarget<'_> {
+    /// The decision for writing to this target.
+    pub fn decision(&self) -> Decision {
+        match self {
+            Self::Sensitive(_) | Self::Protected(_) => Decision::Deny,
+            Self::Sandbox => Decision::Allow,
+            Self::Outside => Decision::Ask,
+        }
+    }
+}
+
+impl std::fmt::Display for WriteTarget<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sensitive(entry) => write!(f, "sensitive path {entry}"),
+            Self::Protected(entry) => write!(f, "protected path {entry}"),
+            Self::Sandbox => write!(f, "sandbox"),
+            Self::Outside => write!(f, "path outside the sandbox"),
+        }
+    }
+}
+
+/// Classifies command arguments against the configured path lists.
+#[derive(Debug, Clone, Default)]
+pub struct PathPolicy {
+    /// Credential and secret stores (e.g. `~/.ssh`, `~/.aws`).
+    sensitive: PathList,
+    /// Locations that must not be written (e.g. `/etc`, `~/.bashrc`).
+    protected: PathList,
+    /// Directories where writes are safe (e.g. `/tmp`).
+    sandbox: PathList,
+    /// Whether the git root of the working directory is in the sandbox.
+    project_sandbox: bool,
+}
+
+impl PathPolicy {
+    /// Build a policy from the `[paths]` config section.
+    ///
+    /// With `project_sandbox` enabled, the git root containing the working
+    /// directory (if any) is added to the sandbox.
+    pub fn from_config(config: &PathsConfig) -> Self {
+        Self {
+            sensitive: PathList::new(&config.sensitive),
+            protected: PathList::new(&config.protected),
+            sandbox: PathList::new(&config.sandbox),
+            project_sandbox: config.project_sandbox,
+        }
+    }
+
+    /// Classify a path that a command running in `cwd` writes to.
+    ///
+    /// Sensitive and protected entries take precedence over the sandbox, so
+    /// `<project>/.git/hooks` is protected even though the project is sandboxed.
+    pub fn classify_write(&self, raw: &str, cwd: &Path) -> WriteTarget<'_> {
+        let path = resolve_in(raw, cwd);
+        if let Some(entry) = self.sensitive.matching(&path) {
+            return WriteTarget::Sensitive(entry);
+        }
+        if let Some(entry) = self.protected.matching(&path) {
+            return WriteTarget::Protected(entry);
+        }
+        let in_project = self.project_sandbox
+            && crate::config::find_git_root(cwd).is_some_and(|root| path.starts_with(root));
+        if in_project || self.sandbox.matching(&path).is_some() {
+            return WriteTarget::Sandbox;
+        }
+        WriteTarget::Outside
+    }
+
+    /// If `raw`, relative to `cwd`, is (or is inside) a sensitive location,
+    /// return the matching config entry.
+    pub fn sensitive_match(&self, raw: &str, cwd: &Path) -> Option<&str> {
+        self.sensitive.matching(&resolve_in(raw, cwd))
+    }
+
+    /// If the directory `raw`, relative to `cwd`, contains a sensitive
+    /// location, return the contained config entry. Used for recursive
+    /// operations (`scp -r ~`).
+    pub fn contains_sensitive(&self, raw: &str, cwd: &Path) -> Option<&str> {
+        self.sensitive.beneath(&resolve_in(raw, cwd))
+    }
+
+    /// If a recursive operation on `raw`, relative to `cwd`, would reach a
+    /// whole system root, describe it: the filesystem root, the home
+    /// directory, a directory fewer than `min_depth` levels below `/`
+    /// (sandbox directories excepted), or everything in one of those (a
+    /// glob such as `/*`). A path starting with a variable that is set
+    /// neither in `env` nor in this process (`"$DIR"/`) is judged as if it
+    /// expanded empty.
+    pub fn system_root(
+        &self,
+        raw: &str,
+        cwd: &Path,
+        min_depth: usize,
+        env: &HashMap<String, String>,
+    ) -> Option<String> {
+        match unset_variable(raw, env) {
+            Some((name, rest)) => self
+                .system_root_at(&rest, cwd, min_depth)
+                .map(|what| format!("{what} if ${name} is empty or unset")),
+            None => self.system_root_at(raw, cwd, min_depth),
+        }
+    }
+
+    fn system_root_at(&self, raw: &str, cwd: &Path, min_depth: usize) -> Option<String> {
+        // A glob reaches everything in the directory it starts in.
+        let (dir, glob) = match raw.find(['*', '?', '[']) {
+            Some(i) => (&raw[..raw[..i].rfind('/').map_or(0, |s| s + 1)], true),
+            None => (raw, false),
+        };
+        let dir = if dir.is_empty() { "." } else { dir };
+        let path = resolve_in(dir, cwd);
+        let depth = path
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .count();
+        let home = std::env::var("HOME")
+            .ok()
+            .filter(|h| !h.is_empty())
+            .map(|h| resolve_in(&h, Path::new("/")));
+        let what = if depth == 0 {
+            "the filesystem root".to_string()
+        } else if home.is_some_and(|home| home == path) {
+            "the home directory".to_string()
+        } else if depth < min_depth && self.classify_write(dir, cwd) != WriteTarget::Sandbox {
+            format!("{} (fewer than {min_depth} levels below /)", path.display())
+        } else {
+            return None;
+        };
+        Some(if glob {
+            format!("everything in {what}")
+        } else {
+            what
+        })
+    }
+}
+
+/// The unset variable `raw` starts with (`$DIR/...`, `"${DIR}"/...`), and
+/// the path after it. `${DIR:?}` and `${DIR:-x}` don't count: they fail or
+/// fall back rather than expanding empty.
+fn unset_variable(raw: &str, env: &HashMap<String, String>) -> Option<(String, String)> {
+    // Quotes around the variable (`"$DIR"/`) don't change what it expands to.
+    let raw = raw.replace('"', "");
+    let rest = raw.strip_prefix('$')?;
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let (name, after) = match rest.strip_prefix('{') {
+        Some(braced) => {
+            let end = braced.find('}')?;
+            (&braced[..end], &braced[end + 1..])
+        }
+        None => rest.split_at(rest.find(|c| !is_name(c)).unwrap_or(rest.len())),
+    };
+    let set = env.get(name).is_some_and(|v| !v.is_empty())
+        || std::env::var(name).is_ok_and(|v| !v.is_empty());
+    (!name.is_empty() && name.chars().all(is_name) && !set && after.starts_with('/'))
+        .then(|| (name.to_string(), after.to_string()))
+}
+
+/// Expand `~`/`$VAR`, make absolute against the current directory, and
+/// lexically normalize a path argument.
+///
+/// Unexpandable variables (unset in this process) are left verbatim.
+pub fn resolve(raw: &str) -> PathBuf {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
+    resolve_in(raw, &cwd)
+}
+
+/// [`resolve`] against `cwd` instead of the current directory.
+pub fn resolve_in(raw: &str, cwd: &Path) -> PathBuf {
+    let expanded = shellexpand::full(raw)
+        .map(|c| c.into_owned())
+        .unwrap_or_else(|_| raw.to_string());
+    let path = Path::new(&expanded);
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        cwd.join(path)
+    };
+    normalize(&absolute)
+}
+
+/// Remove `.` components and resolve `..` lexically (no symlink resolution).
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// The part of a pattern before its first glob character, cut back to the
+/// last complete path component.
+fn literal_prefix(pattern: &str) -> &str {
+    match pattern.find(['*', '?', '[']) {
+        Some(i) => match pattern[..i].rfind('/') {
+            Some(0) => "/",
+            Some(slash) => &pattern[..slash],
+            None => "",
+        },
+        None => pattern,
+    }
+}
+
+/// Match `text` against a shell-style glob: `*` (within a component),
+/// `**` (across components), and `?` (one non-`/` character).
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    glob_match_from(&p, &t)
+}
+
+fn glob_match_from(p: &[char], t: &[char]) -> bool {
+    match p.first() {
+        None => t.is_empty(),
+        Some('*') if p.get(1) == Some(&'*') => {
+            // `**/` may match zero components.
+            let rest = if p.get(2) == Some(&'/') {
+                &p[3..]
+            } else {
+                &p[2..]
+            };
+            (0..=t.len()).any(|i| glob_match_from(rest, &t[i..]))
+                || (p.get(2) == Some(&'/') && glob_match_from(&p[2..], t))
+        }
+        Some('*') => {
+            let limit = t.iter().position(|&c| c == '/').unwrap_or(t.len());
+            (0..=limit).any(|i| glob_match_from(&p[1..], &t[i..]))
+        }
+        Some('?') => t.first().is_some_and(|&c| c != '/') && glob_match_from(&p[1..], &t[1..]),
+        Some(&c) => t.first() == Some(&c) && glob_match_from(&p[1..], &t[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn policy(sensitive: &[&str]) -> PathPolicy {
+        PathPolicy::from_config(&PathsConfig {
+            sensitive: strings(sensitive),
+            ..Default::default()
+        })
+    }
+
+    fn write_policy() -> PathPolicy {
+        PathPolicy::from_config(&PathsConfig {
+            sensitive: strings(&["/home/u/.ssh"]),
+            protected: strings(&["/etc", "/**/.git/hooks"]),
+            sandbox: strings(&["/tmp"]),
+            project_sandbox: false,
+        })
+    }
+
+    fn root() -> &'static Path {
+        Path::new("/")
+    }
+
+    #[test]
+    fn glob_basics() {
+        assert!(glob_match("/tmp/*.log", "/tmp/a.log"));
+        assert!(!glob_match("/tmp/*.log", "/tmp/x/a.log"));
+        assert!(glob_match("/tmp/**/a.log", "/tmp/x/y/a.log"));
+        assert!(glob_match("/tmp/**/a.log", "/tmp/a.log"));
+        assert!(glob_match("/tmp/**", "/tmp/x/y"));
+        assert!(glob_match("/dev/fd/?", "/dev/fd/2"));
+    }
+
+    #[test]
+    fn entry_covers_descendants() {
+        let p = policy(&["/home/u/.ssh"]);
+        assert_eq!(
+            p.sensitive_match("/home/u/.ssh/id_rsa", root()),
+            Some("/home/u/.ssh")
+        );
+        assert_eq!(
+            p.sensitive_match("/home/u/.ssh", root()),
+            Some("/home/u/.ssh")
+        );
+        assert_eq!(p.sensitive_match("/home/u/.sshx", root()), None);
+    }
+
+    #[test]
+    fn dotdot_is_normalized() {
+        let p = policy(&["/home/u/.ssh"]);
+        assert!(
+            p.sensitive_match("/home/u/src/../.ssh/config", root())
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn tilde_expands_on_both_sides() {
+        let p = policy(&["~/.aws"]);
+        assert_eq!(
+            p.sensitive_match("~/.aws/credentials", root()),
+            Some("~/.aws")
+        );
+    }
+
+    #[test]
+    fn classify_write_targets() {
+        let p = write_policy();
+        assert_eq!(
+            p.classify_write("/etc/hosts", root()),
+            WriteTarget::Protected("/etc")
+        );
+        assert_eq!(
+            p.classify_write("/home/u/.ssh/authorized_keys", root()),
+            WriteTarget::Sensitive("/home/u/.ssh")
+        );
+        assert_eq!(p.classify_write("/tmp/x.log", root()), WriteTarget::Sandbox);
+        assert_eq!(p.classify_write("/srv/x", root()), WriteTarget::Outside);
+    }
+
+    #[test]
+    fn protected_overrides_sandbox() {
+        let p = write_policy();
+        assert_eq!(
+            p.classify_write("/tmp/repo/.git/hooks/pre-commit", root()),
+            WriteTarget::Protected("/**/.git/hooks")
+        );
+    }
+
+    #[test]
+    fn relative_paths_resolve_against_cwd() {
+        let p = write_policy();
+        let cwd = Path::new("/etc/nginx");
+        assert_eq!(
+            p.classify_write("sites/default", cwd),
+            WriteTarget::Protected("/etc")
+        );
+        assert_eq!(p.classify_write("../../tmp/x", cwd), WriteTarget::Sandbox);
+        assert_eq!(
+            p.classify_write("sites/default", Path::new("/tmp")),
+            WriteTarget::Sandbox
+        );
+        assert_eq!(
+            p.sensitive_match("id_rsa", Path::new("/home/u/.ssh")),
+            Some("/home/u/.ssh")
+        );
+    }
+
+    #[test]
+    fn write_target_decisions() {
+        assert_eq!(WriteTarget::Protected("/etc").decision(), Decision::Deny);
+        assert_eq!(WriteTarget::Sensitive("~/.ssh").decision(), Decision::Deny);
+        assert_eq!(WriteTarget::Sandbox.decision(), Decision::Allow);
+        assert_eq!(WriteTarget::Outside.decision(), Decision::Ask);
+    }
+
+    #[test]
+    fn containment() {
+        let p = policy(&["/home/u/.ssh"]);
+        assert!(p.contains_sensitive("/home/u", root()).is_some());
+        assert!(p.contains_sensitive("/", root()).is_some());
+        assert!(p.contains_sensitive("/home/u/src", root()).is_none());
+    }
+}
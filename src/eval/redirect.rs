@@ -0,0 +1,180 @@
+//! Output redirection targets that don't count as writes.
+//!
+//! The parser already ignores `/dev/null` and duplication to fds 0-2
+//! (`2>&1`). `[redirection] safe_sinks` extends that to the pseudo-files
+//! that name the same streams, like `/dev/stderr` and `/dev/fd/2`, and
+//! `safe_targets` to scratch locations (`/tmp/**`, `./target/**`, `*.log`).
+//!
+//! Each of a command's redirections is decided on its own, against its
+//! destination resolved once and carried in
+//! [`Redirection::resolved`](agent_shell_parser::parse::Redirection::resolved).
+
+use std::path::{Path, PathBuf};
+
+use agent_shell_parser::parse::Redirection;
+
+use crate::config::{PathsConfig, RedirectionConfig};
+use crate::eval::Decision;
+use crate::eval::paths::{PathPolicy, glob_match, resolve_in};
+
+/// The `[redirection]` settings.
+#[derive(Debug, Clone)]
+pub(crate) struct RedirectPolicy {
+    safe_sinks: Vec<String>,
+    safe_targets: Vec<String>,
+    /// Protected and sensitive paths are never safe targets.
+    paths: PathPolicy,
+}
+
+impl RedirectPolicy {
+    pub(crate) fn from_config(config: &RedirectionConfig, paths: &PathsConfig) -> Self {
+        Self {
+            safe_sinks: config.safe_sinks.clone(),
+            safe_targets: config.safe_targets.clone(),
+            paths: PathPolicy::from_config(paths),
+        }
+    }
+
+    /// Whether a redirection target is a safe sink. Quotes don't matter.
+    fn is_safe_sink(&self, target: &str) -> bool {
+        let target = target.trim_matches(['"', '\'']);
+        self.safe_sinks.iter().any(|sink| glob_match(sink, target))
+    }
+
+    /// Whether a redirection writes to a safe target, by its resolved
+    /// destination. Patterns with a `/` match the whole path (relative ones
+    /// against `cwd`); others match the file name. A target built from a
+    /// variable or a substitution, or one the `[paths]` rules deny, isn't.
+    fn is_safe_target(&self, redirection: &Redirection, cwd: &Path) -> bool {
+        let target = redirection.target.trim_matches(['"', '\'']);
+        let Some(path) = redirection.resolved.as_deref() else {
+            return false;
+        };
+        if redirection.operator == ">&" || target.contains(['$', '`']) {
+            return false;
+        }
+        if self.paths.classify_write(target, cwd).decision() == Decision::Deny {
+            return false;
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let path = path.to_string_lossy();
+        self.safe_targets.iter().any(|pattern| {
+            if pattern.contains('/') {
+                glob_match(&resolve_in(pattern, cwd).to_string_lossy(), &path)
+            } else {
+                glob_match(pattern, &name)
+            }
+        })
+    }
+
+    /// The redirection that counts among a command's `redirections`: the
+    /// first one that isn't to a safe sink or target, with its destination
+    /// resolved against `cwd`.
+    pub(crate) fn effective(
+        &self,
+        redirections: &[Redirection],
+        cwd: &Path,
+    ) -> Option<Redirection> {
+        redirections.iter().find_map(|redirection| {
+            let mut redirection = redirection.clone();
+            if redirection.resolved.is_none() {
+                redirection.resolved = destination(&redirection, cwd);
+            }
+            let safe =
+                self.is_safe_sink(&redirection.target) || self.is_safe_target(&redirection, cwd);
+            (!safe).then_some(redirection)
+        })
+    }
+}
+
+/// A redirection's destination resolved against `cwd`, `None` if it
+/// duplicates a file descriptor (`>&2`) or names none. Quotes don't matter.
+pub(crate) fn destination(redirection: &Redirection, cwd: &Path) -> Option<PathBuf> {
+    let target = redirection.target.trim_matches(['"', '\'']);
+    let fd = !target.is_empty() && target.bytes().all(|b| b.is_ascii_digit());
+    if target.is_empty() || fd || target == "-" {
+        return None;
+    }
+    Some(resolve_in(target, cwd))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use agent_shell_parser::parse::parse_with_substitutions;
+
+    fn effective(command: &str) -> Option<String> {
+        let config = Config::default_config();
+        effective_with(&config, command)
+    }
+
+    fn effective_with(config: &Config, command: &str) -> Option<String> {
+        resolved_with(config, command).map(|r| r.target)
+    }
+
+    fn resolved_with(config: &Config, command: &str) -> Option<Redirection> {
+        let policy = RedirectPolicy::from_config(&config.redirection, &config.paths);
+        let pipeline = parse_with_substitutions(command).unwrap();
+        policy.effective(
+            &pipeline.segments[0].redirections,
+            Path::new("/work/project"),
+        )
+    }
+
+    #[test]
+    fn standard_streams_are_safe() {
+        assert_eq!(effective("echo hi > /dev/stdout"), None);
+        assert_eq!(effective("echo oops >> /dev/stderr"), None);
+        assert_eq!(effective("echo oops > /dev/fd/2"), None);
+        assert_eq!(effective(r#"echo oops > "/dev/stderr""#), None);
+        assert_eq!(effective("echo hi > /dev/fd/3"), Some("/dev/fd/3".into()));
+        assert_eq!(effective("echo hi > out.txt"), Some("out.txt".into()));
+    }
+
+    #[test]
+    fn later_redirections_still_count() {
+        assert_eq!(
+            effective("make 2> /dev/stderr > build.log"),
+            Some("build.log".into())
+        );
+        assert_eq!(effective("make > /dev/stdout 2> /dev/stderr"), None);
+    }
+
+    #[test]
+    fn safe_targets() {
+        let mut config = Config::default_config();
+        config.redirection.safe_targets =
+            vec!["/tmp/**".into(), "./target/**".into(), "*.log".into()];
+        let effective = |command| effective_with(&config, command);
+        assert_eq!(effective("make > /tmp/build/out.txt"), None);
+        assert_eq!(effective("make > target/debug/out.txt"), None);
+        assert_eq!(effective("make 2>> ../build.log"), None);
+        assert_eq!(effective("make > src/out.txt"), Some("src/out.txt".into()));
+        assert_eq!(
+            effective("make > target/../src/x"),
+            Some("target/../src/x".into())
+        );
+        assert_eq!(effective("make > $OUT.log"), Some("$OUT.log".into()));
+        // Protected paths aren't safe, whatever the pattern.
+        assert_eq!(
+            effective("make > /etc/app.log"),
+            Some("/etc/app.log".into())
+        );
+        // Each redirection is decided by its own destination.
+        assert_eq!(
+            effective("make > a.log 2> a.log.bak"),
+            Some("a.log.bak".into())
+        );
+        assert_eq!(effective("make > a.log 2> b.log"), None);
+        assert_eq!(
+            resolved_with(&config, "make > a.log > src/out.txt").and_then(|r| r.resolved),
+            Some(PathBuf::from("/work/project/src/out.txt"))
+        );
+        // Nothing is a safe target by default.
+        assert_eq!(
+            effective_with(&Config::default_config(), "make > x.log"),
+            Some("x.log".into())
+        );
+    }
+}
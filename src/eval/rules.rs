@@ -0,0 +1,374 @@
+//! Conditional `[[rules]]`: a decision for a command when a condition holds.
+//!
+//! Rules are checked in config order, before wrappers and command specs,
+//! and the first whose condition holds decides the segment. A rule with an
+//! empty `command` applies to every command.
+//!
+//! A rule with a `schedule` is only checked while that `[schedules]` window
+//! is active, and one with `require_env` only when the hook process's own
+//! environment has those variables. The command can't influence either.
+//!
+//! A condition that doesn't parse, or fails at run time (a type error),
+//! fails closed rather than silently skipping the rule, as does a missing
+//! or malformed schedule: the command is decided as if it had no rules,
+//! raised to at least ASK, and the reason records the error.
+
+use crate::config::{RuleConfig, ScheduleConfig};
+use crate::eval::condition::{Condition, Scope};
+use crate::eval::schedule::Schedule;
+use crate::eval::trust::TrustPolicy;
+use crate::eval::{CommandContext, Decision, RuleMatch};
+use std::collections::HashMap;
+
+/// One compiled `[[rules]]` entry.
+#[derive(Debug, Clone)]
+struct Rule {
+    command: String,
+    /// The source text, used in reasons.
+    source: String,
+    condition: Result<Condition, String>,
+    decision: Decision,
+    reason: String,
+    /// The window the rule applies in, if restricted.
+    schedule: Option<Result<Schedule, String>>,
+    /// `NAME` or `NAME=value` entries the process environment must match.
+    require_env: Vec<String>,
+}
+
+/// What `[[rules]]` make of a command.
+#[derive(Debug)]
+pub enum RuleOutcome {
+    /// A rule's condition holds: its decision is the command's.
+    Matched(RuleMatch),
+    /// A rule for the command failed to evaluate; the message says which
+    /// and why.
+    Failed(String),
+}
+
+/// Matches commands against `[[rules]]`.
+#[derive(Debug, Clone, Default)]
+pub struct RulePolicy {
+    rules: Vec<Rule>,
+    /// Gives conditions the `trust` tier of the working directory.
+    trust: TrustPolicy,
+}
+
+impl RulePolicy {
+    /// Compile the rules, with the trust tiers and schedules they refer to,
+    /// from configuration.
+    pub fn from_config(
+        config: &[RuleConfig],
+        trust: &HashMap<String, String>,
+        schedules: &HashMap<String, ScheduleConfig>,
+    ) -> Self {
+        let schedule = |name: &str| match schedules.get(name) {
+            Some(schedule) => {
+                Schedule::from_config(schedule).map_err(|e| format!("schedule `{name}`: {e}"))
+            }
+            None => Err(format!("no schedule named `{name}`")),
+        };
+        Self {
+            rules: config
+                .iter()
+                .map(|rule| Rule {
+                    command: rule.command.clone(),
+                    source: rule.condition.clone(),
+                    condition: Condition::parse(&rule.condition),
+                    decision: rule.decision,
+                    reason: rule.reason.clone(),
+                    schedule: (!rule.schedule.is_empty()).then(|| schedule(&rule.schedule)),
+                    require_env: rule.require_env.clone(),
+                })
+                .collect(),
+            trust: TrustPolicy::from_config(trust),
+        }
+    }
+
+    /// Whether there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The decision of the first rule for `ctx` whose condition holds, or
+    /// the failure of a rule checked before it.
+    pub fn evaluate(&self, ctx: &CommandContext) -> Option<RuleOutcome> {
+        self.evaluate_at(ctx, crate::eval::schedule::now)
+    }
+
+    /// [`evaluate`](Self::evaluate) with the clock supplied. `now` is only
+    /// called if a scheduled rule applies to the command.
+    fn evaluate_at(&self, ctx: &CommandContext, now: impl Fn() -> u64) -> Option<RuleOutcome> {
+        let applicable = self
+            .rules
+            .iter()
+            .filter(|rule| rule.command.is_empty() || rule.command == ctx.base_command);
+        let cwd = ctx.working_dir();
+        let scope = Scope {
+            ctx,
+            trust: self.trust.tier(&cwd),
+        };
+        let time = std::cell::OnceCell::new();
+        for rule in applicable {
+            if !rule.require_env.iter().all(|entry| process_env_has(entry)) {
+                continue;
+            }
+            let active = match &rule.schedule {
+                None => Ok(true),
+                Some(schedule) => schedule
+                    .as_ref()
+                    .map(|s| s.is_active(*time.get_or_init(&now)))
+                    .map_err(Clone::clone),
+            };
+            let outcome = active.and_then(|active| match &rule.condition {
+                _ if !active => Ok(false),
+                Ok(condition) => condition.evaluate_in(&scope),
+                Err(e) => Err(e.clone()),
+            });
+            match outcome {
+                Ok(false) => continue,
+                Ok(true) => {
+                    let reason = if rule.reason.is_empty() {
+                        format!("rule: {}", rule.source)
+                    } else {
+                        rule.reason.clone()
+                    };
+                    return Some(RuleOutcome::Matched(RuleMatch {
+                        decision: rule.decision,
+                        reason,
+                    }));
+                }
+                Err(e) => {
+                    return Some(RuleOutcome::Failed(format!(
+                        "rule `{}` failed: {e}",
+                        rule.source
+                    )));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Whether the process environment has `NAME` set and non-empty, or for
+/// `NAME=value`, set to `value`.
+fn process_env_has(entry: &str) -> bool {
+    match entry.split_once('=') {
+        Some((name, value)) => std::env::var(name).is_ok_and(|v| v == value),
+        None => std::env::var(entry).is_ok_and(|v| !v.is_empty()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(command: &str, condition: &str, decision: Decision, reason: &str) -> RuleConfig {
+        RuleConfig {
+            command: command.into(),
+            condition: condition.into(),
+            decision,
+            reason: reason.into(),
+            schedule: String::new(),
+            require_env: Vec::new(),
+        }
+    }
+
+    fn matched(outcome: Option<RuleOutcome>) -> Option<(Decision, String)> {
+        match outcome? {
+            RuleOutcome::Matched(m) => Some((m.decision, m.reason)),
+            RuleOutcome::Failed(e) => panic!("rule failed: {e}"),
+        }
+    }
+
+    fn failure(outcome: Option<RuleOutcome>) -> String {
+        match outcome {
+            Some(RuleOutcome::Failed(e)) => e,
+            other => panic!("expected a failure, got {other:?}"),
+        }
+    }
+
+    fn eval(policy: &RulePolicy, command: &str) -> Option<(Decision, String)> {
+        matched(policy.evaluate(&CommandContext::from_command(command)))
+    }
+
+    #[test]
+    fn first_matching_rule_decides() {
+        let policy = RulePolicy::from_config(
+            &[
+                rule(
+                    "terraform",
+                    r#""-auto-approve" in args"#,
+                    Decision::Deny,
+                    "no unattended applies",
+                ),
+                rule("terraform", r#"args[0] == "plan""#, Decision::Allow, ""),
+                rule("terraform", "true", Decision::Ask, "terraform"),
+            ],
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+        assert_eq!(
+            eval(&policy, "terraform apply -auto-approve"),
+            Some((Decision::Deny, "no unattended applies".into()))
+        );
+        assert_eq!(
+            eval(&policy, "terraform plan"),
+            Some((Decision::Allow, r#"rule: args[0] == "plan""#.into()))
+        );
+        assert_eq!(
+            eval(&policy, "terraform apply"),
+            Some((Decision::Ask, "terraform".into()))
+        );
+        assert_eq!(eval(&policy, "ls"), None);
+    }
+
+    #[test]
+    fn empty_command_applies_to_all() {
+        let policy = RulePolicy::from_config(
+            &[rule(
+                "",
+                r#"redirection.starts_with("/etc/")"#,
+                Decision::Deny,
+                "",
+            )],
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+        assert_eq!(
+            eval(&policy, "echo x > /etc/motd").map(|(d, _)| d),
+            Some(Decision::Deny)
+        );
+        assert_eq!(eval(&policy, "echo x > out"), None);
+    }
+
+    #[test]
+    fn broken_conditions_fail() {
+        let policy = RulePolicy::from_config(
+            &[
+                rule("make", "args.contains(", Decision::Allow, ""),
+                rule("npm", "args > 1", Decision::Allow, ""),
+            ],
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+        let fail = |command| failure(policy.evaluate(&CommandContext::from_command(command)));
+        let reason = fail("make");
+        assert!(reason.contains("unexpected end"), "{reason}");
+        let reason = fail("npm test");
+        assert!(reason.contains("can't order"), "{reason}");
+    }
+
+    #[test]
+    fn scheduled_rules_apply_inside_their_window() {
+        let schedules = HashMap::from([
+            (
+                "working_hours".to_string(),
+                ScheduleConfig {
+                    hours: "09:00-17:00".into(),
+                    ..Default::default()
+                },
+            ),
+            (
+                "broken".to_string(),
+                ScheduleConfig {
+                    days: vec!["someday".into()],
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let policy = RulePolicy::from_config(
+            &[
+                RuleConfig {
+                    schedule: "working_hours".into(),
+                    ..rule("kubectl", "true", Decision::Allow, "rollouts in hours")
+                },
+                RuleConfig {
+                    schedule: "broken".into(),
+                    ..rule("make", "true", Decision::Allow, "")
+                },
+                RuleConfig {
+                    schedule: "missing".into(),
+                    ..rule("npm", "true", Decision::Allow, "")
+                },
+            ],
+            &HashMap::new(),
+            &schedules,
+        );
+        let at = |command: &str, hour: u64| {
+            policy.evaluate_at(&CommandContext::from_command(command), || hour * 3600)
+        };
+        assert_eq!(
+            matched(at("kubectl rollout restart", 10)),
+            Some((Decision::Allow, "rollouts in hours".into()))
+        );
+        assert_eq!(matched(at("kubectl rollout restart", 20)), None);
+        let reason = failure(at("make", 10));
+        assert!(reason.contains("schedule `broken`: days"), "{reason}");
+        let reason = failure(at("npm test", 10));
+        assert!(reason.contains("no schedule named `missing`"), "{reason}");
+        // Rules without a schedule never read the clock.
+        let unscheduled = RulePolicy::from_config(
+            &[rule("ls", "true", Decision::Allow, "")],
+            &HashMap::new(),
+            &schedules,
+        );
+        let ctx = CommandContext::from_command("ls");
+        assert!(
+            unscheduled
+                .evaluate_at(&ctx, || panic!("clock read"))
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn require_env_reads_the_process_environment() {
+        let path = std::env::var("PATH").unwrap();
+        let gated = |require_env: Vec<String>| {
+            let policy = RulePolicy::from_config(
+                &[RuleConfig {
+                    require_env,
+                    ..rule("kubectl", "true", Decision::Deny, "")
+                }],
+                &HashMap::new(),
+                &HashMap::new(),
+            );
+            eval(&policy, "kubectl apply -f x.yaml").map(|(d, _)| d)
+        };
+        assert_eq!(gated(vec!["PATH".into()]), Some(Decision::Deny));
+        assert_eq!(gated(vec![format!("PATH={path}")]), Some(Decision::Deny));
+        assert_eq!(gated(vec!["PATH=/nowhere".into()]), None);
+        assert_eq!(
+            gated(vec!["PATH".into(), "CC_TOOLGATE_TEST_UNSET".into()]),
+            None
+        );
+        // An assignment in the command doesn't count.
+        let policy = RulePolicy::from_config(
+            &[RuleConfig {
+                require_env: vec!["CC_TOOLGATE_TEST_UNSET".into()],
+                ..rule("kubectl", "true", Decision::Allow, "")
+            }],
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+        assert_eq!(
+            eval(&policy, "CC_TOOLGATE_TEST_UNSET=1 kubectl get pods"),
+            None
+        );
+    }
+
+    #[test]
+    fn conditions_see_the_trust_tier_of_the_working_directory() {
+        let cwd = std::env::current_dir().unwrap();
+        let rules = [rule("make", r#"trust != "high""#, Decision::Ask, "")];
+        let trusted = HashMap::from([(cwd.display().to_string(), "high".to_string())]);
+        assert_eq!(
+            eval(
+                &RulePolicy::from_config(&rules, &trusted, &HashMap::new()),
+                "make"
+            ),
+            None
+        );
+        let policy = RulePolicy::from_config(&rules, &HashMap::new(), &HashMap::new());
+        assert_eq!(eval(&policy, "make").map(|(d, _)| d), Some(Decision::Ask));
+    }
+}
@@ -0,0 +1,253 @@
+//! Time windows from `[schedules]`, which restrict when a `[[rules]]` entry
+//! applies.
+//!
+//! A schedule combines optional constraints, all of which must hold:
+//!
+//! - `days`: weekdays (`mon` … `sun`); empty means every day.
+//! - `hours`: a `HH:MM-HH:MM` range, end exclusive. A range whose end is
+//!   before its start wraps past midnight (`22:00-06:00`).
+//! - `from` / `until`: `YYYY-MM-DD` dates, both inclusive, e.g. a change
+//!   freeze.
+//! - `utc_offset`: the offset (`+01:00`, `-05:30`) the fields above are read
+//!   in. Defaults to UTC; there's no time zone database, so a daylight-saving
+//!   change means editing the offset.
+//!
+//! The clock is read only when a scheduled rule applies to a command, so
+//! targets without one (wasm32-unknown-unknown) work as long as no rule
+//! has a schedule.
+
+use crate::config::ScheduleConfig;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// One compiled `[schedules.<name>]` entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    /// Allowed weekdays, Monday = 0. Empty allows every day.
+    days: Vec<u8>,
+    /// Start and end minute of the day.
+    hours: Option<(u32, u32)>,
+    /// First day (days since the Unix epoch), inclusive.
+    from: Option<i64>,
+    /// Last day (days since the Unix epoch), inclusive.
+    until: Option<i64>,
+    /// Seconds east of UTC.
+    offset: i64,
+}
+
+impl Schedule {
+    /// Compile a schedule, reporting the first malformed field.
+    pub fn from_config(config: &ScheduleConfig) -> Result<Self, String> {
+        let days = config
+            .days
+            .iter()
+            .map(|day| {
+                let lower = day.to_ascii_lowercase();
+                DAY_NAMES
+                    .iter()
+                    .position(|name| lower.starts_with(name))
+                    .map(|i| i as u8)
+                    .ok_or_else(|| format!("days: unknown day `{day}`"))
+            })
+            .collect::<Result<_, _>>()?;
+        let hours = match config.hours.trim() {
+            "" => None,
+            range => {
+                let (start, end) = range
+                    .split_once('-')
+                    .ok_or_else(|| format!("hours: expected HH:MM-HH:MM, got `{range}`"))?;
+                Some((minute_of_day(start)?, minute_of_day(end)?))
+            }
+        };
+        let date = |field: &str, text: &str| match text.trim() {
+            "" => Ok(None),
+            text => days_since_epoch(text)
+                .map(Some)
+                .map_err(|e| format!("{field}: {e}")),
+        };
+        Ok(Self {
+            days,
+            hours,
+            from: date("from", &config.from)?,
+            until: date("until", &config.until)?,
+            offset: match config.utc_offset.trim() {
+                "" => 0,
+                offset => utc_offset(offset)?,
+            },
+        })
+    }
+
+    /// Whether `now` (Unix seconds) falls inside the schedule.
+    pub fn is_active(&self, now: u64) -> bool {
+        let local = now as i64 + self.offset;
+        let day = local.div_euclid(86_400);
+        let minute = (local.rem_euclid(86_400) / 60) as u32;
+        // 1970-01-01 was a Thursday.
+        let weekday = (day + 3).rem_euclid(7) as u8;
+
+        self.from.is_none_or(|from| day >= from)
+            && self.until.is_none_or(|until| day <= until)
+            && (self.days.is_empty() || self.days.contains(&weekday))
+            && self.hours.is_none_or(|(start, end)| {
+                if start <= end {
+                    (start..end).contains(&minute)
+                } else {
+                    minute >= start || minute < end
+                }
+            })
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// `HH:MM` → minutes since midnight.
+fn minute_of_day(text: &str) -> Result<u32, String> {
+    let text = text.trim();
+    let parsed = text.split_once(':').and_then(|(h, m)| {
+        let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+        (h <= 24 && m < 60 && h * 60 + m <= 24 * 60).then_some(h * 60 + m)
+    });
+    parsed.ok_or_else(|| format!("hours: expected HH:MM, got `{text}`"))
+}
+
+/// `YYYY-MM-DD` → days since 1970-01-01, in the proleptic Gregorian calendar.
+fn days_since_epoch(text: &str) -> Result<i64, String> {
+    let invalid = || format!("expected YYYY-MM-DD, got `{text}`");
+    let mut parts = text.splitn(3, '-');
+    let mut next = || -> Result<i64, String> {
+        parts
+            .next()
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(invalid)
+    };
+    let (year, month, day) = (next()?, next()?, next()?);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = [
+        31,
+        if leap { 29 } else { 28 },
+        31,
+        30,
+        31,
+        30,
+        31,
+        31,
+        30,
+        31,
+        30,
+        31,
+    ];
+    if !(1..=12).contains(&month) || day < 1 || day > month_days[month as usize - 1] {
+        return Err(invalid());
+    }
+    // Howard Hinnant's days_from_civil.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Ok(era * 146_097 + doe - 719_468)
+}
+
+/// `+HH:MM` / `-HH:MM` → seconds east of UTC.
+fn utc_offset(text: &str) -> Result<i64, String> {
+    let invalid = || format!("utc_offset: expected +HH:MM or -HH:MM, got `{text}`");
+    let (sign, rest) = match text.split_at_checked(1) {
+        Some(("+", rest)) => (1, rest),
+        Some(("-", rest)) => (-1, rest),
+        _ => return Err(invalid()),
+    };
+    let minutes = minute_of_day(rest).map_err(|_| invalid())?;
+    if minutes > 18 * 60 {
+        return Err(invalid());
+    }
+    Ok(sign * i64::from(minutes) * 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-10-12, a Monday, 00:00 UTC.
+    const MONDAY: u64 = 1_791_763_200;
+    const HOUR: u64 = 3600;
+
+    fn schedule(toml: &str) -> Result<Schedule, String> {
+        Schedule::from_config(&toml::from_str(toml).unwrap())
+    }
+
+    #[test]
+    fn dates_count_from_the_epoch() {
+        assert_eq!(days_since_epoch("1970-01-01"), Ok(0));
+        assert_eq!(days_since_epoch("2000-03-01"), Ok(11_017));
+        assert_eq!(days_since_epoch("2026-10-12"), Ok((MONDAY / 86_400) as i64));
+        assert!(days_since_epoch("2026-02-29").is_err());
+        assert!(days_since_epoch("2026-13-01").is_err());
+        assert!(days_since_epoch("next week").is_err());
+    }
+
+    #[test]
+    fn working_hours() {
+        let s = schedule(
+            r#"
+            days = ["mon", "tue", "wed", "thu", "fri"]
+            hours = "09:00-18:00"
+            "#,
+        )
+        .unwrap();
+        assert!(!s.is_active(MONDAY + 8 * HOUR));
+        assert!(s.is_active(MONDAY + 9 * HOUR));
+        assert!(s.is_active(MONDAY + 4 * 24 * HOUR + 17 * HOUR));
+        assert!(!s.is_active(MONDAY + 18 * HOUR));
+        // Saturday
+        assert!(!s.is_active(MONDAY + 5 * 24 * HOUR + 10 * HOUR));
+    }
+
+    #[test]
+    fn overnight_hours_and_offsets() {
+        let s = schedule(
+            r#"
+            hours = "22:00-06:00"
+            utc_offset = "+02:00"
+            "#,
+        )
+        .unwrap();
+        // 21:00 UTC is 23:00 local.
+        assert!(s.is_active(MONDAY + 21 * HOUR));
+        assert!(s.is_active(MONDAY + 3 * HOUR));
+        assert!(!s.is_active(MONDAY + 4 * HOUR));
+        assert!(!s.is_active(MONDAY + 12 * HOUR));
+    }
+
+    #[test]
+    fn freeze_dates_are_inclusive() {
+        let s = schedule(
+            r#"
+            from = "2026-10-13"
+            until = "2026-10-14"
+            "#,
+        )
+        .unwrap();
+        assert!(!s.is_active(MONDAY + 23 * HOUR));
+        assert!(s.is_active(MONDAY + 24 * HOUR));
+        assert!(s.is_active(MONDAY + 3 * 24 * HOUR - 1));
+        assert!(!s.is_active(MONDAY + 3 * 24 * HOUR));
+    }
+
+    #[test]
+    fn malformed_fields_are_reported() {
+        let err = |toml: &str| schedule(toml).unwrap_err();
+        assert!(err(r#"days = ["someday"]"#).starts_with("days:"));
+        assert!(err(r#"hours = "9-5""#).starts_with("hours:"));
+        assert!(err(r#"hours = "09:00-25:00""#).starts_with("hours:"));
+        assert!(err(r#"until = "2026/12/31""#).starts_with("until:"));
+        assert!(err(r#"utc_offset = "CET""#).starts_with("utc_offset:"));
+    }
+}
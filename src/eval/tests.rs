@@ -17,7 +17,9 @@ fn seg(command: &str) -> ShellSegment {
         command: command.to_string(),
         words,
         redirection: None,
+        redirections: vec![],
         substitutions: vec![],
+        groups: vec![],
     }
 }
 
@@ -27,6 +29,7 @@ fn seg_with_subst(command: &str) -> ShellSegment {
         command: command.to_string(),
         words,
         redirection: None,
+        redirections: vec![],
         substitutions: vec![parse::SubstitutionSpan {
             start: 0,
             end: 1,
@@ -35,8 +38,10 @@ fn seg_with_subst(command: &str) -> ShellSegment {
                 operators: vec![],
                 structural_substitutions: vec![],
                 has_parse_errors: false,
+                functions: vec![],
             },
         }],
+        groups: vec![],
     }
 }
 
@@ -588,11 +593,11 @@ fn compound_ask_decision_annotated_with_project_overlay() {
 #[test]
 fn background_operator_evaluates_both_segments() {
     // Background operator (&) separates two commands — both are evaluated,
-    // strictest decision wins (rm -rf / → Ask)
+    // strictest decision wins (rm -rf / → Deny)
     let config = crate::config::Config::default_config();
     let reg = CommandRegistry::from_config(&config);
     let result = reg.evaluate("echo hello & rm -rf /");
-    assert_eq!(result.decision, Decision::Ask, "reason: {}", result.reason);
+    assert_eq!(result.decision, Decision::Deny, "reason: {}", result.reason);
 }
 
 #[test]
@@ -608,3 +613,766 @@ fn background_operator_allows_benign_commands() {
         result.reason
     );
 }
+
+// ── per-segment results ──
+
+#[test]
+fn detailed_simple_command_has_one_segment() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    let eval = reg.evaluate_detailed("ls -la");
+    assert_eq!(eval.result.decision, Decision::Allow);
+    assert_eq!(
+        eval.segments,
+        vec![SegmentResult {
+            command: "ls -la".into(),
+            decision: Decision::Allow,
+            reason: eval.result.reason.clone(),
+            suggestion: None,
+        }]
+    );
+}
+
+#[test]
+fn detailed_compound_reports_each_segment() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    let eval = reg.evaluate_detailed("ls && rm -rf build && shred secret");
+    assert_eq!(eval.result.decision, Decision::Deny);
+    let summary: Vec<(&str, Decision)> = eval
+        .segments
+        .iter()
+        .map(|s| (s.command.as_str(), s.decision))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("ls", Decision::Allow),
+            ("rm -rf build", Decision::Ask),
+            ("shred secret", Decision::Deny),
+        ]
+    );
+}
+
+#[test]
+fn detailed_includes_substitution_segments() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    let eval = reg.evaluate_detailed("echo $(shred secret)");
+    assert_eq!(eval.result.decision, Decision::Deny);
+    assert!(
+        eval.segments
+            .iter()
+            .any(|s| s.command == "shred secret" && s.decision == Decision::Deny),
+        "segments: {:?}",
+        eval.segments
+    );
+}
+
+#[test]
+fn multi_line_segments_read_as_they_run() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    let eval = reg.evaluate_detailed(
+        "# clean up\nif true; then\n\n  rm -rf build \\\n    dist # both\nfi && ls \\\n-la",
+    );
+    let texts: Vec<&str> = eval.segments.iter().map(|s| s.command.as_str()).collect();
+    assert_eq!(texts, ["true", "rm -rf build dist", "ls -la"]);
+    assert!(eval.result.reason.contains("[rm -rf build dist] -> ASK"));
+
+    assert_eq!(segment_text("echo 'a \\\nb'"), "echo 'a \\\nb'");
+    assert_eq!(segment_text("echo \"a \\\n  b\""), "echo \"a   b\"");
+    assert_eq!(segment_text("echo a\\ b \\\n c"), "echo a\\ b c");
+    assert_eq!(
+        segment_text("cat <<EOF\nx \\\ny\nEOF"),
+        "cat <<EOF\nx \\\ny\nEOF"
+    );
+}
+
+#[test]
+fn function_calls_evaluate_the_body() {
+    let mut config = crate::config::Config::default_config();
+    config.commands.deny.push("reboot".into());
+    let reg = CommandRegistry::from_config(&config);
+
+    let eval = reg.evaluate_detailed("g() { git status; }; g");
+    assert_eq!(eval.result.decision, Decision::Allow);
+    assert_eq!(eval.segments[1].command, "g");
+    assert!(
+        eval.segments[1]
+            .reason
+            .starts_with("function g runs [git status]")
+    );
+
+    // Arguments reach the body, and calls reach functions defined earlier.
+    let eval = reg.evaluate_detailed("f() { \"$1\" now; }; g() { f \"$@\"; }; g reboot");
+    assert_eq!(eval.result.decision, Decision::Deny);
+
+    // A function shadows the command of the same name.
+    let eval = reg.evaluate_detailed("ls() { reboot; }; ls");
+    assert_eq!(eval.result.decision, Decision::Deny);
+
+    let eval = reg.evaluate_detailed("f() { f; }; f");
+    assert_eq!(eval.result.decision, Decision::Ask);
+    assert!(eval.result.reason.contains("nested too deeply"));
+}
+
+#[test]
+fn group_floors_apply_to_subshells_and_brace_groups() {
+    let mut config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    assert_eq!(reg.evaluate("(cd src && ls)").decision, Decision::Allow);
+
+    config.groups.subshell = Decision::Ask;
+    let reg = CommandRegistry::from_config(&config);
+    let result = reg.evaluate("(cd src && ls)");
+    assert_eq!(result.decision, Decision::Ask);
+    assert!(
+        result
+            .reason
+            .contains("[(cd src && ls)] -> ASK: grouped command ([groups] subshell floor)"),
+        "{}",
+        result.reason
+    );
+    assert_eq!(reg.evaluate("{ ls; } && pwd").decision, Decision::Allow);
+    // A floor never lowers a stricter segment.
+    assert_eq!(reg.evaluate("(shred secret)").decision, Decision::Deny);
+    // Groups inside substitutions count, and each group is reported once.
+    let result = reg.evaluate("echo $( (cd src; ls) )");
+    assert_eq!(result.decision, Decision::Ask);
+    assert_eq!(result.reason.matches("subshell floor").count(), 1);
+
+    config.groups.brace_group = Decision::Deny;
+    let reg = CommandRegistry::from_config(&config);
+    assert_eq!(reg.evaluate("{ ls; } && pwd").decision, Decision::Deny);
+    // Function bodies aren't brace groups.
+    assert_eq!(reg.evaluate("f() { ls; }; f").decision, Decision::Allow);
+}
+
+#[test]
+fn cmd_exe_command_lines_are_split_by_cmd_rules() {
+    let reg = CommandRegistry::from_config(&crate::config::Config::default_config());
+    let result = reg.evaluate(r#"cmd /c "dir /b & git status""#);
+    assert_eq!(result.decision, Decision::Allow, "{}", result.reason);
+    assert_eq!(
+        result.reason,
+        "cmd.exe runs [dir /b] -> ALLOW: allowed: dir; [git status] -> ALLOW: read-only git status"
+    );
+    assert_eq!(
+        reg.evaluate(r#"cmd.exe /c "del /s /q build && rd /s /q out""#)
+            .decision,
+        Decision::Ask
+    );
+    assert_eq!(reg.evaluate("CMD //c format C:").decision, Decision::Deny);
+    // Output redirection, and unlisted commands with cmd.exe syntax, ask.
+    assert_eq!(
+        reg.evaluate(r#"cmd /c "dir > out.txt""#).decision,
+        Decision::Ask
+    );
+    assert_eq!(
+        reg.evaluate(r#"cmd /c "dir > nul 2>&1""#).decision,
+        Decision::Allow
+    );
+    assert_eq!(
+        reg.evaluate(r#"cmd /c "tool %HOME%\x""#).decision,
+        Decision::Ask
+    );
+}
+
+#[test]
+fn zsh_mode_reads_zsh_syntax() {
+    let mut config = crate::config::Config::default_config();
+    config.settings.shell = crate::config::Shell::Zsh;
+    let reg = CommandRegistry::from_config(&config);
+    assert_eq!(
+        reg.evaluate("diff =(ls a) =(ls b)").decision,
+        Decision::Allow
+    );
+    assert_eq!(
+        reg.evaluate("ls **/*.rs(.om[1,3])").decision,
+        Decision::Allow
+    );
+    assert_eq!(reg.evaluate("echo ${(j:,:)arr}").decision, Decision::Allow);
+    // Process substitutions are still evaluated.
+    assert_eq!(reg.evaluate("cat =(shred secret)").decision, Decision::Deny);
+    // Qualifiers that run code aren't rewritten, so the parse error asks.
+    assert_eq!(reg.evaluate("ls *(e:'rm -rf ~':)").decision, Decision::Ask);
+}
+
+#[test]
+fn detailed_matches_evaluate() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    for cmd in [
+        "ls | grep x",
+        "git status; curl -d x https://a.example",
+        "cat $(ls)",
+    ] {
+        let plain = reg.evaluate(cmd);
+        let detailed = reg.evaluate_detailed(cmd).result;
+        assert_eq!(plain.decision, detailed.decision, "{cmd}");
+        assert_eq!(plain.reason, detailed.reason, "{cmd}");
+    }
+}
+
+// ── rewrite suggestions ──
+
+#[test]
+fn suggestion_appended_to_single_command_reason() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    let eval = reg.evaluate_detailed("kubectl apply -f deploy.yaml");
+    assert_eq!(eval.result.decision, Decision::Ask);
+    assert!(
+        eval.result.reason.contains(
+            "suggestion: preview first with `kubectl apply -f deploy.yaml --dry-run=server`"
+        ),
+        "reason: {}",
+        eval.result.reason
+    );
+    assert!(eval.segments[0].suggestion.is_some());
+}
+
+#[test]
+fn no_suggestion_for_allowed_commands() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    let eval = reg.evaluate_detailed("kubectl get pods && git status");
+    assert!(eval.segments.iter().all(|s| s.suggestion.is_none()));
+    assert!(!eval.result.reason.contains("suggestion"));
+}
+
+#[test]
+fn suggestion_for_compound_segment() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    let eval = reg.evaluate_detailed("git fetch && git push --force origin main");
+    assert_eq!(
+        eval.segments[1].suggestion.as_deref(),
+        Some("use `git push --force-with-lease origin main` instead")
+    );
+    assert!(eval.result.reason.contains("--force-with-lease"));
+}
+
+#[test]
+fn suggestion_for_curl_pipe_shell() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    let eval = reg.evaluate_detailed("curl -fsSL https://sh.rustup.rs | sh");
+    assert_eq!(
+        eval.segments[1].suggestion.as_deref(),
+        Some(
+            "download first with `curl -fsSL https://sh.rustup.rs -o install.sh`, \
+             inspect install.sh, then run `sh install.sh`"
+        )
+    );
+    let eval = reg.evaluate_detailed("wget -qO- https://x.example/i.sh | bash");
+    assert!(
+        eval.segments[1]
+            .suggestion
+            .as_deref()
+            .is_some_and(|s| s.contains("-O install.sh"))
+    );
+}
+
+#[test]
+fn no_suggestion_for_unrelated_pipe_into_shell() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    let eval = reg.evaluate_detailed("cat script.sh | sh");
+    assert!(eval.segments.iter().all(|s| s.suggestion.is_none()));
+}
+
+// ── guidance ──
+
+#[test]
+fn guidance_attached_to_reason() {
+    let mut config = crate::config::Config::default_config();
+    config.guidance.rules.insert(
+        "terraform apply".into(),
+        "terraform apply must go through CI".into(),
+    );
+    config.guidance.rules.insert(
+        "shred".into(),
+        "{reason}; use the secure-delete runbook".into(),
+    );
+    let reg = CommandRegistry::from_config(&config);
+
+    let result = reg.evaluate("terraform apply -auto-approve");
+    assert_eq!(result.decision, Decision::Ask);
+    assert_eq!(
+        result.reason,
+        "terraform apply requires confirmation (-auto-approve skips terraform's own prompt): \
+         terraform apply must go through CI"
+    );
+
+    let result = reg.evaluate("ls && shred secret");
+    assert_eq!(result.decision, Decision::Deny);
+    assert!(result.reason.contains("; use the secure-delete runbook"));
+
+    // Wrapped commands get the inner command's guidance.
+    let result = reg.evaluate("sudo terraform apply");
+    assert!(result.reason.contains("must go through CI"));
+}
+
+#[test]
+fn guidance_skips_allowed_commands() {
+    let mut config = crate::config::Config::default_config();
+    config
+        .guidance
+        .rules
+        .insert("git".into(), "see CONTRIBUTING".into());
+    let reg = CommandRegistry::from_config(&config);
+    assert_eq!(reg.evaluate("git status").reason, "read-only git status");
+}
+
+// ── audit mode ──
+
+#[test]
+fn audited_result_allows_and_records_decision() {
+    let result = crate::evaluate("shred secret");
+    assert_eq!(result.decision, Decision::Deny);
+    let audited = result.audited();
+    assert_eq!(audited.decision, Decision::Allow);
+    assert_eq!(
+        audited.reason,
+        format!("audit mode, would deny: {}", result.reason)
+    );
+}
+
+// ── adversarial input ──
+
+/// Inputs that have tripped shell parsers: unterminated constructs, deep
+/// nesting, control bytes, and oversized words. None may panic.
+const ADVERSARIAL: &[&str] = &[
+    "",
+    "'",
+    "\"",
+    "$(",
+    "`",
+    "echo $((1 +",
+    "cat <<EOF",
+    "case x in",
+    "for i in; do",
+    "if then fi",
+    "a\0b",
+    "echo \u{1b}[31m",
+    "|||",
+    ";;&&||",
+    "$(((((((((((((((((((((((((((((((((((((((((((x",
+    "echo ${x:-${y:-${z:-${w",
+    "\\\\\\",
+    "ls \u{feff}\u{202e}",
+];
+
+#[test]
+fn adversarial_inputs_never_panic() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    let deep = format!("echo {}x{}", "$(".repeat(500), ")".repeat(500));
+    let long = "a".repeat(100_000);
+    for cmd in ADVERSARIAL
+        .iter()
+        .copied()
+        .chain([deep.as_str(), long.as_str()])
+    {
+        let _ = crate::parse::parse_lossy(cmd);
+        // Called directly so a panic fails the test instead of being caught.
+        let eval = reg.evaluate_detailed(cmd);
+        assert!(!eval.result.reason.is_empty(), "{cmd:?}");
+    }
+}
+
+#[test]
+fn panicking_spec_fails_closed() {
+    struct Panics;
+    impl CommandSpec for Panics {
+        fn evaluate(&self, _ctx: &CommandContext) -> RuleMatch {
+            panic!("spec bug");
+        }
+    }
+    let config = crate::config::Config::default_config();
+    let mut reg = CommandRegistry::from_config(&config);
+    reg.specs
+        .insert("ls".into(), LazySpec::ready(Box::new(Panics)));
+    let eval = reg.evaluate_fail_closed("ls -la");
+    assert_eq!(eval.result.decision, Decision::Ask);
+    assert!(eval.result.reason.contains("fail-closed"));
+}
+
+#[cfg(feature = "native")]
+#[test]
+fn evaluation_times_parse_and_eval() {
+    let reg = CommandRegistry::from_config(&crate::config::Config::default_config());
+    let eval = reg.evaluate_detailed("echo $(date) && git status | head");
+    assert!(eval.timings.parse > std::time::Duration::ZERO);
+    assert!(eval.timings.eval > std::time::Duration::ZERO);
+    assert_eq!(eval.timings.total(), eval.timings.parse + eval.timings.eval);
+}
+
+#[test]
+fn segment_context_uses_parsed_redirection() {
+    let pipeline = parse::parse_with_substitutions("echo hi > out.txt && ls").unwrap();
+    let ctx = CommandContext::from_segment(&pipeline.segments[0]);
+    assert_eq!(ctx.redirection.map(|r| r.target), Some("out.txt".into()));
+    assert!(
+        CommandContext::from_segment(&pipeline.segments[1])
+            .redirection
+            .is_none()
+    );
+}
+
+#[test]
+fn wrapped_command_words_are_not_reparsed() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    // Joining the inner words back into "echo a > b" used to read the quoted
+    // argument as a redirection.
+    assert_eq!(reg.evaluate("xargs echo 'a > b'").decision, Decision::Allow);
+    assert_eq!(reg.evaluate("env ls > out").decision, Decision::Ask);
+    let ctx = CommandContext::from_words(parse::tokenize("rm -rf 'my dir'"));
+    assert_eq!(ctx.base_command, "rm");
+    assert_eq!(ctx.args().last().map(|w| w.as_str()), Some("my dir"));
+}
+
+#[test]
+fn context_records_substitutions() {
+    let ctx = CommandContext::from_command("rm -f $(find . -name '*.tmp') `ls` keep");
+    assert_eq!(ctx.substitutions, ["$(find . -name '*.tmp')", "`ls`"]);
+    assert!(ctx.is_substituted("$(find . -name '*.tmp')"));
+    assert!(ctx.is_substituted("`ls`"));
+    assert!(!ctx.is_substituted("keep"));
+    assert!(
+        CommandContext::from_command("ls -la")
+            .substitutions
+            .is_empty()
+    );
+}
+
+#[test]
+fn tool_specs_are_built_on_first_use() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    assert!(!reg.specs["git"].is_built());
+    assert!(!reg.specs["tee"].is_built());
+    reg.evaluate("git status");
+    assert!(reg.specs["git"].is_built());
+    assert!(!reg.specs["tee"].is_built());
+}
+
+#[test]
+fn raw_network_tools_share_one_spec() {
+    let config = crate::config::Config::default_config();
+    let reg = CommandRegistry::from_config(&config);
+    assert!(Arc::ptr_eq(&reg.specs["nc"], &reg.specs["socat"]));
+    assert!(!Arc::ptr_eq(&reg.specs["cp"], &reg.specs["mv"]));
+    // Flat-list commands have no spec of their own.
+    assert!(!reg.specs.contains_key("ls"));
+    assert_eq!(reg.simple.get("ls"), Some(&Decision::Allow));
+}
+
+#[test]
+fn shared_registry_evaluates_concurrently() {
+    let config = crate::config::Config::default_config();
+    let commands = [
+        "ls -la",
+        "git push --force origin main",
+        "tee /etc/hosts",
+        "kubectl delete pod x",
+        "nc -l 8080",
+        "shred x",
+        "cargo build && rm -rf target",
+    ];
+    let expected: Vec<RuleMatch> = {
+        let reg = CommandRegistry::from_config(&config);
+        commands.iter().map(|c| reg.evaluate(c)).collect()
+    };
+
+    // Every thread races to build the same lazily-constructed specs.
+    let mut reg = CommandRegistry::from_config(&config);
+    reg.set_escalate_deny(true);
+    let shared = reg.into_shared();
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let reg = Arc::clone(&shared);
+            std::thread::spawn(move || commands.map(|c| reg.evaluate(c)))
+        })
+        .collect();
+    for handle in handles {
+        for (got, want) in handle.join().unwrap().iter().zip(&expected) {
+            let want = match want.decision {
+                Decision::Deny => Decision::Ask,
+                d => d,
+            };
+            assert_eq!(got.decision, want);
+        }
+    }
+    assert!(shared.specs["git"].is_built());
+}
+
+#[test]
+fn conditional_rules_decide_before_specs() {
+    let config = crate::config::Config::with_overlay(
+        r#"
+        [[rules]]
+        command = "git"
+        condition = 'args[0] == "push" && env["DEPLOY"] == "1"'
+        decision = "deny"
+        reason = "no deploy pushes"
+
+        [[rules]]
+        command = "make"
+        condition = "privileged"
+        decision = "deny"
+
+        [[rules]]
+        command = "make"
+        condition = '"test" in args'
+        decision = "allow"
+        "#,
+    )
+    .unwrap();
+    let reg = CommandRegistry::from_config(&config);
+    let result = reg.evaluate("DEPLOY=1 git push origin main");
+    assert_eq!(result.decision, Decision::Deny);
+    assert_eq!(result.reason, "no deploy pushes");
+    // Exported earlier in the compound command.
+    assert_eq!(
+        reg.evaluate("export DEPLOY=1 && git push").decision,
+        Decision::Deny
+    );
+    // Otherwise the git spec decides as usual.
+    assert_eq!(reg.evaluate("git push origin main").decision, Decision::Ask);
+    assert_eq!(reg.evaluate("make test").decision, Decision::Allow);
+    assert_eq!(reg.evaluate("make install").decision, Decision::Ask);
+    // Rules also see commands unwrapped from sudo.
+    assert_eq!(reg.evaluate("sudo make test").decision, Decision::Deny);
+}
+
+#[test]
+fn failed_rules_fail_closed() {
+    let config = crate::config::Config::with_overlay(
+        r#"
+        [[rules]]
+        condition = "args.contains("
+        decision = "allow"
+        "#,
+    )
+    .unwrap();
+    let reg = CommandRegistry::from_config(&config);
+    // The spec's own DENY stands.
+    let result = reg.evaluate("rm -rf /");
+    assert_eq!(result.decision, Decision::Deny);
+    assert!(result.reason.contains("failed"), "{}", result.reason);
+    // An allowed command asks, and the reason records the error.
+    let result = reg.evaluate("ls");
+    assert_eq!(result.decision, Decision::Ask);
+    assert!(
+        result.reason.starts_with("rule `args.contains(` failed:"),
+        "{}",
+        result.reason
+    );
+}
+
+#[test]
+fn relative_paths_resolve_against_the_registry_cwd() {
+    let mut config = crate::config::Config::default_config();
+    config.paths.project_sandbox = false;
+    config.rules.push(crate::config::RuleConfig {
+        command: String::new(),
+        condition: r#"redirection.starts_with("/etc/")"#.into(),
+        decision: Decision::Deny,
+        reason: String::new(),
+        schedule: String::new(),
+        require_env: Vec::new(),
+    });
+    let mut reg = CommandRegistry::from_config(&config);
+
+    reg.set_cwd(Some("/etc".into()));
+    assert_eq!(reg.evaluate("tee hosts").decision, Decision::Deny);
+    assert_eq!(reg.evaluate("sudo tee hosts").decision, Decision::Deny);
+    assert_eq!(reg.evaluate("echo x > hosts").decision, Decision::Deny);
+    assert_eq!(
+        reg.evaluate("cd /tmp && cp a ../etc/hosts").decision,
+        Decision::Deny
+    );
+
+    reg.set_cwd(Some("/tmp".into()));
+    assert_eq!(reg.evaluate("tee hosts").decision, Decision::Allow);
+    assert_eq!(reg.evaluate("echo x > hosts").decision, Decision::Ask);
+}
+
+#[test]
+fn trace_records_each_lookup() {
+    let reg = CommandRegistry::from_config(&crate::config::Config::default_config());
+    let command = "sudo mkfs.ext4 /dev/sda && frobnicate";
+    let (evaluation, trace) = reg.evaluate_traced(command);
+    let untraced = reg.evaluate(command);
+    assert_eq!(evaluation.result.decision, untraced.decision);
+    assert_eq!(evaluation.result.reason, untraced.reason);
+    assert_eq!(
+        trace,
+        [
+            "segment: sudo mkfs.ext4 /dev/sda",
+            "  wrapper sudo (floor ASK)",
+            "    inner: mkfs.ext4 /dev/sda",
+            "    spec mkfs.ext4: none",
+            "    dotted fallback spec mkfs -> DENY: blocked command: mkfs.ext4",
+            "    => DENY: blocked command: mkfs.ext4",
+            "  => DENY: sudo wraps: blocked command: mkfs.ext4",
+            "segment: frobnicate",
+            "  spec frobnicate: none",
+            "  => ASK: unrecognized command: frobnicate",
+        ]
+    );
+    // Untraced evaluation records nothing.
+    let mut trace = Trace::off();
+    reg.evaluate_detailed_with(command, &mut trace);
+    assert!(trace.into_lines().is_empty());
+}
+
+#[test]
+fn redirect_into_protected_path_denies() {
+    let registry = CommandRegistry::from_config(&crate::config::Config::default_config());
+    let result = registry.evaluate_single("echo x >> /etc/hosts");
+    assert_eq!(result.decision, Decision::Deny);
+    assert_eq!(
+        result.reason,
+        "output redirection to /etc/hosts (protected path /etc)"
+    );
+    // Elsewhere, redirection keeps escalating only to ask.
+    let result = registry.evaluate_single("echo x > /srv/notes.txt");
+    assert_eq!(result.decision, Decision::Ask);
+}
+
+#[test]
+fn unquoted_wildcards_in_destructive_commands_escalate() {
+    let mut config = crate::config::Config::default_config();
+    config.commands.allow.push("rmdir".into());
+    config.commands.ask.retain(|c| c != "rmdir");
+    let mut registry = CommandRegistry::from_config(&config);
+    registry.set_cwd(Some("/work/project".into()));
+    let result = registry.evaluate_single("rmdir */");
+    assert_eq!(result.decision, Decision::Ask);
+    assert_eq!(
+        result.reason,
+        "rmdir with unquoted wildcard */: every directory in /work/project"
+    );
+    // Quoted globs aren't expanded.
+    assert_eq!(
+        registry.evaluate_single("rmdir 'cache*'").decision,
+        Decision::Allow
+    );
+    // An ask keeps its reason, with the scope added.
+    assert_eq!(
+        registry.evaluate_single("chmod -R 777 logs/*").reason,
+        "chmod requires confirmation (wildcard logs/*: everything in /work/project/logs)"
+    );
+    assert_eq!(registry.evaluate_single("ls *").decision, Decision::Allow);
+}
+
+#[test]
+fn app_launch_floor_is_configurable() {
+    let mut config = crate::config::Config::default_config();
+    config.wrappers.app_launch = Decision::Allow;
+    let registry = CommandRegistry::from_config(&config);
+    let result = registry.evaluate_single("flatpak run org.gimp.GIMP photo.png");
+    assert_eq!(result.decision, Decision::Allow);
+    assert_eq!(result.reason, "flatpak run org.gimp.GIMP (no command)");
+    let result = registry.evaluate_single("toolbox run -c dev ls -la");
+    assert_eq!(result.decision, Decision::Allow);
+    assert_eq!(result.reason, "toolbox run dev runs: allowed: ls");
+    // The command run inside still escalates.
+    let result = registry.evaluate_single("distrobox enter dev -- shred /dev/sda");
+    assert_eq!(result.decision, Decision::Deny);
+    assert_eq!(
+        registry
+            .evaluate_single("flatpak run org.app > out.txt")
+            .decision,
+        Decision::Ask
+    );
+}
+
+#[test]
+fn docker_run_decisions_are_configurable() {
+    let mut config = crate::config::Config::default_config();
+    config.docker.run = Decision::Allow;
+    config.docker.escalation = Decision::Ask;
+    let registry = CommandRegistry::from_config(&config);
+    let result = registry.evaluate_single("docker run --rm alpine");
+    assert_eq!(result.decision, Decision::Allow);
+    assert_eq!(result.reason, "docker run alpine (image's default command)");
+    let result = registry.evaluate_single("podman run --rm -it alpine ls -la");
+    assert_eq!(result.decision, Decision::Allow);
+    assert_eq!(result.reason, "podman run alpine runs: allowed: ls");
+    let result = registry.evaluate_single("docker run --pid=host --privileged alpine");
+    assert_eq!(result.decision, Decision::Ask);
+    assert_eq!(
+        result.reason,
+        "docker run alpine with --privileged, --pid=host (image's default command)"
+    );
+    // The command run inside still escalates.
+    let result = registry.evaluate_single("docker run alpine shred /dev/sda");
+    assert_eq!(result.decision, Decision::Deny);
+    // A denied docker stays denied.
+    config.commands.deny.push("docker".into());
+    let registry = CommandRegistry::from_config(&config);
+    let result = registry.evaluate_single("docker run --rm alpine");
+    assert_eq!(result.decision, Decision::Deny);
+}
+
+#[test]
+fn ansible_payload_decisions_are_configurable() {
+    let mut config = crate::config::Config::default_config();
+    config.ansible.run = Decision::Allow;
+    config.ansible.privileged = Decision::Deny;
+    let registry = CommandRegistry::from_config(&config);
+    let result = registry.evaluate_single("ansible web -m shell -a 'uptime'");
+    assert_eq!(result.decision, Decision::Allow);
+    assert_eq!(result.reason, "ansible web -m shell runs: allowed: uptime");
+    let result = registry.evaluate_single("ansible web -a 'df -h' -b");
+    assert_eq!(result.decision, Decision::Deny);
+    assert_eq!(
+        result.reason,
+        "ansible web -m command with --become runs: allowed: df"
+    );
+    // The command line still escalates.
+    let result = registry.evaluate_single("ansible web -m shell -a 'shred /dev/sda'");
+    assert_eq!(result.decision, Decision::Deny);
+    // Check mode skips the command modules.
+    let result = registry.evaluate_single("ansible web -m shell -a 'shred /dev/sda' --check");
+    assert_eq!(result.decision, Decision::Allow);
+    // A denied ansible stays denied.
+    config.commands.deny.push("ansible".into());
+    let registry = CommandRegistry::from_config(&config);
+    let result = registry.evaluate_single("ansible web -m shell -a 'uptime'");
+    assert_eq!(result.decision, Decision::Deny);
+}
+
+#[test]
+fn docker_exec_decisions_are_configurable() {
+    let mut config = crate::config::Config::default_config();
+    config.docker.exec = Decision::Allow;
+    config.docker.interactive_shell = Decision::Ask;
+    let registry = CommandRegistry::from_config(&config);
+    let result = registry.evaluate_single("docker exec web ls -la");
+    assert_eq!(result.decision, Decision::Allow);
+    assert_eq!(result.reason, "docker exec web runs: allowed: ls");
+    let result = registry.evaluate_single("docker exec web sh -c 'cat /etc/hosts'");
+    assert_eq!(result.decision, Decision::Allow);
+    let result = registry.evaluate_single("docker exec -it web bash");
+    assert_eq!(result.decision, Decision::Ask);
+    assert_eq!(
+        result.reason,
+        "docker exec web opens an interactive shell: bash"
+    );
+    // The command string still escalates.
+    let result = registry.evaluate_single("docker exec web bash -c 'shred /dev/sda'");
+    assert_eq!(result.decision, Decision::Deny);
+    assert_eq!(
+        registry
+            .evaluate_single("docker exec web ls > out.txt")
+            .decision,
+        Decision::Ask
+    );
+}
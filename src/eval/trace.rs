@@ -0,0 +1,44 @@
+//! Evaluation trace for `--trace`: which lookups each segment went through
+//! and what they decided, the evaluation-side counterpart of `--dump-ast`.
+
+/// Steps recorded during one evaluation, or nothing if tracing is off.
+#[derive(Debug, Default)]
+pub(crate) struct Trace {
+    lines: Option<Vec<String>>,
+    depth: usize,
+}
+
+impl Trace {
+    /// A trace that records nothing. Steps aren't formatted.
+    pub(crate) fn off() -> Self {
+        Self::default()
+    }
+
+    /// A trace that records every step.
+    pub(crate) fn on() -> Self {
+        Self {
+            lines: Some(Vec::new()),
+            depth: 0,
+        }
+    }
+
+    /// Record a step, indented under the enclosing ones.
+    pub(crate) fn step(&mut self, line: impl FnOnce() -> String) {
+        if let Some(lines) = &mut self.lines {
+            lines.push(format!("{}{}", "  ".repeat(self.depth), line()));
+        }
+    }
+
+    /// Run `f` with its steps indented one level further.
+    pub(crate) fn nested<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// The recorded steps, in order.
+    pub(crate) fn into_lines(self) -> Vec<String> {
+        self.lines.unwrap_or_default()
+    }
+}
@@ -0,0 +1,92 @@
+//! Per-directory trust tiers from `[trust]`.
+//!
+//! Each entry maps a directory glob to a tier name: `"~/work/**" = "high"`.
+//! A directory takes the tier of the longest pattern matching it or one of
+//! its ancestors, or `""` if none does. Tiers are plain names; rules give
+//! them meaning by testing `trust` in their conditions.
+
+use crate::eval::paths::{glob_match, resolve};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// One `[trust]` entry.
+#[derive(Debug, Clone)]
+struct TrustEntry {
+    /// The pattern with `~` and `$VAR` expanded.
+    pattern: String,
+    tier: String,
+}
+
+/// Maps directories to trust tiers.
+#[derive(Debug, Clone, Default)]
+pub struct TrustPolicy {
+    /// Longest pattern first.
+    entries: Vec<TrustEntry>,
+}
+
+impl TrustPolicy {
+    /// Compile the tiers from configuration.
+    pub fn from_config(config: &HashMap<String, String>) -> Self {
+        let mut entries: Vec<TrustEntry> = config
+            .iter()
+            .filter(|(_, tier)| !tier.is_empty())
+            .map(|(pattern, tier)| TrustEntry {
+                pattern: resolve(pattern).to_string_lossy().into_owned(),
+                tier: tier.clone(),
+            })
+            .collect();
+        // Ties broken by pattern so the order doesn't depend on the map.
+        entries.sort_by(|a, b| {
+            b.pattern
+                .len()
+                .cmp(&a.pattern.len())
+                .then_with(|| a.pattern.cmp(&b.pattern))
+        });
+        Self { entries }
+    }
+
+    /// The tier of `dir`, `""` if no pattern matches.
+    pub fn tier(&self, dir: &Path) -> &str {
+        self.entries
+            .iter()
+            .find(|e| {
+                dir.ancestors()
+                    .any(|a| glob_match(&e.pattern, &a.to_string_lossy()))
+            })
+            .map_or("", |e| e.tier.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(entries: &[(&str, &str)]) -> TrustPolicy {
+        TrustPolicy::from_config(
+            &entries
+                .iter()
+                .map(|(p, t)| (p.to_string(), t.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn longest_matching_pattern_wins() {
+        let policy = policy(&[
+            ("/work/**", "high"),
+            ("/work/vendor/**", "low"),
+            ("/work/scratch", "medium"),
+        ]);
+        assert_eq!(policy.tier(Path::new("/work/app/src")), "high");
+        assert_eq!(policy.tier(Path::new("/work/vendor/lib")), "low");
+        // A plain directory pattern covers what's beneath it.
+        assert_eq!(policy.tier(Path::new("/work/scratch/tmp")), "medium");
+        assert_eq!(policy.tier(Path::new("/home/me")), "");
+    }
+
+    #[test]
+    fn empty_tiers_are_ignored() {
+        let policy = policy(&[("/work/**", "")]);
+        assert_eq!(policy.tier(Path::new("/work/app")), "");
+    }
+}
@@ -0,0 +1,288 @@
+//! Option schemas for wrappers agent-shell-parser doesn't know, and the
+//! command string `su -c` and `runuser -c` hand to a shell.
+//!
+//! A wrapper without a schema gets a minimal one that skips flags but not
+//! their values, so `runuser -u app -- ls` would read `app` as the
+//! command, and `chroot /srv/root ls` would read the new root. `su -c
+//! '...'` packs the command into a flag value, which the extractor never
+//! looks inside.
+
+use std::borrow::Cow;
+
+use agent_shell_parser::parse::WrapperSpec;
+
+use crate::commands::args::dequote;
+
+/// A wrapper's options, as given to the parser.
+struct Schema {
+    name: &'static str,
+    short_value_flags: &'static [&'static str],
+    long_value_flags: &'static [&'static str],
+    has_terminator: bool,
+    skip_positionals: usize,
+}
+
+/// `su` and `runuser` share util-linux's option parser.
+const SU_SHORT: &[&str] = &["-c", "-g", "-G", "-s", "-u", "-w"];
+const SU_LONG: &[&str] = &[
+    "--command",
+    "--session-command",
+    "--group",
+    "--supp-group",
+    "--shell",
+    "--user",
+    "--whitelist-environment",
+];
+
+const SCHEMAS: &[Schema] = &[
+    // `su [options] [-] [user [args]]`: the args go to the user's shell.
+    Schema {
+        name: "su",
+        short_value_flags: SU_SHORT,
+        long_value_flags: SU_LONG,
+        has_terminator: true,
+        skip_positionals: 1,
+    },
+    // `runuser -u user [--] command`. Without `-u` it works like su, and
+    // the user name is read as the command.
+    Schema {
+        name: "runuser",
+        short_value_flags: SU_SHORT,
+        long_value_flags: SU_LONG,
+        has_terminator: true,
+        skip_positionals: 0,
+    },
+    // `chroot [--userspec=U:G] [--groups=G] NEWROOT [command]`.
+    Schema {
+        name: "chroot",
+        short_value_flags: &[],
+        long_value_flags: &["--userspec", "--groups"],
+        has_terminator: true,
+        skip_positionals: 1,
+    },
+    // Namespace flags (`-m`, `--net=FILE`) take their value inline only.
+    Schema {
+        name: "nsenter",
+        short_value_flags: &["-t", "-S", "-G", "-W", "-N"],
+        long_value_flags: &["--target", "--setuid", "--setgid", "--wdns", "--net-socket"],
+        has_terminator: true,
+        skip_positionals: 0,
+    },
+    Schema {
+        name: "unshare",
+        short_value_flags: &["-S", "-G", "-R", "-w"],
+        long_value_flags: &[
+            "--setuid",
+            "--setgid",
+            "--root",
+            "--wd",
+            "--propagation",
+            "--setgroups",
+            "--map-user",
+            "--map-group",
+            "--map-users",
+            "--map-groups",
+            "--monotonic",
+            "--boottime",
+        ],
+        has_terminator: true,
+        skip_positionals: 0,
+    },
+    // firejail options are all `--name` or `--name=value`.
+    Schema {
+        name: "firejail",
+        short_value_flags: &[],
+        long_value_flags: &[],
+        has_terminator: true,
+        skip_positionals: 0,
+    },
+];
+
+/// Wrappers that can run a command string of their own.
+const COMMAND_STRING: &[&str] = &["su", "runuser"];
+
+/// Whether `name` can run a command string (`su -c '...'`). The parser
+/// mustn't unwrap these as part of another wrapper, or the string is lost.
+pub(crate) fn runs_command_string(name: &str) -> bool {
+    COMMAND_STRING.contains(&name)
+}
+
+/// Parser schemas for the wrappers that can run a command string.
+pub(crate) fn command_string_specs() -> Vec<WrapperSpec> {
+    COMMAND_STRING
+        .iter()
+        .filter_map(|name| spec(name))
+        .collect()
+}
+
+/// The parser schema for a wrapper cc-toolgate knows the options of.
+pub(crate) fn spec(name: &str) -> Option<WrapperSpec> {
+    let schema = SCHEMAS.iter().find(|s| s.name == name)?;
+    let strings = |flags: &[&str]| flags.iter().map(|f| f.to_string()).collect();
+    Some(WrapperSpec {
+        name: name.to_string(),
+        short_value_flags: strings(schema.short_value_flags),
+        long_value_flags: strings(schema.long_value_flags),
+        unanalyzable_flags: vec![],
+        skip_env_assignments: false,
+        has_terminator: schema.has_terminator,
+        skip_positionals: schema.skip_positionals,
+    })
+}
+
+/// The command string given to `su -c` or `runuser -c` (or
+/// `--command`/`--session-command`), if `words` are such an invocation.
+/// The last one given wins, as it does for su. Without one, arguments
+/// after the user go to the user's shell, so `su root -- -c '...'` runs
+/// a command string too. A string attached to its option
+/// (`--command='...'`, `-c'...'`) is unquoted as the shell would.
+pub(crate) fn su_command<'a>(words: &[&'a str]) -> Option<Cow<'a, str>> {
+    let (first, args) = words.split_first()?;
+    if !matches!(first.rsplit('/').next(), Some("su" | "runuser")) {
+        return None;
+    }
+    let mut command = None;
+    let mut user_given = false;
+    let mut operands = Vec::new();
+    let mut args = args.iter().copied();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            operands.extend(args.by_ref());
+            break;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(dequote(value))),
+                None if SU_LONG.contains(&arg) => (long, args.next().map(Cow::Borrowed)),
+                None => (long, None),
+            };
+            match name {
+                "command" | "session-command" => command = value,
+                "user" => user_given = true,
+                _ => {}
+            }
+            continue;
+        }
+        let Some(cluster) = arg.strip_prefix('-').filter(|c| !c.is_empty()) else {
+            // Options can follow the user name, but with `runuser -u` the
+            // first operand starts the command and the rest are its own.
+            if user_given {
+                return command;
+            }
+            // `-` alone is `--login`.
+            if arg != "-" {
+                operands.push(arg);
+            }
+            continue;
+        };
+        for (at, c) in cluster.char_indices() {
+            if !"cgGsuw".contains(c) {
+                continue;
+            }
+            let value = match &cluster[at + c.len_utf8()..] {
+                "" => args.next().map(Cow::Borrowed),
+                inline => Some(dequote(inline)),
+            };
+            match c {
+                'c' => command = value,
+                'u' => user_given = true,
+                _ => {}
+            }
+            break;
+        }
+    }
+    if command.is_some() || user_given {
+        return command;
+    }
+    let shell_args = operands.get(1..).unwrap_or_default();
+    let at = shell_args
+        .iter()
+        .position(|a| a.starts_with('-') && !a.starts_with("--") && a.contains('c'))?;
+    shell_args.get(at + 1).map(|a| Cow::Borrowed(*a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn su_command_strings() {
+        assert_eq!(
+            su_command(&["su", "-c", "rm -rf /data", "someuser"]).as_deref(),
+            Some("rm -rf /data")
+        );
+        assert_eq!(
+            su_command(&["su", "someuser", "-c", "ls"]).as_deref(),
+            Some("ls")
+        );
+        assert_eq!(
+            su_command(&["su", "-", "root", "-c", "ls"]).as_deref(),
+            Some("ls")
+        );
+        assert_eq!(su_command(&["su", "-lc", "ls"]).as_deref(), Some("ls"));
+        assert_eq!(su_command(&["su", "-cls"]).as_deref(), Some("ls"));
+        assert_eq!(
+            su_command(&["su", "--command=ls", "root"]).as_deref(),
+            Some("ls")
+        );
+        assert_eq!(
+            su_command(&["su", "--command='rm -rf /'"]).as_deref(),
+            Some("rm -rf /")
+        );
+        assert_eq!(su_command(&["su", "-c'ls -l'"]).as_deref(), Some("ls -l"));
+        assert_eq!(
+            su_command(&["su", "--command", "ls"]).as_deref(),
+            Some("ls")
+        );
+        assert_eq!(
+            su_command(&["su", "--session-command", "ls"]).as_deref(),
+            Some("ls")
+        );
+        assert_eq!(
+            su_command(&["/bin/su", "-c", "id", "-c", "ls"]).as_deref(),
+            Some("ls")
+        );
+        assert_eq!(
+            su_command(&["runuser", "-l", "app", "-c", "ls"]).as_deref(),
+            Some("ls")
+        );
+        assert_eq!(
+            su_command(&["su", "root", "--", "-c", "ls"]).as_deref(),
+            Some("ls")
+        );
+        assert_eq!(
+            su_command(&["su", "--", "root", "-lc", "ls"]).as_deref(),
+            Some("ls")
+        );
+    }
+
+    #[test]
+    fn no_command_string() {
+        assert_eq!(su_command(&["su"]).as_deref(), None);
+        assert_eq!(su_command(&["su", "-s", "-c", "root"]).as_deref(), None);
+        assert_eq!(su_command(&["su", "root", "script.sh"]).as_deref(), None);
+        assert_eq!(su_command(&["su", "--", "-c", "ls"]).as_deref(), None);
+        assert_eq!(su_command(&["sudo", "-c", "ls"]).as_deref(), None);
+        // After `runuser -u user`, `-c` belongs to the command.
+        assert_eq!(
+            su_command(&["runuser", "-u", "app", "grep", "-c", "x"]).as_deref(),
+            None
+        );
+    }
+
+    #[test]
+    fn schemas() {
+        let su = spec("su").unwrap();
+        assert!(su.short_value_flags.contains(&"-c".to_string()));
+        assert_eq!(su.skip_positionals, 1);
+        assert!(spec("runuser").is_some());
+        assert_eq!(spec("chroot").unwrap().skip_positionals, 1);
+        assert!(
+            spec("nsenter")
+                .unwrap()
+                .short_value_flags
+                .contains(&"-t".to_string())
+        );
+        assert!(spec("sudo").is_none());
+    }
+}
@@ -0,0 +1,121 @@
+//! Rewrites for zsh syntax the bash grammar can't read, applied before
+//! parsing when `settings.shell = "zsh"`.
+//!
+//! - `=(cmd)`, zsh's temp-file process substitution, becomes `<(cmd)`: both
+//!   run `cmd` and stand for a path, so it's evaluated the same way.
+//! - Glob qualifiers (`*(.)`, `**/*.rs(.om[1,3])`) are dropped. Qualifiers
+//!   that run code (`e:...:`, `+func`) are kept, so the command still fails
+//!   to parse and asks.
+//! - Parameter expansion flags (`${(j:,:)arr}`) are dropped.
+//!
+//! Quoted text is left alone. `**/` recursive globs and `setopt` already
+//! parse as ordinary words and commands.
+
+use std::borrow::Cow;
+
+/// `command` with the zsh-only syntax above rewritten for the bash parser.
+pub(crate) fn normalize(command: &str) -> Cow<'_, str> {
+    if !command.contains('(') {
+        return Cow::Borrowed(command);
+    }
+    let mut out = String::with_capacity(command.len());
+    // Where the current unquoted word starts in `out`.
+    let mut word_start = 0;
+    let (mut single, mut double) = (false, false);
+    let mut rest = command;
+    while let Some(c) = rest.chars().next() {
+        let quoted = single || double;
+        let skip = match c {
+            '\\' if !single => {
+                let len = rest[1..].chars().next().map_or(0, char::len_utf8);
+                out.push_str(&rest[..1 + len]);
+                rest = &rest[1 + len..];
+                continue;
+            }
+            '\'' if !double => {
+                single = !single;
+                None
+            }
+            '"' if !single => {
+                double = !double;
+                None
+            }
+            c if !quoted && (c.is_whitespace() || ";|&".contains(c)) => {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+                word_start = out.len();
+                continue;
+            }
+            '=' if !quoted && rest.starts_with("=(") && out.len() == word_start => {
+                out.push('<');
+                Some(1)
+            }
+            '$' if !single && rest.starts_with("${(") => expansion_flags(&rest[2..]).map(|len| {
+                out.push_str("${");
+                2 + len
+            }),
+            '(' if !quoted && out[word_start..].contains(['*', '?', ']']) => glob_qualifier(rest),
+            _ => None,
+        };
+        match skip {
+            Some(len) => rest = &rest[len..],
+            None => {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if out == command {
+        Cow::Borrowed(command)
+    } else {
+        Cow::Owned(out)
+    }
+}
+
+/// The length of a `(flags)` group at the start of `text`, inside `${`.
+fn expansion_flags(text: &str) -> Option<usize> {
+    let end = text.find(')')?;
+    let flags = &text[1..end];
+    (!flags.is_empty() && !flags.contains(|c: char| c.is_whitespace() || "}$`(".contains(c)))
+        .then_some(end + 1)
+}
+
+/// The length of a glob qualifier at the start of `text`: a parenthesized
+/// group ending the word, made only of qualifiers that don't run code.
+fn glob_qualifier(text: &str) -> Option<usize> {
+    let end = text.find(')')?;
+    let qualifiers = &text[1..end];
+    let ends_word = text[end + 1..]
+        .chars()
+        .next()
+        .is_none_or(|c| c.is_whitespace() || ";|&)".contains(c));
+    let harmless = qualifiers
+        .chars()
+        .all(|c| (c.is_ascii_alphanumeric() && c != 'e') || "./@=*%^-,[]:~".contains(c));
+    (!qualifiers.is_empty() && ends_word && harmless).then_some(end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_zsh_syntax() {
+        assert_eq!(normalize("diff =(ls a) =(ls b)"), "diff <(ls a) <(ls b)");
+        assert_eq!(normalize("ls **/*.rs(.om[1,3]) *(/)"), "ls **/*.rs *");
+        assert_eq!(normalize("echo ${(j:,:)arr} ${arr}"), "echo ${arr} ${arr}");
+    }
+
+    #[test]
+    fn leaves_bash_and_risky_syntax_alone() {
+        for command in [
+            "a=(1 2 3); f() { ls; }; echo $(date) <(ls)",
+            "echo '=(x)' \"*(.)\"",
+            "[[ $x == (a|b) ]]",
+            "ls *(e:'rm -rf ~':)",
+            "ls *(+check)",
+        ] {
+            assert_eq!(normalize(command), command);
+        }
+    }
+}
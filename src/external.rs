@@ -0,0 +1,259 @@
+//! External evaluator: a user script consulted like a command spec.
+//!
+//! `[hooks] external_evaluator` names a program that is run once per
+//! segment it's asked about, with the segment's
+//! [`SerializedContext`](crate::eval::context::SerializedContext) as JSON on
+//! stdin. It prints a verdict on stdout:
+//!
+//! ```json
+//! {"decision": "deny", "reason": "prod deploys go through the pipeline"}
+//! ```
+//!
+//! The script decides the commands listed in `[hooks] commands`, and with
+//! `fallback = true` also the commands no rule recognizes. A non-zero exit,
+//! output that isn't a verdict, or no answer within `timeout_ms` is ASK.
+
+use crate::commands::CommandSpec;
+use crate::config::HooksConfig;
+use crate::eval::context::SerializedContext;
+use crate::eval::{CommandContext, CommandRegistry, Decision, RuleMatch};
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How often to check whether the script has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// What the script prints.
+#[derive(Debug, Deserialize)]
+struct Verdict {
+    decision: Decision,
+    #[serde(default)]
+    reason: String,
+}
+
+/// The configured script, as a [`CommandSpec`].
+#[derive(Debug, Clone)]
+pub struct ExternalEvaluator {
+    program: PathBuf,
+    timeout: Duration,
+}
+
+impl ExternalEvaluator {
+    /// Build from `[hooks]`; `None` if no script is configured.
+    pub fn from_config(config: &HooksConfig) -> Option<Self> {
+        if config.external_evaluator.is_empty() {
+            return None;
+        }
+        Some(Self {
+            program: PathBuf::from(shellexpand::tilde(&config.external_evaluator).as_ref()),
+            timeout: Duration::from_millis(config.timeout_ms),
+        })
+    }
+
+    fn run(&self, ctx: &CommandContext) -> Result<RuleMatch, String> {
+        let input = serde_json::to_vec(&SerializedContext::from(ctx)).map_err(|e| e.to_string())?;
+        let mut child = Command::new(&self.program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("{}: {e}", self.program.display()))?;
+
+        // Feed and drain the pipes off-thread so a script that doesn't read
+        // its input, or writes a lot, can't stall the wait below.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        thread::spawn(move || stdin.write_all(&input));
+        let stdout = read_in_background(child.stdout.take().expect("stdout is piped"));
+        let stderr = read_in_background(child.stderr.take().expect("stderr is piped"));
+
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if Instant::now() < deadline => thread::sleep(POLL_INTERVAL),
+                Ok(None) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!("no verdict within {}ms", self.timeout.as_millis()));
+                }
+                Err(e) => return Err(e.to_string()),
+            }
+        };
+        if !status.success() {
+            let stderr = stderr.join().unwrap_or_default();
+            let detail = stderr.lines().next().unwrap_or_default();
+            return Err(format!("{status} {detail}").trim_end().to_string());
+        }
+        let stdout = stdout.join().unwrap_or_default();
+        let verdict: Verdict = serde_json::from_str(stdout.trim())
+            .map_err(|e| format!("unreadable verdict {:?}: {e}", stdout.trim()))?;
+        Ok(RuleMatch {
+            decision: verdict.decision,
+            reason: if verdict.reason.is_empty() {
+                "external evaluator".to_string()
+            } else {
+                format!("external evaluator: {}", verdict.reason)
+            },
+        })
+    }
+}
+
+impl CommandSpec for ExternalEvaluator {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        self.run(ctx).unwrap_or_else(|e| RuleMatch {
+            decision: Decision::Ask,
+            reason: format!("external evaluator failed: {e}"),
+        })
+    }
+}
+
+/// Read a pipe to the end on another thread.
+fn read_in_background(mut pipe: impl Read + Send + 'static) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut out = String::new();
+        let _ = pipe.read_to_string(&mut out);
+        out
+    })
+}
+
+/// Route `[hooks] commands`, and unrecognized commands when `fallback` is
+/// set, to the configured script.
+pub fn register(registry: &mut CommandRegistry, config: &HooksConfig) {
+    let Some(evaluator) = ExternalEvaluator::from_config(config) else {
+        return;
+    };
+    if !config.commands.is_empty() {
+        registry.register(&config.commands, Box::new(evaluator.clone()));
+    }
+    if config.fallback {
+        registry.set_fallback(Box::new(evaluator));
+    }
+}
+
+// The tests drive real `/bin/sh` scripts.
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Write `body` as an executable shell script in a fresh scratch dir.
+    fn script(tag: &str, body: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("cc-toolgate-test-external-{tag}-{nanos}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("policy.sh");
+        std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn hooks(program: &std::path::Path, commands: &[&str], fallback: bool) -> HooksConfig {
+        HooksConfig {
+            external_evaluator: program.display().to_string(),
+            commands: commands.iter().map(|c| c.to_string()).collect(),
+            fallback,
+            timeout_ms: 2000,
+        }
+    }
+
+    fn registry(hooks: &HooksConfig) -> CommandRegistry {
+        let mut reg = CommandRegistry::from_config(&Config::default_config());
+        register(&mut reg, hooks);
+        reg
+    }
+
+    /// Denies anything mentioning "prod", allows everything else.
+    const PROD_POLICY: &str = r#"if grep -q '"prod"'; then
+  echo '{"decision": "deny", "reason": "no prod"}'
+else
+  echo '{"decision": "allow", "reason": "not prod"}'
+fi"#;
+
+    #[test]
+    fn configured_commands_use_the_script() {
+        let reg = registry(&hooks(
+            &script("commands", PROD_POLICY),
+            &["terraform", "git"],
+            false,
+        ));
+        let result = reg.evaluate("terraform apply staging");
+        assert_eq!(result.decision, Decision::Allow);
+        assert_eq!(result.reason, "external evaluator: not prod");
+        // Replaces the built-in git rules.
+        assert_eq!(reg.evaluate("git push --force").decision, Decision::Allow);
+        // Combines with the rest of a compound command.
+        let result = reg.evaluate("ls && terraform apply prod");
+        assert_eq!(result.decision, Decision::Deny);
+        assert!(result.reason.contains("no prod"), "{}", result.reason);
+        // Unconfigured, unrecognized commands still ask.
+        assert_eq!(reg.evaluate("deployctl prod").decision, Decision::Ask);
+    }
+
+    #[test]
+    fn fallback_only_sees_unrecognized_commands() {
+        let reg = registry(&hooks(&script("fallback", PROD_POLICY), &[], true));
+        assert_eq!(reg.evaluate("deployctl staging").decision, Decision::Allow);
+        assert_eq!(reg.evaluate("deployctl prod").decision, Decision::Deny);
+        // Known commands keep their built-in rules.
+        assert_eq!(reg.evaluate("rm prod").decision, Decision::Ask);
+        assert_eq!(reg.evaluate("shred staging").decision, Decision::Deny);
+    }
+
+    #[test]
+    fn script_failures_ask() {
+        for (tag, body, expected) in [
+            (
+                "exit",
+                "echo 'policy db unavailable' >&2; exit 3",
+                "policy db unavailable",
+            ),
+            ("garbage", "echo maybe", "unreadable verdict"),
+            (
+                "bad-decision",
+                r#"echo '{"decision": "yes"}'"#,
+                "unreadable verdict",
+            ),
+            ("slow", "sleep 5", "no verdict within"),
+        ] {
+            let mut hooks = hooks(&script(tag, body), &["deployctl"], false);
+            hooks.timeout_ms = 300;
+            let result = registry(&hooks).evaluate("deployctl up");
+            assert_eq!(result.decision, Decision::Ask, "{tag}");
+            assert!(result.reason.contains(expected), "{tag}: {}", result.reason);
+        }
+    }
+
+    #[test]
+    fn missing_script_asks() {
+        let reg = registry(&hooks(
+            std::path::Path::new("/nonexistent/policy.sh"),
+            &["ls"],
+            false,
+        ));
+        let result = reg.evaluate("ls");
+        assert_eq!(result.decision, Decision::Ask);
+        assert!(
+            result.reason.contains("external evaluator failed"),
+            "{}",
+            result.reason
+        );
+    }
+
+    #[test]
+    fn disabled_without_a_script() {
+        let config = Config::default_config();
+        assert!(ExternalEvaluator::from_config(&config.hooks).is_none());
+        let mut reg = CommandRegistry::from_config(&config);
+        register(&mut reg, &config.hooks);
+        assert_eq!(reg.evaluate("deployctl up").decision, Decision::Ask);
+        assert_eq!(reg.evaluate("ls").decision, Decision::Allow);
+    }
+}
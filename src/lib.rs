@@ -11,17 +11,59 @@
 //! - **`agent-shell-parser`** — Shell parsing: tree-sitter-bash AST walker, shlex tokenizer, type definitions (external crate).
 //! - **[`eval`]** — Evaluation engine: command registry, decision types, per-segment context.
 //! - **[`commands`]** — Command specs: per-tool evaluation logic (git, cargo, kubectl, gh, etc.).
+//! - **[`claude_settings`]** — Claude Code `Bash(...)` permission patterns translated to rules.
 //! - **[`config`]** — Configuration loading: embedded defaults + user overlay merge.
+//! - **[`external`]** — External evaluator script for custom rules (`[hooks]`).
+//! - **[`parse`]** — Typed, serializable parse results for editors and other tools.
+//! - **[`batch`]** — Batch evaluation over a file of commands or hook inputs.
+//! - **[`bench`](mod@bench)** — Parse and evaluation latency for `cc-toolgate bench`.
+//! - **[`approvals`]** — Remembered approvals in `~/.local/share/cc-toolgate/approvals.json`.
+//! - **`plugin`** — Out-of-tree command specs from dynamic libraries (`plugins` feature).
+//! - **[`reload`]** — Registry that is rebuilt when config files change.
+//! - **`remote`** — Signed team policy fetched over HTTPS (`remote` feature).
+//! - **[`tokens`]** — Signed approval tokens (`CC_TOOLGATE_APPROVAL`) for CI.
+//! - **[`stats`]** — Per-command and per-rule decision counters for `cc-toolgate top`.
 //! - **[`logging`]** — Decision logging to `~/.local/share/cc-toolgate/decisions.log`.
 
+/// Remembered approvals that turn ASK into ALLOW for a time window.
+#[cfg(feature = "native")]
+pub mod approvals;
+/// Batch evaluation of command files (`cc-toolgate eval`).
+pub mod batch;
+/// Latency benchmarks (`cc-toolgate bench`).
+#[cfg(feature = "native")]
+pub mod bench;
+/// Claude Code `permissions` patterns imported as rules.
+pub mod claude_settings;
 /// Command spec trait and per-tool implementations.
 pub mod commands;
 /// Configuration types, loading, and overlay merge logic.
 pub mod config;
 /// Evaluation engine: registry, decision aggregation, command context.
 pub mod eval;
+/// User script consulted for commands (`[hooks] external_evaluator`).
+#[cfg(feature = "native")]
+pub mod external;
 /// File-based decision logging.
+#[cfg(feature = "native")]
 pub mod logging;
+/// Typed parse API: segments, operators, substitutions, redirections.
+pub mod parse;
+/// Command specs loaded from plugin libraries.
+#[cfg(feature = "plugins")]
+pub mod plugin;
+/// Registry rebuilt when config files change, for long-running embedders.
+#[cfg(feature = "native")]
+pub mod reload;
+/// Signed team policy fetched over HTTPS.
+#[cfg(feature = "remote")]
+pub mod remote;
+/// Decision counters for `cc-toolgate top`.
+#[cfg(feature = "native")]
+pub mod stats;
+/// HMAC-signed approval tokens for non-interactive runs.
+#[cfg(feature = "native")]
+pub mod tokens;
 
 use eval::RuleMatch;
 
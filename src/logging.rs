@@ -1,12 +1,21 @@
 //! Decision logging to `~/.local/share/cc-toolgate/decisions.log`.
 //!
 //! Initializes a file logger on first call and writes one line per evaluated
-//! command with the decision, truncated command text, and reason.
+//! command with the decision, truncated command text, and reason, followed
+//! by `key=value` fields for the hook metadata in
+//! [`LogContext`](crate::logging::LogContext). Tabs, newlines and
+//! backslashes are escaped, so [`parse_line`](crate::logging::parse_line)
+//! recovers them exactly.
 
-use crate::eval::RuleMatch;
+use crate::eval::{Decision, RuleMatch, Timings};
 use log::info;
-use simplelog::{Config, LevelFilter, WriteLogger};
+use simplelog::{ConfigBuilder, LevelFilter, WriteLogger};
+use std::path::{Path, PathBuf};
 use std::sync::Once;
+use std::time::Duration;
+
+/// Logged commands are truncated to this many characters.
+pub const COMMAND_LIMIT: usize = 200;
 
 /// Ensures the logger is initialized exactly once per process.
 static INIT: Once = Once::new();
@@ -14,13 +23,12 @@ static INIT: Once = Once::new();
 /// Initialize the file logger. Best-effort: failures are silently ignored.
 pub fn init() {
     INIT.call_once(|| {
-        let Some(home) = std::env::var_os("HOME") else {
+        let Some(log_path) = log_path() else {
             return;
         };
-        let log_dir = std::path::Path::new(&home).join(".local/share/cc-toolgate");
-        let _ = std::fs::create_dir_all(&log_dir);
-
-        let log_path = log_dir.join("decisions.log");
+        if let Some(log_dir) = log_path.parent() {
+            let _ = std::fs::create_dir_all(log_dir);
+        }
         let Ok(file) = std::fs::OpenOptions::new()
             .create(true)
             .append(true)
@@ -29,21 +37,272 @@ pub fn init() {
             return;
         };
 
-        let _ = WriteLogger::init(LevelFilter::Info, Config::default(), file);
+        // A dated UTC timestamp, so `cc-toolgate log --since` can filter.
+        let config = ConfigBuilder::new().set_time_format_rfc3339().build();
+        let _ = WriteLogger::init(LevelFilter::Info, config, file);
     });
 }
 
+/// The decisions log location, if `HOME` is set.
+pub fn log_path() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME")?;
+    Some(Path::new(&home).join(".local/share/cc-toolgate/decisions.log"))
+}
+
+/// What the hook knew about an evaluation besides the command itself.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogContext {
+    /// The Claude Code session.
+    pub session_id: Option<String>,
+    /// The session's working directory.
+    pub cwd: Option<PathBuf>,
+    /// The tool call being decided.
+    pub tool_use_id: Option<String>,
+    /// Time from the start of evaluation to the final decision.
+    pub latency: Option<Duration>,
+    /// Time spent parsing and evaluating, within `latency`.
+    pub timings: Option<Timings>,
+    /// Each segment's decision, in evaluation order.
+    pub segments: Vec<Decision>,
+}
+
 /// Log a decision record.
-/// Format: `{decision}\t{command_truncated}\t{reason_oneline}`
+/// Format: `{decision}\t{command_truncated}\t{reason}` then a
+/// `\t{key}={value}` field for each part of `context` that is set.
 /// Timestamp is provided by simplelog.
-pub fn log_decision(command: &str, result: &RuleMatch) {
-    let reason_oneline = result.reason.replace('\n', "; ");
-    let cmd_truncated: String = command.chars().take(200).collect();
-
-    info!(
-        "{decision}\t{cmd}\t{reason}",
-        decision = result.decision.as_str(),
-        cmd = cmd_truncated,
-        reason = reason_oneline,
+pub fn log_decision(command: &str, result: &RuleMatch, context: &LogContext) {
+    info!("{}", format_record(command, result, context));
+}
+
+/// Log a decision evaluated in audit mode (not enforced).
+/// Format: as [`log_decision`], with the reason prefixed by `(audit) `.
+pub fn log_audit_decision(command: &str, result: &RuleMatch, context: &LogContext) {
+    log_decision(
+        command,
+        &RuleMatch {
+            decision: result.decision,
+            reason: format!("(audit) {}", result.reason),
+        },
+        context,
     );
 }
+
+/// The message part of a log line, after simplelog's timestamp and level.
+fn format_record(command: &str, result: &RuleMatch, context: &LogContext) -> String {
+    let cmd_truncated: String = command.chars().take(COMMAND_LIMIT).collect();
+    let mut line = format!(
+        "{}\t{}\t{}",
+        result.decision.as_str(),
+        escape(&cmd_truncated),
+        escape(&result.reason)
+    );
+    let mut field = |key: &str, value: &str| {
+        line.push_str(&format!("\t{key}={}", escape(value)));
+    };
+    if let Some(id) = &context.session_id {
+        field("session", id);
+    }
+    if let Some(cwd) = &context.cwd {
+        field("cwd", &cwd.to_string_lossy());
+    }
+    if let Some(id) = &context.tool_use_id {
+        field("tool_use", id);
+    }
+    if let Some(latency) = context.latency {
+        field("latency_us", &latency.as_micros().to_string());
+    }
+    if let Some(timings) = context.timings {
+        field("parse_us", &timings.parse.as_micros().to_string());
+        field("eval_us", &timings.eval.as_micros().to_string());
+    }
+    if !context.segments.is_empty() {
+        let decisions: Vec<&str> = context.segments.iter().map(|d| d.as_str()).collect();
+        field("segments", &decisions.join(","));
+    }
+    line
+}
+
+/// One line of the decisions log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    /// When it was logged: RFC 3339 in UTC, or only the time of day in logs
+    /// written before timestamps were dated.
+    pub time: String,
+    /// The decision reached.
+    pub decision: Decision,
+    /// The command, truncated to [`COMMAND_LIMIT`] characters.
+    pub command: String,
+    /// The full reason; audit-mode records start with `(audit)`.
+    pub reason: String,
+    /// Hook metadata; empty for records logged before it was recorded.
+    pub context: LogContext,
+}
+
+impl LogRecord {
+    /// Whether the timestamp includes a date.
+    pub fn is_dated(&self) -> bool {
+        self.time.contains('T')
+    }
+
+    /// Whether the record was logged on or after `since`, a `YYYY-MM-DD`
+    /// date or RFC 3339 time. Undated records can't be placed, so never are.
+    pub fn is_since(&self, since: &str) -> bool {
+        self.is_dated() && self.time.as_str() >= since
+    }
+
+    /// Whether the command may have been cut off at [`COMMAND_LIMIT`].
+    pub fn is_truncated(&self) -> bool {
+        self.command.chars().count() >= COMMAND_LIMIT
+    }
+}
+
+/// Parse one log line; `None` for lines that aren't decision records.
+pub fn parse_line(line: &str) -> Option<LogRecord> {
+    let (time, message) = line.split_once(" [INFO] ")?;
+    let time = time.trim();
+    // Undated records predate escaping: their newlines became `; `, a
+    // backslash is just a backslash, and the reason may hold raw tabs.
+    if !time.contains('T') {
+        let mut fields = message.splitn(3, '\t');
+        return Some(LogRecord {
+            time: time.to_string(),
+            decision: parse_decision(fields.next()?)?,
+            command: fields.next()?.to_string(),
+            reason: fields.next()?.to_string(),
+            context: LogContext::default(),
+        });
+    }
+    let mut fields = message.split('\t');
+    let decision = parse_decision(fields.next()?)?;
+    let (command, reason) = (fields.next()?, fields.next()?);
+    let mut context = LogContext::default();
+    // Unknown keys are skipped, so older binaries can read newer logs.
+    for (key, value) in fields.filter_map(|f| f.split_once('=')) {
+        let value = unescape(value);
+        match key {
+            "session" => context.session_id = Some(value),
+            "cwd" => context.cwd = Some(PathBuf::from(value)),
+            "tool_use" => context.tool_use_id = Some(value),
+            "latency_us" => context.latency = value.parse().ok().map(Duration::from_micros),
+            "parse_us" | "eval_us" => {
+                let Ok(micros) = value.parse() else { continue };
+                let timings = context.timings.get_or_insert_default();
+                let phase = if key == "parse_us" {
+                    &mut timings.parse
+                } else {
+                    &mut timings.eval
+                };
+                *phase = Duration::from_micros(micros);
+            }
+            "segments" => {
+                context.segments = value.split(',').filter_map(parse_decision).collect();
+            }
+            _ => {}
+        }
+    }
+    Some(LogRecord {
+        time: time.to_string(),
+        decision,
+        command: unescape(command),
+        reason: unescape(reason),
+        context,
+    })
+}
+
+fn parse_decision(text: &str) -> Option<Decision> {
+    match text {
+        "allow" => Some(Decision::Allow),
+        "ask" => Some(Decision::Ask),
+        "deny" => Some(Decision::Deny),
+        _ => None,
+    }
+}
+
+/// Escape backslashes, tabs and newlines, which would break the line format.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Reverse [`escape`].
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(next @ ('\\' | 't' | 'n'))) => {
+                chars.next();
+                out.push(match next {
+                    't' => '\t',
+                    'n' => '\n',
+                    _ => '\\',
+                });
+            }
+            (c, _) => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_round_trip() {
+        let reason = "compound command (&&):\n  [rm C:\\tmp] -> ASK:\tconfirm";
+        let result = RuleMatch {
+            decision: Decision::Ask,
+            reason: reason.into(),
+        };
+        let message = format_record("printf 'a\tb\n'", &result, &LogContext::default());
+        assert!(!message.contains('\n'));
+        let record = parse_line(&format!("2026-10-15T08:15:22.5Z [INFO] {message}")).unwrap();
+        assert_eq!(record.time, "2026-10-15T08:15:22.5Z");
+        assert_eq!(record.decision, Decision::Ask);
+        assert_eq!(record.command, "printf 'a\tb\n'");
+        assert_eq!(record.reason, reason);
+        assert_eq!(record.context, LogContext::default());
+    }
+
+    #[test]
+    fn hook_metadata_round_trips() {
+        let context = LogContext {
+            session_id: Some("abc-123".into()),
+            cwd: Some(PathBuf::from("/work/my\tproject")),
+            tool_use_id: Some("toolu_01".into()),
+            latency: Some(Duration::from_micros(1250)),
+            timings: Some(Timings {
+                parse: Duration::from_micros(400),
+                eval: Duration::from_micros(700),
+            }),
+            segments: vec![Decision::Allow, Decision::Ask],
+        };
+        let result = RuleMatch {
+            decision: Decision::Ask,
+            reason: "rm requires confirmation".into(),
+        };
+        let message = format_record("ls && rm x", &result, &context);
+        let line = format!("2026-10-15T08:15:22Z [INFO] {message}\tfuture=field");
+        let record = parse_line(&line).unwrap();
+        assert_eq!(record.context, context);
+        assert_eq!(record.reason, "rm requires confirmation");
+    }
+
+    #[test]
+    fn reads_undated_records_as_written() {
+        let record = parse_line("08:15:22 [INFO] deny\tshred x\tblocked; see C:\\n").unwrap();
+        assert!(!record.is_dated());
+        assert_eq!(record.reason, "blocked; see C:\\n");
+        assert_eq!(parse_line("08:15:22 [WARN] something else"), None);
+        assert_eq!(parse_line("08:15:22 [INFO] maybe\tx\ty"), None);
+    }
+}
@@ -1,10 +1,24 @@
+use cc_toolgate::approvals::{self, ApprovalStore, SessionGrants};
+use cc_toolgate::config::Mode;
+use cc_toolgate::eval::Decision;
+use cc_toolgate::stats::{self, Stats};
+use cc_toolgate::tokens::{self, TokenVerifier};
 use serde::Deserialize;
 use std::io::Read;
 
+/// Session grants files untouched for this long are deleted.
+const SESSION_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(7 * 86_400);
+
 #[derive(Deserialize)]
 struct HookInput {
+    hook_event_name: Option<String>,
+    session_id: Option<String>,
     tool_name: Option<String>,
     tool_input: Option<ToolInput>,
+    /// The session's working directory, which the command runs in.
+    cwd: Option<std::path::PathBuf>,
+    /// Identifies this tool call; recorded in the decision log.
+    tool_use_id: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -13,9 +27,32 @@ struct ToolInput {
 }
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    let mut args: Vec<String> = std::env::args().collect();
     let escalate_deny = args.iter().any(|a| a == "--escalate-deny");
 
+    // --config <path>: use this user overlay instead of config.toml and
+    // config.d. --profile <name>: apply [profiles.<name>] from the user
+    // config. Both are passed to Config::load through its env vars, and
+    // removed from args so subcommands don't see them.
+    for (flag, var, what) in [
+        ("--config", cc_toolgate::config::CONFIG_ENV, "a path"),
+        (
+            "--profile",
+            cc_toolgate::config::PROFILE_ENV,
+            "a profile name",
+        ),
+    ] {
+        if let Some(pos) = args.iter().position(|a| a == flag) {
+            let Some(value) = args.get(pos + 1).cloned() else {
+                eprintln!("{flag} expects {what}");
+                std::process::exit(2);
+            };
+            args.drain(pos..=pos + 1);
+            // SAFETY: no other threads exist yet.
+            unsafe { std::env::set_var(var, value) };
+        }
+    }
+
     // --dump-ast <command>: print tree-sitter AST and parsed pipeline, then exit
     if let Some(pos) = args.iter().position(|a| a == "--dump-ast") {
         let cmd = args.get(pos + 1).map(|s| s.as_str()).unwrap_or("");
@@ -29,10 +66,39 @@ fn main() {
         return;
     }
 
-    // --dump-config [json]: print effective config and exit
-    if let Some(pos) = args.iter().position(|a| a == "--dump-config") {
+    // --trace <command>: print each lookup evaluation goes through, then
+    // exit. A bare --trace in hook mode attaches the trace to the output.
+    let trace = args.iter().any(|a| a == "--trace");
+    if let Some(pos) = args.iter().position(|a| a == "--trace")
+        && let Some(cmd) = args.get(pos + 1)
+    {
         let config = cc_toolgate::config::Config::load();
+        let registry = build_registry(&config, escalate_deny);
+        let (evaluation, steps) = registry.evaluate_traced(cmd);
+        for step in steps {
+            println!("{step}");
+        }
+        println!(
+            "decision: {}: {}",
+            evaluation.result.decision.label(),
+            evaluation.result.reason
+        );
+        return;
+    }
+
+    // --dump-config [json|annotated]: print effective config and exit
+    if let Some(pos) = args.iter().position(|a| a == "--dump-config") {
         let format = args.get(pos + 1).map(|s| s.as_str());
+        if format == Some("annotated") {
+            let (mut config, mut provenance) = cc_toolgate::config::Config::load_traced();
+            if escalate_deny {
+                config.settings.escalate_deny = true;
+                provenance.record("--escalate-deny", &config);
+            }
+            print!("{}", provenance.annotate(&config));
+            return;
+        }
+        let config = cc_toolgate::config::Config::load();
         match format {
             Some("json") => {
                 println!("{}", serde_json::to_string_pretty(&config).unwrap());
@@ -44,13 +110,70 @@ fn main() {
         return;
     }
 
+    // approve <pattern> [--for 24h] / revoke <pattern> / approvals: manage
+    // remembered approvals, then exit
+    if let Some(cmd @ ("approve" | "revoke" | "approvals")) = args.get(1).map(|s| s.as_str()) {
+        std::process::exit(manage_approvals(cmd, &args[2..]));
+    }
+
+    // eval [--file <path>] [--json]: evaluate a batch of commands, then exit
+    if args.get(1).map(|s| s.as_str()) == Some("eval") {
+        std::process::exit(eval_batch(&args[2..], escalate_deny));
+    }
+
+    // test <corpus.toml>: check commands against expected decisions, then exit
+    if args.get(1).map(|s| s.as_str()) == Some("test") {
+        std::process::exit(test_corpus(&args[2..], escalate_deny));
+    }
+
+    // sign <command> [--for 1h]: print a signed approval token, then exit
+    if args.get(1).map(|s| s.as_str()) == Some("sign") {
+        std::process::exit(sign_token(&args[2..]));
+    }
+
+    // config diff: print what the config files change from the defaults,
+    // then exit
+    if args.get(1).map(|s| s.as_str()) == Some("config") {
+        std::process::exit(config_command(&args[2..]));
+    }
+
+    // export [--format claude-settings]: print the effective config in
+    // another tool's format, then exit
+    if args.get(1).map(|s| s.as_str()) == Some("export") {
+        std::process::exit(export_config(&args[2..]));
+    }
+
+    // bench [--file <path>]... [--no-bundled] [--iterations N]: report
+    // parse and evaluation latency, then exit
+    if args.get(1).map(|s| s.as_str()) == Some("bench") {
+        std::process::exit(bench_latency(&args[2..], escalate_deny));
+    }
+
+    // replay [--since YYYY-MM-DD] [--file PATH]: re-evaluate logged
+    // commands and report changed decisions, then exit
+    if args.get(1).map(|s| s.as_str()) == Some("replay") {
+        std::process::exit(replay_log(&args[2..], escalate_deny));
+    }
+
+    // top [--by ask|deny|allow|total] [--limit N] [--reset]: print the
+    // commands and rules with the most decisions, then exit
+    if args.get(1).map(|s| s.as_str()) == Some("top") {
+        std::process::exit(show_top(&args[2..]));
+    }
+
+    // log [--decision D] [--since YYYY-MM-DD] [--grep TEXT] [--file PATH]:
+    // print matching decision records, then exit
+    if args.get(1).map(|s| s.as_str()) == Some("log") {
+        std::process::exit(query_log(&args[2..]));
+    }
+
     let mut input = String::new();
     if std::io::stdin().read_to_string(&mut input).is_err() {
         eprintln!("failed to read stdin");
         std::process::exit(1);
     }
 
-    let hook_input: HookInput = match serde_json::from_str(&input) {
+    let mut hook_input: HookInput = match serde_json::from_str(&input) {
         Ok(v) => v,
         Err(e) => {
             eprintln!("JSON parse error: {e}");
@@ -64,6 +187,7 @@ fn main() {
 
     let command = hook_input
         .tool_input
+        .take()
         .and_then(|t| t.command)
         .unwrap_or_default();
 
@@ -71,27 +195,712 @@ fn main() {
         std::process::exit(0);
     }
 
+    // Evaluation already fails closed; this also covers config loading,
+    // state files and logging, so a bug anywhere still yields a decision
+    // rather than a crashed hook.
+    let output = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        run_hook(hook_input, &command, escalate_deny, trace)
+    }))
+    .unwrap_or_else(|_| {
+        Some(serde_json::json!({
+            "hookSpecificOutput": {
+                "hookEventName": "PreToolUse",
+                "permissionDecision": Decision::Ask.as_str(),
+                "permissionDecisionReason": "internal error in cc-toolgate (fail-closed)",
+            }
+        }))
+    });
+    if let Some(output) = output {
+        println!("{}", serde_json::to_string(&output).unwrap());
+    }
+}
+
+/// Decide a Bash hook call. Returns the hook output, or `None` when there's
+/// nothing to print (PostToolUse).
+fn run_hook(
+    hook_input: HookInput,
+    command: &str,
+    escalate_deny: bool,
+    trace: bool,
+) -> Option<serde_json::Value> {
     // Init logging (best-effort, no-op on failure)
     cc_toolgate::logging::init();
 
     // Load config (user override or embedded defaults) and build registry
     let config = cc_toolgate::config::Config::load();
-    let mut registry = cc_toolgate::eval::CommandRegistry::from_config(&config);
-    if escalate_deny {
-        registry.set_escalate_deny(true);
+    let mut registry = build_registry(&config, escalate_deny);
+    let registry_cwd = hook_input.cwd.filter(|cwd| cwd.is_absolute());
+    registry.set_cwd(registry_cwd.clone());
+    // Session grants: the grants file for this session, when enabled
+    let grants_path = SessionGrants::default_dir()
+        .filter(|_| config.settings.session_grants)
+        .zip(hook_input.session_id.as_deref())
+        .and_then(|(dir, id)| SessionGrants::path_in(&dir, id));
+
+    // PostToolUse: the command ran, so an ASK for it was approved — record it.
+    // In audit mode nothing was asked, so nothing was approved.
+    if hook_input.hook_event_name.as_deref() == Some("PostToolUse") {
+        if let Some(path) = grants_path
+            && config.settings.mode == Mode::Enforce
+            && registry.evaluate(command).decision == Decision::Ask
+        {
+            let mut grants = SessionGrants::load_from(&path);
+            if grants.grant(command) {
+                if let Some(dir) = path.parent() {
+                    SessionGrants::prune_stale(dir, SESSION_MAX_AGE);
+                }
+                if let Err(e) = grants.save_to(&path) {
+                    eprintln!("failed to write {}: {e}", path.display());
+                }
+            }
+        }
+        return None;
     }
-    let result = registry.evaluate(&command);
 
-    // Log decision to ~/.local/share/cc-toolgate/decisions.log
-    cc_toolgate::logging::log_decision(&command, &result);
+    let started = std::time::Instant::now();
+    let (mut evaluation, steps) = if trace {
+        registry.evaluate_traced(command)
+    } else {
+        (registry.evaluate_fail_closed(command), Vec::new())
+    };
+    let threshold = std::time::Duration::from_millis(config.settings.slow_warning_ms);
+    if !threshold.is_zero() && evaluation.timings.total() > threshold {
+        let timings = evaluation.timings;
+        let shown: String = command.chars().take(80).collect();
+        eprintln!(
+            "cc-toolgate: slow evaluation ({:.1?}: parse {:.1?}, eval {:.1?}) for `{shown}`",
+            timings.total(),
+            timings.parse,
+            timings.eval
+        );
+    }
+    // Count segment decisions for `cc-toolgate top` (best-effort)
+    if let Some(path) = Stats::default_path() {
+        let mut stats = Stats::load_from(&path);
+        stats.record(&evaluation.segments);
+        let _ = stats.save_to(&path);
+    }
+    if let Some(path) = ApprovalStore::default_path() {
+        let store = ApprovalStore::load_from(&path);
+        evaluation.result = store.apply(command, evaluation.result, approvals::now());
+    }
+    if let Some(verifier) = TokenVerifier::from_config(&config.approval_tokens) {
+        let tokens = tokens::tokens_from_env();
+        evaluation.result = verifier.apply(&tokens, command, evaluation.result, approvals::now());
+    }
+    if let Some(path) = grants_path {
+        evaluation.result = SessionGrants::load_from(&path).apply(command, evaluation.result);
+    }
+    // Log decision to ~/.local/share/cc-toolgate/decisions.log; audit mode
+    // logs the evaluated decision but always allows
+    let log_context = cc_toolgate::logging::LogContext {
+        session_id: hook_input.session_id,
+        cwd: registry_cwd,
+        tool_use_id: hook_input.tool_use_id,
+        latency: Some(started.elapsed()),
+        timings: Some(evaluation.timings),
+        segments: evaluation.segments.iter().map(|s| s.decision).collect(),
+    };
+    let result = match config.settings.mode {
+        Mode::Enforce => {
+            cc_toolgate::logging::log_decision(command, &evaluation.result, &log_context);
+            evaluation.result.clone()
+        }
+        Mode::Audit => {
+            cc_toolgate::logging::log_audit_decision(command, &evaluation.result, &log_context);
+            evaluation.result.audited()
+        }
+    };
 
-    let output = serde_json::json!({
+    let mut output = serde_json::json!({
         "hookSpecificOutput": {
             "hookEventName": "PreToolUse",
             "permissionDecision": result.decision.as_str(),
             "permissionDecisionReason": result.reason,
+            "segments": evaluation.segments,
         }
     });
+    if trace {
+        output["hookSpecificOutput"]["trace"] = steps.into();
+    }
+    Some(output)
+}
+
+/// Build the registry for `config`, with the external evaluator and any
+/// configured plugins added.
+fn build_registry(
+    config: &cc_toolgate::config::Config,
+    escalate_deny: bool,
+) -> cc_toolgate::eval::CommandRegistry {
+    let mut registry = cc_toolgate::eval::CommandRegistry::from_config(config);
+    if escalate_deny {
+        registry.set_escalate_deny(true);
+    }
+    cc_toolgate::external::register(&mut registry, &config.hooks);
+    // SAFETY: the plugin directory comes from the user config; project
+    // overlays can't set it.
+    #[cfg(feature = "plugins")]
+    unsafe {
+        cc_toolgate::plugin::register(&mut registry, &config.plugins);
+    }
+    registry
+}
+
+/// Handle the `approve`, `revoke`, and `approvals` subcommands.
+/// Returns the process exit code.
+fn manage_approvals(cmd: &str, args: &[String]) -> i32 {
+    let Some(path) = ApprovalStore::default_path() else {
+        eprintln!("HOME is not set; cannot locate the approvals file");
+        return 1;
+    };
+    let now = approvals::now();
+    let mut store = ApprovalStore::load_from(&path);
+    store.prune(now);
+
+    match cmd {
+        "approve" => {
+            let mut pattern = None;
+            let mut ttl = std::time::Duration::from_secs(3600);
+            let mut iter = args.iter();
+            while let Some(arg) = iter.next() {
+                if arg == "--for" {
+                    match iter.next().and_then(|v| approvals::parse_duration(v)) {
+                        Some(d) => ttl = d,
+                        None => {
+                            eprintln!("--for expects a duration like 30m, 24h, or 7d");
+                            return 2;
+                        }
+                    }
+                } else if pattern.is_none() {
+                    pattern = Some(arg.as_str());
+                } else {
+                    eprintln!("unexpected argument: {arg}");
+                    return 2;
+                }
+            }
+            let Some(pattern) = pattern.filter(|p| !p.trim().is_empty()) else {
+                eprintln!("usage: cc-toolgate approve '<command pattern>' [--for 24h]");
+                return 2;
+            };
+            store.approve(pattern, ttl, now);
+            println!(
+                "approved `{pattern}` for {}",
+                approvals::format_duration(ttl)
+            );
+        }
+        "revoke" => {
+            let Some(pattern) = args.first() else {
+                eprintln!("usage: cc-toolgate revoke '<command pattern>'");
+                return 2;
+            };
+            if !store.revoke(pattern) {
+                eprintln!("no approval for `{pattern}`");
+                return 1;
+            }
+            println!("revoked `{pattern}`");
+        }
+        _ => {
+            for a in &store.approvals {
+                let left = std::time::Duration::from_secs(a.expires - now);
+                println!("{}\t{}", approvals::format_duration(left), a.pattern);
+            }
+            return 0;
+        }
+    }
+
+    if let Err(e) = store.save_to(&path) {
+        eprintln!("failed to write {}: {e}", path.display());
+        return 1;
+    }
+    0
+}
+
+/// Handle the `sign` subcommand. Returns the process exit code.
+fn sign_token(args: &[String]) -> i32 {
+    let mut command = None;
+    let mut ttl = std::time::Duration::from_secs(3600);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--for" {
+            match iter.next().and_then(|v| approvals::parse_duration(v)) {
+                Some(d) => ttl = d,
+                None => {
+                    eprintln!("--for expects a duration like 30m, 24h, or 7d");
+                    return 2;
+                }
+            }
+        } else if command.is_none() {
+            command = Some(arg.as_str());
+        } else {
+            eprintln!("unexpected argument: {arg}");
+            return 2;
+        }
+    }
+    let Some(command) = command.filter(|c| !c.trim().is_empty()) else {
+        eprintln!("usage: cc-toolgate sign '<command>' [--for 1h]");
+        return 2;
+    };
+    let config = cc_toolgate::config::Config::load();
+    let Some(verifier) = TokenVerifier::from_config(&config.approval_tokens) else {
+        eprintln!(
+            "approval tokens are disabled: set [approval_tokens] key_file to a non-empty key"
+        );
+        return 1;
+    };
+    let expires = approvals::now().saturating_add(ttl.as_secs());
+    println!("{}", verifier.sign(command, expires));
+    0
+}
+
+/// Handle the `eval` subcommand. Exits 1 if any command is denied, 2 on
+/// usage or input errors.
+fn eval_batch(args: &[String], escalate_deny: bool) -> i32 {
+    let mut file = None;
+    let mut json = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--file" => match iter.next() {
+                Some(path) => file = Some(path.as_str()),
+                None => {
+                    eprintln!("--file expects a path (or - for stdin)");
+                    return 2;
+                }
+            },
+            "--escalate-deny" => {}
+            other => {
+                eprintln!("unexpected argument: {other}");
+                eprintln!("usage: cc-toolgate eval [--file <path>] [--json]");
+                return 2;
+            }
+        }
+    }
+
+    let text = match file {
+        Some(path) if path != "-" => std::fs::read_to_string(path),
+        _ => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text).map(|_| text)
+        }
+    };
+    let text = match text {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("failed to read input: {e}");
+            return 2;
+        }
+    };
 
-    println!("{}", serde_json::to_string(&output).unwrap());
+    let config = cc_toolgate::config::Config::load();
+    let registry = build_registry(&config, escalate_deny);
+    let results = cc_toolgate::batch::evaluate_lines(&registry, &text);
+
+    for r in &results {
+        if json {
+            println!("{}", serde_json::to_string(r).unwrap());
+        } else {
+            println!(
+                "{}\t{}\t{}",
+                r.decision.label(),
+                r.command,
+                cc_toolgate::batch::one_line(&r.reason)
+            );
+        }
+    }
+    if results.iter().any(|r| r.decision == Decision::Deny) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Handle the `test` subcommand. Exits 1 if any command's decision differs
+/// from the corpus, 2 on usage or input errors.
+fn test_corpus(args: &[String], escalate_deny: bool) -> i32 {
+    let paths: Vec<&String> = args.iter().filter(|a| *a != "--escalate-deny").collect();
+    let [path] = paths.as_slice() else {
+        eprintln!("usage: cc-toolgate test <corpus.toml>");
+        return 2;
+    };
+    let corpus = match std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| cc_toolgate::batch::Corpus::from_toml(&text))
+    {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("failed to load {path}: {e}");
+            return 2;
+        }
+    };
+
+    let config = cc_toolgate::config::Config::load();
+    let registry = build_registry(&config, escalate_deny);
+    let mismatches = corpus.check(&registry);
+    for m in &mismatches {
+        println!(
+            "FAIL\t{}\texpected {}, got {}: {}",
+            m.command,
+            m.expected.label(),
+            m.actual.label(),
+            cc_toolgate::batch::one_line(&m.reason)
+        );
+    }
+    println!(
+        "{} passed, {} failed",
+        corpus.len() - mismatches.len(),
+        mismatches.len()
+    );
+    if mismatches.is_empty() { 0 } else { 1 }
+}
+
+/// Handle the `config` subcommand. Exits 2 on usage errors.
+fn config_command(args: &[String]) -> i32 {
+    use cc_toolgate::config::{Config, ConfigChange};
+    if args.len() != 1 || args[0] != "diff" {
+        eprintln!("usage: cc-toolgate config diff");
+        return 2;
+    }
+    let (config, provenance) = Config::load_traced();
+    for change in cc_toolgate::config::diff(&Config::default_config(), &config) {
+        let source = match &change {
+            ConfigChange::Added { path, value }
+            | ConfigChange::Changed {
+                path, to: value, ..
+            } => provenance.source(path, value),
+            ConfigChange::Removed { .. } => None,
+        };
+        match source {
+            Some(source) => println!("{change}  # {source}"),
+            None => println!("{change}"),
+        }
+    }
+    0
+}
+
+/// Handle the `export` subcommand. Exits 2 on usage errors.
+fn export_config(args: &[String]) -> i32 {
+    let format = match args {
+        [] => "claude-settings",
+        [flag, format] if flag == "--format" => format.as_str(),
+        _ => {
+            eprintln!("usage: cc-toolgate export [--format claude-settings]");
+            return 2;
+        }
+    };
+    let config = cc_toolgate::config::Config::load();
+    match format {
+        "claude-settings" => println!("{}", cc_toolgate::claude_settings::export(&config)),
+        other => {
+            eprintln!("unknown export format: {other} (expected claude-settings)");
+            return 2;
+        }
+    }
+    0
+}
+
+/// Handle the `log` subcommand. Exits 2 on usage errors, 1 if the log
+/// can't be read.
+fn query_log(args: &[String]) -> i32 {
+    let usage = "usage: cc-toolgate log [--decision allow|ask|deny] [--since YYYY-MM-DD] [--grep TEXT] [--file PATH]";
+    let (mut decision, mut since, mut grep, mut file) = (None, None, None, None);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let Some(value) = iter.next() else {
+            eprintln!("{usage}");
+            return 2;
+        };
+        match arg.as_str() {
+            "--decision" => {
+                let Some(d) = [Decision::Allow, Decision::Ask, Decision::Deny]
+                    .into_iter()
+                    .find(|d| d.as_str().eq_ignore_ascii_case(value))
+                else {
+                    eprintln!("--decision expects allow, ask, or deny");
+                    return 2;
+                };
+                decision = Some(d);
+            }
+            "--since" => {
+                let Some(value) = since_arg(value) else {
+                    return 2;
+                };
+                since = Some(value);
+            }
+            "--grep" => grep = Some(value.as_str()),
+            "--file" => file = Some(std::path::PathBuf::from(value)),
+            _ => {
+                eprintln!("{usage}");
+                return 2;
+            }
+        }
+    }
+    let Some(records) = read_log(file) else {
+        return 1;
+    };
+
+    for record in records {
+        if decision.is_some_and(|d| record.decision != d)
+            || since.is_some_and(|s| !record.is_since(s))
+            || grep.is_some_and(|g| !record.command.contains(g) && !record.reason.contains(g))
+        {
+            continue;
+        }
+        println!(
+            "{}  {}  {}",
+            record.time,
+            record.decision.label(),
+            record.command
+        );
+        for line in record.reason.lines() {
+            println!("    {line}");
+        }
+        let context = &record.context;
+        let mut details = Vec::new();
+        if let Some(id) = &context.session_id {
+            details.push(format!("session {id}"));
+        }
+        if let Some(id) = &context.tool_use_id {
+            details.push(format!("tool use {id}"));
+        }
+        if let Some(cwd) = &context.cwd {
+            details.push(format!("in {}", cwd.display()));
+        }
+        if let Some(latency) = context.latency {
+            details.push(format!("{:.2} ms", latency.as_secs_f64() * 1000.0));
+        }
+        if context.segments.len() > 1 {
+            let labels: Vec<&str> = context.segments.iter().map(|d| d.label()).collect();
+            details.push(format!("segments {}", labels.join(", ")));
+        }
+        if !details.is_empty() {
+            println!("    ({})", details.join("; "));
+        }
+    }
+    0
+}
+
+/// Handle the `replay` subcommand: re-evaluate logged commands with the
+/// current config (usually a candidate given with `--config`) and report
+/// those whose decision would change. Exits 2 on usage errors, 1 if the log
+/// can't be read.
+fn replay_log(args: &[String], escalate_deny: bool) -> i32 {
+    let usage =
+        "usage: cc-toolgate replay [--config candidate.toml] [--since YYYY-MM-DD] [--file PATH]";
+    let (mut since, mut file) = (None, None);
+    let mut iter = args.iter().filter(|a| *a != "--escalate-deny");
+    while let Some(arg) = iter.next() {
+        match (arg.as_str(), iter.next()) {
+            ("--since", Some(value)) => match since_arg(value) {
+                Some(value) => since = Some(value),
+                None => return 2,
+            },
+            ("--file", Some(value)) => file = Some(std::path::PathBuf::from(value)),
+            _ => {
+                eprintln!("{usage}");
+                return 2;
+            }
+        }
+    }
+    let Some(records) = read_log(file) else {
+        return 1;
+    };
+    let replayable: Vec<_> = records
+        .iter()
+        .filter(|r| since.is_none_or(|s| r.is_since(s)))
+        .filter(|r| !r.is_truncated())
+        .collect();
+    let skipped = records.len() - replayable.len();
+
+    let config = cc_toolgate::config::Config::load();
+    let registry = build_registry(&config, escalate_deny);
+    let (replayed, changes) = cc_toolgate::batch::replay(
+        &registry,
+        replayable.iter().map(|r| (r.command.as_str(), r.decision)),
+    );
+    for c in &changes {
+        println!(
+            "{} -> {}\t{}\t{}",
+            c.expected.label(),
+            c.actual.label(),
+            c.command,
+            cc_toolgate::batch::one_line(&c.reason)
+        );
+    }
+    println!(
+        "{replayed} commands replayed, {} would change{}",
+        changes.len(),
+        if skipped > 0 {
+            format!(" ({skipped} records skipped)")
+        } else {
+            String::new()
+        }
+    );
+    0
+}
+
+/// Validate a `--since` value. Timestamps are RFC 3339, so a date prefix
+/// compares as text.
+fn since_arg(value: &str) -> Option<&str> {
+    let bytes = value.as_bytes();
+    if bytes.len() < 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        eprintln!("--since expects a date like 2024-01-01");
+        return None;
+    }
+    Some(value)
+}
+
+/// Read the decisions log at `file`, or the default location.
+fn read_log(file: Option<std::path::PathBuf>) -> Option<Vec<cc_toolgate::logging::LogRecord>> {
+    let Some(path) = file.or_else(cc_toolgate::logging::log_path) else {
+        eprintln!("HOME is not set; cannot locate the decisions log");
+        return None;
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(text) => Some(
+            text.lines()
+                .filter_map(cc_toolgate::logging::parse_line)
+                .collect(),
+        ),
+        Err(e) => {
+            eprintln!("failed to read {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Handle the `top` subcommand. Exits 2 on usage errors.
+fn show_top(args: &[String]) -> i32 {
+    let usage = "usage: cc-toolgate top [--by ask|deny|allow|total] [--limit N] [--reset]";
+    let Some(path) = Stats::default_path() else {
+        eprintln!("HOME is not set; cannot locate the stats file");
+        return 1;
+    };
+    let (mut by, mut limit, mut reset) = (Some(Decision::Ask), 10, false);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--reset" => reset = true,
+            "--by" => match iter.next().map(|v| v.as_str()) {
+                Some("total") => by = None,
+                value => {
+                    let Some(d) = [Decision::Allow, Decision::Ask, Decision::Deny]
+                        .into_iter()
+                        .find(|d| value.is_some_and(|v| d.as_str().eq_ignore_ascii_case(v)))
+                    else {
+                        eprintln!("--by expects ask, deny, allow, or total");
+                        return 2;
+                    };
+                    by = Some(d);
+                }
+            },
+            "--limit" => match iter.next().and_then(|v| v.parse().ok()) {
+                Some(n) if n > 0 => limit = n,
+                _ => {
+                    eprintln!("--limit expects a positive number");
+                    return 2;
+                }
+            },
+            _ => {
+                eprintln!("{usage}");
+                return 2;
+            }
+        }
+    }
+
+    if reset {
+        if let Err(e) = std::fs::remove_file(&path)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            eprintln!("failed to remove {}: {e}", path.display());
+            return 1;
+        }
+        println!("counters reset");
+        return 0;
+    }
+    let counters = Stats::load_from(&path);
+    for (title, counts) in [("command", &counters.commands), ("rule", &counters.rules)] {
+        println!("{:>7} {:>7} {:>7}  {title}", "ASK", "DENY", "ALLOW");
+        for (name, c) in stats::top(counts, by, limit) {
+            println!("{:>7} {:>7} {:>7}  {name}", c.ask, c.deny, c.allow);
+        }
+        println!();
+    }
+    0
+}
+
+/// Handle the `bench` subcommand. Exits 2 on usage or input errors.
+fn bench_latency(args: &[String], escalate_deny: bool) -> i32 {
+    let mut files = Vec::new();
+    let mut bundled = true;
+    let mut iterations = 100;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--file" => match iter.next() {
+                Some(path) => files.push(path.as_str()),
+                None => {
+                    eprintln!("--file expects a path");
+                    return 2;
+                }
+            },
+            "--iterations" => match iter.next().and_then(|n| n.parse().ok()) {
+                Some(n) if n > 0 => iterations = n,
+                _ => {
+                    eprintln!("--iterations expects a positive number");
+                    return 2;
+                }
+            },
+            "--no-bundled" => bundled = false,
+            "--escalate-deny" => {}
+            other => {
+                eprintln!("unexpected argument: {other}");
+                eprintln!(
+                    "usage: cc-toolgate bench [--file <path>]... [--no-bundled] [--iterations N]"
+                );
+                return 2;
+            }
+        }
+    }
+
+    let mut commands: Vec<String> = Vec::new();
+    if bundled {
+        commands.extend(cc_toolgate::bench::BUNDLED.iter().map(|c| c.to_string()));
+    }
+    for path in files {
+        // A .toml file is a corpus (see `test`); anything else is read like
+        // `eval --file` input.
+        let loaded = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                if path.ends_with(".toml") {
+                    let corpus = cc_toolgate::batch::Corpus::from_toml(&text)?;
+                    Ok([corpus.allow, corpus.ask, corpus.deny].concat())
+                } else {
+                    Ok(text
+                        .lines()
+                        .filter_map(cc_toolgate::batch::command_from_line)
+                        .collect())
+                }
+            });
+        match loaded {
+            Ok(c) => commands.extend(c),
+            Err(e) => {
+                eprintln!("failed to load {path}: {e}");
+                return 2;
+            }
+        }
+    }
+    if commands.is_empty() {
+        eprintln!("no commands to benchmark");
+        return 2;
+    }
+
+    let config = cc_toolgate::config::Config::load();
+    let registry = build_registry(&config, escalate_deny);
+    let report = cc_toolgate::bench::run(&registry, &commands, iterations);
+    println!("{} commands x {iterations} iterations", commands.len());
+    println!("parse     {}", report.parse);
+    println!("evaluate  {}", report.evaluate);
+    0
 }
@@ -0,0 +1,272 @@
+//! Typed parse results for external tooling.
+//!
+//! [`analyze`](crate::parse::analyze) runs the same parser the evaluator
+//! uses and returns a plain, serializable
+//! [`CommandAnalysis`](crate::parse::CommandAnalysis), so editors and other
+//! agents can inspect a command's structure without building a registry or
+//! making a decision:
+//!
+//! ```
+//! let analysis = cc_toolgate::parse::analyze("FOO=1 make && echo $(date) > out").unwrap();
+//! assert_eq!(analysis.operators, ["&&"]);
+//! assert_eq!(analysis.segments[0].base_command, "make");
+//! assert_eq!(analysis.segments[0].env_assignments[0].name, "FOO");
+//! assert_eq!(analysis.segments[1].substitutions[0].text, "$(date)");
+//! ```
+//!
+//! Byte spans are `start..end` offsets into the text that was analyzed: the
+//! full command for top-level segments, the segment's `command` for its
+//! substitutions.
+
+use agent_shell_parser::parse::{self, ParsedPipeline, ShellSegment, SubstitutionSpan};
+use serde::{Deserialize, Serialize};
+
+/// A `start..end` byte range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    /// Offset of the first byte.
+    pub start: usize,
+    /// Offset past the last byte.
+    pub end: usize,
+}
+
+/// The parsed structure of a command string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommandAnalysis {
+    /// Simple commands, in execution order.
+    pub segments: Vec<SegmentAnalysis>,
+    /// Operators between consecutive segments (`&&`, `||`, `;`, `|`, ...).
+    pub operators: Vec<String>,
+    /// Whether the parser recovered from syntax errors.
+    pub has_parse_errors: bool,
+}
+
+/// One simple command within a [`CommandAnalysis`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SegmentAnalysis {
+    /// The command text as it appears in the source (trimmed).
+    pub command: String,
+    /// Where `command` sits in the analyzed text. `None` for commands the
+    /// parser lifted out of a construct whose text differs from the source
+    /// (e.g. the body of a redirected loop).
+    pub span: Option<Span>,
+    /// The command name, after leading assignments, without its directory.
+    pub base_command: String,
+    /// Words as the shell splits them, quotes removed.
+    pub words: Vec<String>,
+    /// Leading `NAME=value` assignments.
+    pub env_assignments: Vec<EnvAssignment>,
+    /// Output redirection, if any.
+    pub redirection: Option<RedirectionAnalysis>,
+    /// Command and process substitutions in `command`, in source order.
+    pub substitutions: Vec<SubstitutionAnalysis>,
+}
+
+/// A leading `NAME=value` assignment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EnvAssignment {
+    /// Variable name.
+    pub name: String,
+    /// Assigned value, quotes removed.
+    pub value: String,
+}
+
+/// An output redirection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedirectionAnalysis {
+    /// The operator (`>`, `>>`, `&>`, ...).
+    pub operator: String,
+    /// Source file descriptor, when given explicitly (`2>`).
+    pub fd: Option<u32>,
+    /// Destination path or descriptor.
+    pub target: String,
+}
+
+/// A `$(...)`, backtick, `<(...)`, or `>(...)` substitution.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubstitutionAnalysis {
+    /// Position within the enclosing segment's `command`.
+    pub span: Span,
+    /// The substitution text, delimiters included.
+    pub text: String,
+    /// The parsed contents.
+    pub inner: CommandAnalysis,
+}
+
+/// Parse `command` without ever panicking.
+///
+/// Returns `None` if the parser reports an error or panics on adversarial
+/// input. Used by the evaluator, [`analyze`] and the fuzz targets.
+pub fn parse_lossy(command: &str) -> Option<ParsedPipeline> {
+    std::panic::catch_unwind(|| parse::parse_with_substitutions(command).ok())
+        .ok()
+        .flatten()
+}
+
+/// Parse `command` into a [`CommandAnalysis`].
+///
+/// Returns `None` if the parser fails. Never panics.
+pub fn analyze(command: &str) -> Option<CommandAnalysis> {
+    parse_lossy(command).map(|pipeline| analyze_pipeline(command, &pipeline))
+}
+
+fn analyze_pipeline(source: &str, pipeline: &ParsedPipeline) -> CommandAnalysis {
+    let mut cursor = 0;
+    let segments = pipeline
+        .segments
+        .iter()
+        .map(|segment| {
+            let span = source[cursor..].find(&segment.command).map(|i| Span {
+                start: cursor + i,
+                end: cursor + i + segment.command.len(),
+            });
+            if let Some(span) = span {
+                cursor = span.end;
+            }
+            analyze_segment(segment, span)
+        })
+        .collect();
+    CommandAnalysis {
+        segments,
+        operators: pipeline
+            .operators
+            .iter()
+            .map(|op| op.as_str().to_string())
+            .collect(),
+        has_parse_errors: pipeline.has_parse_errors,
+    }
+}
+
+fn analyze_segment(segment: &ShellSegment, span: Option<Span>) -> SegmentAnalysis {
+    SegmentAnalysis {
+        command: segment.command.clone(),
+        span,
+        base_command: parse::base_command(&segment.command),
+        words: segment.words.iter().map(|w| w.to_string()).collect(),
+        env_assignments: parse::env_vars(&segment.command)
+            .into_iter()
+            .map(|(name, value)| EnvAssignment { name, value })
+            .collect(),
+        redirection: segment
+            .redirection
+            .clone()
+            .or_else(|| {
+                parse::has_output_redirection(&segment.command)
+                    .ok()
+                    .flatten()
+            })
+            .map(|r| RedirectionAnalysis {
+                operator: r.operator.to_string(),
+                fd: r.fd,
+                target: r.target,
+            }),
+        substitutions: segment
+            .substitutions
+            .iter()
+            .map(|sub| analyze_substitution(&segment.command, sub))
+            .collect(),
+    }
+}
+
+fn analyze_substitution(source: &str, sub: &SubstitutionSpan) -> SubstitutionAnalysis {
+    let text = source.get(sub.start..sub.end).unwrap_or_default();
+    SubstitutionAnalysis {
+        span: Span {
+            start: sub.start,
+            end: sub.end,
+        },
+        text: text.to_string(),
+        inner: analyze_pipeline(inner_text(text), &sub.pipeline),
+    }
+}
+
+/// The substitution body without its `$(`/`<(`/`>(`/backtick delimiters.
+fn inner_text(text: &str) -> &str {
+    ["$(", "<(", ">("]
+        .iter()
+        .find_map(|open| text.strip_prefix(open)?.strip_suffix(')'))
+        .or_else(|| text.strip_prefix('`')?.strip_suffix('`'))
+        .unwrap_or(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lossy_matches_parser() {
+        assert!(parse_lossy("ls && git status").is_some_and(|p| p.segments.len() == 2));
+    }
+
+    #[test]
+    fn segments_operators_and_spans() {
+        let cmd = "git add . && git commit -m 'msg' | tee log";
+        let a = analyze(cmd).unwrap();
+        assert_eq!(a.operators, ["&&", "|"]);
+        let spans: Vec<&str> = a
+            .segments
+            .iter()
+            .map(|s| {
+                let span = s.span.unwrap();
+                &cmd[span.start..span.end]
+            })
+            .collect();
+        assert_eq!(spans, ["git add .", "git commit -m 'msg'", "tee log"]);
+        assert_eq!(a.segments[1].words, ["git", "commit", "-m", "msg"]);
+        assert!(!a.has_parse_errors);
+    }
+
+    #[test]
+    fn env_assignments_and_base_command() {
+        let a = analyze("A=1 B='two words' /usr/bin/env ls").unwrap();
+        let seg = &a.segments[0];
+        assert_eq!(seg.base_command, "env");
+        let vars: Vec<(&str, &str)> = seg
+            .env_assignments
+            .iter()
+            .map(|e| (e.name.as_str(), e.value.as_str()))
+            .collect();
+        assert_eq!(vars, [("A", "1"), ("B", "two words")]);
+    }
+
+    #[test]
+    fn redirection() {
+        let a = analyze("echo hi 2>> err.log").unwrap();
+        let r = a.segments[0].redirection.as_ref().unwrap();
+        assert_eq!((r.operator.as_str(), r.fd), (">>", Some(2)));
+        assert_eq!(r.target, "err.log");
+        assert!(
+            analyze("echo hi").unwrap().segments[0]
+                .redirection
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn nested_substitutions() {
+        let a = analyze("echo $(cat $(ls) | wc -l)").unwrap();
+        let seg = &a.segments[0];
+        let sub = &seg.substitutions[0];
+        assert_eq!(sub.text, "$(cat $(ls) | wc -l)");
+        assert_eq!(&seg.command[sub.span.start..sub.span.end], sub.text);
+        assert_eq!(sub.inner.operators, ["|"]);
+        let cat = &sub.inner.segments[0];
+        assert_eq!(cat.span, Some(Span { start: 0, end: 9 }));
+        assert_eq!(cat.substitutions[0].inner.segments[0].command, "ls");
+    }
+
+    #[test]
+    fn serializes_to_json() {
+        let a = analyze("ls > out").unwrap();
+        let json = serde_json::to_value(&a).unwrap();
+        assert_eq!(json["segments"][0]["base_command"], "ls");
+        assert_eq!(json["segments"][0]["redirection"]["target"], "out");
+    }
+
+    #[test]
+    fn never_panics() {
+        for input in ["", "'unterminated", "$(", "a && && b", "\u{0}"] {
+            let _ = analyze(input);
+        }
+    }
+}
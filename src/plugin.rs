@@ -0,0 +1,585 @@
+//! Out-of-tree command specs loaded from dynamic libraries.
+//!
+//! A plugin is a `cdylib` crate that depends on cc-toolgate, implements
+//! [`CommandSpec`](crate::commands::CommandSpec) for its tools, and exports the spec with
+//! [`export_plugin!`](crate::export_plugin):
+//!
+//! ```
+//! use cc_toolgate::commands::CommandSpec;
+//! use cc_toolgate::eval::{CommandContext, Decision, RuleMatch};
+//!
+//! struct DeploySpec;
+//!
+//! impl CommandSpec for DeploySpec {
+//!     fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+//!         match ctx.args().first().map(|w| w.as_str()) {
+//!             Some("status" | "diff") => RuleMatch {
+//!                 decision: Decision::Allow,
+//!                 reason: "deployctl read-only".into(),
+//!             },
+//!             _ => RuleMatch {
+//!                 decision: Decision::Ask,
+//!                 reason: "deployctl changes an environment".into(),
+//!             },
+//!         }
+//!     }
+//! }
+//!
+//! cc_toolgate::export_plugin!(DeploySpec, ["deployctl"]);
+//! ```
+//!
+//! With `[plugins] dir` set in the user config, the hook loads every library
+//! in that directory at startup and [registers](crate::eval::CommandRegistry::register)
+//! each plugin's spec under the command names it exports. Project overlays
+//! cannot set the directory.
+//!
+//! # ABI
+//!
+//! The boundary is four C functions exchanging NUL-terminated JSON, so a
+//! plugin doesn't have to be built with the same compiler as the hook:
+//!
+//! - `cc_toolgate_plugin_abi() -> u32` returns [`ABI_VERSION`](crate::plugin::ABI_VERSION).
+//! - `cc_toolgate_plugin_commands() -> *mut c_char` returns a JSON array of
+//!   command names.
+//! - `cc_toolgate_plugin_evaluate(*const c_char) -> *mut c_char` takes a
+//!   [`SerializedContext`](crate::eval::context::SerializedContext) and returns a
+//!   [`RuleMatch`](crate::eval::RuleMatch).
+//! - `cc_toolgate_plugin_free(*mut c_char)` frees a string returned by the
+//!   other two.
+//!
+//! A library that can't be loaded, reports another ABI version, or isn't
+//! safely permissioned is skipped with a warning. An evaluation that fails
+//! (no response, malformed JSON, a panic in the plugin) is ASK. Libraries are
+//! never unloaded.
+
+use crate::commands::CommandSpec;
+use crate::config::PluginsConfig;
+use crate::eval::context::SerializedContext;
+use crate::eval::{CommandContext, CommandRegistry, Decision, RuleMatch};
+use std::ffi::{CStr, CString, c_char};
+use std::path::{Path, PathBuf};
+
+/// Version of the plugin ABI described in the module docs.
+pub const ABI_VERSION: u32 = 1;
+
+/// Export a [`CommandSpec`] from a plugin library for the given command
+/// names. See the [module docs](crate::plugin).
+#[macro_export]
+macro_rules! export_plugin {
+    ($spec:expr, [$($name:expr),+ $(,)?]) => {
+        #[unsafe(no_mangle)]
+        pub extern "C" fn cc_toolgate_plugin_abi() -> u32 {
+            $crate::plugin::ABI_VERSION
+        }
+
+        #[unsafe(no_mangle)]
+        pub extern "C" fn cc_toolgate_plugin_commands() -> *mut ::std::ffi::c_char {
+            $crate::plugin::guest::commands(&[$($name),+])
+        }
+
+        /// # Safety
+        ///
+        /// `request` must be a NUL-terminated string.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn cc_toolgate_plugin_evaluate(
+            request: *const ::std::ffi::c_char,
+        ) -> *mut ::std::ffi::c_char {
+            static SPEC: $crate::plugin::guest::SpecCell = $crate::plugin::guest::SpecCell::new();
+            unsafe { $crate::plugin::guest::evaluate(&SPEC, || ::std::boxed::Box::new($spec), request) }
+        }
+
+        /// # Safety
+        ///
+        /// `s` must be null or a string returned by this plugin.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn cc_toolgate_plugin_free(s: *mut ::std::ffi::c_char) {
+            unsafe { $crate::plugin::guest::free(s) }
+        }
+    };
+}
+
+/// Plugin-side support for [`export_plugin!`](crate::export_plugin).
+#[doc(hidden)]
+pub mod guest {
+    use crate::commands::CommandSpec;
+    use crate::eval::context::SerializedContext;
+    use crate::eval::{Decision, RuleMatch};
+    use std::ffi::{CStr, CString, c_char};
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+    use std::sync::OnceLock;
+
+    /// The plugin's spec, built on first evaluation.
+    pub type SpecCell = OnceLock<Box<dyn CommandSpec>>;
+
+    /// The command names as a JSON array.
+    pub fn commands(names: &[&str]) -> *mut c_char {
+        into_raw(serde_json::to_string(names).unwrap_or_default())
+    }
+
+    /// Evaluate a JSON [`SerializedContext`]. Never unwinds into the host: errors and
+    /// panics become ASK.
+    ///
+    /// # Safety
+    ///
+    /// `request` must be a NUL-terminated string.
+    pub unsafe fn evaluate(
+        spec: &SpecCell,
+        build: impl FnOnce() -> Box<dyn CommandSpec>,
+        request: *const c_char,
+    ) -> *mut c_char {
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            if request.is_null() {
+                return Err("null request".to_string());
+            }
+            let text = unsafe { CStr::from_ptr(request) }
+                .to_str()
+                .map_err(|e| format!("request is not UTF-8: {e}"))?;
+            let request: SerializedContext =
+                serde_json::from_str(text).map_err(|e| format!("bad request: {e}"))?;
+            Ok(spec.get_or_init(build).evaluate(&request.into_context()))
+        }))
+        .unwrap_or_else(|_| Err("panicked".to_string()))
+        .unwrap_or_else(|e| RuleMatch {
+            decision: Decision::Ask,
+            reason: format!("plugin error: {e}"),
+        });
+        into_raw(serde_json::to_string(&result).unwrap_or_default())
+    }
+
+    /// Free a string returned by [`commands`] or [`evaluate`].
+    ///
+    /// # Safety
+    ///
+    /// `s` must be null or a pointer returned by this module.
+    pub unsafe fn free(s: *mut c_char) {
+        if !s.is_null() {
+            drop(unsafe { CString::from_raw(s) });
+        }
+    }
+
+    fn into_raw(s: String) -> *mut c_char {
+        CString::new(s).map_or(std::ptr::null_mut(), CString::into_raw)
+    }
+}
+
+type AbiFn = extern "C" fn() -> u32;
+type CommandsFn = extern "C" fn() -> *mut c_char;
+type EvaluateFn = unsafe extern "C" fn(*const c_char) -> *mut c_char;
+type FreeFn = unsafe extern "C" fn(*mut c_char);
+
+/// A loaded plugin library. Evaluates as the spec the library exports.
+pub struct Plugin {
+    path: PathBuf,
+    commands: Vec<String>,
+    evaluate: EvaluateFn,
+    free: FreeFn,
+}
+
+impl Plugin {
+    /// Load the plugin library at `path`.
+    ///
+    /// # Safety
+    ///
+    /// Loading runs the library's initializers, and its exports are trusted
+    /// to follow the plugin ABI. `path` must be a library you trust.
+    pub unsafe fn load(path: &Path) -> Result<Self, String> {
+        #[cfg(unix)]
+        {
+            check_permissions(path)?;
+            let handle = unsafe { dl::open(path) }?;
+            unsafe {
+                Self::from_symbols(
+                    path,
+                    std::mem::transmute::<*mut std::ffi::c_void, AbiFn>(dl::sym(
+                        handle,
+                        c"cc_toolgate_plugin_abi",
+                    )?),
+                    std::mem::transmute::<*mut std::ffi::c_void, CommandsFn>(dl::sym(
+                        handle,
+                        c"cc_toolgate_plugin_commands",
+                    )?),
+                    std::mem::transmute::<*mut std::ffi::c_void, EvaluateFn>(dl::sym(
+                        handle,
+                        c"cc_toolgate_plugin_evaluate",
+                    )?),
+                    std::mem::transmute::<*mut std::ffi::c_void, FreeFn>(dl::sym(
+                        handle,
+                        c"cc_toolgate_plugin_free",
+                    )?),
+                )
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            Err(format!(
+                "{}: plugins are only supported on Unix",
+                path.display()
+            ))
+        }
+    }
+
+    /// Check the ABI version and read the command list.
+    ///
+    /// # Safety
+    ///
+    /// The functions must follow the plugin ABI.
+    unsafe fn from_symbols(
+        path: &Path,
+        abi: AbiFn,
+        commands: CommandsFn,
+        evaluate: EvaluateFn,
+        free: FreeFn,
+    ) -> Result<Self, String> {
+        let label = path.display();
+        let version = abi();
+        if version != ABI_VERSION {
+            return Err(format!(
+                "{label}: plugin ABI {version}, expected {ABI_VERSION}"
+            ));
+        }
+        let mut plugin = Self {
+            path: path.to_path_buf(),
+            commands: Vec::new(),
+            evaluate,
+            free,
+        };
+        let names =
+            unsafe { plugin.take_string(commands()) }.map_err(|e| format!("{label}: {e}"))?;
+        plugin.commands =
+            serde_json::from_str(&names).map_err(|e| format!("{label}: bad command list: {e}"))?;
+        if plugin.commands.is_empty() {
+            return Err(format!("{label}: plugin exports no commands"));
+        }
+        Ok(plugin)
+    }
+
+    /// The library this plugin was loaded from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The command names the plugin handles.
+    pub fn commands(&self) -> &[String] {
+        &self.commands
+    }
+
+    /// Copy a string returned by the plugin and hand it back to be freed.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or a string returned by this plugin.
+    unsafe fn take_string(&self, ptr: *mut c_char) -> Result<String, String> {
+        if ptr.is_null() {
+            return Err("no response".into());
+        }
+        let text = unsafe { CStr::from_ptr(ptr) }
+            .to_str()
+            .map(str::to_owned)
+            .map_err(|e| format!("response is not UTF-8: {e}"));
+        unsafe { (self.free)(ptr) };
+        text
+    }
+
+    fn call(&self, ctx: &CommandContext) -> Result<RuleMatch, String> {
+        let request =
+            serde_json::to_string(&SerializedContext::from(ctx)).map_err(|e| e.to_string())?;
+        let request = CString::new(request).map_err(|e| e.to_string())?;
+        let response = unsafe { self.take_string((self.evaluate)(request.as_ptr())) }?;
+        serde_json::from_str(&response).map_err(|e| format!("bad response: {e}"))
+    }
+}
+
+impl CommandSpec for Plugin {
+    fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+        self.call(ctx).unwrap_or_else(|e| RuleMatch {
+            decision: Decision::Ask,
+            reason: format!("plugin {}: {e}", self.path.display()),
+        })
+    }
+}
+
+/// Load every plugin library (`*.so`, or `*.dylib` on macOS) in `dir`, in
+/// file name order. Returns the plugins that loaded and an error for each
+/// that didn't.
+///
+/// # Safety
+///
+/// See [`Plugin::load`]; every library in `dir` is loaded.
+pub unsafe fn load_dir(dir: &Path) -> (Vec<Plugin>, Vec<String>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => return (Vec::new(), vec![format!("{}: {e}", dir.display())]),
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION)
+        })
+        .collect();
+    paths.sort();
+
+    let (mut plugins, mut errors) = (Vec::new(), Vec::new());
+    for path in paths {
+        match unsafe { Plugin::load(&path) } {
+            Ok(plugin) => plugins.push(plugin),
+            Err(e) => errors.push(e),
+        }
+    }
+    (plugins, errors)
+}
+
+/// Load the plugins in `config.dir` into `registry`. Later libraries (by
+/// file name) win when two claim the same command. Failures are logged and
+/// skipped.
+///
+/// # Safety
+///
+/// See [`Plugin::load`]. The directory must come from a trusted config; the
+/// project overlay can't set it.
+pub unsafe fn register(registry: &mut CommandRegistry, config: &PluginsConfig) {
+    if config.dir.is_empty() {
+        return;
+    }
+    let dir = shellexpand::tilde(&config.dir);
+    let (plugins, errors) = unsafe { load_dir(Path::new(dir.as_ref())) };
+    for error in errors {
+        log::warn!("plugin skipped: {error}");
+    }
+    for plugin in plugins {
+        log::debug!(
+            "plugin {} handles {}",
+            plugin.path.display(),
+            plugin.commands.join(", ")
+        );
+        let names = plugin.commands.clone();
+        registry.register(&names, Box::new(plugin));
+    }
+}
+
+/// Refuse a library, or its directory, that others can write to: anyone who
+/// can replace it can run code inside the hook.
+#[cfg(unix)]
+fn check_permissions(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::MetadataExt;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    for p in [path, dir] {
+        let meta = std::fs::metadata(p).map_err(|e| format!("{}: {e}", p.display()))?;
+        if meta.mode() & 0o022 != 0 {
+            return Err(format!(
+                "{}: writable by group or others, not loading",
+                p.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Minimal `dlopen`/`dlsym` bindings.
+#[cfg(unix)]
+mod dl {
+    use std::ffi::{CStr, CString, c_char, c_int, c_void};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    const RTLD_NOW: c_int = 2;
+
+    #[cfg_attr(target_os = "linux", link(name = "dl"))]
+    unsafe extern "C" {
+        fn dlopen(filename: *const c_char, flag: c_int) -> *mut c_void;
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        fn dlerror() -> *mut c_char;
+    }
+
+    /// The pending `dlerror` message.
+    fn last_error() -> String {
+        let err = unsafe { dlerror() };
+        if err.is_null() {
+            "unknown error".into()
+        } else {
+            unsafe { CStr::from_ptr(err) }
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+
+    /// Open the library at `path`. It is never closed.
+    ///
+    /// # Safety
+    ///
+    /// Runs the library's initializers.
+    pub(super) unsafe fn open(path: &Path) -> Result<*mut c_void, String> {
+        let name = CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        let handle = unsafe { dlopen(name.as_ptr(), RTLD_NOW) };
+        if handle.is_null() {
+            Err(last_error())
+        } else {
+            Ok(handle)
+        }
+    }
+
+    /// Look up `symbol` in an open library.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must come from [`open`].
+    pub(super) unsafe fn sym(handle: *mut c_void, symbol: &CStr) -> Result<*mut c_void, String> {
+        let ptr = unsafe { dlsym(handle, symbol.as_ptr()) };
+        if ptr.is_null() {
+            Err(format!("missing symbol {}", symbol.to_string_lossy()))
+        } else {
+            Ok(ptr)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::CommandRegistry;
+
+    /// Allows `deployctl status`, denies `deployctl destroy`, asks otherwise.
+    struct DeploySpec;
+
+    impl CommandSpec for DeploySpec {
+        fn evaluate(&self, ctx: &CommandContext) -> RuleMatch {
+            let (decision, reason) = match ctx.args().first().map(|w| w.as_str()) {
+                Some("status") => (Decision::Allow, "deployctl status"),
+                Some("destroy") => (Decision::Deny, "deployctl destroy"),
+                Some("panic") => panic!("boom"),
+                _ => (Decision::Ask, "deployctl"),
+            };
+            let reason = match &ctx.redirection {
+                Some(r) => format!("{reason} {} {}", r.operator, r.target),
+                None => reason.to_string(),
+            };
+            RuleMatch { decision, reason }
+        }
+    }
+
+    mod exported {
+        crate::export_plugin!(super::DeploySpec, ["deployctl", "dctl"]);
+    }
+
+    fn plugin() -> Plugin {
+        unsafe {
+            Plugin::from_symbols(
+                Path::new("/plugins/deploy.so"),
+                exported::cc_toolgate_plugin_abi,
+                exported::cc_toolgate_plugin_commands,
+                exported::cc_toolgate_plugin_evaluate,
+                exported::cc_toolgate_plugin_free,
+            )
+        }
+        .unwrap()
+    }
+
+    fn scratch_dir(tag: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("cc-toolgate-test-plugin-{tag}-{nanos}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn exported_plugin_evaluates_across_the_abi() {
+        let plugin = plugin();
+        assert_eq!(plugin.commands(), ["deployctl", "dctl"]);
+        let ctx = CommandContext::from_command("deployctl status > out");
+        let result = plugin.evaluate(&ctx);
+        assert_eq!(result.decision, Decision::Allow);
+        assert_eq!(result.reason, "deployctl status > out");
+    }
+
+    #[test]
+    fn plugin_panic_is_ask() {
+        let result = plugin().evaluate(&CommandContext::from_command("deployctl panic"));
+        assert_eq!(result.decision, Decision::Ask);
+        assert!(result.reason.contains("panicked"), "{}", result.reason);
+    }
+
+    #[test]
+    fn rejects_other_abi_versions() {
+        extern "C" fn abi_v2() -> u32 {
+            2
+        }
+        let err = unsafe {
+            Plugin::from_symbols(
+                Path::new("old.so"),
+                abi_v2,
+                exported::cc_toolgate_plugin_commands,
+                exported::cc_toolgate_plugin_evaluate,
+                exported::cc_toolgate_plugin_free,
+            )
+        }
+        .err()
+        .unwrap();
+        assert!(err.contains("ABI 2"), "{err}");
+    }
+
+    #[test]
+    fn registered_plugin_overrides_and_composes() {
+        let config = crate::config::Config::default_config();
+        let mut reg = CommandRegistry::from_config(&config);
+        assert_eq!(reg.evaluate("dctl status").decision, Decision::Ask);
+
+        let plugin = plugin();
+        let names = plugin.commands().to_vec();
+        reg.register(&names, Box::new(plugin));
+        assert_eq!(reg.evaluate("dctl status").decision, Decision::Allow);
+        assert_eq!(
+            reg.evaluate("ls && deployctl status").decision,
+            Decision::Allow
+        );
+        assert_eq!(
+            reg.evaluate("deployctl status; deployctl destroy").decision,
+            Decision::Deny
+        );
+        assert_eq!(
+            reg.evaluate("sudo deployctl status").decision,
+            Decision::Ask
+        );
+    }
+
+    #[test]
+    fn load_dir_reports_bad_libraries_and_skips_other_files() {
+        let dir = scratch_dir("load");
+        let lib = dir.join(format!("broken.{}", std::env::consts::DLL_EXTENSION));
+        std::fs::write(&lib, b"not a library").unwrap();
+        std::fs::write(dir.join("README.md"), b"notes").unwrap();
+
+        let (plugins, errors) = unsafe { load_dir(&dir) };
+        assert!(plugins.is_empty());
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].contains("broken"), "{}", errors[0]);
+
+        let (_, errors) = unsafe { load_dir(&dir.join("missing")) };
+        assert_eq!(errors.len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_writable_libraries() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = scratch_dir("perms");
+        let lib = dir.join("shared.so");
+        std::fs::write(&lib, b"").unwrap();
+        std::fs::set_permissions(&lib, std::fs::Permissions::from_mode(0o666)).unwrap();
+        let err = unsafe { Plugin::load(&lib) }.err().unwrap();
+        assert!(err.contains("writable by group or others"), "{err}");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn register_without_dir_is_a_no_op() {
+        let config = crate::config::Config::default_config();
+        let mut reg = CommandRegistry::from_config(&config);
+        unsafe { register(&mut reg, &config.plugins) };
+        assert_eq!(reg.evaluate("ls").decision, Decision::Allow);
+    }
+}
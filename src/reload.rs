@@ -0,0 +1,128 @@
+//! A registry that follows config file edits, for long-running embedders.
+//!
+//! The hook binary loads its config once per invocation. A process that
+//! evaluates many commands over its lifetime can instead hold a
+//! [`ReloadingRegistry`](crate::reload::ReloadingRegistry): it rebuilds the
+//! registry when [`Config::fingerprint`](crate::config::Config::fingerprint)
+//! changes and swaps it in whole, so each evaluation sees either the old
+//! policy or the new one, never a mix.
+//!
+//! Changes are found by polling, from
+//! [`ReloadingRegistry::watch`](crate::reload::ReloadingRegistry::watch) or
+//! by calling
+//! [`ReloadingRegistry::reload_if_changed`](crate::reload::ReloadingRegistry::reload_if_changed)
+//! directly.
+
+use crate::config::Config;
+use crate::eval::CommandRegistry;
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Builds a registry from a freshly loaded config.
+type Build = dyn Fn(&Config) -> CommandRegistry + Send + Sync;
+
+/// The current registry, rebuilt when the config files change.
+pub struct ReloadingRegistry {
+    current: RwLock<Arc<CommandRegistry>>,
+    /// The [`Config::fingerprint`] `current` was built from.
+    fingerprint: Mutex<String>,
+    build: Box<Build>,
+}
+
+impl ReloadingRegistry {
+    /// Load the config and build the first registry with `build`, which is
+    /// also used for every reload.
+    pub fn new(build: impl Fn(&Config) -> CommandRegistry + Send + Sync + 'static) -> Self {
+        // Fingerprint first: an edit made while loading is seen next poll.
+        let fingerprint = Config::fingerprint();
+        let registry = build(&Config::load());
+        Self {
+            current: RwLock::new(Arc::new(registry)),
+            fingerprint: Mutex::new(fingerprint),
+            build: Box::new(build),
+        }
+    }
+
+    /// The registry to evaluate with. Stays valid after a reload.
+    pub fn current(&self) -> Arc<CommandRegistry> {
+        Arc::clone(&self.current.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Rebuild and swap in the registry if the config changed since the
+    /// last build. Returns whether it did.
+    pub fn reload_if_changed(&self) -> bool {
+        let mut fingerprint = self.fingerprint.lock().unwrap_or_else(|e| e.into_inner());
+        let latest = Config::fingerprint();
+        if *fingerprint == latest {
+            return false;
+        }
+        let registry = Arc::new((self.build)(&Config::load()));
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = registry;
+        *fingerprint = latest;
+        log::info!("config changed; registry rebuilt");
+        true
+    }
+
+    /// Poll for changes every `interval` on a background thread, which
+    /// exits once the last `Arc` to `self` is dropped.
+    pub fn watch(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let weak: Weak<Self> = Arc::downgrade(self);
+        thread::spawn(move || {
+            loop {
+                thread::sleep(interval);
+                match weak.upgrade() {
+                    Some(registry) => {
+                        registry.reload_if_changed();
+                    }
+                    None => break,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CONFIG_ENV;
+    use crate::eval::Decision;
+
+    #[test]
+    fn rebuilds_when_the_config_file_changes() {
+        assert!(
+            std::env::var("NEXTEST").is_ok(),
+            "this test mutates process HOME and requires nextest (cargo nextest run)"
+        );
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("cc-toolgate-test-reload-{nanos}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config.toml");
+        std::fs::write(&file, "").unwrap();
+        unsafe { std::env::set_var("HOME", &dir) };
+        unsafe { std::env::set_var(CONFIG_ENV, &file) };
+
+        let registry = Arc::new(ReloadingRegistry::new(CommandRegistry::from_config));
+        let before = registry.current();
+        assert_eq!(before.evaluate("deployctl up").decision, Decision::Ask);
+        assert!(!registry.reload_if_changed());
+
+        std::fs::write(&file, "[commands]\nallow = [\"deployctl\"]\n").unwrap();
+        let watcher = registry.watch(Duration::from_millis(10));
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while registry.current().evaluate("deployctl up").decision != Decision::Allow {
+            assert!(std::time::Instant::now() < deadline, "not reloaded");
+            thread::sleep(Duration::from_millis(10));
+        }
+        // A registry taken before the reload keeps the old policy.
+        assert_eq!(before.evaluate("deployctl up").decision, Decision::Ask);
+
+        drop(registry);
+        watcher.join().unwrap();
+        unsafe { std::env::remove_var(CONFIG_ENV) };
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,289 @@
+//! Team policy fetched over HTTPS.
+//!
+//! `[remote] config_url` names a TOML overlay, and `<config_url>.sig` holds
+//! its ed25519 signature in base64. Both are fetched at most every
+//! `refresh_minutes`. The policy is used only if the signature verifies
+//! against `[remote] public_key`. A verified copy is kept in
+//! `~/.cache/cc-toolgate/remote-policy.json`. When a fetch fails, or its
+//! signature doesn't verify, the last verified copy stays in use.
+//!
+//! [`Config::load`](crate::config::Config::load) applies the policy after
+//! the user overlays and before the project overlay. It is verified again
+//! whenever the config is rebuilt, so an edited cache file is rejected.
+
+use crate::config::RemoteConfig;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The last fetch of a remote policy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CachedPolicy {
+    /// The URL fetched.
+    pub url: String,
+    /// The last policy text whose signature verified, if any.
+    #[serde(default)]
+    pub policy: Option<String>,
+    /// Its base64 signature.
+    #[serde(default)]
+    pub signature: String,
+    /// When the URL was last tried, in Unix seconds.
+    #[serde(default)]
+    pub checked_at: u64,
+}
+
+impl CachedPolicy {
+    /// Default cache file location, if `HOME` is set.
+    pub fn default_path() -> Option<PathBuf> {
+        let home = std::env::var_os("HOME")?;
+        Some(Path::new(&home).join(".cache/cc-toolgate/remote-policy.json"))
+    }
+
+    /// Load the cache file; `None` if missing or unreadable.
+    pub fn read(path: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// The cached policy text for `config`, verified again. `None` if no
+    /// URL is configured or nothing has been fetched from it.
+    pub fn policy_for(&self, config: &RemoteConfig) -> Result<Option<&str>, String> {
+        match &self.policy {
+            Some(policy) if !config.config_url.is_empty() && self.url == config.config_url => {
+                verify(policy, &self.signature, &config.public_key)?;
+                Ok(Some(policy))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Check `signature` (base64) over `policy` against `public_key` (base64).
+pub fn verify(policy: &str, signature: &str, public_key: &str) -> Result<(), String> {
+    let key: [u8; 32] = decode(public_key)
+        .map_err(|e| format!("public_key: {e}"))?
+        .try_into()
+        .map_err(|_| "public_key: expected 32 bytes".to_string())?;
+    let key = VerifyingKey::from_bytes(&key).map_err(|e| format!("public_key: {e}"))?;
+    let signature = Signature::from_slice(&decode(signature)?)
+        .map_err(|_| "signature: expected 64 bytes".to_string())?;
+    key.verify(policy.as_bytes(), &signature)
+        .map_err(|_| "signature does not match the policy".to_string())
+}
+
+fn decode(text: &str) -> Result<Vec<u8>, String> {
+    BASE64.decode(text.trim()).map_err(|e| e.to_string())
+}
+
+/// Fetch the policy if the cached copy is due for a refresh, and update the
+/// cache file. Returns true if the policy in use changed, so the config
+/// should be rebuilt.
+pub fn refresh(config: &RemoteConfig) -> bool {
+    match CachedPolicy::default_path() {
+        Some(path) => refresh_at(config, &path, crate::approvals::now(), fetch),
+        None => false,
+    }
+}
+
+/// [`refresh`] with the cache file, clock, and HTTP client supplied.
+fn refresh_at(
+    config: &RemoteConfig,
+    path: &Path,
+    now: u64,
+    fetch: impl FnOnce(&str, Duration) -> Result<(String, String), String>,
+) -> bool {
+    let url = &config.config_url;
+    if url.is_empty() {
+        return false;
+    }
+    let cached = CachedPolicy::read(path).unwrap_or_default();
+    let same_url = &cached.url == url;
+    let due = cached
+        .checked_at
+        .saturating_add(config.refresh_minutes.saturating_mul(60));
+    if same_url && now < due {
+        return false;
+    }
+
+    let timeout = Duration::from_millis(config.timeout_ms);
+    let fetched = fetch(url, timeout).and_then(|(policy, signature)| {
+        verify(&policy, &signature, &config.public_key)?;
+        Ok((policy, signature))
+    });
+    let (updated, changed) = match fetched {
+        Ok((policy, signature)) => {
+            let changed = !same_url || cached.policy.as_ref() != Some(&policy);
+            let updated = CachedPolicy {
+                url: url.clone(),
+                policy: Some(policy),
+                signature: signature.trim().to_string(),
+                checked_at: now,
+            };
+            (updated, changed)
+        }
+        Err(e) => {
+            let fallback = if same_url && cached.policy.is_some() {
+                "; using the cached copy"
+            } else {
+                ""
+            };
+            eprintln!("cc-toolgate: remote policy {url}: {e}{fallback}");
+            let updated = if same_url {
+                CachedPolicy {
+                    checked_at: now,
+                    ..cached
+                }
+            } else {
+                CachedPolicy {
+                    url: url.clone(),
+                    checked_at: now,
+                    ..Default::default()
+                }
+            };
+            (updated, !same_url)
+        }
+    };
+    // If the cache can't be written, report no change: rebuilding would only
+    // fetch again.
+    write(path, &updated).is_ok() && changed
+}
+
+/// Write the cache file atomically (temp file + rename).
+fn write(path: &Path, cached: &CachedPolicy) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    std::fs::write(&tmp, serde_json::to_string_pretty(cached)?)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// GET the policy and its `.sig` over HTTPS.
+fn fetch(url: &str, timeout: Duration) -> Result<(String, String), String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .https_only(true)
+        .timeout_global(Some(timeout))
+        .build()
+        .into();
+    let get = |url: &str| -> Result<String, String> {
+        agent
+            .get(url)
+            .call()
+            .and_then(|mut response| response.body_mut().read_to_string())
+            .map_err(|e| format!("{url}: {e}"))
+    };
+    Ok((get(url)?, get(&format!("{url}.sig"))?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    const URL: &str = "https://policy.example/team.toml";
+    const POLICY: &str = "[commands]\ndeny = [\"terraform\"]\n";
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7; 32])
+    }
+
+    fn sign(policy: &str) -> String {
+        BASE64.encode(signing_key().sign(policy.as_bytes()).to_bytes())
+    }
+
+    fn remote_config() -> RemoteConfig {
+        RemoteConfig {
+            config_url: URL.into(),
+            public_key: BASE64.encode(signing_key().verifying_key().to_bytes()),
+            refresh_minutes: 60,
+            timeout_ms: 1000,
+        }
+    }
+
+    fn cache_file(tag: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir()
+            .join(format!("cc-toolgate-test-remote-{tag}-{nanos}"))
+            .join("remote-policy.json")
+    }
+
+    #[test]
+    fn verifies_signatures() {
+        let key = remote_config().public_key;
+        assert_eq!(verify(POLICY, &sign(POLICY), &key), Ok(()));
+        let tampered = POLICY.replace("deny", "allow");
+        assert!(verify(&tampered, &sign(POLICY), &key).is_err());
+        assert!(verify(POLICY, "not base64!", &key).is_err());
+        assert!(verify(POLICY, &sign(POLICY), "c2hvcnQ=").is_err());
+    }
+
+    #[test]
+    fn fetches_at_most_every_refresh_interval() {
+        let path = cache_file("interval");
+        let config = remote_config();
+        let ok = |_: &str, _: Duration| Ok((POLICY.to_string(), sign(POLICY)));
+        let unreachable = |_: &str, _: Duration| -> Result<(String, String), String> {
+            panic!("fetched before the refresh interval")
+        };
+
+        assert!(refresh_at(&config, &path, 1000, ok));
+        assert!(!refresh_at(&config, &path, 1000 + 59 * 60, unreachable));
+        // Due again, but unchanged.
+        assert!(!refresh_at(&config, &path, 1000 + 60 * 60, ok));
+
+        let cached = CachedPolicy::read(&path).unwrap();
+        assert_eq!(cached.policy_for(&config), Ok(Some(POLICY)));
+        assert_eq!(cached.checked_at, 1000 + 60 * 60);
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn keeps_the_verified_copy_when_offline_or_tampered() {
+        let path = cache_file("fallback");
+        let config = remote_config();
+        assert!(refresh_at(&config, &path, 0, |_, _| Ok((
+            POLICY.to_string(),
+            sign(POLICY)
+        ))));
+
+        let offline = |_: &str, _: Duration| Err("connection refused".to_string());
+        assert!(!refresh_at(&config, &path, 1_000_000, offline));
+        let forged =
+            |_: &str, _: Duration| Ok(("[commands]\nallow = [\"rm\"]\n".into(), sign(POLICY)));
+        assert!(!refresh_at(&config, &path, 2_000_000, forged));
+
+        let cached = CachedPolicy::read(&path).unwrap();
+        assert_eq!(cached.policy_for(&config), Ok(Some(POLICY)));
+
+        // An edited cache file fails verification when the config is rebuilt.
+        let edited = CachedPolicy {
+            policy: Some("[commands]\nallow = [\"rm\"]\n".into()),
+            ..cached
+        };
+        assert!(edited.policy_for(&config).is_err());
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn a_new_url_is_fetched_immediately() {
+        let path = cache_file("new-url");
+        let mut config = remote_config();
+        let ok = |_: &str, _: Duration| Ok((POLICY.to_string(), sign(POLICY)));
+        assert!(refresh_at(&config, &path, 0, ok));
+
+        config.config_url = "https://policy.example/other.toml".into();
+        let offline = |_: &str, _: Duration| Err("timed out".to_string());
+        assert!(refresh_at(&config, &path, 1, offline));
+        // The old URL's policy no longer applies.
+        let cached = CachedPolicy::read(&path).unwrap();
+        assert_eq!(cached.policy_for(&config), Ok(None));
+        assert!(!refresh_at(&RemoteConfig::default(), &path, 2, ok));
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -0,0 +1,192 @@
+//! Decision counters in `~/.local/share/cc-toolgate/stats.json`, for
+//! `cc-toolgate top`.
+//!
+//! Every hook evaluation adds each segment's decision to two tallies: one per
+//! base command (`kubectl`, `rm`) and one per rule, keyed by the segment's
+//! reason. That answers "what keeps asking?" without parsing the decision
+//! log. Counts are policy decisions, before approvals and grants, and are
+//! best-effort: two hooks finishing at once can lose an update.
+
+use crate::approvals::{read_json, write_json};
+use crate::eval::{Decision, SegmentResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// How often one command or rule got each decision.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Counts {
+    /// ALLOW decisions.
+    #[serde(default)]
+    pub allow: u64,
+    /// ASK decisions.
+    #[serde(default)]
+    pub ask: u64,
+    /// DENY decisions.
+    #[serde(default)]
+    pub deny: u64,
+}
+
+impl Counts {
+    /// The count for `decision`.
+    pub fn get(&self, decision: Decision) -> u64 {
+        match decision {
+            Decision::Allow => self.allow,
+            Decision::Ask => self.ask,
+            Decision::Deny => self.deny,
+        }
+    }
+
+    /// All decisions together.
+    pub fn total(&self) -> u64 {
+        self.allow + self.ask + self.deny
+    }
+
+    fn add(&mut self, decision: Decision) {
+        let count = match decision {
+            Decision::Allow => &mut self.allow,
+            Decision::Ask => &mut self.ask,
+            Decision::Deny => &mut self.deny,
+        };
+        *count = count.saturating_add(1);
+    }
+}
+
+/// The counters state file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    /// Per base command.
+    #[serde(default)]
+    pub commands: BTreeMap<String, Counts>,
+    /// Per rule, keyed by the first line of the segment's reason.
+    #[serde(default)]
+    pub rules: BTreeMap<String, Counts>,
+}
+
+impl Stats {
+    /// Default state file location, if `HOME` is set.
+    pub fn default_path() -> Option<PathBuf> {
+        let home = std::env::var_os("HOME")?;
+        Some(Path::new(&home).join(".local/share/cc-toolgate/stats.json"))
+    }
+
+    /// Load the counters from `path`. A missing or unreadable file is empty;
+    /// a corrupt one is reported to stderr and treated as empty.
+    pub fn load_from(path: &Path) -> Self {
+        read_json(path)
+    }
+
+    /// Write the counters to `path`, replacing it atomically.
+    pub fn save_to(&self, path: &Path) -> std::io::Result<()> {
+        write_json(self, path)
+    }
+
+    /// Count each segment's decision.
+    pub fn record(&mut self, segments: &[SegmentResult]) {
+        for segment in segments {
+            let command = base_command(&segment.command);
+            let rule = segment.reason.lines().next().unwrap_or_default();
+            if !command.is_empty() {
+                self.commands
+                    .entry(command.to_string())
+                    .or_default()
+                    .add(segment.decision);
+            }
+            if !rule.is_empty() {
+                self.rules
+                    .entry(rule.to_string())
+                    .or_default()
+                    .add(segment.decision);
+            }
+        }
+    }
+}
+
+/// The `limit` entries of `counts` with the highest count for `by` (or the
+/// total, if `None`), most first. Entries with a zero count are left out.
+pub fn top(
+    counts: &BTreeMap<String, Counts>,
+    by: Option<Decision>,
+    limit: usize,
+) -> Vec<(&str, Counts)> {
+    let key = |c: &Counts| by.map_or_else(|| c.total(), |d| c.get(d));
+    let mut entries: Vec<(&str, Counts)> = counts
+        .iter()
+        .filter(|(_, c)| key(c) > 0)
+        .map(|(name, c)| (name.as_str(), *c))
+        .collect();
+    // Stable sort: ties stay in name order.
+    entries.sort_by_key(|(_, c)| std::cmp::Reverse(key(c)));
+    entries.truncate(limit);
+    entries
+}
+
+/// The command name of a segment, past leading `NAME=value` assignments.
+fn base_command(segment: &str) -> &str {
+    segment
+        .split_whitespace()
+        .find(|word| {
+            !word
+                .split_once('=')
+                .is_some_and(|(name, _)| !name.is_empty() && !name.contains(['/', '"', '\'']))
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(command: &str, decision: Decision, reason: &str) -> SegmentResult {
+        SegmentResult {
+            command: command.into(),
+            decision,
+            reason: reason.into(),
+            suggestion: None,
+        }
+    }
+
+    #[test]
+    fn counts_commands_and_rules() {
+        let mut stats = Stats::default();
+        stats.record(&[
+            segment(
+                "RUST_LOG=debug kubectl delete pod a",
+                Decision::Ask,
+                "kubectl delete requires confirmation",
+            ),
+            segment("ls", Decision::Allow, "allowed: ls"),
+        ]);
+        stats.record(&[segment(
+            "kubectl get pods",
+            Decision::Allow,
+            "kubectl get is read-only",
+        )]);
+        stats.record(&[segment(
+            "rm -rf build",
+            Decision::Ask,
+            "rm requires confirmation\ndetails",
+        )]);
+
+        assert_eq!(
+            stats.commands["kubectl"],
+            Counts {
+                allow: 1,
+                ask: 1,
+                deny: 0
+            }
+        );
+        assert_eq!(stats.rules["rm requires confirmation"].ask, 1);
+
+        let asks: Vec<&str> = top(&stats.commands, Some(Decision::Ask), 10)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(asks, ["kubectl", "rm"]);
+        let busiest: Vec<&str> = top(&stats.commands, None, 1)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(busiest, ["kubectl"]);
+    }
+}
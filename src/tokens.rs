@@ -0,0 +1,232 @@
+//! Signed approval tokens for CI and other non-interactive runs.
+//!
+//! A token pre-approves one exact command until an expiry:
+//!
+//! ```text
+//! v1.<expires>.<sha256(command) hex>.<hmac-sha256(key, "v1.<expires>.<hash>") hex>
+//! ```
+//!
+//! Tokens are minted with `cc-toolgate sign` and supplied through the
+//! `CC_TOOLGATE_APPROVAL` env var (whitespace-separated) or the file named by
+//! `CC_TOOLGATE_APPROVAL_FILE`. They are verified against the key file in
+//! `[approval_tokens]`. A valid token turns an ASK into ALLOW; it never
+//! overrides a DENY.
+
+use crate::approvals::format_duration;
+use crate::config::ApprovalTokensConfig;
+use crate::eval::{Decision, RuleMatch};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// Env var holding tokens directly.
+pub const TOKENS_ENV: &str = "CC_TOOLGATE_APPROVAL";
+/// Env var naming a file of tokens.
+pub const TOKENS_FILE_ENV: &str = "CC_TOOLGATE_APPROVAL_FILE";
+
+/// Token format version prefix.
+const VERSION: &str = "v1";
+/// SHA-256 block size, for HMAC.
+const BLOCK_SIZE: usize = 64;
+
+/// Signs and verifies approval tokens with one HMAC key.
+pub struct TokenVerifier {
+    key: Vec<u8>,
+}
+
+impl TokenVerifier {
+    /// Build a verifier from `[approval_tokens]`. Returns `None` when tokens
+    /// are disabled or the key file is missing or empty.
+    pub fn from_config(config: &ApprovalTokensConfig) -> Option<Self> {
+        if config.key_file.is_empty() {
+            return None;
+        }
+        let path = shellexpand::tilde(&config.key_file);
+        let key = std::fs::read(path.as_ref()).ok()?;
+        Self::from_key(key.trim_ascii())
+    }
+
+    /// Build a verifier from raw key bytes; `None` if the key is empty.
+    pub fn from_key(key: &[u8]) -> Option<Self> {
+        (!key.is_empty()).then(|| Self { key: key.to_vec() })
+    }
+
+    /// Mint a token approving `command` until `expires` (Unix seconds).
+    pub fn sign(&self, command: &str, expires: u64) -> String {
+        let payload = format!("{VERSION}.{expires}.{}", command_hash(command));
+        let mac = hex(&self.hmac(payload.as_bytes()));
+        format!("{payload}.{mac}")
+    }
+
+    /// Whether `token` is a valid, unexpired approval for `command`.
+    /// Returns the expiry on success.
+    pub fn verify(&self, token: &str, command: &str, now: u64) -> Option<u64> {
+        let (payload, mac) = token.trim().rsplit_once('.')?;
+        let mut parts = payload.split('.');
+        let (version, expires, hash) = (parts.next()?, parts.next()?, parts.next()?);
+        if version != VERSION || parts.next().is_some() {
+            return None;
+        }
+        let expires: u64 = expires.parse().ok()?;
+        let expected = hex(&self.hmac(payload.as_bytes()));
+        (constant_time_eq(expected.as_bytes(), mac.as_bytes())
+            && hash == command_hash(command)
+            && expires > now)
+            .then_some(expires)
+    }
+
+    /// Turn an ASK into ALLOW if any of `tokens` approves `command`.
+    /// ALLOW and DENY results are returned unchanged.
+    pub fn apply(
+        &self,
+        tokens: &[String],
+        command: &str,
+        result: RuleMatch,
+        now: u64,
+    ) -> RuleMatch {
+        if result.decision != Decision::Ask {
+            return result;
+        }
+        match tokens.iter().find_map(|t| self.verify(t, command, now)) {
+            Some(expires) => RuleMatch {
+                decision: Decision::Allow,
+                reason: format!(
+                    "pre-approved by signed token (expires in {})",
+                    format_duration(Duration::from_secs(expires - now))
+                ),
+            },
+            None => result,
+        }
+    }
+
+    /// HMAC-SHA256 (RFC 2104) of `message` under the verifier's key.
+    fn hmac(&self, message: &[u8]) -> [u8; 32] {
+        let mut key = [0u8; BLOCK_SIZE];
+        if self.key.len() > BLOCK_SIZE {
+            key[..32].copy_from_slice(&Sha256::digest(&self.key));
+        } else {
+            key[..self.key.len()].copy_from_slice(&self.key);
+        }
+        let pad = |byte: u8| key.map(|k| k ^ byte);
+        let inner = Sha256::new()
+            .chain_update(pad(0x36))
+            .chain_update(message)
+            .finalize();
+        Sha256::new()
+            .chain_update(pad(0x5c))
+            .chain_update(inner)
+            .finalize()
+            .into()
+    }
+}
+
+/// Tokens from `CC_TOOLGATE_APPROVAL` and `CC_TOOLGATE_APPROVAL_FILE`.
+pub fn tokens_from_env() -> Vec<String> {
+    let mut text = std::env::var(TOKENS_ENV).unwrap_or_default();
+    if let Ok(path) = std::env::var(TOKENS_FILE_ENV)
+        && let Ok(file) = std::fs::read_to_string(shellexpand::tilde(&path).as_ref())
+    {
+        text.push('\n');
+        text.push_str(&file);
+    }
+    text.split_whitespace().map(String::from).collect()
+}
+
+/// Hex SHA-256 of the trimmed command text.
+fn command_hash(command: &str) -> String {
+    hex(&Sha256::digest(command.trim().as_bytes()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Compare without short-circuiting on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verifier() -> TokenVerifier {
+        TokenVerifier::from_key(b"ci-secret").unwrap()
+    }
+
+    fn ask() -> RuleMatch {
+        RuleMatch {
+            decision: Decision::Ask,
+            reason: "requires confirmation".into(),
+        }
+    }
+
+    #[test]
+    fn hmac_matches_rfc4231_case_2() {
+        let v = TokenVerifier::from_key(b"Jefe").unwrap();
+        assert_eq!(
+            hex(&v.hmac(b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn hmac_long_key_is_hashed() {
+        // RFC 4231 test case 6: 131-byte key.
+        let v = TokenVerifier::from_key(&[0xaa; 131]).unwrap();
+        assert_eq!(
+            hex(&v.hmac(b"Test Using Larger Than Block-Size Key - Hash Key First")),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let v = verifier();
+        let token = v.sign("cargo publish", 2000);
+        assert_eq!(v.verify(&token, "cargo publish", 1000), Some(2000));
+        assert_eq!(v.verify(&token, " cargo publish ", 1000), Some(2000));
+    }
+
+    #[test]
+    fn rejects_other_command_expired_or_tampered() {
+        let v = verifier();
+        let token = v.sign("cargo publish", 2000);
+        assert_eq!(v.verify(&token, "cargo publish --no-verify", 1000), None);
+        assert_eq!(v.verify(&token, "cargo publish", 2000), None);
+        let extended = token.replacen(".2000.", ".9999.", 1);
+        assert_eq!(v.verify(&extended, "cargo publish", 1000), None);
+        let other_key = TokenVerifier::from_key(b"other").unwrap();
+        assert_eq!(other_key.verify(&token, "cargo publish", 1000), None);
+        assert_eq!(v.verify("garbage", "cargo publish", 1000), None);
+    }
+
+    #[test]
+    fn apply_turns_ask_into_allow_only() {
+        let v = verifier();
+        let tokens = vec!["junk".to_string(), v.sign("make release", 4600)];
+        let result = v.apply(&tokens, "make release", ask(), 1000);
+        assert_eq!(result.decision, Decision::Allow);
+        assert_eq!(
+            result.reason,
+            "pre-approved by signed token (expires in 1h0m)"
+        );
+        let deny = RuleMatch {
+            decision: Decision::Deny,
+            reason: "blocked".into(),
+        };
+        assert_eq!(
+            v.apply(&tokens, "make release", deny, 1000).decision,
+            Decision::Deny
+        );
+        assert_eq!(
+            v.apply(&tokens, "make clean", ask(), 1000).decision,
+            Decision::Ask
+        );
+    }
+
+    #[test]
+    fn empty_key_disables() {
+        assert!(TokenVerifier::from_key(b"").is_none());
+        assert!(TokenVerifier::from_config(&ApprovalTokensConfig::default()).is_none());
+    }
+}
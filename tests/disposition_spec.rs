@@ -146,7 +146,7 @@ const ASK_MUTATING: &[(&str, Decision)] = &[
     ("cp src.txt dst.txt", Ask),
     ("ln -s target link", Ask),
     ("chmod 755 script.sh", Ask),
-    ("tee /tmp/out.txt", Ask),
+    ("tee /srv/out.txt", Ask),
     ("curl https://example.com", Ask),
     ("wget https://example.com/file", Ask),
     ("pip install requests", Ask),
@@ -208,7 +208,7 @@ const ASK_GH_MUTATING: &[(&str, Decision)] = &[
 /// Source: tests/integration.rs — ASK: Privilege escalation
 const ASK_PRIVILEGE_ESCALATION: &[(&str, Decision)] = &[
     ("sudo apt install vim", Ask),
-    ("su - root", Ask),
+    ("su -c 'apt update' root", Ask),
     ("doas pacman -S vim", Ask),
 ];
 
@@ -228,6 +228,7 @@ const DENY_BLOCKED: &[(&str, Decision)] = &[
     ("reboot", Deny),
     ("halt", Deny),
     ("mkfs.ext4 /dev/sda1", Deny), // dotted-command fallback: mkfs.ext4 -> mkfs
+    ("su - root", Deny),           // interactive privileged shell
 ];
 
 /// Redirection escalation: an otherwise-allowed command with output redirection
@@ -287,7 +288,7 @@ const COMPOUND_CHAINS: &[(&str, Decision)] = &[
     ("git log --oneline -5 && gh pr list", Allow),
     ("cd /tmp && ls -la", Allow),
     // Background operator (&)
-    ("ls -la & rm -rf /", Ask),
+    ("ls -la & rm -rf /", Deny),
     ("ls -la & echo hello", Allow),
 ];
 
@@ -361,9 +362,9 @@ const WRAPPERS: &[(&str, Decision)] = &[
     ),
     ("sudo ls", Ask),
     ("sudo shred /dev/sda", Deny),
-    ("sudo rm -rf /", Ask),
+    ("sudo rm -rf /", Deny),
     ("sudo -u postgres psql", Ask),
-    ("doas rm -rf /", Ask),
+    ("doas rm -rf /", Deny),
     ("doas shred /dev/sda", Deny),
     ("su -c rm", Ask),
     ("nohup rm -rf /tmp/test", Ask),
@@ -434,7 +435,7 @@ const HEREDOC_PIPE: &[(&str, Decision)] = &[
 const HEREDOC_COMPOUND: &[(&str, Decision)] = &[
     ("cat <<'EOF' && rm -rf /tmp/test\nbody\nEOF\n", Ask),
     ("cat <<'EOF' ; kubectl delete pod foo\nbody\nEOF\n", Ask),
-    ("cat <<'EOF' || rm -rf /\nbody\nEOF\n", Ask),
+    ("cat <<'EOF' || rm -rf /\nbody\nEOF\n", Deny),
 ];
 
 /// Heredoc substitution expansion.
@@ -466,7 +467,7 @@ const ESCALATE_DENY_CASES: &[(&str, Decision)] = &[
 //
 // Source: tests/integration.rs — subst_double_quoted_expanded
 
-const SUBST_WITH_REASON: &[(&str, Decision)] = &[("echo \"$(rm -rf /)\"", Ask)];
+const SUBST_WITH_REASON: &[(&str, Decision)] = &[("echo \"$(rm -rf /)\"", Deny)];
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // SECTION 5: Tool-specific unit tests (spec-level, default config)
@@ -1003,17 +1004,21 @@ fn git_env_gate_decision(cmd: &str) -> Decision {
     use cc_toolgate::eval::CommandContext;
     use std::collections::HashMap;
 
-    let spec = cc_toolgate::commands::tools::git::GitSpec::from_config(&GitConfig {
-        read_only: vec![
-            "status".into(),
-            "log".into(),
-            "diff".into(),
-            "branch".into(),
-        ],
-        allowed_with_config: vec!["push".into(), "pull".into(), "add".into()],
-        config_env: HashMap::from([("GIT_CONFIG_GLOBAL".into(), "~/.gitconfig.ai".into())]),
-        force_push_flags: vec!["--force".into(), "-f".into(), "--force-with-lease".into()],
-    });
+    let spec = cc_toolgate::commands::tools::git::GitSpec::from_config(
+        &GitConfig {
+            read_only: vec![
+                "status".into(),
+                "log".into(),
+                "diff".into(),
+                "branch".into(),
+            ],
+            allowed_with_config: vec!["push".into(), "pull".into(), "add".into()],
+            config_env: HashMap::from([("GIT_CONFIG_GLOBAL".into(), "~/.gitconfig.ai".into())]),
+            force_push_flags: vec!["--force".into(), "-f".into(), "--force-with-lease".into()],
+            ..Default::default()
+        },
+        &Default::default(),
+    );
     let ctx = CommandContext::from_command(cmd);
     spec.evaluate(&ctx).decision
 }
@@ -1061,12 +1066,16 @@ fn gh_env_gate_decision(cmd: &str) -> Decision {
     use cc_toolgate::eval::CommandContext;
     use std::collections::HashMap;
 
-    let spec = cc_toolgate::commands::tools::gh::GhSpec::from_config(&GhConfig {
-        read_only: vec!["pr list".into(), "pr view".into(), "status".into()],
-        mutating: vec!["repo delete".into()],
-        allowed_with_config: vec!["pr create".into(), "pr merge".into()],
-        config_env: HashMap::from([("GH_CONFIG_DIR".into(), "~/.config/gh-ai".into())]),
-    });
+    let spec = cc_toolgate::commands::tools::gh::GhSpec::from_config(
+        &GhConfig {
+            read_only: vec!["pr list".into(), "pr view".into(), "status".into()],
+            mutating: vec!["repo delete".into()],
+            allowed_with_config: vec!["pr create".into(), "pr merge".into()],
+            config_env: HashMap::from([("GH_CONFIG_DIR".into(), "~/.config/gh-ai".into())]),
+            ..Default::default()
+        },
+        &cc_toolgate::config::Config::default_config().network,
+    );
     let ctx = CommandContext::from_command(cmd);
     spec.evaluate(&ctx).decision
 }
@@ -1093,6 +1102,7 @@ fn kubectl_env_gate_decision(cmd: &str) -> Decision {
         mutating: vec!["delete".into()],
         allowed_with_config: vec!["apply".into(), "rollout".into()],
         config_env: HashMap::from([("KUBECONFIG".into(), "~/.kube/config.ai".into())]),
+        sensitive_resources: Vec::new(),
     });
     let ctx = CommandContext::from_command(cmd);
     spec.evaluate(&ctx).decision
@@ -1264,7 +1274,7 @@ fn spec_kubectl_env_gate_nextest() {
 /// Source: tests/integration.rs — subst_double_quoted_expanded
 #[test]
 fn reason_subst_double_quoted_expanded() {
-    assert_eq!(decision_for("echo \"$(rm -rf /)\""), Ask);
+    assert_eq!(decision_for("echo \"$(rm -rf /)\""), Deny);
     let r = reason_for("echo \"$(rm -rf /)\"");
     assert!(
         r.contains("subst"),